@@ -1,9 +1,13 @@
+use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use serde_json::Value;
+use text_tool_core::foreshadow_md::foreshadows_to_markdown;
+use text_tool_core::ignore::IgnoreRules;
 
 use super::llm_backend::LlmBackend;
-use super::{LlmConfig, WorldObject, StructNode, Foreshadow, Milestone, ObjectKind,
-            StructKind, ChapterTag};
+use super::sync::{read_foreshadows, read_struct_roots, read_world_objects};
+use super::{AppError, DesignPart, LlmConfig, LlmErrorKind, WorldObject, StructNode, Foreshadow, Milestone, ObjectKind,
+            StructKind, normalize_title, same_title};
 
 // ── Skill trait ───────────────────────────────────────────────────────────────
 
@@ -21,6 +25,9 @@ pub trait Skill: Send + Sync {
     fn parameters_schema(&self) -> Value;
     /// Execute the skill with the given arguments and return a JSON result.
     fn execute(&self, args: &Value) -> Result<Value, String>;
+    /// The design data the skill saves to its `Design/` file on success,
+    /// which the app then reloads.
+    fn writes(&self) -> Option<DesignPart> { None }
 
     /// Serialise this skill into the OpenAI `tools` array element format.
     fn to_openai_tool(&self) -> Value {
@@ -88,7 +95,7 @@ impl Skill for GetCharacterInfoSkill {
             .ok_or("缺少参数 name")?;
 
         let obj = self.0.iter()
-            .find(|o| o.name == name)
+            .find(|o| same_title(&o.name, name))
             .ok_or_else(|| format!("未找到对象「{name}」"))?;
 
         Ok(serde_json::json!({
@@ -233,19 +240,23 @@ impl Skill for ListProjectFilesSkill {
     fn execute(&self, _args: &Value) -> Result<Value, String> {
         let root = self.0.as_ref().ok_or("项目未打开")?;
         let mut files = Vec::new();
-        collect_text_files(root, root, &mut files);
-        Ok(Value::Array(files.into_iter().map(|s| Value::String(s)).collect()))
+        collect_text_files(root, root, &IgnoreRules::load(root), &mut files);
+        Ok(Value::Array(files.into_iter().map(Value::String).collect()))
     }
 }
 
-fn collect_text_files(root: &std::path::Path, dir: &std::path::Path, out: &mut Vec<String>) {
+fn collect_text_files(root: &std::path::Path, dir: &std::path::Path, ignore: &IgnoreRules, out: &mut Vec<String>) {
     let Ok(entries) = std::fs::read_dir(dir) else { return };
     let mut sorted: Vec<_> = entries.filter_map(|e| e.ok()).collect();
     sorted.sort_by_key(|e| e.file_name());
     for entry in sorted {
         let path = entry.path();
-        if path.is_dir() {
-            collect_text_files(root, &path, out);
+        let is_dir = path.is_dir();
+        if ignore.is_ignored_path(&path, is_dir) {
+            continue;
+        }
+        if is_dir {
+            collect_text_files(root, &path, ignore, out);
         } else if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
             if matches!(ext, "md" | "markdown" | "json") {
                 if let Ok(rel) = path.strip_prefix(root) {
@@ -315,6 +326,20 @@ impl Skill for GetFileContentSkill {
     }
 }
 
+// ── Write skills: build on the saved files ────────────────────────────────────
+
+/// The collection a write skill changes, as `read` found it saved now, so
+/// that each call builds on the last one's write and on edits the app saved
+/// while the agent ran, not on the snapshot taken when the agent started.
+/// The snapshot stands in while the project has no such file.
+fn saved_or<T: Clone>(read: Option<Result<(Vec<T>, String), AppError>>, snapshot: &[T]) -> Result<Vec<T>, String> {
+    match read {
+        Some(Ok((saved, _))) => Ok(saved),
+        Some(Err(e)) if !e.is_not_found() => Err(e.to_string()),
+        _ => Ok(snapshot.to_vec()),
+    }
+}
+
 // ── AddWorldObjectSkill ───────────────────────────────────────────────────────
 
 /// Add a new world object to the project JSON file.
@@ -326,6 +351,8 @@ pub struct AddWorldObjectSkill {
 impl Skill for AddWorldObjectSkill {
     fn name(&self) -> &str { "add_world_object" }
 
+    fn writes(&self) -> Option<DesignPart> { Some(DesignPart::Objects) }
+
     fn description(&self) -> &str {
         "向项目添加新的世界对象（人物/场景/地点/道具/势力）并保存到 Design/世界对象.json；\
          name 为名称，kind 为类型（人物/场景/地点/道具/势力/其他），description 为描述（可选），background 为背景故事（可选）"
@@ -345,26 +372,24 @@ impl Skill for AddWorldObjectSkill {
     }
 
     fn execute(&self, args: &Value) -> Result<Value, String> {
-        let name = args.get("name").and_then(|v| v.as_str()).ok_or("缺少参数 name")?;
+        let name = &normalize_title(args.get("name").and_then(|v| v.as_str()).ok_or("缺少参数 name")?);
         let kind_str = args.get("kind").and_then(|v| v.as_str()).ok_or("缺少参数 kind")?;
         let description = args.get("description").and_then(|v| v.as_str()).unwrap_or("").to_owned();
         let background  = args.get("background").and_then(|v| v.as_str()).unwrap_or("").to_owned();
 
-        if self.objects.iter().any(|o| o.name == name) {
+        let mut objects = saved_or(self.project_root.as_deref().map(read_world_objects), &self.objects)?;
+        if objects.iter().any(|o| same_title(&o.name, name)) {
             return Err(format!("对象「{name}」已存在，请使用 update_world_object 修改"));
         }
 
-        let kind = match kind_str {
-            "人物" => ObjectKind::Character,
-            "场景" => ObjectKind::Scene,
-            "地点" => ObjectKind::Location,
-            "道具" => ObjectKind::Item,
-            "势力" => ObjectKind::Faction,
-            _      => ObjectKind::Other,
-        };
+        // Built-in kinds, or a custom kind some object already has.
+        let kind = ObjectKind::all().iter()
+            .chain(objects.iter().map(|o| &o.kind))
+            .find(|k| k.label() == kind_str)
+            .cloned()
+            .unwrap_or(ObjectKind::Other);
 
         let root = self.project_root.as_ref().ok_or("项目未打开")?;
-        let mut objects = self.objects.clone();
         let mut new_obj = WorldObject::new(name, kind);
         new_obj.description = description;
         new_obj.background  = background;
@@ -375,12 +400,12 @@ impl Skill for AddWorldObjectSkill {
         let path = root.join("Design").join("世界对象.json");
         std::fs::create_dir_all(path.parent().unwrap())
             .map_err(|e| format!("创建目录失败: {e}"))?;
-        std::fs::write(&path, &json)
+        text_tool_core::safe_write(&path, &json)
             .map_err(|e| format!("写入失败: {e}"))?;
 
         Ok(serde_json::json!({
             "status": "success",
-            "message": format!("已添加对象「{name}」（{kind_str}）到 Design/世界对象.json，请在主界面「从文件加载」以更新显示")
+            "message": format!("已添加对象「{name}」（{kind_str}）到 Design/世界对象.json")
         }))
     }
 }
@@ -396,6 +421,8 @@ pub struct UpdateWorldObjectSkill {
 impl Skill for UpdateWorldObjectSkill {
     fn name(&self) -> &str { "update_world_object" }
 
+    fn writes(&self) -> Option<DesignPart> { Some(DesignPart::Objects) }
+
     fn description(&self) -> &str {
         "更新已有世界对象的描述（description）或背景故事（background）并保存到 Design/世界对象.json；\
          name 为要修改的对象名称（必填）"
@@ -416,9 +443,9 @@ impl Skill for UpdateWorldObjectSkill {
     fn execute(&self, args: &Value) -> Result<Value, String> {
         let name = args.get("name").and_then(|v| v.as_str()).ok_or("缺少参数 name")?;
 
-        let mut objects = self.objects.clone();
+        let mut objects = saved_or(self.project_root.as_deref().map(read_world_objects), &self.objects)?;
         let obj = objects.iter_mut()
-            .find(|o| o.name == name)
+            .find(|o| same_title(&o.name, name))
             .ok_or_else(|| format!("未找到对象「{name}」，请先用 add_world_object 添加"))?;
 
         if let Some(desc) = args.get("description").and_then(|v| v.as_str()) {
@@ -434,12 +461,12 @@ impl Skill for UpdateWorldObjectSkill {
         let path = root.join("Design").join("世界对象.json");
         std::fs::create_dir_all(path.parent().unwrap())
             .map_err(|e| format!("创建目录失败: {e}"))?;
-        std::fs::write(&path, &json)
+        text_tool_core::safe_write(&path, &json)
             .map_err(|e| format!("写入失败: {e}"))?;
 
         Ok(serde_json::json!({
             "status": "success",
-            "message": format!("已更新对象「{name}」并保存到 Design/世界对象.json，请在主界面「从文件加载」以更新显示")
+            "message": format!("已更新对象「{name}」并保存到 Design/世界对象.json")
         }))
     }
 }
@@ -455,6 +482,8 @@ pub struct DeleteWorldObjectSkill {
 impl Skill for DeleteWorldObjectSkill {
     fn name(&self) -> &str { "delete_world_object" }
 
+    fn writes(&self) -> Option<DesignPart> { Some(DesignPart::Objects) }
+
     fn description(&self) -> &str {
         "从项目中删除指定名称的世界对象，并保存到 Design/世界对象.json；name 为要删除的对象名称"
     }
@@ -472,9 +501,9 @@ impl Skill for DeleteWorldObjectSkill {
     fn execute(&self, args: &Value) -> Result<Value, String> {
         let name = args.get("name").and_then(|v| v.as_str()).ok_or("缺少参数 name")?;
 
-        let mut objects = self.objects.clone();
+        let mut objects = saved_or(self.project_root.as_deref().map(read_world_objects), &self.objects)?;
         let before = objects.len();
-        objects.retain(|o| o.name != name);
+        objects.retain(|o| !same_title(&o.name, name));
         if objects.len() == before {
             return Err(format!("未找到对象「{name}」"));
         }
@@ -483,12 +512,12 @@ impl Skill for DeleteWorldObjectSkill {
         let json = serde_json::to_string_pretty(&objects)
             .map_err(|e| format!("序列化失败: {e}"))?;
         let path = root.join("Design").join("世界对象.json");
-        std::fs::write(&path, &json)
+        text_tool_core::safe_write(&path, &json)
             .map_err(|e| format!("写入失败: {e}"))?;
 
         Ok(serde_json::json!({
             "status": "success",
-            "message": format!("已删除对象「{name}」并保存到 Design/世界对象.json，请在主界面「从文件加载」以更新显示")
+            "message": format!("已删除对象「{name}」并保存到 Design/世界对象.json")
         }))
     }
 }
@@ -504,9 +533,11 @@ pub struct AddChapterNodeSkill {
 impl Skill for AddChapterNodeSkill {
     fn name(&self) -> &str { "add_chapter_node" }
 
+    fn writes(&self) -> Option<DesignPart> { Some(DesignPart::Structure) }
+
     fn description(&self) -> &str {
-        "向章节结构添加新节点（总纲/卷/章/节）并保存到 Design/章节结构.json；\
-         title 为节点标题，kind 为层级类型（总纲/卷/章/节），summary 为摘要（可选）"
+        "向章节结构添加新节点（总纲/卷/章/节/场）并保存到 Design/章节结构.json；\
+         title 为节点标题，kind 为层级类型（总纲/卷/章/节/场），summary 为摘要（可选）"
     }
 
     fn parameters_schema(&self) -> Value {
@@ -514,7 +545,7 @@ impl Skill for AddChapterNodeSkill {
             "type": "object",
             "properties": {
                 "title":   { "type": "string", "description": "节点标题" },
-                "kind":    { "type": "string", "description": "层级类型：总纲/卷/章/节" },
+                "kind":    { "type": "string", "description": "层级类型：总纲/卷/章/节/场" },
                 "summary": { "type": "string", "description": "节点摘要（可选）" }
             },
             "required": ["title", "kind"]
@@ -530,20 +561,13 @@ impl Skill for AddChapterNodeSkill {
             "总纲" => StructKind::Outline,
             "卷"   => StructKind::Volume,
             "章"   => StructKind::Chapter,
+            "场"   => StructKind::Scene,
             _      => StructKind::Section,
         };
 
-        let mut roots = self.struct_roots.clone();
-        let node = StructNode {
-            title:   title.to_owned(),
-            kind,
-            tag:     ChapterTag::Normal,
-            summary,
-            done:    false,
-            children: vec![],
-            linked_objects: vec![],
-            node_links: vec![],
-        };
+        let mut roots = saved_or(self.project_root.as_deref().map(read_struct_roots), &self.struct_roots)?;
+        let mut node = StructNode::new(title, kind);
+        node.summary = summary;
         roots.push(node);
 
         let root = self.project_root.as_ref().ok_or("项目未打开")?;
@@ -552,18 +576,36 @@ impl Skill for AddChapterNodeSkill {
         let path = root.join("Design").join("章节结构.json");
         std::fs::create_dir_all(path.parent().unwrap())
             .map_err(|e| format!("创建目录失败: {e}"))?;
-        std::fs::write(&path, &json)
+        text_tool_core::safe_write(&path, &json)
             .map_err(|e| format!("写入失败: {e}"))?;
 
         Ok(serde_json::json!({
             "status": "success",
-            "message": format!("已添加{kind_str}节点「{title}」到 Design/章节结构.json，请在主界面「从文件加载」以更新显示")
+            "message": format!("已添加{kind_str}节点「{title}」到 Design/章节结构.json")
         }))
     }
 }
 
 // ── AddForeshadowSkill ────────────────────────────────────────────────────────
 
+/// Write `foreshadows` to `Design/伏笔.json` and render them to
+/// `Content/伏笔.md`, as the app's own save does.
+fn write_foreshadow_files(root: &std::path::Path, foreshadows: &[Foreshadow]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(foreshadows)
+        .map_err(|e| format!("序列化失败: {e}"))?;
+    let files = [
+        (root.join("Design").join("伏笔.json"), json),
+        (root.join("Content").join("伏笔.md"), foreshadows_to_markdown(foreshadows)),
+    ];
+    for (path, text) in files {
+        std::fs::create_dir_all(path.parent().unwrap())
+            .map_err(|e| format!("创建目录失败: {e}"))?;
+        text_tool_core::safe_write(&path, text)
+            .map_err(|e| format!("写入失败: {e}"))?;
+    }
+    Ok(())
+}
+
 /// Add a new foreshadow entry to the project.
 pub struct AddForeshadowSkill {
     pub foreshadows: Vec<Foreshadow>,
@@ -573,8 +615,10 @@ pub struct AddForeshadowSkill {
 impl Skill for AddForeshadowSkill {
     fn name(&self) -> &str { "add_foreshadow" }
 
+    fn writes(&self) -> Option<DesignPart> { Some(DesignPart::Foreshadows) }
+
     fn description(&self) -> &str {
-        "向项目添加新伏笔并追加到 Content/伏笔.md；\
+        "向项目添加新伏笔并保存到 Design/伏笔.json；\
          name 为伏笔名称，description 为描述（可选），related_chapters 为关联章节列表（可选，逗号分隔）"
     }
 
@@ -591,47 +635,37 @@ impl Skill for AddForeshadowSkill {
     }
 
     fn execute(&self, args: &Value) -> Result<Value, String> {
-        let name = args.get("name").and_then(|v| v.as_str()).ok_or("缺少参数 name")?;
+        let name = &normalize_title(args.get("name").and_then(|v| v.as_str()).ok_or("缺少参数 name")?);
         let description = args.get("description").and_then(|v| v.as_str()).unwrap_or("").to_owned();
         let related_raw = args.get("related_chapters").and_then(|v| v.as_str()).unwrap_or("");
         let related_chapters: Vec<String> = if related_raw.is_empty() {
             vec![]
         } else {
-            related_raw.split(',').map(|s| s.trim().to_owned()).filter(|s| !s.is_empty()).collect()
+            related_raw.split(',').map(normalize_title).filter(|s| !s.is_empty()).collect()
         };
 
-        if self.foreshadows.iter().any(|f| f.name == name) {
+        let mut foreshadows = saved_or(self.project_root.as_deref().map(read_foreshadows), &self.foreshadows)?;
+        if foreshadows.iter().any(|f| same_title(&f.name, name)) {
             return Err(format!("伏笔「{name}」已存在"));
         }
 
         let root = self.project_root.as_ref().ok_or("项目未打开")?;
-        let path = root.join("Content").join("伏笔.md");
-
-        // Append to existing file (or create new).
-        let existing = std::fs::read_to_string(&path).unwrap_or_default();
-        let header = if existing.trim().is_empty() { "# 伏笔列表\n\n".to_owned() } else { String::new() };
-        let mut entry = format!("## {} ⏳ 未解决\n\n", name);
-        if !description.is_empty() {
-            entry.push_str(&format!("{}\n\n", description));
-        }
-        if !related_chapters.is_empty() {
-            entry.push_str(&format!("**关联章节**: {}\n\n", related_chapters.join("、")));
-        }
-        std::fs::create_dir_all(path.parent().unwrap())
-            .map_err(|e| format!("创建目录失败: {e}"))?;
-        std::fs::write(&path, format!("{}{}{}", existing, header, entry))
-            .map_err(|e| format!("写入失败: {e}"))?;
+        let mut new_fs = Foreshadow::new(name);
+        new_fs.description = description;
+        new_fs.related_chapters = related_chapters;
+        foreshadows.push(new_fs);
+        write_foreshadow_files(root, &foreshadows)?;
 
         Ok(serde_json::json!({
             "status": "success",
-            "message": format!("已添加伏笔「{name}」到 Content/伏笔.md，请在主界面「从文件加载」以更新显示")
+            "message": format!("已添加伏笔「{name}」到 Design/伏笔.json")
         }))
     }
 }
 
 // ── ResolveForeshadowSkill ────────────────────────────────────────────────────
 
-/// Mark an existing foreshadow as resolved in the project Markdown file.
+/// Mark an existing foreshadow as resolved in the project's foreshadow files.
 pub struct ResolveForeshadowSkill {
     pub foreshadows: Vec<Foreshadow>,
     pub project_root: Option<std::path::PathBuf>,
@@ -640,8 +674,10 @@ pub struct ResolveForeshadowSkill {
 impl Skill for ResolveForeshadowSkill {
     fn name(&self) -> &str { "resolve_foreshadow" }
 
+    fn writes(&self) -> Option<DesignPart> { Some(DesignPart::Foreshadows) }
+
     fn description(&self) -> &str {
-        "将指定伏笔标记为已解决，并更新 Content/伏笔.md；name 为要解决的伏笔名称"
+        "将指定伏笔标记为已解决，并更新 Design/伏笔.json；name 为要解决的伏笔名称"
     }
 
     fn parameters_schema(&self) -> Value {
@@ -657,36 +693,20 @@ impl Skill for ResolveForeshadowSkill {
     fn execute(&self, args: &Value) -> Result<Value, String> {
         let name = args.get("name").and_then(|v| v.as_str()).ok_or("缺少参数 name")?;
 
-        if !self.foreshadows.iter().any(|f| f.name == name) {
-            return Err(format!("未找到伏笔「{name}」"));
-        }
+        let mut foreshadows = saved_or(self.project_root.as_deref().map(read_foreshadows), &self.foreshadows)?;
+        let fs = foreshadows.iter_mut()
+            .find(|f| same_title(&f.name, name))
+            .ok_or_else(|| format!("未找到伏笔「{name}」"))?;
+        fs.resolved = true;
+        fs.touch();
+        let name = fs.name.clone();
 
         let root = self.project_root.as_ref().ok_or("项目未打开")?;
-        let path = root.join("Content").join("伏笔.md");
-        let content = std::fs::read_to_string(&path)
-            .map_err(|e| format!("读取 Content/伏笔.md 失败: {e}"))?;
-
-        // Replace "## {name} ⏳ 未解决" with "## {name} ✅ 已解决"
-        let updated = content.replace(
-            &format!("## {} ⏳ 未解决", name),
-            &format!("## {} ✅ 已解决", name),
-        );
-        // Also handle case where the foreshadow was added without the status suffix
-        let updated = if updated == content {
-            content.replace(
-                &format!("## {}", name),
-                &format!("## {} ✅ 已解决", name),
-            )
-        } else {
-            updated
-        };
-
-        std::fs::write(&path, &updated)
-            .map_err(|e| format!("写入失败: {e}"))?;
+        write_foreshadow_files(root, &foreshadows)?;
 
         Ok(serde_json::json!({
             "status": "success",
-            "message": format!("已将伏笔「{name}」标记为已解决，请在主界面「从文件加载」以更新显示")
+            "message": format!("已将伏笔「{name}」标记为已解决")
         }))
     }
 }
@@ -751,7 +771,7 @@ impl Skill for WriteFileContentSkill {
             .map_err(|e| format!("创建目录失败: {e}"))?;
 
         if mode == "overwrite" {
-            std::fs::write(&canonical_file, content)
+            text_tool_core::safe_write(&canonical_file, content)
                 .map_err(|e| format!("写入失败: {e}"))?;
         } else {
             // append mode
@@ -838,7 +858,11 @@ impl Skill for GetTextTemplatesSkill {
 // ── SkillSet ──────────────────────────────────────────────────────────────────
 
 /// A collection of skills made available to the agent.
-pub struct SkillSet(Vec<Arc<dyn Skill>>);
+pub struct SkillSet {
+    skills: Vec<Arc<dyn Skill>>,
+    /// Told which design data a write skill saved, so the app can reload it.
+    written: Option<Sender<DesignPart>>,
+}
 
 impl SkillSet {
     /// Build the default skill set from a snapshot of the current app data.
@@ -849,7 +873,7 @@ impl SkillSet {
         milestones:   Vec<Milestone>,
         project_root: Option<std::path::PathBuf>,
     ) -> Self {
-        SkillSet(vec![
+        let skills: Vec<Arc<dyn Skill>> = vec![
             // ── Read-only skills ──────────────────────────────────────────────
             Arc::new(ListCharactersSkill(objects.clone())),
             Arc::new(GetCharacterInfoSkill(objects.clone())),
@@ -867,41 +891,59 @@ impl SkillSet {
             Arc::new(AddForeshadowSkill     { foreshadows: foreshadows.clone(), project_root: project_root.clone() }),
             Arc::new(ResolveForeshadowSkill { foreshadows,              project_root: project_root.clone() }),
             Arc::new(WriteFileContentSkill(project_root)),
-        ])
+        ];
+        SkillSet { skills, written: None }
+    }
+
+    /// Send the part of every successful design write to `written`.
+    pub fn notify_writes(mut self, written: Sender<DesignPart>) -> Self {
+        self.written = Some(written);
+        self
+    }
+
+    /// Drop the skills that write `Design/` JSON files, for a project whose
+    /// design data another computer holds.
+    pub fn without_design_writes(mut self) -> Self {
+        self.skills.retain(|s| s.writes().is_none());
+        self
     }
 
     /// Number of registered skills.
     #[allow(dead_code)]
-    pub fn len(&self) -> usize { self.0.len() }
+    pub fn len(&self) -> usize { self.skills.len() }
 
     /// Returns `true` when no skills are registered.
     #[allow(dead_code)]
-    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+    pub fn is_empty(&self) -> bool { self.skills.is_empty() }
 
     /// Names of all registered skills (used for auto-generating system prompts).
     pub fn tool_names(&self) -> Vec<&str> {
-        self.0.iter().map(|s| s.name()).collect()
+        self.skills.iter().map(|s| s.name()).collect()
     }
 
     /// Names and descriptions of all skills (for displaying in the UI).
     #[allow(dead_code)]
     pub fn descriptions(&self) -> Vec<(String, String)> {
-        self.0.iter()
+        self.skills.iter()
             .map(|s| (s.name().to_owned(), s.description().to_owned()))
             .collect()
     }
 
     /// Serialise into the OpenAI `tools` array.
     pub fn to_openai_tools(&self) -> Value {
-        Value::Array(self.0.iter().map(|s| s.to_openai_tool()).collect())
+        Value::Array(self.skills.iter().map(|s| s.to_openai_tool()).collect())
     }
 
     /// Execute the named skill with the given JSON arguments.
     pub fn execute(&self, name: &str, args: &Value) -> Result<Value, String> {
-        self.0.iter()
+        let skill = self.skills.iter()
             .find(|s| s.name() == name)
-            .ok_or_else(|| format!("未知技能: {name}"))?
-            .execute(args)
+            .ok_or_else(|| format!("未知技能: {name}"))?;
+        let result = skill.execute(args)?;
+        if let (Some(part), Some(written)) = (skill.writes(), &self.written) {
+            let _ = written.send(part);
+        }
+        Ok(result)
     }
 }
 
@@ -937,7 +979,7 @@ impl AgentBackend {
 impl LlmBackend for AgentBackend {
     fn name(&self) -> &'static str { Self::BACKEND_NAME }
 
-    fn complete(&self, config: &LlmConfig, prompt: &str) -> Result<String, String> {
+    fn complete(&self, config: &LlmConfig, prompt: &str) -> Result<String, AppError> {
         let model = {
             let p = config.model_path.trim();
             if p.is_empty() { "gpt-4o" } else { p }
@@ -979,24 +1021,27 @@ impl LlmBackend for AgentBackend {
 
             let mut response = ureq::post(&config.api_url)
                 .send_json(&body)
-                .map_err(|e| format!("请求失败 ({}): {e}", config.api_url))?;
+                .map_err(|e| AppError::llm(LlmErrorKind::Request, format!("{}: {e}", config.api_url)))?;
 
             let json: serde_json::Value = response
                 .body_mut()
                 .read_json()
-                .map_err(|e| format!("响应解析失败: {e}"))?;
+                .map_err(|e| AppError::llm(LlmErrorKind::Response, e.to_string()))?;
 
             // Surface API-level errors (e.g. auth failure, model not found).
             if let Some(err_obj) = json.get("error") {
                 let msg = err_obj.get("message").and_then(|v| v.as_str())
                     .unwrap_or("未知错误");
-                return Err(format!("API 错误: {msg} (原始响应: {err_obj})"));
+                return Err(AppError::llm(LlmErrorKind::Api, format!("{msg} (原始响应: {err_obj})")));
             }
 
             let message = json
                 .get("choices").and_then(|v| v.get(0))
                 .and_then(|v| v.get("message"))
-                .ok_or_else(|| format!("无法解析 LLM 响应 (轮次 {}/{MAX_ROUNDS}): {json}", round + 1))?;
+                .ok_or_else(|| AppError::llm(
+                    LlmErrorKind::Response,
+                    format!("无法解析 LLM 响应 (轮次 {}/{MAX_ROUNDS}): {json}", round + 1),
+                ))?;
 
             // ── Handle tool calls ──────────────────────────────────────────────
             if let Some(calls) = message.get("tool_calls").and_then(|v| v.as_array()) {
@@ -1037,9 +1082,9 @@ impl LlmBackend for AgentBackend {
                 }
 
                 if round + 1 == MAX_ROUNDS {
-                    return Err(format!(
+                    return Err(AppError::llm(LlmErrorKind::NoAnswer, format!(
                         "Agent 达到最大轮次限制 ({MAX_ROUNDS})，请精简请求或减少所需技能数量"
-                    ));
+                    )));
                 }
                 // Continue to next round.
             } else {
@@ -1050,7 +1095,7 @@ impl LlmBackend for AgentBackend {
                     .to_owned();
 
                 if content.trim().is_empty() {
-                    return Err("Agent 返回了空响应，请检查模型配置或简化请求".to_owned());
+                    return Err(AppError::llm(LlmErrorKind::NoAnswer, "Agent 返回了空响应，请检查模型配置或简化请求"));
                 }
 
                 return if agent_log.is_empty() {
@@ -1061,7 +1106,7 @@ impl LlmBackend for AgentBackend {
             }
         }
 
-        Err("Agent 未能生成最终回复".to_owned())
+        Err(AppError::llm(LlmErrorKind::NoAnswer, "Agent 未能生成最终回复"))
     }
 }
 
@@ -1150,6 +1195,31 @@ mod tests {
         assert!(result.unwrap_err().contains("未找到"));
     }
 
+    #[test]
+    fn test_foreshadow_skills_build_on_each_other() {
+        let dir = std::env::temp_dir().join(format!("qingmo_test_fs_skills_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Content")).unwrap();
+
+        // Both skills hold the snapshot taken before either call.
+        let skills = SkillSet::new(sample_objects(), sample_roots(), sample_foreshadows(),
+                                   sample_milestones(), Some(dir.clone()));
+        skills.execute("add_foreshadow", &serde_json::json!({"name": "玉佩"})).unwrap();
+        skills.execute("add_foreshadow", &serde_json::json!({"name": "古琴"})).unwrap();
+        skills.execute("resolve_foreshadow", &serde_json::json!({"name": "玉佩"})).unwrap();
+        let dup = skills.execute("add_foreshadow", &serde_json::json!({"name": "古琴"}));
+        assert!(dup.unwrap_err().contains("已存在"));
+
+        let (saved, _) = read_foreshadows(&dir).unwrap();
+        let names: Vec<&str> = saved.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["神秘信封", "断剑", "玉佩", "古琴"]);
+        assert!(saved[2].resolved && !saved[3].resolved);
+        let md = std::fs::read_to_string(dir.join("Content").join("伏笔.md")).unwrap();
+        assert!(md.contains("## 玉佩 ✅ 已解决") && md.contains("## 古琴 ⏳ 未解决"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_get_character_info_missing_param() {
         let skill = GetCharacterInfoSkill(sample_objects());
@@ -1264,6 +1334,19 @@ mod tests {
         assert!(names.contains(&"write_file_content"));
     }
 
+    #[test]
+    fn test_skill_set_without_design_writes() {
+        let ss = SkillSet::new(vec![], vec![], vec![], vec![], None).without_design_writes();
+        let names = ss.tool_names();
+        assert_eq!(names.len(), 9);
+        assert!(!names.contains(&"add_world_object"));
+        assert!(!names.contains(&"add_chapter_node"));
+        // Foreshadows are kept in Design/伏笔.json too.
+        assert!(!names.contains(&"add_foreshadow"));
+        assert!(!names.contains(&"resolve_foreshadow"));
+        assert!(names.contains(&"write_file_content"));
+    }
+
     #[test]
     fn test_skill_descriptions() {
         let ss = SkillSet::new(vec![], vec![], vec![], vec![], None);
@@ -1515,9 +1598,13 @@ mod tests {
         })).unwrap();
         assert_eq!(result["status"], "success");
 
+        let content = std::fs::read_to_string(dir.join("Design").join("伏笔.json")).unwrap();
+        let saved: Vec<Foreshadow> = serde_json::from_str(&content).unwrap();
+        assert_eq!(saved.len(), 3);
+        assert_eq!((saved[2].name.as_str(), saved[2].description.as_str()), ("新伏笔", "描述"));
+        assert_eq!(saved[2].related_chapters, ["第一章", "第二章"]);
         let content = std::fs::read_to_string(dir.join("Content").join("伏笔.md")).unwrap();
-        assert!(content.contains("新伏笔"));
-        assert!(content.contains("第一章"));
+        assert!(content.contains("## 新伏笔 ⏳ 未解决"));
 
         let _ = std::fs::remove_dir_all(&dir);
     }
@@ -1537,18 +1624,17 @@ mod tests {
         let dir = std::env::temp_dir().join("qingmo_test_resolve_fs");
         std::fs::create_dir_all(dir.join("Content")).unwrap();
 
-        // Write an initial foreshadow file
-        let initial = "# 伏笔列表\n\n## 神秘信封 ⏳ 未解决\n\n第一章出现的信封\n\n";
-        std::fs::write(dir.join("Content").join("伏笔.md"), initial).unwrap();
-
-        let mut fs = sample_foreshadows();
+        let fs = sample_foreshadows();
         let skill = ResolveForeshadowSkill { foreshadows: fs.clone(), project_root: Some(dir.clone()) };
         let result = skill.execute(&serde_json::json!({"name": "神秘信封"})).unwrap();
         assert_eq!(result["status"], "success");
 
+        let content = std::fs::read_to_string(dir.join("Design").join("伏笔.json")).unwrap();
+        let saved: Vec<Foreshadow> = serde_json::from_str(&content).unwrap();
+        assert!(saved[0].resolved && !saved[1].resolved);
+        assert_eq!(saved[0].related_chapters, fs[0].related_chapters);
         let content = std::fs::read_to_string(dir.join("Content").join("伏笔.md")).unwrap();
-        assert!(content.contains("✅ 已解决"));
-        assert!(!content.contains("⏳ 未解决"));
+        assert!(content.contains("## 神秘信封 ✅ 已解决") && content.contains("## 断剑 ⏳ 未解决"));
 
         let _ = std::fs::remove_dir_all(&dir);
     }
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use egui::{Color32, Context, Key, RichText, TextureHandle};
+use text_tool_core::attachments::{
+    attachment_name, delete_attachment_file, import_attachment, is_image_attachment, is_managed_attachment,
+    resolve_attachment,
+};
+
+use super::{rfd_pick_files, AppError, AttachmentRemove, IoOp, TextToolApp};
+
+// ── 附件: reference files of world objects ───────────────────────────────────
+//
+// The object editor lists the attachments with thumbnails for images; the
+// rest open in the system's default program.  Bookkeeping (copying into the
+// project, relative paths) lives in `text_tool_core::attachments`.
+
+/// Longest side of a thumbnail, in pixels.
+const THUMB_PX: u32 = 96;
+
+/// Decoded thumbnails by project-relative path; `None` for files that could
+/// not be read as images.  Cleared when another project opens.
+pub(super) type ThumbCache = HashMap<String, Option<TextureHandle>>;
+
+/// What the 附件 section asks for.
+pub(super) enum AttachmentAction {
+    Add,
+    Open(String),
+    Remove(String),
+}
+
+fn load_thumb(ctx: &Context, path: &Path) -> Option<TextureHandle> {
+    let bytes = std::fs::read(path).ok()?;
+    let img = image::load_from_memory(&bytes).ok()?.thumbnail(THUMB_PX, THUMB_PX).to_rgba8();
+    let size = [img.width() as usize, img.height() as usize];
+    let color = egui::ColorImage::from_rgba_unmultiplied(size, img.as_raw());
+    Some(ctx.load_texture(format!("attachment:{}", path.display()), color, egui::TextureOptions::LINEAR))
+}
+
+/// Open `path` with the program the system associates with it.
+fn open_with_system(path: &Path) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    };
+    #[cfg(target_os = "macos")]
+    let mut cmd = std::process::Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut cmd = std::process::Command::new("xdg-open");
+    cmd.arg(path).spawn().map(|_| ())
+}
+
+/// The 附件 section of the object editor.
+pub(super) fn draw_attachments_section(
+    ui: &mut egui::Ui,
+    attachments: &[String],
+    root: Option<&Path>,
+    thumbs: &mut ThumbCache,
+    action: &mut Option<AttachmentAction>,
+) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("附件").strong());
+        if ui.small_button("➕ 添加…").on_hover_text("选择图片、PDF 等文件，复制到 Design/attachments/").clicked() {
+            *action = Some(AttachmentAction::Add);
+        }
+    });
+    if attachments.is_empty() {
+        ui.label(RichText::new("（暂无附件）").color(Color32::GRAY).small());
+        return;
+    }
+    ui.horizontal_wrapped(|ui| {
+        for rel in attachments {
+            let path = root.map(|r| resolve_attachment(r, rel));
+            let exists = path.as_ref().is_some_and(|p| p.is_file());
+            ui.group(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(THUMB_PX as f32);
+                    let thumb = match &path {
+                        Some(p) if exists && is_image_attachment(rel) => thumbs
+                            .entry(rel.clone())
+                            .or_insert_with(|| load_thumb(ui.ctx(), p))
+                            .clone(),
+                        _ => None,
+                    };
+                    let preview = match &thumb {
+                        Some(tex) => ui.add(egui::Image::new(egui::load::SizedTexture::from_handle(tex))
+                            .max_size(egui::vec2(THUMB_PX as f32, THUMB_PX as f32))
+                            .sense(egui::Sense::click())),
+                        None => ui.add(egui::Label::new(RichText::new("📄").size(32.0)).sense(egui::Sense::click())),
+                    };
+                    let preview_clicked = exists && preview.on_hover_text("点击用默认程序打开").clicked();
+                    let name = attachment_name(rel);
+                    let label = if exists {
+                        RichText::new(name).small()
+                    } else {
+                        RichText::new(format!("{name}（文件不存在）")).small().color(Color32::from_rgb(220, 110, 110))
+                    };
+                    ui.add(egui::Label::new(label).truncate()).on_hover_text(rel);
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(exists, egui::Button::new("打开").small()).clicked() || preview_clicked {
+                            *action = Some(AttachmentAction::Open(rel.clone()));
+                        }
+                        if ui.small_button("🗑").on_hover_text("移除附件").clicked() {
+                            *action = Some(AttachmentAction::Remove(rel.clone()));
+                        }
+                    });
+                });
+            });
+        }
+    });
+}
+
+impl TextToolApp {
+    /// Carry out `action` for the object at `idx`.
+    pub(super) fn apply_attachment_action(&mut self, idx: usize, action: AttachmentAction) {
+        let Some(obj_name) = self.world_objects.get(idx).map(|o| o.name.clone()) else { return };
+        match action {
+            AttachmentAction::Add => {
+                let Some(root) = self.project_root.clone() else {
+                    self.report(AppError::NoProject);
+                    return;
+                };
+                if let Err(e) = self.check_design_writable() {
+                    self.report(e);
+                    return;
+                }
+                let mut added = 0;
+                for source in rfd_pick_files("添加附件") {
+                    match import_attachment(&root, &obj_name, &source) {
+                        Ok(rel) => {
+                            let obj = &mut self.world_objects[idx];
+                            if !obj.attachments.contains(&rel) {
+                                obj.attachments.push(rel);
+                                obj.touch();
+                                added += 1;
+                            }
+                        }
+                        Err(e) => {
+                            self.report(AppError::Io { op: IoOp::Copy, path: source, source: e });
+                            return;
+                        }
+                    }
+                }
+                if added > 0 {
+                    self.refresh_tree();
+                    self.status = format!("已为「{obj_name}」添加 {added} 个附件");
+                }
+            }
+            AttachmentAction::Open(rel) => {
+                let Some(root) = &self.project_root else { return };
+                let path = resolve_attachment(root, &rel);
+                if let Err(e) = open_with_system(&path) {
+                    self.report(AppError::Io { op: IoOp::Open, path, source: e });
+                }
+            }
+            AttachmentAction::Remove(rel) => {
+                self.attachment_remove = Some(AttachmentRemove { object: obj_name, rel, delete_file: false });
+            }
+        }
+    }
+
+    /// Confirmation for removing an attachment, optionally with its copy.
+    pub(super) fn draw_attachment_remove_dialog(&mut self, ctx: &Context) {
+        let Some(dlg) = &mut self.attachment_remove else { return };
+        let managed = is_managed_attachment(&dlg.rel);
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("移除附件")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("从「{}」移除附件「{}」？", dlg.object, attachment_name(&dlg.rel)));
+                if managed {
+                    ui.checkbox(&mut dlg.delete_file, "同时删除复制到项目中的文件");
+                    ui.label(RichText::new(&dlg.rel).small().color(Color32::from_gray(150)));
+                } else {
+                    ui.label(RichText::new("该文件不在 Design/attachments 中，只移除条目，不删除文件。")
+                        .small().color(Color32::from_gray(150)));
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("移除").clicked() { confirmed = true; }
+                    if ui.button("取消").clicked() { cancelled = true; }
+                });
+                if ctx.input(|i| i.key_pressed(Key::Escape)) { cancelled = true; }
+            });
+
+        if cancelled {
+            self.attachment_remove = None;
+        }
+        if !confirmed {
+            return;
+        }
+        let Some(AttachmentRemove { object, rel, delete_file }) = self.attachment_remove.take() else { return };
+        if let Some(obj) = self.world_objects.iter_mut().find(|o| o.name == object) {
+            obj.attachments.retain(|a| *a != rel);
+            obj.touch();
+        }
+        self.attachment_thumbs.remove(&rel);
+        self.status = format!("已移除附件「{}」", attachment_name(&rel));
+        if delete_file && managed {
+            let Some(root) = self.project_root.clone() else { return };
+            match delete_attachment_file(&root, &rel) {
+                Ok(()) => {
+                    self.refresh_tree();
+                    self.status = format!("已移除并删除附件「{}」", attachment_name(&rel));
+                }
+                Err(e) => self.report(AppError::Io { op: IoOp::Write, path: resolve_attachment(&root, &rel), source: e }),
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::stats::apply_auto_done;
+
+use super::session_summary::note_session;
+use super::{AutoDoneToast, TextToolApp};
+
+// ── 达到目标字数自动标记完成 ──────────────────────────────────────────────────
+//
+// Evaluated whenever the project index finishes a scan (the word counts
+// changed) or the rule was edited in 设置.  A node whose 进度 the user set
+// by hand this session is left alone, so the rule never undoes a click.
+
+/// How long the toast stays up.
+const TOAST_SECS: u64 = 8;
+
+impl TextToolApp {
+    /// Re-apply the rule after a new index generation or a settings change.
+    pub(super) fn tick_auto_done(&mut self) {
+        let rule = self.settings.behavior.auto_done;
+        let generation = self.project_index.generation();
+        if !rule.enabled || generation == 0 || self.auto_done_checked == Some((generation, rule)) {
+            return;
+        }
+        self.auto_done_checked = Some((generation, rule));
+        let Some(root) = &self.project_root else { return };
+        let counts = self.project_index.word_counts_by_stem(&root.join("Content"));
+        let manual = &self.auto_done_manual;
+        let changed = apply_auto_done(&mut self.struct_roots, &counts, &rule, &|n| manual.contains(&n.file_key()));
+        if changed.is_empty() {
+            return;
+        }
+        for (title, done) in &changed {
+            note_session(&mut self.session_done, title, *done);
+        }
+        self.autosave_struct();
+        self.auto_done_toast = Some(AutoDoneToast { changed, since: Instant::now() });
+    }
+
+    /// Toast listing what the rule changed, bottom right above the reminder.
+    pub(super) fn draw_auto_done_toast(&mut self, ctx: &Context) {
+        let Some(toast) = &self.auto_done_toast else { return };
+        let left = Duration::from_secs(TOAST_SECS).saturating_sub(toast.since.elapsed());
+        if left.is_zero() {
+            self.auto_done_toast = None;
+            return;
+        }
+        ctx.request_repaint_after(left);
+        let marked = toast.changed.iter().filter(|(_, done)| *done).count();
+        let unmarked = toast.changed.len() - marked;
+        let mut parts = Vec::new();
+        if marked > 0 {
+            parts.push(format!("标记完成 {marked} 个节点"));
+        }
+        if unmarked > 0 {
+            parts.push(format!("取消完成 {unmarked} 个节点"));
+        }
+        let titles = toast.changed.iter()
+            .map(|(title, done)| format!("{} {title}", if *done { "✅" } else { "↩" }))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut close = false;
+        egui::Area::new(egui::Id::new("auto_done_toast"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -196.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(format!("🎯 按目标字数{}", parts.join("，")))
+                            .color(Color32::from_rgb(120, 190, 120)))
+                            .on_hover_text(titles);
+                        if ui.small_button("×").clicked() {
+                            close = true;
+                        }
+                    });
+                });
+            });
+        if close {
+            self.auto_done_toast = None;
+        }
+    }
+}
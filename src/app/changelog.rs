@@ -0,0 +1,162 @@
+use std::path::PathBuf;
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::changelog::{
+    append_entries, entries_to_markdown, format_clock, read_entries, Change, ChangeCategory, CHANGELOG_PATH,
+};
+
+use super::panel::date_picker::date_picker;
+use super::{
+    format_day, local_offset_secs, parse_day, rfd_save_file, today_days, unix_now, AppError, ChangeLogView, IoOp,
+    TextToolApp,
+};
+
+// ── 变更记录: reviewing a session's structural edits ──────────────────────────
+//
+// Mutation sites call `record_change`; the entries wait in memory and are
+// appended to `Design/.texttool/changelog.jsonl` at the end of the frame,
+// when the project switches and when the app closes.
+
+impl TextToolApp {
+    /// Note `change` in the journal.
+    pub(super) fn record_change(&mut self, change: Change) {
+        let entry = change.entry(unix_now());
+        if let Some(view) = &mut self.change_log {
+            view.entries.push(entry.clone());
+        }
+        self.change_log_pending.push(entry);
+    }
+
+    /// Append the pending entries to the open project's journal.  Without a
+    /// project, or while another window holds the design files, they are
+    /// dropped like the edits themselves.
+    pub(super) fn flush_change_log(&mut self) {
+        if self.change_log_pending.is_empty() {
+            return;
+        }
+        let pending = std::mem::take(&mut self.change_log_pending);
+        let Some(root) = &self.project_root else { return };
+        if self.design_read_only() {
+            return;
+        }
+        let path = root.join(CHANGELOG_PATH);
+        if let Err(e) = append_entries(&path, &pending) {
+            self.report(AppError::Io { op: IoOp::Write, path, source: e });
+        }
+    }
+
+    pub(super) fn open_change_log(&mut self) {
+        let Some(root) = &self.project_root else {
+            self.status = "请先打开项目".to_owned();
+            return;
+        };
+        let path = root.join(CHANGELOG_PATH);
+        let mut entries = match read_entries(&path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.report(AppError::Io { op: IoOp::Read, path, source: e });
+                return;
+            }
+        };
+        entries.extend(self.change_log_pending.iter().cloned());
+        self.change_log = Some(ChangeLogView {
+            entries,
+            from: Some(format_day(today_days() - 6)),
+            to: None,
+            category: None,
+        });
+    }
+
+    pub(super) fn draw_change_log(&mut self, ctx: &Context) {
+        let Some(view) = &mut self.change_log else { return };
+        let offset = local_offset_secs();
+        let from = view.from.as_deref().and_then(parse_day);
+        let to = view.to.as_deref().and_then(parse_day);
+        let shown: Vec<_> = view.entries.iter()
+            .filter(|e| from.is_none_or(|d| e.day(offset) >= d) && to.is_none_or(|d| e.day(offset) <= d))
+            .filter(|e| view.category.is_none_or(|c| e.category == c))
+            .cloned()
+            .collect();
+
+        let mut open = true;
+        let mut copy = false;
+        let mut export = false;
+        egui::Window::new("变更记录")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("从");
+                    date_picker(ui, "change_log_from", &mut view.from);
+                    ui.label("到");
+                    date_picker(ui, "change_log_to", &mut view.to);
+                    ui.separator();
+                    if ui.small_button("今天").clicked() {
+                        view.from = Some(format_day(today_days()));
+                        view.to = None;
+                    }
+                    if ui.small_button("近 7 天").clicked() {
+                        view.from = Some(format_day(today_days() - 6));
+                        view.to = None;
+                    }
+                    if ui.small_button("全部").clicked() {
+                        view.from = None;
+                        view.to = None;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut view.category, None, "全部");
+                    for &c in ChangeCategory::all() {
+                        ui.selectable_value(&mut view.category, Some(c), c.label());
+                    }
+                });
+                ui.separator();
+                if shown.is_empty() {
+                    ui.label(RichText::new("所选范围内没有变更").color(Color32::GRAY));
+                }
+                egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    // Newest first, a heading per day.
+                    let mut current_day = None;
+                    for entry in shown.iter().rev() {
+                        let day = entry.day(offset);
+                        if current_day != Some(day) {
+                            ui.label(RichText::new(format_day(day)).strong());
+                            current_day = Some(day);
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(format_clock(entry.at + offset)).small().color(Color32::from_gray(150)));
+                            ui.label(RichText::new(entry.category.label()).small().color(Color32::from_rgb(120, 170, 220)));
+                            ui.label(&entry.description);
+                        });
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("{} 条", shown.len())).small().color(Color32::from_gray(150)));
+                    if ui.add_enabled(!shown.is_empty(), egui::Button::new("复制")).clicked() {
+                        copy = true;
+                    }
+                    if ui.add_enabled(!shown.is_empty(), egui::Button::new("导出 Markdown…")).clicked() {
+                        export = true;
+                    }
+                });
+            });
+
+        if copy {
+            ctx.copy_text(entries_to_markdown(&shown, offset));
+            self.status = format!("已复制 {} 条变更记录", shown.len());
+        }
+        if export {
+            if let Some(dest) = rfd_save_file(&PathBuf::from("变更记录.md")) {
+                match text_tool_core::safe_write(&dest, entries_to_markdown(&shown, offset)) {
+                    Ok(_) => self.status = format!("已导出变更记录到 {}", dest.display()),
+                    Err(e) => self.report(AppError::Io { op: IoOp::Write, path: dest, source: e }),
+                }
+            }
+        }
+        if !open {
+            self.change_log = None;
+        }
+    }
+}
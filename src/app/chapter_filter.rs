@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use text_tool_core::timeline::in_node_cast;
+
+use super::{leaf_for_file, node_at, ObjectKind, StructNode, TextToolApp, WorldObject};
+
+// ── 当前章节相关: object list filtered to a chapter's cast ─────────────────────
+//
+// The chapter is the node selected in the Structure panel, else the leaf
+// linked to the file open in the left pane.  The filter composes with the
+// kind filter chips.
+
+/// Path of the "current chapter": `selected` while it points at a node,
+/// otherwise the leaf whose chapter file is `left_rel` (relative to
+/// `Content/`).
+pub(super) fn current_chapter(roots: &[StructNode], selected: &[usize], left_rel: Option<&str>) -> Option<Vec<usize>> {
+    if !selected.is_empty() && node_at(roots, selected).is_some() {
+        return Some(selected.to_vec());
+    }
+    leaf_for_file(roots, left_rel?)
+}
+
+/// Which objects the object list shows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjListFilter {
+    kind: Option<ObjectKind>,
+    /// Indices of the current chapter's cast while 当前章节相关 is on and a
+    /// chapter could be resolved.
+    cast: Option<HashSet<usize>>,
+}
+
+impl ObjListFilter {
+    pub(super) fn shows(&self, i: usize, obj: &WorldObject) -> bool {
+        self.kind.as_ref().is_none_or(|k| &obj.kind == k)
+            && self.cast.as_ref().is_none_or(|c| c.contains(&i))
+    }
+}
+
+impl TextToolApp {
+    /// Path of the left pane's chapter file relative to `Content/`.
+    fn left_chapter_rel(&self) -> Option<String> {
+        let root = self.project_root.as_ref()?;
+        let file = self.left_file.as_ref().filter(|f| !f.scratch)?;
+        let rel = file.path.strip_prefix(root.join("Content")).ok()?;
+        Some(rel.to_string_lossy().replace('\\', "/"))
+    }
+
+    /// The current chapter's path and node, see [`current_chapter`].
+    pub(super) fn current_chapter_node(&self) -> Option<(Vec<usize>, &StructNode)> {
+        let rel = self.left_chapter_rel();
+        let path = current_chapter(&self.struct_roots, &self.selected_node_path, rel.as_deref())?;
+        node_at(&self.struct_roots, &path).map(|n| (path, n))
+    }
+
+    /// The object list's filter: the kind chip, plus the current chapter's
+    /// cast while 当前章节相关 is on.
+    pub(super) fn obj_list_filter(&self) -> ObjListFilter {
+        let cast = self.obj_chapter_filter
+            .then(|| self.current_chapter_node())
+            .flatten()
+            .map(|(_, node)| self.world_objects.iter().enumerate()
+                .filter(|(_, o)| in_node_cast(node, o))
+                .map(|(i, _)| i)
+                .collect());
+        ObjListFilter { kind: self.obj_kind_filter.clone(), cast }
+    }
+
+    /// [`Self::obj_list_filter`], with the selection cut down to the objects
+    /// it shows whenever it differs from last time: batch actions never
+    /// reach objects a changed filter has hidden.
+    pub(super) fn apply_obj_list_filter(&mut self) -> ObjListFilter {
+        let filter = self.obj_list_filter();
+        if self.obj_filter_applied.as_ref() != Some(&filter) {
+            let objects = &self.world_objects;
+            self.selected_objs.retain(|&i| objects.get(i).is_some_and(|o| filter.shows(i, o)));
+            self.obj_filter_applied = Some(filter.clone());
+        }
+        filter
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::StructKind;
+
+    #[test]
+    fn test_current_chapter() {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        let mut linked = StructNode::new("雨夜", StructKind::Chapter);
+        linked.file = Some("第一卷/001.md".into());
+        vol.children = vec![StructNode::new("第一章", StructKind::Chapter), linked];
+        let roots = vec![vol];
+
+        // The Structure selection wins over the left pane's file.
+        assert_eq!(current_chapter(&roots, &[0, 0], Some("第一卷/001.md")), Some(vec![0, 0]));
+        assert_eq!(current_chapter(&roots, &[], Some("第一卷/001.md")), Some(vec![0, 1]));
+        // A stale selection falls back to the file.
+        assert_eq!(current_chapter(&roots, &[3], Some("第一卷/001.md")), Some(vec![0, 1]));
+        assert_eq!(current_chapter(&roots, &[], Some("笔记.md")), None);
+        assert_eq!(current_chapter(&roots, &[], None), None);
+    }
+
+    #[test]
+    fn test_filter_change_prunes_selection() {
+        let mut app = TextToolApp::blank();
+        app.world_objects = vec![
+            WorldObject::new("林远", ObjectKind::Character),
+            WorldObject::new("青城", ObjectKind::Location),
+            WorldObject::new("苏晴", ObjectKind::Character),
+        ];
+        app.selected_objs = [0, 1, 2].into();
+        app.apply_obj_list_filter();
+        assert_eq!(app.selected_objs.len(), 3);
+
+        // A kind chip hides 青城, and it leaves the selection.
+        app.obj_kind_filter = Some(ObjectKind::Character);
+        app.apply_obj_list_filter();
+        assert_eq!(app.selected_objs, [0, 2].into());
+
+        // An unchanged filter leaves the selection alone, even when an
+        // edit hides a selected object.
+        app.world_objects[2].kind = ObjectKind::Location;
+        app.apply_obj_list_filter();
+        assert_eq!(app.selected_objs, [0, 2].into());
+
+        // The chapter chip narrows it to the chapter's cast.
+        let mut chapter = StructNode::new("第一章", StructKind::Chapter);
+        chapter.linked_objects = vec!["林远".into()];
+        app.struct_roots = vec![chapter];
+        app.selected_node_path = vec![0];
+        app.obj_kind_filter = None;
+        app.obj_chapter_filter = true;
+        app.apply_obj_list_filter();
+        assert_eq!(app.selected_objs, [0].into());
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::ignore::IgnoreRules;
+use text_tool_core::outline::{
+    count_nodes, list_content_files, merge_struct_by_title, struct_from_content,
+};
+
+use super::{find_duplicate_name, ContentImport, StructNode, TextToolApp};
+
+// ── 从 Content 目录生成结构 ───────────────────────────────────────────────────
+
+impl TextToolApp {
+    /// List `Content/` and open the preview of the structure it maps to.
+    pub(super) fn open_content_import(&mut self) {
+        let Some(root) = self.project_root.clone() else {
+            self.status = "请先打开一个项目".to_owned();
+            return;
+        };
+        let files = list_content_files(&root.join("Content"), &IgnoreRules::load(&root));
+        if files.is_empty() {
+            self.status = "Content/ 下没有 Markdown 文件".to_owned();
+            return;
+        }
+        let words = files.iter().map(|f| (f.rel.clone(), f.words)).collect();
+        self.content_import = Some(ContentImport { nodes: struct_from_content(&files), words });
+    }
+
+    pub(super) fn draw_content_import_dialog(&mut self, ctx: &Context) {
+        let Some(dlg) = &self.content_import else {
+            return;
+        };
+        let mut open = true;
+        let mut cancel = false;
+        let mut confirm = false;
+
+        egui::Window::new("从 Content 目录生成结构")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(440.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("一级文件夹 → 卷，Markdown 文件 → 章（标题取首个一级标题或文件名）。");
+                ui.label(RichText::new("与现有章节结构按标题合并：同名节点保留原有内容并关联文件，其余追加到末尾。")
+                    .small().color(Color32::from_gray(140)));
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    draw_import_preview(ui, &dlg.nodes, Some(&self.struct_roots), &dlg.words, 0);
+                });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("合并到章节结构").clicked() {
+                        confirm = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if confirm {
+            if let Some(dlg) = self.content_import.take() {
+                let total = count_nodes(&dlg.nodes);
+                let added = merge_struct_by_title(&mut self.struct_roots, dlg.nodes);
+                self.status = format!("已从 Content 目录导入结构：新增 {added} 个节点，更新 {} 个", total - added);
+            }
+        } else if !open || cancel {
+            self.content_import = None;
+        }
+    }
+}
+
+/// Preview rows of `nodes`, indented by `depth`.  `existing` are the
+/// siblings they merge into, `None` below a new node.
+fn draw_import_preview(
+    ui: &mut egui::Ui,
+    nodes: &[StructNode],
+    existing: Option<&[StructNode]>,
+    words: &HashMap<String, usize>,
+    depth: usize,
+) {
+    for node in nodes {
+        let matched = existing.and_then(|ex| {
+            ex.iter().find(|e| find_duplicate_name(&node.title, [e.title.as_str()]).is_some())
+        });
+        ui.horizontal(|ui| {
+            ui.add_space(depth as f32 * 14.0);
+            ui.label(format!("{} {}", node.kind.icon(), node.title));
+            if let Some(file) = &node.file {
+                let n = words.get(file).copied().unwrap_or(0);
+                ui.label(RichText::new(format!("{file} · {n} 字")).small().color(Color32::from_gray(140)));
+            }
+            if matched.is_some() {
+                ui.label(RichText::new("已存在").small().color(Color32::from_gray(140)));
+            } else {
+                ui.label(RichText::new("新增").small().color(Color32::from_rgb(120, 190, 120)));
+            }
+        });
+        draw_import_preview(ui, &node.children, matched.map(|m| m.children.as_slice()), words, depth + 1);
+    }
+}
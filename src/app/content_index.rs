@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use text_tool_core::content_index::{update_index_document, INDEX_FILE};
+
+use super::TextToolApp;
+
+// ── 生成目录文件: Content/目录.md ─────────────────────────────────────────────
+
+impl TextToolApp {
+    /// 工具 → 生成目录文件.
+    pub(super) fn update_content_index(&mut self) {
+        if self.project_root.is_none() {
+            self.status = "请先打开一个项目".to_owned();
+            return;
+        }
+        if let Some((listed, changed)) = self.regenerate_content_index() {
+            self.status = if changed {
+                format!("已更新 Content/{INDEX_FILE}（{listed} 个文件）")
+            } else {
+                format!("Content/{INDEX_FILE} 已是最新")
+            };
+        }
+    }
+
+    /// 保存时自动更新目录: regenerate the index after `path` was saved, if it
+    /// is a Markdown file of `Content/` other than the index itself.
+    pub(super) fn update_index_after_save(&mut self, path: &Path) {
+        if !self.settings.behavior.auto_update_index {
+            return;
+        }
+        let Some(content_dir) = self.project_root.as_ref().map(|r| r.join("Content")) else {
+            return;
+        };
+        if path.starts_with(&content_dir)
+            && path.extension().and_then(|e| e.to_str()) == Some("md")
+            && path != content_dir.join(INDEX_FILE)
+        {
+            self.regenerate_content_index();
+        }
+    }
+
+    /// Rewrite `Content/目录.md` from a fresh scan of the project index,
+    /// keeping its preamble.  Returns the number of files listed and whether
+    /// the file changed; `None` without a project or when writing failed.
+    fn regenerate_content_index(&mut self) -> Option<(usize, bool)> {
+        let root = self.project_root.clone()?;
+        let content_dir = root.join("Content");
+        // Incremental, so cheap when the background index is up to date.
+        self.project_index.rescan(&root);
+        let files = self.project_index.content_files(&content_dir);
+        let listed = files.iter().filter(|f| f.rel != INDEX_FILE).count();
+
+        let path = content_dir.join(INDEX_FILE);
+        let existing = std::fs::read_to_string(&path).ok();
+        let doc = update_index_document(existing.as_deref(), &files);
+        if existing.as_deref() == Some(doc.as_str()) {
+            return Some((listed, false));
+        }
+        if let Err(e) = text_tool_core::safe_write(&path, &doc) {
+            self.status = format!("写入目录失败: {e}");
+            return None;
+        }
+        // An open, unedited copy follows the file.
+        for f in [&mut self.left_file, &mut self.right_file].into_iter().flatten() {
+            if f.path == path && !f.modified {
+                let _ = f.reload();
+            }
+        }
+        if existing.is_none() {
+            self.refresh_tree();
+        }
+        Some((listed, true))
+    }
+}
@@ -0,0 +1,177 @@
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::crash::{
+    append_crash_log, format_crash_report, panic_message, set_crash_marker, take_crash_marker,
+};
+
+use super::{dirs_home, unix_now, ErrorToast, Panel, TextToolApp};
+
+/// A panic with the same message within this window is not logged again,
+/// so a bug that fires every frame does not flood the log.
+const REPEAT_WINDOW: Duration = Duration::from_secs(5);
+/// How long the error toast stays up.
+const TOAST_SECS: u64 = 10;
+
+/// Where the crash log goes: the open project, else the temp directory.
+static CRASH_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+/// Last logged panic message and when it was logged.
+static LAST_PANIC: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+/// Log written by the most recent panic, for the error toast.
+static LAST_LOG: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// `~/.config/qingmo/crash-pending`: names the log of a session that died.
+fn marker_path() -> Option<PathBuf> {
+    dirs_home().map(|h| h.join(".config").join("qingmo").join("crash-pending"))
+}
+
+/// Log panics to `texttool-crash.log` (see [`set_crash_dir`]) with a
+/// backtrace.  Panics on the UI thread also leave a marker, so the next
+/// start can report them if the process died.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let message = panic_message(info.payload()).to_owned();
+        {
+            let mut last = LAST_PANIC.lock().unwrap_or_else(|e| e.into_inner());
+            if last.as_ref().is_some_and(|(m, t)| *m == message && t.elapsed() < REPEAT_WINDOW) {
+                return;
+            }
+            *last = Some((message.clone(), Instant::now()));
+        }
+        let location = info.location().map(|l| l.to_string());
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        let report = format_crash_report(&message, location.as_deref(), &backtrace, unix_now());
+        let dir = CRASH_DIR.lock().ok()
+            .and_then(|d| d.clone())
+            .unwrap_or_else(std::env::temp_dir);
+        let Ok(log) = append_crash_log(&dir, &report) else { return };
+        if std::thread::current().name() == Some("main") {
+            if let Some(marker) = marker_path() {
+                let _ = set_crash_marker(&marker, &log);
+            }
+        }
+        *LAST_LOG.lock().unwrap_or_else(|e| e.into_inner()) = Some(log);
+    }));
+}
+
+/// Write future crash logs to `dir`, or the temp directory for `None`.
+pub(super) fn set_crash_dir(dir: Option<&Path>) {
+    *CRASH_DIR.lock().unwrap_or_else(|e| e.into_inner()) = dir.map(Path::to_owned);
+}
+
+/// Show `log` in the system's viewer; the panes may hold unsaved text.
+fn open_log_file(ctx: &Context, log: &Path) {
+    ctx.open_url(egui::OpenUrl::new_tab(format!("file://{}", log.display())));
+}
+
+impl TextToolApp {
+    /// Log of the previous session if it ended in a panic.
+    pub(super) fn take_previous_crash() -> Option<PathBuf> {
+        marker_path().and_then(|m| take_crash_marker(&m))
+    }
+
+    /// Called after a frame panicked: the app lives on, so drop the
+    /// marker, copy modified panes to the recovery folder and show a toast.
+    /// A panel other than the editor is left, in case it keeps panicking.
+    pub(super) fn recover_from_panic(&mut self) {
+        if let Some(marker) = marker_path() {
+            take_crash_marker(&marker);
+        }
+        let log = LAST_LOG.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut message = match self.flush_recovery() {
+            Ok(0) => "界面出错，已记录日志".to_owned(),
+            Ok(n) => format!("界面出错，已保存 {n} 份草稿副本并记录日志"),
+            Err(e) => format!("界面出错，草稿副本保存失败: {e}"),
+        };
+        if self.active_panel != Panel::Novel {
+            message.push_str(&format!("（已离开{}面板）", self.active_panel.label()));
+            self.active_panel = Panel::Novel;
+        }
+        self.status = message.clone();
+        self.error_toast = Some(ErrorToast { message, log, since: Instant::now() });
+    }
+
+    /// Show `message` in the error toast.
+    pub(super) fn show_error_toast(&mut self, message: String) {
+        self.error_toast = Some(ErrorToast { message, log: None, since: Instant::now() });
+    }
+
+    /// Error toast, bottom right.
+    pub(super) fn draw_error_toast(&mut self, ctx: &Context) {
+        let Some(toast) = &self.error_toast else { return };
+        let left = Duration::from_secs(TOAST_SECS).saturating_sub(toast.since.elapsed());
+        if left.is_zero() {
+            self.error_toast = None;
+            return;
+        }
+        ctx.request_repaint_after(left);
+        let mut close = false;
+        let mut open_log: Option<PathBuf> = None;
+        egui::Area::new(egui::Id::new("error_toast"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -36.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(format!("⚠ {}", toast.message)).color(Color32::from_rgb(220, 80, 80)));
+                        if let Some(log) = &toast.log {
+                            if ui.small_button("查看日志").clicked() {
+                                open_log = Some(log.clone());
+                            }
+                        }
+                        if ui.small_button("×").clicked() {
+                            close = true;
+                        }
+                    });
+                });
+            });
+        if let Some(log) = open_log {
+            open_log_file(ctx, &log);
+            close = true;
+        }
+        if close {
+            self.error_toast = None;
+        }
+    }
+
+    /// Startup notice when the previous session died in a panic.
+    pub(super) fn draw_crash_notice(&mut self, ctx: &Context) {
+        let Some(log) = &self.crash_notice else { return };
+        let mut open = true;
+        let mut view_log = false;
+        let mut close = false;
+        egui::Window::new("⚠ 上次会话异常退出")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                if self.recovery_dialog.is_some() {
+                    ui.label("已找到未保存的草稿，可在「恢复未保存的修改」中恢复。");
+                } else {
+                    ui.label("没有需要恢复的草稿。");
+                }
+                ui.label(RichText::new(format!("日志: {}", log.display())).small().color(Color32::from_gray(140)));
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("查看日志").clicked() {
+                        view_log = true;
+                    }
+                    if ui.button("关闭").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if view_log {
+            open_log_file(ctx, log);
+        }
+        if !open || close || view_log {
+            self.crash_notice = None;
+        }
+    }
+}
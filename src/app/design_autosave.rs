@@ -0,0 +1,314 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use egui::Context;
+
+use super::{AppError, DesignPart, IoOp, ProjectData, TextToolApp, PROJECT_DATA_FILE};
+
+// ── 自动保存设计数据 ──────────────────────────────────────────────────────────
+//
+// World objects, the chapter structure and the foreshadows write themselves
+// to their `Design/` files a moment after the last change, and before the
+// window closes or another project opens, so nothing waits on a 保存 button.
+// A change is a difference from the digest taken when the data was last
+// saved or loaded (see `panel_switch.rs`), and only the parts that differ
+// are written.  Milestones stay manual: they are read on opening only with
+// 自动加载, so saving them unasked could overwrite a file never loaded.
+//
+// Every autosave also writes the three parts together to `project.json` at
+// the project root.  The `Design/` files stay what opening reads; a part
+// whose file is missing is taken from `project.json` instead, and written
+// back to its own file by the next save.
+
+/// Quiet time after the last change before the data is saved.
+const AUTOSAVE_DELAY: Duration = Duration::from_secs(2);
+
+/// The parts saved automatically, with their names for the status bar.
+const AUTOSAVED: [(DesignPart, &str); 3] = [
+    (DesignPart::Objects, "世界对象"),
+    (DesignPart::Structure, "章节结构"),
+    (DesignPart::Foreshadows, "伏笔"),
+];
+
+/// `project.json` of the project at `root`, parsed.
+pub(super) fn read_project_data(root: &Path) -> Result<(ProjectData, String), AppError> {
+    let path = root.join(PROJECT_DATA_FILE);
+    let text = std::fs::read_to_string(&path).map_err(AppError::io(IoOp::Read, &path))?;
+    let data = serde_json::from_str(&text).map_err(AppError::serde("项目数据"))?;
+    Ok((data, path.display().to_string()))
+}
+
+impl TextToolApp {
+    fn design_autosave_on(&self) -> bool {
+        self.settings.behavior.design_autosave
+            && self.project_root.is_some()
+            && !self.design_read_only()
+            // Not before the user has picked whose edits to keep.
+            && self.design_reload.is_none()
+    }
+
+    /// Note changes to the design data and save them once they have rested
+    /// for [`AUTOSAVE_DELAY`].
+    pub(super) fn tick_design_autosave(&mut self, ctx: &Context) {
+        if !self.design_autosave_on() {
+            self.design_changed_at = None;
+            return;
+        }
+        let now = Instant::now();
+        let digest = self.design_digest();
+        if self.design_seen != Some(digest) {
+            self.design_seen = Some(digest);
+            let unsaved = AUTOSAVED.iter().any(|&(part, _)| self.design_part_unsaved(part));
+            self.design_changed_at = unsaved.then_some(now);
+        }
+        let Some(changed) = self.design_changed_at else { return };
+        let due = changed + AUTOSAVE_DELAY;
+        if now < due {
+            ctx.request_repaint_after(due - now);
+            return;
+        }
+        self.design_changed_at = None;
+        self.save_project_data();
+    }
+
+    /// Save whatever autosaved part differs from its file, now.  Runs
+    /// before the window closes and before another project opens.
+    pub(super) fn flush_design_autosave(&mut self) {
+        // A project folder that went away (an unplugged drive) is not recreated.
+        if self.design_autosave_on() && self.project_root.as_ref().is_some_and(|r| r.is_dir()) {
+            self.design_changed_at = None;
+            self.save_project_data();
+        }
+    }
+
+    /// Write the autosaved parts that differ from their files, then all of
+    /// them to `project.json`.
+    pub(super) fn save_project_data(&mut self) {
+        let mut saved = Vec::new();
+        let mut results = Vec::new();
+        for (part, what) in AUTOSAVED {
+            if !self.design_part_unsaved(part) {
+                continue;
+            }
+            results.push(match part {
+                DesignPart::Objects => self.sync_world_objects_to_json(),
+                DesignPart::Structure => {
+                    self.struct_json_snapshot = serde_json::to_string(&self.struct_roots).ok();
+                    self.sync_struct_to_json()
+                }
+                DesignPart::Foreshadows => self.sync_foreshadows(),
+                DesignPart::Milestones => self.sync_milestones_to_json(),
+            });
+            saved.push(what);
+        }
+        if saved.is_empty() {
+            return;
+        }
+        // A part held back by the overwrite guard is not recorded either.
+        let error = match results.into_iter().find_map(Result::err) {
+            None => self.write_project_data().err(),
+            e => e,
+        };
+        match error {
+            Some(e) => self.report(e),
+            None => self.status = format!("已自动保存: {}", saved.join("、")),
+        }
+    }
+
+    /// The autosaved parts as one [`ProjectData`].
+    fn project_data(&self) -> ProjectData {
+        ProjectData {
+            world_objects: self.world_objects.clone(),
+            struct_roots: self.struct_roots.clone(),
+            foreshadows: self.foreshadows.clone(),
+        }
+    }
+
+    /// Write `project.json`, unless a part could not be read on opening:
+    /// the panels do not hold its data.
+    fn write_project_data(&mut self) -> Result<(), AppError> {
+        if !self.design_unloaded.is_empty() {
+            return Ok(());
+        }
+        self.check_design_writable()?;
+        let json = serde_json::to_string_pretty(&self.project_data()).map_err(AppError::serde("项目数据"))?;
+        let root = self.project_root.as_ref().ok_or(AppError::NoProject)?;
+        let path = root.join(PROJECT_DATA_FILE);
+        text_tool_core::safe_write(&path, json).map_err(AppError::io(IoOp::Write, &path))
+    }
+
+    /// Load `project.json` into the panels.  Parts that differ from their
+    /// `Design/` files are written to them by the next save.
+    pub(super) fn load_project_data(&mut self) -> Result<String, AppError> {
+        let root = self.project_root.as_ref().ok_or(AppError::NoProject)?;
+        let (data, display) = read_project_data(root)?;
+        let parts = AUTOSAVED.map(|(part, _)| part);
+        self.install_project_data(data, &parts);
+        Ok(format!("已从 {display} 加载世界对象、章节结构与伏笔"))
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use super::super::{ObjectKind, WorldObject};
+
+    fn temp_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Content")).unwrap();
+        dir
+    }
+
+    fn reopen(dir: &Path) -> TextToolApp {
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.open_project_now(dir.to_path_buf());
+        app
+    }
+
+    #[test]
+    fn test_design_autosave_after_delay() {
+        let dir = temp_project("qingmo_test_design_autosave");
+        let ctx = Context::default();
+        let mut app = reopen(&dir);
+        app.tick_design_autosave(&ctx);
+        assert!(app.design_changed_at.is_none());
+
+        app.world_objects.push(WorldObject::new("林远", ObjectKind::Character));
+        app.tick_design_autosave(&ctx);
+        assert!(app.design_changed_at.is_some());
+        assert!(!dir.join("Design").join("世界对象.json").exists());
+
+        // Once the change has rested, only the changed part is written.
+        app.design_changed_at = Some(Instant::now() - AUTOSAVE_DELAY);
+        app.tick_design_autosave(&ctx);
+        assert!(app.design_changed_at.is_none());
+        assert_eq!(app.status, "已自动保存: 世界对象");
+        assert!(!dir.join("Design").join("伏笔.json").exists());
+        assert_eq!(reopen(&dir).world_objects.len(), 1);
+
+        // Turned off, nothing is pending.
+        app.settings.behavior.design_autosave = false;
+        app.world_objects.clear();
+        app.tick_design_autosave(&ctx);
+        assert!(app.design_changed_at.is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_design_saved_on_close_and_switch() {
+        let dir = temp_project("qingmo_test_design_autosave_close");
+        let other = temp_project("qingmo_test_design_autosave_other");
+
+        // Quitting right after a change keeps it.
+        let mut app = reopen(&dir);
+        app.world_objects.push(WorldObject::new("青城", ObjectKind::Location));
+        drop(app);
+        let mut app = reopen(&dir);
+        assert_eq!(app.world_objects[0].name, "青城");
+
+        // So does opening another project.
+        app.world_objects[0].description = "山门".into();
+        app.open_project_now(other.clone());
+        assert_eq!(reopen(&dir).world_objects[0].description, "山门");
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(&other);
+    }
+
+    #[test]
+    fn test_project_data_saved_and_loaded() {
+        let dir = temp_project("qingmo_test_design_autosave_project_data");
+        let ctx = Context::default();
+        let mut app = reopen(&dir);
+        app.world_objects.push(WorldObject::new("林远", ObjectKind::Character));
+        app.tick_design_autosave(&ctx);
+        app.design_changed_at = Some(Instant::now() - AUTOSAVE_DELAY);
+        app.tick_design_autosave(&ctx);
+        assert_eq!(read_project_data(&dir).unwrap().0.world_objects[0].name, "林远");
+
+        // A design file that went missing comes back from project.json.
+        std::fs::remove_file(dir.join("Design").join("世界对象.json")).unwrap();
+        let mut app = reopen(&dir);
+        assert_eq!(app.world_objects[0].name, "林远");
+        app.flush_design_autosave();
+        assert_eq!(reopen(&dir).world_objects[0].name, "林远");
+
+        // Loading it by hand replaces the panels, and the files follow.
+        app.world_objects.clear();
+        app.load_project_data().unwrap();
+        assert_eq!(app.world_objects.len(), 1);
+        app.world_objects[0].description = "剑客".into();
+        drop(app);
+        let data = read_project_data(&dir).unwrap().0;
+        assert_eq!(data.world_objects[0].description, "剑客");
+        assert_eq!(reopen(&dir).world_objects[0].description, "剑客");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_agent_writes_reload_before_autosave() {
+        let dir = temp_project("qingmo_test_design_autosave_agent");
+        let ctx = Context::default();
+        let mut app = reopen(&dir);
+        app.world_objects.push(WorldObject::new("林远", ObjectKind::Character));
+        app.flush_design_autosave();
+
+        let skills = app.build_skill_set();
+        skills.execute("add_world_object", &serde_json::json!({ "name": "青城", "kind": "地点" })).unwrap();
+        skills.execute("list_characters", &serde_json::json!({})).unwrap();
+        app.reload_agent_writes();
+        let names: Vec<&str> = app.world_objects.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["林远", "青城"]);
+
+        // Nothing is left for autosave to write back over the skill's file.
+        app.tick_design_autosave(&ctx);
+        assert!(app.design_changed_at.is_none());
+        assert_eq!(reopen(&dir).world_objects.len(), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_agent_writes_keep_panel_edits() {
+        let dir = temp_project("qingmo_test_design_autosave_agent_edits");
+        let mut app = reopen(&dir);
+        app.world_objects.push(WorldObject::new("林远", ObjectKind::Character));
+        app.flush_design_autosave();
+        let skills = app.build_skill_set();
+
+        // An edit saved while the agent runs is kept by its next write.
+        app.world_objects.push(WorldObject::new("白鹤", ObjectKind::Character));
+        app.flush_design_autosave();
+        skills.execute("add_world_object", &serde_json::json!({ "name": "青城", "kind": "地点" })).unwrap();
+        app.reload_agent_writes();
+        let names: Vec<&str> = app.world_objects.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["林远", "白鹤", "青城"]);
+        assert!(app.design_reload.is_none());
+
+        // One not yet saved is not replaced without asking, nor saved over the file.
+        app.world_objects[0].description = "剑客".into();
+        skills.execute("delete_world_object", &serde_json::json!({ "name": "白鹤" })).unwrap();
+        app.reload_agent_writes();
+        assert_eq!(app.world_objects.len(), 3);
+        assert_eq!(app.design_reload.as_ref().unwrap().parts, [DesignPart::Objects]);
+        app.flush_design_autosave();
+        assert_eq!(reopen(&dir).world_objects.len(), 2);
+
+        // 载入智能体的修改 takes the file.
+        app.answer_design_reload(true);
+        let names: Vec<&str> = app.world_objects.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["林远", "青城"]);
+        assert!(app.world_objects[0].description.is_empty());
+        assert_eq!(app.status, "已载入智能体的修改");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,117 @@
+use egui::{Color32, Context, RichText};
+
+use super::{AppError, DesignPart, DesignReload, ReloadSource, TextToolApp};
+
+// ── 重新载入设计数据: files saved outside the panels ──────────────────────────
+//
+// Another computer letting go of the project (see `project_lock.rs`) and the
+// agent's write skills (see `agent.rs`) both leave `Design/` files newer than
+// the panels.  Parts not edited here since they were last saved are loaded
+// at once.  For the others the user picks whose edits to keep, and autosave
+// waits for the answer (see `design_autosave.rs`).
+
+impl TextToolApp {
+    /// Load `parts`, saved by `source`, into the panels not edited here, and
+    /// ask about the rest.  Returns whether it asked.
+    pub(super) fn reload_or_ask(&mut self, source: ReloadSource, parts: Vec<DesignPart>) -> bool {
+        let asked: Vec<DesignPart> = self.design_reload.as_ref().map_or(Vec::new(), |r| r.parts.clone());
+        let (edited, untouched): (Vec<DesignPart>, Vec<DesignPart>) = parts.into_iter()
+            .partition(|&part| asked.contains(&part) || self.design_part_unsaved(part));
+        if let Some(e) = self.reload_design_parts(untouched) {
+            self.report(e);
+        }
+        if edited.is_empty() {
+            return false;
+        }
+        // A question already open takes the new parts too.
+        match &mut self.design_reload {
+            Some(reload) => reload.parts.extend(edited.into_iter().filter(|p| !asked.contains(p))),
+            None => self.design_reload = Some(DesignReload { source, parts: edited }),
+        }
+        true
+    }
+
+    /// Load the design data the agent's skills saved, so the panels show it
+    /// and the next autosave does not write the old data back.
+    pub(super) fn reload_agent_writes(&mut self) {
+        let mut parts: Vec<DesignPart> = Vec::new();
+        while let Ok(part) = self.agent_written_rx.try_recv() {
+            if !parts.contains(&part) {
+                parts.push(part);
+            }
+        }
+        if parts.is_empty() {
+            return;
+        }
+        let saved: Vec<&str> = parts.iter().map(|p| p.label()).collect();
+        let saved = saved.join("、");
+        self.status = if self.reload_or_ask(ReloadSource::Agent, parts) {
+            format!("智能体保存了{saved}，请选择保留哪边的修改")
+        } else {
+            format!("已载入智能体保存的{saved}")
+        };
+    }
+
+    /// 载入…的修改 / 保留本机修改, for parts saved elsewhere while they
+    /// were edited here.
+    pub(super) fn draw_design_reload(&mut self, ctx: &Context) {
+        let Some(reload) = &self.design_reload else { return };
+        let parts: Vec<&str> = reload.parts.iter().map(|p| p.label()).collect();
+        let (title, intro, theirs) = match &reload.source {
+            ReloadSource::Computer(host) => (
+                "项目已释放",
+                format!("{host} 已不再占用此项目，它可能修改过设计数据。"),
+                "对方",
+            ),
+            ReloadSource::Agent => ("智能体修改了设计数据", "智能体刚刚保存了设计数据。".to_owned(), "智能体"),
+        };
+
+        let mut load = false;
+        let mut keep = false;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(intro);
+                ui.label(format!("本机在此期间修改了：{}。", parts.join("、")));
+                ui.label(
+                    RichText::new(format!("载入{theirs}的修改会丢弃本机的这些修改；保留本机修改会用它们覆盖{theirs}写入的文件。"))
+                        .small().color(Color32::from_gray(150)),
+                );
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button(format!("载入{theirs}的修改")).clicked() { load = true; }
+                    if ui.button("保留本机修改").clicked() { keep = true; }
+                });
+            });
+        if load || keep {
+            self.answer_design_reload(load);
+        }
+    }
+
+    /// `load` the saved files over the edited parts, or keep the edits,
+    /// which the next save writes out.
+    pub(super) fn answer_design_reload(&mut self, load: bool) {
+        let Some(reload) = self.design_reload.take() else { return };
+        if !load {
+            let parts: Vec<&str> = reload.parts.iter().map(|p| p.label()).collect();
+            self.status = format!("已保留本机修改: {}", parts.join("、"));
+            return;
+        }
+        match self.reload_design_parts(reload.parts) {
+            Some(e) => self.report(e),
+            None => self.status = match reload.source {
+                ReloadSource::Computer(host) => format!("已载入 {host} 的修改"),
+                ReloadSource::Agent => "已载入智能体的修改".to_owned(),
+            },
+        }
+    }
+
+    /// Load `parts` from their files; the first failure other than a file
+    /// the project does not have.
+    fn reload_design_parts(&mut self, parts: Vec<DesignPart>) -> Option<AppError> {
+        let results: Vec<_> = parts.into_iter().map(|part| self.load_design_part(part)).collect();
+        results.into_iter().find_map(|r| r.err().filter(|e| !e.is_not_found()))
+    }
+}
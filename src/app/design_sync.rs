@@ -0,0 +1,238 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use egui::{Color32, RichText};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::{StructNode, TextToolApp, WorldObject};
+
+/// How often the pane/panel comparison is re-run at most.
+const CHECK_INTERVAL: Duration = Duration::from_millis(750);
+
+// ── Design files shared between an editor pane and a panel ───────────────────
+
+/// A `Design/` JSON file whose contents a panel keeps in memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DesignFile {
+    /// `Design/章节结构.json` ↔ `struct_roots`.
+    Structure,
+    /// `Design/世界对象.json` ↔ `world_objects`.
+    Objects,
+}
+
+impl DesignFile {
+    pub(super) fn all() -> [DesignFile; 2] {
+        [DesignFile::Structure, DesignFile::Objects]
+    }
+
+    pub(super) fn file_name(self) -> &'static str {
+        match self {
+            DesignFile::Structure => "章节结构.json",
+            DesignFile::Objects   => "世界对象.json",
+        }
+    }
+
+    pub(super) fn panel_label(self) -> &'static str {
+        match self {
+            DesignFile::Structure => "章节结构",
+            DesignFile::Objects   => "世界对象",
+        }
+    }
+}
+
+/// An open pane showing a panel-managed Design file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesignShare {
+    pub(super) file: DesignFile,
+    /// The file is open in the left pane (otherwise the right one).
+    pub(super) left: bool,
+    /// Hash of the pane text as of the last check.
+    pane_hash: u64,
+    /// Canonical hash of the pane text, `None` if it does not parse.
+    pane_data: Option<u64>,
+    /// Canonical hash of the panel data as of the last check.
+    panel_data: u64,
+}
+
+impl DesignShare {
+    /// Pane and panel hold different data.
+    pub(super) fn differs(&self) -> bool {
+        self.pane_data != Some(self.panel_data)
+    }
+
+    /// The pane text is valid JSON for the panel's data.
+    pub(super) fn parses(&self) -> bool {
+        self.pane_data.is_some()
+    }
+}
+
+/// Which side wins when resolving a [`DesignShare`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DesignSyncAction {
+    /// Serialise the panel data into the pane buffer.
+    PanelToPane,
+    /// Deserialise the pane text into the panel data.
+    PaneToPanel,
+}
+
+/// Hash of `value` in compact JSON form, so formatting does not matter.
+pub(super) fn data_hash<T: Serialize>(value: &T) -> u64 {
+    let mut h = DefaultHasher::new();
+    serde_json::to_string(value).unwrap_or_default().hash(&mut h);
+    h.finish()
+}
+
+/// [`data_hash`] of `text` parsed as `T`, or `None` if it does not parse.
+pub(super) fn text_data_hash<T: Serialize + DeserializeOwned>(text: &str) -> Option<u64> {
+    serde_json::from_str::<T>(text).ok().map(|v| data_hash(&v))
+}
+
+fn text_hash(text: &str) -> u64 {
+    let mut h = DefaultHasher::new();
+    text.hash(&mut h);
+    h.finish()
+}
+
+// ── Detection and resolution ──────────────────────────────────────────────────
+
+impl TextToolApp {
+    /// Re-check whether a pane shows a panel-managed Design file and whether
+    /// the two differ.  Runs at most every [`CHECK_INTERVAL`], and only
+    /// re-parses the pane text when its hash changed.
+    pub(super) fn refresh_design_share(&mut self) {
+        if self.design_checked_at.is_some_and(|t| t.elapsed() < CHECK_INTERVAL) {
+            return;
+        }
+        self.design_checked_at = Some(Instant::now());
+
+        let Some(design) = self.project_root.as_ref().map(|r| r.join("Design")) else {
+            self.design_share = None;
+            return;
+        };
+        let found = DesignFile::all().into_iter().find_map(|file| {
+            let path = design.join(file.file_name());
+            if self.left_file.as_ref().is_some_and(|f| f.path == path) {
+                Some((file, true))
+            } else if self.right_file.as_ref().is_some_and(|f| f.path == path) {
+                Some((file, false))
+            } else {
+                None
+            }
+        });
+        let Some((file, left)) = found else {
+            self.design_share = None;
+            return;
+        };
+
+        let pane = if left { &self.left_file } else { &self.right_file };
+        let text = pane.as_ref().map(|f| f.content.as_str()).unwrap_or_default();
+        let pane_hash = text_hash(text);
+        let pane_data = match &self.design_share {
+            Some(s) if s.file == file && s.left == left && s.pane_hash == pane_hash => s.pane_data,
+            _ => match file {
+                DesignFile::Structure => text_data_hash::<Vec<StructNode>>(text),
+                DesignFile::Objects   => text_data_hash::<Vec<WorldObject>>(text),
+            },
+        };
+        let panel_data = match file {
+            DesignFile::Structure => data_hash(&self.struct_roots),
+            DesignFile::Objects   => data_hash(&self.world_objects),
+        };
+        self.design_share = Some(DesignShare { file, left, pane_hash, pane_data, panel_data });
+    }
+
+    /// Banner shown above a pane or panel that shares `file` with the other
+    /// side.  Returns the action the user picked.
+    pub(super) fn draw_design_banner(&self, ui: &mut egui::Ui, file: DesignFile) -> Option<DesignSyncAction> {
+        let share = self.design_share.as_ref().filter(|s| s.file == file)?;
+        let mut action = None;
+        let color = if share.differs() {
+            Color32::from_rgb(230, 170, 60)
+        } else {
+            Color32::from_gray(150)
+        };
+        egui::Frame::none()
+            .stroke(egui::Stroke::new(1.0, color))
+            .rounding(4.0)
+            .inner_margin(egui::Margin::symmetric(6.0, 4.0))
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(RichText::new(format!("此文件同时被{}面板管理", file.panel_label())).color(color));
+                    ui.label("—");
+                    if ui.small_button("从面板重新加载")
+                        .on_hover_text("用面板中的数据覆盖编辑区内容")
+                        .clicked()
+                    {
+                        action = Some(DesignSyncAction::PanelToPane);
+                    }
+                    ui.label("/");
+                    let hover = if share.parses() {
+                        "用编辑区中的 JSON 覆盖面板数据"
+                    } else {
+                        "编辑区内容不是有效的 JSON"
+                    };
+                    if ui.add_enabled(share.parses(), egui::Button::new("以文件为准覆盖面板").small())
+                        .on_hover_text(hover)
+                        .on_disabled_hover_text(hover)
+                        .clicked()
+                    {
+                        action = Some(DesignSyncAction::PaneToPanel);
+                    }
+                    if share.differs() {
+                        ui.label(RichText::new("⚠ 内容不一致").small().color(color));
+                    }
+                });
+            });
+        action
+    }
+
+    /// Resolve the current [`DesignShare`] in the direction of `action`.
+    pub(super) fn apply_design_sync(&mut self, action: DesignSyncAction) {
+        let Some(share) = self.design_share.clone() else { return };
+        let file = share.file;
+        let pane = if share.left { &mut self.left_file } else { &mut self.right_file };
+        let Some(f) = pane.as_mut() else { return };
+
+        match action {
+            DesignSyncAction::PanelToPane => {
+                let json = match file {
+                    DesignFile::Structure => serde_json::to_string_pretty(&self.struct_roots),
+                    DesignFile::Objects   => serde_json::to_string_pretty(&self.world_objects),
+                };
+                let json = match json {
+                    Ok(j) => j,
+                    Err(e) => { self.status = format!("序列化失败: {e}"); return; }
+                };
+                if f.content != json {
+                    let undo = if share.left { &mut self.left_undo_stack } else { &mut self.right_undo_stack };
+                    undo.push(std::mem::replace(&mut f.content, json), self.settings.behavior.undo_limit);
+                    f.modified = true;
+                }
+                self.status = format!("已用{}面板数据重新加载 {}", file.panel_label(), file.file_name());
+            }
+            DesignSyncAction::PaneToPanel => {
+                let text = f.content.clone();
+                let result = match file {
+                    DesignFile::Structure => serde_json::from_str::<Vec<StructNode>>(&text)
+                        .map(|nodes| {
+                            self.struct_roots = nodes;
+                            self.selected_node_path.clear();
+                        }),
+                    DesignFile::Objects => serde_json::from_str::<Vec<WorldObject>>(&text)
+                        .map(|objs| {
+                            self.world_objects = objs;
+                            self.clear_obj_selection();
+                        }),
+                };
+                self.status = match result {
+                    Ok(()) => format!("已以 {} 为准覆盖{}面板", file.file_name(), file.panel_label()),
+                    Err(e) => format!("解析失败: {e}"),
+                };
+            }
+        }
+        // Re-check on the next frame.
+        self.design_checked_at = None;
+    }
+}
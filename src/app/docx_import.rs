@@ -0,0 +1,139 @@
+use egui::{Color32, Context, RichText};
+use text_tool_core::docx::read_docx;
+use text_tool_core::split::{sanitize_file_name, split_by_headings};
+
+use super::{count_words, rfd_pick_file, AppError, DocxImportDialog, IoOp, TextToolApp};
+
+// ── 导入 DOCX ─────────────────────────────────────────────────────────────────
+//
+// 文件 → 导入 DOCX… converts a Word manuscript to Markdown, shows what was
+// skipped, then writes it under `Content/` as one file or one file per
+// level-1 heading (the same cut as 按标题拆分文件) and opens the result.
+
+impl TextToolApp {
+    /// Pick a .docx and convert it; the dialog then decides where it goes.
+    pub(super) fn import_docx(&mut self) {
+        if self.project_root.is_none() {
+            self.status = "请先打开一个项目".to_owned();
+            return;
+        }
+        let Some(source) = rfd_pick_file("Word 文档", &["docx"]) else { return };
+        let import = match std::fs::read(&source).map_err(|e| e.to_string()).and_then(|b| read_docx(&b)) {
+            Ok(import) => import,
+            Err(e) => {
+                self.show_error_toast(format!("导入 DOCX 失败: {e}"));
+                return;
+            }
+        };
+        let stem = source.file_stem().unwrap_or_default().to_string_lossy();
+        let split = split_by_headings(&import.markdown, false).len() > 1;
+        self.docx_import = Some(DocxImportDialog {
+            name: sanitize_file_name(&stem),
+            source,
+            import,
+            split,
+            overwrite: false,
+            build_struct: true,
+        });
+    }
+
+    pub(super) fn draw_docx_import_dialog(&mut self, ctx: &Context) {
+        let (Some(dlg), Some(root)) = (&mut self.docx_import, &self.project_root) else { return };
+        let content_dir = root.join("Content");
+        let mut open = true;
+        let mut cancel = false;
+        let mut confirm = false;
+
+        let parts = split_by_headings(&dlg.import.markdown, false);
+        let single = content_dir.join(format!("{}.md", sanitize_file_name(&dlg.name)));
+        let collisions = if dlg.split {
+            parts.iter().filter(|p| content_dir.join(p.relative_path()).exists()).count()
+        } else {
+            usize::from(single.exists())
+        };
+
+        egui::Window::new("导入 DOCX")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(460.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                let file = dlg.source.file_name().unwrap_or_default().to_string_lossy();
+                ui.label(format!("{file} · {} 字", count_words(&dlg.import.markdown)));
+                if !dlg.import.warnings.is_empty() {
+                    ui.label(RichText::new(format!("⚠ 以下内容未导入（{} 处）:", dlg.import.warnings.len()))
+                        .color(Color32::from_rgb(220, 160, 60)));
+                    egui::ScrollArea::vertical().id_salt("docx_warnings").max_height(100.0).show(ui, |ui| {
+                        for w in &dlg.import.warnings {
+                            ui.label(RichText::new(w).small().color(Color32::from_gray(140)));
+                        }
+                    });
+                }
+                ui.separator();
+
+                ui.add_enabled(parts.len() > 1, egui::Checkbox::new(&mut dlg.split, "按一级标题拆分为多个文件"));
+                if dlg.split {
+                    ui.checkbox(&mut dlg.build_struct, "同时在章节结构中添加对应节点");
+                    ui.label(format!("将在 Content/ 下生成 {} 个文件:", parts.len()));
+                    egui::ScrollArea::vertical().id_salt("docx_parts").max_height(220.0).show(ui, |ui| {
+                        for part in &parts {
+                            let name = RichText::new(part.relative_path());
+                            let exists = content_dir.join(part.relative_path()).exists();
+                            ui.horizontal(|ui| {
+                                ui.label(if exists { name.color(Color32::from_rgb(220, 80, 80)) } else { name });
+                                ui.label(RichText::new(format!("{} 字", count_words(&part.content)))
+                                    .small().color(Color32::from_gray(140)));
+                            });
+                        }
+                    });
+                } else {
+                    ui.horizontal(|ui| {
+                        ui.label("Content/");
+                        ui.text_edit_singleline(&mut dlg.name);
+                        ui.label(".md");
+                    });
+                }
+                if collisions > 0 {
+                    ui.label(RichText::new(format!("⚠ {collisions} 个文件已存在"))
+                        .color(Color32::from_rgb(220, 160, 60)));
+                    ui.checkbox(&mut dlg.overwrite, "覆盖已存在的文件（否则跳过）");
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    let ready = !dlg.import.markdown.is_empty() && (dlg.split || !dlg.name.trim().is_empty());
+                    if ui.add_enabled(ready, egui::Button::new("导入")).clicked() {
+                        confirm = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if confirm {
+            let Some(dlg) = self.docx_import.take() else { return };
+            if dlg.split {
+                self.write_split_parts(&content_dir, &parts, dlg.overwrite, dlg.build_struct);
+                if let Some(first) = parts.first() {
+                    if let Err(e) = self.open_file_in_pane(&content_dir.join(first.relative_path()), true) {
+                        self.report(e);
+                    }
+                }
+            } else if single.exists() && !dlg.overwrite {
+                self.status = format!("{} 已存在，未导入", single.display());
+            } else {
+                let res = std::fs::create_dir_all(&content_dir)
+                    .and_then(|_| text_tool_core::safe_write(&single, &dlg.import.markdown))
+                    .map_err(AppError::io(IoOp::Write, &single))
+                    .and_then(|_| {
+                        self.refresh_tree();
+                        self.open_file_in_pane(&single, true)
+                    });
+                self.notify(res.map(|_| format!("已导入 {}", single.display())));
+            }
+        } else if !open || cancel {
+            self.docx_import = None;
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use egui::{Color32, Context, RichText};
+use text_tool_core::duplicates::DEFAULT_MIN_CHARS;
+
+use super::{DuplicateEvent, DuplicatesView, TextToolApp};
+
+// ── 查找重复段落: the same scene pasted into two chapters ─────────────────────
+
+/// Characters of a paragraph shown before it is cut off with `…`.
+const EXCERPT_CHARS: usize = 80;
+
+impl TextToolApp {
+    /// 工具 → 查找重复段落: scan every `Content/` Markdown file on the
+    /// background index, keeping the threshold of the last run.
+    pub(super) fn open_duplicates(&mut self) {
+        let Some(root) = self.project_root.clone() else {
+            self.status = "请先打开一个项目".to_owned();
+            return;
+        };
+        let min_chars = self.duplicates.as_ref().map_or(DEFAULT_MIN_CHARS, |v| v.min_chars);
+        let content_dir = root.join("Content");
+        self.duplicates = Some(DuplicatesView {
+            pending: Some(self.project_index.spawn_duplicate_check(root, content_dir, min_chars)),
+            progress: (0, 0),
+            groups: Vec::new(),
+            min_chars,
+        });
+    }
+
+    pub(super) fn draw_duplicates(&mut self, ctx: &Context) {
+        let Some(view) = &mut self.duplicates else { return };
+        while let Some(rx) = &view.pending {
+            match rx.try_recv() {
+                Ok(DuplicateEvent::Progress(done, total)) => view.progress = (done, total),
+                Ok(DuplicateEvent::Done(groups)) => {
+                    view.groups = groups;
+                    view.pending = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    ctx.request_repaint();
+                    break;
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => view.pending = None,
+            }
+        }
+
+        let root = self.project_root.clone().unwrap_or_default();
+        let mut open = true;
+        let mut rerun = false;
+        let mut jump = None;
+        egui::Window::new("查找重复段落")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([520.0, 420.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("段落至少");
+                    ui.add(egui::DragValue::new(&mut view.min_chars).range(1..=2000).suffix(" 字"));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        rerun = ui.add_enabled(view.pending.is_none(), egui::Button::new("🔄 重新查找")).clicked();
+                    });
+                });
+                ui.label(RichText::new("Content/ 中已保存的 Markdown 文件；忽略空白差异").small().color(Color32::from_gray(140)));
+                ui.separator();
+
+                if view.pending.is_some() {
+                    let (done, total) = view.progress;
+                    let fraction = if total == 0 { 0.0 } else { done as f32 / total as f32 };
+                    ui.add(egui::ProgressBar::new(fraction).text(format!("正在查找… {done}/{total}")));
+                    return;
+                }
+                if view.groups.is_empty() {
+                    ui.label(RichText::new("✅ 没有重复段落").color(Color32::from_rgb(120, 190, 120)));
+                    return;
+                }
+                ui.label(format!("{} 组重复段落", view.groups.len()));
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    for group in &view.groups {
+                        let mut excerpt: String = group.text.chars().take(EXCERPT_CHARS).collect();
+                        if group.text.chars().count() > EXCERPT_CHARS {
+                            excerpt.push('…');
+                        }
+                        ui.label(RichText::new(excerpt).strong()).on_hover_text(&group.text);
+                        ui.label(RichText::new(format!("{} 字 · {} 处", group.chars, group.locations.len()))
+                            .small().color(Color32::from_gray(150)));
+                        for loc in &group.locations {
+                            let rel = loc.file.strip_prefix(&root).unwrap_or(&loc.file).display().to_string();
+                            ui.horizontal(|ui| {
+                                ui.add_space(12.0);
+                                if ui.small_button("跳转").on_hover_text(format!("{rel} 第 {} 行", loc.line_no)).clicked() {
+                                    jump = Some((loc.file.clone(), loc.line_no));
+                                }
+                                ui.label(RichText::new(format!("{rel}:{}", loc.line_no)).small());
+                            });
+                        }
+                        ui.add_space(6.0);
+                    }
+                });
+            });
+
+        if !open {
+            self.duplicates = None;
+            return;
+        }
+        if rerun {
+            self.open_duplicates();
+        }
+        if let Some((path, line_no)) = jump {
+            self.open_at_line(&path, line_no);
+        }
+    }
+}
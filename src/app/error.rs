@@ -0,0 +1,227 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::TextToolApp;
+
+// ── AppError: failures reported to the user ──────────────────────────────────
+//
+// Operations that can fail return `Result<_, AppError>`; the UI hands the
+// error to [`TextToolApp::report`], which words it the same way everywhere
+// and decides from its kind how loudly to show it: warnings go to the status
+// bar, errors also raise the error toast.
+
+/// How loudly an error is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Status bar; something the user can put right (no project, bad input).
+    Warning,
+    /// Status bar and the error toast.
+    Error,
+}
+
+/// What was being done to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoOp {
+    Read,
+    Write,
+    Create,
+    Copy,
+    /// Handing a file to the system's default program.
+    Open,
+}
+
+impl IoOp {
+    pub fn label(self) -> &'static str {
+        match self {
+            IoOp::Read => "读取",
+            IoOp::Write => "写入",
+            IoOp::Create => "创建",
+            IoOp::Copy => "复制",
+            IoOp::Open => "打开",
+        }
+    }
+}
+
+/// Where an LLM call went wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmErrorKind {
+    /// The request did not get through: network, refused, HTTP status.
+    Request,
+    /// The service answered with an error of its own.
+    Api,
+    /// The answer was not in the expected shape.
+    Response,
+    /// Nothing usable came back: an empty reply, too many agent rounds.
+    NoAnswer,
+}
+
+#[derive(Debug)]
+pub enum AppError {
+    /// The operation needs an open project.
+    NoProject,
+    /// Another computer holds the project's design data (see `project_lock.rs`).
+    DesignLocked { host: String },
+    Io { op: IoOp, path: PathBuf, source: io::Error },
+    /// `what` (世界对象, 章节结构, …) could not be read or written as JSON.
+    Serde { what: String, source: serde_json::Error },
+    Llm { kind: LlmErrorKind, detail: String },
+    /// Input the operation rejects, in the words shown to the user.
+    Validation(String),
+}
+
+impl AppError {
+    /// For `map_err`: the I/O error of `op` on `path`.
+    pub fn io(op: IoOp, path: &Path) -> impl FnOnce(io::Error) -> AppError + '_ {
+        move |source| AppError::Io { op, path: path.to_owned(), source }
+    }
+
+    /// For `map_err`: the JSON error of `what`.
+    pub fn serde(what: &str) -> impl FnOnce(serde_json::Error) -> AppError + '_ {
+        move |source| AppError::Serde { what: what.to_owned(), source }
+    }
+
+    pub fn llm(kind: LlmErrorKind, detail: impl Into<String>) -> AppError {
+        AppError::Llm { kind, detail: detail.into() }
+    }
+
+    /// A read of a file that does not exist (yet).
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, AppError::Io { source, .. } if source.kind() == io::ErrorKind::NotFound)
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            AppError::NoProject | AppError::DesignLocked { .. } | AppError::Validation(_) => Severity::Warning,
+            AppError::Io { .. } | AppError::Serde { .. } | AppError::Llm { .. } => Severity::Error,
+        }
+    }
+}
+
+/// The reason part of an I/O error, in Chinese for the common cases.
+fn io_reason(e: &io::Error) -> String {
+    match e.kind() {
+        io::ErrorKind::NotFound => "文件或文件夹不存在".to_owned(),
+        io::ErrorKind::PermissionDenied => "没有访问权限".to_owned(),
+        io::ErrorKind::AlreadyExists => "已存在同名文件".to_owned(),
+        io::ErrorKind::InvalidData => "不是有效的 UTF-8 文本".to_owned(),
+        _ => e.to_string(),
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NoProject => write!(f, "请先打开一个项目"),
+            AppError::DesignLocked { host } => {
+                write!(f, "{host} 正在编辑此项目，设计数据未写入（可在横幅中强制接管）")
+            }
+            AppError::Io { op, path, source } => {
+                write!(f, "{} {} 失败: {}", op.label(), path.display(), io_reason(source))
+            }
+            AppError::Serde { what, source } => write!(f, "{what}格式错误: {source}"),
+            AppError::Llm { kind, detail } => {
+                let what = match kind {
+                    LlmErrorKind::Request => "LLM 请求失败",
+                    LlmErrorKind::Api => "LLM 服务返回错误",
+                    LlmErrorKind::Response => "LLM 响应解析失败",
+                    LlmErrorKind::NoAnswer => "LLM 未给出回复",
+                };
+                write!(f, "{what}: {detail}")
+            }
+            AppError::Validation(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Io { source, .. } => Some(source),
+            AppError::Serde { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl TextToolApp {
+    /// Tell the user about `err`: status bar, plus the error toast when its
+    /// severity is [`Severity::Error`].
+    pub(super) fn report(&mut self, err: AppError) {
+        let message = err.to_string();
+        if err.severity() == Severity::Error {
+            self.show_error_toast(message.clone());
+        }
+        self.status = message;
+    }
+
+    /// Status message of a finished operation, or its error.
+    pub(super) fn notify(&mut self, result: Result<String, AppError>) {
+        match result {
+            Ok(message) => self.status = message,
+            Err(e) => self.report(e),
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_error_messages() {
+        let not_found = AppError::io(IoOp::Read, Path::new("Design/世界对象.json"))(io::ErrorKind::NotFound.into());
+        assert_eq!(not_found.to_string(), "读取 Design/世界对象.json 失败: 文件或文件夹不存在");
+        assert_eq!(not_found.severity(), Severity::Error);
+
+        let denied = AppError::io(IoOp::Write, Path::new("x.md"))(io::ErrorKind::PermissionDenied.into());
+        assert_eq!(denied.to_string(), "写入 x.md 失败: 没有访问权限");
+
+        let bad_json = serde_json::from_str::<Vec<u32>>("[1,").unwrap_err();
+        let serde = AppError::serde("章节结构")(bad_json);
+        assert!(serde.to_string().starts_with("章节结构格式错误: "), "{serde}");
+
+        assert_eq!(AppError::llm(LlmErrorKind::Request, "连接被拒绝").to_string(), "LLM 请求失败: 连接被拒绝");
+        assert_eq!(AppError::NoProject.to_string(), "请先打开一个项目");
+        assert_eq!(AppError::NoProject.severity(), Severity::Warning);
+        let locked = AppError::DesignLocked { host: "laptop".to_owned() };
+        assert_eq!(locked.to_string(), "laptop 正在编辑此项目，设计数据未写入（可在横幅中强制接管）");
+        assert_eq!(AppError::Validation("文件名不能为空".to_owned()).to_string(), "文件名不能为空");
+    }
+
+    #[test]
+    fn test_report_routes_by_severity() {
+        let mut app = TextToolApp::blank();
+        app.report(AppError::NoProject);
+        assert_eq!(app.status, "请先打开一个项目");
+        assert!(app.error_toast.is_none());
+
+        app.report(AppError::io(IoOp::Create, Path::new("a.md"))(io::ErrorKind::AlreadyExists.into()));
+        assert_eq!(app.status, "创建 a.md 失败: 已存在同名文件");
+        assert_eq!(app.error_toast.as_ref().map(|t| t.message.as_str()), Some(app.status.as_str()));
+
+        app.notify(Ok("已保存".to_owned()));
+        assert_eq!(app.status, "已保存");
+    }
+
+    #[test]
+    fn test_failed_pane_save_shows_toast() {
+        let dir = std::env::temp_dir().join(format!("qingmo_test_save_error_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("第一章.md")).unwrap();
+
+        // The pane's path is a folder: the save fails, loudly, and the edit stays.
+        let mut app = TextToolApp::blank();
+        let mut f = super::super::OpenFile::new(dir.join("第一章.md"), "雨夜".to_owned());
+        f.modified = true;
+        app.left_file = Some(f);
+        app.save_left();
+        assert!(app.status.starts_with("写入 "), "{}", app.status);
+        assert!(app.error_toast.is_some());
+        assert!(app.left_file.as_ref().unwrap().modified);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
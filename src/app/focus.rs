@@ -0,0 +1,137 @@
+use egui::{Context, Id, Key, Modifiers, Rect};
+
+use super::{list_step, node_paths, FocusRegion, Panel, TextToolApp};
+
+// ── Keyboard focus regions ────────────────────────────────────────────────────
+//
+// F6 / Ctrl+` moves focus to the next major region of the panel (Shift: the
+// previous one) and outlines it.  Editors receive real keyboard focus; lists
+// give it up so ↑ / ↓ move their selection.  Esc in a list returns to the
+// editor last written in.  Clicking into a region makes it the current one.
+
+const LEFT_EDITOR: &str = "left_editor_main";
+const RIGHT_EDITOR: &str = "right_editor_main";
+
+impl TextToolApp {
+    /// F6 / Ctrl+`, Esc and list ↑ / ↓.  Runs before the panels draw.
+    pub(super) fn handle_focus_keys(&mut self, ctx: &Context) {
+        if self.reading.is_some() {
+            return;
+        }
+        let focused = ctx.memory(|m| m.focused());
+        if focused == Some(Id::new(LEFT_EDITOR)) {
+            self.focus_region = Some(FocusRegion::LeftEditor);
+        } else if focused == Some(Id::new(RIGHT_EDITOR)) {
+            self.focus_region = Some(FocusRegion::RightEditor);
+        }
+
+        let (backward, forward) = ctx.input_mut(|i| {
+            let backward = i.consume_key(Modifiers::SHIFT, Key::F6)
+                || i.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::Backtick);
+            let forward = i.consume_key(Modifiers::NONE, Key::F6) || i.consume_key(Modifiers::COMMAND, Key::Backtick);
+            (backward, forward)
+        });
+        if backward || forward {
+            let right_open = self.right_file.is_some();
+            let region = FocusRegion::step(self.active_panel, right_open, self.focus_region, backward);
+            self.focus_on(ctx, region);
+            self.status = format!("焦点: {}", region.label());
+            return;
+        }
+
+        let Some(region) = self.focus_region.filter(|r| !r.is_editor()) else { return };
+        if focused.is_some() {
+            return;
+        }
+        if self.active_panel == Panel::Novel && ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape)) {
+            let editor = if self.last_focused_left || self.right_file.is_none() {
+                FocusRegion::LeftEditor
+            } else {
+                FocusRegion::RightEditor
+            };
+            self.focus_on(ctx, editor);
+            return;
+        }
+        if region.is_list() {
+            let (up, down) = ctx.input_mut(|i| {
+                (i.consume_key(Modifiers::NONE, Key::ArrowUp), i.consume_key(Modifiers::NONE, Key::ArrowDown))
+            });
+            if up != down {
+                self.step_list_selection(region, up);
+            }
+        }
+    }
+
+    /// Make `region` current: editors get keyboard focus, anything else
+    /// takes it away from whichever widget holds it.
+    fn focus_on(&mut self, ctx: &Context, region: FocusRegion) {
+        self.focus_region = Some(region);
+        match region {
+            FocusRegion::LeftEditor => {
+                self.last_focused_left = true;
+                ctx.memory_mut(|m| m.request_focus(Id::new(LEFT_EDITOR)));
+            }
+            FocusRegion::RightEditor => {
+                self.last_focused_left = false;
+                ctx.memory_mut(|m| m.request_focus(Id::new(RIGHT_EDITOR)));
+            }
+            _ => ctx.memory_mut(|m| {
+                if let Some(id) = m.focused() {
+                    m.surrender_focus(id);
+                }
+            }),
+        }
+    }
+
+    /// ↑ / ↓ in a focused list: select the previous / next item.
+    fn step_list_selection(&mut self, region: FocusRegion, up: bool) {
+        match region {
+            FocusRegion::ObjectList | FocusRegion::ObjectRefs => {
+                // The object list hides filtered-out objects; the 对象参考
+                // sidebar always lists every object.
+                let filter = (region == FocusRegion::ObjectList).then(|| self.obj_list_filter());
+                let order: Vec<usize> = self.world_objects.iter().enumerate()
+                    .filter(|(i, o)| filter.as_ref().is_none_or(|f| f.shows(*i, o)))
+                    .map(|(i, _)| i)
+                    .collect();
+                let current = self.selected_obj_idx().or(self.obj_select_anchor);
+                if let Some(i) = list_step(&order, current.as_ref(), up) {
+                    self.select_obj(i);
+                    self.focus_scroll = true;
+                }
+            }
+            FocusRegion::StructTree => {
+                let order = node_paths(&self.struct_roots);
+                let current = Some(&self.selected_node_path).filter(|p| !p.is_empty());
+                if let Some(path) = list_step(&order, current, up) {
+                    self.selected_node_path = path;
+                    self.focus_scroll = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// After `region` has drawn into `rect`: a press inside makes it the
+    /// current region, and the current region gets a subtle outline.
+    pub(super) fn note_focus_region(&mut self, ctx: &Context, region: FocusRegion, rect: Rect) {
+        let pressed_inside = ctx.input(|i| {
+            i.pointer.any_pressed() && i.pointer.interact_pos().is_some_and(|p| rect.contains(p))
+        });
+        // Ignore presses that land on a window or popup above the panel.
+        let on_panel = ctx.input(|i| i.pointer.interact_pos())
+            .and_then(|p| ctx.layer_id_at(p))
+            .is_some_and(|layer| layer == egui::LayerId::background());
+        if pressed_inside && on_panel {
+            self.focus_region = Some(region);
+            if region.is_editor() {
+                self.last_focused_left = region == FocusRegion::LeftEditor;
+            }
+        }
+        if self.focus_region == Some(region) {
+            let color = ctx.style().visuals.selection.stroke.color.gamma_multiply(0.5);
+            ctx.layer_painter(egui::LayerId::background())
+                .rect_stroke(rect.shrink(1.0), 3.0, egui::Stroke::new(1.0, color));
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::TextToolApp;
+
+// ── 中文字体: where the CJK glyphs come from ──────────────────────────────────
+//
+// The font is looked for in this order: the file chosen in 设置, the copy
+// compiled in with the `embed-font` feature, then the usual system CJK fonts.
+// Without any of them egui's own font still draws Latin text, and a toast
+// warns that 中文 may show as boxes.  The definitions are built once per
+// chosen file; the theme only changes visuals and never rebuilds them.
+
+/// The font compiled into the binary (`embed-font` feature).
+#[cfg(feature = "embed-font")]
+const EMBEDDED_FONT: Option<&[u8]> = Some(include_bytes!("../../assets/NotoSansCJKsc-Regular.otf"));
+#[cfg(not(feature = "embed-font"))]
+const EMBEDDED_FONT: Option<&[u8]> = None;
+
+/// CJK fonts commonly installed on Windows, macOS and Linux.
+const SYSTEM_FONTS: &[&str] = &[
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/STHeiti Light.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+];
+
+/// Where the loaded font came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontSource {
+    /// The file set in 设置.
+    Configured(PathBuf),
+    Embedded,
+    System(PathBuf),
+}
+
+/// The fonts installed into the context.
+pub struct InstalledFont {
+    /// `cjk_font` setting they were built from.
+    pub(super) setting: Option<String>,
+    /// `None` when egui's default font is all there is.
+    pub(super) source: Option<FontSource>,
+}
+
+/// Outcome of [`resolve_font`].
+pub(super) struct ResolvedFont {
+    /// `None` when no CJK font was found.
+    pub(super) source: Option<FontSource>,
+    pub(super) data: Option<egui::FontData>,
+    /// Shown in the toast: the configured file failed, or nothing was found.
+    pub(super) warning: Option<String>,
+}
+
+/// `bytes` as font data, if they parse as a TTF/OTF font (the first face of
+/// a collection).  egui would panic on anything else, a frame later.
+fn parse_font(bytes: Vec<u8>) -> Result<egui::FontData, String> {
+    ab_glyph::FontRef::try_from_slice_and_index(&bytes, 0).map_err(|_| "不是有效的 TTF/OTF 字体".to_owned())?;
+    Ok(egui::FontData::from_owned(bytes))
+}
+
+/// The first font that loads: `configured`, then `embedded`, then `system`.
+/// A file that reads but does not parse counts as not loading.
+pub(super) fn resolve_font(
+    configured: Option<&Path>,
+    embedded: Option<&'static [u8]>,
+    system: &[&str],
+    read: impl Fn(&Path) -> io::Result<Vec<u8>>,
+) -> ResolvedFont {
+    let mut problem = None;
+    let mut found = None;
+    if let Some(path) = configured {
+        match read(path).map_err(|e| e.to_string()).and_then(parse_font) {
+            Ok(data) => found = Some((FontSource::Configured(path.to_owned()), data)),
+            Err(e) => problem = Some(format!("无法读取字体文件 {}（{e}）", path.display())),
+        }
+    }
+    if found.is_none() {
+        found = embedded.map(|bytes| (FontSource::Embedded, egui::FontData::from_static(bytes)));
+    }
+    if found.is_none() {
+        found = system.iter().map(Path::new).find_map(|path| {
+            let data = parse_font(read(path).ok()?).ok()?;
+            Some((FontSource::System(path.to_owned()), data))
+        });
+    }
+    let warning = match (&found, problem) {
+        (Some((source, _)), Some(problem)) => Some(format!("{problem}，已改用{}", source.label())),
+        (None, problem) => Some(format!(
+            "{}未找到中文字体，中文可能显示为方框；可在「设置 → 编辑器」中选择字体文件",
+            problem.map(|p| format!("{p}；")).unwrap_or_default(),
+        )),
+        (Some(_), None) => None,
+    };
+    let (source, data) = found.unzip();
+    ResolvedFont { source, data, warning }
+}
+
+impl FontSource {
+    pub(super) fn label(&self) -> String {
+        match self {
+            FontSource::Configured(path) => format!("字体 {}", path.display()),
+            FontSource::Embedded => "内置字体".to_owned(),
+            FontSource::System(path) => format!("系统字体 {}", path.display()),
+        }
+    }
+}
+
+impl TextToolApp {
+    /// Load the Chinese font set in 设置 into `ctx`, once for all windows.
+    /// Does nothing when the fonts already come from that setting.
+    pub(super) fn install_fonts(&mut self, ctx: &egui::Context) {
+        let configured = self.settings.cjk_font.clone();
+        if self.installed_font.as_ref().is_some_and(|f| f.setting == configured) {
+            return;
+        }
+        let resolved = resolve_font(configured.as_deref().map(Path::new), EMBEDDED_FONT, SYSTEM_FONTS, |p| std::fs::read(p));
+        let mut fonts = egui::FontDefinitions::default();
+        if let Some(data) = resolved.data {
+            fonts.font_data.insert("chinese".to_owned(), data);
+            fonts.families.get_mut(&egui::FontFamily::Proportional).unwrap().insert(0, "chinese".to_owned());
+            fonts.families.get_mut(&egui::FontFamily::Monospace).unwrap().insert(0, "chinese".to_owned());
+        }
+        ctx.set_fonts(fonts);
+        self.installed_font = Some(InstalledFont { setting: configured, source: resolved.source });
+        if let Some(warning) = resolved.warning {
+            self.show_error_toast(warning.clone());
+            self.status = warning;
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMBEDDED: &[u8] = b"embedded";
+
+    /// A file system holding only `files`, each a real font, and `.bad`
+    /// files that are not fonts.
+    fn reader(files: &'static [&'static str]) -> impl Fn(&Path) -> io::Result<Vec<u8>> {
+        move |path| {
+            if !files.iter().any(|f| Path::new(f) == path) {
+                Err(io::ErrorKind::NotFound.into())
+            } else if path.extension().is_some_and(|e| e == "bad") {
+                Ok(b"not a font".to_vec())
+            } else {
+                Ok(egui::FontDefinitions::default().font_data["Ubuntu-Light"].font.to_vec())
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolve_font_order() {
+        let system = &["/sys/a.ttc", "/sys/b.ttc"];
+
+        // The configured file wins.
+        let r = resolve_font(Some(Path::new("/me/font.otf")), Some(EMBEDDED), system, reader(&["/me/font.otf", "/sys/a.ttc"]));
+        assert_eq!(r.source, Some(FontSource::Configured(PathBuf::from("/me/font.otf"))));
+        assert!(r.warning.is_none());
+
+        // Unset: the embedded copy, before any system font.
+        let r = resolve_font(None, Some(EMBEDDED), system, reader(&["/sys/a.ttc"]));
+        assert_eq!(r.source, Some(FontSource::Embedded));
+        assert!(r.warning.is_none());
+
+        // Missing configured file: fall back, and say so.
+        let r = resolve_font(Some(Path::new("/gone.otf")), Some(EMBEDDED), system, reader(&[]));
+        assert_eq!(r.source, Some(FontSource::Embedded));
+        let warning = r.warning.unwrap();
+        assert!(warning.starts_with("无法读取字体文件 /gone.otf"), "{warning}");
+        assert!(warning.ends_with("已改用内置字体"), "{warning}");
+
+        // A file that reads but is no font: the same, before egui can choke on it.
+        let r = resolve_font(Some(Path::new("/me/broken.bad")), Some(EMBEDDED), system, reader(&["/me/broken.bad"]));
+        assert_eq!(r.source, Some(FontSource::Embedded));
+        let warning = r.warning.unwrap();
+        assert!(warning.starts_with("无法读取字体文件 /me/broken.bad"), "{warning}");
+
+        // A broken system font is passed over too.
+        let r = resolve_font(None, None, &["/sys/a.bad", "/sys/b.ttc"], reader(&["/sys/a.bad", "/sys/b.ttc"]));
+        assert_eq!(r.source, Some(FontSource::System(PathBuf::from("/sys/b.ttc"))));
+
+        // Without the embedded font: the first system font present.
+        let r = resolve_font(None, None, system, reader(&["/sys/b.ttc"]));
+        assert_eq!(r.source, Some(FontSource::System(PathBuf::from("/sys/b.ttc"))));
+    }
+
+    #[test]
+    fn test_resolve_font_none_found() {
+        let r = resolve_font(None, None, &["/sys/a.ttc"], reader(&[]));
+        assert!(r.source.is_none() && r.data.is_none());
+        assert!(r.warning.unwrap().starts_with("未找到中文字体"));
+
+        let r = resolve_font(Some(Path::new("/gone.otf")), None, &[], reader(&[]));
+        let warning = r.warning.unwrap();
+        assert!(warning.starts_with("无法读取字体文件 /gone.otf"), "{warning}");
+        assert!(warning.contains("；未找到中文字体"), "{warning}");
+    }
+}
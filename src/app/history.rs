@@ -0,0 +1,241 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::diff::{diff_lines, TextDiff};
+use text_tool_core::history::{list_revisions, read_revision, revision_dir, save_revision, Revision};
+
+use super::pane_diff::{draw_diff_rows, draw_hunk_nav};
+use super::{format_day, local_offset_secs, AppError, HistoryBrowser, PaneOpen, Panel, TextToolApp};
+
+// ── 历史版本: revisions of a file and their diff against the current text ─────
+//
+// With 保存历史版本 on (设置 → 行为), every save of a Content Markdown file
+// stores a compressed copy (see `text_tool_core::history`).  历史版本… in
+// the file tree lists a file's revisions with their change in length and
+// diffs the chosen one against the text in the pane, or on disk;
+// 还原此版本 puts it in the pane as one undo step.
+
+fn now_millis() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as i64)
+}
+
+/// Local date and time of a revision: `2024-03-01 21:30:05`.
+fn revision_time(millis: i64) -> String {
+    let local = millis.div_euclid(1000) + local_offset_secs();
+    let s = local.rem_euclid(86_400);
+    format!("{} {:02}:{:02}:{:02}", format_day(local.div_euclid(86_400)), s / 3600, s % 3600 / 60, s % 60)
+}
+
+/// Change in length from the revision before `revisions[i]`: `+120`, `−8`.
+fn size_delta(revisions: &[Revision], i: usize) -> Option<String> {
+    let older = revisions.get(i + 1)?;
+    Some(match revisions[i].chars as i64 - older.chars as i64 {
+        d if d > 0 => format!("+{d}"),
+        d if d < 0 => format!("−{}", -d),
+        _ => "±0".to_owned(),
+    })
+}
+
+fn file_label(path: &Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned())
+}
+
+impl HistoryBrowser {
+    /// Show revision `i` against the `current` text.
+    fn select(&mut self, i: usize, current: String) {
+        self.selected = i;
+        self.text = self.revisions.get(i)
+            .ok_or_else(|| "版本不存在".to_owned())
+            .and_then(|r| read_revision(r).map_err(|e| e.to_string()));
+        self.current = current;
+        self.refresh_diff();
+    }
+
+    fn refresh_diff(&mut self) {
+        self.diff = match &self.text {
+            Ok(text) => diff_lines(text, &self.current),
+            Err(_) => TextDiff::default(),
+        };
+        self.hunk = self.hunk.min(self.diff.hunks.len().saturating_sub(1));
+        self.scroll = true;
+    }
+}
+
+impl TextToolApp {
+    /// Keep a revision of `path`, just saved as `content`, when 保存历史版本
+    /// is on and it is a Markdown file under `Content/`.
+    pub(super) fn record_revision(&mut self, path: &Path, content: &str) {
+        let settings = &self.settings.behavior.history;
+        let Some(root) = self.project_root.as_ref().filter(|_| settings.enabled) else { return };
+        let markdown = path.extension().is_some_and(|e| e == "md" || e == "markdown");
+        if !markdown || !path.starts_with(root.join("Content")) {
+            return;
+        }
+        if let Err(e) = save_revision(root, path, content, now_millis(), settings) {
+            self.status = format!("{}（历史版本未保存: {e}）", self.status);
+        }
+    }
+
+    /// The revision of the file in a pane that was just saved.
+    pub(super) fn record_pane_revision(&mut self, path: &Path) {
+        let content = [&self.left_file, &self.right_file].into_iter().flatten()
+            .find(|f| f.path == path)
+            .map(|f| f.content.clone());
+        if let Some(content) = content {
+            self.record_revision(path, &content);
+        }
+    }
+
+    /// 历史版本…: browse the revisions of `path`.
+    pub(super) fn open_history(&mut self, path: &Path) {
+        let Some(root) = &self.project_root else {
+            self.report(AppError::NoProject);
+            return;
+        };
+        let revisions = revision_dir(root, path).map(|dir| list_revisions(&dir)).unwrap_or_default();
+        if revisions.is_empty() {
+            let name = file_label(path);
+            self.status = if self.settings.behavior.history.enabled {
+                format!("「{name}」还没有历史版本")
+            } else {
+                format!("「{name}」没有历史版本，可在 设置 → 行为 中开启「保存历史版本」")
+            };
+            return;
+        }
+        let current = [&self.left_file, &self.right_file].into_iter().flatten()
+            .find(|f| f.path == path)
+            .map(|f| f.content.clone())
+            .or_else(|| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        let mut browser = HistoryBrowser {
+            file: path.to_path_buf(),
+            revisions,
+            selected: 0,
+            text: Ok(String::new()),
+            current: String::new(),
+            diff: TextDiff::default(),
+            hunk: 0,
+            scroll: true,
+        };
+        browser.select(0, current);
+        self.history_browser = Some(browser);
+    }
+
+    /// Revisions on the left, newest first; the chosen one's diff against
+    /// the current text on the right.  Follows edits in the pane showing
+    /// the file while open.
+    pub(super) fn draw_history_browser(&mut self, ctx: &Context) {
+        let Some(browser) = &mut self.history_browser else { return };
+        if let Some(f) = [&self.left_file, &self.right_file].into_iter().flatten().find(|f| f.path == browser.file) {
+            if f.content != browser.current {
+                browser.current = f.content.clone();
+                browser.refresh_diff();
+            }
+        }
+
+        let mut open = true;
+        let mut select: Option<usize> = None;
+        let mut restore = false;
+        egui::Window::new(format!("历史版本 — {}", file_label(&browser.file)))
+            .id(egui::Id::new("history_browser"))
+            .open(&mut open)
+            .resizable(true)
+            .default_size([960.0, 560.0])
+            .show(ctx, |ui| {
+                egui::SidePanel::left("history_revisions")
+                    .resizable(true)
+                    .default_width(210.0)
+                    .show_inside(ui, |ui| {
+                        ui.label(RichText::new(format!("{} 个版本", browser.revisions.len()))
+                            .small().color(Color32::from_gray(150)));
+                        egui::ScrollArea::vertical().id_salt("history_revision_list").show(ui, |ui| {
+                            for (i, revision) in browser.revisions.iter().enumerate() {
+                                let delta = size_delta(&browser.revisions, i).unwrap_or_else(|| "最早".to_owned());
+                                let text = RichText::new(format!("{}\n{} 字符  {delta}", revision_time(revision.millis), revision.chars));
+                                if ui.selectable_label(i == browser.selected, text).clicked() && i != browser.selected {
+                                    select = Some(i);
+                                }
+                            }
+                        });
+                    });
+
+                let when = browser.revisions.get(browser.selected).map(|r| revision_time(r.millis)).unwrap_or_default();
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("{when} → 当前")).strong());
+                    ui.separator();
+                    draw_hunk_nav(ui, &browser.diff, &mut browser.hunk, &mut browser.scroll);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let enabled = browser.text.is_ok() && !browser.diff.is_identical();
+                        if ui.add_enabled(enabled, egui::Button::new("还原此版本"))
+                            .on_hover_text("把此版本载入编辑区（可 Ctrl+Z 撤销），保存后生效")
+                            .clicked()
+                        {
+                            restore = true;
+                        }
+                    });
+                });
+                ui.separator();
+                match &browser.text {
+                    Ok(text) => draw_diff_rows(
+                        ui, "history_diff_rows", text, &browser.current, &browser.diff, browser.hunk, &mut browser.scroll,
+                    ),
+                    Err(e) => {
+                        ui.label(RichText::new(format!("无法读取此版本: {e}")).color(Color32::from_rgb(220, 110, 110)));
+                    }
+                }
+            });
+
+        if let Some(i) = select {
+            let current = std::mem::take(&mut browser.current);
+            browser.select(i, current);
+        }
+        if restore {
+            if let Ok(text) = browser.text.clone() {
+                let (file, when) = (browser.file.clone(), browser.revisions[browser.selected].millis);
+                self.restore_revision(&file, text, when);
+            }
+        }
+        if !open {
+            self.history_browser = None;
+        }
+    }
+
+    /// 还原此版本: `text` replaces the content of the pane showing `path`,
+    /// opened first when no pane does, as one undo step.
+    fn restore_revision(&mut self, path: &Path, text: String, millis: i64) {
+        let shows = |f: &Option<super::OpenFile>| f.as_ref().is_some_and(|f| f.path == path);
+        let left = if shows(&self.left_file) {
+            true
+        } else if shows(&self.right_file) {
+            false
+        } else {
+            let left = self.opens_in_left(path);
+            let pane = if left { &self.left_file } else { &self.right_file };
+            if let Some(f) = pane.as_ref().filter(|f| f.modified && !f.scratch) {
+                self.status = format!("「{}」有未保存的修改，请先保存", file_label(&f.path));
+                return;
+            }
+            match self.open_file_in_pane(path, left) {
+                Ok(PaneOpen::Opened(_)) => {}
+                r => {
+                    self.notify_open(r);
+                    return;
+                }
+            }
+            left
+        };
+        let limit = self.settings.behavior.undo_limit;
+        let (pane, undo) = if left {
+            (&mut self.left_file, &mut self.left_undo_stack)
+        } else {
+            (&mut self.right_file, &mut self.right_undo_stack)
+        };
+        let Some(f) = pane.as_mut() else { return };
+        undo.push(std::mem::replace(&mut f.content, text), limit);
+        f.modified = true;
+        self.last_focused_left = left;
+        self.active_panel = Panel::Novel;
+        self.status = format!("已还原 {} 的版本（Ctrl+Z 可撤销），保存后生效", revision_time(millis));
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use egui::{Color32, Context, Key, RichText};
+use text_tool_core::links::{heading_anchors, markdown_link, relative_link};
+use text_tool_core::outline::parse_outline_entries;
+
+use super::merge_files::collect_tree_files;
+use super::{all_node_titles, LinkChoice, LinkPicker, OutlineEntry, TextToolApp};
+
+// ── 插入章节链接 ──────────────────────────────────────────────────────────────
+
+impl TextToolApp {
+    /// Open the picker listing project Markdown files and struct nodes.
+    pub(super) fn open_link_picker(&mut self) {
+        let Some(current) = self.left_file.as_ref().filter(|f| f.is_markdown()) else {
+            self.status = "请先在左侧打开一个 Markdown 文件".to_owned();
+            return;
+        };
+        let mut files = Vec::new();
+        collect_tree_files(&self.file_tree, &mut files);
+        files.retain(|p| p.extension().is_some_and(|e| e == "md") && *p != current.path);
+
+        let headings: Vec<Vec<OutlineEntry>> = files.iter()
+            .map(|p| self.file_headings(p))
+            .collect();
+        let mut choices = Vec::new();
+        for (path, entries) in files.iter().zip(&headings) {
+            let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            let first = entries.first();
+            choices.push(LinkChoice {
+                title: first.map_or(stem, |e| e.title.clone()),
+                path: path.clone(),
+                anchor: first.map(|_| heading_anchors(entries).remove(0)),
+                from_node: false,
+            });
+        }
+        // Struct nodes resolve to the first heading of that title, else to a
+        // file named after them; unresolved nodes are left out.
+        let mut seen = BTreeSet::new();
+        for title in all_node_titles(&self.struct_roots) {
+            let title = title.trim();
+            if !seen.insert(title.to_owned()) {
+                continue;
+            }
+            let by_heading = files.iter().zip(&headings).find_map(|(path, entries)| {
+                let i = entries.iter().position(|e| e.title == title)?;
+                Some((path, Some(heading_anchors(entries).swap_remove(i))))
+            });
+            let target = by_heading.or_else(|| {
+                files.iter().find(|p| p.file_stem().is_some_and(|s| s == title)).map(|p| (p, None))
+            });
+            if let Some((path, anchor)) = target {
+                choices.push(LinkChoice {
+                    title: title.to_owned(),
+                    path: path.clone(),
+                    anchor,
+                    from_node: true,
+                });
+            }
+        }
+        self.link_picker = Some(LinkPicker { query: String::new(), choices });
+    }
+
+    fn file_headings(&self, path: &Path) -> Vec<OutlineEntry> {
+        self.project_index.headings(path).unwrap_or_else(|| {
+            std::fs::read_to_string(path)
+                .map(|text| parse_outline_entries(&text))
+                .unwrap_or_default()
+        })
+    }
+
+    pub(super) fn draw_link_picker(&mut self, ctx: &Context) {
+        let Some(picker) = &mut self.link_picker else {
+            return;
+        };
+        let root = self.project_root.clone().unwrap_or_default();
+        let mut open = true;
+        let mut chosen: Option<LinkChoice> = None;
+
+        let query = picker.query.to_lowercase();
+        let matches: Vec<&LinkChoice> = picker.choices.iter()
+            .filter(|c| query.is_empty()
+                || c.title.to_lowercase().contains(&query)
+                || c.path.to_string_lossy().to_lowercase().contains(&query))
+            .collect();
+
+        egui::Window::new("插入章节链接")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(420.0)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+            .show(ctx, |ui| {
+                let resp = ui.add(egui::TextEdit::singleline(&mut picker.query)
+                    .hint_text("搜索文件或章节标题，回车插入第一项")
+                    .desired_width(f32::INFINITY));
+                resp.request_focus();
+                if resp.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    chosen = matches.first().map(|c| (*c).clone());
+                }
+                ui.separator();
+                if matches.is_empty() {
+                    ui.label(RichText::new("没有匹配的文件或章节").color(Color32::GRAY));
+                }
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    for choice in &matches {
+                        let icon = if choice.from_node { "📑" } else { "📄" };
+                        let rel = choice.path.strip_prefix(&root).unwrap_or(&choice.path);
+                        ui.horizontal(|ui| {
+                            if ui.selectable_label(false, format!("{icon} {}", choice.title)).clicked() {
+                                chosen = Some((*choice).clone());
+                            }
+                            ui.label(RichText::new(rel.display().to_string())
+                                .small().color(Color32::from_gray(140)));
+                        });
+                    }
+                });
+            });
+
+        if ctx.input(|i| i.key_pressed(Key::Escape)) {
+            open = false;
+        }
+        if let Some(choice) = chosen {
+            self.link_picker = None;
+            self.insert_link(ctx, &choice);
+        } else if !open {
+            self.link_picker = None;
+        }
+    }
+
+    /// Insert the link at the left editor's caret.  A selection becomes the
+    /// link text; otherwise the target's title is used.
+    fn insert_link(&mut self, ctx: &Context, choice: &LinkChoice) {
+        let te_id = egui::Id::new("left_editor_main");
+        let Some(f) = &mut self.left_file else {
+            return;
+        };
+        let Some(dir) = f.path.parent() else {
+            return;
+        };
+        let mut state = egui::text_edit::TextEditState::load(ctx, te_id).unwrap_or_default();
+        let chars: Vec<char> = f.content.chars().collect();
+        let (from, to) = state.cursor.char_range()
+            .map(|r| {
+                let (a, b) = (r.primary.index, r.secondary.index);
+                (a.min(b).min(chars.len()), a.max(b).min(chars.len()))
+            })
+            .unwrap_or((chars.len(), chars.len()));
+        let selected: String = chars[from..to].iter().collect();
+        let text = if selected.trim().is_empty() { choice.title.as_str() } else { selected.as_str() };
+        let link = markdown_link(text, &relative_link(dir, &choice.path), choice.anchor.as_deref());
+
+        let mut new_content: String = chars[..from].iter().collect();
+        new_content.push_str(&link);
+        new_content.extend(&chars[to..]);
+        self.left_undo_stack.push(std::mem::replace(&mut f.content, new_content), self.settings.behavior.undo_limit);
+        f.modified = true;
+
+        let caret = from + link.chars().count();
+        state.cursor.set_char_range(Some(egui::text::CCursorRange::one(
+            egui::text::CCursor::new(caret))));
+        egui::text_edit::TextEditState::store(state, ctx, te_id);
+        ctx.memory_mut(|m| m.request_focus(te_id));
+        self.last_focused_left = true;
+        self.status = format!("已插入链接: {link}");
+    }
+}
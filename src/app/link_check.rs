@@ -0,0 +1,211 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::links::{heading_anchors, relative_link, BrokenLink, LinkProblem};
+
+use super::{LinkCheckEvent, LinkCheckView, OutlineEntry, TextToolApp};
+
+// ── 检查文档内链接: relative links whose file or heading is gone ──────────────
+
+/// Whether one file stem contains the other; 修正为… lists such files first.
+fn similar(a: &str, b: &str) -> bool {
+    !a.is_empty() && !b.is_empty() && (a.contains(b) || b.contains(a))
+}
+
+/// Replacement targets for `b`: headings of its file when only the anchor
+/// is wrong, else files (similarly named first) matching `filter`, keeping
+/// the anchor where the new file has it.  `(label, new target)` pairs.
+fn fix_choices(b: &BrokenLink, targets: &[(PathBuf, Vec<OutlineEntry>)], root: &Path, filter: &str) -> Vec<(String, String)> {
+    let from_dir = b.file.parent().unwrap_or(Path::new(""));
+    if b.problem == LinkProblem::MissingAnchor {
+        let path = b.link.target.split_once('#').map_or("", |(p, _)| p);
+        let Some((_, entries)) = targets.iter().find(|(p, _)| *p == b.target) else { return Vec::new() };
+        return entries.iter().zip(heading_anchors(entries))
+            .map(|(e, anchor)| (format!("# {}", e.title), format!("{path}#{anchor}")))
+            .collect();
+    }
+    let stem = b.target.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let filter = filter.trim().to_lowercase();
+    let mut files: Vec<&(PathBuf, Vec<OutlineEntry>)> = targets.iter()
+        .filter(|(p, _)| *p != b.file)
+        .filter(|(p, _)| filter.is_empty() || p.to_string_lossy().to_lowercase().contains(&filter))
+        .collect();
+    files.sort_by_key(|(p, _)| !similar(&p.file_stem().unwrap_or_default().to_string_lossy(), &stem));
+    files.into_iter()
+        .map(|(p, entries)| {
+            let mut target = relative_link(from_dir, p);
+            if let Some(anchor) = b.anchor.as_ref().filter(|a| heading_anchors(entries).contains(&a.to_lowercase())) {
+                target = format!("{target}#{anchor}");
+            }
+            (p.strip_prefix(root).unwrap_or(p).display().to_string(), target)
+        })
+        .collect()
+}
+
+impl TextToolApp {
+    /// 工具 → 检查文档内链接: check every `Content/` Markdown file on the
+    /// background index.
+    pub(super) fn open_link_check(&mut self) {
+        let Some(root) = self.project_root.clone() else {
+            self.status = "请先打开一个项目".to_owned();
+            return;
+        };
+        let content_dir = root.join("Content");
+        self.link_check = Some(LinkCheckView {
+            pending: Some(self.project_index.spawn_link_check(root, content_dir)),
+            progress: (0, 0),
+            broken: Vec::new(),
+            targets: Vec::new(),
+            filter: String::new(),
+        });
+    }
+
+    pub(super) fn draw_link_check(&mut self, ctx: &Context) {
+        let Some(view) = &mut self.link_check else { return };
+        while let Some(rx) = &view.pending {
+            match rx.try_recv() {
+                Ok(LinkCheckEvent::Progress(done, total)) => view.progress = (done, total),
+                Ok(LinkCheckEvent::Done { broken, targets }) => {
+                    view.broken = broken;
+                    view.targets = targets;
+                    view.pending = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    ctx.request_repaint();
+                    break;
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => view.pending = None,
+            }
+        }
+
+        let root = self.project_root.clone().unwrap_or_default();
+        let mut open = true;
+        let mut rerun = false;
+        let mut jump = None;
+        let mut fix: Option<(usize, String)> = None;
+        egui::Window::new("检查文档内链接")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([520.0, 420.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Content/ 中已保存的 Markdown 文件").small().color(Color32::from_gray(140)));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        rerun = ui.add_enabled(view.pending.is_none(), egui::Button::new("🔄 重新检查")).clicked();
+                    });
+                });
+                ui.separator();
+
+                if view.pending.is_some() {
+                    let (done, total) = view.progress;
+                    let fraction = if total == 0 { 0.0 } else { done as f32 / total as f32 };
+                    ui.add(egui::ProgressBar::new(fraction).text(format!("正在检查… {done}/{total}")));
+                    return;
+                }
+                if view.broken.is_empty() {
+                    ui.label(RichText::new("✅ 没有失效链接").color(Color32::from_rgb(120, 190, 120)));
+                    return;
+                }
+                ui.label(format!("{} 处失效链接", view.broken.len()));
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    for (i, b) in view.broken.iter().enumerate() {
+                        let rel = b.file.strip_prefix(&root).unwrap_or(&b.file).display().to_string();
+                        ui.horizontal(|ui| {
+                            if ui.small_button("跳转").on_hover_text(format!("{rel} 第 {} 行", b.link.line_no)).clicked() {
+                                jump = Some((b.file.clone(), b.link.line_no));
+                            }
+                            ui.label(RichText::new(format!("{rel}:{}", b.link.line_no)).small().strong());
+                            ui.label(RichText::new(b.message()).small().color(Color32::from_rgb(230, 120, 100)));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add_space(12.0);
+                            ui.label(RichText::new(format!("[{}]({})", b.link.text, b.link.target))
+                                .small().monospace().color(Color32::from_gray(160)));
+                            ui.menu_button("修正为…", |ui| {
+                                if b.problem == LinkProblem::MissingFile {
+                                    ui.add(egui::TextEdit::singleline(&mut view.filter)
+                                        .hint_text("筛选文件")
+                                        .desired_width(220.0));
+                                }
+                                let choices = fix_choices(b, &view.targets, &root, &view.filter);
+                                if choices.is_empty() {
+                                    ui.label(RichText::new("没有可选的目标").small().color(Color32::GRAY));
+                                }
+                                egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+                                    for (label, target) in choices {
+                                        if ui.button(label).on_hover_text(&target).clicked() {
+                                            fix = Some((i, target));
+                                            ui.close_menu();
+                                        }
+                                    }
+                                });
+                            });
+                        });
+                        ui.add_space(4.0);
+                    }
+                });
+            });
+
+        if !open {
+            self.link_check = None;
+            return;
+        }
+        if rerun {
+            self.open_link_check();
+        }
+        if let Some((i, target)) = fix {
+            self.fix_link(i, &target);
+        }
+        if let Some((path, line_no)) = jump {
+            self.open_at_line(&path, line_no);
+        }
+    }
+
+    /// Point broken link `i` at `target`: in the pane showing its file (one
+    /// undo step), else on disk.  Later links of the same file are shifted.
+    fn fix_link(&mut self, i: usize, target: &str) {
+        let Some(b) = self.link_check.as_ref().and_then(|v| v.broken.get(i)).cloned() else { return };
+        let range = b.link.range.clone();
+        let replace = |text: &str| -> Option<String> {
+            (text.get(range.clone()) == Some(b.link.target.as_str()))
+                .then(|| format!("{}{target}{}", &text[..range.start], &text[range.end..]))
+        };
+        let limit = self.settings.behavior.undo_limit;
+        let name = b.file.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let fixed = if let Some(f) = self.left_file.as_mut().filter(|f| f.path == b.file) {
+            replace(&f.content).map(|content| {
+                self.left_undo_stack.push(std::mem::replace(&mut f.content, content), limit);
+                f.modified = true;
+            })
+        } else if let Some(f) = self.right_file.as_mut().filter(|f| f.path == b.file) {
+            replace(&f.content).map(|content| {
+                self.right_undo_stack.push(std::mem::replace(&mut f.content, content), limit);
+                f.modified = true;
+            })
+        } else {
+            match std::fs::read_to_string(&b.file).ok().and_then(|text| replace(&text)) {
+                Some(content) => match text_tool_core::safe_write(&b.file, content) {
+                    Ok(_) => Some(()),
+                    Err(e) => {
+                        self.status = format!("写入「{name}」失败: {e}");
+                        return;
+                    }
+                },
+                None => None,
+            }
+        };
+        if fixed.is_none() {
+            self.status = format!("「{name}」已更改，请重新检查");
+            return;
+        }
+
+        let Some(view) = &mut self.link_check else { return };
+        view.broken.remove(i);
+        let delta = target.len() as isize - b.link.target.len() as isize;
+        for other in view.broken.iter_mut().filter(|o| o.file == b.file && o.link.range.start > range.start) {
+            other.link.range.start = other.link.range.start.saturating_add_signed(delta);
+            other.link.range.end = other.link.range.end.saturating_add_signed(delta);
+        }
+        self.status = format!("已修正链接: {target}");
+    }
+}
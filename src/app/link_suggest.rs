@@ -0,0 +1,93 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::timeline::suggest_links;
+
+use super::{leaf_for_file, node_at, node_at_mut, push_unique_names, LinkSuggestion, TextToolApp};
+
+// ── 未关联对象 suggestions after saving a chapter ─────────────────────────────
+
+/// How long the suggestion toast stays up.
+const TOAST_SECS: u64 = 15;
+/// Names listed in the toast before 等.
+const SHOWN_NAMES: usize = 5;
+
+impl TextToolApp {
+    /// After saving `path`: if it is the chapter file of a struct leaf, offer
+    /// to link the world objects `text` mentions that the leaf does not.
+    pub(super) fn suggest_links_after_save(&mut self, path: &Path, text: &str) {
+        let Some(root) = &self.project_root else { return };
+        let Ok(rel) = path.strip_prefix(root.join("Content")) else { return };
+        if path.extension().and_then(|e| e.to_str()) != Some("md") {
+            return;
+        }
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        let Some(leaf) = leaf_for_file(&self.struct_roots, &rel) else { return };
+        let Some(node) = node_at(&self.struct_roots, &leaf) else { return };
+        let names: Vec<&str> = self.world_objects.iter().map(|o| o.name.as_str()).collect();
+        let missing = suggest_links(text, &node.linked_objects, &names);
+        self.link_suggestion = (!missing.is_empty()).then(|| LinkSuggestion {
+            path: leaf,
+            title: node.title.clone(),
+            names: missing.into_iter().map(str::to_owned).collect(),
+            since: Instant::now(),
+        });
+    }
+
+    /// Link the suggested objects and save the structure.
+    fn accept_link_suggestion(&mut self, s: LinkSuggestion) {
+        match node_at_mut(&mut self.struct_roots, &s.path).filter(|n| n.title == s.title) {
+            Some(node) => {
+                let added = push_unique_names(&mut node.linked_objects, s.names);
+                if added > 0 {
+                    node.touch();
+                }
+                self.autosave_struct();
+                self.status = format!("已为「{}」关联 {added} 个对象", s.title);
+            }
+            None => self.status = format!("章节「{}」已不在结构中", s.title),
+        }
+    }
+
+    /// Suggestion toast, bottom right above the crash toast.
+    pub(super) fn draw_link_suggestion(&mut self, ctx: &Context) {
+        let Some(s) = &self.link_suggestion else { return };
+        let left = Duration::from_secs(TOAST_SECS).saturating_sub(s.since.elapsed());
+        if left.is_zero() {
+            self.link_suggestion = None;
+            return;
+        }
+        ctx.request_repaint_after(left);
+        let mut listed = s.names.iter().take(SHOWN_NAMES).cloned().collect::<Vec<_>>().join("、");
+        if s.names.len() > SHOWN_NAMES {
+            listed.push_str(" 等");
+        }
+        let mut accept = false;
+        let mut close = false;
+        egui::Area::new(egui::Id::new("link_suggestion_toast"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -76.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("检测到 {} 个未关联对象：{listed} —", s.names.len()))
+                            .on_hover_text(format!("「{}」正文提到但未关联的对象", s.title));
+                        if ui.button(RichText::new("一键添加").color(Color32::from_rgb(120, 190, 120))).clicked() {
+                            accept = true;
+                        }
+                        if ui.small_button("×").clicked() {
+                            close = true;
+                        }
+                    });
+                });
+            });
+        if accept {
+            if let Some(s) = self.link_suggestion.take() {
+                self.accept_link_suggestion(s);
+            }
+        } else if close {
+            self.link_suggestion = None;
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::app::LlmConfig;
+use crate::app::{AppError, LlmConfig, LlmErrorKind};
 
 // ── LlmBackend trait ──────────────────────────────────────────────────────────
 
@@ -9,8 +9,8 @@ use crate::app::LlmConfig;
 /// support being wrapped in `Arc<dyn LlmBackend>` and shared across threads.
 pub trait LlmBackend: Send + Sync {
     /// Send a completion request with the given prompt and return the
-    /// model's text response, or a human-readable error string on failure.
-    fn complete(&self, config: &LlmConfig, prompt: &str) -> Result<String, String>;
+    /// model's text response.
+    fn complete(&self, config: &LlmConfig, prompt: &str) -> Result<String, AppError>;
 
     /// Human-readable name shown in the UI.
     fn name(&self) -> &'static str;
@@ -25,9 +25,9 @@ pub struct MockBackend;
 impl LlmBackend for MockBackend {
     fn name(&self) -> &'static str { "模拟模型" }
 
-    fn complete(&self, config: &LlmConfig, prompt: &str) -> Result<String, String> {
+    fn complete(&self, config: &LlmConfig, prompt: &str) -> Result<String, AppError> {
         if prompt.trim().is_empty() {
-            return Err("提示词为空，请输入内容后再试".to_owned());
+            return Err(AppError::Validation("提示词为空，请输入内容后再试".to_owned()));
         }
         Ok(format!(
             "【模拟输出 – 请配置真实模型】\n\n根据您的提示「{}…」，这里将显示模型生成的文本。\n\n当前配置:\n- {}: {}\n- 温度: {:.2}\n- 最大Token: {}",
@@ -54,7 +54,7 @@ pub struct ApiBackend;
 impl LlmBackend for ApiBackend {
     fn name(&self) -> &'static str { "HTTP API" }
 
-    fn complete(&self, config: &LlmConfig, prompt: &str) -> Result<String, String> {
+    fn complete(&self, config: &LlmConfig, prompt: &str) -> Result<String, AppError> {
         let url = config.api_url.trim_end_matches('/');
 
         if url.ends_with("/api/generate") {
@@ -67,7 +67,7 @@ impl LlmBackend for ApiBackend {
 
 impl ApiBackend {
     /// Call an Ollama `/api/generate` endpoint.
-    fn call_ollama(config: &LlmConfig, prompt: &str) -> Result<String, String> {
+    fn call_ollama(config: &LlmConfig, prompt: &str) -> Result<String, AppError> {
         let model = Self::model_name(config);
         let body = serde_json::json!({
             "model": model,
@@ -81,21 +81,21 @@ impl ApiBackend {
 
         let mut response = ureq::post(&config.api_url)
             .send_json(&body)
-            .map_err(|e| format!("请求失败 ({}): {e}", config.api_url))?;
+            .map_err(|e| AppError::llm(LlmErrorKind::Request, format!("{}: {e}", config.api_url)))?;
 
         let json: serde_json::Value = response
             .body_mut()
             .read_json()
-            .map_err(|e| format!("响应解析失败: {e}"))?;
+            .map_err(|e| AppError::llm(LlmErrorKind::Response, e.to_string()))?;
 
         json.get("response")
             .and_then(|v| v.as_str())
             .map(|s| s.to_owned())
-            .ok_or_else(|| format!("无法从响应中读取 'response' 字段: {json}"))
+            .ok_or_else(|| AppError::llm(LlmErrorKind::Response, format!("无法从响应中读取 'response' 字段: {json}")))
     }
 
     /// Call an OpenAI-compatible `/v1/chat/completions` endpoint.
-    fn call_openai(config: &LlmConfig, prompt: &str) -> Result<String, String> {
+    fn call_openai(config: &LlmConfig, prompt: &str) -> Result<String, AppError> {
         let model = Self::model_name(config);
 
         // Build messages array; include system prompt if configured.
@@ -114,12 +114,12 @@ impl ApiBackend {
 
         let mut response = ureq::post(&config.api_url)
             .send_json(&body)
-            .map_err(|e| format!("请求失败 ({}): {e}", config.api_url))?;
+            .map_err(|e| AppError::llm(LlmErrorKind::Request, format!("{}: {e}", config.api_url)))?;
 
         let json: serde_json::Value = response
             .body_mut()
             .read_json()
-            .map_err(|e| format!("响应解析失败: {e}"))?;
+            .map_err(|e| AppError::llm(LlmErrorKind::Response, e.to_string()))?;
 
         json.get("choices")
             .and_then(|v| v.get(0))
@@ -127,7 +127,7 @@ impl ApiBackend {
             .and_then(|v| v.get("content"))
             .and_then(|v| v.as_str())
             .map(|s| s.to_owned())
-            .ok_or_else(|| format!("无法从响应中读取 choices[0].message.content: {json}"))
+            .ok_or_else(|| AppError::llm(LlmErrorKind::Response, format!("无法从响应中读取 choices[0].message.content: {json}")))
     }
 
     /// Extract a model name from the config: use model_path if set, else a default.
@@ -154,7 +154,7 @@ pub struct LocalServerBackend;
 impl LlmBackend for LocalServerBackend {
     fn name(&self) -> &'static str { "本地服务器 (llama.cpp)" }
 
-    fn complete(&self, config: &LlmConfig, prompt: &str) -> Result<String, String> {
+    fn complete(&self, config: &LlmConfig, prompt: &str) -> Result<String, AppError> {
         let url = config.api_url.trim_end_matches('/');
         // Append /completion if the URL doesn't already end with it.
         let endpoint = if url.ends_with("/completion") {
@@ -180,17 +180,17 @@ impl LlmBackend for LocalServerBackend {
 
         let mut response = ureq::post(&endpoint)
             .send_json(&body)
-            .map_err(|e| format!("请求失败 ({}): {e}", endpoint))?;
+            .map_err(|e| AppError::llm(LlmErrorKind::Request, format!("{}: {e}", endpoint)))?;
 
         let json: serde_json::Value = response
             .body_mut()
             .read_json()
-            .map_err(|e| format!("响应解析失败: {e}"))?;
+            .map_err(|e| AppError::llm(LlmErrorKind::Response, e.to_string()))?;
 
         json.get("content")
             .and_then(|v| v.as_str())
             .map(|s| s.to_owned())
-            .ok_or_else(|| format!("无法从响应中读取 'content' 字段: {json}"))
+            .ok_or_else(|| AppError::llm(LlmErrorKind::Response, format!("无法从响应中读取 'content' 字段: {json}")))
     }
 }
 
@@ -291,7 +291,7 @@ impl PromptTemplate {
 /// State for a non-blocking LLM request running on a background thread.
 /// The UI polls `try_recv()` each frame to check for completion.
 pub struct LlmTask {
-    pub receiver: std::sync::mpsc::Receiver<Result<String, String>>,
+    pub receiver: std::sync::mpsc::Receiver<Result<String, AppError>>,
 }
 
 impl LlmTask {
@@ -334,7 +334,7 @@ mod tests {
         let backend = MockBackend;
         let result = backend.complete(&default_config(), "");
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("提示词为空"));
+        assert!(result.unwrap_err().to_string().contains("提示词为空"));
     }
 
     #[test]
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::export::merge_documents;
+
+use super::{rfd_save_file, FileNode, MergeDialog, TextToolApp, TreeFilter};
+
+// ── 合并为一个文件 ────────────────────────────────────────────────────────────
+
+impl TextToolApp {
+    /// Open the merge dialog for the Ctrl+click selection, in tree order.
+    pub(super) fn open_merge_dialog(&mut self) {
+        let mut files = Vec::new();
+        collect_tree_files(&self.file_tree, &mut files);
+        files.retain(|p| self.multi_selected.contains(p));
+        let Some(first) = files.first() else {
+            return;
+        };
+        let dest = first.parent().unwrap_or(Path::new(".")).join("合并.md");
+        self.merge_dialog = Some(MergeDialog { files, dest, trash_originals: false });
+    }
+
+    pub(super) fn draw_merge_dialog(&mut self, ctx: &Context) {
+        let Some(dlg) = &mut self.merge_dialog else {
+            return;
+        };
+        let mut open = true;
+        let mut cancel = false;
+        let mut confirm = false;
+        let mut swap: Option<(usize, usize)> = None;
+
+        egui::Window::new("合并为一个文件")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(420.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("合并顺序（每个文件前插入以文件名为题的一级标题，原有标题降一级）:");
+                egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+                    let n = dlg.files.len();
+                    for (i, path) in dlg.files.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(i > 0, egui::Button::new("⬆").small()).clicked() {
+                                swap = Some((i, i - 1));
+                            }
+                            if ui.add_enabled(i + 1 < n, egui::Button::new("⬇").small()).clicked() {
+                                swap = Some((i, i + 1));
+                            }
+                            ui.label(format!("{}. {}", i + 1, file_stem(path)));
+                        });
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("输出到:");
+                    ui.label(RichText::new(dlg.dest.display().to_string()).small());
+                    if ui.small_button("选择…").clicked() {
+                        if let Some(p) = rfd_save_file(&dlg.dest) {
+                            dlg.dest = p;
+                        }
+                    }
+                });
+                if dlg.dest.exists() {
+                    ui.label(RichText::new("⚠ 目标文件已存在，将被覆盖")
+                        .color(Color32::from_rgb(220, 160, 60)));
+                }
+                ui.checkbox(&mut dlg.trash_originals, "合并后将原文件移入废稿");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("合并").clicked() {
+                        confirm = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if let Some((a, b)) = swap {
+            dlg.files.swap(a, b);
+        }
+        if confirm {
+            if let Some(dlg) = self.merge_dialog.take() {
+                self.merge_files(dlg);
+            }
+        } else if !open || cancel {
+            self.merge_dialog = None;
+        }
+    }
+
+    fn merge_files(&mut self, dlg: MergeDialog) {
+        let mut docs = Vec::new();
+        for path in &dlg.files {
+            match std::fs::read_to_string(path) {
+                Ok(text) => docs.push((file_stem(path), text)),
+                Err(e) => {
+                    self.status = format!("读取 {} 失败: {e}", path.display());
+                    return;
+                }
+            }
+        }
+        if let Err(e) = text_tool_core::safe_write(&dlg.dest, merge_documents(&docs)) {
+            self.status = format!("合并失败: {e}");
+            return;
+        }
+        if dlg.trash_originals {
+            for path in dlg.files.iter().filter(|p| **p != dlg.dest) {
+                self.move_to_trash(path);
+            }
+        }
+        self.multi_selected.clear();
+        self.refresh_tree();
+        let r = self.open_file_in_pane(&dlg.dest, true);
+        self.notify(r.map(|_| format!("已合并 {} 个文件到 {}", dlg.files.len(), dlg.dest.display())));
+    }
+}
+
+/// Files of the tree in display (depth-first) order.  Folders that were
+/// not loaded yet are listed from disk.
+pub(super) fn collect_tree_files(nodes: &[FileNode], out: &mut Vec<PathBuf>) {
+    for node in nodes {
+        if node.is_dir && !node.loaded {
+            let mut node = node.clone();
+            node.load_all(&TreeFilter::default());
+            collect_tree_files(&node.children, out);
+        } else if node.is_dir {
+            collect_tree_files(&node.children, out);
+        } else {
+            out.push(node.path.clone());
+        }
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default()
+}
@@ -1,7 +1,28 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, Sender};
 use std::time::Instant;
 
+use text_tool_core::annotations::Annotation;
+use text_tool_core::diff::TextDiff;
+use text_tool_core::duplicates::DuplicateGroup;
+use text_tool_core::foreshadow_check::ForeshadowWarning;
+use text_tool_core::history::Revision;
+use text_tool_core::ignore::IgnoreRules;
+use text_tool_core::links::BrokenLink;
+use text_tool_core::markers::Marker;
+use text_tool_core::node_rename::NodeRenamePlan;
+use text_tool_core::outline_doc::OutlineDocOptions;
+use text_tool_core::paragraphs::FormatOptions;
+use text_tool_core::project_lock::ProjectLock;
+use text_tool_core::report::SessionSummary;
+use text_tool_core::reading::ReadingBook;
+use text_tool_core::recovery::RecoveryManifest;
+use text_tool_core::synopsis::SynopsisOptions;
+use text_tool_core::undo::UndoState;
+use text_tool_core::usage::LinkUsage;
+use text_tool_core::word_freq::FreqReport;
+
 /// Returns the home directory, checking platform-appropriate env vars.
 fn dirs_home() -> Option<PathBuf> {
     // On Windows USERPROFILE is the standard home location; on Unix $HOME.
@@ -19,40 +40,142 @@ fn dirs_home() -> Option<PathBuf> {
 /// which gives the correct value for UTC+0 and is always monotonically correct
 /// within a day.  No external crate is needed.
 fn chrono_label() -> String {
+    let local = local_unix_secs().rem_euclid(86400);
+    let hh = local / 3600;
+    let mm = (local % 3600) / 60;
+    let ss = local % 60;
+    format!("{hh:02}:{mm:02}:{ss:02}")
+}
+
+/// Seconds since the Unix epoch shifted by the best-effort local offset
+/// (see [`chrono_label`]).
+fn local_unix_secs() -> i64 {
     // Best-effort local-time from SystemTime + timezone env var.
     let utc_secs = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs() as i64;
 
+    utc_secs + local_offset_secs()
+}
+
+/// Seconds local time is ahead of UTC.
+fn local_offset_secs() -> i64 {
     // Read TZ offset hours from env (e.g. "Asia/Shanghai" won't parse but
     // "UTC+8" or "+0800" style vars might be set via TZOFFSET).
-    let offset_secs: i64 = std::env::var("TZOFFSET")
+    std::env::var("TZOFFSET")
         .ok()
         .and_then(|s| s.parse::<i64>().ok())
         .map(|h| h * 3600)
-        .unwrap_or(0);
+        .unwrap_or(0)
+}
 
-    let local = (utc_secs + offset_secs).rem_euclid(86400);
-    let hh = local / 3600;
-    let mm = (local % 3600) / 60;
-    let ss = local % 60;
-    format!("{hh:02}:{mm:02}:{ss:02}")
+/// Today's local date as a day number (days since 1970-01-01).
+fn today_days() -> i64 {
+    local_unix_secs().div_euclid(86400)
 }
 
 mod models;
+mod error;
 mod file_manager;
 mod llm_backend;
 mod agent;
 mod sync;
 mod search;
+mod project_index;
+mod project_prompt;
+mod report;
+mod paragraph_format;
+mod split_file;
+mod docx_import;
+mod merge_files;
+mod content_import;
+mod insert_link;
+mod link_suggest;
+mod outline_export;
+mod synopsis;
+mod object_kinds;
+mod changelog;
+mod fonts;
+mod content_index;
+mod pane_diff;
+mod word_freq;
+mod link_check;
+mod duplicates;
+mod outline_compare;
+mod reminders;
+mod auto_done;
+mod relation_matrix;
+mod sync_guard;
+mod design_autosave;
+mod design_reload;
+mod session_summary;
+mod unsaved_prompt;
+mod attachments;
+mod chapter_filter;
+mod sample_project;
+mod project_loading;
+mod history;
+mod snapshot;
+mod project_lock;
+mod windows;
+mod focus;
+mod panel_switch;
+mod name_cleanup;
+mod object_picker;
+mod design_sync;
+mod recovery;
+mod crash;
+mod settings;
 mod panel;
 mod ui_helpers;
 
+pub use text_tool_core::models::*;
+use text_tool_core::changelog::{ChangeCategory, ChangeEntry};
+pub use text_tool_core::file_manager::{FileNode, OpenFile, TreeFilter};
+use text_tool_core::file_manager::find_node_mut;
 pub use models::*;
+pub use error::{AppError, IoOp, LlmErrorKind};
 pub use file_manager::*;
 pub use llm_backend::{LlmBackend, LlmTask, MockBackend, ApiBackend, LocalServerBackend, PromptTemplate};
 pub use agent::{Skill, SkillSet, AgentBackend};
+pub use project_index::ProjectIndex;
+use project_index::{DuplicateEvent, LinkCheckEvent};
+use snapshot::SnapshotEvent;
+use design_sync::{DesignFile, DesignShare};
+use project_loading::{ProjectLoadJob, ProjectLoaded, ProjectLoading};
+use fonts::InstalledFont;
+use outline_compare::CompareColumn;
+use object_picker::ObjectNav;
+use reminders::{ReminderKind, ReminderState};
+use project_prompt::SaveTrigger;
+use unsaved_prompt::{AfterOpen, PaneOpen, UnsavedAction};
+use panel_switch::{DesignDigest, DesignPart};
+use chapter_filter::ObjListFilter;
+pub use crash::install_panic_hook;
+
+/// Depth of the Ctrl+Shift+T reopen stack.
+const MAX_CLOSED_FILES: usize = 20;
+
+/// Project folders shown in the file tree, in order.
+const TREE_DIRS: [&str; 3] = ["Content", "Design", "废稿"];
+
+/// The file tree of the project at `root`: [`TREE_DIRS`] fully listed,
+/// except 废稿, which starts collapsed and lists its subfolders on expand.
+fn build_file_tree(root: &Path, filter: &TreeFilter) -> Vec<FileNode> {
+    TREE_DIRS
+        .iter()
+        .filter_map(|sub| {
+            let mut node = FileNode::from_path_filtered(&root.join(sub), filter)?;
+            if *sub == "废稿" {
+                node.expanded = false;
+            } else {
+                node.load_all(filter);
+            }
+            Some(node)
+        })
+        .collect()
+}
 
 // ── Application state ─────────────────────────────────────────────────────────
 
@@ -63,17 +186,50 @@ pub struct TextToolApp {
     // Project
     pub(super) project_root: Option<PathBuf>,
     pub(super) file_tree: Vec<FileNode>,
+    /// `.gitignore` / `.texttoolignore` rules the file tree was built with.
+    pub(super) ignore_rules: IgnoreRules,
+    /// Set while the project's tree and design data load in the background.
+    pub(super) project_loading: Option<ProjectLoading>,
+    /// Generation of the latest load; results of earlier ones are dropped.
+    pub(super) project_load_generation: u64,
+    pub(super) project_load_tx: Sender<ProjectLoaded>,
+    pub(super) project_load_rx: Receiver<ProjectLoaded>,
 
     // Editors
     pub(super) left_file: Option<OpenFile>,
     pub(super) right_file: Option<OpenFile>,
+    /// Node title of the 写作上下文 scratch buffer in the right pane.
+    pub(super) writing_context_for: Option<String>,
 
-    // Undo stacks (simple: store last content)
-    pub(super) left_undo_stack: VecDeque<String>,
-    pub(super) right_undo_stack: VecDeque<String>,
+    // Undo stacks (whole-content snapshots, capped by `undo_limit`)
+    pub(super) left_undo_stack: UndoState,
+    pub(super) right_undo_stack: UndoState,
 
     // Track which editor pane was last focused for undo
     pub(super) last_focused_left: bool,
+    /// Region F6 last moved keyboard focus to, or that was last clicked.
+    pub(super) focus_region: Option<FocusRegion>,
+    /// Scroll the row ↑ / ↓ just selected into view this frame.
+    pub(super) focus_scroll: bool,
+
+    /// Pending "scroll pane to line" request, consumed by `draw_editors`.
+    pub(super) pane_scroll_request: Option<ScrollRequest>,
+    /// Line briefly highlighted in the left editor after a scroll request.
+    pub(super) left_flash_line: Option<(usize, Instant)>,
+    /// 1-based line currently at the top of the left editor viewport.
+    pub(super) left_top_line: usize,
+    /// Caret and scroll of each editor pane as of the last frame.
+    pub(super) left_view: PaneView,
+    pub(super) right_view: PaneView,
+    /// Remembered position of a file just opened in a pane, applied before
+    /// the pane's first frame.
+    pub(super) left_view_restore: Option<PaneView>,
+    pub(super) right_view_restore: Option<PaneView>,
+    /// `@` completion popup: highlighted row, whether it was shown last frame
+    /// (so Tab is left to it), and the `@` position dismissed with Esc.
+    pub(super) mention_selected: usize,
+    pub(super) mention_popup_open: bool,
+    pub(super) mention_dismissed_at: Option<usize>,
 
     // Status bar message
     pub(super) status: String,
@@ -85,10 +241,28 @@ pub struct TextToolApp {
     pub(super) rename_dialog: Option<RenameDialog>,
     /// Currently selected file path in the navigation tree (used for F2 rename).
     pub(super) selected_file_path: Option<PathBuf>,
+    /// Folder the file tree should scroll to on its next draw (breadcrumb click).
+    pub(super) tree_reveal: Option<PathBuf>,
+    /// Files picked with Ctrl+click in the file tree, in click order.
+    pub(super) multi_selected: Vec<PathBuf>,
 
     // ── World Objects (Panel::Objects) ────────────────────────────────────────
     pub(super) world_objects: Vec<WorldObject>,
-    pub(super) selected_obj_idx: Option<usize>,
+    /// Indices of the selected objects (Ctrl/Shift+click for multi-select).
+    /// The single-object editor is shown only when exactly one is selected.
+    pub(super) selected_objs: BTreeSet<usize>,
+    /// Anchor for Shift+click range selection.
+    pub(super) obj_select_anchor: Option<usize>,
+    /// Kind applied by the batch "修改类型" action.
+    pub(super) batch_obj_kind: ObjectKind,
+    /// Tag input for the batch "添加标签" action.
+    pub(super) batch_tag_input: String,
+    /// Whether the batch-delete confirmation dialog is open.
+    pub(super) batch_delete_confirm: bool,
+    /// Name of the object shown in the 打印视图 window.
+    pub(super) print_sheet: Option<String>,
+    /// Object name clicked elsewhere; opens it in the Objects panel next frame.
+    pub(super) object_nav: Option<ObjectNav>,
     pub(super) new_obj_name: String,
     pub(super) new_obj_kind: ObjectKind,
     /// Input fields for adding a new ObjectLink on the selected object.
@@ -99,6 +273,10 @@ pub struct TextToolApp {
     pub(super) new_link_note: String,
     /// Kind filter shown in the object list side-panel (None = show all).
     pub(super) obj_kind_filter: Option<ObjectKind>,
+    /// 当前章节相关: show only the current chapter's cast in the object list.
+    pub(super) obj_chapter_filter: bool,
+    /// The list filter the object selection was last pruned to.
+    pub(super) obj_filter_applied: Option<ObjListFilter>,
 
     // ── Structure (Panel::Structure) ──────────────────────────────────────────
     pub(super) struct_roots: Vec<StructNode>,
@@ -110,13 +288,23 @@ pub struct TextToolApp {
     pub(super) new_node_link_title: String,
     pub(super) new_node_link_kind: RelationKind,
     pub(super) new_node_link_note: String,
-    /// Name input for linking a WorldObject to the selected StructNode.
-    pub(super) new_node_obj_link: String,
+    /// 常用项 of the Objects and Structure link forms.
+    pub(super) link_usage: LinkUsage,
+    /// Open 选择对象 popup for the selected StructNode's linked objects.
+    pub(super) object_picker: Option<ObjectPicker>,
 
     // ── Outline & Foreshadowing (Panel::Structure – foreshadow sub-section) ───
     pub(super) foreshadows: Vec<Foreshadow>,
     pub(super) selected_fs_idx: Option<usize>,
     pub(super) new_fs_name: String,
+    /// Struct-node title → related foreshadows, for the tree badges.
+    pub(super) fs_index: ForeshadowIndex,
+    /// Show only the foreshadows related to this node title.
+    pub(super) fs_filter: Option<String>,
+    /// Result of the last 检查顺序 run; cleared when the list is reordered.
+    pub(super) fs_warnings: Option<Vec<ForeshadowWarning>>,
+    /// Show the foreshadows as the 伏笔看板 instead of the list.
+    pub(super) fs_board: bool,
 
     // ── Milestones (Panel::Structure – milestone sub-section) ────────────────
     pub(super) milestones: Vec<Milestone>,
@@ -125,6 +313,7 @@ pub struct TextToolApp {
 
     // ── View mode toggles ─────────────────────────────────────────────────────
     pub(super) obj_view_mode: ObjectViewMode,
+    pub(super) obj_editor_tab: ObjectEditorTab,
     pub(super) struct_view_mode: StructViewMode,
     /// Toggle between filesystem and chapter-tree in the Novel panel left sidebar.
     pub(super) file_tree_mode: FileTreeMode,
@@ -137,16 +326,20 @@ pub struct TextToolApp {
     pub(super) llm_backend_idx: usize,
     /// Active non-blocking LLM task (Some while a request is in-flight).
     pub(super) llm_task: Option<LlmTask>,
+    /// Design data saved by the agent's skills, reloaded before the next
+    /// autosave can write over it.
+    pub(super) agent_written_tx: Sender<DesignPart>,
+    pub(super) agent_written_rx: Receiver<DesignPart>,
     /// Character name selected for dialogue-style optimisation.
     pub(super) llm_dialogue_char: String,
 
     // ── Markdown preview ─────────────────────────────────────────────────────
     pub(super) left_preview_mode: bool,
-    pub(super) md_settings: MarkdownSettings,
-    pub(super) show_settings_window: bool,
 
-    // ── Theme ─────────────────────────────────────────────────────────────────
-    pub(super) theme: AppTheme,
+    // ── Settings window ───────────────────────────────────────────────────────
+    pub(super) settings: AppSettings,
+    pub(super) show_settings_window: bool,
+    pub(super) settings_tab: SettingsTab,
 
     // ── Auto-save ─────────────────────────────────────────────────────────────
     /// When the last auto-save ran (None = not yet started this session).
@@ -157,27 +350,266 @@ pub struct TextToolApp {
     // ── Delete confirmation ────────────────────────────────────────────────────
     /// File path pending deletion (move to 废稿) — shown in confirm dialog.
     pub(super) delete_confirm_path: Option<PathBuf>,
+    /// Pane (left = `true`) waiting for 重新载入 to be confirmed.
+    pub(super) reload_confirm: Option<bool>,
 
     // ── Config persistence ────────────────────────────────────────────────────
     pub(super) last_project: Option<PathBuf>,
-    /// Auto-load world objects / struct / foreshadows / milestones from files when opening project.
-    pub(super) auto_load_from_files: bool,
 
     // ── Full-text search ──────────────────────────────────────────────────────
     pub(super) show_search: bool,
     pub(super) search_query: String,
     pub(super) search_results: Vec<SearchResult>,
+    /// Index generation `search_results` were computed from, if any.
+    pub(super) search_generation: Option<u64>,
+
+    // ── Project index ─────────────────────────────────────────────────────────
+    /// Background cache of file contents, word counts and headings.
+    pub(super) project_index: ProjectIndex,
+    /// 待处理标记 of the whole project, from the index.
+    pub(super) project_markers: Vec<(PathBuf, Vec<Marker>)>,
+    /// Index generation and marker tokens `project_markers` were collected with.
+    pub(super) markers_key: Option<(u64, Vec<String>)>,
+    /// 注释 of the whole project, from the index.
+    pub(super) project_annotations: Vec<(PathBuf, Vec<Annotation>)>,
+    /// Index generation `project_annotations` were collected at.
+    pub(super) annotations_generation: Option<u64>,
 
     // ── Structure panel auto-save ─────────────────────────────────────────────
     /// Serialised JSON snapshot of `struct_roots` as of the last save.
     /// Used to detect changes and trigger auto-save without a dirty flag.
     pub(super) struct_json_snapshot: Option<String>,
-
-    // ── Panel-switch tracking (for Structure auto-load) ───────────────────────
+    /// Design file open in a pane while a panel also manages it.
+    pub(super) design_share: Option<DesignShare>,
+    /// Time of the last `design_share` check; `None` forces a re-check.
+    pub(super) design_checked_at: Option<Instant>,
+
+    /// Hover card of the struct tree row under the pointer.
+    pub(super) struct_hover: Option<StructHoverCard>,
+    /// Preview card of the chapter title under the pointer in a design panel.
+    pub(super) chapter_preview: Option<ChapterPreviewCard>,
+    /// Chapter previews by normalized title, refreshed when stale.
+    pub(super) chapter_previews: HashMap<String, ChapterPreview>,
+    /// The structure panel's 统计 window is open.
+    pub(super) show_struct_stats: bool,
+    /// Root node the 计划 vs 实际 chart is limited to; `None` for all.
+    pub(super) burndown_scope: Option<usize>,
+
+    // ── Panel-switch tracking (see `on_panel_transition`) ─────────────────────
     pub(super) last_active_panel: Panel,
+    /// Design data as it was when a design panel was entered; compared on
+    /// leaving it by 切换面板时自动保存.
+    pub(super) design_entry_digest: Option<DesignDigest>,
+    /// Design data as last synced or loaded; the 保存提醒 compares against it.
+    pub(super) design_saved: Option<DesignDigest>,
+    /// Design data as the auto-save last saw it, and when it last changed
+    /// with something left unsaved (see `design_autosave.rs`).
+    pub(super) design_seen: Option<DesignDigest>,
+    pub(super) design_changed_at: Option<Instant>,
+    pub(super) design_reminder: ReminderState,
+    pub(super) pane_reminder: ReminderState,
+    /// When the 保存提醒 last looked at the unsaved state.
+    pub(super) reminder_checked: Option<Instant>,
+    pub(super) reminder_toast: Option<ReminderToast>,
+    /// Index generation and rule the 自动标记完成 last ran with.
+    pub(super) auto_done_checked: Option<(u64, AutoDone)>,
+    /// `file_key`s of nodes whose 进度 was set by hand this session; the
+    /// automatic rule leaves them alone.
+    pub(super) auto_done_manual: BTreeSet<String>,
+    pub(super) auto_done_toast: Option<AutoDoneToast>,
+    /// Titles of the nodes marked done this session, for 本次写作小结.
+    pub(super) session_done: BTreeSet<String>,
+    /// Names of the foreshadows resolved this session.
+    pub(super) session_resolved: BTreeSet<String>,
+    /// Open 本次写作小结 window.
+    pub(super) session_summary: Option<SessionSummary>,
+    /// The summary held back a close request already; the next one quits.
+    pub(super) session_summary_shown: bool,
+    /// Open 未保存的修改 dialog, with the action it holds back.
+    pub(super) unsaved_prompt: Option<UnsavedAction>,
+    /// The dialog let the window close; the next close request passes.
+    pub(super) close_confirmed: bool,
 
     // ── Novel template dialog ─────────────────────────────────────────────────
     pub(super) show_template_dialog: bool,
+    /// Toolbar tour shown after 新建示例项目, until dismissed.
+    pub(super) sample_tour: bool,
+    /// Options of the open 段落格式整理 dialog.
+    pub(super) paragraph_format: Option<FormatOptions>,
+    /// Options of the open 按标题拆分文件 dialog.
+    pub(super) split_dialog: Option<SplitDialog>,
+    /// Open 导入 DOCX dialog.
+    pub(super) docx_import: Option<DocxImportDialog>,
+    /// Open 添加到结构 dialog.
+    pub(super) struct_add_file: Option<StructAddFile>,
+    /// Structure fingerprint and the chapter file → node map built from it.
+    pub(super) file_node_map: (u64, FileNodeMap),
+    /// State of the open 合并为一个文件 dialog.
+    pub(super) merge_dialog: Option<MergeDialog>,
+    /// Preview of the open 从 Content 目录生成结构 dialog.
+    pub(super) content_import: Option<ContentImport>,
+    /// Open 插入章节链接 picker.
+    pub(super) link_picker: Option<LinkPicker>,
+    /// Open 阅读 view of a volume; replaces the panel while set.
+    pub(super) reading: Option<ReadingView>,
+    /// 保存到项目 prompt: data was entered but no project is open.
+    pub(super) show_project_prompt: bool,
+    /// The user chose 稍后; autosaves stop asking for this session.
+    pub(super) project_prompt_dismissed: bool,
+    /// State of the open 导出结构为大纲文档 dialog.
+    pub(super) outline_export: Option<OutlineExport>,
+    /// State of the open 导出关系矩阵 dialog.
+    pub(super) relation_matrix: Option<RelationMatrixDialog>,
+    /// Open 拆分节点 dialog.
+    pub(super) node_split: Option<NodeSplitDialog>,
+    /// Path and title of the node whose title field has focus.
+    pub(super) node_title_before: Option<(Vec<usize>, String)>,
+    /// Open 节点改名 checklist.
+    pub(super) node_rename: Option<NodeRenameDialog>,
+    /// Thumbnails of image attachments in the object editor.
+    pub(super) attachment_thumbs: attachments::ThumbCache,
+    /// Open 移除附件 confirmation.
+    pub(super) attachment_remove: Option<AttachmentRemove>,
+    /// Design saves waiting for 覆盖确认, one per part.
+    pub(super) overwrite_confirm: Vec<OverwriteConfirm>,
+    /// 我确认要覆盖 ticked in the 覆盖确认 dialog.
+    pub(super) overwrite_acknowledged: bool,
+    /// Guarded parts whose file could not be read when the project opened;
+    /// writing them asks first until they load or are saved by hand.
+    pub(super) design_unloaded: Vec<DesignPart>,
+    pub(super) synopsis: Option<SynopsisDialog>,
+    /// Open 管理对象类型 window.
+    pub(super) object_kinds_dialog: Option<ObjectKindsDialog>,
+    /// Fonts of the context; `None` until the first
+    /// [`TextToolApp::install_fonts`].
+    pub(super) installed_font: Option<InstalledFont>,
+    /// Open 变更记录 window.
+    pub(super) change_log: Option<ChangeLogView>,
+    /// Journal entries not yet appended to `changelog.jsonl`.
+    pub(super) change_log_pending: Vec<ChangeEntry>,
+    /// Open 对比左右 view.
+    pub(super) pane_diff: Option<PaneDiff>,
+    /// Open 历史版本 browser.
+    pub(super) history_browser: Option<HistoryBrowser>,
+    /// Open 词频分析 window.
+    pub(super) word_freq: Option<WordFreqView>,
+    /// Open 检查文档内链接 window.
+    pub(super) link_check: Option<LinkCheckView>,
+    /// Open 查找重复段落 window.
+    pub(super) duplicates: Option<DuplicatesView>,
+    /// Open 对比大纲与结构 window.
+    pub(super) outline_compare: Option<OutlineCompareView>,
+    /// Report of the last 规范化名称 run, shown until closed.
+    pub(super) name_cleanup_report: Option<Vec<NameChange>>,
+    /// 未关联对象 toast after saving a chapter.
+    pub(super) link_suggestion: Option<LinkSuggestion>,
+    /// Running or just finished 项目快照.
+    pub(super) snapshot_job: Option<SnapshotJob>,
+    /// Project and day the 定期快照 was last checked for.
+    pub(super) snapshot_checked: Option<(PathBuf, i64)>,
+    /// Claim on the open project's `.texttool/lock`.
+    pub(super) design_lock: Option<DesignLock>,
+    /// Open 载入对方的修改 / 保留本机修改 question (see `design_reload.rs`).
+    pub(super) design_reload: Option<DesignReload>,
+
+    // ── Per-project config (pins, …) ──────────────────────────────────────────
+    pub(super) project_config: ProjectConfig,
+    /// Files replaced in a pane, most recent last, for Ctrl+Shift+T.
+    pub(super) closed_files: Vec<PathBuf>,
+    /// Daily word counts from `Design/写作统计.json`.
+    pub(super) writing_stats: WritingStats,
+
+    // ── Crash recovery ────────────────────────────────────────────────────────
+    /// `.texttool/recovery/manifest.json` of the open project.
+    pub(super) recovery_manifest: RecoveryManifest,
+    /// Hash and length of the last recovery copy of each buffer.
+    pub(super) recovery_copies: HashMap<PathBuf, (u64, usize)>,
+    pub(super) recovery_tick: Option<Instant>,
+    /// Buffers offered by the startup 恢复未保存的修改 dialog.
+    pub(super) recovery_dialog: Option<Vec<RecoveryCandidate>>,
+    /// Error toast after a frame panicked or a file operation failed.
+    pub(super) error_toast: Option<ErrorToast>,
+    /// Crash log of the previous session, shown once at startup.
+    pub(super) crash_notice: Option<PathBuf>,
+
+    // ── Windows ───────────────────────────────────────────────────────────────
+    /// Projects opened by 在新窗口打开项目, each in its own native window.
+    /// Only the main window owns any.
+    pub(super) project_windows: Vec<ProjectWindow>,
+    /// This state lives in an extra window: it neither writes the config
+    /// file nor becomes the project reopened at startup.
+    pub(super) secondary: bool,
+    /// Project picked by 在新窗口打开项目, collected by the main window.
+    pub(super) new_window_request: Option<PathBuf>,
+}
+
+/// An extra native window with its own, fully separate app state.
+pub(super) struct ProjectWindow {
+    pub(super) id: egui::ViewportId,
+    pub(super) app: Box<TextToolApp>,
+    /// The window's widget memory (caret, scroll offsets, …).  egui keeps one
+    /// such map for all viewports, so it is swapped in while the window
+    /// draws.
+    pub(super) data: egui::util::IdTypeMap,
+}
+
+/// This window's claim on the open project's `.texttool/lock`.
+#[derive(Debug)]
+pub(super) struct DesignLock {
+    /// Project the lock belongs to.
+    pub(super) root: PathBuf,
+    /// Another computer's fresh lock: nothing is written to `Design/`.
+    pub(super) held_by: Option<ProjectLock>,
+    /// Last heartbeat or check.
+    pub(super) checked: Instant,
+}
+
+/// Design files saved outside the panels while the panels held unsaved
+/// edits of them; the edits wait until the user picks whose data to keep.
+pub(super) struct DesignReload {
+    pub(super) source: ReloadSource,
+    /// Parts edited here since they were last saved or loaded.
+    pub(super) parts: Vec<DesignPart>,
+}
+
+/// Who saved the design files a [`DesignReload`] asks about.
+pub(super) enum ReloadSource {
+    /// Another computer, by host name, that held the project.
+    Computer(String),
+    /// The agent's write skills.
+    Agent,
+}
+
+#[derive(Debug)]
+pub(super) struct ErrorToast {
+    pub(super) message: String,
+    /// Crash log offered by 查看日志, after a caught panic.
+    pub(super) log: Option<PathBuf>,
+    pub(super) since: Instant,
+}
+
+#[derive(Debug)]
+pub(super) struct LinkSuggestion {
+    /// Leaf of the saved chapter, and its title to detect a changed tree.
+    pub(super) path: Vec<usize>,
+    pub(super) title: String,
+    /// Mentioned objects the node does not link yet.
+    pub(super) names: Vec<String>,
+    pub(super) since: Instant,
+}
+
+#[derive(Debug)]
+pub(super) struct AutoDoneToast {
+    /// Node titles and the state they were given, in tree order.
+    pub(super) changed: Vec<(String, bool)>,
+    pub(super) since: Instant,
+}
+
+#[derive(Debug)]
+pub(super) struct ReminderToast {
+    /// What has stayed unsaved, in the order it came due.
+    pub(super) kinds: Vec<ReminderKind>,
+    pub(super) since: Instant,
 }
 
 #[derive(Debug)]
@@ -186,6 +618,341 @@ pub(super) struct NewFileDialog {
     pub(super) dir: PathBuf,
 }
 
+#[derive(Debug)]
+pub(super) struct SplitDialog {
+    /// Level-1 headings become folders, level-2 headings files.
+    pub(super) folder_per_top: bool,
+    pub(super) overwrite: bool,
+    /// Append matching volume/chapter nodes to the structure.
+    pub(super) build_struct: bool,
+}
+
+/// The open 导入 DOCX dialog: the converted manuscript, before writing.
+#[derive(Debug)]
+pub(super) struct DocxImportDialog {
+    pub(super) source: PathBuf,
+    pub(super) import: text_tool_core::docx::DocxImport,
+    /// File name under `Content/` when not split.
+    pub(super) name: String,
+    /// One file per level-1 heading instead of a single file.
+    pub(super) split: bool,
+    pub(super) overwrite: bool,
+    pub(super) build_struct: bool,
+}
+
+/// The open 添加到结构 dialog for an unlinked chapter file.
+#[derive(Debug)]
+pub(super) struct StructAddFile {
+    /// File relative to `Content/`, `/`-separated.
+    pub(super) rel: String,
+    pub(super) title: String,
+    /// Node to add the chapter under; `None` for the top level.
+    pub(super) parent: Option<Vec<usize>>,
+}
+
+#[derive(Debug)]
+pub(super) struct MergeDialog {
+    /// Source files in merge order.
+    pub(super) files: Vec<PathBuf>,
+    pub(super) dest: PathBuf,
+    /// Move the sources to 废稿/ after a successful merge.
+    pub(super) trash_originals: bool,
+}
+
+#[derive(Debug)]
+pub(super) struct PaneDiff {
+    /// Pane texts the diff was computed from; recomputed when they change.
+    pub(super) left: String,
+    pub(super) right: String,
+    pub(super) diff: TextDiff,
+    /// Index into `diff.hunks` of the change navigated to.
+    pub(super) current: usize,
+    /// Scroll to the current change on the next frame.
+    pub(super) scroll: bool,
+}
+
+/// 历史版本 of one file, compared with its current text.
+pub(super) struct HistoryBrowser {
+    pub(super) file: PathBuf,
+    /// Newest first.
+    pub(super) revisions: Vec<Revision>,
+    /// Index into `revisions`.
+    pub(super) selected: usize,
+    /// Text of the selected revision, or why it could not be read.
+    pub(super) text: Result<String, String>,
+    /// Current text of the file the diff was computed from.
+    pub(super) current: String,
+    pub(super) diff: TextDiff,
+    /// Index into `diff.hunks` of the change navigated to.
+    pub(super) hunk: usize,
+    /// Scroll to the current change on the next frame.
+    pub(super) scroll: bool,
+}
+
+#[derive(Debug)]
+pub(super) struct OutlineExport {
+    pub(super) options: OutlineDocOptions,
+    /// Word counts by lowercase chapter file stem, taken when the dialog opened.
+    pub(super) word_counts: BTreeMap<String, usize>,
+}
+
+pub(super) struct RelationMatrixDialog {
+    /// Names of the objects forming the rows and columns.
+    pub(super) selected: BTreeSet<String>,
+    /// 省略没有关联的行列.
+    pub(super) drop_empty: bool,
+    /// CSV instead of a Markdown table.
+    pub(super) csv: bool,
+}
+
+/// 拆分节点 dialog for the node at `path`.
+pub(super) struct NodeSplitDialog {
+    pub(super) path: Vec<usize>,
+    /// Title of the new sibling.
+    pub(super) title: String,
+    /// Indices of the children moving to the new node.
+    pub(super) moved: BTreeSet<usize>,
+    /// Also cut the chapter file at `heading_line`.
+    pub(super) split_file: bool,
+    /// The node's chapter file, if found.
+    pub(super) file: Option<PathBuf>,
+    /// Headings of `file` after the first: 1-based line and title.
+    pub(super) headings: Vec<(usize, String)>,
+    pub(super) heading_line: Option<usize>,
+}
+
+/// 节点改名 checklist for the node at `path`.
+pub(super) struct NodeRenameDialog {
+    pub(super) path: Vec<usize>,
+    pub(super) plan: NodeRenamePlan,
+    /// Checked items: the file rename, each link, each foreshadow.
+    pub(super) file: bool,
+    pub(super) links: Vec<bool>,
+    pub(super) foreshadows: Vec<bool>,
+    /// Once applied: one line per item and whether it worked.
+    pub(super) results: Option<Vec<(String, Result<(), String>)>>,
+}
+
+/// Attachment `rel` of `object` about to be removed.
+pub(super) struct AttachmentRemove {
+    pub(super) object: String,
+    pub(super) rel: String,
+    /// Also delete the copy under `Design/attachments/`.
+    pub(super) delete_file: bool,
+}
+
+/// A save of `part` held back because the panel has far fewer entries than
+/// the file, or the file never loaded (see `sync_guard.rs`).
+pub(super) struct OverwriteConfirm {
+    pub(super) part: DesignPart,
+    /// The file could not be read when the project opened.
+    pub(super) unloaded: bool,
+    /// Top-level entries in the file.
+    pub(super) existing: usize,
+    /// Entries in the panel.
+    pub(super) new_len: usize,
+}
+
+/// 生成梗概 window for one structure node.
+pub(super) struct SynopsisDialog {
+    pub(super) title: String,
+    /// The node, cloned when the window opened.
+    pub(super) node: StructNode,
+    pub(super) options: SynopsisOptions,
+    /// The synopsis as edited in the window.
+    pub(super) text: String,
+    /// Running 压缩 request.
+    pub(super) task: Option<LlmTask>,
+}
+
+/// 管理对象类型 window: the form for a new custom kind.
+pub(super) struct ObjectKindsDialog {
+    pub(super) name: String,
+    pub(super) icon: String,
+    pub(super) color: [u8; 3],
+    /// Icons or colors were edited; saved when the window closes.
+    pub(super) dirty: bool,
+}
+
+/// 变更记录 window.
+pub(super) struct ChangeLogView {
+    /// The journal as read when the window opened, plus entries since.
+    pub(super) entries: Vec<ChangeEntry>,
+    /// `YYYY-MM-DD` bounds, inclusive; `None` for open-ended.
+    pub(super) from: Option<String>,
+    pub(super) to: Option<String>,
+    /// Only entries of this category, or all.
+    pub(super) category: Option<ChangeCategory>,
+}
+
+/// Columns the 词频分析 table sorts by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum WordFreqSort {
+    Phrase,
+    Count,
+    Kind,
+}
+
+#[derive(Debug)]
+pub(super) struct WordFreqView {
+    /// Whole project (`Content/`) rather than the left pane.
+    pub(super) project: bool,
+    /// Documents of the report, indexed by `FreqItem::first`.
+    pub(super) files: Vec<PathBuf>,
+    pub(super) report: Option<FreqReport>,
+    /// Project-wide analysis still running on the background index.
+    pub(super) pending: Option<std::sync::mpsc::Receiver<(Vec<PathBuf>, FreqReport)>>,
+    pub(super) sort: WordFreqSort,
+    pub(super) descending: bool,
+}
+
+#[derive(Debug)]
+pub(super) struct LinkCheckView {
+    /// Check still running on the background index.
+    pub(super) pending: Option<std::sync::mpsc::Receiver<LinkCheckEvent>>,
+    /// Files checked and total, while pending.
+    pub(super) progress: (usize, usize),
+    pub(super) broken: Vec<BrokenLink>,
+    /// Markdown files under `Content/` with their headings, offered by 修正为….
+    pub(super) targets: Vec<(PathBuf, Vec<OutlineEntry>)>,
+    /// Filter of the 修正为… file list.
+    pub(super) filter: String,
+}
+
+#[derive(Debug)]
+pub(super) struct DuplicatesView {
+    /// Scan still running on the background index.
+    pub(super) pending: Option<std::sync::mpsc::Receiver<DuplicateEvent>>,
+    /// Files scanned and total, while pending.
+    pub(super) progress: (usize, usize),
+    pub(super) groups: Vec<DuplicateGroup>,
+    /// Shortest paragraph reported, in 字; applies from the next run.
+    pub(super) min_chars: usize,
+}
+
+#[derive(Debug)]
+pub(super) struct OutlineCompareView {
+    /// The planning document, compared as it stands in its pane.
+    pub(super) path: PathBuf,
+    /// Rows hidden by 忽略: column and normalized title.
+    pub(super) ignored: BTreeSet<(CompareColumn, String)>,
+}
+
+#[derive(Debug)]
+pub(super) struct SnapshotJob {
+    /// Zip still being written on a background thread.
+    pub(super) pending: Option<std::sync::mpsc::Receiver<SnapshotEvent>>,
+    /// Entries written and total, while pending.
+    pub(super) progress: (usize, usize),
+    pub(super) dest: PathBuf,
+    /// A 定期快照 rather than one the user asked for.
+    pub(super) daily: bool,
+    /// No toast: a 定期快照, or the user closed it.
+    pub(super) hidden: bool,
+    /// When the zip was finished, and its size.
+    pub(super) done: Option<(Instant, u64)>,
+}
+
+#[derive(Debug)]
+pub(super) struct ContentImport {
+    /// Structure mapped from the `Content/` listing.
+    pub(super) nodes: Vec<StructNode>,
+    /// Word count per file, keyed like [`StructNode::file`].
+    pub(super) words: HashMap<String, usize>,
+}
+
+#[derive(Debug)]
+pub(super) struct LinkPicker {
+    pub(super) query: String,
+    pub(super) choices: Vec<LinkChoice>,
+}
+
+#[derive(Debug)]
+pub(super) struct ObjectPicker {
+    /// Node whose linked objects the picker adds to.
+    pub(super) path: Vec<usize>,
+    pub(super) query: String,
+    /// Checked object names, in click order.
+    pub(super) checked: Vec<String>,
+}
+
+/// One link target of the picker: a file (linked at its first heading) or a
+/// struct node resolved to a heading or file of the same title.
+#[derive(Debug, Clone)]
+pub(super) struct LinkChoice {
+    /// Listed in the picker and used as link text.
+    pub(super) title: String,
+    pub(super) path: PathBuf,
+    pub(super) anchor: Option<String>,
+    pub(super) from_node: bool,
+}
+
+#[derive(Debug)]
+pub(super) struct ReadingView {
+    /// Title of the volume, the key of its saved position.
+    pub(super) volume: String,
+    pub(super) book: ReadingBook,
+    pub(super) page: usize,
+    /// Scroll fraction within the page, `0.0..=1.0`.
+    pub(super) scroll: f32,
+    /// Scroll fraction to jump to once the page has been laid out.
+    pub(super) pending: Option<f32>,
+    /// Page whose scroll range `max_scroll` was measured on.
+    pub(super) measured: Option<usize>,
+    pub(super) max_scroll: f32,
+    /// Panel the view was opened from; Esc returns to it, switching to
+    /// another panel closes the view.
+    pub(super) from: Panel,
+}
+
+/// Hover card of a struct tree row (see `draw_struct_hover_card`).
+#[derive(Debug)]
+pub(super) struct StructHoverCard {
+    pub(super) path: Vec<usize>,
+    /// Screen rect of the row the card belongs to.
+    pub(super) row: egui::Rect,
+    /// When the pointer arrived on the row.
+    pub(super) since: Instant,
+    pub(super) open: bool,
+    /// Summary being edited in the card.
+    pub(super) edit: Option<String>,
+}
+
+/// Hover card previewing a chapter named in a design panel (see
+/// `draw_chapter_preview`).
+#[derive(Debug)]
+pub(super) struct ChapterPreviewCard {
+    pub(super) title: String,
+    /// Screen rect of the hovered title.
+    pub(super) anchor: egui::Rect,
+    /// When the pointer arrived on the title.
+    pub(super) since: Instant,
+    pub(super) open: bool,
+}
+
+/// Cached start of a chapter's text for [`ChapterPreviewCard`].
+#[derive(Debug)]
+pub(super) struct ChapterPreview {
+    /// When the entry was built; it expires after a few seconds.
+    pub(super) fetched: Instant,
+    /// [`ProjectIndex::generation`] the entry was built from.
+    pub(super) generation: u64,
+    /// The chapter's file; `None` when no file matches.
+    pub(super) file: Option<PathBuf>,
+    pub(super) text: String,
+    /// The file has more lines than `text`.
+    pub(super) truncated: bool,
+}
+
+/// Unsaved buffer found in `.texttool/recovery/` on startup.
+#[derive(Debug)]
+pub(super) struct RecoveryCandidate {
+    pub(super) path: PathBuf,
+    pub(super) content: String,
+    /// Unix seconds of the copy.
+    pub(super) saved_at: i64,
+}
+
 #[derive(Debug)]
 pub(super) struct RenameDialog {
     pub(super) path: PathBuf,
@@ -194,31 +961,103 @@ pub(super) struct RenameDialog {
 
 impl TextToolApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        // Load Chinese font
-        let mut fonts = egui::FontDefinitions::default();
-        fonts.font_data.insert(
-            "chinese".to_owned(),
-            egui::FontData::from_static(include_bytes!("../../assets/NotoSansCJKsc-Regular.otf")),
-        );
-        fonts.families.get_mut(&egui::FontFamily::Proportional).unwrap().insert(0, "chinese".to_owned());
-        fonts.families.get_mut(&egui::FontFamily::Monospace).unwrap().insert(0, "chinese".to_owned());
-        cc.egui_ctx.set_fonts(fonts);
+        let mut app = Self::blank();
+        app.crash_notice = Self::take_previous_crash();
+
+        // Apply saved configuration (LLM settings, MD settings, last session).
+        if let Some(cfg) = Self::load_config() {
+            app.llm_config = cfg.llm_config;
+            app.settings = cfg.settings;
+            if app.settings.behavior.restore_session {
+                if let Some(p) = cfg.last_project {
+                    let pb = PathBuf::from(p);
+                    if pb.is_dir() {
+                        app.last_project = Some(pb.clone());
+                        app.open_project(pb);
+                    }
+                }
+                app.restore_session(&cfg.last_session);
+            }
+        }
+        // Fonts belong to the context, which every window shares.
+        app.install_fonts(&cc.egui_ctx);
+
+        app
+    }
 
-        let mut app = TextToolApp {
+    /// Reopen the files and panel of `session`, skipping files that no
+    /// longer exist.
+    fn restore_session(&mut self, session: &LastSession) {
+        let files = [(&session.left_file, true), (&session.right_file, false)];
+        for (path, left) in files {
+            let Some(path) = path.as_deref().map(Path::new).filter(|p| p.is_file()) else { continue };
+            if matches!(self.open_file_in_pane(path, left), Ok(PaneOpen::Opened(_))) && left {
+                self.left_preview_mode = session.left_preview;
+            }
+        }
+        if let Some(panel) = Panel::from_key(&session.panel) {
+            self.active_panel = panel;
+        }
+    }
+
+    /// The files and panel of this window, for [`restore_session`](Self::restore_session).
+    fn session_now(&self) -> LastSession {
+        let path = |f: &Option<OpenFile>| {
+            f.as_ref().filter(|f| !f.scratch).map(|f| f.path.to_string_lossy().into_owned())
+        };
+        LastSession {
+            left_file: path(&self.left_file),
+            right_file: path(&self.right_file),
+            panel: self.active_panel.key().to_owned(),
+            left_preview: self.left_preview_mode,
+        }
+    }
+
+    /// State of a window with no project and default settings.
+    fn blank() -> Self {
+        let (project_load_tx, project_load_rx) = std::sync::mpsc::channel();
+        let (agent_written_tx, agent_written_rx) = std::sync::mpsc::channel();
+        TextToolApp {
             active_panel: Panel::Novel,
             project_root: None,
             file_tree: vec![],
+            ignore_rules: IgnoreRules::default(),
+            project_loading: None,
+            project_load_generation: 0,
+            project_load_tx,
+            project_load_rx,
             left_file: None,
             right_file: None,
-            left_undo_stack: VecDeque::new(),
-            right_undo_stack: VecDeque::new(),
+            writing_context_for: None,
+            left_undo_stack: UndoState::new(),
+            right_undo_stack: UndoState::new(),
             last_focused_left: true,
+            focus_region: None,
+            focus_scroll: false,
+            pane_scroll_request: None,
+            left_flash_line: None,
+            left_top_line: 1,
+            left_view: PaneView::default(),
+            right_view: PaneView::default(),
+            left_view_restore: None,
+            right_view_restore: None,
+            mention_selected: 0,
+            mention_popup_open: false,
+            mention_dismissed_at: None,
             status: "欢迎使用清墨".to_owned(),
             new_file_dialog: None,
             rename_dialog: None,
             selected_file_path: None,
+            tree_reveal: None,
+            multi_selected: vec![],
             world_objects: vec![],
-            selected_obj_idx: None,
+            selected_objs: BTreeSet::new(),
+            obj_select_anchor: None,
+            batch_obj_kind: ObjectKind::Character,
+            batch_tag_input: String::new(),
+            batch_delete_confirm: false,
+            print_sheet: None,
+            object_nav: None,
             new_obj_name: String::new(),
             new_obj_kind: ObjectKind::Character,
             new_link_name: String::new(),
@@ -226,6 +1065,8 @@ impl TextToolApp {
             new_link_is_node: false,
             new_link_note: String::new(),
             obj_kind_filter: None,
+            obj_chapter_filter: false,
+            obj_filter_applied: None,
             struct_roots: vec![],
             selected_node_path: vec![],
             new_node_title: String::new(),
@@ -233,10 +1074,15 @@ impl TextToolApp {
             new_node_link_title: String::new(),
             new_node_link_kind: RelationKind::Foreshadows,
             new_node_link_note: String::new(),
-            new_node_obj_link: String::new(),
+            link_usage: LinkUsage::default(),
+            object_picker: None,
             foreshadows: vec![],
             selected_fs_idx: None,
             new_fs_name: String::new(),
+            fs_index: ForeshadowIndex::default(),
+            fs_filter: None,
+            fs_warnings: None,
+            fs_board: false,
             milestones: vec![
                 Milestone::new("完成 VS Code 风格 UI 复刻"),
                 Milestone::new("实现本地 MD/JSON 文件操作"),
@@ -249,121 +1095,405 @@ impl TextToolApp {
             selected_ms_idx: None,
             new_ms_name: String::new(),
             obj_view_mode: ObjectViewMode::List,
+            obj_editor_tab: ObjectEditorTab::Details,
             struct_view_mode: StructViewMode::Tree,
             file_tree_mode: FileTreeMode::Chapters,
-            llm_config: LlmConfig {
-                model_path: String::new(),
-                api_url: "http://localhost:11434/api/generate".to_owned(),
-                temperature: 0.7,
-                max_tokens: 512,
-                use_local: true,
-                system_prompt: String::new(),
-            },
+            llm_config: LlmConfig::default(),
             llm_prompt: String::new(),
             llm_output: String::new(),
             llm_backend_idx: 0,
             llm_task: None,
+            agent_written_tx,
+            agent_written_rx,
             llm_dialogue_char: String::new(),
             left_preview_mode: false,
-            md_settings: MarkdownSettings::default(),
+            settings: AppSettings::default(),
             show_settings_window: false,
-            theme: AppTheme::Dark,
+            settings_tab: SettingsTab::Editor,
             last_auto_save: None,
             last_auto_save_label: String::new(),
             delete_confirm_path: None,
+            reload_confirm: None,
             last_project: None,
-            auto_load_from_files: false,
             show_search: false,
             search_query: String::new(),
             search_results: vec![],
+            search_generation: None,
+            project_index: ProjectIndex::new(),
+            project_markers: Vec::new(),
+            markers_key: None,
+            project_annotations: Vec::new(),
+            annotations_generation: None,
             struct_json_snapshot: None,
+            design_share: None,
+            struct_hover: None,
+            chapter_preview: None,
+            chapter_previews: HashMap::new(),
+            show_struct_stats: false,
+            burndown_scope: None,
+            design_checked_at: None,
             last_active_panel: Panel::Novel,
+            design_entry_digest: None,
+            design_saved: None,
+            design_seen: None,
+            design_changed_at: None,
+            design_reminder: ReminderState::default(),
+            pane_reminder: ReminderState::default(),
+            reminder_checked: None,
+            reminder_toast: None,
+            auto_done_checked: None,
+            auto_done_manual: BTreeSet::new(),
+            auto_done_toast: None,
+            session_done: BTreeSet::new(),
+            session_resolved: BTreeSet::new(),
+            session_summary: None,
+            session_summary_shown: false,
+            unsaved_prompt: None,
+            close_confirmed: false,
             show_template_dialog: false,
-        };
-
-        // Apply saved configuration (LLM settings, MD settings, last project).
-        if let Some(cfg) = Self::load_config() {
-            app.llm_config = cfg.llm_config;
-            app.md_settings = cfg.md_settings;
-            app.auto_load_from_files = cfg.auto_load;
-            app.theme = cfg.theme;
-            if let Some(p) = cfg.last_project {
-                let pb = PathBuf::from(p);
-                if pb.is_dir() {
-                    app.last_project = Some(pb.clone());
-                    app.open_project(pb);
-                }
-            }
+            sample_tour: false,
+            paragraph_format: None,
+            split_dialog: None,
+            docx_import: None,
+            struct_add_file: None,
+            file_node_map: (0, FileNodeMap::default()),
+            merge_dialog: None,
+            content_import: None,
+            link_picker: None,
+            reading: None,
+            show_project_prompt: false,
+            project_prompt_dismissed: false,
+            outline_export: None,
+            relation_matrix: None,
+            node_split: None,
+            node_title_before: None,
+            node_rename: None,
+            attachment_thumbs: HashMap::new(),
+            attachment_remove: None,
+            overwrite_confirm: Vec::new(),
+            overwrite_acknowledged: false,
+            design_unloaded: Vec::new(),
+            synopsis: None,
+            object_kinds_dialog: None,
+            installed_font: None,
+            change_log: None,
+            change_log_pending: Vec::new(),
+            pane_diff: None,
+            history_browser: None,
+            word_freq: None,
+            link_check: None,
+            duplicates: None,
+            outline_compare: None,
+            name_cleanup_report: None,
+            link_suggestion: None,
+            snapshot_job: None,
+            snapshot_checked: None,
+            design_lock: None,
+            design_reload: None,
+            project_config: ProjectConfig::default(),
+            closed_files: Vec::new(),
+            writing_stats: WritingStats::default(),
+            recovery_manifest: RecoveryManifest::default(),
+            recovery_copies: HashMap::new(),
+            recovery_tick: None,
+            recovery_dialog: None,
+            error_toast: None,
+            crash_notice: None,
+            project_windows: Vec::new(),
+            secondary: false,
+            new_window_request: None,
         }
-
-        app
     }
 
     // ── Project operations ────────────────────────────────────────────────────
 
+    /// Open `path` as the project.  The file tree and the design data load
+    /// on a worker thread (see `project_loading.rs`); the window shows a
+    /// spinner until they arrive.
     pub(super) fn open_project(&mut self, path: PathBuf) {
+        let job = self.begin_open_project(path);
+        let tx = self.project_load_tx.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(job.run());
+        });
+    }
+
+    /// [`open_project`](Self::open_project) on the calling thread, for
+    /// callers that work with the project's data right away.
+    pub(super) fn open_project_now(&mut self, path: PathBuf) {
+        let job = self.begin_open_project(path);
+        self.install_project_load(job.run());
+    }
+
+    /// The quick part of opening `path`; returns the loading still to do.
+    fn begin_open_project(&mut self, path: PathBuf) -> ProjectLoadJob {
         // Ensure required subdirectories exist
-        for sub in &["Content", "Design", "废稿"] {
+        for sub in TREE_DIRS {
             let _ = std::fs::create_dir_all(path.join(sub));
         }
+        self.close_reading();
+        self.flush_design_autosave();
+        self.flush_change_log();
+        self.change_log = None;
+        // Another project's design data never stays behind, not even when
+        // this one's files turn out unreadable.
+        if self.project_root.as_ref() != Some(&path) {
+            self.clear_design();
+        }
+        self.design_unloaded.clear();
+        self.release_project_lock();
         self.project_root = Some(path.clone());
-        self.last_project = Some(path.clone());
-        self.refresh_tree();
-        self.status = format!("已打开项目: {}", path.display());
-        self.save_config();
-        if self.auto_load_from_files {
-            self.load_all_from_files();
+        self.acquire_project_lock(false);
+        if !self.secondary {
+            self.last_project = Some(path.clone());
+            crash::set_crash_dir(Some(&path));
         }
+        self.load_project_config();
+        self.load_writing_stats();
+        self.load_recovery();
+        self.file_tree.clear();
+        self.status = format!("正在打开项目: {}", path.display());
+        self.save_config();
+        self.auto_done_manual.clear();
+        self.session_done.clear();
+        self.session_resolved.clear();
+        self.attachment_thumbs.clear();
+        self.start_project_load(path)
     }
 
+    /// Rebuild the file tree, or re-list only the folders that changed when
+    /// the tree already belongs to the open project and its ignore rules are
+    /// unchanged.  废稿 starts collapsed and its subfolders load when expanded.
     pub(super) fn refresh_tree(&mut self) {
-        let hide_json = self.md_settings.hide_json;
         if let Some(root) = &self.project_root {
-            self.file_tree = ["Content", "Design", "废稿"]
-                .iter()
-                .filter_map(|sub| {
-                    let p = root.join(sub);
-                    FileNode::from_path_filtered(&p, hide_json)
-                })
-                .collect();
+            let rules = IgnoreRules::load(root);
+            let same_tree = self.file_tree.len() == TREE_DIRS.len()
+                && self.file_tree.iter().zip(TREE_DIRS).all(|(n, d)| n.path == root.join(d))
+                && rules == self.ignore_rules;
+            let root = root.clone();
+            self.ignore_rules = rules;
+            let filter = self.tree_filter();
+            if same_tree {
+                for node in &mut self.file_tree {
+                    node.refresh(&filter);
+                }
+            } else {
+                self.file_tree = build_file_tree(&root, &filter);
+            }
+        }
+        self.reindex_project();
+    }
+
+    /// What the file tree leaves out under the current settings.
+    pub(super) fn tree_filter(&self) -> TreeFilter {
+        TreeFilter {
+            hide_json: self.settings.markdown.hide_json,
+            ignore: self.ignore_rules.clone(),
+            show_ignored: self.settings.markdown.show_ignored,
+        }
+    }
+
+    /// Install folder listings finished in the background.
+    pub(super) fn apply_dir_listings(&mut self) {
+        for (dir, children, mtime) in self.project_index.take_dir_listings() {
+            if let Some(node) = find_node_mut(&mut self.file_tree, &dir) {
+                node.set_children(children, mtime);
+            }
+        }
+    }
+
+    /// Queue a background re-index of the open project.
+    pub(super) fn reindex_project(&self) {
+        if let Some(root) = &self.project_root {
+            self.project_index.spawn_rescan(root.clone());
         }
     }
 
     // ── File operations ───────────────────────────────────────────────────────
 
-    pub(super) fn open_file_in_pane(&mut self, path: &Path, left: bool) {
-        match std::fs::read_to_string(path) {
-            Ok(content) => {
-                let f = OpenFile::new(path.to_owned(), content);
-                if left {
-                    // Apply the default preview setting for Markdown files
-                    self.left_preview_mode = f.is_markdown() && self.md_settings.default_to_preview;
-                    self.left_file = Some(f);
-                    self.left_undo_stack.clear();
-                } else {
-                    self.right_file = Some(f);
-                    self.right_undo_stack.clear();
-                }
-                self.status = format!("已打开: {}", path.display());
+    /// Open `path` into a pane, unless the pane's unsaved text has to be
+    /// asked about first (see `unsaved_prompt.rs`).
+    pub(super) fn open_file_in_pane(&mut self, path: &Path, left: bool) -> Result<PaneOpen, AppError> {
+        self.open_file_with(path, left, None)
+    }
+
+    /// [`open_file_in_pane`](Self::open_file_in_pane), then `then`: now,
+    /// or after the question about the pane's unsaved text.
+    pub(super) fn open_file_then(&mut self, path: &Path, left: bool, then: AfterOpen) -> Result<PaneOpen, AppError> {
+        self.open_file_with(path, left, Some(then))
+    }
+
+    /// Show line `line_no` of `path` in the left pane, opening the file
+    /// there first unless it already is.
+    pub(super) fn open_at_line(&mut self, path: &Path, line_no: usize) {
+        if self.left_file.as_ref().is_some_and(|f| f.path == path) {
+            self.request_scroll_to_line(true, line_no);
+        } else {
+            let r = self.open_file_then(path, true, AfterOpen::ScrollTo(line_no));
+            self.notify_open(r);
+        }
+    }
+
+    /// [`open_file_in_pane`](Self::open_file_in_pane) without asking about
+    /// the pane's unsaved text.
+    pub(super) fn load_file_into_pane(&mut self, path: &Path, left: bool) -> Result<String, AppError> {
+        let content = std::fs::read_to_string(path).map_err(AppError::io(IoOp::Read, path))?;
+        self.remember_editor_position(left);
+        let view = self.project_relative(path)
+            .and_then(|rel| self.project_config.editor_position(&rel, content.chars().count()))
+            .map(|p| PaneView { caret: p.caret, scroll: p.scroll });
+        let f = OpenFile::new(path.to_owned(), content);
+        let previous = if left { &self.left_file } else { &self.right_file };
+        if let Some(prev) = previous.as_ref().filter(|p| p.path != path && !p.scratch) {
+            self.note_closed_file(prev.path.clone());
+        }
+        if left {
+            self.left_view = view.unwrap_or_default();
+            self.left_view_restore = view;
+        } else {
+            self.right_view = view.unwrap_or_default();
+            self.right_view_restore = view;
+        }
+        if left {
+            // Apply the default preview setting for Markdown files
+            self.left_preview_mode = f.is_markdown() && self.settings.markdown.default_to_preview;
+            self.left_file = Some(f);
+            self.left_undo_stack.clear();
+            self.left_top_line = 1;
+            self.left_flash_line = None;
+        } else {
+            self.right_file = Some(f);
+            self.right_undo_stack.clear();
+        }
+        self.note_recent_file(path);
+        Ok(format!("已打开: {}", path.display()))
+    }
+
+    /// Remember `path` for Ctrl+Shift+T after it left a pane.
+    fn note_closed_file(&mut self, path: PathBuf) {
+        self.closed_files.retain(|p| *p != path);
+        self.closed_files.push(path);
+        if self.closed_files.len() > MAX_CLOSED_FILES {
+            self.closed_files.remove(0);
+        }
+    }
+
+    /// Close the (saved) file of the right pane, hiding the pane.
+    pub(super) fn close_right_file(&mut self) {
+        if let Some(f) = self.right_file.take() {
+            if !f.scratch {
+                self.note_closed_file(f.path);
             }
-            Err(e) => self.status = format!("打开失败: {e}"),
+            self.right_undo_stack.clear();
+            self.last_focused_left = true;
         }
     }
 
-    pub(super) fn save_left(&mut self) {
-        if let Some(f) = &mut self.left_file {
-            match f.save() {
-                Ok(_) => self.status = format!("已保存: {}", f.path.display()),
-                Err(e) => self.status = format!("保存失败: {e}"),
+    /// Default pane for `path` under the pane roles set in 设置 → 行为.
+    pub(super) fn opens_in_left(&self, path: &Path) -> bool {
+        let behavior = &self.settings.behavior;
+        route_to_pane(path, behavior.left_pane, behavior.right_pane, self.last_focused_left) == PaneTarget::Left
+    }
+
+    /// Ctrl+Shift+T: reopen the file most recently replaced in a pane.
+    pub(super) fn reopen_closed_file(&mut self) {
+        while let Some(path) = self.closed_files.pop() {
+            if path.is_file() {
+                let r = self.open_file_in_pane(&path, self.opens_in_left(&path));
+                self.notify_open(r);
+                self.active_panel = Panel::Novel;
+                return;
             }
         }
+        self.status = "没有可重新打开的文件".to_owned();
+    }
+
+    /// Ask an editor pane to scroll to `line_no` (1-based) on the next frame
+    /// and flash that line.  Used by the heading outline, search results, etc.
+    pub(super) fn request_scroll_to_line(&mut self, left: bool, line_no: usize) {
+        if left {
+            // Line positions only exist in the plain-text editor.
+            self.left_preview_mode = false;
+        }
+        self.pane_scroll_request = Some(ScrollRequest { left, line_no });
+    }
+
+    pub(super) fn save_left(&mut self) {
+        if let Err(e) = self.save_pane(true) {
+            self.report(e);
+        }
     }
 
     pub(super) fn save_right(&mut self) {
-        if let Some(f) = &mut self.right_file {
-            match f.save() {
-                Ok(_) => self.status = format!("已保存: {}", f.path.display()),
-                Err(e) => self.status = format!("保存失败: {e}"),
+        if let Err(e) = self.save_pane(false) {
+            self.report(e);
+        }
+    }
+
+    /// Write the left or right pane to its file, then do the bookkeeping
+    /// of a save (recovery draft, history, word count, index).
+    fn save_pane(&mut self, left: bool) -> Result<(), AppError> {
+        let file = if left { &self.left_file } else { &self.right_file };
+        let Some(f) = file.as_ref() else {
+            return Err(AppError::Validation("没有打开的文件".to_owned()));
+        };
+        if f.scratch {
+            return Err(AppError::Validation(format!("「{}」是临时文档，不会保存", f.path.display())));
+        }
+        let delta = self.unsaved_word_delta(f);
+        let f = if left { self.left_file.as_mut() } else { self.right_file.as_mut() }.expect("pane checked above");
+        f.save().map_err(AppError::io(IoOp::Write, &f.path))?;
+        let (path, text) = (f.path.clone(), f.content.clone());
+        self.status = format!("已保存: {}", path.display());
+
+        self.record_words_written(delta);
+        self.discard_recovery(&path);
+        self.record_revision(&path, &text);
+        self.remember_editor_position(left);
+        self.note_recent_file(&path);
+        self.suggest_links_after_save(&path, &text);
+        self.update_index_after_save(&path);
+        self.reindex_project();
+        Ok(())
+    }
+
+    /// 重新载入当前文件: reload the pane at once if it is unmodified,
+    /// otherwise ask first.
+    pub(super) fn request_reload(&mut self, left: bool) {
+        match if left { &self.left_file } else { &self.right_file } {
+            None => self.status = "没有打开的文件".to_owned(),
+            Some(f) if f.modified => self.reload_confirm = Some(left),
+            Some(_) => self.reload_pane(left),
+        }
+    }
+
+    /// Replace the pane's buffer with the file on disk.  The discarded text
+    /// goes onto the undo stack; a missing file keeps the buffer.
+    pub(super) fn reload_pane(&mut self, left: bool) {
+        let (pane, undo) = if left {
+            (&mut self.left_file, &mut self.left_undo_stack)
+        } else {
+            (&mut self.right_file, &mut self.right_undo_stack)
+        };
+        let Some(f) = pane else { return };
+        match f.reload() {
+            Ok(discarded) => {
+                if discarded != f.content {
+                    undo.push(discarded, self.settings.behavior.undo_limit);
+                }
+                let path = f.path.clone();
+                self.status = format!("已重新载入: {}", path.display());
+                self.discard_recovery(&path);
+            }
+            Err(e) => {
+                let name = f.path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                let message = if e.kind() == std::io::ErrorKind::NotFound {
+                    format!("「{name}」已不在磁盘上，保留当前内容")
+                } else {
+                    format!("重新载入「{name}」失败: {e}")
+                };
+                self.status = message.clone();
+                self.show_error_toast(message);
             }
         }
     }
@@ -375,15 +1505,11 @@ impl TextToolApp {
         });
     }
 
-    pub(super) fn create_file(&mut self, path: PathBuf) {
-        if let Err(e) = std::fs::write(&path, "") {
-            self.status = format!("创建失败: {e}");
-        } else {
-            self.refresh_tree();
-            let open_in_left = !path.extension().and_then(|e| e.to_str()).eq(&Some("json"));
-            self.open_file_in_pane(&path, open_in_left);
-            self.status = format!("已创建: {}", path.display());
-        }
+    pub(super) fn create_file(&mut self, path: PathBuf) -> Result<String, AppError> {
+        text_tool_core::safe_write(&path, "").map_err(AppError::io(IoOp::Create, &path))?;
+        self.refresh_tree();
+        self.open_file_in_pane(&path, self.opens_in_left(&path))?;
+        Ok(format!("已创建: {}", path.display()))
     }
 
     // ── Structured context builders (used by LLM panel) ──────────────────────
@@ -398,7 +1524,7 @@ impl TextToolApp {
         char_name: &str,
         dialogue_text: &str,
     ) -> Option<String> {
-        let obj = self.world_objects.iter().find(|o| o.name == char_name)?;
+        let obj = self.world_objects.iter().find(|o| same_title(&o.name, char_name))?;
 
         let mut ctx = format!("## 人物：{} ({})\n", obj.name, obj.kind.label());
         if !obj.description.is_empty() {
@@ -470,20 +1596,42 @@ impl TextToolApp {
                 self.status = format!("重命名失败: {e}");
                 return;
             }
-            // Update open file references if needed
-            if let Some(f) = &mut self.left_file {
-                if f.path == old_path { f.path = new_path.clone(); }
-            }
-            if let Some(f) = &mut self.right_file {
-                if f.path == old_path { f.path = new_path.clone(); }
-            }
-            if self.selected_file_path.as_deref() == Some(old_path) {
-                self.selected_file_path = Some(new_path);
-            }
+            self.note_path_renamed(old_path, &new_path);
             self.refresh_tree();
             self.status = format!("已重命名: {}", new_name);
         }
-    }    /// Move `path` into the project's `废稿/` folder.
+    }
+
+    /// Point open panes, the file-tree selection, recent and pinned files
+    /// at `new_path` after `old_path` was renamed on disk.
+    pub(super) fn note_path_renamed(&mut self, old_path: &Path, new_path: &Path) {
+        if let Some(f) = &mut self.left_file {
+            if f.path == old_path { f.path = new_path.to_path_buf(); }
+        }
+        if let Some(f) = &mut self.right_file {
+            if f.path == old_path { f.path = new_path.to_path_buf(); }
+        }
+        if self.selected_file_path.as_deref() == Some(old_path) {
+            self.selected_file_path = Some(new_path.to_path_buf());
+        }
+        if let Some(p) = self.multi_selected.iter_mut().find(|p| p.as_path() == old_path) {
+            *p = new_path.to_path_buf();
+        }
+        if let (Some(old_rel), Some(new_rel)) =
+            (self.project_relative(old_path), self.project_relative(new_path))
+        {
+            if let Some(recent) = self.project_config.recent_files.iter_mut().find(|r| r.path == old_rel) {
+                recent.path = new_rel.clone();
+                self.save_project_config();
+            }
+            if let Some(pin) = self.project_config.pinned_files.iter_mut().find(|p| **p == old_rel) {
+                *pin = new_rel;
+                self.save_project_config();
+            }
+        }
+    }
+
+    /// Move `path` into the project's `废稿/` folder.
     /// Creates `废稿/` if it doesn't exist. Appends a numeric suffix if a
     /// file with the same name already exists there.
     pub(super) fn move_to_trash(&mut self, path: &Path) {
@@ -527,6 +1675,10 @@ impl TextToolApp {
         if self.selected_file_path.as_deref() == Some(path) {
             self.selected_file_path = None;
         }
+        self.multi_selected.retain(|p| p != path);
+        if self.is_file_pinned(path) {
+            self.toggle_file_pin(path);
+        }
 
         if let Err(e) = std::fs::rename(path, &dest) {
             self.status = format!("移动失败: {e}");
@@ -540,13 +1692,18 @@ impl TextToolApp {
     }
 
     pub(super) fn build_skill_set(&self) -> SkillSet {
-        SkillSet::new(
+        let skills = SkillSet::new(
             self.world_objects.clone(),
             self.struct_roots.clone(),
             self.foreshadows.clone(),
             self.milestones.clone(),
             self.project_root.clone(),
-        )
+        ).notify_writes(self.agent_written_tx.clone());
+        if self.design_read_only() {
+            skills.without_design_writes()
+        } else {
+            skills
+        }
     }
 
     /// Construct the `AgentBackend` for the currently-open project.
@@ -582,13 +1739,76 @@ impl TextToolApp {
         }
     }
 
-    // ── Tree helpers ──────────────────────────────────────────────────────────
+    // ── File pins ─────────────────────────────────────────────────────────────
+
+    /// `path` relative to the project root, with `/` separators.
+    pub(super) fn project_relative(&self, path: &Path) -> Option<String> {
+        let root = self.project_root.as_ref()?;
+        let rel = path.strip_prefix(root).ok()?;
+        Some(rel.to_string_lossy().replace('\\', "/"))
+    }
+
+    /// Remember the caret and scroll of the file in the left or right pane,
+    /// to restore when it is opened again.  Saved with the project config by
+    /// the caller.
+    pub(super) fn remember_editor_position(&mut self, left: bool) {
+        let (file, view) = if left { (&self.left_file, self.left_view) } else { (&self.right_file, self.right_view) };
+        let Some(f) = file.as_ref().filter(|f| !f.scratch) else { return };
+        if let Some(rel) = self.project_relative(&f.path) {
+            self.project_config.note_editor_position(&rel, view.caret, view.scroll);
+        }
+    }
+
+    /// Put `path` at the top of the project's 最近编辑的文件 list.
+    pub(super) fn note_recent_file(&mut self, path: &Path) {
+        if let Some(rel) = self.project_relative(path) {
+            self.project_config.note_recent_file(&rel, unix_now());
+            self.save_project_config();
+        }
+    }
+
+    pub(super) fn is_file_pinned(&self, path: &Path) -> bool {
+        self.project_relative(path)
+            .is_some_and(|rel| self.project_config.pinned_files.contains(&rel))
+    }
+
+    /// Pin `path` to the quick-access strip, or unpin it if already pinned.
+    pub(super) fn toggle_file_pin(&mut self, path: &Path) {
+        let Some(rel) = self.project_relative(path) else { return };
+        let pins = &mut self.project_config.pinned_files;
+        if let Some(pos) = pins.iter().position(|p| *p == rel) {
+            pins.remove(pos);
+        } else {
+            pins.push(rel);
+        }
+        self.save_project_config();
+    }
+
+    // ── Object selection ──────────────────────────────────────────────────────
+
+    /// The selected object index when exactly one object is selected.
+    pub(super) fn selected_obj_idx(&self) -> Option<usize> {
+        if self.selected_objs.len() == 1 {
+            self.selected_objs.first().copied()
+        } else {
+            None
+        }
+    }
+
+    /// Replace the selection with the single object at `idx`.
+    pub(super) fn select_obj(&mut self, idx: usize) {
+        self.selected_objs.clear();
+        self.selected_objs.insert(idx);
+        self.obj_select_anchor = Some(idx);
+    }
 
-    /// Collect the names of all world objects for auto-complete / validation.
-    pub(super) fn all_object_names(&self) -> Vec<String> {
-        self.world_objects.iter().map(|o| o.name.clone()).collect()
+    pub(super) fn clear_obj_selection(&mut self) {
+        self.selected_objs.clear();
+        self.obj_select_anchor = None;
     }
 
+    // ── Tree helpers ──────────────────────────────────────────────────────────
+
     /// Collect all structure node titles (depth-first).
     pub(super) fn all_struct_node_titles(&self) -> Vec<String> {
         all_node_titles(&self.struct_roots)
@@ -601,21 +1821,24 @@ impl TextToolApp {
         dirs_home().map(|h| h.join(".config").join("qingmo").join("config.json"))
     }
 
-    /// Save LLM config, Markdown settings, and last project to disk.
+    /// Save LLM config, Markdown settings, and last project to disk.  Extra
+    /// windows leave this to the main window, which adopts their settings.
     pub(super) fn save_config(&self) {
+        if self.secondary {
+            return;
+        }
         let Some(path) = Self::config_path() else { return };
         let cfg = AppConfig {
             llm_config: self.llm_config.clone(),
-            md_settings: self.md_settings.clone(),
+            settings: self.settings.clone(),
             last_project: self.last_project.as_ref().map(|p| p.to_string_lossy().into_owned()),
-            auto_load: self.auto_load_from_files,
-            theme: self.theme,
+            last_session: self.session_now(),
         };
         if let Some(parent) = path.parent() {
             let _ = std::fs::create_dir_all(parent);
         }
         if let Ok(json) = serde_json::to_string_pretty(&cfg) {
-            let _ = std::fs::write(&path, json);
+            let _ = text_tool_core::safe_write(&path, json);
         }
     }
 
@@ -629,34 +1852,119 @@ impl TextToolApp {
 
 // ── eframe::App impl ──────────────────────────────────────────────────────────
 
+impl Drop for TextToolApp {
+    fn drop(&mut self) {
+        self.flush_design_autosave();
+        self.flush_change_log();
+        self.release_project_lock();
+    }
+}
+
 impl eframe::App for TextToolApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.intercept_close_for_summary(ctx);
+        if self.session_summary.is_none() {
+            self.intercept_close_for_unsaved(ctx);
+        }
+        self.run_frame(ctx);
+        self.draw_project_windows(ctx);
+    }
+
+    /// The session as the window closes, for the next start.
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.save_config();
+    }
+}
+
+impl TextToolApp {
+    /// Write `Design/章节结构.json` if the structure changed since the last
+    /// write.  Silent: the structure panel calls this every frame.
+    pub(super) fn autosave_struct(&mut self) {
+        let Some(root) = self.project_root.clone() else {
+            self.request_project_for_save(SaveTrigger::Autosave);
+            return;
+        };
+        if let Ok(current_json) = serde_json::to_string(&self.struct_roots) {
+            if self.struct_json_snapshot.as_deref() != Some(&current_json) {
+                if self.design_read_only() {
+                    return;
+                }
+                self.struct_json_snapshot = Some(current_json);
+                if let Err(e) = self.guard_design_overwrite(DesignPart::Structure, self.struct_roots.len()) {
+                    self.report(e);
+                    return;
+                }
+                let path = root.join("Design").join("章节结构.json");
+                if let Ok(pretty) = serde_json::to_string_pretty(&self.struct_roots) {
+                    if text_tool_core::safe_write(&path, pretty).is_ok() {
+                        self.note_design_saved(DesignPart::Structure);
+                    }
+                }
+            }
+        }
+    }
+
+    /// One frame of this window's UI.
+    pub(super) fn run_frame(&mut self, ctx: &egui::Context) {
+        // A panic in panel code costs one frame instead of the session.
+        let frame = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.update_frame(ctx)));
+        if frame.is_err() {
+            self.recover_from_panic();
+            ctx.request_repaint();
+        }
+        self.draw_error_toast(ctx);
+    }
+
+    fn update_frame(&mut self, ctx: &egui::Context) {
         // Apply theme every frame (cheap: egui diffs visuals internally)
-        ctx.set_visuals(match self.theme {
+        ctx.set_visuals(match self.settings.theme {
             AppTheme::Dark  => egui::Visuals::dark(),
             AppTheme::Light => egui::Visuals::light(),
         });
 
+        // A project opening in the background takes over the window.
+        self.poll_project_load();
+        if self.draw_project_loading(ctx) {
+            return;
+        }
+
         // Keyboard shortcuts (checked before UI to avoid conflicts)
+        self.handle_focus_keys(ctx);
         self.handle_keyboard(ctx);
 
+        // Keep polling while the project index is being rebuilt.
+        self.apply_dir_listings();
+        if self.project_index.is_scanning() || self.project_index.is_listing() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
         // ── Auto-save tick ────────────────────────────────────────────────────
-        if self.md_settings.auto_save_interval_secs > 0 {
-            let interval = self.md_settings.auto_save_interval_secs as u64;
+        if self.settings.markdown.auto_save_interval_secs > 0 {
+            let interval = self.settings.markdown.auto_save_interval_secs as u64;
             let should_save = match self.last_auto_save {
                 None => false, // don't save on the very first frame
                 Some(last) => last.elapsed().as_secs() >= interval,
             };
             if should_save {
-                let mut saved_any = false;
+                let mut saved = Vec::new();
+                let mut delta = 0;
+                let left_delta = self.left_file.as_ref().map_or(0, |f| self.unsaved_word_delta(f));
+                let right_delta = self.right_file.as_ref().map_or(0, |f| self.unsaved_word_delta(f));
                 if let Some(f) = &mut self.left_file {
-                    if f.modified && f.save().is_ok() { saved_any = true; }
+                    if f.modified && f.save().is_ok() { saved.push(f.path.clone()); delta += left_delta; }
                 }
                 if let Some(f) = &mut self.right_file {
-                    if f.modified && f.save().is_ok() { saved_any = true; }
+                    if f.modified && f.save().is_ok() { saved.push(f.path.clone()); delta += right_delta; }
+                }
+                let saved_any = !saved.is_empty();
+                for path in &saved {
+                    self.discard_recovery(path);
+                    self.record_pane_revision(path);
                 }
+                self.record_words_written(delta);
                 self.last_auto_save = Some(Instant::now());
                 if saved_any {
+                    self.reindex_project();
                     // Record elapsed time since session start as a human-readable label.
                     // (We avoid a UTC clock to sidestep timezone issues without a date library.)
                     self.last_auto_save_label = chrono_label();
@@ -670,30 +1978,34 @@ impl eframe::App for TextToolApp {
             ctx.request_repaint_after(std::time::Duration::from_secs(interval));
         }
 
+        self.tick_recovery(ctx);
+        self.reload_agent_writes();
+        self.tick_design_autosave(ctx);
+        self.tick_save_reminders(ctx);
+        self.tick_auto_done();
+        self.check_daily_snapshot();
+        self.tick_project_lock(ctx);
+
         // UI layers always visible
         self.draw_menu_bar(ctx);
+        self.draw_pin_strip(ctx);
+        self.draw_project_lock_banner(ctx);
         self.draw_status_bar(ctx);
         self.draw_toolbar(ctx);
 
+        self.apply_object_nav();
+
         // Content area switches based on active panel
-        // ── Auto-load Structure panel on first switch ─────────────────────────
-        if self.active_panel == Panel::Structure
-            && self.last_active_panel != Panel::Structure
-            && self.project_root.is_some()
-        {
-            // Silently try to load chapter structure; if file is missing, do nothing.
-            if let Ok((text, _)) = self.read_project_file("Design", "章节结构.json") {
-                if let Ok(nodes) = serde_json::from_str::<Vec<StructNode>>(&text) {
-                    self.struct_roots = nodes;
-                    self.selected_node_path.clear();
-                }
-            }
-            // Reset snapshot so the freshly-loaded data is not immediately re-saved.
-            self.struct_json_snapshot = serde_json::to_string(&self.struct_roots).ok();
+        // ── Panel enter / leave hooks (auto-load, 切换面板时自动保存) ─────────
+        if let Some((from, to)) = panel_transition(self.last_active_panel, self.active_panel) {
+            self.on_panel_transition(from, to);
         }
         self.last_active_panel = self.active_panel;
+        self.refresh_design_share();
 
         match self.active_panel {
+            // The 阅读 view stands in for every panel while it is open.
+            _ if self.reading.is_some() => self.draw_reading_view(ctx),
             Panel::Novel => {
                 self.draw_file_tree(ctx);
                 self.draw_editors(ctx);
@@ -703,23 +2015,7 @@ impl eframe::App for TextToolApp {
             }
             Panel::Structure => {
                 self.draw_structure_panel(ctx);
-
-                // ── Auto-save structure when changed ──────────────────────────
-                if self.project_root.is_some() {
-                    if let Ok(current_json) = serde_json::to_string(&self.struct_roots) {
-                        let changed = self.struct_json_snapshot.as_deref() != Some(&current_json);
-                        if changed {
-                            self.struct_json_snapshot = Some(current_json.clone());
-                            // Write silently — status bar only if there's an error.
-                            if let Some(root) = &self.project_root.clone() {
-                                let path = root.join("Design").join("章节结构.json");
-                                if let Ok(pretty) = serde_json::to_string_pretty(&self.struct_roots) {
-                                    let _ = std::fs::write(&path, pretty);
-                                }
-                            }
-                        }
-                    }
-                }
+                self.autosave_struct();
             }
             Panel::Llm => {
                 self.draw_llm_panel(ctx);
@@ -730,9 +2026,47 @@ impl eframe::App for TextToolApp {
         self.draw_new_file_dialog(ctx);
         self.draw_rename_dialog(ctx);
         self.draw_delete_confirm_dialog(ctx);
+        self.draw_reload_confirm_dialog(ctx);
+        self.draw_unsaved_prompt(ctx);
+        self.draw_design_reload(ctx);
         self.draw_settings_window(ctx);
         self.draw_search_window(ctx);
         self.draw_template_dialog(ctx);
+        self.draw_sample_tour(ctx);
+        self.draw_paragraph_format_dialog(ctx);
+        self.draw_split_dialog(ctx);
+        self.draw_docx_import_dialog(ctx);
+        self.draw_struct_add_file_dialog(ctx);
+        self.draw_merge_dialog(ctx);
+        self.draw_content_import_dialog(ctx);
+        self.draw_outline_export_dialog(ctx);
+        self.draw_relation_matrix(ctx);
+        self.draw_overwrite_confirm(ctx);
+        self.draw_session_summary(ctx);
+        self.draw_attachment_remove_dialog(ctx);
+        self.draw_node_split_dialog(ctx);
+        self.draw_node_rename_dialog(ctx);
+        self.draw_synopsis_dialog(ctx);
+        self.draw_object_kinds_dialog(ctx);
+        self.draw_change_log(ctx);
+        self.draw_name_cleanup_report(ctx);
+        self.draw_pane_diff(ctx);
+        self.draw_history_browser(ctx);
+        self.draw_word_frequency(ctx);
+        self.draw_link_check(ctx);
+        self.draw_duplicates(ctx);
+        self.draw_outline_compare(ctx);
+        self.draw_project_prompt(ctx);
+        self.draw_object_picker(ctx);
+        self.draw_link_picker(ctx);
+        self.draw_recovery_dialog(ctx);
+        self.draw_crash_notice(ctx);
+        self.draw_link_suggestion(ctx);
+        self.draw_snapshot_toast(ctx);
+        self.draw_reminder_toast(ctx);
+        self.draw_auto_done_toast(ctx);
+        self.flush_change_log();
+        self.focus_scroll = false;
     }
 }
 
@@ -820,6 +2154,324 @@ mod tests {
         assert_eq!(d.links[0].kind, RelationKind::Enemy);
     }
 
+    #[test]
+    fn test_object_kind_custom_serialization() {
+        // Built-in kinds keep their variant names; custom kinds are bare strings.
+        let kinds = vec![ObjectKind::Character, ObjectKind::Custom("功法".to_owned()), ObjectKind::Other];
+        let json = serde_json::to_string(&kinds).unwrap();
+        assert_eq!(json, r#"["Character","功法","Other"]"#);
+        let back: Vec<ObjectKind> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, kinds);
+
+        // Old files load unchanged; unknown names read back as custom kinds.
+        let objs: Vec<WorldObject> = serde_json::from_str(
+            r#"[{"name":"林枫","kind":"Character","description":"","background":"","links":[]},
+                {"name":"九阳真经","kind":"功法","description":"","background":"","links":[]},
+                {"name":"青云宗","kind":"Faction","description":"","background":"","links":[]}]"#,
+        ).unwrap();
+        let kinds: Vec<&ObjectKind> = objs.iter().map(|o| &o.kind).collect();
+        assert_eq!(kinds, [&ObjectKind::Character, &ObjectKind::Custom("功法".to_owned()), &ObjectKind::Faction]);
+        assert_eq!(objs[1].kind.label(), "功法");
+        assert_eq!(objs[1].icon(), CUSTOM_KIND_ICON);
+        let again: Vec<WorldObject> = serde_json::from_str(&serde_json::to_string(&objs).unwrap()).unwrap();
+        assert_eq!(again[1].kind, objs[1].kind);
+        assert!(serde_json::from_str::<ObjectKind>("3").is_err());
+    }
+
+    #[test]
+    fn test_object_kind_registry() {
+        let mut config: ProjectConfig = serde_json::from_str(r#"{"pinned_files":[]}"#).unwrap();
+        assert!(config.object_kinds.is_empty());
+
+        config.add_object_kind(" 功法 ", "📜", [200, 120, 40]).unwrap();
+        config.add_object_kind("种族", "", [90, 160, 90]).unwrap();
+        assert_eq!(config.add_object_kind("功法", "x", [0, 0, 0]).unwrap_err(), "已存在类型「功法」");
+        assert_eq!(config.add_object_kind("人物", "x", [0, 0, 0]).unwrap_err(), "「人物」是内置类型");
+        assert!(config.add_object_kind("Item", "x", [0, 0, 0]).is_err());
+        assert!(config.add_object_kind("  ", "x", [0, 0, 0]).is_err());
+
+        let gongfa = ObjectKind::Custom("功法".to_owned());
+        assert_eq!(config.kind_icon(&gongfa), "📜");
+        assert_eq!(config.kind_icon(&ObjectKind::Custom("种族".to_owned())), CUSTOM_KIND_ICON);
+        assert_eq!(config.kind_icon(&ObjectKind::Custom("未登记".to_owned())), CUSTOM_KIND_ICON);
+        assert_eq!(config.kind_icon(&ObjectKind::Item), "🗡");
+        assert_eq!(config.custom_kind(&gongfa).map(|c| c.color), Some([200, 120, 40]));
+
+        // Offered: built-ins, the registry, then unregistered kinds in use.
+        let objs = vec![
+            WorldObject::new("灵狐", ObjectKind::Custom("灵兽".to_owned())),
+            WorldObject::new("九阳真经", gongfa.clone()),
+        ];
+        let offered = object_kinds(&config.object_kinds, &objs);
+        let labels: Vec<&str> = offered.iter().map(|k| k.label()).collect();
+        assert_eq!(labels, ["人物", "场景", "地点", "道具", "势力", "其他", "功法", "种族", "灵兽"]);
+
+        let back: ProjectConfig = serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert_eq!(back.object_kinds, config.object_kinds);
+    }
+
+    #[test]
+    fn test_world_object_tags_default_on_old_json() {
+        let old_json = r#"{"name":"林枫","kind":"Character","description":"","background":"","links":[]}"#;
+        let obj: WorldObject = serde_json::from_str(old_json).unwrap();
+        assert!(obj.tags.is_empty());
+        assert!(!obj.pinned);
+    }
+
+    #[test]
+    fn test_project_config_pinned_files() {
+        let cfg: ProjectConfig = serde_json::from_str("{}").unwrap();
+        assert!(cfg.pinned_files.is_empty());
+
+        let cfg = ProjectConfig {
+            pinned_files: vec!["Content/第一章.md".to_owned()],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&cfg).unwrap();
+        let d: ProjectConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(d.pinned_files, cfg.pinned_files);
+    }
+
+    #[test]
+    fn test_project_config_recent_files() {
+        let mut cfg: ProjectConfig = serde_json::from_str(r#"{"pinned_files":[]}"#).unwrap();
+        assert!(cfg.recent_files.is_empty());
+
+        cfg.note_recent_file("Content/第一章.md", 100);
+        cfg.note_recent_file("Design/世界对象.json", 200);
+        cfg.note_recent_file("Content/第一章.md", 300);
+        let paths: Vec<&str> = cfg.recent_files.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, vec!["Content/第一章.md", "Design/世界对象.json"]);
+        assert_eq!(cfg.recent_files[0].at, 300);
+
+        for i in 0..20 {
+            cfg.note_recent_file(&format!("Content/{i}.md"), 400 + i);
+        }
+        assert_eq!(cfg.recent_files.len(), MAX_RECENT_FILES);
+        assert_eq!(cfg.recent_files[0].path, "Content/19.md");
+    }
+
+    #[test]
+    fn test_project_config_reading_positions() {
+        let cfg: ProjectConfig = serde_json::from_str(r#"{"pinned_files":[]}"#).unwrap();
+        assert!(cfg.reading_positions.is_empty());
+
+        let mut cfg = ProjectConfig::default();
+        let pos = ReadingPosition { chapter: 2, title: "第三章".into(), scroll: 0.25 };
+        cfg.reading_positions.insert("第一卷".into(), pos.clone());
+        let json = serde_json::to_string(&cfg).unwrap();
+        let d: ProjectConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(d.reading_positions.get("第一卷"), Some(&pos));
+    }
+
+    #[test]
+    fn test_project_config_editor_positions() {
+        let cfg: ProjectConfig = serde_json::from_str(r#"{"pinned_files":[]}"#).unwrap();
+        assert!(cfg.editor_positions.is_empty());
+
+        let mut cfg = ProjectConfig::default();
+        cfg.note_editor_position("Content/第一章.md", 120, 300.0);
+        cfg.note_editor_position("Content/第二章.md", 5, 0.0);
+        cfg.note_editor_position("Content/第一章.md", 150, 340.0);
+        let paths: Vec<&str> = cfg.editor_positions.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(paths, vec!["Content/第一章.md", "Content/第二章.md"]);
+
+        let json = serde_json::to_string(&cfg).unwrap();
+        let mut d: ProjectConfig = serde_json::from_str(&json).unwrap();
+        let pos = d.editor_position("Content/第一章.md", 1000).unwrap();
+        assert_eq!((pos.caret, pos.scroll), (150, 340.0));
+        // At the very end is still inside the file.
+        assert!(d.editor_position("Content/第一章.md", 150).is_some());
+        assert!(d.editor_position("Content/第三章.md", 1000).is_none());
+
+        // The file got shorter outside the app: the entry is dropped.
+        assert!(d.editor_position("Content/第一章.md", 100).is_none());
+        assert!(d.editor_position("Content/第一章.md", 1000).is_none());
+        assert_eq!(d.editor_positions.len(), 1);
+
+        for i in 0..MAX_EDITOR_POSITIONS + 5 {
+            cfg.note_editor_position(&format!("Content/{i}.md"), i, 0.0);
+        }
+        assert_eq!(cfg.editor_positions.len(), MAX_EDITOR_POSITIONS);
+        assert_eq!(cfg.editor_positions[0].path, format!("Content/{}.md", MAX_EDITOR_POSITIONS + 4));
+        assert!(!cfg.editor_positions.iter().any(|p| p.path == "Content/第一章.md"));
+    }
+
+    // ── Deferred open tests ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_request_open_simultaneous_signals() {
+        let a = PathBuf::from("Content/第一章.md");
+        let b = PathBuf::from("Content/第二章.md");
+
+        // Double-click and a context-menu "open" landing in the same frame,
+        // followed by a stray right-pane signal: exactly one open survives.
+        let mut slot = None;
+        request_open(&mut slot, a.clone(), PaneTarget::Left);
+        request_open(&mut slot, a.clone(), PaneTarget::Left);
+        request_open(&mut slot, a.clone(), PaneTarget::Right);
+        assert_eq!(slot, Some((a.clone(), PaneTarget::Left)));
+
+        // Pointer click beats a keyboard Enter raised later in the frame.
+        let mut slot = None;
+        request_open(&mut slot, b.clone(), PaneTarget::Left);
+        request_open(&mut slot, a, PaneTarget::Left);
+        assert_eq!(slot, Some((b, PaneTarget::Left)));
+    }
+
+    #[test]
+    fn test_route_to_pane_defaults() {
+        use PaneAccept::{Json, Markdown};
+        // Default roles: Markdown left, JSON right, focus does not matter.
+        for focus_left in [true, false] {
+            let route = |p: &str| route_to_pane(Path::new(p), Markdown, Json, focus_left);
+            assert_eq!(route("Design/世界对象.json"), PaneTarget::Right);
+            assert_eq!(route("Content/第一章.md"), PaneTarget::Left);
+            assert_eq!(route("Content/番外.markdown"), PaneTarget::Left);
+            // Neither pane takes it: the main editor does.
+            assert_eq!(route("README"), PaneTarget::Left);
+        }
+    }
+
+    #[test]
+    fn test_panel_transition() {
+        assert_eq!(panel_transition(Panel::Novel, Panel::Novel), None);
+        assert_eq!(panel_transition(Panel::Novel, Panel::Objects), Some((Panel::Novel, Panel::Objects)));
+        assert_eq!(panel_transition(Panel::Structure, Panel::Llm), Some((Panel::Structure, Panel::Llm)));
+    }
+
+    #[test]
+    fn test_focus_region_cycle() {
+        use FocusRegion::*;
+        // Forward wraps around; the right editor only joins while open.
+        let step = |current, right_open, backward| FocusRegion::step(Panel::Novel, right_open, current, backward);
+        assert_eq!(step(None, false, false), Navigator);
+        assert_eq!(step(Some(Navigator), false, false), LeftEditor);
+        assert_eq!(step(Some(LeftEditor), false, false), ObjectRefs);
+        assert_eq!(step(Some(LeftEditor), true, false), RightEditor);
+        assert_eq!(step(Some(ObjectRefs), true, false), Navigator);
+        // Backward, and from no region at the last one.
+        assert_eq!(step(Some(Navigator), true, true), ObjectRefs);
+        assert_eq!(step(None, false, true), ObjectRefs);
+        // A region gone from the cycle restarts it.
+        assert_eq!(step(Some(RightEditor), false, false), Navigator);
+        assert_eq!(FocusRegion::step(Panel::Objects, false, Some(LeftEditor), false), ObjectList);
+        assert_eq!(FocusRegion::step(Panel::Structure, false, Some(StructTree), false), NodeEditor);
+        assert_eq!(FocusRegion::step(Panel::Llm, false, Some(LlmWork), false), LlmConfig);
+
+        for panel in [Panel::Novel, Panel::Objects, Panel::Structure, Panel::Llm] {
+            let cycle = FocusRegion::cycle(panel, true);
+            assert!(cycle.len() >= 2);
+            let mut at = cycle[0];
+            for _ in 0..cycle.len() {
+                at = FocusRegion::step(panel, true, Some(at), false);
+            }
+            assert_eq!(at, cycle[0]);
+        }
+    }
+
+    #[test]
+    fn test_list_step() {
+        let order = [2, 5, 7];
+        assert_eq!(list_step(&order, None, false), Some(2));
+        assert_eq!(list_step(&order, None, true), Some(7));
+        assert_eq!(list_step(&order, Some(&5), false), Some(7));
+        assert_eq!(list_step(&order, Some(&7), false), Some(7));
+        assert_eq!(list_step(&order, Some(&2), true), Some(2));
+        // Selected item filtered out: start over.
+        assert_eq!(list_step(&order, Some(&3), false), Some(2));
+        assert_eq!(list_step::<usize>(&[], None, false), None);
+
+        let mut volume = StructNode::new("第一卷", StructKind::Volume);
+        volume.children.push(StructNode::new("第一章", StructKind::Chapter));
+        let roots = vec![volume, StructNode::new("第二卷", StructKind::Volume)];
+        let paths = node_paths(&roots);
+        assert_eq!(paths, [vec![0], vec![0, 0], vec![1]]);
+        assert_eq!(list_step(&paths, Some(&vec![0, 0]), false), Some(vec![1]));
+    }
+
+    #[test]
+    fn test_panel_switch_saves_matrix() {
+        let all = SaveSet { panes: true, objects: true, structure: true, foreshadows: true, milestones: true };
+        let design = SaveSet { panes: false, ..all };
+        let on = PanelSwitchSave { enabled: true, ..PanelSwitchSave::default() };
+        let panels = [Panel::Novel, Panel::Objects, Panel::Structure, Panel::Llm];
+
+        // Off by default: nothing is saved, whatever is dirty.
+        for from in panels {
+            assert!(!panel_switch_saves(from, &PanelSwitchSave::default(), all).any());
+        }
+        // Leaving 小说编辑 saves panes only; design panels save design data only.
+        assert_eq!(panel_switch_saves(Panel::Novel, &on, all), SaveSet { panes: true, ..SaveSet::default() });
+        assert_eq!(panel_switch_saves(Panel::Objects, &on, all), design);
+        assert_eq!(panel_switch_saves(Panel::Structure, &on, all), design);
+        assert!(!panel_switch_saves(Panel::Llm, &on, all).any());
+        // Only what is dirty.
+        assert!(!panel_switch_saves(Panel::Novel, &on, design).any());
+        let objects = SaveSet { objects: true, ..SaveSet::default() };
+        assert_eq!(panel_switch_saves(Panel::Structure, &on, objects), objects);
+        // Per-category toggles.
+        let no_objects = PanelSwitchSave { objects: false, panes: false, ..on };
+        assert!(!panel_switch_saves(Panel::Objects, &no_objects, objects).any());
+        assert!(!panel_switch_saves(Panel::Novel, &no_objects, all).any());
+        assert_eq!(
+            panel_switch_saves(Panel::Objects, &no_objects, all),
+            SaveSet { objects: false, ..design },
+        );
+    }
+
+    #[test]
+    fn test_route_to_pane_matrix() {
+        let md = Path::new("Content/第一章.md");
+        let json = Path::new("Design/章节结构.json");
+        let txt = Path::new("notes.txt");
+        for &left in PaneAccept::all() {
+            for &right in PaneAccept::all() {
+                for focus_left in [true, false] {
+                    for path in [md, json, txt] {
+                        let target = route_to_pane(path, left, right, focus_left);
+                        let expected = match (left.accepts(path), right.accepts(path)) {
+                            (true, true) if focus_left => PaneTarget::Left,
+                            (true, true) => PaneTarget::Right,
+                            (false, true) => PaneTarget::Right,
+                            _ => PaneTarget::Left,
+                        };
+                        assert_eq!(target, expected, "{left:?}/{right:?} focus_left={focus_left} {path:?}");
+                    }
+                }
+            }
+        }
+        // Two Markdown panes side by side: the last-focused one wins.
+        let both = PaneAccept::Markdown;
+        assert_eq!(route_to_pane(md, both, both, true), PaneTarget::Left);
+        assert_eq!(route_to_pane(md, both, both, false), PaneTarget::Right);
+        assert_eq!(route_to_pane(json, both, both, false), PaneTarget::Left);
+        // 任意 takes everything.
+        assert!(PaneAccept::Any.accepts(txt));
+        assert!(!PaneAccept::Markdown.accepts(txt) && !PaneAccept::Json.accepts(md));
+    }
+
+    // ── Selection helper tests ────────────────────────────────────────────────
+
+    #[test]
+    fn test_index_after_move() {
+        // Move item 0 to position 2: items 1 and 2 shift up.
+        assert_eq!(index_after_move(0, 0, 2), 2);
+        assert_eq!(index_after_move(1, 0, 2), 0);
+        assert_eq!(index_after_move(3, 0, 2), 3);
+        // Move item 3 to position 1: items 1 and 2 shift down.
+        assert_eq!(index_after_move(1, 3, 1), 2);
+        assert_eq!(index_after_move(0, 3, 1), 0);
+    }
+
+    #[test]
+    fn test_selection_after_removal() {
+        let sel: BTreeSet<usize> = [1, 3, 5].into_iter().collect();
+        let removed: BTreeSet<usize> = [0, 3].into_iter().collect();
+        let out = selection_after_removal(&sel, &removed);
+        assert_eq!(out.into_iter().collect::<Vec<_>>(), vec![0, 3]);
+    }
+
     // ── StructKind tests ──────────────────────────────────────────────────────
 
     #[test]
@@ -828,6 +2480,13 @@ mod tests {
         assert_eq!(StructKind::Volume.label(), "卷");
         assert_eq!(StructKind::Chapter.label(), "章");
         assert_eq!(StructKind::Section.label(), "节");
+        assert_eq!(StructKind::Scene.label(), "场");
+    }
+
+    #[test]
+    fn test_struct_kind_all_ordered_by_rank() {
+        let ranks: Vec<usize> = StructKind::all().iter().map(|k| k.rank()).collect();
+        assert_eq!(ranks, vec![0, 1, 2, 3, 4]);
     }
 
     #[test]
@@ -835,6 +2494,60 @@ mod tests {
         assert_eq!(StructKind::Outline.default_child_kind(), StructKind::Volume);
         assert_eq!(StructKind::Volume.default_child_kind(), StructKind::Chapter);
         assert_eq!(StructKind::Chapter.default_child_kind(), StructKind::Section);
+        assert_eq!(StructKind::Section.default_child_kind(), StructKind::Scene);
+        assert_eq!(StructKind::Scene.default_child_kind(), StructKind::Scene);
+    }
+
+    #[test]
+    fn test_struct_kind_child_within_depth() {
+        assert_eq!(StructKind::Section.child_kind_within(&StructKind::Section), StructKind::Section);
+        assert_eq!(StructKind::Section.child_kind_within(&StructKind::Scene), StructKind::Scene);
+        assert_eq!(StructKind::Volume.child_kind_within(&StructKind::Section), StructKind::Chapter);
+    }
+
+    #[test]
+    fn test_struct_kind_scene_serialization() {
+        let mut sec = StructNode::new("第一节", StructKind::Section);
+        sec.children.push(StructNode::new("雨夜", StructKind::Scene));
+        let json = serde_json::to_string(&sec).unwrap();
+        assert!(json.contains("\"Scene\""));
+        let d: StructNode = serde_json::from_str(&json).unwrap();
+        assert_eq!(d.children[0].kind, StructKind::Scene);
+    }
+
+    #[test]
+    fn test_struct_json_without_scene_still_loads() {
+        let old_json = r#"[{"title":"第一卷","kind":"Volume","tag":"Normal","summary":"","done":false,
+            "children":[{"title":"第一章","kind":"Chapter","tag":"Normal","summary":"","done":true,
+            "children":[],"linked_objects":[],"node_links":[]}],
+            "linked_objects":[],"node_links":[]}]"#;
+        let roots: Vec<StructNode> = serde_json::from_str(old_json).unwrap();
+        assert_eq!(roots[0].children[0].kind, StructKind::Chapter);
+        assert_eq!(roots[0].done_count(), 1);
+        assert!(roots[0].children[0].notes.is_empty());
+    }
+
+    #[test]
+    fn test_struct_node_notes_roundtrip() {
+        let mut ch = StructNode::new("第一章", StructKind::Chapter);
+        ch.notes = "## 节拍\n\n- 雨夜相遇\n- [资料](https://example.com)".to_owned();
+        let json = serde_json::to_string(&ch).unwrap();
+        assert!(json.contains("\"notes\""));
+        let back: StructNode = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.notes, ch.notes);
+    }
+
+    #[test]
+    fn test_leaf_count_with_scenes() {
+        let mut ch = StructNode::new("第一章", StructKind::Chapter);
+        let mut sec = StructNode::new("第一节", StructKind::Section);
+        let mut scene = StructNode::new("雨夜", StructKind::Scene);
+        scene.done = true;
+        sec.children.push(scene);
+        sec.children.push(StructNode::new("追兵", StructKind::Scene));
+        ch.children.push(sec);
+        assert_eq!(ch.leaf_count(), 2);
+        assert_eq!(ch.done_count(), 1);
     }
 
     // ── StructNode tests ──────────────────────────────────────────────────────
@@ -882,8 +2595,115 @@ mod tests {
         assert_eq!(d.linked_objects[0], "主角");
     }
 
+    #[test]
+    fn test_struct_node_stage() {
+        let mut node = StructNode::new("第一章", StructKind::Chapter);
+        assert_eq!(node.stage(), NodeStage::NotStarted);
+        node.set_stage(NodeStage::Drafting);
+        assert_eq!(node.stage(), NodeStage::Drafting);
+        assert!(!node.done);
+        node.set_stage(NodeStage::Done);
+        assert!(node.done);
+        // Un-checking 已完成 elsewhere leaves the chapter in progress.
+        node.done = false;
+        assert_eq!(node.stage(), NodeStage::Drafting);
+        // Older files have no stage field.
+        let json = r#"{"title":"旧章","kind":"Chapter","tag":"Normal","summary":"","done":true,
+            "children":[],"linked_objects":[],"node_links":[]}"#;
+        let old: StructNode = serde_json::from_str(json).unwrap();
+        assert_eq!(old.stage, None);
+        assert_eq!(old.stage(), NodeStage::Done);
+    }
+
+    #[test]
+    fn test_foreshadow_columns() {
+        let unplanted = Foreshadow::new("神秘来信");
+        let mut pending = Foreshadow::new("玉佩来历");
+        pending.related_chapters = vec!["第一章".to_owned()];
+        let mut resolved = pending.clone();
+        resolved.name = "师父身份".to_owned();
+        resolved.resolved = true;
+        // Resolved without any planted chapter still counts as 已回收.
+        let mut bare = Foreshadow::new("旧伤");
+        bare.resolved = true;
+        let mut list = vec![unplanted, pending, resolved, bare];
+
+        assert_eq!(foreshadow_columns(&list), [vec![0], vec![1], vec![2, 3]]);
+
+        // 待回收 ⇄ 已回收 by dragging.
+        assert!(list[1].set_stage(ForeshadowStage::Resolved));
+        assert!(list[1].resolved);
+        assert!(list[2].set_stage(ForeshadowStage::Pending));
+        assert!(!list[2].resolved);
+        assert_eq!(foreshadow_columns(&list), [vec![0], vec![2], vec![1, 3]]);
+
+        // 未植入 follows from the chapters: no drags in or out.
+        assert!(!list[0].set_stage(ForeshadowStage::Resolved));
+        assert!(!list[3].set_stage(ForeshadowStage::Pending));
+        assert!(!list[2].set_stage(ForeshadowStage::Unplanted));
+        assert!(!list[2].set_stage(ForeshadowStage::Pending));
+        assert_eq!(foreshadow_columns(&list), [vec![0], vec![2], vec![1, 3]]);
+    }
+
     // ── node_at / node_at_mut tests ───────────────────────────────────────────
 
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("  Lin  Feng "), "linfeng");
+        assert_eq!(normalize_name("林　枫"), "林枫");
+        assert_eq!(normalize_name("ＡＢＣ１２３"), "abc123");
+        assert_eq!(normalize_name("第一章（上）"), "第一章(上)");
+        assert_eq!(normalize_name("　\t "), "");
+
+        let existing = ["林枫", "Blue Sword", "第一章"];
+        assert_eq!(find_duplicate_name("林 枫", existing), Some("林枫"));
+        assert_eq!(find_duplicate_name("ｂｌｕｅ　ｓｗｏｒｄ", existing), Some("Blue Sword"));
+        assert_eq!(find_duplicate_name("林枫二", existing), None);
+        assert_eq!(find_duplicate_name("  ", ["", " "]), None);
+
+        assert_eq!(unique_name("新章节", existing), "新章节");
+        assert_eq!(unique_name("新章节", ["新章节", "新章节 2"]), "新章节 3");
+    }
+
+    #[test]
+    fn test_push_unique_names() {
+        let mut linked = vec!["林枫".to_owned()];
+        let added = push_unique_names(&mut linked, ["苏婉", "林 枫", " ", "青云宗", "苏婉"].map(String::from));
+        assert_eq!(added, 2);
+        assert_eq!(linked, ["林枫", "苏婉", "青云宗"]);
+        assert_eq!(push_unique_names(&mut linked, Vec::new()), 0);
+    }
+
+    #[test]
+    fn test_should_prompt_for_project() {
+        use project_prompt::should_prompt_for_project as prompt;
+        use SaveTrigger::{Autosave, Explicit};
+        // An open project or nothing to lose never asks.
+        assert!(!prompt(true, true, Explicit, false));
+        assert!(!prompt(false, false, Explicit, false));
+        assert!(!prompt(false, false, Autosave, false));
+        // Unsaved data without a project asks…
+        assert!(prompt(false, true, Explicit, false));
+        assert!(prompt(false, true, Autosave, false));
+        // …and after 稍后 only an explicit sync asks again.
+        assert!(prompt(false, true, Explicit, true));
+        assert!(!prompt(false, true, Autosave, true));
+    }
+
+    #[test]
+    fn test_append_new_by_name() {
+        let mut on_disk = vec![WorldObject::new("林枫", ObjectKind::Character)];
+        let mut entered = WorldObject::new("林枫", ObjectKind::Character);
+        entered.description = "内存里的版本".to_owned();
+        let typed = vec![entered, WorldObject::new("青云宗", ObjectKind::Faction)];
+        let added = project_prompt::append_new_by_name(&mut on_disk, typed, |o| o.name.as_str());
+        assert_eq!(added, 1);
+        let names: Vec<&str> = on_disk.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["林枫", "青云宗"]);
+        // The project's own data wins on a clash.
+        assert!(on_disk[0].description.is_empty());
+    }
+
     #[test]
     fn test_node_at() {
         let mut roots = vec![StructNode::new("第一卷", StructKind::Volume)];
@@ -893,6 +2713,90 @@ mod tests {
         assert!(node_at(&roots, &[1]).is_none());
     }
 
+    #[test]
+    fn test_leaf_for_file() {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        let mut linked = StructNode::new("雨夜", StructKind::Chapter);
+        linked.file = Some("第一卷/001.md".into());
+        vol.children = vec![StructNode::new("第一章", StructKind::Chapter), linked];
+        let roots = vec![vol, StructNode::new("001", StructKind::Chapter)];
+        // The explicit link wins over a title matching the stem.
+        assert_eq!(leaf_for_file(&roots, "第一卷/001.md"), Some(vec![0, 1]));
+        assert_eq!(leaf_for_file(&roots, "其他/001.md"), Some(vec![1]));
+        assert_eq!(leaf_for_file(&roots, "第一章.md"), Some(vec![0, 0]));
+        // Volumes are not chapters.
+        assert_eq!(leaf_for_file(&roots, "第一卷.md"), None);
+
+        // The lookup map agrees, whatever the separators.
+        let map = FileNodeMap::build(&roots);
+        for rel in ["第一卷/001.md", "其他/001.md", "第一章.md", "第一卷.md"] {
+            assert_eq!(map.lookup(rel), leaf_for_file(&roots, rel).as_deref(), "{rel}");
+        }
+        assert_eq!(map.lookup("./第一卷\\001.md"), Some(&[0, 1][..]));
+    }
+
+    #[test]
+    fn test_normalize_rel_path() {
+        assert_eq!(normalize_rel_path("第一卷/01.md"), "第一卷/01.md");
+        assert_eq!(normalize_rel_path("./第一卷\\01.md"), "第一卷/01.md");
+        assert_eq!(normalize_rel_path("第一卷//草稿/../01.md"), "第一卷/01.md");
+        assert_eq!(normalize_rel_path("../01.md"), "01.md");
+        assert_eq!(normalize_rel_path(""), "");
+    }
+
+    #[test]
+    fn test_secondary_window_leaves_global_state() {
+        let dir = std::env::temp_dir().join("qingmo_test_secondary_window");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.open_project(dir.clone());
+        assert_eq!(app.project_root.as_deref(), Some(dir.as_path()));
+        assert!(dir.join("Content").is_dir());
+        // Not the project reopened at startup, and no config written.
+        assert_eq!(app.last_project, None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_restore_session() {
+        let dir = std::env::temp_dir().join(format!("qingmo_test_restore_session_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Content")).unwrap();
+        let chapter = dir.join("Content").join("第一章.md");
+        std::fs::write(&chapter, "# 第一章\n").unwrap();
+
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.open_project_now(dir.clone());
+        app.open_file_in_pane(&chapter, true).unwrap();
+        app.left_file.as_mut().unwrap().content.push_str("未保存");
+        app.left_preview_mode = true;
+        app.active_panel = Panel::Objects;
+        let mut session = app.session_now();
+        assert_eq!(session.left_file, Some(chapter.to_string_lossy().into_owned()));
+        assert_eq!((session.right_file.as_deref(), session.panel.as_str()), (None, "objects"));
+
+        // The file is read from disk again; a file that is gone is skipped.
+        session.right_file = Some(dir.join("Content").join("已删除.md").to_string_lossy().into_owned());
+        let mut restored = TextToolApp::blank();
+        restored.secondary = true;
+        restored.restore_session(&session);
+        assert_eq!(restored.left_file.as_ref().unwrap().content, "# 第一章\n");
+        assert!(restored.left_preview_mode && restored.right_file.is_none());
+        assert_eq!(restored.active_panel, Panel::Objects);
+
+        // An unknown panel leaves the default.
+        let mut other = TextToolApp::blank();
+        other.restore_session(&LastSession { panel: "gone".into(), ..LastSession::default() });
+        assert_eq!(other.active_panel, Panel::Novel);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_node_at_mut() {
         let mut roots = vec![StructNode::new("第一卷", StructKind::Volume)];
@@ -997,7 +2901,7 @@ mod tests {
     #[test]
     fn test_build_dialogue_optimization_prompt_found() {
         use crate::app::{ObjectLink, LinkTarget};
-        let mut app_objs = vec![WorldObject::new("张三", ObjectKind::Character)];
+        let mut app_objs = [WorldObject::new("张三", ObjectKind::Character)];
         app_objs[0].description = "热情开朗".to_owned();
         app_objs[0].links.push(ObjectLink {
             target: LinkTarget::Object("李四".to_owned()),
@@ -1101,21 +3005,84 @@ mod tests {
                 use_local: true,
                 system_prompt: String::new(),
             },
-            md_settings: MarkdownSettings {
-                preview_font_size: 16.0,
-                default_to_preview: true,
-                ..MarkdownSettings::default()
+            settings: AppSettings {
+                markdown: MarkdownSettings {
+                    preview_font_size: 16.0,
+                    default_to_preview: true,
+                    ..MarkdownSettings::default()
+                },
+                auto_load: true,
+                ..AppSettings::default()
             },
             last_project: Some("/home/user/my_novel".to_owned()),
-            auto_load: true,
-            theme: AppTheme::Dark,
+            last_session: LastSession {
+                left_file: Some("/home/user/my_novel/Content/第一章.md".to_owned()),
+                panel: "structure".to_owned(),
+                ..LastSession::default()
+            },
         };
         let json = serde_json::to_string_pretty(&cfg).unwrap();
+        // Flattened: the settings keys sit at the top level as before.
+        assert!(json.contains("\"md_settings\""));
         let d: AppConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(d.llm_config.model_path, "phi2");
-        assert_eq!(d.md_settings.preview_font_size, 16.0);
+        assert_eq!(d.settings.markdown.preview_font_size, 16.0);
         assert_eq!(d.last_project, Some("/home/user/my_novel".to_owned()));
-        assert!(d.auto_load);
+        assert_eq!(d.last_session, cfg.last_session);
+        assert!(d.settings.auto_load);
+    }
+
+    #[test]
+    fn test_app_settings_defaults() {
+        let s = AppSettings::default();
+        assert_eq!(s.theme, AppTheme::Dark);
+        assert!(!s.auto_load);
+        assert_eq!(s.behavior.undo_limit, 200);
+        assert_eq!(s.behavior.left_pane, PaneAccept::Markdown);
+        assert_eq!(s.behavior.right_pane, PaneAccept::Json);
+        assert!(!s.behavior.fullwidth_punctuation);
+        assert_eq!(s.behavior.timeline_gap_warning, 5);
+        // Missing sections and fields fall back to the same defaults.
+        let d: AppSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(d, s);
+        let d: AppSettings = serde_json::from_str(r#"{"behavior":{"undo_limit":50}}"#).unwrap();
+        assert_eq!(d.behavior.undo_limit, 50);
+        assert_eq!(d.behavior.right_pane, PaneAccept::Json);
+        assert_eq!(d.behavior.stuck_markers, ["TODO", "【卡】"]);
+        assert!(!d.behavior.panel_switch_save.enabled && d.behavior.panel_switch_save.panes);
+        assert!(d.behavior.daily_goal == 1000 && d.behavior.session_summary);
+        assert!(d.behavior.design_autosave && d.behavior.restore_session);
+        assert!(!d.behavior.history.enabled && d.behavior.history.keep == 50 && d.behavior.history.max_age_days == 90);
+    }
+
+    #[test]
+    fn test_app_settings_roundtrip() {
+        let mut s = AppSettings::default();
+        s.markdown.editor_font_size = 18.0;
+        s.theme = AppTheme::Light;
+        s.behavior = BehaviorSettings {
+            undo_limit: 500,
+            left_pane: PaneAccept::Markdown,
+            right_pane: PaneAccept::Markdown,
+            fullwidth_punctuation: true,
+            timeline_gap_warning: 8,
+            stuck_markers: vec!["FIXME".to_owned()],
+            auto_update_index: true,
+            panel_switch_save: PanelSwitchSave { enabled: true, objects: false, ..PanelSwitchSave::default() },
+            design_autosave: false,
+            restore_session: false,
+            save_reminder: SaveReminder { enabled: false, minutes: 45 },
+            auto_done: AutoDone { enabled: true, unmark: true, margin_percent: 5 },
+            remember_link_usage: true,
+            word_frequency: WordFrequencySettings { top_n: 10, ..WordFrequencySettings::default() },
+            snapshot: SnapshotSettings { daily: true, keep: 3, ..SnapshotSettings::default() },
+            daily_goal: 2500,
+            session_summary: false,
+            history: HistorySettings { enabled: true, keep: 20, max_age_days: 0 },
+        };
+        let json = serde_json::to_string(&s).unwrap();
+        let d: AppSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(d, s);
     }
 
     // ── Phase 4: Reverse sync helpers ─────────────────────────────────────────
@@ -1182,15 +3149,19 @@ mod tests {
     // ── Phase 4: Search helper ────────────────────────────────────────────────
 
     #[test]
-    fn test_search_dir_finds_matches() {
+    fn test_project_index_search_finds_matches() {
         let dir = std::env::temp_dir().join("qingmo_test_search");
         std::fs::create_dir_all(&dir).unwrap();
         std::fs::write(dir.join("chapter1.md"), "# 第一章\n\n主角走进了森林。").unwrap();
         std::fs::write(dir.join("notes.json"), "{\"title\":\"主角笔记\"}").unwrap();
         std::fs::write(dir.join("ignore.txt"), "主角 should not be found").unwrap();
+        std::fs::create_dir_all(dir.join(".texttool")).unwrap();
+        std::fs::write(dir.join(".texttool").join("copy.md"), "主角 recovery copy").unwrap();
 
-        let mut results = Vec::new();
-        crate::app::search::search_dir(&dir, "主角", &mut results);
+        let index = ProjectIndex::new();
+        index.rescan(&dir);
+        let results = index.search("主角");
+        assert!(!results.iter().any(|r| r.file_path.starts_with(dir.join(".texttool"))));
 
         // Should find matches in .md and .json but not .txt
         assert!(!results.is_empty());
@@ -1198,6 +3169,211 @@ mod tests {
         assert!(paths.iter().any(|p| p.ends_with(".md")));
         assert!(paths.iter().any(|p| p.ends_with(".json")));
         assert!(!paths.iter().any(|p| p.ends_with(".txt")));
+        assert_eq!(results.iter().find(|r| r.file_path.ends_with("chapter1.md")).unwrap().line_no, 3);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_project_index_honors_ignore_files() {
+        let dir = std::env::temp_dir().join("qingmo_test_index_ignore");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("site").join("node_modules").join("pkg")).unwrap();
+        std::fs::create_dir_all(dir.join("Content")).unwrap();
+        std::fs::write(dir.join(".gitignore"), "node_modules/\n*.draft.md\n").unwrap();
+        std::fs::write(dir.join(".texttoolignore"), "!keep.draft.md\n").unwrap();
+        std::fs::write(dir.join("site").join("node_modules").join("pkg").join("README.md"), "主角").unwrap();
+        std::fs::write(dir.join("Content").join("第一章.md"), "主角").unwrap();
+        std::fs::write(dir.join("Content").join("old.draft.md"), "主角").unwrap();
+        std::fs::write(dir.join("Content").join("keep.draft.md"), "主角").unwrap();
+
+        let index = ProjectIndex::new();
+        index.rescan(&dir);
+        let mut names: Vec<_> = index.search("主角").iter()
+            .map(|r| r.file_path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["keep.draft.md", "第一章.md"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_change_log_flushes_to_project() {
+        use text_tool_core::changelog::{read_entries, Change, CHANGELOG_PATH};
+        let dir = std::env::temp_dir().join("qingmo_test_change_log");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Without a project the entries are dropped.
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.record_change(Change::ObjectRemoved { name: "旧人" });
+        app.flush_change_log();
+        assert!(app.change_log_pending.is_empty());
+
+        app.open_project(dir.clone());
+        app.record_change(Change::ObjectAdded { name: "林远", kind: "人物" });
+        app.record_change(Change::ObjectRenamed { from: "林远", to: "林逸" });
+        app.flush_change_log();
+        app.flush_change_log();
+        let entries = read_entries(&dir.join(CHANGELOG_PATH)).unwrap();
+        let descriptions: Vec<_> = entries.iter().map(|e| e.description.as_str()).collect();
+        assert_eq!(descriptions, vec!["新增人物「林远」", "对象「林远」改名为「林逸」"]);
+
+        drop(app);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_open_sample_project() {
+        let dir = std::env::temp_dir().join("qingmo_test_sample_project");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.open_sample_project(dir.clone());
+        assert_eq!(app.project_root.as_deref(), Some(dir.as_path()));
+        let names: Vec<&str> = app.world_objects.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, vec!["林远", "苏晚"]);
+        assert_eq!(app.struct_roots[0].children.len(), 2);
+        assert_eq!(app.left_file.as_ref().unwrap().path, dir.join("Content").join("第一章 雨夜.md"));
+        assert!(app.sample_tour);
+
+        drop(app);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_project_index_search_ignores_case() {
+        let dir = std::env::temp_dir().join("qingmo_test_index_case");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.md"), "Hello World\nhello again").unwrap();
+
+        let index = ProjectIndex::new();
+        index.rescan(&dir);
+        let results = index.search("HELLO");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].line, "Hello World");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_project_index_content_files() {
+        let dir = std::env::temp_dir().join("qingmo_test_index_content_files");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Content").join("第一卷")).unwrap();
+        std::fs::create_dir_all(dir.join("Design")).unwrap();
+        std::fs::write(dir.join("Content").join("第一卷").join("第1章.md"), "## 小节
+# 第一章 雨夜
+正文").unwrap();
+        std::fs::write(dir.join("Content").join("序章.md"), "没有标题").unwrap();
+        std::fs::write(dir.join("Design").join("设定.md"), "# 设定").unwrap();
+        std::fs::write(dir.join("Content").join("数据.json"), "{}").unwrap();
+
+        let index = ProjectIndex::new();
+        index.rescan(&dir);
+        let mut files = index.content_files(&dir.join("Content"));
+        files.sort_by(|a, b| a.rel.cmp(&b.rel));
+        let summary: Vec<_> = files.iter().map(|f| (f.rel.as_str(), f.heading.as_deref(), f.words)).collect();
+        // Only Content/ Markdown; the heading is the first `#` one.
+        assert_eq!(summary, [
+            ("序章.md", None, 4),
+            ("第一卷/第1章.md", Some("第一章 雨夜"), count_words("## 小节\n# 第一章 雨夜\n正文")),
+        ]);
+        let doc = text_tool_core::content_index::update_index_document(None, &files);
+        assert!(doc.contains("- [第一章 雨夜](第一卷/第1章.md)"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_chapter_preview_lookup() {
+        let dir = std::env::temp_dir().join("qingmo_test_chapter_preview");
+        let _ = std::fs::remove_dir_all(&dir);
+        let content = dir.join("Content");
+        std::fs::create_dir_all(content.join("第一卷")).unwrap();
+        let long: Vec<String> = (1..=50).map(|n| format!("第{n}行")).collect();
+        std::fs::write(content.join("第一卷").join("ch01.md"), format!("# 第一章 雨夜\n{}", long.join("\n"))).unwrap();
+        std::fs::write(content.join("尾声.md"), "# 尾声\n\n完。").unwrap();
+
+        let index = ProjectIndex::new();
+        index.rescan(&dir);
+        // By first heading (whitespace-insensitive) or by file stem.
+        let ch1 = content.join("第一卷").join("ch01.md");
+        assert_eq!(index.md_file_titled(&content, "第一章　雨夜"), Some(ch1.clone()));
+        assert_eq!(index.md_file_titled(&content, "CH01"), Some(ch1.clone()));
+        assert_eq!(index.md_file_titled(&content, "尾声 "), Some(content.join("尾声.md")));
+        assert_eq!(index.md_file_titled(&content, "第二章"), None);
+
+        let (head, truncated) = index.text_head(&ch1, 40).unwrap();
+        assert_eq!(head.lines().count(), 40);
+        assert!(head.starts_with("# 第一章 雨夜\n第1行") && head.ends_with("第39行"));
+        assert!(truncated);
+        assert_eq!(index.text_head(&content.join("尾声.md"), 40), Some(("# 尾声\n\n完。".to_owned(), false)));
+        assert_eq!(index.text_head(&content.join("缺失.md"), 40), None);
+
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        vol.children.push(StructNode::new("第一章 雨夜", StructKind::Chapter));
+        let roots = vec![vol];
+        assert_eq!(node_titled(&roots, " 第一章  雨夜").map(|n| n.kind.clone()), Some(StructKind::Chapter));
+        assert!(node_titled(&roots, "第二章").is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_project_index_incremental_rescan() {
+        let dir = std::env::temp_dir().join("qingmo_test_index_incremental");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Content")).unwrap();
+        let ch1 = dir.join("Content").join("第一章.md");
+        let ch2 = dir.join("Content").join("第二章.md");
+        std::fs::write(&ch1, "# 第一章\n\n风起。").unwrap();
+        std::fs::write(&ch2, "# 第二章\n\n云涌。").unwrap();
+
+        let index = ProjectIndex::new();
+        assert_eq!(index.generation(), 0);
+        assert!(!index.is_indexed(&dir));
+        assert_eq!(index.rescan(&dir), 2);
+        assert!(index.is_indexed(&dir));
+        let gen = index.generation();
+        assert_eq!(index.word_count(&ch1), Some(7));
+        assert_eq!(index.headings(&ch2).unwrap()[0].title, "第二章");
+
+        // Nothing changed: nothing re-read, generation unchanged.
+        assert_eq!(index.rescan(&dir), 0);
+        assert_eq!(index.generation(), gen);
+
+        // Only the edited file is re-indexed.
+        std::fs::write(&ch1, "# 第一章\n\n风起云涌，山雨欲来。").unwrap();
+        assert_eq!(index.rescan(&dir), 1);
+        assert!(index.generation() > gen);
+        assert_eq!(index.word_count(&ch1), Some(14));
+
+        // Deleted files drop out of the index.
+        let gen = index.generation();
+        std::fs::remove_file(&ch2).unwrap();
+        assert_eq!(index.rescan(&dir), 0);
+        assert!(index.generation() > gen);
+        assert_eq!(index.word_count(&ch2), None);
+        assert!(index.search("云涌").iter().all(|r| r.file_path == ch1));
+
+        // 待处理标记 come from the cached text.
+        std::fs::write(&ch1, "# 第一章\n\n风起。【卡】\n").unwrap();
+        index.rescan(&dir);
+        let markers = index.markers(&["【卡】".to_owned()]);
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].0, ch1);
+        assert_eq!((markers[0].1[0].line_no, markers[0].1[0].column), (3, 4));
+
+        // So do 注释.
+        std::fs::write(&ch1, "# 第一章\n\n风起。%% 太快\n再铺垫 %%\n").unwrap();
+        index.rescan(&dir);
+        let notes = index.annotations();
+        assert_eq!(notes.len(), 1);
+        assert_eq!((notes[0].1[0].line_no, notes[0].1[0].text.as_str()), (3, "太快\n再铺垫"));
 
         let _ = std::fs::remove_dir_all(&dir);
     }
@@ -1235,6 +3411,7 @@ mod tests {
         assert_eq!(s.auto_save_interval_secs, 60);
         // Files tab hidden by default
         assert!(!s.show_files_tab);
+        assert_eq!(s.deepest_struct_kind, StructKind::Scene);
     }
 
     #[test]
@@ -1245,6 +3422,91 @@ mod tests {
         assert!(s.hide_json);        // should default to true
         assert_eq!(s.tab_size, 2);   // should default to 2
         assert!((s.editor_font_size - 13.0).abs() < 1e-5); // should default to 13.0
+        assert_eq!(s.reading_speed, 400);
+        // Prose wraps, JSON does not.
+        assert!(s.wrap_markdown);
+        assert!(!s.wrap_json);
+    }
+
+    #[test]
+    fn test_wrap_settings_by_file_type() {
+        let mut settings = AppSettings::default();
+        let md = &settings.markdown;
+        assert!(md.wrap_for(Path::new("Content/第一章.md")));
+        assert!(md.wrap_for(Path::new("笔记.txt")));
+        assert!(!md.wrap_for(Path::new("Design/章节结构.json")));
+        // The ↩ toggle overrides the file's type, whichever pane it is in.
+        *settings.markdown.wrap_for_mut(Path::new("Design/世界对象.json")) = true;
+        *settings.markdown.wrap_for_mut(Path::new("Content/第一章.md")) = false;
+        let json = serde_json::to_string(&settings).unwrap();
+        let back: AppSettings = serde_json::from_str(&json).unwrap();
+        assert!(back.markdown.wrap_for(Path::new("Design/伏笔.json")));
+        assert!(!back.markdown.wrap_for(Path::new("Content/第二章.MD")));
+        assert_eq!(back, settings);
+    }
+
+    #[test]
+    fn test_text_stats_cjk_aware() {
+        let stats = TextStats::of("他说：“Hello world！” 2024年");
+        // 他 说 年 + Hello, world, 2024
+        assert_eq!(stats.words, 6);
+        assert_eq!(stats.chars, count_words("他说：“Hello world！” 2024年"));
+        assert_eq!(TextStats::of("  \n"), TextStats { chars: 0, words: 0 });
+        assert!((TextStats::of(&"字".repeat(800)).reading_minutes(400) - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_foreshadow_index_title_collisions() {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        vol.children.push(StructNode::new("序章", StructKind::Chapter));
+        let roots = vec![
+            vol,
+            StructNode::new(" 序章 ", StructKind::Chapter),
+            StructNode::new("第二章", StructKind::Chapter),
+        ];
+        let mut letter = Foreshadow::new("神秘信件");
+        letter.related_chapters = vec!["序章".into(), "序章".into(), "第二章".into()];
+        let mut sword = Foreshadow::new("古剑来历");
+        sword.related_chapters = vec!["序章".into(), "不存在的章".into()];
+
+        let index = ForeshadowIndex::build(&roots, &[letter.clone(), sword.clone()]);
+        // Both nodes titled 序章 (modulo whitespace) share one entry;
+        // the duplicate mention in 神秘信件 counts once.
+        assert_eq!(index.get("序章"), &[0, 1]);
+        assert_eq!(index.get(" 序章 "), &[0, 1]);
+        assert_eq!(index.get("第二章"), &[0]);
+        assert!(index.get("第一卷").is_empty());
+        assert!(index.get("不存在的章").is_empty());
+
+        // Refresh picks up edits to either collection.
+        let mut index = index;
+        letter.related_chapters.clear();
+        index.refresh(&roots, &[letter.clone(), sword.clone()]);
+        assert_eq!(index.get("序章"), &[1]);
+        index.refresh(&roots[..1], &[sword, letter]);
+        assert_eq!(index.get("序章"), &[0]);
+        assert!(index.get("第二章").is_empty());
+    }
+
+    #[test]
+    fn test_timestamps_default_for_old_files() {
+        let obj: WorldObject = serde_json::from_str(
+            r#"{"name":"林枫","kind":"Character","description":"","background":"","links":[]}"#,
+        ).unwrap();
+        assert_eq!((obj.created_at, obj.updated_at), (0, 0));
+
+        let mut node = StructNode::new("第一章", StructKind::Chapter);
+        assert!(node.created_at > 0);
+        node.updated_at = 0;
+        node.touch();
+        assert!(node.updated_at >= node.created_at);
+    }
+
+    #[test]
+    fn test_format_reading_time() {
+        assert_eq!(format_reading_time(0.4), "不到 1 分钟");
+        assert_eq!(format_reading_time(2.6), "约 3 分钟");
+        assert_eq!(format_reading_time(65.0), "约 1 小时 5 分钟");
     }
 
     #[test]
@@ -1254,7 +3516,8 @@ mod tests {
                 "md_settings":{"preview_font_size":14.0,"default_to_preview":false},
                 "last_project":null,"auto_load":false}"#
         ).unwrap();
-        assert_eq!(cfg.theme, AppTheme::Dark); // serde default
+        assert_eq!(cfg.settings.theme, AppTheme::Dark); // serde default
+        assert_eq!(cfg.settings.behavior, BehaviorSettings::default());
     }
 
     #[test]
@@ -1264,8 +3527,8 @@ mod tests {
         std::fs::write(dir.join("chapter1.md"), "hello").unwrap();
         std::fs::write(dir.join("data.json"), "{}").unwrap();
 
-        let node_show = FileNode::from_path_filtered(&dir, false).unwrap();
-        let node_hide = FileNode::from_path_filtered(&dir, true).unwrap();
+        let node_show = FileNode::from_path_filtered(&dir, &TreeFilter::default()).unwrap();
+        let node_hide = FileNode::from_path_filtered(&dir, &TreeFilter { hide_json: true, ..Default::default() }).unwrap();
 
         let show_names: Vec<_> = node_show.children.iter().map(|n| &n.name).collect();
         let hide_names: Vec<_> = node_hide.children.iter().map(|n| &n.name).collect();
@@ -1276,4 +3539,20 @@ mod tests {
 
         let _ = std::fs::remove_dir_all(&dir);
     }
+
+    #[test]
+    fn test_design_data_hash_ignores_formatting() {
+        use design_sync::{data_hash, text_data_hash};
+        let roots = vec![StructNode::new("第一卷", StructKind::Volume)];
+        let pretty = serde_json::to_string_pretty(&roots).unwrap();
+        let compact = serde_json::to_string(&roots).unwrap();
+        assert_ne!(pretty, compact);
+        assert_eq!(text_data_hash::<Vec<StructNode>>(&pretty), Some(data_hash(&roots)));
+        assert_eq!(text_data_hash::<Vec<StructNode>>(&compact), Some(data_hash(&roots)));
+
+        let mut edited = roots.clone();
+        edited[0].title = "第二卷".into();
+        assert_ne!(text_data_hash::<Vec<StructNode>>(&pretty), Some(data_hash(&edited)));
+        assert_eq!(text_data_hash::<Vec<StructNode>>("[{\"title\": "), None);
+    }
 }
@@ -1,555 +1,333 @@
-use egui::Color32;
-use serde::{Deserialize, Serialize};
-
-// ── ObjectKind ────────────────────────────────────────────────────────────────
-
-/// The category of a world object (content element).
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum ObjectKind {
-    Character,  // 人物
-    Scene,      // 场景
-    Location,   // 地点
-    Item,       // 道具
-    Faction,    // 势力
-    Other,      // 其他
-}
+use std::path::{Path, PathBuf};
 
-impl ObjectKind {
-    pub fn label(&self) -> &'static str {
-        match self {
-            ObjectKind::Character => "人物",
-            ObjectKind::Scene     => "场景",
-            ObjectKind::Location  => "地点",
-            ObjectKind::Item      => "道具",
-            ObjectKind::Faction   => "势力",
-            ObjectKind::Other     => "其他",
-        }
-    }
-    pub fn icon(&self) -> &'static str {
-        match self {
-            ObjectKind::Character => "👤",
-            ObjectKind::Scene     => "🎭",
-            ObjectKind::Location  => "📍",
-            ObjectKind::Item      => "🗡",
-            ObjectKind::Faction   => "🏰",
-            ObjectKind::Other     => "⬡",
-        }
-    }
-    pub fn all() -> &'static [ObjectKind] {
-        &[
-            ObjectKind::Character,
-            ObjectKind::Scene,
-            ObjectKind::Location,
-            ObjectKind::Item,
-            ObjectKind::Faction,
-            ObjectKind::Other,
-        ]
-    }
-}
+use text_tool_core::models::{PaneAccept, PanelSwitchSave};
+
+// ── Deferred pane opens ───────────────────────────────────────────────────────
 
-// ── RelationKind ──────────────────────────────────────────────────────────────
-
-/// The semantic type of a link between two elements.
-/// Works for Object↔Object, Object↔StructNode, and StructNode↔StructNode links.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum RelationKind {
-    // Object ↔ Object
-    Friend,     // 友好
-    Enemy,      // 敌对
-    Family,     // 亲属
-    Owns,       // 持有 (持有某道具)
-    LocatedAt,  // 所在 (人物所在地点)
-    BelongsTo,  // 所属 (人物所属势力)
-    // Object ↔ StructNode
-    AppearsIn,  // 出场 (对象在某章节出现)
-    MentionedIn,// 提及 (对象在某章节被提及)
-    // StructNode ↔ StructNode (non-parent cross links)
-    Foreshadows,// 铺垫 (一节为另一节铺垫)
-    Resolves,   // 回收 (一节回收另一节的伏笔)
-    Parallels,  // 并行 (两节并行叙述)
-    // Fallback
-    Other,      // 其他
+/// Editor pane a deferred "open file" action targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaneTarget {
+    Left,
+    Right,
+    /// Whichever pane [`route_to_pane`] picks for the file.
+    Auto,
 }
 
-impl RelationKind {
-    pub fn label(&self) -> &'static str {
-        match self {
-            RelationKind::Friend      => "友好",
-            RelationKind::Enemy       => "敌对",
-            RelationKind::Family      => "亲属",
-            RelationKind::Owns        => "持有",
-            RelationKind::LocatedAt   => "所在",
-            RelationKind::BelongsTo   => "所属",
-            RelationKind::AppearsIn   => "出场",
-            RelationKind::MentionedIn => "提及",
-            RelationKind::Foreshadows => "铺垫",
-            RelationKind::Resolves    => "回收",
-            RelationKind::Parallels   => "并行",
-            RelationKind::Other       => "其他",
-        }
+/// Pane a file opens in when none was named: the pane whose
+/// [`PaneAccept`] takes it, the last-focused pane when both do, and the
+/// left (main) pane when neither does.
+pub fn route_to_pane(path: &Path, left: PaneAccept, right: PaneAccept, last_focused_left: bool) -> PaneTarget {
+    match (left.accepts(path), right.accepts(path)) {
+        (true, true) if !last_focused_left => PaneTarget::Right,
+        (false, true) => PaneTarget::Right,
+        _ => PaneTarget::Left,
     }
-    pub fn all() -> &'static [RelationKind] {
-        &[
-            RelationKind::Friend,
-            RelationKind::Enemy,
-            RelationKind::Family,
-            RelationKind::Owns,
-            RelationKind::LocatedAt,
-            RelationKind::BelongsTo,
-            RelationKind::AppearsIn,
-            RelationKind::MentionedIn,
-            RelationKind::Foreshadows,
-            RelationKind::Resolves,
-            RelationKind::Parallels,
-            RelationKind::Other,
-        ]
+}
+
+/// Record an open request raised while drawing a frame.  The first request
+/// wins, so overlapping signals (double-click plus a context-menu click, or
+/// a click plus Enter) can never open a file twice or in both panes.
+pub fn request_open(
+    slot: &mut Option<(PathBuf, PaneTarget)>,
+    path: PathBuf,
+    target: PaneTarget,
+) {
+    if slot.is_none() {
+        *slot = Some((path, target));
     }
 }
 
-// ── LinkTarget ────────────────────────────────────────────────────────────────
-
-/// What a link points to — another world object (by name) or a structure node
-/// (by title).  Using names rather than integer IDs keeps the data human-readable
-/// and consistent with the rest of the app, which uses names throughout.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum LinkTarget {
-    /// Name of another `WorldObject`.
-    Object(String),
-    /// Title path of a `StructNode` (e.g. "第一卷/第一章").
-    Node(String),
+/// A request for an editor pane to scroll to a given line.
+/// Set via `TextToolApp::request_scroll_to_line` and consumed by `draw_editors`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollRequest {
+    /// Target the left (main) editor pane.
+    pub left: bool,
+    /// 1-based line number to bring to the top of the pane.
+    pub line_no: usize,
 }
 
-impl LinkTarget {
-    pub fn display_name(&self) -> &str {
-        match self {
-            LinkTarget::Object(n) | LinkTarget::Node(n) => n,
-        }
-    }
-    pub fn type_label(&self) -> &'static str {
-        match self {
-            LinkTarget::Object(_) => "对象",
-            LinkTarget::Node(_)   => "章节",
-        }
-    }
+/// Caret and vertical scroll offset of an editor pane: tracked while the
+/// pane is drawn, remembered per file, and seeded back when it reopens.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PaneView {
+    /// Char index of the caret.
+    pub caret: usize,
+    pub scroll: f32,
 }
 
-// ── ObjectLink ────────────────────────────────────────────────────────────────
+// ── View mode toggles ─────────────────────────────────────────────────────────
 
-/// A directed association from a `WorldObject` to another element.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ObjectLink {
-    pub target: LinkTarget,
-    pub kind: RelationKind,
-    pub note: String,
+/// Toggle between list/card views in the Objects panel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectViewMode {
+    List,
+    Card,
 }
 
-// ── WorldObject ───────────────────────────────────────────────────────────────
-
-/// A unified "content element": character, scene, location, item, faction, …
-/// Replaces the old `Character` struct and extends it with a `kind` discriminant
-/// and a generalised `links` list that can point to other objects *or* to
-/// structure nodes.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorldObject {
-    pub name: String,
-    pub kind: ObjectKind,
-    /// Core traits / description (what was `traits` in the old Character).
-    pub description: String,
-    pub background: String,
-    pub links: Vec<ObjectLink>,
+/// Tabs of the selected-object editor in the Objects panel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectEditorTab {
+    Details,
+    /// Where the object appears across the structure's chapters.
+    Timeline,
 }
 
-impl WorldObject {
-    pub fn new(name: &str, kind: ObjectKind) -> Self {
-        WorldObject {
-            name: name.to_owned(),
-            kind,
-            description: String::new(),
-            background: String::new(),
-            links: vec![],
-        }
-    }
-    pub fn icon(&self) -> &'static str { self.kind.icon() }
+/// Toggle between tree/timeline/kanban views in the Structure panel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StructViewMode {
+    Tree,
+    Timeline,
+    /// Leaf nodes as cards in 未开始 / 草稿中 / 已完成 columns.
+    Kanban,
+}
+
+/// Toggle between filesystem view and chapter-tree view in the Novel panel left sidebar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileTreeMode {
+    /// Show the raw project filesystem (folders and files).
+    Files,
+    /// Show the chapter structure tree (from struct_roots). Each leaf chapter
+    /// can be clicked to open its associated `.md` file in the editor.
+    Chapters,
+    /// Show the headings of the file open in the left editor. Clicking a
+    /// heading scrolls the editor to it.
+    Headings,
+    /// Show the 待处理标记 of the left file and of the whole project.
+    /// Clicking one opens its file at that line.
+    Markers,
+    /// Show the 注释 (`%% … %%`) of the left file, with a button resolving
+    /// each, and of the other project files.  Clicking one jumps to it.
+    Annotations,
 }
 
-// ── ChapterTag ────────────────────────────────────────────────────────────────
+// ── Diagram export ────────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum ChapterTag {
-    Normal,     // 普通
-    Climax,     // 高潮
-    Foreshadow, // 伏笔
-    Transition, // 过渡
+/// Mermaid diagrams offered under 工具.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MermaidExport {
+    /// Object ↔ object links of the world objects.
+    Relations,
+    /// The structure tree with 铺垫 / 回收 cross-links.
+    Structure,
 }
 
-impl ChapterTag {
-    pub fn label(&self) -> &'static str {
+impl MermaidExport {
+    pub fn label(self) -> &'static str {
         match self {
-            ChapterTag::Normal     => "普通",
-            ChapterTag::Climax     => "高潮",
-            ChapterTag::Foreshadow => "伏笔",
-            ChapterTag::Transition => "过渡",
+            MermaidExport::Relations => "导出关系图 (Mermaid)",
+            MermaidExport::Structure => "导出章节结构 (Mermaid)",
         }
     }
-    pub fn all() -> &'static [ChapterTag] {
-        &[ChapterTag::Normal, ChapterTag::Climax, ChapterTag::Foreshadow, ChapterTag::Transition]
-    }
-    pub fn color(&self) -> Color32 {
+    /// File written under `Design/`.
+    pub fn file_name(self) -> &'static str {
         match self {
-            ChapterTag::Normal     => Color32::from_gray(160),
-            ChapterTag::Climax     => Color32::from_rgb(220, 80, 80),
-            ChapterTag::Foreshadow => Color32::from_rgb(80, 160, 220),
-            ChapterTag::Transition => Color32::from_rgb(120, 190, 120),
+            MermaidExport::Relations => "关系图.mmd",
+            MermaidExport::Structure => "章节结构图.mmd",
         }
     }
 }
 
-// ── StructKind ────────────────────────────────────────────────────────────────
+// ── Settings window ───────────────────────────────────────────────────────────
 
-/// The hierarchical level of a structure node.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum StructKind {
-    Outline,  // 总纲
-    Volume,   // 卷
-    Chapter,  // 章
-    Section,  // 节
+/// Tabs of the settings window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingsTab {
+    Editor,
+    Preview,
+    Behavior,
+    Shortcuts,
+    Llm,
 }
 
-impl StructKind {
-    pub fn label(&self) -> &'static str {
-        match self {
-            StructKind::Outline => "总纲",
-            StructKind::Volume  => "卷",
-            StructKind::Chapter => "章",
-            StructKind::Section => "节",
-        }
-    }
-    pub fn icon(&self) -> &'static str {
-        match self {
-            StructKind::Outline => "📋",
-            StructKind::Volume  => "📚",
-            StructKind::Chapter => "📖",
-            StructKind::Section => "📑",
-        }
-    }
-    pub fn all() -> &'static [StructKind] {
-        &[StructKind::Outline, StructKind::Volume, StructKind::Chapter, StructKind::Section]
+impl SettingsTab {
+    pub fn all() -> [SettingsTab; 5] {
+        [
+            SettingsTab::Editor,
+            SettingsTab::Preview,
+            SettingsTab::Behavior,
+            SettingsTab::Shortcuts,
+            SettingsTab::Llm,
+        ]
     }
-    /// The natural child kind when adding a child to this level.
-    pub fn default_child_kind(&self) -> StructKind {
+    pub fn label(self) -> &'static str {
         match self {
-            StructKind::Outline => StructKind::Volume,
-            StructKind::Volume  => StructKind::Chapter,
-            StructKind::Chapter => StructKind::Section,
-            StructKind::Section => StructKind::Section,
+            SettingsTab::Editor    => "编辑器",
+            SettingsTab::Preview   => "预览",
+            SettingsTab::Behavior  => "行为",
+            SettingsTab::Shortcuts => "快捷键",
+            SettingsTab::Llm       => "LLM",
         }
     }
 }
 
-// ── NodeLink ──────────────────────────────────────────────────────────────────
-
-/// A non-parent cross-link between two structure nodes (e.g. a chapter that
-/// foreshadows another chapter many levels away).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NodeLink {
-    /// Title of the target node.
-    pub target_title: String,
-    pub kind: RelationKind,
-    pub note: String,
-}
+// ── Panel IDs ─────────────────────────────────────────────────────────────────
 
-// ── StructNode ────────────────────────────────────────────────────────────────
-
-/// A hierarchical structure element (总纲 / 卷 / 章 / 节).
-/// Replaces the old flat `Chapter` and adds nesting, a `kind` discriminant,
-/// a list of linked world-objects, and cross-node links.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StructNode {
-    pub title: String,
-    pub kind: StructKind,
-    pub tag: ChapterTag,
-    pub summary: String,
-    pub done: bool,
-    /// Nested children (e.g. a Volume contains Chapters).
-    pub children: Vec<StructNode>,
-    /// Names of `WorldObject`s associated with this node.
-    pub linked_objects: Vec<String>,
-    /// Non-parent cross-links to other structure nodes.
-    pub node_links: Vec<NodeLink>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Panel {
+    Novel,
+    /// 世界对象设计 (人物 / 场景 / 地点 / 道具 / 势力)
+    Objects,
+    /// 章节结构设计 (总纲 / 卷 / 章 / 节)
+    Structure,
+    Llm,
 }
 
-impl StructNode {
-    pub fn new(title: &str, kind: StructKind) -> Self {
-        StructNode {
-            title: title.to_owned(),
-            kind,
-            tag: ChapterTag::Normal,
-            summary: String::new(),
-            done: false,
-            children: vec![],
-            linked_objects: vec![],
-            node_links: vec![],
+impl Panel {
+    pub fn icon(self) -> &'static str {
+        match self {
+            Panel::Novel     => "📝",
+            Panel::Objects   => "🌐",
+            Panel::Structure => "纲",
+            Panel::Llm       => "智",
         }
     }
-
-    /// Total number of leaf nodes (nodes without children).
-    pub fn leaf_count(&self) -> usize {
-        if self.children.is_empty() {
-            1
-        } else {
-            self.children.iter().map(|c| c.leaf_count()).sum()
+    pub fn label(self) -> &'static str {
+        match self {
+            Panel::Novel     => "小说编辑",
+            Panel::Objects   => "世界对象",
+            Panel::Structure => "章节结构",
+            Panel::Llm       => "LLM辅助",
         }
     }
-
-    /// Number of done leaf nodes.
-    pub fn done_count(&self) -> usize {
-        if self.children.is_empty() {
-            if self.done { 1 } else { 0 }
-        } else {
-            self.children.iter().map(|c| c.done_count()).sum()
+    /// Name saved in the config for the last session.
+    pub fn key(self) -> &'static str {
+        match self {
+            Panel::Novel     => "novel",
+            Panel::Objects   => "objects",
+            Panel::Structure => "structure",
+            Panel::Llm       => "llm",
         }
     }
-}
-
-// ── Tree helpers ──────────────────────────────────────────────────────────────
-
-/// Navigate immutably into a tree of `StructNode`s by index path.
-#[allow(dead_code)]
-pub fn node_at<'a>(roots: &'a [StructNode], path: &[usize]) -> Option<&'a StructNode> {
-    if path.is_empty() { return None; }
-    let node = roots.get(path[0])?;
-    if path.len() == 1 { Some(node) } else { node_at(&node.children, &path[1..]) }
-}
-
-/// Navigate mutably into a tree of `StructNode`s by index path.
-pub fn node_at_mut<'a>(roots: &'a mut [StructNode], path: &[usize]) -> Option<&'a mut StructNode> {
-    if path.is_empty() { return None; }
-    if path.len() == 1 {
-        return roots.get_mut(path[0]);
+    pub fn from_key(key: &str) -> Option<Panel> {
+        [Panel::Novel, Panel::Objects, Panel::Structure, Panel::Llm].into_iter().find(|p| p.key() == key)
     }
-    let node = roots.get_mut(path[0])?;
-    node_at_mut(&mut node.children, &path[1..])
 }
 
-/// Collect the flat title of every node in the tree (depth-first).
-pub fn all_node_titles(roots: &[StructNode]) -> Vec<String> {
-    let mut out = Vec::new();
-    fn walk(nodes: &[StructNode], out: &mut Vec<String>) {
-        for n in nodes {
-            out.push(n.title.clone());
-            walk(&n.children, out);
+// ── Keyboard focus regions (F6) ───────────────────────────────────────────────
+
+/// A major region of a panel; F6 / Ctrl+` moves keyboard focus between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusRegion {
+    /// 导航: the file tree / chapter tree / heading list.
+    Navigator,
+    LeftEditor,
+    RightEditor,
+    /// 对象参考 sidebar of 小说编辑.
+    ObjectRefs,
+    ObjectList,
+    ObjectDetail,
+    StructTree,
+    NodeEditor,
+    LlmConfig,
+    LlmWork,
+}
+
+impl FocusRegion {
+    /// Regions of `panel` in cycle order, left to right on screen.  The
+    /// right editor takes part only while a file is open there.
+    pub fn cycle(panel: Panel, right_open: bool) -> Vec<FocusRegion> {
+        use FocusRegion::*;
+        match panel {
+            Panel::Novel if right_open => vec![Navigator, LeftEditor, RightEditor, ObjectRefs],
+            Panel::Novel => vec![Navigator, LeftEditor, ObjectRefs],
+            Panel::Objects => vec![ObjectList, ObjectDetail],
+            Panel::Structure => vec![StructTree, NodeEditor],
+            Panel::Llm => vec![LlmConfig, LlmWork],
         }
     }
-    walk(roots, &mut out);
-    out
-}
 
-// ── Milestone ─────────────────────────────────────────────────────────────────
-
-/// A project milestone – a named, describable, completable target for the novel.
-/// Examples: "完成第一章草稿", "10万字初稿", "第一阶段验收".
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Milestone {
-    pub name: String,
-    pub description: String,
-    pub completed: bool,
-}
-
-impl Milestone {
-    pub fn new(name: &str) -> Self {
-        Milestone {
-            name: name.to_owned(),
-            description: String::new(),
-            completed: false,
-        }
+    /// The region after `current` (before it with `backward`), wrapping
+    /// around.  From no region, or one not in the cycle (the panel changed,
+    /// the right pane closed), forward starts at the first region and
+    /// backward at the last.
+    pub fn step(panel: Panel, right_open: bool, current: Option<FocusRegion>, backward: bool) -> FocusRegion {
+        let cycle = Self::cycle(panel, right_open);
+        let n = cycle.len();
+        let next = match current.and_then(|c| cycle.iter().position(|&r| r == c)) {
+            Some(i) if backward => (i + n - 1) % n,
+            Some(i) => (i + 1) % n,
+            None if backward => n - 1,
+            None => 0,
+        };
+        cycle[next]
     }
-}
 
-// ── Foreshadow ────────────────────────────────────────────────────────────────
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Foreshadow {
-    pub name: String,
-    pub description: String,
-    pub related_chapters: Vec<String>,
-    pub resolved: bool,
-}
-
-impl Foreshadow {
-    pub fn new(name: &str) -> Self {
-        Foreshadow {
-            name: name.to_owned(),
-            description: String::new(),
-            related_chapters: vec![],
-            resolved: false,
-        }
+    pub fn is_editor(self) -> bool {
+        matches!(self, FocusRegion::LeftEditor | FocusRegion::RightEditor)
     }
-}
 
-// ── LLM config ────────────────────────────────────────────────────────────────
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LlmConfig {
-    pub model_path: String,
-    pub api_url: String,
-    pub temperature: f32,
-    pub max_tokens: u32,
-    pub use_local: bool,
-    /// Optional system prompt sent before the user message (OpenAI / llama.cpp).
-    pub system_prompt: String,
-}
-
-// ── App theme ─────────────────────────────────────────────────────────────────
-
-/// UI colour theme preference.
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
-pub enum AppTheme {
-    /// Follow the operating-system dark/light preference (egui default).
-    #[default]
-    Dark,
-    Light,
-}
+    /// Lists that ↑ / ↓ move the selection in while they have focus.
+    pub fn is_list(self) -> bool {
+        matches!(self, FocusRegion::ObjectRefs | FocusRegion::ObjectList | FocusRegion::StructTree)
+    }
 
-impl AppTheme {
+    /// Name shown in the status bar when focus moves here.
     pub fn label(self) -> &'static str {
         match self {
-            AppTheme::Dark  => "暗色",
-            AppTheme::Light => "亮色",
+            FocusRegion::Navigator    => "导航",
+            FocusRegion::LeftEditor   => "左侧编辑区",
+            FocusRegion::RightEditor  => "右侧编辑区",
+            FocusRegion::ObjectRefs   => "对象参考",
+            FocusRegion::ObjectList   => "对象列表",
+            FocusRegion::ObjectDetail => "对象详情",
+            FocusRegion::StructTree   => "章节结构",
+            FocusRegion::NodeEditor   => "节点编辑",
+            FocusRegion::LlmConfig    => "模型配置",
+            FocusRegion::LlmWork      => "LLM 工作区",
         }
     }
-    pub fn all() -> &'static [AppTheme] {
-        &[AppTheme::Dark, AppTheme::Light]
-    }
 }
 
-// ── Markdown rendering settings ───────────────────────────────────────────────
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MarkdownSettings {
-    /// Base font size used when rendering the preview.
-    pub preview_font_size: f32,
-    /// When a Markdown file is opened, default to preview mode.
-    pub default_to_preview: bool,
-    /// Hide `.json` files from the project file tree by default.
-    /// JSON files are internal data files; users primarily write Markdown.
-    #[serde(default = "default_true")]
-    pub hide_json: bool,
-    /// Number of spaces inserted when Tab is pressed in the Markdown editor.
-    #[serde(default = "default_tab_size")]
-    pub tab_size: u8,
-    /// Automatically extract Markdown headings into the Structure panel when
-    /// a file is saved (Ctrl+S).
-    #[serde(default)]
-    pub auto_extract_structure: bool,
-    /// Font size for the plain-text Markdown editor (independent of preview).
-    #[serde(default = "default_editor_font_size")]
-    pub editor_font_size: f32,
-    /// Auto-save interval in seconds. 0 = disabled.
-    #[serde(default = "default_auto_save_interval")]
-    pub auto_save_interval_secs: u32,
-    /// Show the "Files" tab in the navigation sidebar.
-    /// Off by default — users primarily navigate via the Chapter tree.
-    /// Can be enabled in Settings.
-    #[serde(default)]
-    pub show_files_tab: bool,
+/// The item of `order` one step from `current` (up or down), stopping at
+/// either end.  Without a current item, down starts at the first and up at
+/// the last.
+pub fn list_step<T: PartialEq + Clone>(order: &[T], current: Option<&T>, up: bool) -> Option<T> {
+    let i = match current.and_then(|c| order.iter().position(|x| x == c)) {
+        Some(i) if up => i.saturating_sub(1),
+        Some(i) => (i + 1).min(order.len().checked_sub(1)?),
+        None if up => order.len().checked_sub(1)?,
+        None => 0,
+    };
+    order.get(i).cloned()
 }
 
-fn default_true() -> bool { true }
-fn default_tab_size() -> u8 { 2 }
-fn default_editor_font_size() -> f32 { 13.0 }
-fn default_auto_save_interval() -> u32 { 60 }
-
-impl Default for MarkdownSettings {
-    fn default() -> Self {
-        MarkdownSettings {
-            preview_font_size: 14.0,
-            default_to_preview: false,
-            hide_json: true,
-            tab_size: 2,
-            auto_extract_structure: false,
-            editor_font_size: 13.0,
-            auto_save_interval_secs: 60,
-            show_files_tab: false,
-        }
-    }
-}
+// ── Panel transitions ─────────────────────────────────────────────────────────
 
-// ── App configuration (persisted to disk) ─────────────────────────────────────
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppConfig {
-    pub llm_config: LlmConfig,
-    pub md_settings: MarkdownSettings,
-    pub last_project: Option<String>,
-    /// Whether to automatically load JSON/MD data files when opening a project.
-    pub auto_load: bool,
-    /// UI colour theme.
-    #[serde(default)]
-    pub theme: AppTheme,
+/// The panel change since the last frame, as `(from, to)`.
+pub fn panel_transition(last: Panel, now: Panel) -> Option<(Panel, Panel)> {
+    (last != now).then_some((last, now))
 }
 
-// ── Full-text search result ────────────────────────────────────────────────────
-
-#[derive(Debug, Clone)]
-pub struct SearchResult {
-    pub file_path: std::path::PathBuf,
-    pub line_no: usize,
-    pub line: String,
+/// One flag per kind of data 切换面板时自动保存 can write.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SaveSet {
+    pub panes: bool,
+    pub objects: bool,
+    pub structure: bool,
+    pub foreshadows: bool,
+    pub milestones: bool,
 }
 
-// ── View mode toggles ─────────────────────────────────────────────────────────
-
-/// Toggle between list/card views in the Objects panel.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum ObjectViewMode {
-    List,
-    Card,
-}
-
-/// Toggle between tree/timeline views in the Structure panel.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum StructViewMode {
-    Tree,
-    Timeline,
-}
-
-/// Toggle between filesystem view and chapter-tree view in the Novel panel left sidebar.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum FileTreeMode {
-    /// Show the raw project filesystem (folders and files).
-    Files,
-    /// Show the chapter structure tree (from struct_roots). Each leaf chapter
-    /// can be clicked to open its associated `.md` file in the editor.
-    Chapters,
-}
-
-// ── Panel IDs ─────────────────────────────────────────────────────────────────
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Panel {
-    Novel,
-    /// 世界对象设计 (人物 / 场景 / 地点 / 道具 / 势力)
-    Objects,
-    /// 章节结构设计 (总纲 / 卷 / 章 / 节)
-    Structure,
-    Llm,
+impl SaveSet {
+    pub fn any(self) -> bool {
+        self.panes || self.objects || self.structure || self.foreshadows || self.milestones
+    }
 }
 
-impl Panel {
-    pub fn icon(self) -> &'static str {
-        match self {
-            Panel::Novel     => "📝",
-            Panel::Objects   => "🌐",
-            Panel::Structure => "纲",
-            Panel::Llm       => "智",
-        }
+/// What to save when leaving `from`: the `dirty` data that belongs to the
+/// panel being left (panes for 小说编辑, design data for the design panels)
+/// and whose category `opts` ticks.
+pub fn panel_switch_saves(from: Panel, opts: &PanelSwitchSave, dirty: SaveSet) -> SaveSet {
+    if !opts.enabled {
+        return SaveSet::default();
     }
-    pub fn label(self) -> &'static str {
-        match self {
-            Panel::Novel     => "小说编辑",
-            Panel::Objects   => "世界对象",
-            Panel::Structure => "章节结构",
-            Panel::Llm       => "LLM辅助",
-        }
+    let design = matches!(from, Panel::Objects | Panel::Structure);
+    SaveSet {
+        panes: from == Panel::Novel && opts.panes && dirty.panes,
+        objects: design && opts.objects && dirty.objects,
+        structure: design && opts.structure && dirty.structure,
+        foreshadows: design && opts.foreshadows && dirty.foreshadows,
+        milestones: design && opts.milestones && dirty.milestones,
     }
 }
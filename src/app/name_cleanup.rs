@@ -0,0 +1,75 @@
+use egui::{Color32, Context, RichText};
+
+use super::{normalize_all_names, TextToolApp};
+
+// ── 规范化名称: one-time cleanup of stray whitespace in names ─────────────────
+
+impl TextToolApp {
+    /// Normalize every object name, node title and foreshadow name plus the
+    /// references to them, save what changed and open the report.
+    pub(super) fn normalize_names(&mut self) {
+        let changes = normalize_all_names(
+            &mut self.world_objects,
+            &mut self.struct_roots,
+            &mut self.foreshadows,
+        );
+        if changes.is_empty() {
+            self.status = "所有名称均已规范，无需修改".to_owned();
+            return;
+        }
+        let changed = |place: &[&str]| changes.iter().any(|c| place.contains(&c.place));
+        let mut results = Vec::new();
+        if changed(&["对象", "对象关系"]) {
+            results.push(self.sync_world_objects_to_json());
+        }
+        if changed(&["章节", "关联对象", "跨节点关联"]) {
+            results.push(self.sync_struct_to_json());
+            self.struct_json_snapshot = serde_json::to_string(&self.struct_roots).ok();
+        }
+        if changed(&["伏笔", "伏笔关联章节"]) {
+            results.push(self.sync_foreshadows());
+        }
+        match results.into_iter().find_map(Result::err) {
+            Some(e) => self.report(e),
+            None => self.status = format!("已规范化 {} 处名称", changes.len()),
+        }
+        self.name_cleanup_report = Some(changes);
+    }
+
+    /// What 规范化名称 rewrote, one row per name.
+    pub(super) fn draw_name_cleanup_report(&mut self, ctx: &Context) {
+        let Some(changes) = &self.name_cleanup_report else {
+            return;
+        };
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new("规范化名称")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(420.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("已去除首尾空白、合并连续空格（含全角空格），共修改 {} 处：", changes.len()));
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    egui::Grid::new("name_cleanup_grid").striped(true).show(ui, |ui| {
+                        for change in changes {
+                            ui.label(RichText::new(change.place).small().color(Color32::from_gray(150)));
+                            ui.label(format!("「{}」", change.before));
+                            ui.label("→");
+                            ui.label(format!("「{}」", change.after));
+                            ui.end_row();
+                        }
+                    });
+                });
+                ui.add_space(6.0);
+                if ui.button("关闭").clicked() {
+                    close = true;
+                }
+            });
+        if !open || close {
+            self.name_cleanup_report = None;
+        }
+    }
+}
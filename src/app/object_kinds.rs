@@ -0,0 +1,139 @@
+use egui::{Color32, Context, RichText};
+use text_tool_core::models::object_kinds;
+
+use super::{AppError, ObjectKind, ObjectKindsDialog, TextToolApp};
+
+// ── 自定义对象类型 ────────────────────────────────────────────────────────────
+//
+// Besides the six built-in kinds a project can define its own (功法, 种族,
+// …).  The registry in `Design/项目配置.json` holds each one's icon and
+// color; objects store only the name, so a kind removed from the registry
+// still reads back and shows with the generic icon.
+
+/// Color of the new-kind form when the window opens.
+const DEFAULT_KIND_COLOR: [u8; 3] = [150, 170, 210];
+
+impl TextToolApp {
+    /// Kinds offered by the filter chips and kind pickers.
+    pub(super) fn object_kind_list(&self) -> Vec<ObjectKind> {
+        object_kinds(&self.project_config.object_kinds, &self.world_objects)
+    }
+
+    /// `icon label` of `kind`; custom kinds in their registry color.
+    pub(super) fn kind_text(&self, kind: &ObjectKind) -> RichText {
+        let text = RichText::new(format!("{} {}", self.project_config.kind_icon(kind), kind.label()));
+        match self.project_config.custom_kind(kind) {
+            Some(custom) => text.color(custom.color32()),
+            None => text,
+        }
+    }
+
+    /// [`TextToolApp::object_kind_list`] with each kind's [`TextToolApp::kind_text`].
+    pub(super) fn kind_choices(&self) -> Vec<(ObjectKind, RichText)> {
+        self.object_kind_list().into_iter()
+            .map(|k| {
+                let text = self.kind_text(&k);
+                (k, text)
+            })
+            .collect()
+    }
+
+    pub(super) fn open_object_kinds(&mut self) {
+        self.object_kinds_dialog = Some(ObjectKindsDialog {
+            name: String::new(),
+            icon: String::new(),
+            color: DEFAULT_KIND_COLOR,
+            dirty: false,
+        });
+    }
+
+    pub(super) fn draw_object_kinds_dialog(&mut self, ctx: &Context) {
+        let Some(dlg) = &mut self.object_kinds_dialog else { return };
+        let mut open = true;
+        let mut add = false;
+        let mut remove: Option<usize> = None;
+        let usage = |name: &str| self.world_objects.iter()
+            .filter(|o| matches!(&o.kind, ObjectKind::Custom(n) if n == name))
+            .count();
+        let counts: Vec<usize> = self.project_config.object_kinds.iter().map(|c| usage(&c.name)).collect();
+        let kinds = &mut self.project_config.object_kinds;
+
+        egui::Window::new("管理对象类型")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                let builtin: Vec<String> = ObjectKind::all().iter()
+                    .map(|k| format!("{} {}", k.icon(), k.label()))
+                    .collect();
+                ui.label(RichText::new(format!("内置类型：{}", builtin.join("  "))).small().color(Color32::from_gray(150)));
+                ui.separator();
+                if kinds.is_empty() {
+                    ui.label(RichText::new("尚无自定义类型").color(Color32::GRAY));
+                }
+                egui::Grid::new("object_kinds_grid").num_columns(5).spacing([8.0, 4.0]).show(ui, |ui| {
+                    for (i, kind) in kinds.iter_mut().enumerate() {
+                        let resp = ui.add(egui::TextEdit::singleline(&mut kind.icon).desired_width(28.0));
+                        if resp.changed() {
+                            dlg.dirty = true;
+                        }
+                        ui.label(RichText::new(&kind.name).strong().color(kind.color32()));
+                        if ui.color_edit_button_srgb(&mut kind.color).changed() {
+                            dlg.dirty = true;
+                        }
+                        ui.label(RichText::new(format!("{} 个对象", counts[i])).small().color(Color32::from_gray(150)));
+                        let delete = ui.add_enabled(counts[i] == 0, egui::Button::new("🗑").small())
+                            .on_hover_text("删除此类型")
+                            .on_disabled_hover_text("仍有对象使用此类型；可先用「批量修改类型」改为其他类型");
+                        if delete.clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut dlg.icon).hint_text("图标").desired_width(28.0));
+                    let resp = ui.add(egui::TextEdit::singleline(&mut dlg.name).hint_text("新类型名称，如 功法").desired_width(140.0));
+                    ui.color_edit_button_srgb(&mut dlg.color);
+                    if ui.button("➕ 添加").clicked()
+                        || (resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+                    {
+                        add = true;
+                    }
+                });
+            });
+
+        let mut save = false;
+        if add {
+            match self.project_config.add_object_kind(&dlg.name, &dlg.icon, dlg.color) {
+                Ok(()) => {
+                    self.status = format!("已添加对象类型「{}」", dlg.name.trim());
+                    dlg.name.clear();
+                    dlg.icon.clear();
+                    save = true;
+                }
+                Err(message) => {
+                    self.report(AppError::Validation(message));
+                }
+            }
+        }
+        if let Some(i) = remove {
+            let removed = self.project_config.object_kinds.remove(i);
+            if self.obj_kind_filter == Some(ObjectKind::Custom(removed.name.clone())) {
+                self.obj_kind_filter = None;
+            }
+            self.status = format!("已删除对象类型「{}」", removed.name);
+            save = true;
+        }
+        let Some(dlg) = &self.object_kinds_dialog else { return };
+        if !open {
+            save |= dlg.dirty;
+            self.object_kinds_dialog = None;
+        }
+        if save {
+            self.save_project_config();
+        }
+    }
+}
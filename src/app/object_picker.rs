@@ -0,0 +1,320 @@
+use egui::{Color32, Context, Key, RichText};
+
+use super::{
+    node_at, node_at_mut, push_unique_names, same_title, ObjectKind, ObjectPicker, Panel, ProjectConfig, TextToolApp,
+    WorldObject,
+};
+use text_tool_core::changelog::Change;
+
+// ── 关联的世界对象: chips, hover cards and 选择对象 popup ─────────────────────
+
+/// Text colour of a chip whose name matches no world object.
+const MISSING_COLOR: Color32 = Color32::from_rgb(230, 150, 60);
+/// Characters of the description shown in the hover card.
+const CARD_DESCRIPTION_CHARS: usize = 200;
+
+/// What the user did on the linked-object chips of a node.
+pub(super) enum ChipAction {
+    Remove(usize),
+    /// Move chip `usize` one place to the front (`true`) or back.
+    Move(usize, bool),
+    /// Create a world object of this kind named like chip `usize`.
+    Create(usize, ObjectKind),
+}
+
+/// An object name clicked outside the Objects panel, applied by
+/// [`TextToolApp::apply_object_nav`] before the panels are drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectNav {
+    /// Select the object at this index.
+    Select(usize),
+    /// No object has this name: create one and select it.
+    Create(String),
+}
+
+/// Index of the object named `name`, compared with [`same_title`].
+pub(super) fn find_object(objects: &[WorldObject], name: &str) -> Option<usize> {
+    objects.iter().position(|o| same_title(&o.name, name))
+}
+
+/// The first `max` characters of `text`, trimmed, with `…` when cut.
+fn excerpt(text: &str, max: usize) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_owned(),
+    }
+}
+
+/// `text` as a clickable object name.  Hovering shows the card of the
+/// object named `name` — icon, kind, the start of the description and the
+/// tags — or 未找到对象，点击创建; a click records where to go in `nav`.
+pub(super) fn object_hover_ui(
+    ui: &mut egui::Ui,
+    text: RichText,
+    name: &str,
+    objects: &[WorldObject],
+    config: &ProjectConfig,
+    nav: &mut Option<ObjectNav>,
+) -> egui::Response {
+    let found = find_object(objects, name);
+    let resp = ui.add(egui::Label::new(text).sense(egui::Sense::click()))
+        .on_hover_cursor(egui::CursorIcon::PointingHand)
+        .on_hover_ui(|ui| {
+            ui.set_max_width(320.0);
+            let Some(obj) = found.map(|i| &objects[i]) else {
+                ui.label(RichText::new(name).strong());
+                ui.label(RichText::new("未找到对象，点击创建").color(MISSING_COLOR));
+                return;
+            };
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(format!("{} {}", config.kind_icon(&obj.kind), obj.name)).strong());
+                let kind = RichText::new(obj.kind.label()).small();
+                match config.custom_kind(&obj.kind) {
+                    Some(custom) => ui.label(kind.color(custom.color32())),
+                    None => ui.label(kind.color(Color32::from_gray(150))),
+                };
+            });
+            if obj.description.trim().is_empty() {
+                ui.label(RichText::new("（暂无描述）").small().color(Color32::GRAY));
+            } else {
+                ui.label(excerpt(&obj.description, CARD_DESCRIPTION_CHARS));
+            }
+            if !obj.tags.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    for tag in &obj.tags {
+                        egui::Frame::none()
+                            .fill(Color32::from_gray(50))
+                            .rounding(10.0)
+                            .inner_margin(egui::Margin::symmetric(6.0, 1.0))
+                            .show(ui, |ui| ui.label(RichText::new(tag).small()));
+                    }
+                });
+            }
+            ui.label(RichText::new("点击在世界对象面板中打开").small().color(Color32::from_gray(140)));
+        });
+    if resp.clicked() {
+        *nav = Some(match found {
+            Some(i) => ObjectNav::Select(i),
+            None => ObjectNav::Create(name.to_owned()),
+        });
+    }
+    resp
+}
+
+/// `names` as removable chips with the [`object_hover_ui`] card.  Names
+/// matching no object are drawn orange with a 创建该对象 menu offering
+/// `kinds`.
+pub(super) fn draw_object_chips(
+    ui: &mut egui::Ui,
+    names: &[String],
+    objects: &[WorldObject],
+    config: &ProjectConfig,
+    kinds: &[(ObjectKind, RichText)],
+    nav: &mut Option<ObjectNav>,
+) -> Option<ChipAction> {
+    let mut action = None;
+    let last = names.len().saturating_sub(1);
+    ui.horizontal_wrapped(|ui| {
+        for (i, name) in names.iter().enumerate() {
+            let icon = find_object(objects, name).map(|o| config.kind_icon(&objects[o].kind));
+            let fill = if icon.is_some() { Color32::from_gray(50) } else { Color32::from_rgb(80, 55, 25) };
+            egui::Frame::none()
+                .fill(fill)
+                .rounding(10.0)
+                .inner_margin(egui::Margin::symmetric(6.0, 1.0))
+                .show(ui, |ui| {
+                    ui.spacing_mut().item_spacing.x = 4.0;
+                    let text = match icon {
+                        Some(icon) => RichText::new(format!("{icon} {name}")),
+                        None => RichText::new(name).color(MISSING_COLOR),
+                    };
+                    let label = object_hover_ui(ui, text, name, objects, config, nav);
+                    label.context_menu(|ui| {
+                        if ui.add_enabled(i > 0, egui::Button::new("← 前移")).clicked() {
+                            action = Some(ChipAction::Move(i, true));
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(i < last, egui::Button::new("→ 后移")).clicked() {
+                            action = Some(ChipAction::Move(i, false));
+                            ui.close_menu();
+                        }
+                    });
+                    if icon.is_none() {
+                        ui.menu_button(RichText::new("创建该对象").small(), |ui| {
+                            for (k, text) in kinds {
+                                if ui.button(text.clone()).clicked() {
+                                    action = Some(ChipAction::Create(i, k.clone()));
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    }
+                    if ui.small_button("×").on_hover_text("移除关联").clicked() {
+                        action = Some(ChipAction::Remove(i));
+                    }
+                });
+        }
+    });
+    action
+}
+
+impl TextToolApp {
+    /// Follow the object name clicked last frame: select it in the Objects
+    /// panel, creating it first (as the quick-add form's kind) when missing.
+    pub(super) fn apply_object_nav(&mut self) {
+        let Some(nav) = self.object_nav.take() else { return };
+        let idx = match nav {
+            ObjectNav::Select(idx) if idx < self.world_objects.len() => idx,
+            ObjectNav::Select(_) => return,
+            ObjectNav::Create(name) => {
+                let kind = self.new_obj_kind.clone();
+                self.status = format!("已创建{}「{name}」", kind.label());
+                self.record_change(Change::ObjectAdded { name: &name, kind: kind.label() });
+                self.world_objects.push(WorldObject::new(&name, kind));
+                self.world_objects.len() - 1
+            }
+        };
+        if self.obj_kind_filter.as_ref().is_some_and(|k| k != &self.world_objects[idx].kind) {
+            self.obj_kind_filter = None;
+        }
+        if !self.obj_list_filter().shows(idx, &self.world_objects[idx]) {
+            self.obj_chapter_filter = false;
+        }
+        self.active_panel = Panel::Objects;
+        self.select_obj(idx);
+        self.focus_scroll = true;
+    }
+
+    /// Open the 选择对象 popup for the selected node.
+    pub(super) fn open_object_picker(&mut self) {
+        self.object_picker = Some(ObjectPicker {
+            path: self.selected_node_path.clone(),
+            query: String::new(),
+            checked: Vec::new(),
+        });
+    }
+
+    pub(super) fn draw_object_picker(&mut self, ctx: &Context) {
+        if self.object_picker.is_none() {
+            return;
+        }
+        let kinds = self.kind_choices();
+        let Some(picker) = &mut self.object_picker else {
+            return;
+        };
+        let Some(node) = node_at(&self.struct_roots, &picker.path) else {
+            self.object_picker = None;
+            return;
+        };
+        let mut open = true;
+        let mut cancel = false;
+        let mut confirm = false;
+
+        let query = picker.query.trim().to_lowercase();
+        let matches = |name: &str, tags: &[String]| query.is_empty()
+            || name.to_lowercase().contains(&query)
+            || tags.iter().any(|t| t.to_lowercase().contains(&query));
+
+        egui::Window::new(format!("选择对象 — {}", node.title))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(320.0)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+            .show(ctx, |ui| {
+                ui.add(egui::TextEdit::singleline(&mut picker.query)
+                    .hint_text("搜索名称或标签")
+                    .desired_width(f32::INFINITY))
+                    .request_focus();
+                ui.separator();
+                let mut any = false;
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    for (kind, text) in &kinds {
+                        let objs: Vec<&str> = self.world_objects.iter()
+                            .filter(|o| o.kind == *kind && matches(&o.name, &o.tags))
+                            .map(|o| o.name.as_str())
+                            .collect();
+                        if objs.is_empty() {
+                            continue;
+                        }
+                        any = true;
+                        ui.horizontal(|ui| {
+                            ui.label(text.clone().strong());
+                            ui.label(RichText::new(format!("({})", objs.len())).strong());
+                        });
+                        for name in objs {
+                            ui.horizontal(|ui| {
+                                ui.add_space(12.0);
+                                if node.linked_objects.iter().any(|l| same_title(l, name)) {
+                                    ui.add_enabled(false, egui::Checkbox::new(&mut true, name))
+                                        .on_disabled_hover_text("已关联");
+                                    return;
+                                }
+                                let pos = picker.checked.iter().position(|c| c == name);
+                                let mut on = pos.is_some();
+                                if ui.checkbox(&mut on, name).changed() {
+                                    match pos {
+                                        Some(p) => { picker.checked.remove(p); }
+                                        None => picker.checked.push(name.to_owned()),
+                                    }
+                                }
+                            });
+                        }
+                    }
+                });
+                if !any {
+                    ui.label(RichText::new("没有匹配的对象").color(Color32::GRAY));
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let n = picker.checked.len();
+                    if ui.add_enabled(n > 0, egui::Button::new(format!("添加所选 ({n})"))).clicked() {
+                        confirm = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if ctx.input(|i| i.key_pressed(Key::Escape)) {
+            cancel = true;
+        }
+        if confirm {
+            if let Some(picker) = self.object_picker.take() {
+                if let Some(node) = node_at_mut(&mut self.struct_roots, &picker.path) {
+                    let added = push_unique_names(&mut node.linked_objects, picker.checked);
+                    if added > 0 {
+                        node.touch();
+                    }
+                    self.status = format!("已关联 {added} 个对象");
+                }
+            }
+        } else if !open || cancel {
+            self.object_picker = None;
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_object_and_excerpt() {
+        let objects = vec![
+            WorldObject::new("林 枫", ObjectKind::Character),
+            WorldObject::new("青云宗", ObjectKind::Faction),
+        ];
+        assert_eq!(find_object(&objects, " 林\u{3000}枫"), Some(0));
+        assert_eq!(find_object(&objects, "青云宗"), Some(1));
+        assert_eq!(find_object(&objects, "林"), None);
+
+        assert_eq!(excerpt("  短描述 ", 5), "短描述");
+        assert_eq!(excerpt("一二三四五六", 5), "一二三四五…");
+        assert_eq!(excerpt("一二三四五", 5), "一二三四五");
+    }
+}
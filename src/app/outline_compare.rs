@@ -0,0 +1,278 @@
+use std::path::Path;
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::models::{node_at, node_at_mut, normalize_title};
+use text_tool_core::outline::parse_outline_entries;
+use text_tool_core::outline_compare::{align_outline, heading_insertion, insert_heading, node_insertion};
+
+use super::{rfd_pick_file, AfterOpen, OutlineCompareView, Panel, StructKind, StructNode, TextToolApp};
+
+// ── 对比大纲与结构: a planning document against the structure tree ────────────
+//
+// The document is compared as it stands in its editor pane, so headings
+// added from here are ordinary edits (one undo step each) saved with the
+// file.  Nodes added from here are saved like the structure panel's.
+
+/// Column of the report; part of the 忽略 key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompareColumn {
+    OnlyMarkdown,
+    OnlyTree,
+    LevelMismatch,
+}
+
+/// Row action, applied after the window is drawn.
+enum CompareAction {
+    AddNode(usize),
+    AddHeading(Vec<usize>),
+    Ignore(CompareColumn, String),
+    JumpToLine(usize),
+    SelectNode(Vec<usize>),
+}
+
+impl TextToolApp {
+    /// 工具 → 对比大纲与结构: compare the Markdown file in the left pane,
+    /// or a chosen one.
+    pub(super) fn open_outline_compare(&mut self) {
+        let left_md = self.left_file.as_ref()
+            .filter(|f| !f.scratch && f.path.extension().is_some_and(|e| e == "md"))
+            .map(|f| f.path.clone());
+        match left_md {
+            Some(path) => self.outline_compare = Some(OutlineCompareView { path, ignored: Default::default() }),
+            None => self.pick_outline_compare_file(),
+        }
+    }
+
+    /// Ask for the planning document and open it in the left pane.
+    fn pick_outline_compare_file(&mut self) {
+        let Some(path) = rfd_pick_file("Markdown", &["md"]) else { return };
+        if self.left_file.as_ref().is_some_and(|f| f.path == path) {
+            self.outline_compare = Some(OutlineCompareView { path, ignored: Default::default() });
+        } else {
+            let r = self.open_file_then(&path, true, AfterOpen::CompareOutline);
+            self.notify_open(r);
+        }
+    }
+
+    /// Whether the compared file is in the left pane (`Some(true)`), the
+    /// right one, or in neither.
+    fn outline_compare_pane(&self, path: &Path) -> Option<bool> {
+        if self.left_file.as_ref().is_some_and(|f| f.path == path) {
+            Some(true)
+        } else if self.right_file.as_ref().is_some_and(|f| f.path == path) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn draw_outline_compare(&mut self, ctx: &Context) {
+        let Some(view) = &self.outline_compare else { return };
+        let pane = self.outline_compare_pane(&view.path);
+        let text = match pane {
+            Some(true) => self.left_file.as_ref().map(|f| f.content.as_str()),
+            Some(false) => self.right_file.as_ref().map(|f| f.content.as_str()),
+            None => None,
+        };
+        let entries = text.map(parse_outline_entries).unwrap_or_default();
+        let alignment = align_outline(&entries, &self.struct_roots);
+        let name = view.path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let visible = |column: CompareColumn, title: &str| !view.ignored.contains(&(column, normalize_title(title)));
+        let node_title = |path: &[usize]| node_at(&self.struct_roots, path).map_or(String::new(), |n| n.title.clone());
+        let node_parent = |path: &[usize]| {
+            let titles: Vec<String> = (1..path.len()).map(|n| node_title(&path[..n])).collect();
+            if titles.is_empty() { "顶层".to_owned() } else { titles.join(" › ") }
+        };
+
+        let mut open = true;
+        let mut change_file = false;
+        let mut reopen = false;
+        let mut action = None;
+        egui::Window::new("对比大纲与结构")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([720.0, 420.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("📄 {name}")).strong());
+                    if let Some(t) = alignment.title_entry {
+                        ui.label(RichText::new(format!("（文档标题「{}」不参与对比）", entries[t].title))
+                            .small().color(Color32::from_gray(140)));
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        change_file = ui.button("换文件…").clicked();
+                    });
+                });
+                if pane.is_none() {
+                    ui.label(RichText::new("该文件已不在编辑区").color(Color32::from_rgb(220, 180, 60)));
+                    reopen = ui.button("在左侧重新打开").clicked();
+                    return;
+                }
+                ui.separator();
+
+                ui.columns(3, |cols| {
+                    let header = |ui: &mut egui::Ui, label: &str, count: usize| {
+                        ui.label(RichText::new(format!("{label}（{count}）")).strong());
+                        ui.separator();
+                    };
+
+                    let rows: Vec<usize> = alignment.only_markdown.iter().copied()
+                        .filter(|&e| visible(CompareColumn::OnlyMarkdown, &entries[e].title))
+                        .collect();
+                    header(&mut cols[0], "仅在 Markdown 中", rows.len());
+                    egui::ScrollArea::vertical().id_salt("cmp_md").show(&mut cols[0], |ui| {
+                        for e in rows {
+                            let entry = &entries[e];
+                            ui.label(format!("{} {}", "#".repeat(entry.level), entry.title));
+                            ui.horizontal(|ui| {
+                                let place = node_insertion(&entries, &self.struct_roots, &alignment, e);
+                                if ui.add_enabled(place.is_some(), egui::Button::new("➕ 添加节点").small())
+                                    .on_disabled_hover_text("上级标题在结构树中还没有对应节点，请先添加上级")
+                                    .clicked()
+                                {
+                                    action = Some(CompareAction::AddNode(e));
+                                }
+                                if ui.small_button("跳转").clicked() {
+                                    action = Some(CompareAction::JumpToLine(entry.line_no));
+                                }
+                                if ui.small_button("忽略").clicked() {
+                                    action = Some(CompareAction::Ignore(CompareColumn::OnlyMarkdown, entry.title.clone()));
+                                }
+                            });
+                            ui.add_space(4.0);
+                        }
+                    });
+
+                    let rows: Vec<&Vec<usize>> = alignment.only_tree.iter()
+                        .filter(|p| visible(CompareColumn::OnlyTree, &node_title(p)))
+                        .collect();
+                    header(&mut cols[1], "仅在结构树中", rows.len());
+                    egui::ScrollArea::vertical().id_salt("cmp_tree").show(&mut cols[1], |ui| {
+                        for path in rows {
+                            let title = node_title(path);
+                            ui.label(&title).on_hover_text(node_parent(path));
+                            ui.horizontal(|ui| {
+                                let siblings = match path.split_last() {
+                                    Some((_, [])) => self.struct_roots.len(),
+                                    Some((_, parent)) => node_at(&self.struct_roots, parent).map_or(0, |n| n.children.len()),
+                                    None => 0,
+                                };
+                                let line_count = text.map_or(0, |t| t.lines().count());
+                                let place = heading_insertion(&entries, line_count, &alignment, path, siblings);
+                                if ui.add_enabled(place.is_some(), egui::Button::new("➕ 添加标题").small())
+                                    .on_disabled_hover_text("上级节点在 Markdown 中还没有对应标题，请先添加上级")
+                                    .clicked()
+                                {
+                                    action = Some(CompareAction::AddHeading(path.clone()));
+                                }
+                                if ui.small_button("定位").on_hover_text("在章节结构中选中").clicked() {
+                                    action = Some(CompareAction::SelectNode(path.clone()));
+                                }
+                                if ui.small_button("忽略").clicked() {
+                                    action = Some(CompareAction::Ignore(CompareColumn::OnlyTree, title.clone()));
+                                }
+                            });
+                            ui.add_space(4.0);
+                        }
+                    });
+
+                    let rows: Vec<_> = alignment.level_mismatch.iter()
+                        .filter(|m| visible(CompareColumn::LevelMismatch, &entries[m.entry].title))
+                        .collect();
+                    header(&mut cols[2], "标题相同但层级不同", rows.len());
+                    egui::ScrollArea::vertical().id_salt("cmp_level").show(&mut cols[2], |ui| {
+                        for m in rows {
+                            let entry = &entries[m.entry];
+                            ui.label(&entry.title);
+                            ui.label(RichText::new(format!(
+                                "Markdown 第 {} 层 · 结构树第 {} 层（{}）",
+                                m.markdown_depth, m.tree_depth, node_parent(&m.node),
+                            )).small().color(Color32::from_gray(150)));
+                            ui.horizontal(|ui| {
+                                if ui.small_button("跳转").clicked() {
+                                    action = Some(CompareAction::JumpToLine(entry.line_no));
+                                }
+                                if ui.small_button("定位").on_hover_text("在章节结构中选中").clicked() {
+                                    action = Some(CompareAction::SelectNode(m.node.clone()));
+                                }
+                                if ui.small_button("忽略").clicked() {
+                                    action = Some(CompareAction::Ignore(CompareColumn::LevelMismatch, entry.title.clone()));
+                                }
+                            });
+                            ui.add_space(4.0);
+                        }
+                    });
+                });
+            });
+
+        let path = view.path.clone();
+        if !open {
+            self.outline_compare = None;
+            return;
+        }
+        if change_file {
+            self.pick_outline_compare_file();
+            return;
+        }
+        if reopen {
+            let r = self.open_file_in_pane(&path, true);
+            self.notify_open(r);
+            return;
+        }
+        let Some(left) = pane else { return };
+        match action {
+            Some(CompareAction::AddNode(e)) => {
+                let Some((parent, index)) = node_insertion(&entries, &self.struct_roots, &alignment, e) else { return };
+                let deepest = self.settings.markdown.deepest_struct_kind.clone();
+                let kind = match node_at(&self.struct_roots, &parent) {
+                    _ if parent.is_empty() => self.struct_roots.first().map_or(StructKind::Volume, |s| s.kind.clone()),
+                    Some(p) => p.children.first().map_or_else(|| p.kind.child_kind_within(&deepest), |s| s.kind.clone()),
+                    None => return,
+                };
+                let node = StructNode::new(&entries[e].title, kind);
+                match node_at_mut(&mut self.struct_roots, &parent) {
+                    _ if parent.is_empty() => self.struct_roots.insert(index, node),
+                    Some(p) => p.children.insert(index, node),
+                    None => return,
+                }
+                let mut new_path = parent;
+                new_path.push(index);
+                self.selected_node_path = new_path;
+                self.autosave_struct();
+                self.status = format!("已添加节点「{}」", entries[e].title);
+            }
+            Some(CompareAction::AddHeading(node)) => {
+                let siblings = match node.split_last() {
+                    Some((_, [])) => self.struct_roots.len(),
+                    Some((_, parent)) => node_at(&self.struct_roots, parent).map_or(0, |n| n.children.len()),
+                    None => 0,
+                };
+                let title = node_at(&self.struct_roots, &node).map_or(String::new(), |n| n.title.clone());
+                let limit = self.settings.behavior.undo_limit;
+                let (file, undo) = if left {
+                    (self.left_file.as_mut(), &mut self.left_undo_stack)
+                } else {
+                    (self.right_file.as_mut(), &mut self.right_undo_stack)
+                };
+                let Some(f) = file else { return };
+                let Some((line, level)) = heading_insertion(&entries, f.content.lines().count(), &alignment, &node, siblings)
+                else { return };
+                let content = insert_heading(&f.content, line, level, &title);
+                undo.push(std::mem::replace(&mut f.content, content), limit);
+                f.modified = true;
+                self.status = format!("已在「{name}」中添加标题「{title}」（未保存）");
+            }
+            Some(CompareAction::Ignore(column, title)) => {
+                if let Some(view) = &mut self.outline_compare {
+                    view.ignored.insert((column, normalize_title(&title)));
+                }
+            }
+            Some(CompareAction::JumpToLine(line_no)) => self.request_scroll_to_line(left, line_no),
+            Some(CompareAction::SelectNode(node)) => {
+                self.selected_node_path = node;
+                self.active_panel = Panel::Structure;
+            }
+            None => {}
+        }
+    }
+}
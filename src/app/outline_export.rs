@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::outline_doc::{build_outline_document, OutlineDocOptions};
+
+use super::{rfd_save_file, AppError, IoOp, OutlineExport, TextToolApp};
+
+// ── 导出结构为大纲文档 ────────────────────────────────────────────────────────
+
+/// File name under `Design/`.
+const OUTLINE_DOC_FILE: &str = "章节大纲.md";
+
+impl TextToolApp {
+    /// Open the export dialog with word counts fresh from the index.
+    pub(super) fn open_outline_export(&mut self) {
+        let Some(root) = self.project_root.clone() else {
+            self.status = "请先打开一个项目".to_owned();
+            return;
+        };
+        if self.struct_roots.is_empty() {
+            self.status = "章节结构为空，无可导出的大纲".to_owned();
+            return;
+        }
+        // Incremental, so cheap when the background index is up to date.
+        self.project_index.rescan(&root);
+        self.outline_export = Some(OutlineExport {
+            options: OutlineDocOptions::default(),
+            word_counts: self.project_index.word_counts_by_stem(&root.join("Content")),
+        });
+    }
+
+    /// Options, a preview of the document, and the destinations.
+    pub(super) fn draw_outline_export_dialog(&mut self, ctx: &Context) {
+        let Some(dlg) = &mut self.outline_export else {
+            return;
+        };
+        let mut open = true;
+        let mut cancel = false;
+        let mut to_design = false;
+        let mut save_as = false;
+
+        let doc = build_outline_document(&self.struct_roots, &dlg.word_counts, &dlg.options);
+        egui::Window::new("导出结构为大纲文档")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(460.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("总纲、卷作为标题，章、节、场作为嵌套列表。");
+                ui.horizontal(|ui| {
+                    ui.label("包含:");
+                    ui.checkbox(&mut dlg.options.tags, "标签");
+                    ui.checkbox(&mut dlg.options.status, "完成状态");
+                    ui.checkbox(&mut dlg.options.word_counts, "字数");
+                    ui.checkbox(&mut dlg.options.summaries, "摘要");
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    ui.label(RichText::new(&doc).monospace().small().color(Color32::from_gray(190)));
+                });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button(format!("写入 Design/{OUTLINE_DOC_FILE}")).clicked() {
+                        to_design = true;
+                    }
+                    if ui.button("另存为…").clicked() {
+                        save_as = true;
+                    }
+                    if ui.button("复制到剪贴板").clicked() {
+                        ui.ctx().copy_text(doc.clone());
+                        self.status = "大纲文档已复制到剪贴板".to_owned();
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if to_design {
+            match self.write_project_file("Design", OUTLINE_DOC_FILE, &doc) {
+                Ok(_) => {
+                    self.refresh_tree();
+                    self.status = format!("已导出 Design/{OUTLINE_DOC_FILE}");
+                    self.outline_export = None;
+                }
+                Err(e) => self.report(e),
+            }
+        } else if save_as {
+            if let Some(dest) = rfd_save_file(&PathBuf::from(OUTLINE_DOC_FILE)) {
+                match text_tool_core::safe_write(&dest, &doc) {
+                    Ok(_) => {
+                        self.status = format!("已导出大纲到 {}", dest.display());
+                        self.outline_export = None;
+                    }
+                    Err(e) => self.report(AppError::Io { op: IoOp::Write, path: dest, source: e }),
+                }
+            }
+        } else if !open || cancel {
+            self.outline_export = None;
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::ops::Range;
+
+use egui::text::{LayoutJob, TextFormat, TextWrapping};
+use egui::{Color32, Context, FontId, RichText};
+use text_tool_core::diff::{char_changes, diff_lines, RowKind, TextDiff};
+
+use super::{PaneDiff, TextToolApp};
+
+// ── 对比左右: read-only diff of the two editor panes ─────────────────────────
+
+/// Rows kept above the current change when scrolling to it.
+const CONTEXT_ROWS: usize = 3;
+
+const REMOVED_BG: Color32 = Color32::from_rgb(72, 34, 34);
+const REMOVED_MARK: Color32 = Color32::from_rgb(150, 55, 55);
+const ADDED_BG: Color32 = Color32::from_rgb(30, 62, 38);
+const ADDED_MARK: Color32 = Color32::from_rgb(50, 125, 65);
+/// Background of the missing side of an added / removed row.
+const FILLER_BG: Color32 = Color32::from_gray(32);
+
+impl TextToolApp {
+    /// Open the diff view of the left pane against the right one.
+    pub(super) fn open_pane_diff(&mut self) {
+        let (Some(left), Some(right)) = (&self.left_file, &self.right_file) else {
+            self.status = "请先在左右两侧各打开一个文件".to_owned();
+            return;
+        };
+        let diff = diff_lines(&left.content, &right.content);
+        if diff.is_identical() {
+            self.status = "左右两侧内容相同".to_owned();
+        }
+        self.pane_diff = Some(PaneDiff {
+            left: left.content.clone(),
+            right: right.content.clone(),
+            diff,
+            current: 0,
+            scroll: true,
+        });
+    }
+
+    /// Side-by-side rows with changes highlighted, hunk navigation and
+    /// 将右侧应用到左侧.  Follows edits made in the panes while open.
+    pub(super) fn draw_pane_diff(&mut self, ctx: &Context) {
+        let Some(dlg) = &mut self.pane_diff else {
+            return;
+        };
+        let (Some(left), Some(right)) = (&self.left_file, &self.right_file) else {
+            self.pane_diff = None;
+            return;
+        };
+        if dlg.left != left.content || dlg.right != right.content {
+            dlg.left = left.content.clone();
+            dlg.right = right.content.clone();
+            dlg.diff = diff_lines(&dlg.left, &dlg.right);
+            dlg.current = dlg.current.min(dlg.diff.hunks.len().saturating_sub(1));
+        }
+        let left_name = file_label(&left.path);
+        let right_name = file_label(&right.path);
+
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new("对比左右")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([900.0, 560.0])
+            .show(ctx, |ui| {
+                let hunks = dlg.diff.hunks.len();
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("左: {left_name}")).strong());
+                    ui.label("→");
+                    ui.label(RichText::new(format!("右: {right_name}")).strong());
+                    ui.separator();
+                    draw_hunk_nav(ui, &dlg.diff, &mut dlg.current, &mut dlg.scroll);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.add_enabled(hunks > 0, egui::Button::new("将右侧应用到左侧"))
+                            .on_hover_text("用右侧内容替换左侧（可 Ctrl+Z 撤销）")
+                            .clicked()
+                        {
+                            apply = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                draw_diff_rows(ui, "pane_diff_rows", &dlg.left, &dlg.right, &dlg.diff, dlg.current, &mut dlg.scroll);
+            });
+
+        if apply {
+            if let (Some(l), Some(r)) = (&mut self.left_file, &self.right_file) {
+                let before = std::mem::replace(&mut l.content, r.content.clone());
+                self.left_undo_stack.push(before, self.settings.behavior.undo_limit);
+                l.modified = true;
+                self.last_focused_left = true;
+                self.status = "已将右侧内容应用到左侧（Ctrl+Z 可撤销）".to_owned();
+            }
+        }
+        if !open {
+            self.pane_diff = None;
+        }
+    }
+}
+
+/// File name of `path` for the header.
+fn file_label(path: &std::path::Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// `+added −removed` and the ◀ ▶ navigation between the hunks of `diff`;
+/// moving sets `scroll`.
+pub(super) fn draw_hunk_nav(ui: &mut egui::Ui, diff: &TextDiff, current: &mut usize, scroll: &mut bool) {
+    let hunks = diff.hunks.len();
+    ui.label(RichText::new(format!("+{}", diff.added)).color(Color32::from_rgb(110, 200, 120)));
+    ui.label(RichText::new(format!("−{}", diff.removed)).color(Color32::from_rgb(220, 110, 110)));
+    ui.separator();
+    if hunks == 0 {
+        ui.label(RichText::new("内容相同").color(Color32::GRAY));
+        return;
+    }
+    if ui.small_button("◀").on_hover_text("上一处修改").clicked() {
+        *current = (*current + hunks - 1) % hunks;
+        *scroll = true;
+    }
+    ui.label(format!("第 {}/{hunks} 处", *current + 1));
+    if ui.small_button("▶").on_hover_text("下一处修改").clicked() {
+        *current = (*current + 1) % hunks;
+        *scroll = true;
+    }
+}
+
+/// The rows of `diff` between `left` and `right` side by side, the rows of
+/// hunk `current` marked.  With `scroll` set, scrolls to that hunk and
+/// clears it.
+pub(super) fn draw_diff_rows(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    left: &str,
+    right: &str,
+    diff: &TextDiff,
+    current: usize,
+    scroll: &mut bool,
+) {
+    let font = FontId::monospace(13.0);
+    let row_height = ui.fonts(|f| f.row_height(&font)) + 2.0;
+    let col_width = ((ui.available_width() - ui.spacing().item_spacing.x) / 2.0).max(80.0);
+    let left_lines: Vec<&str> = left.lines().collect();
+    let right_lines: Vec<&str> = right.lines().collect();
+    let current = diff.hunks.get(current).cloned();
+
+    let mut area = egui::ScrollArea::both().id_salt(id_salt).auto_shrink([false, false]);
+    if *scroll {
+        *scroll = false;
+        if let Some(hunk) = &current {
+            let row = hunk.start.saturating_sub(CONTEXT_ROWS);
+            area = area.vertical_scroll_offset(row as f32 * (row_height + ui.spacing().item_spacing.y));
+        }
+    }
+    area.show_rows(ui, row_height, diff.rows.len(), |ui, rows| {
+        for i in rows {
+            let row = diff.rows[i];
+            let in_current = current.as_ref().is_some_and(|h| h.contains(&i));
+            let l = row.left.map(|n| (n, left_lines.get(n).copied().unwrap_or_default()));
+            let r = row.right.map(|n| (n, right_lines.get(n).copied().unwrap_or_default()));
+            let (l_marks, r_marks) = match (row.kind, l, r) {
+                (RowKind::Changed, Some((_, a)), Some((_, b))) => char_changes(a, b),
+                _ => (Vec::new(), Vec::new()),
+            };
+            let (l_bg, r_bg) = match row.kind {
+                RowKind::Same => (Color32::TRANSPARENT, Color32::TRANSPARENT),
+                RowKind::Changed => (REMOVED_BG, ADDED_BG),
+                RowKind::Removed => (REMOVED_BG, FILLER_BG),
+                RowKind::Added => (FILLER_BG, ADDED_BG),
+            };
+            ui.horizontal(|ui| {
+                diff_cell(ui, [col_width, row_height], &font, l, &l_marks, l_bg, REMOVED_MARK, in_current);
+                diff_cell(ui, [col_width, row_height], &font, r, &r_marks, r_bg, ADDED_MARK, in_current);
+            });
+        }
+    });
+}
+
+/// One side of a diff row: line number and text on `bg`, with the `marks`
+/// byte ranges on `mark`.  `line` is `None` for the empty side.
+#[allow(clippy::too_many_arguments)]
+fn diff_cell(
+    ui: &mut egui::Ui,
+    size: [f32; 2],
+    font: &FontId,
+    line: Option<(usize, &str)>,
+    marks: &[Range<usize>],
+    bg: Color32,
+    mark: Color32,
+    in_current: bool,
+) {
+    let (rect, _) = ui.allocate_exact_size(size.into(), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, bg);
+    if in_current {
+        let bar = egui::Rect::from_min_size(rect.min, egui::vec2(2.0, rect.height()));
+        painter.rect_filled(bar, 0.0, Color32::from_rgb(100, 170, 230));
+    }
+    let Some((n, text)) = line else {
+        return;
+    };
+    let color = ui.visuals().text_color();
+    let plain = TextFormat::simple(font.clone(), color);
+    let marked = TextFormat { background: mark, ..plain.clone() };
+    let mut job = LayoutJob::default();
+    job.append(&format!("{:>5} ", n + 1), 0.0, TextFormat::simple(font.clone(), Color32::from_gray(110)));
+    let mut at = 0;
+    for range in marks {
+        job.append(&text[at..range.start], 0.0, plain.clone());
+        job.append(&text[range.clone()], 0.0, marked.clone());
+        at = range.end;
+    }
+    job.append(&text[at..], 0.0, plain);
+    job.wrap = TextWrapping {
+        max_width: size[0] - 6.0,
+        max_rows: 1,
+        break_anywhere: true,
+        overflow_character: Some('…'),
+    };
+    let galley = ui.fonts(|f| f.layout_job(job));
+    let pos = egui::pos2(rect.left() + 4.0, rect.center().y - galley.size().y / 2.0);
+    painter.galley(pos, galley, color);
+}
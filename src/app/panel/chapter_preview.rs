@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Context, RichText};
+
+use super::super::{
+    ChapterPreview, ChapterPreviewCard, Panel, TextToolApp, node_at, node_path_titled_near, normalize_title, same_title,
+};
+use super::markdown::render_markdown;
+use super::novel::node_md_file;
+
+// ── Chapter preview: hover card over chapter titles in design panels ──────────
+
+/// Hover time before the card opens.
+const PREVIEW_DELAY: Duration = Duration::from_millis(400);
+/// Cached previews older than this are rebuilt from the index.
+const PREVIEW_TTL: Duration = Duration::from_secs(10);
+/// Lines of the chapter shown in the card.
+const PREVIEW_LINES: usize = 40;
+
+/// A chapter title that opens the preview card when hovered; records the
+/// title and its rect in `hovered` for [`TextToolApp::draw_chapter_preview`].
+pub(in crate::app) fn chapter_title_label(
+    ui: &mut egui::Ui,
+    text: RichText,
+    title: &str,
+    hovered: &mut Option<(String, egui::Rect)>,
+) -> egui::Response {
+    let resp = ui.add(egui::Label::new(text.underline()).sense(egui::Sense::hover()));
+    if resp.hovered() {
+        *hovered = Some((title.to_owned(), resp.rect));
+    }
+    resp
+}
+
+impl TextToolApp {
+    /// Card with the first lines of the chapter titled as the hovered label,
+    /// rendered as Markdown, and 在左侧打开.  Opens after [`PREVIEW_DELAY`]
+    /// and closes once the pointer is over neither the title nor the card.
+    pub(in crate::app) fn draw_chapter_preview(&mut self, ctx: &Context, hovered: Option<(String, egui::Rect)>) {
+        let card_id = egui::Id::new("chapter_preview_card");
+        let over_card = self.chapter_preview.as_ref().is_some_and(|c| c.open)
+            && ctx.memory(|m| m.area_rect(card_id))
+                .zip(ctx.pointer_hover_pos())
+                .is_some_and(|(r, p)| r.expand(4.0).contains(p));
+
+        match hovered {
+            _ if over_card => {}
+            Some((title, anchor)) => match &mut self.chapter_preview {
+                Some(card) if same_title(&card.title, &title) => card.anchor = anchor,
+                _ => {
+                    self.chapter_preview = Some(ChapterPreviewCard {
+                        title, anchor, since: Instant::now(), open: false,
+                    });
+                }
+            },
+            None => self.chapter_preview = None,
+        }
+
+        let Some(card) = &mut self.chapter_preview else { return };
+        if !card.open {
+            let waited = card.since.elapsed();
+            if waited < PREVIEW_DELAY {
+                ctx.request_repaint_after(PREVIEW_DELAY - waited);
+                return;
+            }
+            card.open = true;
+        }
+        let title = card.title.clone();
+        let anchor = card.anchor;
+        let key = self.refresh_chapter_preview(&title);
+        let preview = &self.chapter_previews[&key];
+        let content_dir = self.project_root.as_ref().map(|r| r.join("Content"));
+
+        let mut open_left = None;
+        egui::Area::new(card_id)
+            .order(egui::Order::Foreground)
+            .fixed_pos(anchor.left_bottom() + egui::vec2(0.0, 2.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_max_width(420.0);
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&title).strong());
+                        if let Some(file) = &preview.file {
+                            let rel = content_dir.as_deref()
+                                .and_then(|c| file.strip_prefix(c).ok())
+                                .unwrap_or(file);
+                            ui.label(RichText::new(rel.to_string_lossy()).small().color(Color32::from_gray(150)));
+                        }
+                    });
+                    ui.separator();
+                    let Some(file) = &preview.file else {
+                        ui.label(RichText::new("未找到对应文件").color(Color32::GRAY));
+                        return;
+                    };
+                    egui::ScrollArea::vertical()
+                        .id_salt("chapter_preview_scroll")
+                        .max_height(320.0)
+                        .show(ui, |ui| {
+                            if preview.text.trim().is_empty() {
+                                ui.label(RichText::new("（空文件）").color(Color32::GRAY));
+                            } else {
+                                render_markdown(ui, &preview.text, &self.settings.markdown);
+                            }
+                            if preview.truncated {
+                                ui.label(RichText::new(format!("…… 仅显示前 {PREVIEW_LINES} 行"))
+                                    .small().color(Color32::from_gray(140)));
+                            }
+                        });
+                    ui.separator();
+                    if ui.button("在左侧打开").clicked() {
+                        open_left = Some(file.clone());
+                    }
+                });
+            });
+
+        if let Some(path) = open_left {
+            self.chapter_preview = None;
+            let r = self.open_file_in_pane(&path, true);
+            self.notify_open(r);
+            self.active_panel = Panel::Novel;
+        }
+    }
+
+    /// Make sure the cached preview of `title` is fresh: rebuilt from the
+    /// project index once older than [`PREVIEW_TTL`] or after the index
+    /// changed.  Returns its cache key.
+    fn refresh_chapter_preview(&mut self, title: &str) -> String {
+        // Of nodes sharing the title, the one nearest the selected node.
+        let node_path = node_path_titled_near(&self.struct_roots, title, &self.selected_node_path);
+        let key = match &node_path {
+            Some(path) => format!("{}@{path:?}", normalize_title(title)),
+            None => normalize_title(title),
+        };
+        let generation = self.project_index.generation();
+        let fresh = self.chapter_previews.get(&key)
+            .is_some_and(|p| p.generation == generation && p.fetched.elapsed() < PREVIEW_TTL);
+        if fresh {
+            return key;
+        }
+        self.chapter_previews.retain(|_, p| p.fetched.elapsed() < PREVIEW_TTL);
+
+        let file = self.project_root.as_ref().and_then(|root| {
+            let content = root.join("Content");
+            node_path.as_deref()
+                .and_then(|path| node_at(&self.struct_roots, path))
+                .and_then(|node| node_md_file(&content, node))
+                .or_else(|| self.project_index.md_file_titled(&content, title))
+        });
+        // Not indexed yet (e.g. created since the last scan): read it directly.
+        let (text, truncated) = file.as_deref()
+            .and_then(|path| self.project_index.text_head(path, PREVIEW_LINES).or_else(|| {
+                let text = std::fs::read_to_string(path).ok()?;
+                let mut lines = text.lines();
+                let head: Vec<&str> = lines.by_ref().take(PREVIEW_LINES).collect();
+                Some((head.join("\n"), lines.next().is_some()))
+            }))
+            .unwrap_or_default();
+        self.chapter_previews.insert(key.clone(), ChapterPreview {
+            fetched: Instant::now(),
+            generation,
+            file,
+            text,
+            truncated,
+        });
+        key
+    }
+}
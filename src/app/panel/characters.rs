@@ -1,8 +1,21 @@
-use egui::{Context, RichText, Color32};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use egui::{Context, RichText, Color32, Key};
 use super::super::{
-    TextToolApp, WorldObject, ObjectKind, ObjectLink, LinkTarget, RelationKind,
-    StructNode, ObjectViewMode,
+    TextToolApp, FocusRegion, WorldObject, ObjectKind, ObjectLink, LinkTarget,
+    StructNode, ObjectViewMode, ObjectEditorTab, Panel, DesignFile, format_relative_time, index_after_move,
+    selection_after_removal, rfd_save_file, unix_now, find_duplicate_name,
+    move_item, normalize_title, same_title, AppError, IoOp,
 };
+use text_tool_core::changelog::Change;
+use text_tool_core::profile::ProfileSheet;
+use text_tool_core::split::sanitize_file_name;
+use text_tool_core::timeline::{object_timeline, timeline_gaps};
+use super::chapter_preview::chapter_title_label;
+use super::super::attachments::draw_attachments_section;
+use super::super::object_picker::object_hover_ui;
+use super::link_form::{relation_combo, target_menu};
+use super::markdown::markdown_field;
 
 impl TextToolApp {
     // ── Panel: World Objects ──────────────────────────────────────────────────
@@ -11,15 +24,27 @@ impl TextToolApp {
     // Central panel:   relationship canvas (nodes + connecting lines)
 
     pub(in crate::app) fn draw_objects_panel(&mut self, ctx: &Context) {
-        let mut open_obj: Option<usize> = None;
+        let mut click_obj: Option<(usize, egui::Modifiers)> = None;
         let mut remove_obj: Option<usize> = None;
+        let mut do_export_selected = false;
         let mut do_sync = false;
         let mut do_add_link = false;
         let mut remove_link: Option<usize> = None;
+        // (link index, up)
+        let mut move_link: Option<(usize, bool)> = None;
+        let mut attachment_action = None;
+        let mut do_sort_recent = false;
+        let mut open_node: Option<Vec<usize>> = None;
+        // (old name, new name) once the name field loses focus
+        let mut renamed: Option<(String, String)> = None;
+        let mut design_action = None;
+        let mut hovered_chapter: Option<(String, egui::Rect)> = None;
+        let now = unix_now();
+        let kind_choices = self.kind_choices();
 
         // Collect autocomplete before any mutable borrow (unused for now but needed for future autocomplete)
 
-        egui::SidePanel::left("obj_list")
+        let rect = egui::SidePanel::left("obj_list")
             .resizable(true)
             .default_width(300.0)
             .min_width(200.0)
@@ -33,7 +58,11 @@ impl TextToolApp {
                             do_sync = true;
                         }
                         if ui.small_button("⬇").on_hover_text("从 Design/世界对象.json 加载世界对象").clicked() {
-                            self.load_world_objects_from_json();
+                            let r = self.load_world_objects_from_json();
+                            self.notify(r);
+                        }
+                        if ui.small_button("⏱").on_hover_text("按更新时间排序（最近修改在前）").clicked() {
+                            do_sort_recent = true;
                         }
                         // View mode toggle
                         let is_card = self.obj_view_mode == ObjectViewMode::Card;
@@ -45,24 +74,49 @@ impl TextToolApp {
                         }
                     });
                 });
+                design_action = self.draw_design_banner(ui, DesignFile::Objects);
                 // Kind filter chips
                 ui.horizontal_wrapped(|ui| {
                     let all_sel = self.obj_kind_filter.is_none();
                     if ui.selectable_label(all_sel, "全部").clicked() {
                         self.obj_kind_filter = None;
                     }
-                    for k in ObjectKind::all() {
+                    for (k, text) in &kind_choices {
                         let sel = self.obj_kind_filter.as_ref() == Some(k);
-                        if ui.selectable_label(sel,
-                            format!("{} {}", k.icon(), k.label())).clicked()
-                        {
+                        if ui.selectable_label(sel, text.clone()).clicked() {
                             self.obj_kind_filter = if sel { None } else { Some(k.clone()) };
                         }
                     }
+                    if ui.small_button("⚙").on_hover_text("管理对象类型：添加自定义类型（功法、种族…）").clicked() {
+                        self.open_object_kinds();
+                    }
                 });
+                // Chapter filter chip, composing with the kind chips
+                let chapter = self.current_chapter_node().map(|(_, n)| n.title.clone());
+                ui.horizontal(|ui| {
+                    let chip = if self.obj_chapter_filter {
+                        match &chapter {
+                            Some(title) => format!("📖 当前章节相关：{title}"),
+                            None => "📖 当前章节相关（未找到章节）".to_owned(),
+                        }
+                    } else {
+                        "📖 当前章节相关".to_owned()
+                    };
+                    if ui.selectable_label(self.obj_chapter_filter, chip)
+                        .on_hover_text("只显示结构面板选中的节点（或左侧打开的章节文件）关联、出场或提及的对象")
+                        .clicked()
+                    {
+                        self.obj_chapter_filter = !self.obj_chapter_filter;
+                    }
+                    if self.obj_chapter_filter && ui.small_button("✖").on_hover_text("清除章节筛选").clicked() {
+                        self.obj_chapter_filter = false;
+                    }
+                });
+                let list_filter = self.apply_obj_list_filter();
                 ui.separator();
 
                 // ── Object list (top portion) ──────────────────────────────────
+                // Click = select, Ctrl+click = toggle, Shift+click = range.
                 let list_height = 160.0_f32;
                 egui::ScrollArea::vertical()
                     .id_salt("obj_list_scroll")
@@ -72,11 +126,10 @@ impl TextToolApp {
                             let mut pending_move: Option<(usize, usize)> = None;
                             for i in 0..self.world_objects.len() {
                                 let obj = &self.world_objects[i];
-                                if let Some(ref filter) = self.obj_kind_filter {
-                                    if &obj.kind != filter { continue; }
-                                }
-                                let selected = self.selected_obj_idx == Some(i);
-                                let label = format!("{} {}", obj.icon(), obj.name);
+                                if !list_filter.shows(i, obj) { continue; }
+                                let selected = self.selected_objs.contains(&i);
+                                let star = if obj.pinned { " ⭐" } else { "" };
+                                let label = format!("{} {}{star}", self.project_config.kind_icon(&obj.kind), obj.name);
                                 let item_id = egui::Id::new(("wo_drag", i));
                                 let ir = ui.dnd_drag_source(item_id, i, |ui| {
                                     ui.selectable_label(selected, &label)
@@ -91,29 +144,28 @@ impl TextToolApp {
                                         ui.close_menu();
                                     }
                                 });
-                                if ir.inner.clicked() { open_obj = Some(i); }
+                                if selected && self.focus_scroll {
+                                    ir.inner.scroll_to_me(None);
+                                }
+                                if ir.inner.clicked() {
+                                    click_obj = Some((i, ui.input(|inp| inp.modifiers)));
+                                }
                             }
                             if let Some((from, to)) = pending_move {
                                 if from < self.world_objects.len() && to < self.world_objects.len() {
                                     let item = self.world_objects.remove(from);
                                     self.world_objects.insert(to, item);
-                                    if let Some(sel) = self.selected_obj_idx {
-                                        if sel == from {
-                                            self.selected_obj_idx = Some(to);
-                                        } else if from < to && sel > from && sel <= to {
-                                            self.selected_obj_idx = Some(sel - 1);
-                                        } else if from > to && sel >= to && sel < from {
-                                            self.selected_obj_idx = Some(sel + 1);
-                                        }
-                                    }
+                                    self.selected_objs = self.selected_objs.iter()
+                                        .map(|&s| index_after_move(s, from, to))
+                                        .collect();
+                                    self.obj_select_anchor = self.obj_select_anchor
+                                        .map(|a| index_after_move(a, from, to));
                                 }
                             }
                         } else {
                             for (i, obj) in self.world_objects.iter().enumerate() {
-                                if let Some(ref filter) = self.obj_kind_filter {
-                                    if &obj.kind != filter { continue; }
-                                }
-                                let selected = self.selected_obj_idx == Some(i);
+                                if !list_filter.shows(i, obj) { continue; }
+                                let selected = self.selected_objs.contains(&i);
                                 let bg = if selected { Color32::from_rgb(0, 80, 140) } else { Color32::from_gray(38) };
                                 let card_resp = egui::Frame::none()
                                     .fill(bg).rounding(6.0)
@@ -121,9 +173,12 @@ impl TextToolApp {
                                     .show(ui, |ui| {
                                         ui.set_min_width(ui.available_width());
                                         ui.horizontal(|ui| {
-                                            ui.label(RichText::new(obj.icon()).size(18.0));
+                                            ui.label(RichText::new(self.project_config.kind_icon(&obj.kind)).size(18.0));
                                             ui.label(RichText::new(&obj.name).strong());
-                                            ui.label(RichText::new(obj.kind.label()).small().color(Color32::from_gray(160)));
+                                            if obj.pinned { ui.label("⭐"); }
+                                            let kind_color = self.project_config.custom_kind(&obj.kind)
+                                                .map_or(Color32::from_gray(160), |c| c.color32());
+                                            ui.label(RichText::new(obj.kind.label()).small().color(kind_color));
                                         });
                                     }).response.interact(egui::Sense::click());
                                 card_resp.context_menu(|ui| {
@@ -132,153 +187,380 @@ impl TextToolApp {
                                         ui.close_menu();
                                     }
                                 });
-                                if card_resp.clicked() { open_obj = Some(i); }
+                                if selected && self.focus_scroll {
+                                    card_resp.scroll_to_me(None);
+                                }
+                                if card_resp.clicked() {
+                                    click_obj = Some((i, ui.input(|inp| inp.modifiers)));
+                                }
                                 ui.add_space(2.0);
                             }
                         }
                     });
 
+                // ── Batch action bar (multi-selection) ─────────────────────────
+                if self.selected_objs.len() > 1 {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(format!("已选 {} 个对象", self.selected_objs.len())).strong());
+                        if ui.small_button("✖").on_hover_text("清除选择").clicked() {
+                            self.clear_obj_selection();
+                        }
+                    });
+                    ui.horizontal_wrapped(|ui| {
+                        if ui.button("🗑 批量删除").clicked() {
+                            self.batch_delete_confirm = true;
+                        }
+                        if ui.button("📤 导出所选为 Markdown").clicked() {
+                            do_export_selected = true;
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("batch_obj_kind")
+                            .selected_text(self.kind_text(&self.batch_obj_kind))
+                            .width(80.0)
+                            .show_ui(ui, |ui| {
+                                for (k, text) in &kind_choices {
+                                    ui.selectable_value(&mut self.batch_obj_kind, k.clone(), text.clone());
+                                }
+                            });
+                        if ui.button("批量修改类型").clicked() {
+                            for &i in &self.selected_objs {
+                                if let Some(obj) = self.world_objects.get_mut(i) {
+                                    obj.kind = self.batch_obj_kind.clone();
+                                }
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut self.batch_tag_input)
+                            .hint_text("标签").desired_width(100.0));
+                        if ui.button("🏷 批量添加标签").clicked() {
+                            let tag = self.batch_tag_input.trim().to_owned();
+                            if !tag.is_empty() {
+                                for &i in &self.selected_objs {
+                                    if let Some(obj) = self.world_objects.get_mut(i) {
+                                        if !obj.tags.contains(&tag) {
+                                            obj.tags.push(tag.clone());
+                                        }
+                                    }
+                                }
+                                self.batch_tag_input.clear();
+                            }
+                        }
+                    });
+                }
+
                 // ── Quick-add form ─────────────────────────────────────────────
                 ui.separator();
+                let duplicate = find_duplicate_name(
+                    &self.new_obj_name,
+                    self.world_objects.iter().map(|o| o.name.as_str()),
+                ).map(str::to_owned);
+                let mut do_add = false;
                 ui.horizontal(|ui| {
                     ui.add(egui::TextEdit::singleline(&mut self.new_obj_name)
                         .hint_text("新对象名称").desired_width(100.0));
                     egui::ComboBox::from_id_salt("new_obj_kind")
-                        .selected_text(format!("{} {}", self.new_obj_kind.icon(), self.new_obj_kind.label()))
+                        .selected_text(self.kind_text(&self.new_obj_kind))
                         .width(80.0)
                         .show_ui(ui, |ui| {
-                            for k in ObjectKind::all() {
-                                let label = format!("{} {}", k.icon(), k.label());
-                                ui.selectable_value(&mut self.new_obj_kind, k.clone(), label);
+                            for (k, text) in &kind_choices {
+                                ui.selectable_value(&mut self.new_obj_kind, k.clone(), text.clone());
                             }
                         });
-                    if ui.button("➕").on_hover_text("添加新对象").clicked() {
-                        let name = self.new_obj_name.trim().to_owned();
-                        if !name.is_empty() {
-                            let idx = self.world_objects.len();
-                            self.world_objects.push(WorldObject::new(&name, self.new_obj_kind.clone()));
-                            self.selected_obj_idx = Some(idx);
-                            self.new_obj_name.clear();
-                        }
+                    if ui.add_enabled(duplicate.is_none(), egui::Button::new("➕"))
+                        .on_hover_text("添加新对象")
+                        .clicked()
+                    {
+                        do_add = true;
                     }
                 });
+                if let Some(existing) = &duplicate {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(format!("已存在同名对象「{existing}」"))
+                            .small().color(Color32::from_rgb(220, 80, 80)));
+                        if ui.small_button("仍然添加").clicked() {
+                            do_add = true;
+                        }
+                    });
+                }
+                if do_add {
+                    let name = normalize_title(&self.new_obj_name);
+                    if !name.is_empty() {
+                        let idx = self.world_objects.len();
+                        let kind = self.new_obj_kind.clone();
+                        self.world_objects.push(WorldObject::new(&name, kind.clone()));
+                        self.record_change(Change::ObjectAdded { name: &name, kind: kind.label() });
+                        self.select_obj(idx);
+                        self.new_obj_name.clear();
+                    }
+                }
                 ui.separator();
 
                 // ── Selected-object detail editor ──────────────────────────────
-                if let Some(idx) = self.selected_obj_idx {
+                if let Some(idx) = self.selected_obj_idx() {
                     if idx < self.world_objects.len() {
-                        egui::ScrollArea::vertical().id_salt("obj_detail_scroll").show(ui, |ui| {
-                            let obj = &mut self.world_objects[idx];
+                        ui.horizontal(|ui| {
+                            ui.selectable_value(&mut self.obj_editor_tab, ObjectEditorTab::Details, "详情");
+                            ui.selectable_value(&mut self.obj_editor_tab, ObjectEditorTab::Timeline, "时间线");
+                        });
+                        if self.obj_editor_tab == ObjectEditorTab::Timeline {
+                            open_node = self.draw_object_timeline(ui, idx);
+                        } else {
+                            // Lookup for the hover cards of linked objects, taken
+                            // before the selected object is borrowed for editing.
+                            let others: Vec<WorldObject> = if self.world_objects[idx].links.iter()
+                                .any(|l| matches!(l.target, LinkTarget::Object(_)))
+                            {
+                                self.world_objects.clone()
+                            } else {
+                                Vec::new()
+                            };
+                            // Choices of the link form's target menu.
+                            let object_names: Vec<String> = self.world_objects.iter().enumerate()
+                                .filter(|&(i, _)| i != idx)
+                                .map(|(_, o)| o.name.clone())
+                                .collect();
+                            let node_titles = self.all_struct_node_titles();
+                            egui::ScrollArea::vertical().id_salt("obj_detail_scroll").show(ui, |ui| {
+                                let obj = &mut self.world_objects[idx];
+                                let mut edited = false;
 
-                            ui.horizontal(|ui| {
-                                ui.label(RichText::new(obj.icon()).size(18.0));
-                                ui.text_edit_singleline(&mut obj.name);
-                            });
+                                ui.horizontal(|ui| {
+                                    ui.label(RichText::new(self.project_config.kind_icon(&obj.kind)).size(18.0));
+                                    let resp = ui.text_edit_singleline(&mut obj.name);
+                                    edited |= resp.changed();
+                                    if resp.gained_focus() {
+                                        ui.data_mut(|d| d.insert_temp(resp.id, obj.name.clone()));
+                                    }
+                                    if resp.lost_focus() {
+                                        if normalize_title(&obj.name) != obj.name {
+                                            obj.name = normalize_title(&obj.name);
+                                            edited = true;
+                                        }
+                                        let before = ui.data_mut(|d| d.remove_temp::<String>(resp.id));
+                                        renamed = before.filter(|b| *b != obj.name).map(|b| (b, obj.name.clone()));
+                                    }
+                                    let (star, tip) = if obj.pinned {
+                                        ("⭐", "取消固定")
+                                    } else {
+                                        ("☆", "固定到快捷栏")
+                                    };
+                                    if ui.small_button(star).on_hover_text(tip).clicked() {
+                                        obj.pinned = !obj.pinned;
+                                    }
+                                    if obj.kind == ObjectKind::Character
+                                        && ui.small_button("🖨 打印视图").on_hover_text("以单栏档案页查看、导出").clicked()
+                                    {
+                                        self.print_sheet = Some(obj.name.clone());
+                                    }
+                                });
+                                ui.label(RichText::new(format!(
+                                    "更新于 {} · 创建于 {}",
+                                    format_relative_time(obj.updated_at, now),
+                                    format_relative_time(obj.created_at, now),
+                                )).small().color(Color32::GRAY));
 
-                            ui.add_space(2.0);
-                            ui.label("描述 / 核心特质:");
-                            ui.add(egui::TextEdit::multiline(&mut obj.description)
-                                .desired_rows(2).desired_width(f32::INFINITY));
+                                ui.add_space(2.0);
+                                ui.label("描述 / 核心特质:");
+                                edited |= markdown_field(ui, &mut obj.description, &self.settings.markdown, ("obj_description", idx), 2, "");
 
-                            ui.add_space(2.0);
-                            ui.label("背景故事:");
-                            ui.add(egui::TextEdit::multiline(&mut obj.background)
-                                .desired_rows(3).desired_width(f32::INFINITY));
+                                ui.add_space(2.0);
+                                ui.label("背景故事:");
+                                edited |= markdown_field(ui, &mut obj.background, &self.settings.markdown, ("obj_background", idx), 3, "");
 
-                            ui.add_space(4.0);
-                            ui.separator();
-                            ui.label(RichText::new("关联").strong());
+                                ui.add_space(2.0);
+                                ui.horizontal(|ui| {
+                                    ui.label("标签:");
+                                    let mut tags = obj.tags.join("、");
+                                    if ui.add(egui::TextEdit::singleline(&mut tags)
+                                        .hint_text("逗号分隔")
+                                        .desired_width(f32::INFINITY)).changed()
+                                    {
+                                        obj.tags = tags
+                                            .split(['，', '、', ','])
+                                            .map(|s| s.trim().to_owned())
+                                            .filter(|s| !s.is_empty())
+                                            .collect();
+                                        edited = true;
+                                    }
+                                });
 
-                            if obj.links.is_empty() {
-                                ui.label(RichText::new("（暂无关联）").color(Color32::GRAY).small());
-                            } else {
-                                for (li, link) in obj.links.iter().enumerate() {
-                                    ui.horizontal(|ui| {
-                                        ui.label(RichText::new(link.target.type_label()).small()
-                                            .color(Color32::from_rgb(120, 180, 240)));
-                                        ui.label(RichText::new(link.target.display_name()).small());
-                                        ui.label(RichText::new(link.kind.label()).small());
-                                        if ui.small_button("🗑").clicked() {
-                                            remove_link = Some(li);
-                                        }
-                                    });
+                                ui.add_space(4.0);
+                                ui.separator();
+                                draw_attachments_section(ui, &obj.attachments, self.project_root.as_deref(),
+                                    &mut self.attachment_thumbs, &mut attachment_action);
+
+                                ui.add_space(4.0);
+                                ui.separator();
+                                ui.label(RichText::new("关联").strong());
+
+                                if obj.links.is_empty() {
+                                    ui.label(RichText::new("（暂无关联）").color(Color32::GRAY).small());
+                                } else {
+                                    let last = obj.links.len() - 1;
+                                    for (li, link) in obj.links.iter().enumerate() {
+                                        ui.horizontal(|ui| {
+                                            if ui.add_enabled(li > 0, egui::Button::new("↑").small())
+                                                .on_hover_text("上移").clicked()
+                                            {
+                                                move_link = Some((li, true));
+                                            }
+                                            if ui.add_enabled(li < last, egui::Button::new("↓").small())
+                                                .on_hover_text("下移").clicked()
+                                            {
+                                                move_link = Some((li, false));
+                                            }
+                                            ui.label(RichText::new(link.target.type_label()).small()
+                                                .color(Color32::from_rgb(120, 180, 240)));
+                                            let name = link.target.display_name();
+                                            if matches!(link.target, LinkTarget::Node(_)) {
+                                                chapter_title_label(ui, RichText::new(name).small(), name, &mut hovered_chapter);
+                                            } else {
+                                                object_hover_ui(ui, RichText::new(name).small(), name,
+                                                    &others, &self.project_config, &mut self.object_nav);
+                                            }
+                                            ui.label(RichText::new(link.kind.label()).small());
+                                            if ui.small_button("🗑").clicked() {
+                                                remove_link = Some(li);
+                                            }
+                                        });
+                                    }
                                 }
-                            }
 
-                            if let Some(li) = remove_link { obj.links.remove(li); }
+                                if let Some(li) = remove_link {
+                                    obj.links.remove(li);
+                                    edited = true;
+                                }
+                                if let Some((li, up)) = move_link {
+                                    edited |= move_item(&mut obj.links, li, up).is_some();
+                                }
+                                if edited {
+                                    obj.touch();
+                                }
 
-                            ui.add_space(4.0);
-                            ui.label(RichText::new("添加关联:").small());
-                            ui.horizontal(|ui| {
-                                if ui.selectable_label(!self.new_link_is_node, "对象").clicked() { self.new_link_is_node = false; }
-                                if ui.selectable_label(self.new_link_is_node, "章节").clicked() { self.new_link_is_node = true; }
-                            });
-                            ui.horizontal(|ui| {
-                                let hint = if self.new_link_is_node { "节点标题" } else { "对象名称" };
-                                ui.add(egui::TextEdit::singleline(&mut self.new_link_name)
-                                    .hint_text(hint).desired_width(90.0));
-                                egui::ComboBox::from_id_salt("new_link_rel")
-                                    .selected_text(self.new_link_rel_kind.label())
-                                    .width(70.0)
-                                    .show_ui(ui, |ui| {
-                                        for k in RelationKind::all() {
-                                            ui.selectable_value(&mut self.new_link_rel_kind, k.clone(), k.label());
+                                ui.add_space(4.0);
+                                ui.label(RichText::new("添加关联:").small());
+                                ui.horizontal(|ui| {
+                                    if ui.selectable_label(!self.new_link_is_node, "对象").clicked() { self.new_link_is_node = false; }
+                                    if ui.selectable_label(self.new_link_is_node, "章节").clicked() { self.new_link_is_node = true; }
+                                });
+                                ui.horizontal(|ui| {
+                                    let hint = if self.new_link_is_node { "节点标题" } else { "对象名称" };
+                                    ui.add(egui::TextEdit::singleline(&mut self.new_link_name)
+                                        .hint_text(hint).desired_width(90.0));
+                                    if self.new_link_is_node {
+                                        target_menu(ui, &mut self.new_link_name, &node_titles, &self.link_usage.node_targets);
+                                    } else {
+                                        target_menu(ui, &mut self.new_link_name, &object_names, &self.link_usage.object_targets);
+                                    }
+                                    relation_combo(ui, "new_link_rel", &mut self.new_link_rel_kind,
+                                        &self.link_usage.object_relations, 70.0);
+                                    if ui.button("➕").clicked() {
+                                        let name = self.new_link_name.trim().to_owned();
+                                        if !name.is_empty() { do_add_link = true; }
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("备注:");
+                                    ui.add(egui::TextEdit::singleline(&mut self.new_link_note)
+                                        .desired_width(f32::INFINITY));
+                                });
+
+                                // Reverse links: chapters whose nodes list this object.
+                                let reverse = Self::collect_nodes_linking_object(&self.struct_roots, &obj.name);
+                                ui.add_space(4.0);
+                                ui.separator();
+                                ui.label(RichText::new("出现于章节").strong());
+                                if reverse.is_empty() {
+                                    ui.label(RichText::new("（暂无章节关联此对象）").color(Color32::GRAY).small());
+                                } else {
+                                    ui.horizontal_wrapped(|ui| {
+                                        for title in &reverse {
+                                            chapter_title_label(ui, RichText::new(title).small()
+                                                .color(Color32::from_rgb(120, 200, 120)), title, &mut hovered_chapter);
                                         }
                                     });
-                                if ui.button("➕").clicked() {
-                                    let name = self.new_link_name.trim().to_owned();
-                                    if !name.is_empty() { do_add_link = true; }
                                 }
                             });
-                            ui.horizontal(|ui| {
-                                ui.label("备注:");
-                                ui.add(egui::TextEdit::singleline(&mut self.new_link_note)
-                                    .desired_width(f32::INFINITY));
-                            });
-                        });
+                        }
                     }
+                } else if self.selected_objs.len() > 1 {
+                    ui.label(RichText::new("已选择多个对象，可使用上方批量操作").color(Color32::GRAY));
                 } else {
-                    ui.label(RichText::new("← 点击对象以编辑").color(Color32::GRAY));
+                    ui.label(RichText::new("← 点击对象以编辑（Ctrl/Shift+点击多选）").color(Color32::GRAY));
                 }
-            });
+            }).response.rect;
+        self.note_focus_region(ctx, FocusRegion::ObjectList, rect);
 
         // Apply deferred mutations
-        if let Some(i) = open_obj { self.selected_obj_idx = Some(i); }
+        if let Some((from, to)) = renamed {
+            self.record_change(Change::ObjectRenamed { from: &from, to: &to });
+        }
+        if let Some(path) = open_node {
+            self.selected_node_path = path;
+            self.active_panel = Panel::Structure;
+        }
+        if let Some((i, mods)) = click_obj {
+            self.click_obj_in_list(i, mods);
+        }
         if let Some(i) = remove_obj {
-            self.world_objects.remove(i);
-            match self.selected_obj_idx {
-                Some(s) if s == i => self.selected_obj_idx = None,
-                Some(s) if s > i  => self.selected_obj_idx = Some(s - 1),
-                _ => {}
-            }
+            self.remove_objects(&BTreeSet::from([i]));
+        }
+        if do_export_selected {
+            self.export_selected_objects_md();
+        }
+        if do_sort_recent {
+            self.sort_objects_by_updated();
         }
+        self.draw_batch_delete_confirm(ctx);
+        self.draw_print_sheet(ctx);
         if do_add_link {
-            let name = self.new_link_name.trim().to_owned();
+            let name = normalize_title(&self.new_link_name);
             let target = if self.new_link_is_node {
                 LinkTarget::Node(name)
             } else {
                 LinkTarget::Object(name)
             };
-            if let Some(idx) = self.selected_obj_idx {
+            let to = target.display_name().to_owned();
+            if let Some(idx) = self.selected_obj_idx() {
                 if let Some(obj) = self.world_objects.get_mut(idx) {
                     obj.links.push(ObjectLink {
                         target,
                         kind: self.new_link_rel_kind.clone(),
                         note: self.new_link_note.trim().to_owned(),
                     });
+                    obj.touch();
+                    let from = obj.name.clone();
+                    self.record_change(Change::LinkAdded { from: &from, relation: self.new_link_rel_kind.label(), to: &to });
+                    let (relation, is_node) = (self.new_link_rel_kind.label(), self.new_link_is_node);
+                    self.note_link_usage(|usage| {
+                        usage.object_relations.record(relation);
+                        if is_node { &mut usage.node_targets } else { &mut usage.object_targets }.record(&to);
+                    });
                 }
             }
             self.new_link_name.clear();
             self.new_link_note.clear();
         }
-        if do_sync { self.sync_world_objects_to_json(); }
+        if let Some((action, idx)) = attachment_action.zip(self.selected_obj_idx()) {
+            self.apply_attachment_action(idx, action);
+        }
+        if do_sync {
+            let r = self.sync_world_objects_to_json();
+            self.notify(r);
+        }
+        if let Some(action) = design_action {
+            self.apply_design_sync(action);
+        }
 
         // ── Central: relationship canvas ───────────────────────────────────────
-        egui::CentralPanel::default().show(ctx, |ui| {
+        let rect = egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("关系图谱");
             ui.separator();
 
-            let sel_idx = self.selected_obj_idx;
+            let sel_idx = self.selected_obj_idx();
             if sel_idx.is_none() || self.world_objects.is_empty() {
                 ui.centered_and_justified(|ui| {
                     ui.label(
@@ -430,7 +712,272 @@ impl TextToolApp {
                     *color,
                 );
             }
+        }).response.rect;
+        self.note_focus_region(ctx, FocusRegion::ObjectDetail, rect);
+
+        self.draw_chapter_preview(ctx, hovered_chapter);
+    }
+
+    // ── Multi-selection ───────────────────────────────────────────────────────
+
+    /// Apply a click on list item `i`: plain click selects it alone,
+    /// Ctrl+click toggles it, Shift+click selects the visible range from the
+    /// anchor (respecting the current kind and chapter filters).
+    fn click_obj_in_list(&mut self, i: usize, mods: egui::Modifiers) {
+        if mods.command || mods.ctrl {
+            if !self.selected_objs.remove(&i) {
+                self.selected_objs.insert(i);
+            }
+            self.obj_select_anchor = Some(i);
+        } else if mods.shift {
+            let Some(anchor) = self.obj_select_anchor else {
+                self.select_obj(i);
+                return;
+            };
+            let (lo, hi) = (anchor.min(i), anchor.max(i));
+            let filter = self.obj_list_filter();
+            self.selected_objs = (lo..=hi)
+                .filter(|&j| self.world_objects.get(j).is_some_and(|o| filter.shows(j, o)))
+                .collect();
+        } else {
+            self.select_obj(i);
+        }
+    }
+
+    /// Delete the objects at `indices` and re-index the selection.
+    fn remove_objects(&mut self, indices: &BTreeSet<usize>) {
+        for &i in indices.iter().rev() {
+            if i < self.world_objects.len() {
+                let removed = self.world_objects.remove(i);
+                self.record_change(Change::ObjectRemoved { name: &removed.name });
+            }
+        }
+        self.selected_objs = selection_after_removal(&self.selected_objs, indices);
+        self.obj_select_anchor = self.obj_select_anchor
+            .filter(|a| !indices.contains(a))
+            .map(|a| a - indices.range(..a).count());
+    }
+
+    /// Reorder objects by last modification, newest first, keeping the selection.
+    fn sort_objects_by_updated(&mut self) {
+        let mut indexed: Vec<(usize, WorldObject)> =
+            std::mem::take(&mut self.world_objects).into_iter().enumerate().collect();
+        indexed.sort_by_key(|(_, o)| std::cmp::Reverse(o.updated_at));
+        let mut new_pos = vec![0; indexed.len()];
+        for (new, (old, _)) in indexed.iter().enumerate() {
+            new_pos[*old] = new;
+        }
+        self.world_objects = indexed.into_iter().map(|(_, o)| o).collect();
+        self.selected_objs = self.selected_objs.iter().map(|&i| new_pos[i]).collect();
+        self.obj_select_anchor = self.obj_select_anchor.map(|a| new_pos[a]);
+    }
+
+    /// Export the selected objects to a user-chosen Markdown file.
+    fn export_selected_objects_md(&mut self) {
+        let mut md = String::from("# 世界对象\n\n");
+        for &i in &self.selected_objs {
+            if let Some(obj) = self.world_objects.get(i) {
+                md.push_str(&obj.to_markdown());
+            }
+        }
+        if let Some(dest) = rfd_save_file(&PathBuf::from("世界对象.md")) {
+            let r = text_tool_core::safe_write(&dest, &md)
+                .map(|_| format!("已导出 {} 个对象到 {}", self.selected_objs.len(), dest.display()))
+                .map_err(AppError::io(IoOp::Write, &dest));
+            self.notify(r);
+        }
+    }
+
+    /// Confirmation dialog for 批量删除.
+    fn draw_batch_delete_confirm(&mut self, ctx: &Context) {
+        if !self.batch_delete_confirm { return; }
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("确认批量删除")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("删除选中的 {} 个世界对象？", self.selected_objs.len()));
+                let note = if self.settings.behavior.design_autosave {
+                    "此操作不可撤销，删除会在片刻后自动保存到 Design/世界对象.json。"
+                } else {
+                    "此操作不可撤销（除非尚未同步到 JSON）。"
+                };
+                ui.label(RichText::new(note).small().color(Color32::from_gray(150)));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("删除").clicked() { confirmed = true; }
+                    if ui.button("取消").clicked() { cancelled = true; }
+                });
+                if ctx.input(|i| i.key_pressed(Key::Escape)) { cancelled = true; }
+            });
+        if confirmed {
+            let indices = std::mem::take(&mut self.selected_objs);
+            let count = indices.len();
+            self.remove_objects(&indices);
+            self.clear_obj_selection();
+            self.status = format!("已删除 {count} 个对象");
+        }
+        if confirmed || cancelled {
+            self.batch_delete_confirm = false;
+        }
+    }
+
+    /// 打印视图: the object's profile as a single-column, A4-proportioned
+    /// sheet, with HTML export for printing.
+    fn draw_print_sheet(&mut self, ctx: &Context) {
+        let Some(name) = &self.print_sheet else { return };
+        let Some(obj) = self.world_objects.iter().find(|o| &o.name == name) else {
+            self.print_sheet = None;
+            return;
+        };
+        let mut sheet = ProfileSheet::new(obj).with_timeline(obj, &self.struct_roots);
+        sheet.icon = self.project_config.kind_icon(&obj.kind).to_owned();
+        let mut open = true;
+        let mut export_html = false;
+        let mut print = false;
+        let file_name = format!("{}.html", sanitize_file_name(&sheet.name));
+
+        egui::Window::new(format!("🖨 {}", sheet.name))
+            .id(egui::Id::new("print_sheet_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_size([420.0, 594.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("导出 HTML…").clicked() {
+                        export_html = true;
+                    }
+                    if ui.button("🖨 导出 PDF").on_hover_text("在浏览器中打开，用浏览器的打印功能另存为 PDF").clicked() {
+                        print = true;
+                    }
+                    if ui.button("复制 Markdown").clicked() {
+                        ctx.copy_text(sheet.to_markdown());
+                        self.status = "档案已复制到剪贴板".to_owned();
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().id_salt("print_sheet_scroll").show(ui, |ui| {
+                    let muted = Color32::from_gray(140);
+                    ui.heading(format!("{} {}", sheet.icon, sheet.name));
+                    ui.label(RichText::new(&sheet.kind).color(muted));
+                    if !sheet.tags.is_empty() {
+                        ui.horizontal_wrapped(|ui| {
+                            for t in &sheet.tags {
+                                ui.label(RichText::new(t).small().background_color(Color32::from_gray(50)));
+                            }
+                        });
+                    }
+                    for (title, text) in [("描述", &sheet.description), ("背景", &sheet.background)] {
+                        if !text.is_empty() {
+                            ui.add_space(8.0);
+                            ui.strong(title);
+                            ui.label(text);
+                        }
+                    }
+                    if !sheet.relations.is_empty() {
+                        ui.add_space(8.0);
+                        ui.strong("关联");
+                        for g in &sheet.relations {
+                            ui.label(RichText::new(g.kind).color(muted));
+                            for r in &g.items {
+                                let note = if r.note.is_empty() { String::new() } else { format!(" — {}", r.note) };
+                                ui.label(format!("  • {} ({}){note}", r.target, r.target_type));
+                            }
+                        }
+                    }
+                    if let Some(apps) = &sheet.appearances {
+                        ui.add_space(8.0);
+                        ui.strong("出场");
+                        if apps.is_empty() {
+                            ui.label(RichText::new("（暂无出场章节）").color(muted));
+                        }
+                        for (i, title) in apps.iter().enumerate() {
+                            ui.label(format!("{}. {title}", i + 1));
+                        }
+                    }
+                });
+            });
+
+        if export_html {
+            if let Some(dest) = rfd_save_file(&PathBuf::from(&file_name)) {
+                let r = text_tool_core::safe_write(&dest, sheet.to_html())
+                    .map(|_| format!("已导出档案到 {}", dest.display()))
+                    .map_err(AppError::io(IoOp::Write, &dest));
+                self.notify(r);
+            }
+        }
+        if print {
+            let dir = std::env::temp_dir().join("qingmo_print");
+            let dest = dir.join(&file_name);
+            let r = std::fs::create_dir_all(&dir)
+                .and_then(|_| text_tool_core::safe_write(&dest, sheet.to_html()))
+                .map(|_| {
+                    ctx.open_url(egui::OpenUrl::new_tab(format!("file://{}", dest.display())));
+                    "已在浏览器中打开档案，可用浏览器打印为 PDF".to_owned()
+                })
+                .map_err(AppError::io(IoOp::Write, &dest));
+            self.notify(r);
+        }
+        if !open {
+            self.print_sheet = None;
+        }
+    }
+
+    /// 时间线 tab: every structure leaf in order, the object's appearances
+    /// highlighted and long absences flagged.  Returns the path of a clicked
+    /// node, to be shown in the Structure panel.
+    fn draw_object_timeline(&mut self, ui: &mut egui::Ui, idx: usize) -> Option<Vec<usize>> {
+        let entries = object_timeline(&self.struct_roots, &self.world_objects[idx]);
+        let mut max_missing = self.settings.behavior.timeline_gap_warning;
+        ui.horizontal(|ui| {
+            ui.label("连续缺席超过");
+            ui.add(egui::DragValue::new(&mut max_missing).range(1..=50));
+            ui.label("章时提醒");
+        });
+        if max_missing != self.settings.behavior.timeline_gap_warning {
+            self.settings.behavior.timeline_gap_warning = max_missing;
+            self.save_config();
+        }
+        if entries.is_empty() {
+            ui.label(RichText::new("章节结构中还没有章节").color(Color32::GRAY));
+            return None;
+        }
+        let present = entries.iter().filter(|e| e.present).count();
+        ui.label(RichText::new(format!("出场 {present} / {} 章", entries.len()))
+            .small().color(Color32::from_gray(140)));
+        ui.separator();
+
+        let gaps = timeline_gaps(&entries, max_missing);
+        let mut clicked = None;
+        egui::ScrollArea::vertical().id_salt("obj_timeline_scroll").show(ui, |ui| {
+            for (i, entry) in entries.iter().enumerate() {
+                if let Some(gap) = gaps.iter().find(|g| g.start == i) {
+                    let text = if gap.trailing {
+                        format!("⚠ 此后连续 {} 章未出场", gap.len)
+                    } else {
+                        format!("⚠ 连续 {} 章未出场", gap.len)
+                    };
+                    ui.label(RichText::new(text).small().color(Color32::from_rgb(220, 160, 60)));
+                }
+                ui.horizontal(|ui| {
+                    let label = if entry.present {
+                        RichText::new(format!("● {}", entry.title)).color(entry.tag.color()).strong()
+                    } else {
+                        RichText::new(format!("○ {}", entry.title)).color(Color32::from_gray(100))
+                    };
+                    if ui.selectable_label(false, label).on_hover_text("在章节结构中查看").clicked() {
+                        clicked = Some(entry.path.clone());
+                    }
+                    if let Some(note) = &entry.note {
+                        ui.label(RichText::new(note).small().color(Color32::from_gray(140)));
+                    }
+                });
+            }
         });
+        clicked
     }
 
     /// Collect titles of all `StructNode`s that list `obj_name` in their `linked_objects`.
@@ -438,7 +985,7 @@ impl TextToolApp {
         let mut out = Vec::new();
         fn walk(nodes: &[StructNode], name: &str, out: &mut Vec<String>) {
             for n in nodes {
-                if n.linked_objects.iter().any(|o| o == name) {
+                if n.linked_objects.iter().any(|o| same_title(o, name)) {
                     out.push(n.title.clone());
                 }
                 walk(&n.children, name, out);
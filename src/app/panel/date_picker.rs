@@ -0,0 +1,84 @@
+use egui::{Color32, RichText};
+use text_tool_core::models::{civil_from_days, days_from_civil, format_day, parse_day};
+
+use super::super::today_days;
+
+// ── Date picker: a button opening a month calendar ────────────────────────────
+
+const WEEKDAYS: [&str; 7] = ["一", "二", "三", "四", "五", "六", "日"];
+
+/// Button showing `date` (`YYYY-MM-DD`) that opens a month calendar below
+/// it; the popup can also clear the date.  Returns whether `date` changed.
+pub(in crate::app) fn date_picker(ui: &mut egui::Ui, id_salt: &str, date: &mut Option<String>) -> bool {
+    let id = ui.make_persistent_id(id_salt);
+    let popup_id = id.with("popup");
+    let current = date.as_deref().and_then(parse_day);
+    let text = current.map_or_else(|| "未设置".to_owned(), format_day);
+    let button = ui.button(format!("📅 {text}"));
+    if button.clicked() {
+        // Open on the chosen month, or the current one.
+        let (y, m, _) = civil_from_days(current.unwrap_or_else(today_days));
+        ui.data_mut(|d| d.insert_temp(id, (y, m)));
+        ui.memory_mut(|m| m.toggle_popup(popup_id));
+    }
+
+    let mut picked: Option<Option<i64>> = None;
+    egui::popup::popup_below_widget(ui, popup_id, &button, egui::PopupCloseBehavior::CloseOnClickOutside, |ui| {
+        ui.set_min_width(200.0);
+        let (mut y, mut m) = ui.data(|d| d.get_temp::<(i64, i64)>(id)).unwrap_or((1970, 1));
+        ui.horizontal(|ui| {
+            if ui.small_button("◀").clicked() {
+                (y, m) = if m == 1 { (y - 1, 12) } else { (y, m - 1) };
+            }
+            ui.label(RichText::new(format!("{y} 年 {m} 月")).strong());
+            if ui.small_button("▶").clicked() {
+                (y, m) = if m == 12 { (y + 1, 1) } else { (y, m + 1) };
+            }
+        });
+        ui.data_mut(|d| d.insert_temp(id, (y, m)));
+
+        let first = days_from_civil(y, m, 1);
+        let len = days_from_civil(y + m / 12, m % 12 + 1, 1) - first;
+        // Monday-first column of the 1st; 1970-01-01 was a Thursday.
+        let offset = (first + 3).rem_euclid(7);
+        let today = today_days();
+        egui::Grid::new(id.with("grid")).spacing([2.0, 2.0]).show(ui, |ui| {
+            for w in WEEKDAYS {
+                ui.label(RichText::new(w).small().color(Color32::GRAY));
+            }
+            ui.end_row();
+            for cell in 0..offset + len {
+                if cell > 0 && cell % 7 == 0 {
+                    ui.end_row();
+                }
+                if cell < offset {
+                    ui.label("");
+                    continue;
+                }
+                let day = first + cell - offset;
+                let mut label = RichText::new(format!("{:>2}", day - first + 1)).monospace();
+                if day == today {
+                    label = label.underline();
+                }
+                if ui.selectable_label(current == Some(day), label).clicked() {
+                    picked = Some(Some(day));
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.small_button("今天").clicked() {
+                picked = Some(Some(today));
+            }
+            if ui.small_button("清除").clicked() {
+                picked = Some(None);
+            }
+        });
+    });
+
+    let Some(day) = picked else { return false };
+    ui.memory_mut(|m| m.close_popup());
+    let new = day.map(format_day);
+    let changed = *date != new;
+    *date = new;
+    changed
+}
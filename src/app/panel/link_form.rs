@@ -0,0 +1,76 @@
+use egui::{Color32, RichText};
+use text_tool_core::usage::{LinkUsage, UsageRank, USAGE_TOP};
+
+use super::super::{RelationKind, TextToolApp};
+
+// ── Link forms: 常用项 first ──────────────────────────────────────────────────
+//
+// The relation ComboBox and the target menu of the Objects and Structure
+// link forms list the entries used most lately above a separator, then the
+// full list.
+
+/// Relation kind picker with `rank`'s 常用项 on top.
+pub(super) fn relation_combo(
+    ui: &mut egui::Ui,
+    id: &str,
+    kind: &mut RelationKind,
+    rank: &UsageRank,
+    width: f32,
+) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(kind.label())
+        .width(width)
+        .show_ui(ui, |ui| {
+            let recent = rank.top(RelationKind::all(), |k| k.label(), USAGE_TOP);
+            if !recent.is_empty() {
+                ui.label(RichText::new("常用").small().color(Color32::from_gray(140)));
+                for k in recent {
+                    ui.selectable_value(kind, k.clone(), k.label());
+                }
+                ui.separator();
+            }
+            for k in RelationKind::all() {
+                ui.selectable_value(kind, k.clone(), k.label());
+            }
+        });
+}
+
+/// `▾` menu filling `target` from `all`, with `rank`'s 常用项 on top.
+pub(super) fn target_menu(ui: &mut egui::Ui, target: &mut String, all: &[String], rank: &UsageRank) {
+    ui.menu_button("▾", |ui| {
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            let recent = rank.top(all, String::as_str, USAGE_TOP);
+            if !recent.is_empty() {
+                ui.label(RichText::new("常用").small().color(Color32::from_gray(140)));
+                for name in recent {
+                    if ui.button(name).clicked() {
+                        target.clone_from(name);
+                        ui.close_menu();
+                    }
+                }
+                ui.separator();
+            }
+            if all.is_empty() {
+                ui.label(RichText::new("（没有可选项）").color(Color32::GRAY));
+            }
+            for name in all {
+                if ui.button(name).clicked() {
+                    target.clone_from(name);
+                    ui.close_menu();
+                }
+            }
+        });
+    }).response.on_hover_text("从列表选择");
+}
+
+impl TextToolApp {
+    /// Count a link just added in the 常用项 ranking, and keep it in the
+    /// project config when 记住常用项 is on.
+    pub(in crate::app) fn note_link_usage(&mut self, record: impl FnOnce(&mut LinkUsage)) {
+        record(&mut self.link_usage);
+        if self.settings.behavior.remember_link_usage && self.project_root.is_some() {
+            self.project_config.link_usage = self.link_usage.clone();
+            self.save_project_config();
+        }
+    }
+}
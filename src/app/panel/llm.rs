@@ -1,5 +1,5 @@
 use egui::{RichText, Color32};
-use super::super::{TextToolApp, LlmTask, PromptTemplate};
+use super::super::{TextToolApp, FocusRegion, LlmTask, PromptTemplate};
 
 impl TextToolApp {
     // ── Panel: LLM Assistance ─────────────────────────────────────────────────
@@ -16,8 +16,8 @@ impl TextToolApp {
                 }
                 Ok(Err(e)) => {
                     self.llm_output = format!("【错误】{e}");
-                    self.status = format!("LLM 调用失败: {e}");
                     self.llm_task = None;
+                    self.report(e);
                     ctx.request_repaint();
                 }
                 Err(std::sync::mpsc::TryRecvError::Empty) => {
@@ -37,7 +37,7 @@ impl TextToolApp {
             .map(|o| o.name.clone())
             .collect();
 
-        egui::SidePanel::left("llm_config")
+        let rect = egui::SidePanel::left("llm_config")
             .resizable(true)
             .default_width(260.0)
             .min_width(180.0)
@@ -212,9 +212,10 @@ impl TextToolApp {
                         .color(Color32::from_gray(140))
                         .small(),
                 );
-            });
+            }).response.rect;
+        self.note_focus_region(ctx, FocusRegion::LlmConfig, rect);
 
-        egui::CentralPanel::default().show(ctx, |ui| {
+        let rect = egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("LLM 辅助写作");
             ui.separator();
 
@@ -378,7 +379,8 @@ impl TextToolApp {
                             .hint_text("LLM 输出将显示在这里")
                     );
                 });
-        });
+        }).response.rect;
+        self.note_focus_region(ctx, FocusRegion::LlmWork, rect);
     }
 }
 
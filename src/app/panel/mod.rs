@@ -3,3 +3,11 @@ mod characters;
 mod outline;
 mod llm;
 mod markdown;
+mod chapter_preview;
+mod link_form;
+mod node_split;
+mod node_rename;
+pub(super) mod date_picker;
+mod reading;
+mod tree_status;
+mod writing_context;
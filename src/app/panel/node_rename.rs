@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, Context, Key, RichText};
+use text_tool_core::models::{node_at, node_at_mut, unique_title_for};
+use text_tool_core::node_rename::{apply_link_rewrites, file_named_after, plan_node_rename, retitle_chapter_refs, LinkRewrite};
+
+use super::super::{NodeRenameDialog, TextToolApp};
+use super::novel::node_md_file;
+
+// ── 节点改名: rename the chapter file and references along with the title ─────
+//
+// When the title field of a node loses focus with a new title, the plan of
+// `text_tool_core::node_rename` is offered as a checklist: the chapter file
+// named after the old title, the links to it, and the foreshadows listing
+// the old title.  Every applied item reports its own result.
+
+/// `path` relative to `dir` with `/` separators.
+fn rel_to(dir: &Path, path: &Path) -> Option<String> {
+    Some(path.strip_prefix(dir).ok()?.to_string_lossy().replace('\\', "/"))
+}
+
+fn file_label(path: &Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned())
+}
+
+impl TextToolApp {
+    /// The title of the node at `path` changed from `old_title`: open the
+    /// 节点改名 checklist if anything else should follow.
+    pub(in crate::app) fn propose_node_rename(&mut self, path: &[usize], old_title: &str) {
+        let Some(root) = self.project_root.clone() else { return };
+        let Some(node) = node_at(&self.struct_roots, path) else { return };
+        let content = root.join("Content");
+        // Found under the old title: an unlinked file matched the title.
+        let mut before = node.clone();
+        before.title = old_title.to_owned();
+        let chapter_file = node_md_file(&content, &before).and_then(|f| rel_to(&content, &f));
+
+        let mut docs: Vec<(PathBuf, String)> = Vec::new();
+        if chapter_file.as_deref().is_some_and(|f| file_named_after(f, old_title)) {
+            self.project_index.rescan(&root);
+            docs = self.project_index.md_texts();
+            // Open panes may be ahead of the disk.
+            for pane in [&self.left_file, &self.right_file].into_iter().flatten().filter(|f| !f.scratch) {
+                match docs.iter_mut().find(|(p, _)| *p == pane.path) {
+                    Some((_, text)) => text.clone_from(&pane.content),
+                    None => docs.push((pane.path.clone(), pane.content.clone())),
+                }
+            }
+        }
+        let doc_refs: Vec<(&Path, &str)> = docs.iter().map(|(p, t)| (p.as_path(), t.as_str())).collect();
+        let plan = plan_node_rename(
+            old_title,
+            &node.title,
+            chapter_file.as_deref(),
+            &content,
+            |rel| content.join(rel).exists(),
+            &doc_refs,
+            &self.foreshadows,
+        );
+        if plan.is_empty() {
+            return;
+        }
+        self.node_rename = Some(NodeRenameDialog {
+            path: path.to_vec(),
+            file: plan.file.as_ref().is_some_and(|f| !f.taken),
+            links: vec![true; plan.links.len()],
+            foreshadows: vec![true; plan.foreshadows.len()],
+            plan,
+            results: None,
+        });
+    }
+
+    /// 重命名为唯一标题: give the node at `path` its parent's title as a
+    /// suffix, then offer the usual 节点改名 follow-ups.
+    pub(in crate::app) fn make_node_title_unique(&mut self, path: &[usize]) {
+        let Some(title) = unique_title_for(&self.struct_roots, path) else { return };
+        let Some(node) = node_at_mut(&mut self.struct_roots, path) else { return };
+        let old = std::mem::replace(&mut node.title, title.clone());
+        node.touch();
+        self.autosave_struct();
+        self.status = format!("已将「{old}」改为「{title}」");
+        self.propose_node_rename(path, &old);
+    }
+
+    pub(in crate::app) fn draw_node_rename_dialog(&mut self, ctx: &Context) {
+        let Some(dlg) = &mut self.node_rename else { return };
+        let mut open = true;
+        let mut apply = false;
+        let mut close = false;
+        egui::Window::new("节点改名")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(460.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("「{}」→「{}」", dlg.plan.old_title, dlg.plan.new_title));
+                if let Some(results) = &dlg.results {
+                    ui.separator();
+                    egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                        for (label, result) in results {
+                            match result {
+                                Ok(()) => ui.label(RichText::new(format!("✅ {label}")).color(Color32::from_rgb(120, 190, 120))),
+                                Err(e) => ui.label(RichText::new(format!("❌ {label}：{e}")).color(Color32::from_rgb(220, 110, 110))),
+                            };
+                        }
+                    });
+                    ui.add_space(6.0);
+                    if ui.button("关闭").clicked() {
+                        close = true;
+                    }
+                    return;
+                }
+                ui.label(RichText::new("选择要随标题一起更新的内容：").small().color(Color32::from_gray(150)));
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    if let Some(file) = &dlg.plan.file {
+                        if file.taken {
+                            ui.add_enabled(false, egui::Checkbox::new(&mut dlg.file, format!("重命名章节文件 {} → {}", file.from, file.to)));
+                            ui.label(RichText::new("已存在同名文件，章节文件保持原名，节点继续关联它")
+                                .small().color(Color32::from_rgb(220, 170, 90)));
+                        } else {
+                            ui.checkbox(&mut dlg.file, format!("重命名章节文件 {} → {}", file.from, file.to));
+                        }
+                    }
+                    if !dlg.plan.links.is_empty() {
+                        ui.add_space(4.0);
+                        ui.label(RichText::new(format!("指向该文件的链接（{}）", dlg.plan.links.len())).strong());
+                        ui.add_enabled_ui(dlg.file, |ui| {
+                            for (link, on) in dlg.plan.links.iter().zip(&mut dlg.links) {
+                                ui.checkbox(on, format!("{}:{}  {} → {}", file_label(&link.file), link.line_no, link.old_target, link.new_target));
+                            }
+                        });
+                    }
+                    if !dlg.plan.foreshadows.is_empty() {
+                        ui.add_space(4.0);
+                        ui.label(RichText::new(format!("关联此章节的伏笔（{}）", dlg.plan.foreshadows.len())).strong());
+                        for (fs, on) in dlg.plan.foreshadows.iter().zip(&mut dlg.foreshadows) {
+                            ui.checkbox(on, format!("伏笔「{}」", fs.name));
+                        }
+                    }
+                });
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("应用").clicked() {
+                        apply = true;
+                    }
+                    if ui.button("跳过").on_hover_text("只改标题，其余保持不变").clicked() {
+                        close = true;
+                    }
+                });
+                if ctx.input(|i| i.key_pressed(Key::Escape)) {
+                    close = true;
+                }
+            });
+
+        if !open || close {
+            self.node_rename = None;
+        } else if apply {
+            self.apply_node_rename();
+        }
+    }
+
+    /// Carry out the checked items of the open 节点改名 dialog and keep the
+    /// dialog open on the per-item results.
+    fn apply_node_rename(&mut self) {
+        let Some(dlg) = &self.node_rename else { return };
+        let Some(root) = self.project_root.clone() else { return };
+        let content = root.join("Content");
+        let path = dlg.path.clone();
+        let plan = dlg.plan.clone();
+        let rename_file = dlg.file;
+        let links: Vec<&LinkRewrite> = plan.links.iter().zip(&dlg.links).filter(|(_, on)| **on).map(|(l, _)| l).collect();
+        let foreshadows: Vec<usize> = plan.foreshadows.iter().zip(&dlg.foreshadows)
+            .filter(|(_, on)| **on)
+            .map(|(f, _)| f.index)
+            .collect();
+        let mut results: Vec<(String, Result<(), String>)> = Vec::new();
+        let node_ok = node_at(&self.struct_roots, &path).is_some_and(|n| n.title == plan.new_title);
+
+        // The chapter file first: the links follow only a renamed file.
+        let mut renamed: Option<(PathBuf, PathBuf)> = None;
+        if let Some(file) = plan.file.as_ref().filter(|f| rename_file && !f.taken) {
+            let from = content.join(&file.from);
+            let to = content.join(&file.to);
+            let label = format!("重命名章节文件 {} → {}", file.from, file.to);
+            let unsaved = [&self.left_file, &self.right_file].into_iter().flatten().any(|f| f.path == from && f.modified);
+            let result = if !node_ok {
+                Err("节点已不在原位置或标题又有改动".to_owned())
+            } else if unsaved {
+                Err("文件在编辑区中有未保存的修改，请先保存".to_owned())
+            } else if to.exists() {
+                Err("已存在同名文件".to_owned())
+            } else {
+                std::fs::rename(&from, &to).map_err(|e| e.to_string())
+            };
+            if result.is_ok() {
+                self.note_path_renamed(&from, &to);
+                renamed = Some((from, to));
+            }
+            results.push((label, result));
+        }
+        if node_ok {
+            if let (Some(file), Some(node)) = (&plan.file, node_at_mut(&mut self.struct_roots, &path)) {
+                // Keep the node on its file, whichever name it has now.
+                let rel = if renamed.is_some() { &file.to } else { &file.from };
+                if node.file.as_deref() != Some(rel) {
+                    node.file = Some(rel.clone());
+                    node.touch();
+                    results.push((format!("节点关联文件 {rel}"), Ok(())));
+                }
+            }
+        }
+
+        if !links.is_empty() {
+            match &renamed {
+                Some((from, to)) => {
+                    let mut by_file: BTreeMap<&Path, Vec<&LinkRewrite>> = BTreeMap::new();
+                    for link in &links {
+                        by_file.entry(link.file.as_path()).or_default().push(link);
+                    }
+                    for (file, rewrites) in by_file {
+                        // A link in the chapter itself moved with it.
+                        let file = if file == from.as_path() { to.as_path() } else { file };
+                        let label = format!("更新 {} 中的 {} 处链接", rel_to(&root, file).unwrap_or_else(|| file_label(file)), rewrites.len());
+                        results.push((label, self.rewrite_links_in(file, &rewrites)));
+                    }
+                }
+                None => results.push((format!("跳过 {} 处链接", links.len()), Err("章节文件未重命名".to_owned()))),
+            }
+        }
+
+        let mut retitled = 0;
+        for i in foreshadows {
+            let Some(fs) = self.foreshadows.get_mut(i) else { continue };
+            let label = format!("伏笔「{}」", fs.name);
+            if retitle_chapter_refs(fs, &plan.old_title, &plan.new_title) > 0 {
+                fs.touch();
+                retitled += 1;
+                results.push((label, Ok(())));
+            } else {
+                results.push((label, Err("已不再关联旧标题".to_owned())));
+            }
+        }
+        if retitled > 0 {
+            if let Err(e) = self.sync_foreshadows() {
+                results.push(("保存伏笔".to_owned(), Err(e.to_string())));
+            }
+        }
+
+        self.autosave_struct();
+        if renamed.is_some() {
+            self.refresh_tree();
+            self.reindex_project();
+        }
+        let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+        self.status = if failed == 0 {
+            format!("已随标题更新 {} 项", results.len())
+        } else {
+            format!("已随标题更新 {} 项，{failed} 项失败", results.len() - failed)
+        };
+        if let Some(dlg) = &mut self.node_rename {
+            dlg.results = Some(results);
+        }
+    }
+
+    /// Apply `rewrites` to `file`: in the pane showing it (one undo step),
+    /// else on disk.
+    fn rewrite_links_in(&mut self, file: &Path, rewrites: &[&LinkRewrite]) -> Result<(), String> {
+        let limit = self.settings.behavior.undo_limit;
+        let changed = "文件内容已改变，请手动更新".to_owned();
+        for (pane, undo) in [(&mut self.left_file, &mut self.left_undo_stack), (&mut self.right_file, &mut self.right_undo_stack)] {
+            if let Some(f) = pane.as_mut().filter(|f| f.path == file) {
+                let content = apply_link_rewrites(&f.content, rewrites).ok_or(changed)?;
+                undo.push(std::mem::replace(&mut f.content, content), limit);
+                f.modified = true;
+                return Ok(());
+            }
+        }
+        let text = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+        let content = apply_link_rewrites(&text, rewrites).ok_or(changed)?;
+        text_tool_core::safe_write(file, content).map(|_| ()).map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::attachments::project_relative;
+use text_tool_core::models::{node_at, node_at_mut, normalize_title};
+use text_tool_core::node_ops::{merge_with_next, merged_title, split_node};
+use text_tool_core::outline::parse_outline_entries;
+use text_tool_core::split::{sanitize_file_name, split_at_line};
+
+use super::super::{AppError, IoOp, NodeSplitDialog, TextToolApp};
+use super::novel::node_md_file;
+
+// ── 拆分节点 / 与下一个兄弟合并 ───────────────────────────────────────────────
+//
+// Context actions of the structure tree.  The tree edits are the pure
+// functions of `text_tool_core::node_ops`; splitting can also cut the
+// node's chapter file at a heading, the part from there on going to a new
+// file beside it that the new node points at.
+
+/// A node operation picked in the tree's context menu.
+pub(super) enum NodeOp {
+    Split(Vec<usize>),
+    /// Path of the first node; whether to keep its title.
+    MergeNext(Vec<usize>, bool),
+}
+
+/// 与下一个兄弟合并 submenu: concatenate the titles or keep the first.
+pub(super) fn merge_next_menu(ui: &mut egui::Ui, title: &str, next: &str, path: &[usize], op: &mut Option<NodeOp>) {
+    ui.menu_button("⛓ 与下一个兄弟合并", |ui| {
+        if ui.button(format!("标题合并为「{}」", merged_title(title, next, false))).clicked() {
+            *op = Some(NodeOp::MergeNext(path.to_vec(), false));
+            ui.close_menu();
+        }
+        if ui.button(format!("保留标题「{title}」")).clicked() {
+            *op = Some(NodeOp::MergeNext(path.to_vec(), true));
+            ui.close_menu();
+        }
+    }).response.on_hover_text(format!("「{next}」的子节点、关联对象、跨节点关联与摘要并入本节点，然后删除它"));
+}
+
+impl TextToolApp {
+    pub(in crate::app) fn merge_node_with_next(&mut self, path: &[usize], keep_first: bool) {
+        let Some(node) = node_at(&self.struct_roots, path) else { return };
+        let first = node.title.clone();
+        if !merge_with_next(&mut self.struct_roots, path, keep_first) {
+            return;
+        }
+        self.selected_node_path = path.to_vec();
+        self.struct_hover = None;
+        self.autosave_struct();
+        self.status = format!("已将下一个节点合并到「{first}」");
+    }
+
+    pub(in crate::app) fn open_node_split(&mut self, path: &[usize]) {
+        let Some(node) = node_at(&self.struct_roots, path) else { return };
+        let file = self.project_root.as_ref().and_then(|root| node_md_file(&root.join("Content"), node));
+        let headings = file.as_ref()
+            .and_then(|f| std::fs::read_to_string(f).ok())
+            .map(|text| parse_outline_entries(&text).into_iter().skip(1).map(|e| (e.line_no, e.title)).collect())
+            .unwrap_or_default();
+        self.node_split = Some(NodeSplitDialog {
+            path: path.to_vec(),
+            title: format!("{}（下）", node.title),
+            moved: BTreeSet::new(),
+            split_file: false,
+            file,
+            headings,
+            heading_line: None,
+        });
+    }
+
+    pub(in crate::app) fn draw_node_split_dialog(&mut self, ctx: &Context) {
+        let Some(dlg) = &mut self.node_split else { return };
+        let Some(node) = node_at(&self.struct_roots, &dlg.path) else {
+            self.node_split = None;
+            return;
+        };
+        let mut open = true;
+        let mut confirm = false;
+        let mut cancel = false;
+        egui::Window::new(format!("拆分节点「{}」", node.title))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(380.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("新节点标题:");
+                    ui.text_edit_singleline(&mut dlg.title);
+                });
+                ui.label(RichText::new("新节点插在本节点之后，与它同级同层级。").small().color(Color32::from_gray(150)));
+                ui.add_space(4.0);
+                if node.children.is_empty() {
+                    ui.label(RichText::new("（本节点没有子节点）").color(Color32::GRAY).small());
+                } else {
+                    ui.label("移到新节点的子节点:");
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        for (i, child) in node.children.iter().enumerate() {
+                            let mut on = dlg.moved.contains(&i);
+                            if ui.checkbox(&mut on, format!("{} {}", child.kind.icon(), child.title)).changed() {
+                                if on { dlg.moved.insert(i); } else { dlg.moved.remove(&i); }
+                            }
+                        }
+                    });
+                }
+                ui.separator();
+                match &dlg.file {
+                    Some(file) if !dlg.headings.is_empty() => {
+                        let name = file.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                        ui.checkbox(&mut dlg.split_file, format!("同时拆分章节文件 {name}"));
+                        ui.add_enabled_ui(dlg.split_file, |ui| {
+                            let selected = dlg.headings.iter()
+                                .find(|(line, _)| Some(*line) == dlg.heading_line)
+                                .map_or("选择标题…".to_owned(), |(line, title)| format!("{title}（第 {line} 行）"));
+                            egui::ComboBox::from_id_salt("node_split_heading")
+                                .selected_text(selected)
+                                .width(300.0)
+                                .show_ui(ui, |ui| {
+                                    for (line, title) in &dlg.headings {
+                                        ui.selectable_value(&mut dlg.heading_line, Some(*line), format!("{title}（第 {line} 行）"));
+                                    }
+                                });
+                            ui.label(RichText::new("从该标题起的内容移到新文件，新节点指向它")
+                                .small().color(Color32::from_gray(150)));
+                        });
+                    }
+                    Some(_) => {
+                        ui.label(RichText::new("章节文件中没有可拆分的标题（第一个标题之后）").small().color(Color32::GRAY));
+                    }
+                    None => {
+                        ui.label(RichText::new("未找到本节点的章节文件").small().color(Color32::GRAY));
+                    }
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let ready = !normalize_title(&dlg.title).is_empty() && (!dlg.split_file || dlg.heading_line.is_some());
+                    if ui.add_enabled(ready, egui::Button::new("拆分")).clicked() { confirm = true; }
+                    if ui.button("取消").clicked() { cancel = true; }
+                });
+            });
+
+        if !open || cancel {
+            self.node_split = None;
+        } else if confirm {
+            if let Some(dlg) = self.node_split.take() {
+                self.apply_node_split(dlg);
+            }
+        }
+    }
+
+    fn apply_node_split(&mut self, dlg: NodeSplitDialog) {
+        let title = normalize_title(&dlg.title);
+        // Cut the file first: nothing changes in the tree if that fails.
+        let new_file = match (dlg.split_file, &dlg.file, dlg.heading_line) {
+            (true, Some(file), Some(line)) => match self.split_chapter_file(file, line, &title) {
+                Ok(rel) => Some(rel),
+                Err(e) => {
+                    self.report(e);
+                    return;
+                }
+            },
+            _ => None,
+        };
+        let Some(new_path) = split_node(&mut self.struct_roots, &dlg.path, &title, &dlg.moved) else { return };
+        if let Some(node) = node_at_mut(&mut self.struct_roots, &new_path) {
+            node.file = new_file.clone();
+        }
+        self.selected_node_path = new_path;
+        self.struct_hover = None;
+        self.autosave_struct();
+        self.status = match new_file {
+            Some(rel) => format!("已拆分出「{title}」，章节文件后半部分写入 Content/{rel}"),
+            None => format!("已拆分出「{title}」"),
+        };
+    }
+
+    /// Move the part of `file` from `line` on to a new file named after
+    /// `title` in the same folder; returns its path relative to `Content/`.
+    fn split_chapter_file(&mut self, file: &Path, line: usize, title: &str) -> Result<String, AppError> {
+        let root = self.project_root.clone().ok_or(AppError::NoProject)?;
+        let open_in = [(&self.left_file, true), (&self.right_file, false)]
+            .into_iter()
+            .filter(|(pane, _)| pane.as_ref().is_some_and(|f| f.path == file))
+            .map(|(pane, left)| (pane.as_ref().is_some_and(|f| f.modified), left))
+            .collect::<Vec<_>>();
+        if open_in.iter().any(|(modified, _)| *modified) {
+            return Err(AppError::Validation("章节文件在编辑区中有未保存的修改，请先保存".to_owned()));
+        }
+        let text = std::fs::read_to_string(file).map_err(AppError::io(IoOp::Read, file))?;
+        let (first, second) = split_at_line(&text, line);
+        let dir = file.parent().map_or_else(|| root.join("Content"), Path::to_path_buf);
+        let stem = sanitize_file_name(title);
+        let mut dest = dir.join(format!("{stem}.md"));
+        let mut n = 2;
+        while dest.exists() {
+            dest = dir.join(format!("{stem} ({n}).md"));
+            n += 1;
+        }
+        text_tool_core::safe_write(&dest, &second).map_err(AppError::io(IoOp::Write, &dest))?;
+        text_tool_core::safe_write(file, &first).map_err(AppError::io(IoOp::Write, file))?;
+        for (_, left) in open_in {
+            self.reload_pane(left);
+        }
+        self.refresh_tree();
+        self.reindex_project();
+        Ok(project_relative(&root.join("Content"), &dest).unwrap_or_else(|| stem + ".md"))
+    }
+}
@@ -1,53 +1,76 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use egui::{Context, RichText, Color32, Key};
-use super::super::{TextToolApp, FileNode, StructNode, FileTreeMode, Panel, rfd_pick_folder};
+use super::super::{TextToolApp, FocusRegion, FileNode, StructNode, TreeFilter, ChapterTag, FileTreeMode, Panel, PaneAccept, PaneTarget, PaneView, ProjectIndex,
+    TextStats, count_words, format_reading_time, request_open, rfd_pick_folder};
 use super::markdown::render_markdown;
+use super::tree_status::TreeStructure;
+use text_tool_core::file_manager::{breadcrumb, elide_crumbs, find_node_mut, middle_truncate};
+use text_tool_core::changelog::Change;
+use text_tool_core::completion::{apply_mention, filter_mentions, mention_before_caret};
+use text_tool_core::gutter::{gutter_digits, row_line_numbers};
+use text_tool_core::annotations::{annotation_ranges, find_annotations, remove_annotation, Annotation};
+use text_tool_core::markers::{find_markers, marker_ranges, Marker};
+use text_tool_core::outline::parse_outline_entries;
+use text_tool_core::paragraphs::convert_punct_before_caret;
 
 impl TextToolApp {
     // ── Novel panel: file tree + dual editors ─────────────────────────────────
 
     pub(in crate::app) fn draw_file_tree(&mut self, ctx: &Context) {
-        let mut open_left: Option<PathBuf> = None;
-        let mut open_right: Option<PathBuf> = None;
+        let mut open: Option<(PathBuf, PaneTarget)> = None;
         let mut new_in: Option<PathBuf> = None;
         let mut toggle_path: Option<PathBuf> = None;
         let mut select_path: Option<PathBuf> = None;
         let mut rename_path: Option<PathBuf> = None;
         let mut delete_path: Option<PathBuf> = None;
+        let mut pin_path: Option<PathBuf> = None;
+        let mut add_to_struct: Option<PathBuf> = None;
+        let mut history_for: Option<PathBuf> = None;
+        let mut multi_toggle: Option<PathBuf> = None;
+        let mut scroll_to_line: Option<usize> = None;
+        let mut jump_to_marker: Option<(PathBuf, usize)> = None;
 
-        egui::SidePanel::left("file_tree")
+        let rect = egui::SidePanel::left("file_tree")
             .resizable(true)
             .default_width(210.0)
             .min_width(130.0)
             .show(ctx, |ui| {
                 ui.add_space(4.0);
-                // When the Files tab is hidden (default), force Chapter-tree mode.
-                if !self.md_settings.show_files_tab {
+                // When the Files tab is hidden (default), fall back to Chapter-tree mode.
+                if !self.settings.markdown.show_files_tab && self.file_tree_mode == FileTreeMode::Files {
                     self.file_tree_mode = FileTreeMode::Chapters;
                 }
                 ui.horizontal(|ui| {
                     ui.heading("导航");
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        // Mode toggle: Files ↔ Chapter tree
+                        // Mode toggle: Files ↔ Chapter tree ↔ Headings ↔ Markers ↔ Annotations
                         // Only show the Files toggle when the setting is enabled.
-                        let is_chapters = self.file_tree_mode == FileTreeMode::Chapters;
-                        if self.md_settings.show_files_tab {
-                            if ui.selectable_label(is_chapters, "📖 章节")
-                                .on_hover_text("章节树视图（按结构导航）").clicked()
-                            {
-                                self.file_tree_mode = FileTreeMode::Chapters;
-                            }
-                            if ui.selectable_label(!is_chapters, "📁 文件")
+                        if self.settings.markdown.show_files_tab
+                            && ui.selectable_label(self.file_tree_mode == FileTreeMode::Files, "📁 文件")
                                 .on_hover_text("文件系统视图").clicked()
-                            {
-                                self.file_tree_mode = FileTreeMode::Files;
-                            }
-                        } else {
-                            // Files tab disabled — show a static label only.
-                            ui.label(
-                                egui::RichText::new("📖 章节")
-                                    .color(Color32::from_rgb(100, 200, 120)),
-                            );
+                        {
+                            self.file_tree_mode = FileTreeMode::Files;
+                        }
+                        if ui.selectable_label(self.file_tree_mode == FileTreeMode::Annotations, "💬 注释")
+                            .on_hover_text("正文中的 %% 注释 %%（单击跳转，导出时不包含）").clicked()
+                        {
+                            self.file_tree_mode = FileTreeMode::Annotations;
+                        }
+                        if ui.selectable_label(self.file_tree_mode == FileTreeMode::Markers, "📌 标记")
+                            .on_hover_text("待处理标记（TODO、【卡】等，单击跳转）").clicked()
+                        {
+                            self.file_tree_mode = FileTreeMode::Markers;
+                        }
+                        if ui.selectable_label(self.file_tree_mode == FileTreeMode::Headings, "📑 大纲")
+                            .on_hover_text("当前文件的标题大纲（单击跳转）").clicked()
+                        {
+                            self.file_tree_mode = FileTreeMode::Headings;
+                        }
+                        if ui.selectable_label(self.file_tree_mode == FileTreeMode::Chapters, "📖 章节")
+                            .on_hover_text("章节树视图（按结构导航）").clicked()
+                        {
+                            self.file_tree_mode = FileTreeMode::Chapters;
                         }
                     });
                 });
@@ -61,8 +84,7 @@ impl TextToolApp {
                         if ui.button("📂 打开项目").clicked() {
                             if let Some(path) = rfd_pick_folder() {
                                 // will be applied after the panel closes
-                                open_left = Some(path.clone()); // reuse as signal
-                                let _ = path; // handled below via special case
+                                request_open(&mut open, path, PaneTarget::Left);
                             }
                         }
                     });
@@ -80,6 +102,18 @@ impl TextToolApp {
                             }
                         }
                     });
+                    // Multi-selection actions
+                    if self.multi_selected.len() >= 2 && self.file_tree_mode == FileTreeMode::Files {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(format!("已选 {} 个文件", self.multi_selected.len())).small());
+                            if ui.small_button("合并为一个文件…").clicked() {
+                                self.open_merge_dialog();
+                            }
+                            if ui.small_button("清除").clicked() {
+                                self.multi_selected.clear();
+                            }
+                        });
+                    }
                     // F2 hint when a file is selected
                     if self.selected_file_path.is_some() && self.file_tree_mode == FileTreeMode::Files {
                         ui.label(
@@ -91,16 +125,38 @@ impl TextToolApp {
 
                     egui::ScrollArea::vertical().id_salt("file_tree_scroll").show(ui, |ui| {
                         if self.file_tree_mode == FileTreeMode::Files {
+                            self.refresh_file_node_map();
                             let nodes = self.file_tree.clone();
                             let selected = &self.selected_file_path;
+                            let pinned: Vec<PathBuf> = match &self.project_root {
+                                Some(root) => self.project_config.pinned_files.iter()
+                                    .map(|rel| root.join(rel)).collect(),
+                                None => Vec::new(),
+                            };
+                            let reveal = self.tree_reveal.take();
+                            let structure = self.tree_structure();
                             for node in &nodes {
                                 Self::draw_tree_node(
                                     ui, node, 0,
-                                    &mut open_left, &mut open_right, &mut new_in,
+                                    &mut open, &mut new_in,
                                     &mut toggle_path, selected, &mut select_path,
+                                    &self.multi_selected, &mut multi_toggle,
                                     &mut rename_path, &mut delete_path,
+                                    &pinned, &mut pin_path, &self.project_index,
+                                    reveal.as_deref(), &structure, &mut add_to_struct, &mut history_for,
                                 );
                             }
+                        } else if self.file_tree_mode == FileTreeMode::Headings {
+                            // ── Heading outline of the left file ──────────────
+                            if let Some(line_no) = self.draw_heading_outline(ui) {
+                                scroll_to_line = Some(line_no);
+                            }
+                        } else if self.file_tree_mode == FileTreeMode::Markers {
+                            // ── 待处理标记 ────────────────────────────────────
+                            jump_to_marker = self.draw_marker_list(ui);
+                        } else if self.file_tree_mode == FileTreeMode::Annotations {
+                            // ── 注释 ──────────────────────────────────────────
+                            jump_to_marker = self.draw_annotation_list(ui);
                         } else {
                             // ── Chapter tree view ─────────────────────────────
                             if self.struct_roots.is_empty() {
@@ -113,34 +169,67 @@ impl TextToolApp {
                                 let roots = self.struct_roots.clone();
                                 Self::draw_chapter_tree(
                                     ui, &roots, 0,
-                                    &mut open_left,
+                                    &mut open,
                                     &self.project_root,
                                 );
                             }
                         }
                     });
                 }
-            });
+            }).response.rect;
+        self.note_focus_region(ctx, FocusRegion::Navigator, rect);
+
+        // Enter opens the highlighted file through the same deferred path.
+        if self.file_tree_mode == FileTreeMode::Files
+            && ctx.memory(|m| m.focused().is_none())
+            && ctx.input(|i| i.key_pressed(Key::Enter) && i.modifiers.is_none())
+        {
+            if let Some(p) = self.selected_file_path.clone().filter(|p| p.is_file()) {
+                request_open(&mut open, p, PaneTarget::Auto);
+            }
+        }
 
         // Apply deferred actions
-        if let Some(p) = open_left {
+        if let Some((p, target)) = open {
             // Special case: if it's a directory, open as project
             if p.is_dir() && self.project_root.is_none() {
                 self.open_project(p);
             } else {
-                self.open_file_in_pane(&p, true);
+                let left = match target {
+                    PaneTarget::Left => true,
+                    PaneTarget::Right => false,
+                    PaneTarget::Auto => self.opens_in_left(&p),
+                };
+                let r = self.open_file_in_pane(&p, left);
+                self.notify_open(r);
             }
         }
-        if let Some(p) = open_right {
-            self.open_file_in_pane(&p, false);
-        }
         if let Some(p) = new_in {
             self.new_file(p);
         }
         if let Some(p) = toggle_path {
             Self::toggle_expand_in_tree(&mut self.file_tree, &p);
+            // First expansion of a folder: list it in the background.
+            if find_node_mut(&mut self.file_tree, &p).is_some_and(|n| n.expanded && !n.loaded) {
+                self.project_index.spawn_list_dir(p, self.tree_filter());
+            }
         }
         if let Some(p) = select_path {
+            self.multi_selected.clear();
+            self.selected_file_path = Some(p);
+        }
+        if let Some(p) = multi_toggle {
+            // The plainly-selected file joins the first Ctrl+click.
+            if self.multi_selected.is_empty() {
+                if let Some(sel) = self.selected_file_path.clone().filter(|s| *s != p && s.is_file()) {
+                    self.multi_selected.push(sel);
+                }
+            }
+            if let Some(pos) = self.multi_selected.iter().position(|m| *m == p) {
+                self.multi_selected.remove(pos);
+            } else {
+                self.multi_selected.push(p.clone());
+            }
             self.selected_file_path = Some(p);
         }
         if let Some(p) = rename_path {
@@ -154,9 +243,24 @@ impl TextToolApp {
             });
         }
         // Open delete confirmation dialog (deferred to avoid borrow conflict)
+        if let Some(p) = add_to_struct {
+            self.open_struct_add_file(&p);
+        }
+        if let Some(p) = history_for {
+            self.open_history(&p);
+        }
+        if let Some(p) = pin_path {
+            self.toggle_file_pin(&p);
+        }
         if let Some(p) = delete_path {
             self.delete_confirm_path = Some(p);
         }
+        if let Some(line_no) = scroll_to_line {
+            self.request_scroll_to_line(true, line_no);
+        }
+        if let Some((path, line_no)) = jump_to_marker {
+            self.open_at_line(&path, line_no);
+        }
 
         // Handle F2 key: open rename dialog for selected file when panel is focused
         if self.rename_dialog.is_none() {
@@ -175,12 +279,180 @@ impl TextToolApp {
         }
     }
 
+    /// Render the headings of the left file. The heading containing the top of
+    /// the editor viewport is highlighted. Returns the line to jump to when an
+    /// entry is clicked.
+    fn draw_heading_outline(&self, ui: &mut egui::Ui) -> Option<usize> {
+        let Some(f) = self.left_file.as_ref().filter(|f| f.is_markdown()) else {
+            ui.label(RichText::new("请先在编辑区打开一个 Markdown 文件").small().color(Color32::GRAY));
+            return None;
+        };
+        let entries = parse_outline_entries(&f.content);
+        if entries.is_empty() {
+            ui.label(RichText::new("当前文件没有标题").small().color(Color32::GRAY));
+            return None;
+        }
+        let current = entries.iter().rposition(|e| e.line_no <= self.left_top_line);
+        let min_level = entries.iter().map(|e| e.level).min().unwrap_or(1);
+        let mut clicked = None;
+        for (i, entry) in entries.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add_space((entry.level - min_level) as f32 * 12.0);
+                let resp = ui.selectable_label(current == Some(i), &entry.title)
+                    .on_hover_text(format!("第 {} 行", entry.line_no));
+                if resp.clicked() {
+                    clicked = Some(entry.line_no);
+                }
+            });
+        }
+        clicked
+    }
+
+    /// Render the 待处理标记 of the left file (live) and of the other project
+    /// files (from the index).  Returns the file and line to jump to.
+    fn draw_marker_list(&mut self, ui: &mut egui::Ui) -> Option<(PathBuf, usize)> {
+        let tokens = &self.settings.behavior.stuck_markers;
+        if tokens.iter().all(|t| t.trim().is_empty()) {
+            ui.label(RichText::new("尚未设置标记\n请在「设置 → 行为」中添加").small().color(Color32::GRAY));
+            return None;
+        }
+        let generation = self.project_index.generation();
+        if self.markers_key.as_ref().is_none_or(|(g, t)| *g != generation || t != tokens) {
+            self.project_markers = self.project_index.markers(tokens);
+            self.markers_key = Some((generation, tokens.clone()));
+        }
+
+        let mut clicked = None;
+        let current = self.left_file.as_ref().filter(|f| f.is_markdown());
+        ui.label(RichText::new("当前文件").strong());
+        match current {
+            None => {
+                ui.label(RichText::new("请先在编辑区打开一个 Markdown 文件").small().color(Color32::GRAY));
+            }
+            Some(f) => {
+                let found = find_markers(&f.content, tokens);
+                if found.is_empty() {
+                    ui.label(RichText::new("没有待处理标记").small().color(Color32::GRAY));
+                }
+                for m in &found {
+                    if marker_row(ui, m) {
+                        clicked = Some((f.path.clone(), m.line_no));
+                    }
+                }
+            }
+        }
+
+        ui.add_space(6.0);
+        let others: Vec<&(PathBuf, Vec<Marker>)> = self.project_markers.iter()
+            .filter(|(p, _)| current.is_none_or(|f| f.path != *p))
+            .collect();
+        let total: usize = others.iter().map(|(_, found)| found.len()).sum();
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("其他文件 ({total})")).strong());
+            if self.project_index.is_scanning() {
+                ui.spinner();
+            }
+        });
+        for (path, found) in others {
+            let name = self.project_root.as_deref()
+                .and_then(|root| path.strip_prefix(root).ok())
+                .unwrap_or(path)
+                .display()
+                .to_string();
+            egui::CollapsingHeader::new(format!("{name} ({})", found.len()))
+                .id_salt(path)
+                .default_open(true)
+                .show(ui, |ui| {
+                    for m in found {
+                        if marker_row(ui, m) {
+                            clicked = Some((path.clone(), m.line_no));
+                        }
+                    }
+                });
+        }
+        clicked
+    }
+
+    /// Render the 注释 of the left file (live, each with a 解决 button that
+    /// deletes it) and of the other project files (from the index).
+    /// Returns the file and line to jump to.
+    fn draw_annotation_list(&mut self, ui: &mut egui::Ui) -> Option<(PathBuf, usize)> {
+        let generation = self.project_index.generation();
+        if self.annotations_generation != Some(generation) {
+            self.project_annotations = self.project_index.annotations();
+            self.annotations_generation = Some(generation);
+        }
+
+        let mut clicked = None;
+        let mut resolve = None;
+        let current = self.left_file.as_ref().filter(|f| f.is_markdown() && !f.scratch);
+        ui.label(RichText::new("当前文件").strong());
+        match current {
+            None => {
+                ui.label(RichText::new("请先在编辑区打开一个 Markdown 文件").small().color(Color32::GRAY));
+            }
+            Some(f) => {
+                let found = find_annotations(&f.content);
+                if found.is_empty() {
+                    ui.label(RichText::new("没有注释\n用 %% … %% 在正文中添加").small().color(Color32::GRAY));
+                }
+                for a in &found {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✔").on_hover_text("解决：从正文中删除这条注释").clicked() {
+                            resolve = Some(a.range.clone());
+                        }
+                        if annotation_row(ui, a) {
+                            clicked = Some((f.path.clone(), a.line_no));
+                        }
+                    });
+                }
+            }
+        }
+
+        ui.add_space(6.0);
+        let others: Vec<&(PathBuf, Vec<Annotation>)> = self.project_annotations.iter()
+            .filter(|(p, _)| current.is_none_or(|f| f.path != *p))
+            .collect();
+        let total: usize = others.iter().map(|(_, found)| found.len()).sum();
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("其他文件 ({total})")).strong());
+            if self.project_index.is_scanning() {
+                ui.spinner();
+            }
+        });
+        for (path, found) in others {
+            let name = self.project_root.as_deref()
+                .and_then(|root| path.strip_prefix(root).ok())
+                .unwrap_or(path)
+                .display()
+                .to_string();
+            egui::CollapsingHeader::new(format!("{name} ({})", found.len()))
+                .id_salt(("annotations", path))
+                .default_open(true)
+                .show(ui, |ui| {
+                    for a in found {
+                        if annotation_row(ui, a) {
+                            clicked = Some((path.clone(), a.line_no));
+                        }
+                    }
+                });
+        }
+
+        if let (Some(range), Some(f)) = (resolve, self.left_file.as_mut()) {
+            let content = remove_annotation(&f.content, range);
+            self.left_undo_stack.push(std::mem::replace(&mut f.content, content), self.settings.behavior.undo_limit);
+            f.modified = true;
+            self.status = "已解决 1 条注释".to_owned();
+        }
+        clicked
+    }
+
     /// Render the chapter structure tree. Clicking a leaf chapter opens its `.md` file.
     pub(in crate::app) fn draw_chapter_tree(
         ui: &mut egui::Ui,
         nodes: &[StructNode],
         depth: usize,
-        open_left: &mut Option<PathBuf>,
+        open: &mut Option<(PathBuf, PaneTarget)>,
         project_root: &Option<std::path::PathBuf>,
     ) {
         for (idx, node) in nodes.iter().enumerate() {
@@ -196,9 +468,8 @@ impl TextToolApp {
                     );
                     if resp.clicked() || resp.double_clicked() {
                         if let Some(root) = project_root {
-                            let needle = node.title.to_lowercase();
-                            if let Some(path) = find_md_for_title(&root.join("Content"), &needle) {
-                                *open_left = Some(path);
+                            if let Some(path) = node_md_file(&root.join("Content"), node) {
+                                request_open(open, path, PaneTarget::Auto);
                             }
                         }
                     }
@@ -216,38 +487,58 @@ impl TextToolApp {
                 .id_salt(format!("ch_tree_{}_{}", depth, idx))
                 .default_open(true)
                 .show(ui, |ui| {
-                    Self::draw_chapter_tree(ui, &node.children, depth + 1, open_left, project_root);
+                    Self::draw_chapter_tree(ui, &node.children, depth + 1, open, project_root);
                 });
             }
         }
     }
 
+    #[allow(clippy::too_many_arguments, clippy::only_used_in_recursion)]
     pub(in crate::app) fn draw_tree_node(
         ui: &mut egui::Ui,
         node: &FileNode,
         depth: usize,
-        open_left: &mut Option<PathBuf>,
-        open_right: &mut Option<PathBuf>,
+        open: &mut Option<(PathBuf, PaneTarget)>,
         new_in: &mut Option<PathBuf>,
         toggle_path: &mut Option<PathBuf>,
         selected_path: &Option<PathBuf>,
         select_path: &mut Option<PathBuf>,
+        multi_selected: &[PathBuf],
+        multi_toggle: &mut Option<PathBuf>,
         rename_path: &mut Option<PathBuf>,
         delete_path: &mut Option<PathBuf>,
+        pinned: &[PathBuf],
+        pin_path: &mut Option<PathBuf>,
+        index: &ProjectIndex,
+        reveal: Option<&Path>,
+        structure: &TreeStructure,
+        add_to_struct: &mut Option<PathBuf>,
+        history_for: &mut Option<PathBuf>,
     ) {
         let indent = depth as f32 * 12.0;
+        // Listed only with 显示被忽略的文件 on.
+        let ignored_color = Color32::from_gray(110);
         ui.horizontal(|ui| {
             ui.add_space(indent);
             if node.is_dir {
                 let icon = if node.expanded { "▼" } else { "▶" };
-                let resp = ui.selectable_label(
-                    false,
-                    format!("{icon} 📁 {}", node.name),
-                );
+                let mut text = RichText::new(format!("{icon} 📁 {}", node.name));
+                if node.ignored {
+                    text = text.color(ignored_color);
+                }
+                let resp = ui.selectable_label(false, text);
                 if resp.clicked() {
                     *toggle_path = Some(node.path.clone());
                 }
-                resp.on_hover_text(if node.expanded { "点击折叠" } else { "点击展开" });
+                if reveal == Some(node.path.as_path()) {
+                    resp.scroll_to_me(Some(egui::Align::Center));
+                }
+                let hint = if node.expanded { "点击折叠" } else { "点击展开" };
+                if node.ignored {
+                    resp.on_hover_text(format!("{hint}（被 .gitignore / .texttoolignore 忽略）"));
+                } else {
+                    resp.on_hover_text(hint);
+                }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.small_button("➕").on_hover_text("新建文件").clicked() {
                         *new_in = Some(node.path.clone());
@@ -261,11 +552,50 @@ impl TextToolApp {
                 } else {
                     "📃"
                 };
-                let is_selected = selected_path.as_deref() == Some(node.path.as_path());
-                let resp = ui.selectable_label(is_selected, format!("{icon} {}", node.name));
+                let is_selected = selected_path.as_deref() == Some(node.path.as_path())
+                    || multi_selected.contains(&node.path);
+                let is_pinned = pinned.contains(&node.path);
+                let star = if is_pinned { " ⭐" } else { "" };
+                let chapter = structure.node_for(&node.path);
+                let linked = chapter.flatten();
+                let unlinked = matches!(chapter, Some(None));
+                let done = linked.is_some_and(|n| n.done);
+                let mut text = RichText::new(format!("{icon} {}{star}{}", node.name, if done { " ✅" } else { "" }));
+                if node.ignored {
+                    text = text.color(ignored_color);
+                } else if done {
+                    text = text.color(Color32::from_rgb(120, 180, 120));
+                }
+                let mut resp = ui.selectable_label(is_selected, text);
+                if node.ignored {
+                    resp = resp.on_hover_text("被 .gitignore / .texttoolignore 忽略：不参与搜索、索引和导出");
+                } else if let Some(n) = linked.filter(|n| n.tag != ChapterTag::Normal) {
+                    ui.label(RichText::new("●").small().color(n.tag.color()))
+                        .on_hover_text(format!("{}: {}", n.title, n.tag.label()));
+                } else if unlinked && structure.marks_unlinked() {
+                    ui.label(RichText::new("未纳入结构").small().color(Color32::from_gray(120)))
+                        .on_hover_text("章节结构中没有对应此文件的章节；右键可添加");
+                }
                 resp.context_menu(|ui| {
-                    if ui.button("打开 / 在左侧打开").clicked() {
-                        *open_left = Some(node.path.clone());
+                    if ui.button("在左侧打开").clicked() {
+                        request_open(open, node.path.clone(), PaneTarget::Left);
+                        ui.close_menu();
+                    }
+                    if ui.button("在右侧打开").clicked() {
+                        request_open(open, node.path.clone(), PaneTarget::Right);
+                        ui.close_menu();
+                    }
+                    let pin_label = if is_pinned { "取消固定" } else { "⭐ 固定到快捷栏" };
+                    if ui.button(pin_label).clicked() {
+                        *pin_path = Some(node.path.clone());
+                        ui.close_menu();
+                    }
+                    if unlinked && ui.button("添加到结构…").clicked() {
+                        *add_to_struct = Some(node.path.clone());
+                        ui.close_menu();
+                    }
+                    if icon == "📄" && ui.button("历史版本…").clicked() {
+                        *history_for = Some(node.path.clone());
                         ui.close_menu();
                     }
                     ui.separator();
@@ -279,26 +609,58 @@ impl TextToolApp {
                     }
                 });
                 if resp.clicked() {
-                    *select_path = Some(node.path.clone());
+                    if ui.input(|i| i.modifiers.command) {
+                        *multi_toggle = Some(node.path.clone());
+                    } else {
+                        *select_path = Some(node.path.clone());
+                    }
                 }
                 if resp.double_clicked() {
-                    // All files open in the left (main) editor
-                    *open_left = Some(node.path.clone());
+                    request_open(open, node.path.clone(), PaneTarget::Auto);
+                }
+                resp.on_hover_ui(|ui| {
+                    ui.label("单击选中  Ctrl+单击多选  双击打开  右键菜单");
+                    // Heading preview from the project index
+                    for h in index.headings(&node.path).unwrap_or_default().iter().take(8) {
+                        ui.label(RichText::new(format!("{}{}", "  ".repeat(h.level.saturating_sub(1)), h.title))
+                            .small().color(Color32::from_gray(160)));
+                    }
+                });
+                if icon == "📄" {
+                    if let Some(count) = index.word_count(&node.path) {
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.label(RichText::new(count.to_string())
+                                .small().color(Color32::from_gray(120)))
+                                .on_hover_text("字数");
+                        });
+                    }
                 }
-                resp.on_hover_text("单击选中  双击打开  右键菜单");
             }
         });
 
+        if node.is_dir && node.expanded && !node.loaded {
+            // Only show the spinner when the listing takes a noticeable time.
+            let slow = index.listing_since(&node.path)
+                .is_some_and(|t| t.elapsed() > std::time::Duration::from_millis(200));
+            if slow {
+                ui.horizontal(|ui| {
+                    ui.add_space(indent + 12.0);
+                    ui.spinner();
+                    ui.label(RichText::new("加载中…").small().color(Color32::from_gray(140)));
+                });
+            }
+        }
         if node.is_dir && node.expanded {
             for child in &node.children {
-                Self::draw_tree_node(ui, child, depth + 1, open_left, open_right, new_in,
-                    toggle_path, selected_path, select_path, rename_path, delete_path);
+                Self::draw_tree_node(ui, child, depth + 1, open, new_in,
+                    toggle_path, selected_path, select_path, multi_selected, multi_toggle,
+                    rename_path, delete_path, pinned, pin_path, index, reveal, structure, add_to_struct, history_for);
             }
         }
     }
 
     /// Toggle the `expanded` flag of the tree node matching `path`.
-    pub(in crate::app) fn toggle_expand_in_tree(nodes: &mut Vec<FileNode>, path: &std::path::Path) -> bool {
+    pub(in crate::app) fn toggle_expand_in_tree(nodes: &mut [FileNode], path: &std::path::Path) -> bool {
         for node in nodes.iter_mut() {
             if node.path == path {
                 node.expanded = !node.expanded;
@@ -311,18 +673,57 @@ impl TextToolApp {
         false
     }
 
+    /// Expand every folder on the way to `path` (inclusive), loading
+    /// unloaded ones on the spot.
+    fn expand_to_path(nodes: &mut [FileNode], path: &Path, filter: &TreeFilter) -> bool {
+        for node in nodes.iter_mut() {
+            if node.is_dir && path.starts_with(&node.path) {
+                if !node.loaded {
+                    let _ = node.load_children(filter);
+                }
+                node.expanded = true;
+                Self::expand_to_path(&mut node.children, path, filter);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Show `dir` in the Files view of the navigation tree, expanded and
+    /// scrolled into view.
+    pub(in crate::app) fn reveal_in_tree(&mut self, dir: &Path) {
+        if !self.settings.markdown.show_files_tab {
+            self.status = "请先在设置中启用「文件」标签".to_owned();
+            return;
+        }
+        let filter = self.tree_filter();
+        if Self::expand_to_path(&mut self.file_tree, dir, &filter) {
+            self.file_tree_mode = FileTreeMode::Files;
+            self.tree_reveal = Some(dir.to_owned());
+        }
+    }
+
     pub(in crate::app) fn draw_editors(&mut self, ctx: &Context) {
         let mut do_sync_folders   = false;
+        let mut open_diff = false;
         let mut switch_to_obj_idx: Option<usize> = None;
+        let mut reveal: Option<PathBuf> = None;
+        let mut reload = false;
+        let mut show_markers = false;
+        let mut show_annotations = false;
+        let mut design_action = None;
 
         // ── Right sidebar: world-object reference cards ───────────────────────
         // Snapshot non-mutable data before any borrow of `self`.
         let objects_snapshot: Vec<_> = self.world_objects.iter().enumerate()
-            .map(|(i, o)| (i, o.icon(), o.name.clone(), o.kind.label(), o.description.clone(), o.links.len()))
+            .map(|(i, o)| {
+                let icon = self.project_config.kind_icon(&o.kind).to_owned();
+                (i, icon, o.name.clone(), o.kind.label().to_owned(), o.description.clone(), o.links.len())
+            })
             .collect();
-        let selected_obj = self.selected_obj_idx;
+        let selected_obj = self.selected_obj_idx();
 
-        egui::SidePanel::right("obj_ref_sidebar")
+        let rect = egui::SidePanel::right("obj_ref_sidebar")
             .resizable(true)
             .default_width(190.0)
             .min_width(120.0)
@@ -338,10 +739,10 @@ impl TextToolApp {
                         // Quick-add: add a new unnamed object and switch to edit it
                         if ui.small_button("➕").on_hover_text("快速添加新对象（切换到世界对象面板编辑）").clicked() {
                             let idx = self.world_objects.len();
-                            self.world_objects.push(crate::app::WorldObject::new(
-                                &format!("新对象{}", idx + 1),
-                                crate::app::ObjectKind::Character,
-                            ));
+                            let name = format!("新对象{}", idx + 1);
+                            let kind = crate::app::ObjectKind::Character;
+                            self.record_change(Change::ObjectAdded { name: &name, kind: kind.label() });
+                            self.world_objects.push(crate::app::WorldObject::new(&name, kind));
                             switch_to_obj_idx = Some(idx);
                         }
                     });
@@ -369,11 +770,11 @@ impl TextToolApp {
                                 .show(ui, |ui| {
                                     ui.set_min_width(ui.available_width());
                                     ui.horizontal(|ui| {
-                                        ui.label(RichText::new(*icon).size(16.0));
+                                        ui.label(RichText::new(icon).size(16.0));
                                         ui.vertical(|ui| {
                                             ui.label(RichText::new(name).strong().size(12.0));
                                             ui.label(
-                                                RichText::new(*kind)
+                                                RichText::new(kind)
                                                     .size(10.0)
                                                     .color(Color32::from_gray(160)),
                                             );
@@ -400,6 +801,9 @@ impl TextToolApp {
                                 .response
                                 .interact(egui::Sense::click());
 
+                            if is_sel && self.focus_scroll {
+                                card.scroll_to_me(None);
+                            }
                             if card.clicked() {
                                 switch_to_obj_idx = Some(*i);
                             }
@@ -412,10 +816,118 @@ impl TextToolApp {
                         }
                     });
                 }
-            });
+            }).response.rect;
+        self.note_focus_region(ctx, FocusRegion::ObjectRefs, rect);
+
+        // ── Right editor pane: shown while a file is open there ──────────────
+        let mut save_right = false;
+        let mut wrap_toggled = false;
+        let mut close_right = false;
+        let mut regenerate_context = false;
+        if self.right_file.is_some() {
+            let rect = egui::SidePanel::right("right_editor_pane")
+                .resizable(true)
+                .default_width(420.0)
+                .min_width(200.0)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        let Some(f) = &self.right_file else { return };
+                        if f.modified {
+                            ui.label(RichText::new("●").strong());
+                        }
+                        let name = f.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                        if f.scratch {
+                            ui.label(RichText::new(name).strong())
+                                .on_hover_text("临时文档：只读，不会保存");
+                            ui.label(RichText::new("只读").small().color(Color32::GRAY));
+                        } else {
+                            ui.label(RichText::new(name).strong()).on_hover_text(f.path.display().to_string());
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.add_enabled(!f.modified, egui::Button::new("✕").small())
+                                .on_hover_text("关闭右侧文件")
+                                .on_disabled_hover_text("有未保存的修改，请先保存")
+                                .clicked()
+                            {
+                                close_right = true;
+                            }
+                            if f.scratch {
+                                if ui.small_button("🔄").on_hover_text("按当前设定重新生成").clicked() {
+                                    regenerate_context = true;
+                                }
+                            } else if ui.small_button("💾").on_hover_text("保存 (Ctrl+Shift+S)").clicked() {
+                                save_right = true;
+                            }
+                            wrap_toggled |= wrap_toggle(ui, self.settings.markdown.wrap_for_mut(&f.path));
+                        });
+                    });
+                    if let Some(share) = self.design_share.as_ref().filter(|s| !s.left) {
+                        design_action = self.draw_design_banner(ui, share.file);
+                    }
+                    ui.separator();
+                    let Some(f) = &mut self.right_file else { return };
+                    let prev = f.content.clone();
+                    let te_id = egui::Id::new("right_editor_main");
+                    let mut area = egui::ScrollArea::both().id_salt("right_editor");
+                    if let Some(view) = self.right_view_restore.take() {
+                        seed_editor_caret(ctx, te_id, view.caret);
+                        area = area.vertical_scroll_offset(view.scroll);
+                    }
+                    let scrolled = area.show(ui, |ui| {
+                            let font_id = egui::FontId::monospace(self.settings.markdown.editor_font_size);
+                            let gutter = self.settings.markdown.show_line_numbers
+                                .then(|| gutter_width(ui, &f.content, &font_id));
+                            let output = ui.horizontal_top(|ui| {
+                                if let Some(width) = gutter {
+                                    ui.add_space(width);
+                                }
+                                // A scratch buffer can be selected and copied, not edited.
+                                let mut shown;
+                                let buffer: &mut dyn egui::TextBuffer = if f.scratch {
+                                    shown = f.content.as_str();
+                                    &mut shown
+                                } else {
+                                    &mut f.content
+                                };
+                                let wrap = self.settings.markdown.wrap_for(&f.path);
+                                let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+                                    let color = ui.visuals().override_text_color
+                                        .unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
+                                    let job = egui::text::LayoutJob::simple(
+                                        text.to_owned(), font_id.clone(), color, editor_wrap_width(wrap, wrap_width),
+                                    );
+                                    ui.fonts(|fonts| fonts.layout_job(job))
+                                };
+                                egui::TextEdit::multiline(buffer)
+                                    .id(te_id)
+                                    .desired_width(f32::INFINITY)
+                                    .desired_rows(30)
+                                    .font(font_id.clone())
+                                    .code_editor()
+                                    .layouter(&mut layouter)
+                                    .show(ui)
+                            }).inner;
+                            if gutter.is_some() {
+                                paint_line_gutter(ui, &output, &font_id);
+                            }
+                            let resp = output.response;
+                            if resp.has_focus() {
+                                self.last_focused_left = false;
+                            }
+                            if resp.changed() {
+                                if prev != f.content {
+                                    self.right_undo_stack.push(prev, self.settings.behavior.undo_limit);
+                                }
+                                f.modified = true;
+                            }
+                        });
+                    self.right_view = PaneView { caret: editor_caret(ctx, te_id), scroll: scrolled.state.offset.y };
+                }).response.rect;
+            self.note_focus_region(ctx, FocusRegion::RightEditor, rect);
+        }
 
         // ── Central panel: single full-width Markdown editor ──────────────────
-        egui::CentralPanel::default().show(ctx, |ui| {
+        let rect = egui::CentralPanel::default().show(ctx, |ui| {
             // Toolbar row above editor
             ui.horizontal(|ui| {
                 ui.label(RichText::new("编辑区").strong());
@@ -426,6 +938,14 @@ impl TextToolApp {
                 {
                     do_sync_folders = true;
                 }
+                let both_open = self.left_file.is_some() && self.right_file.is_some();
+                if ui.add_enabled(both_open, egui::Button::new("对比左右"))
+                    .on_hover_text("并排比较左右编辑区的文本")
+                    .on_disabled_hover_text("左右两侧都打开文件后可用")
+                    .clicked()
+                {
+                    open_diff = true;
+                }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     ui.label(
                         RichText::new("Ctrl+B 粗体  Ctrl+I 斜体  Ctrl+Z 撤销  Ctrl+S 保存  Ctrl+滚轮 缩放")
@@ -438,28 +958,90 @@ impl TextToolApp {
 
             let available = ui.available_size();
 
-            // File header bar
-            let file_title = self.left_file.as_ref()
-                .map(|f| f.title())
-                .unwrap_or_else(|| "文本编辑区".to_owned());
+            // File header bar: project-relative breadcrumb of the open file
+            let crumbs = self.left_file.as_ref()
+                .map(|f| breadcrumb(self.project_root.as_deref(), &f.path))
+                .unwrap_or_default();
+            let modified = self.left_file.as_ref().is_some_and(|f| f.modified);
 
             ui.horizontal(|ui| {
-                ui.label(RichText::new(&file_title).strong());
+                if crumbs.is_empty() {
+                    ui.label(RichText::new("文本编辑区").strong());
+                } else {
+                    if modified {
+                        ui.label(RichText::new("●").strong());
+                    }
+                    // Rough character budget: leave room for the buttons on the right.
+                    let max_chars = ((ui.available_width() - 200.0) / 9.0).max(12.0) as usize;
+                    let last = crumbs.len() - 1;
+                    ui.scope(|ui| {
+                        ui.spacing_mut().item_spacing.x = 2.0;
+                        for (n, slot) in elide_crumbs(&crumbs, max_chars).into_iter().enumerate() {
+                            if n > 0 {
+                                ui.label(RichText::new("▸").small().color(Color32::from_gray(120)));
+                            }
+                            match slot {
+                                None => { ui.label(RichText::new("…").color(Color32::from_gray(120))); }
+                                Some(i) if i == last => {
+                                    let name = RichText::new(middle_truncate(&crumbs[i].label, max_chars)).strong();
+                                    ui.add(egui::Label::new(name).sense(egui::Sense::click()))
+                                        .on_hover_text(crumbs[i].path.display().to_string())
+                                        .context_menu(|ui| {
+                                            if ui.button("重新载入当前文件").clicked() {
+                                                reload = true;
+                                                ui.close_menu();
+                                            }
+                                        });
+                                }
+                                Some(i) => {
+                                    if ui.link(middle_truncate(&crumbs[i].label, max_chars))
+                                        .on_hover_text("在文件树中定位此文件夹")
+                                        .clicked()
+                                    {
+                                        reveal = Some(crumbs[i].path.clone());
+                                    }
+                                }
+                            }
+                        }
+                    });
+                }
                 // Word count
                 if let Some(f) = &self.left_file {
                     if f.is_markdown() {
-                        let char_count: usize = f.content.chars()
-                            .filter(|c| !c.is_whitespace()).count();
+                        let char_count = count_words(&f.content);
                         ui.label(
                             RichText::new(format!("字数: {char_count}"))
                                 .small().color(Color32::from_gray(150)),
                         );
+                        let markers = marker_ranges(&f.content, &self.settings.behavior.stuck_markers).len();
+                        if markers > 0 {
+                            let count = RichText::new(format!("📌 {markers}")).small().color(MARKER_COLOR);
+                            if ui.add(egui::Label::new(count).sense(egui::Sense::click()))
+                                .on_hover_text("待处理标记（单击在导航中列出）")
+                                .clicked()
+                            {
+                                show_markers = true;
+                            }
+                        }
+                        let notes = annotation_ranges(&f.content).len();
+                        if notes > 0 {
+                            let count = RichText::new(format!("💬 {notes}")).small().color(ANNOTATION_COLOR);
+                            if ui.add(egui::Label::new(count).sense(egui::Sense::click()))
+                                .on_hover_text("注释（单击在导航中列出）")
+                                .clicked()
+                            {
+                                show_annotations = true;
+                            }
+                        }
                     }
                 }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.small_button("💾").on_hover_text("保存 (Ctrl+S)").clicked() {
                         self.save_left();
                     }
+                    if let Some(f) = &self.left_file {
+                        wrap_toggled |= wrap_toggle(ui, self.settings.markdown.wrap_for_mut(&f.path));
+                    }
                     let is_md = self.left_file.as_ref().map(|f| f.is_markdown()).unwrap_or(false);
                     if is_md {
                         let toggle_label = if self.left_preview_mode { "✏ 编辑" } else { "👁 预览" };
@@ -470,6 +1052,9 @@ impl TextToolApp {
                     }
                 });
             });
+            if let Some(share) = self.design_share.as_ref().filter(|s| s.left) {
+                design_action = self.draw_design_banner(ui, share.file);
+            }
             ui.separator();
 
             let height = available.y - 80.0;
@@ -479,7 +1064,7 @@ impl TextToolApp {
             if is_preview {
                 if let Some(f) = &self.left_file {
                     let content: &str = &f.content;
-                    let settings = &self.md_settings;
+                    let settings = &self.settings.markdown;
                     egui::ScrollArea::vertical()
                         .id_salt("left_preview")
                         .show(ui, |ui| {
@@ -489,52 +1074,437 @@ impl TextToolApp {
                 }
             } else if let Some(f) = &mut self.left_file {
                 let prev = f.content.clone();
-                egui::ScrollArea::both()
-                    .id_salt("left_editor")
-                    .show(ui, |ui| {
-                        let font_id = egui::FontId::monospace(self.md_settings.editor_font_size);
+                let scroll_req = self.pane_scroll_request.filter(|r| r.left);
+                if scroll_req.is_some() {
+                    self.pane_scroll_request = None;
+                }
+                let mut flash = self.left_flash_line;
+                let mut top_line = self.left_top_line;
+
+                // `@` completion: find the token before the caret and take the
+                // popup's navigation keys before the TextEdit sees them.
+                let te_id = egui::Id::new("left_editor_main");
+                let popup_id = egui::Id::new("mention_popup");
+                // Pressing on the popup steals focus from the editor; keep it
+                // open while the pointer is over it so the click can land.
+                let over_popup = self.mention_popup_open
+                    && ctx.memory(|m| m.area_rect(popup_id))
+                        .zip(ctx.pointer_hover_pos())
+                        .is_some_and(|(r, p)| r.contains(p));
+                let active = ctx.memory(|m| m.has_focus(te_id)) || over_popup;
+                let caret = egui::text_edit::TextEditState::load(ctx, te_id)
+                    .and_then(|s| s.cursor.char_range())
+                    .filter(|r| r.primary == r.secondary)
+                    .map(|r| r.primary.index);
+                let token = caret.filter(|_| active)
+                    .and_then(|c| mention_before_caret(&f.content, c).map(|t| (t, c)));
+                if token.is_none() {
+                    self.mention_dismissed_at = None;
+                }
+                let token = token.filter(|(t, _)| self.mention_dismissed_at != Some(t.start));
+                let names: Vec<String> = self.world_objects.iter()
+                    .filter(|o| !o.name.is_empty())
+                    .map(|o| o.name.clone())
+                    .collect();
+                let candidates: Vec<String> = token.as_ref()
+                    .map(|(t, _)| filter_mentions(&names, &t.query, MENTION_LIMIT))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(str::to_owned)
+                    .collect();
+                let mut selected = self.mention_selected.min(candidates.len().saturating_sub(1));
+                let mut accept: Option<String> = None;
+                let mut dismissed = false;
+                if !candidates.is_empty() {
+                    let (up, down, enter, esc) = ctx.input_mut(|i| (
+                        i.consume_key(egui::Modifiers::NONE, Key::ArrowUp),
+                        i.consume_key(egui::Modifiers::NONE, Key::ArrowDown),
+                        i.consume_key(egui::Modifiers::NONE, Key::Enter)
+                            | i.consume_key(egui::Modifiers::NONE, Key::Tab),
+                        i.consume_key(egui::Modifiers::NONE, Key::Escape),
+                    ));
+                    if up {
+                        selected = (selected + candidates.len() - 1) % candidates.len();
+                    }
+                    if down {
+                        selected = (selected + 1) % candidates.len();
+                    }
+                    if enter {
+                        accept = Some(candidates[selected].clone());
+                    }
+                    if esc {
+                        self.mention_dismissed_at = token.as_ref().map(|(t, _)| t.start);
+                        dismissed = true;
+                    }
+                }
+                let show_popup = !candidates.is_empty() && accept.is_none() && !dismissed;
+                let mut area = egui::ScrollArea::both().id_salt("left_editor");
+                if let Some(view) = self.left_view_restore.take() {
+                    seed_editor_caret(ctx, te_id, view.caret);
+                    area = area.vertical_scroll_offset(view.scroll);
+                }
+                let scrolled = area.show(ui, |ui| {
+                        let font_id = egui::FontId::monospace(self.settings.markdown.editor_font_size);
+                        let tokens = &self.settings.behavior.stuck_markers;
+                        let wrap = self.settings.markdown.wrap_for(&f.path);
+                        let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+                            let color = ui.visuals().override_text_color
+                                .unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
+                            let mut job = editor_layout_job(text, tokens, font_id.clone(), color);
+                            job.wrap.max_width = editor_wrap_width(wrap, wrap_width);
+                            ui.fonts(|fonts| fonts.layout_job(job))
+                        };
+                        let gutter = self.settings.markdown.show_line_numbers
+                            .then(|| gutter_width(ui, &f.content, &font_id));
                         let editor = egui::TextEdit::multiline(&mut f.content)
-                            .id(egui::Id::new("left_editor_main"))
+                            .id(te_id)
                             .desired_width(f32::INFINITY)
                             .desired_rows(30)
                             .min_size(egui::vec2(0.0, height))
-                            .font(font_id)
-                            .code_editor();
-                        let resp = ui.add(editor);
+                            .font(font_id.clone())
+                            .code_editor()
+                            .layouter(&mut layouter);
+                        let output = ui.horizontal_top(|ui| {
+                            if let Some(width) = gutter {
+                                ui.add_space(width);
+                            }
+                            editor.show(ui)
+                        }).inner;
+                        if gutter.is_some() {
+                            paint_line_gutter(ui, &output, &font_id);
+                        }
+                        let resp = &output.response;
                         if resp.has_focus() {
                             self.last_focused_left = true;
                         }
                         if resp.changed() {
                             if prev != f.content {
-                                self.left_undo_stack.push_back(prev);
-                                if self.left_undo_stack.len() > 200 {
-                                    self.left_undo_stack.pop_front();
-                                }
+                                self.left_undo_stack.push(prev, self.settings.behavior.undo_limit);
                             }
                             f.modified = true;
+                            if self.settings.behavior.fullwidth_punctuation {
+                                let caret = output.cursor_range.map(|r| r.primary.ccursor.index);
+                                if let Some(text) = caret.and_then(|c| convert_punct_before_caret(&f.content, c)) {
+                                    f.content = text;
+                                }
+                            }
+                        }
+
+                        // Scroll request: bring the target line to the top and flash it.
+                        if let Some(req) = scroll_req {
+                            let rect = editor_line_rect(&output, req.line_no);
+                            ui.scroll_to_rect(rect, Some(egui::Align::TOP));
+                            flash = Some((req.line_no, Instant::now()));
+                        }
+                        if let Some((line_no, since)) = flash {
+                            let t = since.elapsed().as_secs_f32() / FLASH_SECS;
+                            if t < 1.0 {
+                                let rect = editor_line_rect(&output, line_no);
+                                let alpha = ((1.0 - t) * 90.0) as u8;
+                                ui.painter().rect_filled(
+                                    rect, 2.0,
+                                    Color32::from_rgba_unmultiplied(230, 200, 60, alpha),
+                                );
+                                ui.ctx().request_repaint();
+                            } else {
+                                flash = None;
+                            }
+                        }
+
+                        // Track the line at the top of the viewport (for outline highlighting).
+                        let y = ui.clip_rect().top() - output.galley_pos.y;
+                        top_line = output.galley.cursor_from_pos(egui::vec2(0.0, y.max(0.0)))
+                            .pcursor.paragraph + 1;
+
+                        // Selection length and reading time, floating above the
+                        // selection end; non-interactive so typing is unaffected.
+                        if let Some(range) = output.cursor_range.filter(|r| resp.has_focus() && !r.is_empty()) {
+                            let [start, end] = range.sorted_cursors();
+                            let selected: String = f.content.chars()
+                                .skip(start.ccursor.index)
+                                .take(end.ccursor.index - start.ccursor.index)
+                                .collect();
+                            let stats = TextStats::of(&selected);
+                            let minutes = stats.reading_minutes(self.settings.markdown.reading_speed);
+                            let at = output.galley.pos_from_ccursor(range.primary.ccursor);
+                            egui::Area::new(egui::Id::new("selection_stats"))
+                                .order(egui::Order::Tooltip)
+                                .interactable(false)
+                                .pivot(egui::Align2::LEFT_BOTTOM)
+                                .fixed_pos(output.galley_pos + at.left_top().to_vec2() - egui::vec2(0.0, 2.0))
+                                .show(ui.ctx(), |ui| {
+                                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                                        ui.label(RichText::new(format!(
+                                            "{} 字符 · {} 词 · 阅读{}",
+                                            stats.chars, stats.words, format_reading_time(minutes),
+                                        )).small());
+                                    });
+                                });
+                        }
+
+                        // Completion popup, anchored just below the `@`.
+                        if let (true, Some((tok, _))) = (show_popup, &token) {
+                            let at = output.galley.pos_from_ccursor(egui::text::CCursor::new(tok.start));
+                            let pos = output.galley_pos + at.left_bottom().to_vec2() + egui::vec2(0.0, 2.0);
+                            egui::Area::new(popup_id)
+                                .order(egui::Order::Foreground)
+                                .fixed_pos(pos)
+                                .show(ui.ctx(), |ui| {
+                                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                                        for (i, name) in candidates.iter().enumerate() {
+                                            if ui.selectable_label(i == selected, name).clicked() {
+                                                accept = Some(name.clone());
+                                            }
+                                        }
+                                    });
+                                });
                         }
                     });
+                self.left_view = PaneView { caret: editor_caret(ctx, te_id), scroll: scrolled.state.offset.y };
+                self.left_flash_line = flash;
+                self.left_top_line = top_line;
+                self.mention_selected = selected;
+                self.mention_popup_open = show_popup && accept.is_none();
+
+                if let (Some(name), Some((tok, caret))) = (accept, token) {
+                    let (content, new_caret) = apply_mention(&f.content, &tok, caret, &name);
+                    self.left_undo_stack.push(std::mem::replace(&mut f.content, content), self.settings.behavior.undo_limit);
+                    f.modified = true;
+                    if let Some(mut state) = egui::text_edit::TextEditState::load(ctx, te_id) {
+                        state.cursor.set_char_range(Some(egui::text::CCursorRange::one(
+                            egui::text::CCursor::new(new_caret))));
+                        egui::text_edit::TextEditState::store(state, ctx, te_id);
+                    }
+                    ctx.memory_mut(|m| m.request_focus(te_id));
+                    self.mention_selected = 0;
+                }
             } else {
-                ui.centered_and_justified(|ui| {
-                    ui.label(
-                        RichText::new("从左侧文件树双击打开文件，\n或通过菜单「文件 → 打开项目文件夹」")
-                            .color(Color32::GRAY),
-                    );
-                });
+                let hint = empty_pane_hint(self.settings.behavior.left_pane);
+                if self.project_root.is_some() {
+                    ui.centered_and_justified(|ui| {
+                        ui.label(RichText::new(hint).color(Color32::GRAY));
+                    });
+                } else {
+                    ui.vertical_centered(|ui| {
+                        ui.add_space(ui.available_height() / 3.0);
+                        ui.label(RichText::new(hint).color(Color32::GRAY));
+                        ui.add_space(8.0);
+                        if ui.button("✨ 第一次使用？新建示例项目…").clicked() {
+                            self.create_sample_project();
+                        }
+                    });
+                }
             }
-        });
+        }).response.rect;
+        self.note_focus_region(ctx, FocusRegion::LeftEditor, rect);
 
         // Apply deferred actions
         if let Some(idx) = switch_to_obj_idx {
-            self.selected_obj_idx = Some(idx);
+            self.select_obj(idx);
             self.active_panel = Panel::Objects;
         }
-        if do_sync_folders   { self.sync_struct_from_folders(); }
+        if do_sync_folders {
+            let r = self.sync_struct_from_folders();
+            self.notify(r);
+        }
+        if open_diff {
+            self.open_pane_diff();
+        }
+        if wrap_toggled {
+            self.save_config();
+        }
+        if save_right {
+            self.save_right();
+        }
+        if close_right {
+            self.close_right_file();
+        }
+        if regenerate_context {
+            self.regenerate_writing_context();
+        }
+        if let Some(action) = design_action {
+            self.apply_design_sync(action);
+        }
+        if reload {
+            self.request_reload(true);
+        }
+        if show_markers {
+            self.file_tree_mode = FileTreeMode::Markers;
+        }
+        if show_annotations {
+            self.file_tree_mode = FileTreeMode::Annotations;
+        }
+        if let Some(dir) = reveal {
+            self.reveal_in_tree(&dir);
+        }
+    }
+}
+
+/// Placeholder of an empty editor pane that accepts `accept` files.
+fn empty_pane_hint(accept: PaneAccept) -> String {
+    let kind = match accept {
+        PaneAccept::Any => String::new(),
+        other => format!(" {} ", other.label()),
+    };
+    format!("从左侧文件树双击打开{kind}文件，\n或通过菜单「文件 → 打开项目文件夹」")
+}
+
+// ── Editor geometry helpers ───────────────────────────────────────────────────
+
+/// How long a jumped-to line stays highlighted.
+const FLASH_SECS: f32 = 1.2;
+
+/// Most names shown in the `@` completion popup.
+const MENTION_LIMIT: usize = 8;
+
+/// Char index of the caret of editor `id`, `0` before it was first shown.
+fn editor_caret(ctx: &Context, id: egui::Id) -> usize {
+    egui::text_edit::TextEditState::load(ctx, id)
+        .and_then(|s| s.cursor.char_range())
+        .map_or(0, |r| r.primary.index)
+}
+
+/// Put the caret of editor `id` at char index `caret` ahead of its next frame.
+fn seed_editor_caret(ctx: &Context, id: egui::Id, caret: usize) {
+    let mut state = egui::text_edit::TextEditState::load(ctx, id).unwrap_or_default();
+    state.cursor.set_char_range(Some(egui::text::CCursorRange::one(egui::text::CCursor::new(caret))));
+    state.store(ctx, id);
+}
+
+/// Screen rect of the 1-based `line_no` in a laid-out editor, spanning the
+/// full editor width.
+fn editor_line_rect(output: &egui::text_edit::TextEditOutput, line_no: usize) -> egui::Rect {
+    let pcursor = egui::epaint::text::cursor::PCursor {
+        paragraph: line_no.saturating_sub(1),
+        offset: 0,
+        prefer_next_row: false,
+    };
+    let r = output.galley.pos_from_pcursor(pcursor).translate(output.galley_pos.to_vec2());
+    egui::Rect::from_x_y_ranges(output.response.rect.x_range(), r.y_range())
+}
+
+/// Width of the line-number gutter left of an editor showing `text`.
+fn gutter_width(ui: &egui::Ui, text: &str, font: &egui::FontId) -> f32 {
+    let digits = gutter_digits(text.matches('\n').count() + 1);
+    let digit = ui.fonts(|f| f.glyph_width(font, '0'));
+    digits as f32 * digit + 8.0
+}
+
+/// Paint line numbers into the gutter left of `output`'s editor, one per
+/// hard line on the row it starts at, for the rows in view.  The caret's
+/// line is drawn brighter.  Lives inside the editor's scroll area, so it
+/// scrolls with the text.
+fn paint_line_gutter(ui: &egui::Ui, output: &egui::text_edit::TextEditOutput, font: &egui::FontId) {
+    let numbers = row_line_numbers(output.galley.rows.iter().map(|r| r.ends_with_newline));
+    let caret_line = output.cursor_range.map(|r| r.primary.pcursor.paragraph + 1);
+    let clip = ui.clip_rect();
+    let right = output.response.rect.left() - ui.spacing().item_spacing.x - 4.0;
+    let painter = ui.painter();
+    for (row, number) in output.galley.rows.iter().zip(numbers) {
+        let y = output.galley_pos.y + row.rect.center().y;
+        if y - row.rect.height() > clip.bottom() {
+            break;
+        }
+        let Some(n) = number.filter(|_| y + row.rect.height() >= clip.top()) else { continue };
+        let color = if caret_line == Some(n) {
+            ui.visuals().strong_text_color()
+        } else {
+            Color32::from_gray(110)
+        };
+        painter.text(egui::pos2(right, y), egui::Align2::RIGHT_CENTER, n.to_string(), font.clone(), color);
+    }
+}
+
+// ── 待处理标记 helpers ─────────────────────────────────────────────────────────
+
+/// Colour of the 待处理标记 count in the file header.
+const MARKER_COLOR: Color32 = Color32::from_rgb(230, 150, 60);
+/// Background behind marker tokens in the editor.
+const MARKER_BG: Color32 = Color32::from_rgba_premultiplied(90, 58, 22, 90);
+/// Characters of the marker's line shown in the 待处理标记 list.
+const MARKER_PREVIEW_CHARS: usize = 30;
+
+/// Width an editor pane lays its text out to: the pane's `available` width
+/// when wrapping, unbounded otherwise so long lines run on and the pane's
+/// horizontal scroll bar engages.  A wide `desired_width` alone would not do
+/// it: `TextEdit` clamps the wrap width to the space available.
+fn editor_wrap_width(wrap: bool, available: f32) -> f32 {
+    if wrap { available } else { f32::INFINITY }
+}
+
+/// 自动换行 toggle in an editor pane's header; `true` when clicked.
+fn wrap_toggle(ui: &mut egui::Ui, wrap: &mut bool) -> bool {
+    let hover = if *wrap { "自动换行：开（单击关闭，长行可横向滚动）" } else { "自动换行：关（单击开启）" };
+    let clicked = ui.selectable_label(*wrap, "↩").on_hover_text(hover).clicked();
+    if clicked {
+        *wrap = !*wrap;
+    }
+    clicked
+}
+
+/// Editor layout of `text` with the marker `tokens` and the 注释
+/// highlighted.  A marker inside a comment takes the comment's style.
+fn editor_layout_job(text: &str, tokens: &[String], font_id: egui::FontId, color: Color32) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    let plain = egui::TextFormat::simple(font_id.clone(), color);
+    let marked = egui::TextFormat { background: MARKER_BG, ..plain.clone() };
+    let comment = egui::TextFormat { color: ANNOTATION_COLOR, background: ANNOTATION_BG, italics: true, ..plain.clone() };
+    let notes = annotation_ranges(text);
+    let mut spans: Vec<(std::ops::Range<usize>, &egui::TextFormat)> = marker_ranges(text, tokens).into_iter()
+        .filter(|m| !notes.iter().any(|n| n.start < m.end && m.start < n.end))
+        .map(|m| (m, &marked))
+        .chain(notes.iter().map(|n| (n.clone(), &comment)))
+        .collect();
+    spans.sort_by_key(|(range, _)| range.start);
+    let mut at = 0;
+    for (range, format) in spans {
+        job.append(&text[at..range.start], 0.0, plain.clone());
+        job.append(&text[range.clone()], 0.0, format.clone());
+        at = range.end;
     }
+    job.append(&text[at..], 0.0, plain);
+    job
+}
+
+/// One row of the 待处理标记 list; `true` when clicked.
+fn marker_row(ui: &mut egui::Ui, m: &Marker) -> bool {
+    let mut chars = m.line.chars();
+    let preview: String = (&mut chars).take(MARKER_PREVIEW_CHARS).collect();
+    let suffix = if chars.next().is_some() { "…" } else { "" };
+    ui.selectable_label(false, RichText::new(format!("{}:{}  {preview}{suffix}", m.line_no, m.column)).small())
+        .on_hover_text(format!("第 {} 行第 {} 列 · {}", m.line_no, m.column, m.token))
+        .clicked()
+}
+
+// ── 注释 helpers ──────────────────────────────────────────────────────────────
+
+/// Text colour of 注释 in the editor and of their count in the file header.
+const ANNOTATION_COLOR: Color32 = Color32::from_rgb(120, 170, 130);
+/// Background behind 注释 in the editor.
+const ANNOTATION_BG: Color32 = Color32::from_rgba_premultiplied(30, 60, 40, 70);
+
+/// One row of the 注释 list; `true` when clicked.
+fn annotation_row(ui: &mut egui::Ui, a: &Annotation) -> bool {
+    let flat = a.text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut chars = flat.chars();
+    let preview: String = (&mut chars).take(MARKER_PREVIEW_CHARS).collect();
+    let suffix = if chars.next().is_some() { "…" } else { "" };
+    ui.selectable_label(false, RichText::new(format!("{}:{}  {preview}{suffix}", a.line_no, a.column)).small())
+        .on_hover_text(format!("第 {} 行第 {} 列\n{}", a.line_no, a.column, a.text))
+        .clicked()
 }
 
 // ── Chapter tree file-finding helper ─────────────────────────────────────────
 
+/// Markdown file of `node` under `content`: its linked [`StructNode::file`]
+/// if that exists, else the first file whose stem matches the title.
+pub(in crate::app) fn node_md_file(content: &Path, node: &StructNode) -> Option<PathBuf> {
+    node.file.as_ref()
+        .map(|f| content.join(f))
+        .filter(|p| p.is_file())
+        .or_else(|| find_md_for_title(content, &node.file_key()))
+}
+
 /// Recursively search `dir` for a `.md` file whose stem (lowercased) matches `needle`.
 fn find_md_for_title(dir: &std::path::Path, needle: &str) -> Option<PathBuf> {
     let entries = std::fs::read_dir(dir).ok()?;
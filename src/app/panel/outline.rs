@@ -1,13 +1,96 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
 use egui::{Context, RichText, Color32};
+use text_tool_core::changelog::Change;
+use text_tool_core::foreshadow_check::check_foreshadows;
+use text_tool_core::stats::{burndown, projected_finish, BurndownPoint, StructStats};
 use super::super::{
-    TextToolApp, StructNode, StructKind, ChapterTag, NodeLink, RelationKind,
-    Foreshadow, Milestone, StructViewMode, node_at_mut,
+    TextToolApp, FocusRegion, StructHoverCard, StructNode, StructKind, ChapterTag, NodeLink,
+    Foreshadow, ForeshadowIndex, ForeshadowStage, Milestone, StructViewMode, DesignFile, NodeStage, node_at, node_position,
+    node_at_mut, hierarchy_issues, duplicate_titles, unique_title_for, kanban_columns, format_relative_time, unix_now, find_duplicate_name,
+    unique_name, move_item, foreshadow_columns, ObjectKind, WorldObject, count_words, normalize_title, format_day, today_days,
 };
+use super::super::object_picker::{draw_object_chips, ChipAction};
+use super::super::session_summary::note_session;
+use super::link_form::{relation_combo, target_menu};
+use super::node_split::{merge_next_menu, NodeOp};
+use super::chapter_preview::chapter_title_label;
+use super::date_picker::date_picker;
+use super::markdown::markdown_field;
+
+/// Drag payload of a 看板 card: the node's index path.
+struct KanbanCard(Vec<usize>);
+
+/// Drag payload of a 伏笔看板 card: the foreshadow's index.
+struct ForeshadowCard(usize);
+
+/// Characters of the description a 伏笔看板 card shows.
+const CARD_EXCERPT_CHARS: usize = 40;
+
+/// Hover time on a struct tree row before its card opens.
+const HOVER_CARD_DELAY: Duration = Duration::from_millis(500);
+/// 目标字数 a node gets when its target is switched on.
+const DEFAULT_TARGET_WORDS: usize = 3000;
+/// Days of writing the 计划 vs 实际 projection takes the pace from.
+const PACE_WINDOW_DAYS: i64 = 14;
+const PLAN_COLOR: Color32 = Color32::from_rgb(120, 160, 220);
+const WRITTEN_COLOR: Color32 = Color32::from_rgb(120, 200, 120);
+
+/// Words written for `node`: its own file's count for a leaf, the sum over
+/// its leaves otherwise.
+fn leaf_word_count(node: &StructNode, counts: &BTreeMap<String, usize>) -> usize {
+    if node.children.is_empty() {
+        counts.get(&node.file_key()).copied().unwrap_or(0)
+    } else {
+        node.children.iter().map(|c| leaf_word_count(c, counts)).sum()
+    }
+}
+
+/// Chapter titles typed as a list separated by `，`, `、` or `,`.
+fn split_chapter_list(text: &str) -> Vec<String> {
+    text.split(['，', '、', ','])
+        .map(normalize_title)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// One 伏笔看板 card: name, the start of the description and the related
+/// chapters.  Returns whether the name was clicked.
+fn foreshadow_card(ui: &mut egui::Ui, fs: &Foreshadow, selected: bool) -> bool {
+    let fill = if selected { Color32::from_rgb(0, 100, 170) } else { Color32::from_gray(38) };
+    egui::Frame::none()
+        .fill(fill)
+        .rounding(4.0)
+        .inner_margin(egui::Margin::symmetric(6.0, 3.0))
+        .show(ui, |ui| {
+            ui.set_min_width(ui.available_width());
+            let clicked = ui.selectable_label(selected, RichText::new(&fs.name).size(13.0)).clicked();
+            let first_line = fs.description.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("");
+            if !first_line.is_empty() {
+                let mut excerpt: String = first_line.chars().take(CARD_EXCERPT_CHARS).collect();
+                if excerpt.len() < first_line.len() {
+                    excerpt.push('…');
+                }
+                ui.label(RichText::new(excerpt).small().color(Color32::from_gray(170)));
+            }
+            if !fs.related_chapters.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 4.0;
+                    for chapter in &fs.related_chapters {
+                        ui.label(RichText::new(chapter).small().background_color(Color32::from_gray(60)));
+                    }
+                });
+            }
+            clicked
+        })
+        .inner
+}
 
 impl TextToolApp {
     // ── Panel: Chapter Structure ──────────────────────────────────────────────
     //
-    // Left: hierarchical struct tree (总纲/卷/章/节) with add/remove/reorder
+    // Left: hierarchical struct tree (总纲/卷/章/节/场) with add/remove/reorder
     // Central: selected node editor + linked objects + node cross-links
     // Bottom strip: progress tracking + foreshadow management
 
@@ -17,10 +100,20 @@ impl TextToolApp {
         let mut add_child: Option<(Vec<usize>, String, StructKind)> = None;
         let mut remove_node: Option<Vec<usize>> = None;
         let mut move_up: Option<Vec<usize>> = None;
+        let mut synopsis_for: Option<Vec<usize>> = None;
+        let mut node_op: Option<NodeOp> = None;
         let mut root_dnd_move: Option<(usize, usize)> = None;
+        // Badge click: select the node and filter the foreshadow list by its title.
+        let mut fs_badge_click: Option<(Vec<usize>, String)> = None;
+        let mut design_action = None;
+        let mut hovered_row: Option<(Vec<usize>, egui::Rect)> = None;
+        let mut hovered_chapter: Option<(String, egui::Rect)> = None;
+        // 看板 drop: move the node at the path to the stage.
+        let mut stage_drop: Option<(Vec<usize>, NodeStage)> = None;
+        self.fs_index.refresh(&self.struct_roots, &self.foreshadows);
 
         // ── Left: struct tree ──────────────────────────────────────────────────
-        egui::SidePanel::left("struct_tree")
+        let rect = egui::SidePanel::left("struct_tree")
             .resizable(true)
             .default_width(240.0)
             .min_width(160.0)
@@ -29,14 +122,24 @@ impl TextToolApp {
                 ui.horizontal(|ui| {
                     ui.heading("章节结构");
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("📊 统计").on_hover_text("查看整个章节结构的统计").clicked() {
+                            self.show_struct_stats = true;
+                        }
                         // View mode toggle
+                        let is_kanban = self.struct_view_mode == StructViewMode::Kanban;
+                        if ui.selectable_label(is_kanban, "📋 看板")
+                            .on_hover_text("按写作进度分栏显示章节，拖动卡片可改变进度").clicked()
+                        {
+                            self.struct_view_mode = StructViewMode::Kanban;
+                        }
                         let is_timeline = self.struct_view_mode == StructViewMode::Timeline;
                         if ui.selectable_label(is_timeline, "🕐 时间轴")
                             .on_hover_text("切换到时间轴视图").clicked()
                         {
                             self.struct_view_mode = StructViewMode::Timeline;
                         }
-                        if ui.selectable_label(!is_timeline, "🌲 树形")
+                        let is_tree = self.struct_view_mode == StructViewMode::Tree;
+                        if ui.selectable_label(is_tree, "🌲 树形")
                             .on_hover_text("切换到树形视图").clicked()
                         {
                             self.struct_view_mode = StructViewMode::Tree;
@@ -47,9 +150,15 @@ impl TextToolApp {
                     RichText::new("💾 修改自动保存  切换面板时自动加载")
                         .small().color(Color32::from_gray(110)),
                 );
+                design_action = self.draw_design_banner(ui, DesignFile::Structure);
                 ui.separator();
 
                 // Add root node controls
+                let duplicate = find_duplicate_name(
+                    &self.new_node_title,
+                    self.struct_roots.iter().map(|n| n.title.as_str()),
+                ).map(str::to_owned);
+                let mut do_add = false;
                 ui.horizontal(|ui| {
                     ui.add(egui::TextEdit::singleline(&mut self.new_node_title)
                         .hint_text("标题")
@@ -63,39 +172,148 @@ impl TextToolApp {
                                 ui.selectable_value(&mut self.new_node_kind, k.clone(), label);
                             }
                         });
-                    if ui.button("➕").on_hover_text("添加根节点").clicked() {
-                        let title = self.new_node_title.trim().to_owned();
-                        if !title.is_empty() {
-                            add_root = Some((title, self.new_node_kind.clone()));
-                            self.new_node_title.clear();
-                        }
+                    if ui.add_enabled(duplicate.is_none(), egui::Button::new("➕"))
+                        .on_hover_text("添加根节点")
+                        .clicked()
+                    {
+                        do_add = true;
                     }
                 });
+                if let Some(existing) = &duplicate {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(format!("已存在同名节点「{existing}」"))
+                            .small().color(Color32::from_rgb(220, 80, 80)));
+                        if ui.small_button("仍然添加").clicked() {
+                            do_add = true;
+                        }
+                    });
+                }
+                if do_add {
+                    let title = normalize_title(&self.new_node_title);
+                    if !title.is_empty() {
+                        add_root = Some((title, self.new_node_kind.clone()));
+                        self.new_node_title.clear();
+                    }
+                }
                 ui.separator();
 
+                // Hierarchy lint: out-of-order kinds are allowed but listed here.
+                let issues = hierarchy_issues(&self.struct_roots);
+                if !issues.is_empty() {
+                    egui::CollapsingHeader::new(
+                        RichText::new(format!("⚠ 层级检查 ({})", issues.len()))
+                            .color(Color32::from_rgb(220, 180, 60)),
+                    )
+                    .id_salt("struct_hierarchy_lint")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        for issue in &issues {
+                            if ui.selectable_label(false, RichText::new(issue.describe()).small())
+                                .on_hover_text("点击选中该节点")
+                                .clicked()
+                            {
+                                self.selected_node_path = issue.path.clone();
+                            }
+                        }
+                    });
+                    ui.separator();
+                }
+
+                // Duplicate titles: links by title cannot tell these nodes apart.
+                let duplicates = duplicate_titles(&self.struct_roots);
+                if !duplicates.is_empty() {
+                    let mut make_unique: Option<Vec<usize>> = None;
+                    egui::CollapsingHeader::new(
+                        RichText::new(format!("⚠ {} 个重复标题", duplicates.len()))
+                            .color(Color32::from_rgb(220, 180, 60)),
+                    )
+                    .id_salt("struct_duplicate_titles")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(RichText::new("跨节点关联、章节文件和伏笔按标题找节点，重名时可能指向别处")
+                            .small().color(Color32::from_gray(140)));
+                        for dup in &duplicates {
+                            ui.label(RichText::new(format!("「{}」", dup.title)).small().strong());
+                            for path in &dup.paths {
+                                ui.horizontal(|ui| {
+                                    let selected = self.selected_node_path == *path;
+                                    if ui.selectable_label(selected, RichText::new(title_breadcrumb(&self.struct_roots, path)).small())
+                                        .on_hover_text("点击选中该节点")
+                                        .clicked()
+                                    {
+                                        self.selected_node_path = path.clone();
+                                    }
+                                    if let Some(title) = unique_title_for(&self.struct_roots, path) {
+                                        if ui.small_button("重命名为唯一标题")
+                                            .on_hover_text(format!("改为「{title}」"))
+                                            .clicked()
+                                        {
+                                            make_unique = Some(path.clone());
+                                        }
+                                    }
+                                });
+                            }
+                        }
+                    });
+                    if let Some(path) = make_unique {
+                        self.make_node_title_unique(&path);
+                    }
+                    ui.separator();
+                }
+
+                let deepest = self.settings.markdown.deepest_struct_kind.clone();
                 egui::ScrollArea::vertical().id_salt("struct_tree_scroll").show(ui, |ui| {
-                    if self.struct_view_mode == StructViewMode::Tree {
-                        let roots_snapshot = self.struct_roots.clone();
-                        let selected = self.selected_node_path.clone();
-                        Self::draw_struct_tree(
-                            ui, &roots_snapshot, &selected, &[],
-                            &mut add_child, &mut remove_node, &mut move_up,
-                            &mut root_dnd_move,
-                            &mut self.selected_node_path,
-                        );
-                    } else {
-                        let roots_snapshot = self.struct_roots.clone();
-                        let selected = self.selected_node_path.clone();
-                        Self::draw_struct_timeline(
-                            ui, &roots_snapshot, &selected, &[],
-                            &mut self.selected_node_path,
-                        );
+                    match self.struct_view_mode {
+                        StructViewMode::Tree => {
+                            let roots_snapshot = self.struct_roots.clone();
+                            let selected = self.selected_node_path.clone();
+                            Self::draw_struct_tree(
+                                ui, &roots_snapshot, &selected, &[], &deepest,
+                                &mut add_child, &mut remove_node, &mut move_up,
+                                &mut synopsis_for, &mut node_op, &mut root_dnd_move,
+                                &mut self.selected_node_path,
+                                &self.foreshadows, &self.fs_index, &mut fs_badge_click,
+                                &mut hovered_row, self.focus_scroll,
+                            );
+                        }
+                        StructViewMode::Timeline => {
+                            let roots_snapshot = self.struct_roots.clone();
+                            let selected = self.selected_node_path.clone();
+                            Self::draw_struct_timeline(
+                                ui, &roots_snapshot, &selected, &[],
+                                &mut self.selected_node_path,
+                            );
+                        }
+                        StructViewMode::Kanban => {
+                            Self::draw_struct_kanban(
+                                ui, &self.struct_roots,
+                                &mut self.selected_node_path, &mut stage_drop,
+                            );
+                        }
                     }
                 });
 
-            });
+            }).response.rect;
+        self.note_focus_region(ctx, FocusRegion::StructTree, rect);
+
+        self.draw_struct_hover_card(ctx, hovered_row);
+        self.draw_struct_stats_window(ctx);
 
         // ── Apply deferred tree mutations ──────────────────────────────────────
+        if let Some(action) = design_action {
+            self.apply_design_sync(action);
+        }
+        if let Some((path, stage)) = stage_drop {
+            if let Some(node) = node_at_mut(&mut self.struct_roots, &path) {
+                node.set_stage(stage);
+                self.auto_done_manual.insert(node.file_key());
+                note_session(&mut self.session_done, &node.title, node.done);
+            }
+        }
+        if let Some((path, title)) = fs_badge_click {
+            self.selected_node_path = path;
+            self.fs_filter = Some(title);
+        }
         if let Some((title, kind)) = add_root {
             let idx = self.struct_roots.len();
             self.struct_roots.push(StructNode::new(&title, kind));
@@ -104,6 +322,8 @@ impl TextToolApp {
         if let Some((parent_path, title, kind)) = add_child {
             if let Some(parent) = node_at_mut(&mut self.struct_roots, &parent_path) {
                 let child_idx = parent.children.len();
+                // Default titles get a number rather than repeating a sibling.
+                let title = unique_name(&title, parent.children.iter().map(|c| c.title.as_str()));
                 parent.children.push(StructNode::new(&title, kind));
                 let mut new_path = parent_path.clone();
                 new_path.push(child_idx);
@@ -116,6 +336,14 @@ impl TextToolApp {
                 self.selected_node_path.clear();
             }
         }
+        if let Some(path) = synopsis_for {
+            self.open_synopsis(&path);
+        }
+        match node_op {
+            Some(NodeOp::Split(path)) => self.open_node_split(&path),
+            Some(NodeOp::MergeNext(path, keep_first)) => self.merge_node_with_next(&path, keep_first),
+            None => {}
+        }
         if let Some(path) = move_up {
             Self::move_node_up(&mut self.struct_roots, &path);
             // Adjust selection if it was pointing at the moved node
@@ -123,6 +351,7 @@ impl TextToolApp {
                 if *last > 0 {
                     let mut new_path = path.clone();
                     *new_path.last_mut().unwrap() -= 1;
+                    self.record_node_move(&new_path);
                     if self.selected_node_path == path {
                         self.selected_node_path = new_path;
                     }
@@ -133,6 +362,7 @@ impl TextToolApp {
             if from < self.struct_roots.len() && to < self.struct_roots.len() {
                 let node = self.struct_roots.remove(from);
                 self.struct_roots.insert(to, node);
+                self.record_node_move(&[to]);
                 // Update selection path if it was pointing at a moved root
                 if let Some(first) = self.selected_node_path.first_mut() {
                     if *first == from {
@@ -147,15 +377,16 @@ impl TextToolApp {
         }
 
         // ── Central: node editor ───────────────────────────────────────────────
-        egui::CentralPanel::default().show(ctx, |ui| {
+        let rect = egui::CentralPanel::default().show(ctx, |ui| {
             // Top strip: progress overview derived from all struct nodes
             let (total, done) = Self::count_progress(&self.struct_roots);
             ui.group(|ui| {
                 ui.horizontal(|ui| {
                     ui.heading("进度追踪");
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        if ui.button("💾 同步伏笔到 MD").clicked() {
-                            self.sync_foreshadows_to_md();
+                        if ui.button("💾 保存伏笔").clicked() {
+                            let r = self.sync_foreshadows();
+                            self.notify(r);
                         }
                     });
                 });
@@ -183,34 +414,86 @@ impl TextToolApp {
             }
 
             // Collect data before mutable borrow
-            let obj_names   = self.all_object_names();
+            let kind_choices = self.kind_choices();
             let node_titles = self.all_struct_node_titles();
             let path = self.selected_node_path.clone();
+            let deepest = self.settings.markdown.deepest_struct_kind.clone();
+            // Breadcrumb of the ancestors, root first, and the computed position.
+            let ancestors: Vec<(Vec<usize>, String)> = (1..path.len())
+                .filter_map(|len| node_at(&self.struct_roots, &path[..len])
+                    .map(|n| (path[..len].to_vec(), format!("{} {}", n.kind.icon(), n.title))))
+                .collect();
+            let position = node_position(&self.struct_roots, &path);
+            let mut select_ancestor: Option<Vec<usize>> = None;
 
-            let mut do_add_obj_link  = false;
+            let mut open_obj_picker  = false;
+            let mut open_reading     = false;
+            let mut start_writing    = false;
+            let mut create_obj: Option<(String, ObjectKind)> = None;
             let mut do_add_node_link = false;
             // Set to Some(child_idx) when the inline "add child" button is clicked.
             let mut add_inline_child: Option<usize> = None;
+            // Old title once the title field loses focus with a new one.
+            let mut retitled: Option<String> = None;
 
             if let Some(node) = node_at_mut(&mut self.struct_roots, &path) {
+                let mut edited = false;
+                let now = unix_now();
                 egui::ScrollArea::vertical().id_salt("node_editor_scroll").show(ui, |ui| {
+                    if !ancestors.is_empty() {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.spacing_mut().item_spacing.x = 2.0;
+                            for (ancestor, label) in &ancestors {
+                                if ui.add(egui::Label::new(RichText::new(label).small().color(Color32::from_gray(170)))
+                                    .sense(egui::Sense::click()))
+                                    .on_hover_text("选中此上级节点")
+                                    .clicked()
+                                {
+                                    select_ancestor = Some(ancestor.clone());
+                                }
+                                ui.label(RichText::new("›").small().color(Color32::GRAY));
+                            }
+                            ui.label(RichText::new(&node.title).small());
+                        });
+                    }
                     ui.horizontal(|ui| {
                         ui.heading(format!("{} {}",
                             node.kind.icon(), node.title.clone()));
                         // Add child button
-                        let child_kind = node.kind.default_child_kind();
+                        let child_kind = node.kind.child_kind_within(&deepest);
                         if ui.button(format!("➕ 添加子{}", child_kind.label()))
                             .on_hover_text("添加子节点到此项").clicked()
                         {
                             let child_idx = node.children.len();
-                            node.children.push(StructNode::new(
+                            let title = unique_name(
                                 &format!("新{}", child_kind.label()),
-                                child_kind.clone(),
-                            ));
+                                node.children.iter().map(|c| c.title.as_str()),
+                            );
+                            node.children.push(StructNode::new(&title, child_kind.clone()));
                             // Signal to update selection after this borrow ends.
                             add_inline_child = Some(child_idx);
                         }
+                        if node.kind == StructKind::Volume
+                            && ui.button("📖 阅读").on_hover_text("按章节顺序阅读本卷").clicked()
+                        {
+                            open_reading = true;
+                        }
+                        if ui.button("✍ 开始写作")
+                            .on_hover_text("左侧打开章节文件，右侧显示摘要、笔记、关联对象与伏笔")
+                            .clicked()
+                        {
+                            start_writing = true;
+                        }
                     });
+                    if let Some(position) = &position {
+                        ui.label(RichText::new(format!("位置: {position}")).small().color(Color32::from_gray(170)))
+                            .on_hover_text("按同级同层级节点的顺序计算");
+                    }
+                    ui.label(RichText::new(format!(
+                        "更新于 {} · 创建于 {}",
+                        format_relative_time(node.updated_at, now),
+                        format_relative_time(node.created_at, now),
+                    )).small().color(Color32::GRAY));
                     ui.separator();
 
                     // Kind selector
@@ -222,12 +505,26 @@ impl TextToolApp {
                                 format!("{} {}", k.icon(), k.label())).clicked()
                             {
                                 node.kind = k.clone();
+                                edited = true;
                             }
                         }
                     });
                     ui.horizontal(|ui| {
                         ui.label("标题:");
-                        ui.text_edit_singleline(&mut node.title);
+                        let resp = ui.text_edit_singleline(&mut node.title);
+                        edited |= resp.changed();
+                        if resp.gained_focus() {
+                            self.node_title_before = Some((path.clone(), node.title.clone()));
+                        }
+                        if resp.lost_focus() {
+                            if normalize_title(&node.title) != node.title {
+                                node.title = normalize_title(&node.title);
+                                edited = true;
+                            }
+                            retitled = self.node_title_before.take()
+                                .filter(|(p, old)| *p == path && *old != node.title && !node.title.is_empty())
+                                .map(|(_, old)| old);
+                        }
                     });
                     ui.horizontal(|ui| {
                         ui.label("标签:");
@@ -237,50 +534,91 @@ impl TextToolApp {
                                 RichText::new(tag.label()).color(tag.color())).clicked()
                             {
                                 node.tag = tag.clone();
+                                edited = true;
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("进度:");
+                        let current = node.stage();
+                        for stage in NodeStage::all() {
+                            if ui.selectable_label(current == stage, stage.label()).clicked()
+                                && current != stage
+                            {
+                                node.set_stage(stage);
+                                self.auto_done_manual.insert(node.file_key());
+                                note_session(&mut self.session_done, &node.title, node.done);
+                                edited = true;
                             }
                         }
-                        ui.checkbox(&mut node.done, "已完成");
                     });
+                    ui.horizontal(|ui| {
+                        ui.label("目标字数:");
+                        let mut has_target = node.target_words.is_some();
+                        if ui.checkbox(&mut has_target, "").changed() {
+                            node.target_words = has_target.then_some(DEFAULT_TARGET_WORDS);
+                            edited = true;
+                        }
+                        if let Some(target) = &mut node.target_words {
+                            edited |= ui.add(egui::DragValue::new(target)
+                                .range(0..=10_000_000)
+                                .speed(100)
+                                .suffix(" 字")).changed();
+                        }
+                        ui.label("截止日期:");
+                        edited |= date_picker(ui, "struct_node_due", &mut node.due_date);
+                    }).response.on_hover_text("有目标字数和截止日期的节点计入统计窗口的「计划 vs 实际」");
                     ui.label("摘要:");
-                    ui.add(egui::TextEdit::multiline(&mut node.summary)
-                        .desired_rows(3)
-                        .desired_width(f32::INFINITY));
+                    edited |= markdown_field(ui, &mut node.summary, &self.settings.markdown, ("struct_node_summary", &path), 3, "");
+
+                    // ── Planning notes (Markdown, not part of the manuscript) ──
+                    let notes_title = if node.notes.trim().is_empty() {
+                        "筹备笔记".to_owned()
+                    } else {
+                        format!("筹备笔记（{} 字）", count_words(&node.notes))
+                    };
+                    egui::CollapsingHeader::new(notes_title)
+                        .id_salt("struct_node_notes")
+                        .show(ui, |ui| {
+                            edited |= markdown_field(
+                                ui,
+                                &mut node.notes,
+                                &self.settings.markdown,
+                                ("struct_node_notes", &path),
+                                6,
+                                "节拍、资料链接等，支持 Markdown，不会写入正文",
+                            );
+                        });
 
                     ui.add_space(6.0);
                     ui.separator();
 
                     // ── Linked world objects ───────────────────────────────────
-                    ui.label(RichText::new("关联的世界对象:").strong());
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("关联的世界对象:").strong());
+                        if ui.small_button("➕ 选择对象…").clicked() {
+                            open_obj_picker = true;
+                        }
+                    });
                     if node.linked_objects.is_empty() {
                         ui.label(RichText::new("（暂无关联对象）").color(Color32::GRAY).small());
-                    } else {
-                        let mut rm: Option<usize> = None;
-                        for (i, name) in node.linked_objects.iter().enumerate() {
-                            ui.horizontal(|ui| {
-                                ui.label(format!("• {name}"));
-                                if ui.small_button("🗑").clicked() { rm = Some(i); }
-                            });
-                        }
-                        if let Some(i) = rm { node.linked_objects.remove(i); }
                     }
-                    ui.horizontal(|ui| {
-                        ui.add(egui::TextEdit::singleline(&mut self.new_node_obj_link)
-                            .hint_text("对象名称")
-                            .desired_width(120.0));
-                        // Autocomplete hint
-                        if !self.new_node_obj_link.is_empty() {
-                            let m: Vec<&str> = obj_names.iter()
-                                .filter(|n| n.contains(self.new_node_obj_link.as_str()))
-                                .map(|s| s.as_str()).take(3).collect();
-                            if !m.is_empty() {
-                                ui.label(RichText::new(m.join(" / ")).small()
-                                    .color(Color32::from_gray(150)));
-                            }
+                    match draw_object_chips(
+                        ui, &node.linked_objects, &self.world_objects, &self.project_config, &kind_choices,
+                        &mut self.object_nav,
+                    ) {
+                        Some(ChipAction::Remove(i)) => {
+                            node.linked_objects.remove(i);
+                            edited = true;
                         }
-                        if ui.button("➕ 关联对象").clicked() {
-                            do_add_obj_link = true;
+                        Some(ChipAction::Move(i, up)) => {
+                            edited |= move_item(&mut node.linked_objects, i, up).is_some();
                         }
-                    });
+                        Some(ChipAction::Create(i, kind)) => {
+                            create_obj = Some((node.linked_objects[i].clone(), kind));
+                        }
+                        None => {}
+                    }
 
                     ui.add_space(6.0);
                     ui.separator();
@@ -300,7 +638,8 @@ impl TextToolApp {
                             .striped(true)
                             .show(ui, |ui| {
                                 for (i, nl) in node.node_links.iter().enumerate() {
-                                    ui.label(RichText::new(&nl.target_title).small());
+                                    chapter_title_label(ui, RichText::new(&nl.target_title).small(),
+                                        &nl.target_title, &mut hovered_chapter);
                                     ui.label(RichText::new(nl.kind.label()).small()
                                         .color(Color32::from_rgb(200, 160, 100)));
                                     ui.label(RichText::new(&nl.note).small()
@@ -309,12 +648,16 @@ impl TextToolApp {
                                     ui.end_row();
                                 }
                             });
-                        if let Some(i) = rm { node.node_links.remove(i); }
+                        if let Some(i) = rm {
+                            node.node_links.remove(i);
+                            edited = true;
+                        }
                     }
                     ui.horizontal(|ui| {
                         ui.add(egui::TextEdit::singleline(&mut self.new_node_link_title)
                             .hint_text("目标节点标题")
                             .desired_width(110.0));
+                        target_menu(ui, &mut self.new_node_link_title, &node_titles, &self.link_usage.node_targets);
                         // Autocomplete
                         if !self.new_node_link_title.is_empty() {
                             let m: Vec<&str> = node_titles.iter()
@@ -325,15 +668,8 @@ impl TextToolApp {
                                     .color(Color32::from_gray(150)));
                             }
                         }
-                        egui::ComboBox::from_id_salt("new_node_link_kind")
-                            .selected_text(self.new_node_link_kind.label())
-                            .width(80.0)
-                            .show_ui(ui, |ui| {
-                                for k in RelationKind::all() {
-                                    ui.selectable_value(
-                                        &mut self.new_node_link_kind, k.clone(), k.label());
-                                }
-                            });
+                        relation_combo(ui, "new_node_link_kind", &mut self.new_node_link_kind,
+                            &self.link_usage.node_relations, 80.0);
                         ui.add(egui::TextEdit::singleline(&mut self.new_node_link_note)
                             .hint_text("备注")
                             .desired_width(80.0));
@@ -343,6 +679,9 @@ impl TextToolApp {
                         }
                     });
                 });
+                if edited {
+                    node.touch();
+                }
             }
 
             // Deferred: update selection after inline child add
@@ -351,25 +690,44 @@ impl TextToolApp {
                 new_path.push(child_idx);
                 self.selected_node_path = new_path;
             }
-            // Deferred: add linked object
-            if do_add_obj_link {
-                let name = self.new_node_obj_link.trim().to_owned();
-                if let Some(node) = node_at_mut(&mut self.struct_roots, &path) {
-                    if !node.linked_objects.contains(&name) {
-                        node.linked_objects.push(name);
-                    }
-                }
-                self.new_node_obj_link.clear();
+            if let Some(ancestor) = select_ancestor {
+                self.selected_node_path = ancestor;
+            }
+            if open_obj_picker {
+                self.open_object_picker();
+            }
+            if open_reading {
+                self.open_reading(&path);
+            }
+            if start_writing {
+                self.start_writing(&path);
+            }
+            if let Some(old) = retitled {
+                self.propose_node_rename(&path, &old);
+            }
+            // Deferred: create the object an orange chip names
+            if let Some((name, kind)) = create_obj {
+                self.status = format!("已创建{}「{name}」", kind.label());
+                self.record_change(Change::ObjectAdded { name: &name, kind: kind.label() });
+                self.world_objects.push(WorldObject::new(&name, kind));
             }
             // Deferred: add node cross-link
             if do_add_node_link {
-                let title = self.new_node_link_title.trim().to_owned();
+                let title = normalize_title(&self.new_node_link_title);
                 if let Some(node) = node_at_mut(&mut self.struct_roots, &path) {
+                    let from = node.title.clone();
                     node.node_links.push(NodeLink {
-                        target_title: title,
+                        target_title: title.clone(),
                         kind: self.new_node_link_kind.clone(),
                         note: self.new_node_link_note.trim().to_owned(),
                     });
+                    node.touch();
+                    let relation = self.new_node_link_kind.label();
+                    self.record_change(Change::LinkAdded { from: &from, relation, to: &title });
+                    self.note_link_usage(|usage| {
+                        usage.node_relations.record(relation);
+                        usage.node_targets.record(&title);
+                    });
                 }
                 self.new_node_link_title.clear();
                 self.new_node_link_note.clear();
@@ -380,7 +738,10 @@ impl TextToolApp {
             self.draw_foreshadow_section(ui);
             ui.add_space(4.0);
             self.draw_milestone_section(ui);
-        });
+        }).response.rect;
+        self.note_focus_region(ctx, FocusRegion::NodeEditor, rect);
+
+        self.draw_chapter_preview(ctx, hovered_chapter);
     }
 
     // ── Struct tree recursive renderer ────────────────────────────────────────
@@ -391,13 +752,23 @@ impl TextToolApp {
         nodes: &[StructNode],
         selected: &[usize],
         path: &[usize],
+        deepest: &StructKind,
         add_child: &mut Option<(Vec<usize>, String, StructKind)>,
         remove_node: &mut Option<Vec<usize>>,
         move_up: &mut Option<Vec<usize>>,
+        synopsis_for: &mut Option<Vec<usize>>,
+        node_op: &mut Option<NodeOp>,
         // Drag-and-drop reorder target for root-level nodes only.
         // Passed through recursion unchanged; only written when `path.is_empty()`.
         root_dnd_move: &mut Option<(usize, usize)>,
         selected_path: &mut Vec<usize>,
+        foreshadows: &[Foreshadow],
+        fs_index: &ForeshadowIndex,
+        fs_badge_click: &mut Option<(Vec<usize>, String)>,
+        // Row under the pointer, for the hover card.
+        hovered_row: &mut Option<(Vec<usize>, egui::Rect)>,
+        // Scroll the selected row into view (↑ / ↓ while the tree has focus).
+        scroll_to_selected: bool,
     ) {
         for (i, node) in nodes.iter().enumerate() {
             let mut cur_path = path.to_vec();
@@ -415,9 +786,10 @@ impl TextToolApp {
                         ui.add_space(indent);
                         let label = format!("{} {}", node.kind.icon(), node.title);
                         let resp = ui.selectable_label(is_selected, &label);
+                        if is_selected && scroll_to_selected { resp.scroll_to_me(None); }
                         if resp.clicked() { *selected_path = cur_path.clone(); }
                         resp.context_menu(|ui| {
-                            let child_kind = node.kind.default_child_kind();
+                            let child_kind = node.kind.child_kind_within(deepest);
                             if ui.button(format!("➕ 添加子{}", child_kind.label())).clicked() {
                                 *add_child = Some((
                                     cur_path.clone(),
@@ -430,6 +802,17 @@ impl TextToolApp {
                                 *move_up = Some(cur_path.clone());
                                 ui.close_menu();
                             }
+                            if ui.button("📝 生成梗概…").clicked() {
+                                *synopsis_for = Some(cur_path.clone());
+                                ui.close_menu();
+                            }
+                            if ui.button("✂ 拆分节点…").clicked() {
+                                *node_op = Some(NodeOp::Split(cur_path.clone()));
+                                ui.close_menu();
+                            }
+                            if let Some(next) = nodes.get(i + 1) {
+                                merge_next_menu(ui, &node.title, &next.title, &cur_path, node_op);
+                            }
                             ui.separator();
                             if ui.button("🗑 删除").clicked() {
                                 *remove_node = Some(cur_path.clone());
@@ -442,22 +825,27 @@ impl TextToolApp {
                             ui.label(RichText::new(node.tag.label())
                                 .small().color(node.tag.color()));
                         }
+                        Self::foreshadow_badge(ui, node, &cur_path, foreshadows, fs_index, fs_badge_click);
                     });
                 });
                 if let Some(payload) = ir.response.dnd_release_payload::<usize>() {
                     let from = *payload;
                     if from != i { *root_dnd_move = Some((from, i)); }
                 }
+                if ui.rect_contains_pointer(ir.response.rect) {
+                    *hovered_row = Some((cur_path.clone(), ir.response.rect));
+                }
             } else {
-                ui.horizontal(|ui| {
+                let row = ui.horizontal(|ui| {
                     ui.add_space(indent);
                     let label = format!("{} {}", node.kind.icon(), node.title);
                     let resp = ui.selectable_label(is_selected, &label);
+                    if is_selected && scroll_to_selected { resp.scroll_to_me(None); }
                     if resp.clicked() {
                         *selected_path = cur_path.clone();
                     }
                     resp.context_menu(|ui| {
-                        let child_kind = node.kind.default_child_kind();
+                        let child_kind = node.kind.child_kind_within(deepest);
                         if ui.button(format!("➕ 添加子{}", child_kind.label())).clicked() {
                             *add_child = Some((
                                 cur_path.clone(),
@@ -470,6 +858,17 @@ impl TextToolApp {
                             *move_up = Some(cur_path.clone());
                             ui.close_menu();
                         }
+                        if ui.button("📝 生成梗概…").clicked() {
+                            *synopsis_for = Some(cur_path.clone());
+                            ui.close_menu();
+                        }
+                        if ui.button("✂ 拆分节点…").clicked() {
+                            *node_op = Some(NodeOp::Split(cur_path.clone()));
+                            ui.close_menu();
+                        }
+                        if let Some(next) = nodes.get(i + 1) {
+                            merge_next_menu(ui, &node.title, &next.title, &cur_path, node_op);
+                        }
                         ui.separator();
                         if ui.button("🗑 删除").clicked() {
                             *remove_node = Some(cur_path.clone());
@@ -482,18 +881,272 @@ impl TextToolApp {
                         ui.label(RichText::new(node.tag.label())
                             .small().color(node.tag.color()));
                     }
-                });
+                    Self::foreshadow_badge(ui, node, &cur_path, foreshadows, fs_index, fs_badge_click);
+                }).response.rect;
+                if ui.rect_contains_pointer(row) {
+                    *hovered_row = Some((cur_path.clone(), row));
+                }
             }
 
             if !node.children.is_empty() {
                 Self::draw_struct_tree(
-                    ui, &node.children, selected, &cur_path,
-                    add_child, remove_node, move_up, root_dnd_move, selected_path,
+                    ui, &node.children, selected, &cur_path, deepest,
+                    add_child, remove_node, move_up, synopsis_for, node_op, root_dnd_move, selected_path,
+                    foreshadows, fs_index, fs_badge_click, hovered_row, scroll_to_selected,
                 );
             }
         }
     }
 
+    /// Hover card of a struct tree row: tag, word count and summary, with
+    /// inline editing of the summary.  Opens after [`HOVER_CARD_DELAY`] on
+    /// the same row and closes once the pointer is over neither the row nor
+    /// the card; closing while editing commits the edit.
+    fn draw_struct_hover_card(&mut self, ctx: &Context, hovered: Option<(Vec<usize>, egui::Rect)>) {
+        let card_id = egui::Id::new("struct_hover_card");
+        let over_card = self.struct_hover.as_ref().is_some_and(|c| c.open)
+            && ctx.memory(|m| m.area_rect(card_id))
+                .zip(ctx.pointer_hover_pos())
+                .is_some_and(|(r, p)| r.expand(4.0).contains(p));
+
+        match hovered {
+            _ if over_card => {}
+            Some((path, row)) => match &mut self.struct_hover {
+                Some(card) if card.path == path => card.row = row,
+                _ => {
+                    self.commit_hover_edit();
+                    self.struct_hover = Some(StructHoverCard {
+                        path, row, since: Instant::now(), open: false, edit: None,
+                    });
+                }
+            },
+            None => {
+                self.commit_hover_edit();
+                self.struct_hover = None;
+            }
+        }
+
+        let Some(card) = &mut self.struct_hover else { return };
+        let Some(node) = node_at(&self.struct_roots, &card.path) else {
+            self.struct_hover = None;
+            return;
+        };
+        if !card.open {
+            let waited = card.since.elapsed();
+            if waited < HOVER_CARD_DELAY {
+                ctx.request_repaint_after(HOVER_CARD_DELAY - waited);
+                return;
+            }
+            card.open = true;
+        }
+
+        let words = self.project_root.as_ref().map_or(0, |root| {
+            let counts = self.project_index.word_counts_by_stem(&root.join("Content"));
+            leaf_word_count(node, &counts)
+        });
+        let mut start_edit = false;
+        let mut commit = false;
+        let mut cancel = false;
+        egui::Area::new(card_id)
+            .order(egui::Order::Foreground)
+            .fixed_pos(card.row.right_top())
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_max_width(260.0);
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(format!("{} {}", node.kind.icon(), node.title)).strong());
+                        ui.label(RichText::new(node.tag.label()).small().color(node.tag.color()));
+                    });
+                    ui.label(RichText::new(format!("字数: {words}")).small().color(Color32::from_gray(150)));
+                    ui.separator();
+                    match &mut card.edit {
+                        Some(text) => {
+                            let edit_id = egui::Id::new("struct_hover_card_edit");
+                            let resp = ui.add(egui::TextEdit::singleline(text)
+                                .id(edit_id)
+                                .hint_text("摘要")
+                                .desired_width(240.0));
+                            if !resp.has_focus() && !resp.lost_focus() {
+                                resp.request_focus();
+                            }
+                            if resp.lost_focus() {
+                                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                                    cancel = true;
+                                } else {
+                                    commit = true;
+                                }
+                            }
+                        }
+                        None => {
+                            ui.horizontal(|ui| {
+                                if node.summary.trim().is_empty() {
+                                    ui.label(RichText::new("（无摘要）").small().color(Color32::GRAY));
+                                } else {
+                                    ui.label(RichText::new(&node.summary).small());
+                                }
+                                if ui.small_button("✏").on_hover_text("编辑摘要").clicked() {
+                                    start_edit = true;
+                                }
+                            });
+                        }
+                    }
+                });
+            });
+
+        if start_edit {
+            card.edit = Some(node.summary.clone());
+        } else if commit {
+            self.commit_hover_edit();
+        } else if cancel {
+            card.edit = None;
+        }
+    }
+
+    /// Read-only 统计 window over the whole structure tree.
+    fn draw_struct_stats_window(&mut self, ctx: &Context) {
+        if !self.show_struct_stats { return; }
+
+        let counts = self.project_root.as_ref()
+            .map(|root| self.project_index.word_counts_by_stem(&root.join("Content")))
+            .unwrap_or_default();
+        let stats = StructStats::collect(&self.struct_roots, &counts);
+        let mut open = true;
+        egui::Window::new("📊 章节结构统计")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([360.0, 420.0])
+            .show(ctx, |ui| {
+                if ui.button("📋 复制为 Markdown").clicked() {
+                    ctx.copy_text(stats.to_markdown());
+                    self.status = "统计已复制到剪贴板".to_owned();
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().id_salt("struct_stats_scroll").show(ui, |ui| {
+                    let muted = Color32::from_gray(150);
+                    ui.strong("节点数量");
+                    ui.horizontal_wrapped(|ui| {
+                        for (kind, n) in &stats.kinds {
+                            ui.label(format!("{} {} {n}", kind.icon(), kind.label()));
+                        }
+                    });
+
+                    ui.add_space(6.0);
+                    ui.strong("分卷进度");
+                    if stats.volumes.is_empty() {
+                        ui.label(RichText::new("（无分卷）").small().color(muted));
+                    }
+                    for v in &stats.volumes {
+                        ui.horizontal(|ui| {
+                            ui.label(&v.title);
+                            ui.add(egui::ProgressBar::new(v.ratio())
+                                .desired_width(160.0)
+                                .text(format!("{} / {}", v.done, v.total)));
+                        });
+                    }
+
+                    ui.add_space(6.0);
+                    ui.strong("标签分布");
+                    ui.horizontal_wrapped(|ui| {
+                        for (tag, n) in &stats.tags {
+                            ui.label(RichText::new(format!("{} {n}", tag.label())).color(tag.color()));
+                        }
+                    });
+
+                    ui.add_space(6.0);
+                    ui.strong("概要长度");
+                    match stats.summary {
+                        Some((mean, median)) => {
+                            ui.label(format!("平均 {mean:.1} 字 · 中位数 {median:.1} 字"));
+                        }
+                        None => {
+                            ui.label(RichText::new("（暂无概要）").small().color(muted));
+                        }
+                    }
+
+                    ui.add_space(6.0);
+                    ui.strong(format!("未关联对象的章节（{}）", stats.unlinked.len()));
+                    for title in &stats.unlinked {
+                        ui.label(RichText::new(title).small());
+                    }
+
+                    ui.add_space(6.0);
+                    ui.strong("字数");
+                    ui.label(format!("已写 {}", stats.words));
+
+                    ui.add_space(6.0);
+                    ui.horizontal(|ui| {
+                        ui.strong("计划 vs 实际");
+                        let scope_label = self.burndown_scope
+                            .and_then(|i| self.struct_roots.get(i))
+                            .map_or("全部".to_owned(), |n| n.title.clone());
+                        egui::ComboBox::from_id_salt("burndown_scope")
+                            .selected_text(scope_label)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.burndown_scope, None, "全部");
+                                for (i, root) in self.struct_roots.iter().enumerate() {
+                                    ui.selectable_value(&mut self.burndown_scope, Some(i), &root.title);
+                                }
+                            });
+                    });
+                    let roots = match self.burndown_scope {
+                        Some(i) => self.struct_roots.get(i..=i).unwrap_or_default(),
+                        None => &self.struct_roots[..],
+                    };
+                    let today = today_days();
+                    let points = burndown(roots, &self.writing_stats.daily_words, today);
+                    if points.is_empty() {
+                        ui.label(RichText::new("（没有同时设置目标字数和截止日期的节点）").small().color(muted));
+                    } else {
+                        draw_burndown_chart(ui, &points, today);
+                        draw_burndown_summary(ui, &points, today);
+                        ui.label(RichText::new("实际字数取自每日写作统计（全项目合计）").small().color(muted));
+                    }
+                });
+            });
+        self.show_struct_stats = open;
+    }
+
+    /// Write the hover card's edited summary back to its node.
+    fn commit_hover_edit(&mut self) {
+        let Some(card) = &mut self.struct_hover else { return };
+        let Some(text) = card.edit.take() else { return };
+        if let Some(node) = node_at_mut(&mut self.struct_roots, &card.path) {
+            if node.summary != text {
+                node.summary = text;
+                node.touch();
+            }
+        }
+    }
+
+    /// "🔖N" after a tree row when N foreshadows list the node's title as a
+    /// related chapter; hover lists them, click filters the foreshadow list.
+    fn foreshadow_badge(
+        ui: &mut egui::Ui,
+        node: &StructNode,
+        path: &[usize],
+        foreshadows: &[Foreshadow],
+        fs_index: &ForeshadowIndex,
+        fs_badge_click: &mut Option<(Vec<usize>, String)>,
+    ) {
+        let related = fs_index.get(&node.title);
+        if related.is_empty() {
+            return;
+        }
+        let names: Vec<String> = related.iter()
+            .filter_map(|&i| foreshadows.get(i))
+            .map(|fs| format!("{} {}", if fs.resolved { "✅" } else { "⏳" }, fs.name))
+            .collect();
+        let badge = egui::Label::new(RichText::new(format!("🔖{}", related.len()))
+            .small().color(Color32::from_rgb(200, 160, 100)))
+            .sense(egui::Sense::click());
+        if ui.add(badge)
+            .on_hover_text(format!("相关伏笔（点击筛选）:\n{}", names.join("\n")))
+            .clicked()
+        {
+            *fs_badge_click = Some((path.to_vec(), node.title.trim().to_owned()));
+        }
+    }
+
     // ── Tree mutation helpers ──────────────────────────────────────────────────
 
     fn remove_node_at(roots: &mut Vec<StructNode>, path: &[usize]) {
@@ -523,6 +1176,18 @@ impl TextToolApp {
         }
     }
 
+    /// Journal the node now at `path` as moved there.
+    fn record_node_move(&mut self, path: &[usize]) {
+        let Some((&index, parent_path)) = path.split_last() else { return };
+        let Some(title) = node_at(&self.struct_roots, path).map(|n| n.title.clone()) else { return };
+        let parent = if parent_path.is_empty() {
+            None
+        } else {
+            node_at(&self.struct_roots, parent_path).map(|n| n.title.clone())
+        };
+        self.record_change(Change::NodeMoved { title: &title, parent: parent.as_deref(), index });
+    }
+
     fn count_progress(roots: &[StructNode]) -> (usize, usize) {
         let total: usize = roots.iter().map(|n| n.leaf_count()).sum();
         let done:  usize = roots.iter().map(|n| n.done_count()).sum();
@@ -532,12 +1197,41 @@ impl TextToolApp {
     // ── Foreshadow sub-section (shared with no-selection state) ───────────────
 
     fn draw_foreshadow_section(&mut self, ui: &mut egui::Ui) {
+        let mut toggled: Option<(String, bool)> = None;
         ui.group(|ui| {
             ui.horizontal(|ui| {
                 ui.heading("伏笔管理");
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    if ui.button("💾 同步到 MD").clicked() {
-                        self.sync_foreshadows_to_md();
+                    if ui.selectable_label(self.fs_board, "📋 看板")
+                        .on_hover_text("按 未植入 / 待回收 / 已回收 分列显示；卡片可在待回收与已回收之间拖动")
+                        .clicked()
+                    {
+                        self.fs_board = !self.fs_board;
+                    }
+                    if ui.button("💾 保存伏笔").clicked() {
+                        let r = self.sync_foreshadows();
+                        self.notify(r);
+                    }
+                    if ui.button("🔍 检查顺序")
+                        .on_hover_text("按章节结构顺序检查：回收是否早于埋设，关联章节是否存在")
+                        .clicked()
+                    {
+                        let warnings = check_foreshadows(&self.struct_roots, &self.foreshadows);
+                        self.status = if warnings.is_empty() {
+                            "伏笔顺序检查：未发现问题".to_owned()
+                        } else {
+                            format!("伏笔顺序检查：发现 {} 个问题", warnings.len())
+                        };
+                        self.fs_warnings = Some(warnings);
+                    }
+                    if ui.small_button("⏱").on_hover_text("按更新时间排序（最近修改在前）").clicked() {
+                        let mut indexed: Vec<(usize, Foreshadow)> =
+                            std::mem::take(&mut self.foreshadows).into_iter().enumerate().collect();
+                        indexed.sort_by_key(|(_, fs)| std::cmp::Reverse(fs.updated_at));
+                        self.selected_fs_idx = self.selected_fs_idx
+                            .and_then(|sel| indexed.iter().position(|(old, _)| *old == sel));
+                        self.foreshadows = indexed.into_iter().map(|(_, fs)| fs).collect();
+                        self.fs_warnings = None;
                     }
                 });
             });
@@ -553,24 +1247,86 @@ impl TextToolApp {
                         self.foreshadows.push(Foreshadow::new(&name));
                         self.selected_fs_idx = Some(idx);
                         self.new_fs_name.clear();
+                        self.fs_filter = None;
+                        self.fs_warnings = None;
                     }
                 }
             });
 
             ui.add_space(4.0);
 
+            let filter: Option<Vec<usize>> = self.fs_filter.as_ref()
+                .map(|title| self.fs_index.get(title).to_vec());
+            if self.fs_board {
+                if let Some(title) = &self.fs_filter {
+                    let mut clear = false;
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(format!("关联「{title}」的伏笔"))
+                            .color(Color32::from_rgb(200, 160, 100)));
+                        clear = ui.small_button("✕").on_hover_text("显示全部伏笔").clicked();
+                    });
+                    if clear {
+                        self.fs_filter = None;
+                    }
+                }
+                let mut stage_drop = None;
+                Self::draw_foreshadow_board(
+                    ui, &self.foreshadows, filter.as_deref(), &mut self.selected_fs_idx, &mut stage_drop,
+                );
+                if let Some((i, stage)) = stage_drop {
+                    if let Some(fs) = self.foreshadows.get_mut(i) {
+                        if fs.set_stage(stage) {
+                            toggled = Some((fs.name.clone(), fs.resolved));
+                        } else {
+                            self.status = format!("「{}」在哪一列由关联章节决定，无法拖到{}", fs.name, stage.label());
+                        }
+                    }
+                }
+                if self.selected_fs_idx.is_some() {
+                    ui.separator();
+                    if let Some(change) = self.draw_foreshadow_editor(ui) {
+                        toggled = Some(change);
+                    }
+                }
+                return;
+            }
             ui.columns(2, |cols| {
-                cols[0].label("伏笔列表:");
+                if let Some(title) = &self.fs_filter {
+                    let mut clear = false;
+                    cols[0].horizontal(|ui| {
+                        ui.label(RichText::new(format!("伏笔列表（关联「{title}」）:"))
+                            .color(Color32::from_rgb(200, 160, 100)));
+                        clear = ui.small_button("✕").on_hover_text("显示全部伏笔").clicked();
+                    });
+                    if clear {
+                        self.fs_filter = None;
+                    }
+                } else {
+                    cols[0].label("伏笔列表:");
+                }
                 egui::ScrollArea::vertical().id_salt("fs_list_scroll").show(&mut cols[0], |ui| {
                     let mut to_remove: Option<usize> = None;
                     for (i, fs) in self.foreshadows.iter().enumerate() {
+                        if filter.as_ref().is_some_and(|f| !f.contains(&i)) {
+                            continue;
+                        }
                         let selected = self.selected_fs_idx == Some(i);
                         let label = if fs.resolved {
                             format!("✅ {}", fs.name)
                         } else {
                             format!("⏳ {}", fs.name)
                         };
-                        let resp = ui.selectable_label(selected, &label);
+                        let problems: Vec<String> = self.fs_warnings.iter().flatten()
+                            .filter(|w| w.index == i)
+                            .map(|w| w.issue.message())
+                            .collect();
+                        let resp = if problems.is_empty() {
+                            ui.selectable_label(selected, &label)
+                        } else {
+                            ui.selectable_label(selected, RichText::new(format!("{label} ⚠"))
+                                .color(Color32::from_rgb(230, 160, 60)))
+                                .on_hover_text(problems.join("\n"))
+                        };
                         resp.context_menu(|ui| {
                             if ui.button("删除").clicked() {
                                 to_remove = Some(i);
@@ -583,6 +1339,7 @@ impl TextToolApp {
                     }
                     if let Some(idx) = to_remove {
                         self.foreshadows.remove(idx);
+                        self.fs_warnings = None;
                         if self.selected_fs_idx == Some(idx) {
                             self.selected_fs_idx = None;
                         } else if let Some(sel) = self.selected_fs_idx {
@@ -591,32 +1348,107 @@ impl TextToolApp {
                     }
                 });
 
-                if let Some(idx) = self.selected_fs_idx {
-                    if let Some(fs) = self.foreshadows.get_mut(idx) {
-                        cols[1].label("伏笔名称:");
-                        cols[1].text_edit_singleline(&mut fs.name);
-                        cols[1].add_space(4.0);
-                        cols[1].label("描述:");
-                        cols[1].text_edit_multiline(&mut fs.description);
-                        cols[1].add_space(4.0);
-                        cols[1].checkbox(&mut fs.resolved, "已解决/揭示");
-                        cols[1].add_space(4.0);
-                        cols[1].label("关联章节 (逗号分隔):");
-                        let mut related = fs.related_chapters.join("、");
-                        if cols[1].text_edit_singleline(&mut related).changed() {
-                            fs.related_chapters = related
-                                .split(['，', '、', ','])
-                                .map(|s| s.trim().to_owned())
-                                .filter(|s| !s.is_empty())
-                                .collect();
+                toggled = self.draw_foreshadow_editor(&mut cols[1]);
+            });
+        });
+        if let Some((name, resolved)) = toggled {
+            note_session(&mut self.session_resolved, &name, resolved);
+            self.record_change(Change::ForeshadowResolved { name: &name, resolved });
+        }
+    }
+
+    /// Detail editor of the selected foreshadow.  Returns its name and
+    /// state when 已解决/揭示 was toggled.
+    fn draw_foreshadow_editor(&mut self, ui: &mut egui::Ui) -> Option<(String, bool)> {
+        let Some(fs) = self.selected_fs_idx.and_then(|i| self.foreshadows.get_mut(i)) else {
+            ui.centered_and_justified(|ui| {
+                ui.label(RichText::new("选择左侧伏笔以编辑").color(Color32::GRAY));
+            });
+            return None;
+        };
+        let mut toggled = None;
+        let mut edited = false;
+        ui.label("伏笔名称:");
+        edited |= ui.text_edit_singleline(&mut fs.name).changed();
+        let now = unix_now();
+        ui.label(RichText::new(format!(
+            "更新于 {} · 创建于 {}",
+            format_relative_time(fs.updated_at, now),
+            format_relative_time(fs.created_at, now),
+        )).small().color(Color32::GRAY));
+        ui.add_space(4.0);
+        ui.label("描述:");
+        edited |= ui.text_edit_multiline(&mut fs.description).changed();
+        ui.add_space(4.0);
+        if ui.checkbox(&mut fs.resolved, "已解决/揭示").changed() {
+            toggled = Some((fs.name.clone(), fs.resolved));
+            edited = true;
+        }
+        ui.add_space(4.0);
+        ui.label("关联章节 (逗号分隔):")
+            .on_hover_text("埋下或提到这条伏笔的章节");
+        let mut related = fs.related_chapters.join("、");
+        if ui.text_edit_singleline(&mut related).changed() {
+            fs.related_chapters = split_chapter_list(&related);
+            edited = true;
+        }
+        ui.label("回收章节 (逗号分隔):")
+            .on_hover_text("揭示或回收这条伏笔的章节；「检查顺序」会核对它不早于首次埋设");
+        let mut resolving = fs.resolve_chapters.join("、");
+        if ui.text_edit_singleline(&mut resolving).changed() {
+            fs.resolve_chapters = split_chapter_list(&resolving);
+            edited = true;
+        }
+        if edited {
+            fs.touch();
+        }
+        toggled
+    }
+
+    /// 伏笔看板: a column per [`ForeshadowStage`], limited to `filter` when
+    /// set.  Clicking a card selects it; dropping one on another column
+    /// records the move in `stage_drop`.  未植入 cards do not drag: that
+    /// column follows from the chapters.
+    fn draw_foreshadow_board(
+        ui: &mut egui::Ui,
+        foreshadows: &[Foreshadow],
+        filter: Option<&[usize]>,
+        selected: &mut Option<usize>,
+        stage_drop: &mut Option<(usize, ForeshadowStage)>,
+    ) {
+        let columns = foreshadow_columns(foreshadows);
+        ui.columns(columns.len(), |cols| {
+            for ((ui, stage), indices) in cols.iter_mut().zip(ForeshadowStage::all()).zip(&columns) {
+                let shown: Vec<usize> = indices.iter().copied()
+                    .filter(|i| filter.is_none_or(|f| f.contains(i)))
+                    .collect();
+                ui.label(RichText::new(format!("{} ({})", stage.label(), shown.len())).strong());
+                let frame = egui::Frame::none()
+                    .rounding(4.0)
+                    .inner_margin(egui::Margin::same(4.0));
+                let (_, dropped) = ui.dnd_drop_zone::<ForeshadowCard, _>(frame, |ui| {
+                    ui.set_min_size(egui::vec2(ui.available_width(), 60.0));
+                    for i in shown {
+                        let fs = &foreshadows[i];
+                        let is_selected = *selected == Some(i);
+                        let clicked = if stage == ForeshadowStage::Unplanted {
+                            foreshadow_card(ui, fs, is_selected)
+                        } else {
+                            let id = egui::Id::new(("fs_board_card", i));
+                            ui.dnd_drag_source(id, ForeshadowCard(i), |ui| foreshadow_card(ui, fs, is_selected)).inner
+                        };
+                        if clicked {
+                            *selected = Some(i);
                         }
+                        ui.add_space(2.0);
+                    }
+                });
+                if let Some(card) = dropped {
+                    if foreshadows.get(card.0).is_some_and(|fs| fs.stage() != stage) {
+                        *stage_drop = Some((card.0, stage));
                     }
-                } else {
-                    cols[1].centered_and_justified(|ui| {
-                        ui.label(RichText::new("选择左侧伏笔以编辑").color(Color32::GRAY));
-                    });
                 }
-            });
+            }
         });
     }
 
@@ -686,6 +1518,63 @@ impl TextToolApp {
         }
     }
 
+    /// 看板 view: leaf nodes as cards in one column per [`NodeStage`].
+    /// Dropping a card on another column records the move in `stage_drop`.
+    fn draw_struct_kanban(
+        ui: &mut egui::Ui,
+        roots: &[StructNode],
+        selected_path: &mut Vec<usize>,
+        stage_drop: &mut Option<(Vec<usize>, NodeStage)>,
+    ) {
+        let columns = kanban_columns(roots);
+        ui.columns(columns.len(), |cols| {
+            for ((ui, stage), paths) in cols.iter_mut().zip(NodeStage::all()).zip(&columns) {
+                ui.label(RichText::new(format!("{} ({})", stage.label(), paths.len())).strong());
+                let frame = egui::Frame::none()
+                    .rounding(4.0)
+                    .inner_margin(egui::Margin::same(4.0));
+                let (_, dropped) = ui.dnd_drop_zone::<KanbanCard, _>(frame, |ui| {
+                    ui.set_min_size(egui::vec2(ui.available_width(), 60.0));
+                    for path in paths {
+                        let Some(node) = node_at(roots, path) else { continue };
+                        let is_selected = *selected_path == *path;
+                        let id = egui::Id::new(("struct_kanban_card", path));
+                        ui.dnd_drag_source(id, KanbanCard(path.clone()), |ui| {
+                            let fill = if is_selected {
+                                Color32::from_rgb(0, 100, 170)
+                            } else {
+                                Color32::from_gray(38)
+                            };
+                            egui::Frame::none()
+                                .fill(fill)
+                                .rounding(4.0)
+                                .inner_margin(egui::Margin::symmetric(6.0, 3.0))
+                                .show(ui, |ui| {
+                                    ui.set_min_width(ui.available_width());
+                                    if ui.selectable_label(is_selected, RichText::new(&node.title).size(13.0))
+                                        .clicked()
+                                    {
+                                        *selected_path = path.clone();
+                                    }
+                                    if node.tag != ChapterTag::Normal {
+                                        ui.label(RichText::new(node.tag.label())
+                                            .small().color(node.tag.color()));
+                                    }
+                                });
+                        });
+                        ui.add_space(2.0);
+                    }
+                });
+                if let Some(card) = dropped {
+                    let moved = node_at(roots, &card.0).is_some_and(|n| n.stage() != stage);
+                    if moved {
+                        *stage_drop = Some((card.0.clone(), stage));
+                    }
+                }
+            }
+        });
+    }
+
     // ── Milestone sub-section ─────────────────────────────────────────────────
 
     fn draw_milestone_section(&mut self, ui: &mut egui::Ui) {
@@ -698,7 +1587,8 @@ impl TextToolApp {
                 ui.heading("里程碑");
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button("💾 同步到 JSON").clicked() {
-                        self.sync_milestones_to_json();
+                        let r = self.sync_milestones_to_json();
+                        self.notify(r);
                     }
                 });
             });
@@ -792,3 +1682,92 @@ impl TextToolApp {
         });
     }
 }
+
+// ── 计划 vs 实际 chart ────────────────────────────────────────────────────────
+
+/// Cumulative plan and words written as two lines over the days of
+/// `points`, today marked; hovering shows the values of a day.
+/// Titles from the root down to the node at `path`, `第一卷 › 第一章`.
+fn title_breadcrumb(roots: &[StructNode], path: &[usize]) -> String {
+    (1..=path.len())
+        .filter_map(|len| node_at(roots, &path[..len]).map(|n| n.title.as_str()))
+        .collect::<Vec<_>>()
+        .join(" › ")
+}
+
+fn draw_burndown_chart(ui: &mut egui::Ui, points: &[BurndownPoint], today: i64) {
+    let size = egui::vec2(ui.available_width().max(240.0), 160.0);
+    let (rect, resp) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let plot = egui::Rect::from_min_max(rect.min + egui::vec2(4.0, 14.0), rect.max - egui::vec2(4.0, 14.0));
+    let muted = Color32::from_gray(140);
+    let font = egui::FontId::proportional(10.0);
+
+    let values = points.iter().map(|p| p.planned as i64).chain(points.iter().filter_map(|p| p.written));
+    let (lo, hi) = values.fold((0, 1), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let span = points.len().saturating_sub(1).max(1) as f32;
+    let x_at = |i: usize| plot.left() + plot.width() * i as f32 / span;
+    let y_at = |v: i64| plot.bottom() - plot.height() * (v - lo) as f32 / (hi - lo) as f32;
+
+    painter.rect_stroke(plot, 0.0, egui::Stroke::new(1.0, Color32::from_gray(70)));
+    if let Some(i) = points.iter().position(|p| p.day == today) {
+        painter.vline(x_at(i), plot.y_range(), egui::Stroke::new(1.0, Color32::from_gray(90)));
+        painter.text(egui::pos2(x_at(i), plot.top()), egui::Align2::CENTER_BOTTOM, "今天", font.clone(), muted);
+    }
+    let plan: Vec<egui::Pos2> = points.iter().enumerate()
+        .map(|(i, p)| egui::pos2(x_at(i), y_at(p.planned as i64)))
+        .collect();
+    painter.add(egui::Shape::line(plan, egui::Stroke::new(1.5, PLAN_COLOR)));
+    let written: Vec<egui::Pos2> = points.iter().enumerate()
+        .filter_map(|(i, p)| Some(egui::pos2(x_at(i), y_at(p.written?))))
+        .collect();
+    painter.add(egui::Shape::line(written, egui::Stroke::new(1.5, WRITTEN_COLOR)));
+
+    painter.text(plot.left_top() + egui::vec2(3.0, 2.0), egui::Align2::LEFT_TOP, hi.to_string(), font.clone(), muted);
+    if let (Some(first), Some(last)) = (points.first(), points.last()) {
+        painter.text(plot.left_bottom(), egui::Align2::LEFT_TOP, format_day(first.day), font.clone(), muted);
+        painter.text(plot.right_bottom(), egui::Align2::RIGHT_TOP, format_day(last.day), font, muted);
+    }
+
+    if let Some(pos) = resp.hover_pos() {
+        let i = (((pos.x - plot.left()) / plot.width() * span).round().max(0.0) as usize).min(points.len() - 1);
+        painter.vline(x_at(i), plot.y_range(), egui::Stroke::new(1.0, Color32::from_gray(160)));
+        let p = points[i];
+        resp.on_hover_ui_at_pointer(|ui| {
+            ui.label(format_day(p.day));
+            ui.label(RichText::new(format!("计划 {}", p.planned)).color(PLAN_COLOR));
+            if let Some(w) = p.written {
+                ui.label(RichText::new(format!("实际 {w}")).color(WRITTEN_COLOR));
+            }
+        });
+    }
+}
+
+/// Legend with today's standing, and when the plan's total is reached at
+/// the recent pace.
+fn draw_burndown_summary(ui: &mut egui::Ui, points: &[BurndownPoint], today: i64) {
+    let Some(last) = points.last() else { return };
+    let now = points.iter().rev().find(|p| p.day <= today).copied().unwrap_or(points[0]);
+    let written = now.written.unwrap_or(0);
+    ui.horizontal_wrapped(|ui| {
+        ui.label(RichText::new(format!("— 计划 {}", now.planned)).color(PLAN_COLOR));
+        ui.label(RichText::new(format!("— 实际 {written}")).color(WRITTEN_COLOR));
+        let ahead = written - now.planned as i64;
+        let (text, color) = if ahead >= 0 {
+            (format!("领先 {ahead} 字"), WRITTEN_COLOR)
+        } else {
+            (format!("落后 {} 字", -ahead), Color32::from_rgb(220, 150, 80))
+        };
+        ui.label(RichText::new(text).color(color));
+    });
+    let deadline = last.day;
+    let pace = match projected_finish(points, today, PACE_WINDOW_DAYS) {
+        Some(day) if day <= deadline => format!("按近 {PACE_WINDOW_DAYS} 天的速度，{} 可完成全部 {} 字（截止 {}）",
+            format_day(day), last.planned, format_day(deadline)),
+        Some(day) => format!("按近 {PACE_WINDOW_DAYS} 天的速度，{} 才能完成，比截止日期 {} 晚 {} 天",
+            format_day(day), format_day(deadline), day - deadline),
+        None => format!("近 {PACE_WINDOW_DAYS} 天没有新增字数，无法预计完成日期"),
+    };
+    ui.label(RichText::new(pace).small());
+}
+
@@ -0,0 +1,143 @@
+use egui::{Color32, Context, Key, RichText};
+use text_tool_core::reading::ReadingBook;
+
+use super::super::{node_at, ReadingView, TextToolApp};
+use super::markdown::render_markdown_sized;
+use super::novel::node_md_file;
+
+// ── 阅读 view ─────────────────────────────────────────────────────────────────
+
+/// Widest text column, in multiples of the font size.
+const COLUMN_EMS: f32 = 40.0;
+
+impl TextToolApp {
+    /// Compile the chapters of the volume at `path` and open them in the
+    /// 阅读 view, at the position saved for the volume.
+    pub(in crate::app) fn open_reading(&mut self, path: &[usize]) {
+        let Some(root) = self.project_root.clone() else {
+            self.status = "请先打开一个项目".to_owned();
+            return;
+        };
+        let Some(volume) = node_at(&self.struct_roots, path) else {
+            return;
+        };
+        let content = root.join("Content");
+        let mut docs = Vec::new();
+        let mut missing = 0;
+        for leaf in volume.leaves() {
+            match node_md_file(&content, leaf).and_then(|p| std::fs::read_to_string(p).ok()) {
+                Some(text) => docs.push((leaf.title.clone(), text)),
+                None => missing += 1,
+            }
+        }
+        if docs.is_empty() {
+            self.status = format!("「{}」下没有找到章节文件", volume.title);
+            return;
+        }
+        let book = ReadingBook::compile(&docs);
+        let (page, scroll) = self.project_config.reading_positions.get(&volume.title)
+            .map(|pos| (book.resolve(pos), pos.scroll))
+            .unwrap_or((0, 0.0));
+        self.status = if missing > 0 {
+            format!("阅读「{}」：{} 章，{missing} 章没有文件已跳过", volume.title, docs.len())
+        } else {
+            format!("阅读「{}」：{} 章", volume.title, docs.len())
+        };
+        self.reading = Some(ReadingView {
+            volume: volume.title.clone(),
+            book,
+            page,
+            scroll,
+            pending: Some(scroll),
+            measured: None,
+            max_scroll: 0.0,
+            from: self.active_panel,
+        });
+    }
+
+    /// Close the 阅读 view and remember where reading stopped.
+    pub(in crate::app) fn close_reading(&mut self) {
+        let Some(view) = self.reading.take() else {
+            return;
+        };
+        let pos = view.book.position(view.page, view.scroll);
+        self.project_config.reading_positions.insert(view.volume, pos);
+        self.save_project_config();
+    }
+
+    pub(in crate::app) fn draw_reading_view(&mut self, ctx: &Context) {
+        let Some(view) = &mut self.reading else {
+            return;
+        };
+        let mut close = view.from != self.active_panel
+            || ctx.input(|i| i.key_pressed(Key::Escape));
+        let mut goto: Option<(usize, f32)> = None;
+        let n = view.book.pages.len();
+        let font_size = self.settings.markdown.preview_font_size + 2.0;
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(format!("📖 {}", view.volume));
+                ui.label(RichText::new(format!("第 {} / {n} 章 · {}", view.page + 1, view.book.pages[view.page].title))
+                    .color(Color32::from_gray(160)));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("✖ 退出阅读").on_hover_text("Esc").clicked() {
+                        close = true;
+                    }
+                });
+            });
+            ui.horizontal(|ui| {
+                if ui.add_enabled(view.page > 0, egui::Button::new("◀ 上一章")).clicked() {
+                    goto = Some((view.page - 1, 0.0));
+                }
+                if ui.add_enabled(view.page + 1 < n, egui::Button::new("下一章 ▶")).clicked() {
+                    goto = Some((view.page + 1, 0.0));
+                }
+                let mut progress = view.book.progress(view.page, view.scroll);
+                ui.spacing_mut().slider_width = (ui.available_width() - 60.0).max(80.0);
+                if ui.add(egui::Slider::new(&mut progress, 0.0..=1.0).show_value(false)).changed() {
+                    goto = Some(view.book.locate(progress));
+                }
+                ui.label(format!("{:.0}%", progress * 100.0));
+            });
+            ui.separator();
+
+            let mut area = egui::ScrollArea::vertical()
+                .id_salt(("reading_page", view.page))
+                .auto_shrink(false);
+            if view.measured == Some(view.page) {
+                if let Some(frac) = view.pending.take() {
+                    area = area.vertical_scroll_offset(frac * view.max_scroll);
+                }
+            }
+            let out = area.show(ui, |ui| {
+                let width = ui.available_width();
+                let column = width.min(font_size * COLUMN_EMS);
+                egui::Frame::none()
+                    .inner_margin(egui::Margin::symmetric((width - column) / 2.0, 12.0))
+                    .show(ui, |ui| {
+                        render_markdown_sized(ui, &view.book.pages[view.page].text, font_size, Some(font_size * 1.9));
+                    });
+            });
+            view.max_scroll = (out.content_size.y - out.inner_rect.height()).max(0.0);
+            view.measured = Some(view.page);
+            if view.pending.is_some() {
+                ctx.request_repaint();
+            } else if view.max_scroll > 0.0 {
+                view.scroll = (out.state.offset.y / view.max_scroll).clamp(0.0, 1.0);
+            }
+        });
+
+        if let Some((page, scroll)) = goto {
+            if page != view.page {
+                view.measured = None;
+            }
+            view.page = page;
+            view.scroll = scroll;
+            view.pending = Some(scroll);
+        }
+        if close {
+            self.close_reading();
+        }
+    }
+}
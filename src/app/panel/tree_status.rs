@@ -0,0 +1,164 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::content_index::INDEX_FILE;
+
+use super::super::{
+    node_at, node_at_mut, node_paths, node_position, normalize_rel_path, same_title, FileNodeMap, StructAddFile,
+    StructKind, StructNode, TextToolApp,
+};
+
+// ── File tree × structure: status of each chapter file ───────────────────────
+//
+// Files under `Content/` that a structure leaf stands for show that node's
+// state in the file tree (✅ when done, a dot in its tag color); the others
+// are marked 未纳入结构 and can be added to the structure from their context
+// menu.
+
+/// `Content/` files that are not chapters.
+const NOT_CHAPTERS: [&str; 2] = [INDEX_FILE, "伏笔.md"];
+
+/// Hash of what [`FileNodeMap`] and the tree badges read from the structure.
+fn struct_file_fingerprint(roots: &[StructNode]) -> u64 {
+    fn walk(nodes: &[StructNode], h: &mut DefaultHasher) {
+        for n in nodes {
+            (&n.title, &n.file, n.done, n.tag.label(), n.children.len()).hash(h);
+            walk(&n.children, h);
+        }
+    }
+    let mut h = DefaultHasher::new();
+    walk(roots, &mut h);
+    h.finish()
+}
+
+/// What the file tree needs to decorate chapter files.
+pub(in crate::app) struct TreeStructure<'a> {
+    content: Option<PathBuf>,
+    map: &'a FileNodeMap,
+    roots: &'a [StructNode],
+}
+
+impl TreeStructure<'_> {
+    /// `None` for files that are not chapters (outside `Content/`, not
+    /// Markdown); else the structure leaf standing for `path`, if any.
+    pub(in crate::app) fn node_for(&self, path: &Path) -> Option<Option<&StructNode>> {
+        let rel = path.strip_prefix(self.content.as_ref()?).ok()?.to_string_lossy().into_owned();
+        if !rel.ends_with(".md") || NOT_CHAPTERS.contains(&rel.as_str()) {
+            return None;
+        }
+        Some(self.map.lookup(&rel).and_then(|p| node_at(self.roots, p)))
+    }
+
+    /// Whether unlinked files get the 未纳入结构 marker: not while there is
+    /// no structure at all, when every file would.
+    pub(in crate::app) fn marks_unlinked(&self) -> bool {
+        !self.roots.is_empty()
+    }
+}
+
+impl TextToolApp {
+    /// Rebuild the file → node map when the structure changed since it was
+    /// built.
+    pub(in crate::app) fn refresh_file_node_map(&mut self) {
+        let fingerprint = struct_file_fingerprint(&self.struct_roots);
+        if self.file_node_map.0 != fingerprint {
+            self.file_node_map = (fingerprint, FileNodeMap::build(&self.struct_roots));
+        }
+    }
+
+    /// Chapter file status for the file tree; see [`Self::refresh_file_node_map`].
+    pub(in crate::app) fn tree_structure(&self) -> TreeStructure<'_> {
+        TreeStructure {
+            content: self.project_root.as_ref().map(|r| r.join("Content")),
+            map: &self.file_node_map.1,
+            roots: &self.struct_roots,
+        }
+    }
+
+    /// 添加到结构…: propose a chapter named after the file, under the node
+    /// named after its folder when there is one.
+    pub(in crate::app) fn open_struct_add_file(&mut self, path: &Path) {
+        let Some(content) = self.project_root.as_ref().map(|r| r.join("Content")) else { return };
+        let Ok(rel) = path.strip_prefix(&content) else { return };
+        let rel = normalize_rel_path(&rel.to_string_lossy());
+        let title = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let folder = rel.rsplit_once('/').map(|(dir, _)| dir.rsplit('/').next().unwrap_or(dir));
+        let parent = folder.and_then(|f| {
+            node_paths(&self.struct_roots).into_iter()
+                .find(|p| node_at(&self.struct_roots, p).is_some_and(|n| same_title(&n.title, f)))
+        });
+        self.struct_add_file = Some(StructAddFile { rel, title, parent });
+    }
+
+    pub(in crate::app) fn draw_struct_add_file_dialog(&mut self, ctx: &Context) {
+        let Some(dlg) = &mut self.struct_add_file else { return };
+        let mut open = true;
+        let mut cancel = false;
+        let mut confirm = false;
+        let roots = &self.struct_roots;
+        let parent_label = |p: &Option<Vec<usize>>| match p.as_deref().and_then(|p| node_at(roots, p).map(|n| (p, n))) {
+            Some((p, n)) => format!("{} {}", node_position(roots, p).unwrap_or_default(), n.title).trim().to_owned(),
+            None => "（顶层）".to_owned(),
+        };
+
+        egui::Window::new("添加到结构")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(RichText::new(format!("Content/{}", dlg.rel)).small().color(Color32::from_gray(140)));
+                egui::Grid::new("struct_add_file_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("章节标题:");
+                    ui.text_edit_singleline(&mut dlg.title);
+                    ui.end_row();
+                    ui.label("上级节点:");
+                    egui::ComboBox::from_id_salt("struct_add_file_parent")
+                        .selected_text(parent_label(&dlg.parent))
+                        .width(220.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut dlg.parent, None, "（顶层）");
+                            for path in node_paths(roots) {
+                                let Some(node) = node_at(roots, &path) else { continue };
+                                if node.kind == StructKind::Scene {
+                                    continue;
+                                }
+                                let label = format!("{}{}", "  ".repeat(path.len() - 1), node.title);
+                                ui.selectable_value(&mut dlg.parent, Some(path), label);
+                            }
+                        });
+                    ui.end_row();
+                });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!dlg.title.trim().is_empty(), egui::Button::new("添加")).clicked() {
+                        confirm = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if confirm {
+            let Some(dlg) = self.struct_add_file.take() else { return };
+            let mut node = StructNode::new(dlg.title.trim(), StructKind::Chapter);
+            node.file = Some(dlg.rel);
+            let siblings = match dlg.parent.as_deref() {
+                Some(p) => node_at_mut(&mut self.struct_roots, p).map(|n| &mut n.children),
+                None => Some(&mut self.struct_roots),
+            };
+            match siblings {
+                Some(siblings) => {
+                    siblings.push(node);
+                    self.status = format!("已将「{}」添加到章节结构", dlg.title.trim());
+                }
+                None => self.status = "上级节点已不存在".to_owned(),
+            }
+        } else if !open || cancel {
+            self.struct_add_file = None;
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use text_tool_core::writing_context::{writing_context, WRITING_CONTEXT_NAME};
+
+use super::super::{node_at, node_titled_near, AfterOpen, OpenFile, Panel, TextToolApp};
+use super::novel::node_md_file;
+
+// ── 开始写作: chapter on the left, its 写作上下文 on the right ─────────────────
+
+impl TextToolApp {
+    /// 开始写作 on the node at `path`: open its chapter file in the left pane
+    /// and the node's 写作上下文 as a scratch buffer in the right pane.
+    pub(in crate::app) fn start_writing(&mut self, path: &[usize]) {
+        let Some(node) = node_at(&self.struct_roots, path) else { return };
+        let title = node.title.clone();
+        if let Some(f) = self.right_file.as_ref().filter(|f| f.modified && !f.scratch) {
+            self.status = format!("右侧「{}」有未保存的修改，请先保存", f.path.display());
+            return;
+        }
+        let file = self.project_root.as_ref()
+            .and_then(|root| node_md_file(&root.join("Content"), node));
+
+        match file {
+            Some(file) => {
+                let r = self.open_file_then(&file, true, AfterOpen::WritingContext(title));
+                self.notify_open(r);
+            }
+            None => {
+                self.show_writing_context(&title);
+                self.active_panel = Panel::Novel;
+                self.status = format!("未找到「{title}」对应的章节文件，仅打开了写作上下文");
+            }
+        }
+    }
+
+    /// 🔄 in the right pane: rebuild the open 写作上下文 from the current
+    /// design data.
+    pub(in crate::app) fn regenerate_writing_context(&mut self) {
+        let Some(title) = self.writing_context_for.clone() else { return };
+        if node_titled_near(&self.struct_roots, &title, &self.selected_node_path).is_none() {
+            self.status = format!("「{title}」已不在章节结构中");
+            return;
+        }
+        self.show_writing_context(&title);
+        self.status = format!("已重新生成「{title}」的写作上下文");
+    }
+
+    /// Put the 写作上下文 of the node titled `title` in the right pane; of
+    /// nodes sharing the title, the one nearest the selected node.
+    pub(in crate::app) fn show_writing_context(&mut self, title: &str) {
+        let Some(node) = node_titled_near(&self.struct_roots, title, &self.selected_node_path) else { return };
+        let doc = writing_context(node, &self.world_objects, &self.foreshadows);
+        if let Some(prev) = self.right_file.take().filter(|f| !f.scratch) {
+            self.note_closed_file(prev.path);
+        }
+        self.right_file = Some(OpenFile::scratch(WRITING_CONTEXT_NAME, doc));
+        self.right_undo_stack.clear();
+        self.writing_context_for = Some(title.to_owned());
+    }
+}
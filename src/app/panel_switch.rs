@@ -0,0 +1,182 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use super::{panel_switch_saves, Panel, SaveSet, StructNode, TextToolApp};
+
+// ── Panel transitions: per-panel enter / leave logic ─────────────────────────
+
+/// Hashes of the design data, taken on entering a design panel; whatever
+/// differs when leaving was edited there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DesignDigest {
+    objects: u64,
+    structure: u64,
+    foreshadows: u64,
+    milestones: u64,
+}
+
+/// One kind of design data covered by a [`DesignDigest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DesignPart {
+    Objects,
+    Structure,
+    Foreshadows,
+    Milestones,
+}
+
+impl DesignPart {
+    pub(super) fn label(self) -> &'static str {
+        match self {
+            DesignPart::Objects     => "世界对象",
+            DesignPart::Structure   => "章节结构",
+            DesignPart::Foreshadows => "伏笔",
+            DesignPart::Milestones  => "里程碑",
+        }
+    }
+}
+
+impl DesignDigest {
+    fn part(&self, part: DesignPart) -> u64 {
+        match part {
+            DesignPart::Objects     => self.objects,
+            DesignPart::Structure   => self.structure,
+            DesignPart::Foreshadows => self.foreshadows,
+            DesignPart::Milestones  => self.milestones,
+        }
+    }
+
+    fn part_mut(&mut self, part: DesignPart) -> &mut u64 {
+        match part {
+            DesignPart::Objects     => &mut self.objects,
+            DesignPart::Structure   => &mut self.structure,
+            DesignPart::Foreshadows => &mut self.foreshadows,
+            DesignPart::Milestones  => &mut self.milestones,
+        }
+    }
+}
+
+fn hash_of(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl TextToolApp {
+    /// Run once per change of `active_panel`, before the new panel is drawn.
+    pub(super) fn on_panel_transition(&mut self, from: Panel, to: Panel) {
+        self.leave_panel(from);
+        self.enter_panel(to);
+    }
+
+    fn leave_panel(&mut self, from: Panel) {
+        let entered = self.design_entry_digest.take();
+        let dirty = match entered.filter(|_| self.project_root.is_some()) {
+            Some(before) => {
+                let now = self.design_digest();
+                SaveSet {
+                    objects: now.objects != before.objects,
+                    structure: now.structure != before.structure,
+                    foreshadows: now.foreshadows != before.foreshadows,
+                    milestones: now.milestones != before.milestones,
+                    ..SaveSet::default()
+                }
+            }
+            None => SaveSet::default(),
+        };
+        let dirty = SaveSet {
+            panes: self.left_file.as_ref().is_some_and(|f| f.modified)
+                || self.right_file.as_ref().is_some_and(|f| f.modified),
+            ..dirty
+        };
+        let saves = panel_switch_saves(from, &self.settings.behavior.panel_switch_save, dirty);
+        if !saves.any() {
+            return;
+        }
+
+        let mut saved = Vec::new();
+        let mut results = Vec::new();
+        if saves.panes {
+            if self.left_file.as_ref().is_some_and(|f| f.modified) {
+                self.save_left();
+            }
+            if self.right_file.as_ref().is_some_and(|f| f.modified) {
+                self.save_right();
+            }
+            saved.push("编辑区文件");
+        }
+        if saves.objects {
+            results.push(self.sync_world_objects_to_json());
+            saved.push("世界对象");
+        }
+        if saves.structure {
+            results.push(self.sync_struct_to_json());
+            self.struct_json_snapshot = serde_json::to_string(&self.struct_roots).ok();
+            saved.push("章节结构");
+        }
+        if saves.foreshadows {
+            results.push(self.sync_foreshadows());
+            saved.push("伏笔");
+        }
+        if saves.milestones {
+            results.push(self.sync_milestones_to_json());
+            saved.push("里程碑");
+        }
+        match results.into_iter().find_map(Result::err) {
+            Some(e) => self.report(e),
+            None => self.status = format!("切换面板时已自动保存: {}", saved.join("、")),
+        }
+    }
+
+    fn enter_panel(&mut self, to: Panel) {
+        if to == Panel::Structure && self.project_root.is_some() {
+            // Silently try to load chapter structure; if file is missing, do nothing.
+            if let Ok((text, _)) = self.read_project_file("Design", "章节结构.json") {
+                if let Ok(nodes) = serde_json::from_str::<Vec<StructNode>>(&text) {
+                    self.struct_roots = nodes;
+                    self.selected_node_path.clear();
+                    self.note_design_saved(DesignPart::Structure);
+                }
+            }
+            // Reset snapshot so the freshly-loaded data is not immediately re-saved.
+            self.struct_json_snapshot = serde_json::to_string(&self.struct_roots).ok();
+        }
+        if self.settings.behavior.panel_switch_save.enabled
+            && matches!(to, Panel::Objects | Panel::Structure)
+        {
+            self.design_entry_digest = Some(self.design_digest());
+        }
+    }
+
+    fn design_part_hash(&self, part: DesignPart) -> u64 {
+        let json = |v: serde_json::Result<String>| hash_of(&v.unwrap_or_default());
+        match part {
+            DesignPart::Objects     => json(serde_json::to_string(&self.world_objects)),
+            DesignPart::Structure   => json(serde_json::to_string(&self.struct_roots)),
+            DesignPart::Foreshadows => json(serde_json::to_string(&self.foreshadows)),
+            DesignPart::Milestones  => json(serde_json::to_string(&self.milestones)),
+        }
+    }
+
+    pub(super) fn design_digest(&self) -> DesignDigest {
+        DesignDigest {
+            objects: self.design_part_hash(DesignPart::Objects),
+            structure: self.design_part_hash(DesignPart::Structure),
+            foreshadows: self.design_part_hash(DesignPart::Foreshadows),
+            milestones: self.design_part_hash(DesignPart::Milestones),
+        }
+    }
+
+    /// Whether `part` changed since it was last saved or loaded.
+    pub(super) fn design_part_unsaved(&self, part: DesignPart) -> bool {
+        self.design_saved.is_some_and(|saved| saved.part(part) != self.design_part_hash(part))
+    }
+
+    /// Take `part` as matching its file, after it was saved or loaded.
+    pub(super) fn note_design_saved(&mut self, part: DesignPart) {
+        self.design_unloaded.retain(|&p| p != part);
+        let hash = self.design_part_hash(part);
+        if let Some(saved) = &mut self.design_saved {
+            *saved.part_mut(part) = hash;
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use egui::{Color32, Context, RichText};
+use text_tool_core::paragraphs::{format_paragraphs, FormatOptions, ParagraphStyle};
+
+use super::TextToolApp;
+
+/// Changed paragraphs listed in the dialog's preview.
+const PREVIEW_COUNT: usize = 5;
+
+// ── 段落格式整理 ──────────────────────────────────────────────────────────────
+
+impl TextToolApp {
+    pub(super) fn open_paragraph_format_dialog(&mut self) {
+        if !self.left_file.as_ref().is_some_and(|f| f.is_markdown()) {
+            self.status = "请先在左侧打开一个 Markdown 文件".to_owned();
+            return;
+        }
+        self.paragraph_format = Some(FormatOptions {
+            style: ParagraphStyle::Indent,
+            collapse_blank_lines: true,
+            trim_trailing_whitespace: true,
+        });
+    }
+
+    /// Options, preview of the first changed paragraphs, and apply.
+    pub(super) fn draw_paragraph_format_dialog(&mut self, ctx: &Context) {
+        let (Some(opts), Some(f)) = (&mut self.paragraph_format, &self.left_file) else {
+            return;
+        };
+        let mut open = true;
+        let mut apply: Option<String> = None;
+        let mut cancel = false;
+
+        egui::Window::new("段落格式整理")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(460.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(RichText::new(f.path.file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default()).strong());
+                ui.horizontal(|ui| {
+                    ui.label("目标格式:");
+                    for style in [ParagraphStyle::BlankLine, ParagraphStyle::Indent, ParagraphStyle::Both] {
+                        ui.radio_value(&mut opts.style, style, style.label());
+                    }
+                });
+                ui.checkbox(&mut opts.collapse_blank_lines, "连续 3 个以上空行合并为 1 个");
+                ui.checkbox(&mut opts.trim_trailing_whitespace, "删除行尾空白");
+                ui.label(RichText::new("标题、列表、引用与代码块保持不变")
+                    .small().color(Color32::from_gray(140)));
+                ui.separator();
+
+                let result = format_paragraphs(&f.content, opts);
+                if result.text == f.content {
+                    ui.label(RichText::new("已符合所选格式，无需修改").color(Color32::GRAY));
+                } else {
+                    ui.label(format!("共 {} 段将被修改，预览前 {} 段:",
+                        result.changes.len(), PREVIEW_COUNT.min(result.changes.len())));
+                    egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+                        for change in result.changes.iter().take(PREVIEW_COUNT) {
+                            ui.label(RichText::new(format!("第 {} 行", change.line_no))
+                                .small().color(Color32::from_gray(140)));
+                            ui.label(RichText::new(format!("- {}", change.before))
+                                .monospace().color(Color32::from_rgb(200, 90, 90)));
+                            if change.blank_inserted {
+                                ui.label(RichText::new("+").monospace()
+                                    .color(Color32::from_rgb(90, 170, 90)));
+                            }
+                            ui.label(RichText::new(format!("+ {}", change.after))
+                                .monospace().color(Color32::from_rgb(90, 170, 90)));
+                            ui.add_space(4.0);
+                        }
+                    });
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(result.text != f.content, egui::Button::new("应用")).clicked() {
+                        apply = Some(result.text);
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if let Some(text) = apply {
+            if let Some(f) = &mut self.left_file {
+                // One snapshot, so a single Ctrl+Z restores the whole file.
+                self.left_undo_stack.push(std::mem::replace(&mut f.content, text), self.settings.behavior.undo_limit);
+                f.modified = true;
+                self.last_focused_left = true;
+                self.status = "已整理段落格式 (Ctrl+Z 可撤销)".to_owned();
+            }
+            open = false;
+        }
+        if !open || cancel {
+            self.paragraph_format = None;
+        }
+    }
+}
@@ -0,0 +1,461 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Instant, SystemTime};
+
+use text_tool_core::annotations::{find_annotations, Annotation};
+use text_tool_core::duplicates::{find_duplicate_paragraphs, DuplicateGroup};
+use text_tool_core::ignore::IgnoreRules;
+use text_tool_core::links::{check_links, BrokenLink};
+use text_tool_core::markers::{find_markers, Marker};
+use text_tool_core::outline::{parse_outline_entries, ContentFile};
+use text_tool_core::word_freq::{word_frequency, FreqReport};
+
+use super::{
+    count_words, normalize_title, same_title, FileNode, OutlineEntry, SearchResult, TreeFilter, WordFrequencySettings,
+};
+
+// ── Project index ─────────────────────────────────────────────────────────────
+
+/// Cached facts about one `.md` / `.json` file of the project.
+#[derive(Debug, Clone)]
+pub struct IndexedFile {
+    pub modified: Option<SystemTime>,
+    pub len: u64,
+    /// Hash of the file content, used to tell real edits from mere touches.
+    pub hash: u64,
+    /// Non-whitespace character count, same rule as the editor's 字数 label.
+    pub word_count: usize,
+    pub headings: Vec<OutlineEntry>,
+    pub text: String,
+    /// Lowercase copy of `text` for case-insensitive search.
+    pub lower: String,
+}
+
+impl IndexedFile {
+    fn build(text: String, modified: Option<SystemTime>, len: u64) -> Self {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        Self {
+            modified,
+            len,
+            hash: hasher.finish(),
+            word_count: count_words(&text),
+            headings: parse_outline_entries(&text),
+            lower: text.to_lowercase(),
+            text,
+        }
+    }
+}
+
+/// Message from [`ProjectIndex::spawn_link_check`].
+#[derive(Debug)]
+pub enum LinkCheckEvent {
+    /// Files checked so far, and the total.
+    Progress(usize, usize),
+    Done {
+        broken: Vec<BrokenLink>,
+        /// Every checked file with its headings.
+        targets: Vec<(PathBuf, Vec<OutlineEntry>)>,
+    },
+}
+
+/// Message from [`ProjectIndex::spawn_duplicate_check`].
+#[derive(Debug)]
+pub enum DuplicateEvent {
+    /// Files scanned so far, and the total.
+    Progress(usize, usize),
+    Done(Vec<DuplicateGroup>),
+}
+
+/// Folder listings for the lazily loaded file tree.
+#[derive(Default)]
+struct DirListings {
+    /// Folders being listed, with the time the listing started.
+    started: BTreeMap<PathBuf, Instant>,
+    /// Finished listings: folder, entries and the folder's modification time.
+    done: Vec<(PathBuf, Vec<FileNode>, Option<SystemTime>)>,
+}
+
+#[derive(Default)]
+struct IndexState {
+    root: Option<PathBuf>,
+    files: BTreeMap<PathBuf, IndexedFile>,
+    /// Bumped every time the cached data changes.
+    generation: u64,
+}
+
+/// Shared, thread-safe cache of per-file data for project-wide features
+/// (search, word counts, …).  Cloning yields another handle to the same index.
+///
+/// Scans run on a background thread; the UI compares [`ProjectIndex::generation`]
+/// with the generation its own data was built from to detect staleness.
+#[derive(Clone, Default)]
+pub struct ProjectIndex {
+    state: Arc<RwLock<IndexState>>,
+    /// Serialises scans so two threads never race on the same root.
+    scan_lock: Arc<Mutex<()>>,
+    pending: Arc<AtomicUsize>,
+    listings: Arc<Mutex<DirListings>>,
+}
+
+impl ProjectIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current generation; `0` until the first scan has finished.
+    pub fn generation(&self) -> u64 {
+        self.read().generation
+    }
+
+    /// `true` while a background scan is queued or running.
+    pub fn is_scanning(&self) -> bool {
+        self.pending.load(Ordering::SeqCst) > 0
+    }
+
+    /// `true` once at least one scan of `root` has completed.
+    pub fn is_indexed(&self, root: &Path) -> bool {
+        let st = self.read();
+        st.generation > 0 && st.root.as_deref() == Some(root)
+    }
+
+    /// Re-index `root` on a background thread.
+    pub fn spawn_rescan(&self, root: PathBuf) {
+        let index = self.clone();
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        std::thread::spawn(move || {
+            index.rescan(&root);
+            index.pending.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+
+    /// Walk `root` and update the cache, re-reading only files whose size or
+    /// modification time changed.  Files the project's ignore rules match are
+    /// not indexed.  Switching to a different root drops the old entries.
+    /// Returns the number of files whose cached content changed.
+    pub fn rescan(&self, root: &Path) -> usize {
+        let _guard = self.scan_lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut found = Vec::new();
+        collect_files(root, &IgnoreRules::load(root), &mut found);
+
+        let stale: Vec<(PathBuf, Option<SystemTime>, u64)> = {
+            let st = self.read();
+            let same_root = st.root.as_deref() == Some(root);
+            found.iter()
+                .filter(|(path, modified, len)| {
+                    !same_root || st.files.get(path)
+                        .is_none_or(|f| f.modified != *modified || f.len != *len)
+                })
+                .cloned()
+                .collect()
+        };
+
+        // Read outside the lock so the UI keeps serving the previous data.
+        let updated: Vec<(PathBuf, IndexedFile)> = stale.into_iter()
+            .filter_map(|(path, modified, len)| {
+                let text = std::fs::read_to_string(&path).ok()?;
+                Some((path, IndexedFile::build(text, modified, len)))
+            })
+            .collect();
+
+        let mut st = self.state.write().unwrap_or_else(|e| e.into_inner());
+        let mut changed = st.generation == 0;
+        if st.root.as_deref() != Some(root) {
+            st.files.clear();
+            st.root = Some(root.to_owned());
+            changed = true;
+        }
+
+        let live: BTreeSet<&PathBuf> = found.iter().map(|(p, _, _)| p).collect();
+        let before = st.files.len();
+        st.files.retain(|p, _| live.contains(p));
+        changed |= st.files.len() != before;
+
+        let mut reindexed = 0;
+        for (path, entry) in updated {
+            match st.files.get_mut(&path) {
+                Some(old) if old.hash == entry.hash => {
+                    old.modified = entry.modified;
+                    old.len = entry.len;
+                }
+                _ => {
+                    st.files.insert(path, entry);
+                    reindexed += 1;
+                }
+            }
+        }
+        if changed || reindexed > 0 {
+            st.generation += 1;
+        }
+        reindexed
+    }
+
+    /// List the entries of `dir` on a background thread; pick the result up
+    /// with [`ProjectIndex::take_dir_listings`].
+    pub fn spawn_list_dir(&self, dir: PathBuf, filter: TreeFilter) {
+        {
+            let mut l = self.lock_listings();
+            if l.started.contains_key(&dir) {
+                return;
+            }
+            l.started.insert(dir.clone(), Instant::now());
+        }
+        let listings = self.listings.clone();
+        std::thread::spawn(move || {
+            let mtime = std::fs::metadata(&dir).and_then(|m| m.modified()).ok();
+            let children = FileNode::list_dir(&dir, &filter).unwrap_or_default();
+            let mut l = listings.lock().unwrap_or_else(|e| e.into_inner());
+            l.started.remove(&dir);
+            l.done.push((dir, children, mtime));
+        });
+    }
+
+    /// When the listing of `dir` started, if it is still running.
+    pub fn listing_since(&self, dir: &Path) -> Option<Instant> {
+        self.lock_listings().started.get(dir).copied()
+    }
+
+    /// `true` while folder listings are running or waiting to be taken.
+    pub fn is_listing(&self) -> bool {
+        let l = self.lock_listings();
+        !l.started.is_empty() || !l.done.is_empty()
+    }
+
+    /// Finished folder listings since the last call.
+    pub fn take_dir_listings(&self) -> Vec<(PathBuf, Vec<FileNode>, Option<SystemTime>)> {
+        std::mem::take(&mut self.lock_listings().done)
+    }
+
+    fn lock_listings(&self) -> std::sync::MutexGuard<'_, DirListings> {
+        self.listings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Case-insensitive line search over every indexed file.
+    pub fn search(&self, query: &str) -> Vec<SearchResult> {
+        let query = query.to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let st = self.read();
+        let mut results = Vec::new();
+        for (path, file) in &st.files {
+            for ((line_no, line), lower) in file.text.lines().enumerate().zip(file.lower.lines()) {
+                if lower.contains(&query) {
+                    results.push(SearchResult {
+                        file_path: path.clone(),
+                        line_no: line_no + 1,
+                        line: line.to_owned(),
+                    });
+                }
+            }
+        }
+        results
+    }
+
+    pub fn word_count(&self, path: &Path) -> Option<usize> {
+        self.read().files.get(path).map(|f| f.word_count)
+    }
+
+    /// Word counts of the `.md` files under `dir`, keyed by lowercase file stem
+    /// (first match in path order wins, as in the chapter tree).
+    pub fn word_counts_by_stem(&self, dir: &Path) -> BTreeMap<String, usize> {
+        let st = self.read();
+        let mut out = BTreeMap::new();
+        for (path, file) in &st.files {
+            if path.starts_with(dir) && path.extension().and_then(|e| e.to_str()) == Some("md") {
+                if let Some(stem) = path.file_stem() {
+                    out.entry(stem.to_string_lossy().to_lowercase()).or_insert(file.word_count);
+                }
+            }
+        }
+        out
+    }
+
+    /// The `.md` files under `content_dir` as a [`ContentFile`] snapshot, as
+    /// [`list_content_files`](text_tool_core::outline::list_content_files)
+    /// would read them from disk.
+    pub fn content_files(&self, content_dir: &Path) -> Vec<ContentFile> {
+        let st = self.read();
+        st.files.iter()
+            .filter(|(path, _)| path.extension().and_then(|e| e.to_str()) == Some("md"))
+            .filter_map(|(path, file)| {
+                let rel = path.strip_prefix(content_dir).ok()?;
+                Some(ContentFile {
+                    rel: rel.to_string_lossy().replace('\\', "/"),
+                    heading: file.headings.iter().find(|h| h.level == 1).map(|h| h.title.clone()),
+                    words: file.word_count,
+                })
+            })
+            .collect()
+    }
+
+    /// The first `max_lines` lines of `path` and whether the file goes on.
+    pub fn text_head(&self, path: &Path, max_lines: usize) -> Option<(String, bool)> {
+        let st = self.read();
+        let mut lines = st.files.get(path)?.text.lines();
+        let head: Vec<&str> = lines.by_ref().take(max_lines).collect();
+        Some((head.join("\n"), lines.next().is_some()))
+    }
+
+    /// First `.md` file under `content_dir` (in path order) named after
+    /// `title`: its stem or its first `#` heading is the same title.
+    pub fn md_file_titled(&self, content_dir: &Path, title: &str) -> Option<PathBuf> {
+        let key = normalize_title(title).to_lowercase();
+        let st = self.read();
+        st.files.iter()
+            .filter(|(path, _)| path.starts_with(content_dir)
+                && path.extension().and_then(|e| e.to_str()) == Some("md"))
+            .find(|(path, file)| {
+                path.file_stem().is_some_and(|s| normalize_title(&s.to_string_lossy()).to_lowercase() == key)
+                    || file.headings.iter().find(|h| h.level == 1).is_some_and(|h| same_title(&h.title, title))
+            })
+            .map(|(path, _)| path.clone())
+    }
+
+    /// Path and text of every indexed `.md` file, in path order.
+    pub fn md_texts(&self) -> Vec<(PathBuf, String)> {
+        let st = self.read();
+        st.files.iter()
+            .filter(|(path, _)| path.extension().and_then(|e| e.to_str()) == Some("md"))
+            .map(|(path, file)| (path.clone(), file.text.clone()))
+            .collect()
+    }
+
+    pub fn headings(&self, path: &Path) -> Option<Vec<OutlineEntry>> {
+        self.read().files.get(path).map(|f| f.headings.clone())
+    }
+
+    /// 待处理标记 of every indexed `.md` file that has any, in path order.
+    pub fn markers(&self, tokens: &[String]) -> Vec<(PathBuf, Vec<Marker>)> {
+        let st = self.read();
+        st.files.iter()
+            .filter(|(path, _)| path.extension().and_then(|e| e.to_str()) == Some("md"))
+            .map(|(path, file)| (path.clone(), find_markers(&file.text, tokens)))
+            .filter(|(_, found)| !found.is_empty())
+            .collect()
+    }
+
+    /// 注释 of every indexed `.md` file that has any, in path order.
+    pub fn annotations(&self) -> Vec<(PathBuf, Vec<Annotation>)> {
+        let st = self.read();
+        st.files.iter()
+            .filter(|(path, _)| path.extension().and_then(|e| e.to_str()) == Some("md"))
+            .map(|(path, file)| (path.clone(), find_annotations(&file.text)))
+            .filter(|(_, found)| !found.is_empty())
+            .collect()
+    }
+
+    /// 词频分析 of the `.md` files under `dir` accepted by `include`, on a
+    /// background thread after an incremental rescan of `root`.  Sends the
+    /// files in the report's document order along with the report.
+    pub fn spawn_word_frequency(
+        &self,
+        root: PathBuf,
+        dir: PathBuf,
+        include: impl Fn(&Path) -> bool + Send + 'static,
+        settings: WordFrequencySettings,
+    ) -> std::sync::mpsc::Receiver<(Vec<PathBuf>, FreqReport)> {
+        let index = self.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            index.rescan(&root);
+            let st = index.read();
+            let (paths, texts): (Vec<PathBuf>, Vec<&str>) = st.files.iter()
+                .filter(|(path, _)| path.starts_with(&dir)
+                    && path.extension().and_then(|e| e.to_str()) == Some("md")
+                    && include(path))
+                .map(|(path, file)| (path.clone(), file.text.as_str()))
+                .unzip();
+            let report = word_frequency(&texts, &settings);
+            let _ = tx.send((paths, report));
+        });
+        rx
+    }
+
+    /// 检查文档内链接 over the `.md` files under `dir`, on a background
+    /// thread after an incremental rescan of `root`.  Sends a
+    /// [`LinkCheckEvent::Progress`] per file, then the result with every
+    /// checked file's headings (the targets 修正为… offers).
+    pub fn spawn_link_check(&self, root: PathBuf, dir: PathBuf) -> std::sync::mpsc::Receiver<LinkCheckEvent> {
+        let index = self.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            index.rescan(&root);
+            let st = index.read();
+            let files: Vec<(&PathBuf, &IndexedFile)> = st.files.iter()
+                .filter(|(path, _)| path.starts_with(&dir) && path.extension().and_then(|e| e.to_str()) == Some("md"))
+                .collect();
+            let docs: Vec<(&Path, &str)> = files.iter().map(|(p, f)| (p.as_path(), f.text.as_str())).collect();
+            let broken = check_links(&docs, |done, total| {
+                let _ = tx.send(LinkCheckEvent::Progress(done, total));
+            });
+            let targets = files.iter().map(|(p, f)| ((*p).clone(), f.headings.clone())).collect();
+            let _ = tx.send(LinkCheckEvent::Done { broken, targets });
+        });
+        rx
+    }
+
+    /// 查找重复段落 over the `.md` files under `dir`, on a background thread
+    /// after an incremental rescan of `root`.  Sends a
+    /// [`DuplicateEvent::Progress`] per file, then the groups found.
+    pub fn spawn_duplicate_check(
+        &self,
+        root: PathBuf,
+        dir: PathBuf,
+        min_chars: usize,
+    ) -> std::sync::mpsc::Receiver<DuplicateEvent> {
+        let index = self.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            index.rescan(&root);
+            let st = index.read();
+            let docs: Vec<(&Path, &str)> = st.files.iter()
+                .filter(|(path, _)| path.starts_with(&dir) && path.extension().and_then(|e| e.to_str()) == Some("md"))
+                .map(|(path, file)| (path.as_path(), file.text.as_str()))
+                .collect();
+            let groups = find_duplicate_paragraphs(&docs, min_chars, |done, total| {
+                let _ = tx.send(DuplicateEvent::Progress(done, total));
+            });
+            let _ = tx.send(DuplicateEvent::Done(groups));
+        });
+        rx
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, IndexState> {
+        self.state.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Recursively list `.md` / `.json` files under `dir` with their metadata.
+/// Hidden folders such as `.texttool/` (recovery copies) and whatever
+/// `ignore` matches are skipped.
+fn collect_files(dir: &Path, ignore: &IgnoreRules, out: &mut Vec<(PathBuf, Option<SystemTime>, u64)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_dir = path.is_dir();
+        if ignore.is_ignored_path(&path, is_dir) {
+            continue;
+        }
+        if is_dir {
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                collect_files(&path, ignore, out);
+            }
+        } else {
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            if ext == "md" || ext == "json" {
+                let meta = entry.metadata().ok();
+                let modified = meta.as_ref().and_then(|m| m.modified().ok());
+                let len = meta.map(|m| m.len()).unwrap_or(0);
+                out.push((path, modified, len));
+            }
+        }
+    }
+}
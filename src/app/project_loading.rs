@@ -0,0 +1,422 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::ignore::IgnoreRules;
+
+use super::design_autosave::read_project_data;
+use super::sync::{read_foreshadows, read_milestones, read_struct_roots, read_world_objects};
+use super::{build_file_tree, AppError, DesignPart, FileNode, Foreshadow, Milestone, ProjectData, StructNode, TextToolApp,
+            TreeFilter, WorldObject};
+
+// ── 打开项目: the file tree and design data load off the UI thread ───────────
+//
+// Listing a project on a network drive can take seconds.  `open_project`
+// does the quick bookkeeping and hands the rest to a worker; the window
+// shows a spinner until the result comes back over the app's channel.
+// Every load has a generation, and only the latest one's result is
+// installed, so opening another project before the first finishes drops
+// the first.
+
+/// A project load in progress.
+pub struct ProjectLoading {
+    pub(super) generation: u64,
+    pub(super) root: PathBuf,
+    since: Instant,
+}
+
+/// What a worker hands back.
+pub struct ProjectLoaded {
+    generation: u64,
+    root: PathBuf,
+    ignore_rules: IgnoreRules,
+    tree: Vec<FileNode>,
+    /// `Design/世界对象.json`, `Design/章节结构.json` and `Design/伏笔.json`
+    /// are read on every opening: objects, structure and foreshadows are
+    /// never left behind by reopening a project, nor carried into another.
+    objects: Result<(Vec<WorldObject>, String), AppError>,
+    roots: Result<(Vec<StructNode>, String), AppError>,
+    foreshadows: Result<(Vec<Foreshadow>, String), AppError>,
+    /// `project.json`, standing in for whichever of the three files above
+    /// is missing; `None` when it is missing or unreadable too.
+    project_data: Option<ProjectData>,
+    /// Read only with 自动加载 on.
+    milestones: Option<Result<(Vec<Milestone>, String), AppError>>,
+}
+
+/// The slow part of opening a project, runnable on any thread.
+pub(super) struct ProjectLoadJob {
+    generation: u64,
+    root: PathBuf,
+    hide_json: bool,
+    show_ignored: bool,
+    read_design: bool,
+}
+
+impl ProjectLoadJob {
+    pub(super) fn run(self) -> ProjectLoaded {
+        let ignore_rules = IgnoreRules::load(&self.root);
+        let filter = TreeFilter { hide_json: self.hide_json, ignore: ignore_rules.clone(), show_ignored: self.show_ignored };
+        ProjectLoaded {
+            generation: self.generation,
+            tree: build_file_tree(&self.root, &filter),
+            objects: read_world_objects(&self.root),
+            roots: read_struct_roots(&self.root),
+            foreshadows: read_foreshadows(&self.root),
+            project_data: read_project_data(&self.root).ok().map(|(data, _)| data),
+            milestones: self.read_design.then(|| read_milestones(&self.root)),
+            root: self.root,
+            ignore_rules,
+        }
+    }
+}
+
+/// Whether the result of load `generation` for `root` belongs to the load
+/// still in progress.
+pub(super) fn is_current_load(loading: Option<&ProjectLoading>, generation: u64, root: &Path) -> bool {
+    loading.is_some_and(|l| l.generation == generation && l.root == root)
+}
+
+impl TextToolApp {
+    /// Start the load of `root`, superseding any load in progress.
+    pub(super) fn start_project_load(&mut self, root: PathBuf) -> ProjectLoadJob {
+        self.project_load_generation += 1;
+        let generation = self.project_load_generation;
+        self.project_loading = Some(ProjectLoading { generation, root: root.clone(), since: Instant::now() });
+        ProjectLoadJob {
+            generation,
+            root,
+            hide_json: self.settings.markdown.hide_json,
+            show_ignored: self.settings.markdown.show_ignored,
+            read_design: self.settings.auto_load,
+        }
+    }
+
+    /// Put a finished load in place; results of superseded loads are
+    /// dropped.  Returns whether `loaded` was installed.
+    pub(super) fn install_project_load(&mut self, loaded: ProjectLoaded) -> bool {
+        if !is_current_load(self.project_loading.as_ref(), loaded.generation, &loaded.root) {
+            return false;
+        }
+        self.project_loading = None;
+        self.ignore_rules = loaded.ignore_rules;
+        self.file_tree = loaded.tree;
+        self.reindex_project();
+        self.status = format!("已打开项目: {}", loaded.root.display());
+        let missing: Vec<DesignPart> = [
+            (DesignPart::Objects, loaded.objects.as_ref().err()),
+            (DesignPart::Structure, loaded.roots.as_ref().err()),
+            (DesignPart::Foreshadows, loaded.foreshadows.as_ref().err()),
+        ].into_iter()
+            .filter(|(_, e)| e.is_some_and(AppError::is_not_found))
+            .map(|(part, _)| part)
+            .collect();
+        self.install_world_objects(loaded.objects);
+        self.install_struct_roots(loaded.roots);
+        self.install_foreshadows(loaded.foreshadows);
+        if let Some(milestones) = loaded.milestones {
+            self.install_milestones(milestones);
+        }
+        self.design_saved = Some(self.design_digest());
+        if let Some(data) = loaded.project_data {
+            self.install_project_data(data, &missing);
+        }
+        true
+    }
+
+    /// Install whatever the workers have finished.
+    pub(super) fn poll_project_load(&mut self) {
+        while let Ok(loaded) = self.project_load_rx.try_recv() {
+            self.install_project_load(loaded);
+        }
+    }
+
+    /// While a project loads, a spinner with its path stands in for the
+    /// whole window.  Returns whether it did.
+    pub(super) fn draw_project_loading(&mut self, ctx: &Context) -> bool {
+        let Some(loading) = &self.project_loading else { return false };
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                ui.add(egui::Spinner::new().size(32.0));
+                ui.add_space(8.0);
+                ui.label(RichText::new("正在打开项目…").size(18.0));
+                ui.label(RichText::new(loading.root.display().to_string()).color(Color32::from_gray(160)));
+                let secs = loading.since.elapsed().as_secs();
+                if secs >= 3 {
+                    ui.label(RichText::new(format!("已用时 {secs} 秒，网络驱动器上的大项目可能需要更久"))
+                        .small().color(Color32::from_gray(140)));
+                }
+            });
+        });
+        ctx.request_repaint_after(Duration::from_millis(100));
+        true
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{ChapterTag, LinkTarget, NodeLink, ObjectKind, ObjectLink, RelationKind, StructKind, PROJECT_DATA_FILE};
+
+    fn temp_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Content")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_only_latest_project_load_installs() {
+        let first = temp_project("qingmo_test_load_first");
+        let second = temp_project("qingmo_test_load_second");
+        std::fs::write(second.join("Content").join("第一章.md"), "# 第一章\n").unwrap();
+
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        let job_first = app.start_project_load(first.clone());
+        let job_second = app.start_project_load(second.clone());
+        assert!(!is_current_load(app.project_loading.as_ref(), job_first.generation, &first));
+        assert!(is_current_load(app.project_loading.as_ref(), job_second.generation, &second));
+
+        // The second load finishes first; the first one's late result is dropped.
+        let (late, done) = (job_first.run(), job_second.run());
+        assert!(app.install_project_load(done));
+        assert!(app.project_loading.is_none());
+        assert!(!app.install_project_load(late));
+        let content = app.file_tree.iter().find(|n| n.path == second.join("Content")).unwrap();
+        assert!(content.children.iter().any(|c| c.name == "第一章.md"));
+
+        // Nothing is installed once no load is in progress.
+        let again = app.start_project_load(second.clone());
+        app.project_loading = None;
+        assert!(!app.install_project_load(again.run()));
+
+        let _ = std::fs::remove_dir_all(&first);
+        let _ = std::fs::remove_dir_all(&second);
+    }
+
+    #[test]
+    fn test_reopen_loads_world_objects() {
+        let dir = temp_project("qingmo_test_reopen_objects");
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.open_project_now(dir.clone());
+        let mut hero = WorldObject::new("林远", ObjectKind::Character);
+        hero.description = "剑客，沉默寡言".into();
+        hero.links = vec![
+            ObjectLink { target: LinkTarget::Node("第一卷/第一章".into()), kind: RelationKind::AppearsIn, note: "初登场".into() },
+            ObjectLink { target: LinkTarget::Object("青城".into()), kind: RelationKind::Other, note: String::new() },
+        ];
+        app.world_objects = vec![hero, WorldObject::new("青城", ObjectKind::Location)];
+        app.sync_world_objects_to_json().unwrap();
+
+        // 自动加载 is off: the objects still come back on reopening.
+        let mut reopened = TextToolApp::blank();
+        reopened.secondary = true;
+        assert!(!reopened.settings.auto_load);
+        reopened.open_project_now(dir.clone());
+        let saved = serde_json::to_value(&app.world_objects).unwrap();
+        assert_eq!(serde_json::to_value(&reopened.world_objects).unwrap(), saved);
+        assert_eq!(reopened.world_objects[0].links[0].target, LinkTarget::Node("第一卷/第一章".into()));
+
+        // A malformed file keeps the current list and says why.
+        std::fs::write(dir.join("Design").join("世界对象.json"), "[{\"name\": ").unwrap();
+        reopened.open_project_now(dir.clone());
+        assert_eq!(serde_json::to_value(&reopened.world_objects).unwrap(), saved);
+        assert!(reopened.status.contains("世界对象"), "{}", reopened.status);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_reopen_loads_struct_roots() {
+        let dir = temp_project("qingmo_test_reopen_struct");
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.open_project_now(dir.clone());
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        let mut ch = StructNode::new("第一章", StructKind::Chapter);
+        ch.linked_objects = vec!["林远".into()];
+        ch.node_links = vec![NodeLink { target_title: "第二章".into(), kind: RelationKind::Foreshadows, note: "玉佩".into() }];
+        ch.tag = ChapterTag::Climax;
+        ch.done = true;
+        vol.children = vec![ch, StructNode::new("第二章", StructKind::Chapter)];
+        app.struct_roots = vec![vol];
+        app.sync_struct_to_json().unwrap();
+
+        let mut reopened = TextToolApp::blank();
+        reopened.secondary = true;
+        reopened.selected_node_path = vec![4, 2];
+        reopened.open_project_now(dir.clone());
+        let saved = serde_json::to_value(&app.struct_roots).unwrap();
+        assert_eq!(serde_json::to_value(&reopened.struct_roots).unwrap(), saved);
+        assert!(reopened.selected_node_path.is_empty());
+
+        // A corrupt file is reported with the parse error; the path is reset.
+        let file = dir.join("Design").join("章节结构.json");
+        std::fs::write(&file, r#"[{"title": "第一卷", "kind": 3}]"#).unwrap();
+        reopened.selected_node_path = vec![0, 1];
+        reopened.open_project_now(dir.clone());
+        assert!(reopened.status.starts_with("章节结构格式错误"), "{}", reopened.status);
+        assert!(reopened.selected_node_path.is_empty());
+
+        // An empty or missing file is an empty structure.
+        std::fs::write(&file, "\n").unwrap();
+        reopened.open_project_now(dir.clone());
+        assert!(reopened.struct_roots.is_empty());
+        // With 自动保存设计数据 the tree would be written back before reopening.
+        reopened.settings.behavior.design_autosave = false;
+        reopened.struct_roots = app.struct_roots.clone();
+        std::fs::remove_file(&file).unwrap();
+        reopened.open_project_now(dir.clone());
+        assert!(reopened.struct_roots.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_reopen_loads_foreshadows() {
+        let dir = temp_project("qingmo_test_reopen_foreshadows");
+        // An older project has only the Markdown; it migrates on opening.
+        std::fs::write(
+            dir.join("Content").join("伏笔.md"),
+            "# 伏笔列表\n\n## 玉佩 ✅ 已解决\n\n幼时所得\n\n**关联章节**: 第一章、第三章\n\n## 断剑 ⏳ 未解决\n\n",
+        ).unwrap();
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.open_project_now(dir.clone());
+        let names: Vec<&str> = app.foreshadows.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["玉佩", "断剑"]);
+        assert!(app.foreshadows[0].resolved);
+        assert_eq!(app.foreshadows[0].description, "幼时所得");
+        assert_eq!(app.foreshadows[0].related_chapters, ["第一章", "第三章"]);
+
+        // Once saved, the JSON is what loads, whatever the Markdown says.
+        app.foreshadows[1].resolve_chapters = vec!["第九章".into()];
+        app.sync_foreshadows().unwrap();
+        std::fs::write(dir.join("Content").join("伏笔.md"), "# 伏笔列表\n\n## 别的 ⏳ 未解决\n").unwrap();
+        let mut reopened = TextToolApp::blank();
+        reopened.secondary = true;
+        reopened.open_project_now(dir.clone());
+        let saved = serde_json::to_value(&app.foreshadows).unwrap();
+        assert_eq!(serde_json::to_value(&reopened.foreshadows).unwrap(), saved);
+
+        // A malformed file keeps the list and says why; no file at all is no foreshadows.
+        std::fs::write(dir.join("Design").join("伏笔.json"), "[{").unwrap();
+        reopened.open_project_now(dir.clone());
+        assert_eq!(serde_json::to_value(&reopened.foreshadows).unwrap(), saved);
+        assert!(reopened.status.starts_with("伏笔格式错误"), "{}", reopened.status);
+        std::fs::remove_file(dir.join("Design").join("伏笔.json")).unwrap();
+        std::fs::remove_file(dir.join("Content").join("伏笔.md")).unwrap();
+        reopened.open_project_now(dir.clone());
+        assert!(reopened.foreshadows.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_switch_project_leaves_design_behind() {
+        let first = temp_project("qingmo_test_switch_first");
+        let second = temp_project("qingmo_test_switch_second");
+        for auto_load in [false, true] {
+            let mut app = TextToolApp::blank();
+            app.secondary = true;
+            app.settings.auto_load = auto_load;
+            app.open_project_now(first.clone());
+            app.world_objects.push(WorldObject::new("林远", ObjectKind::Character));
+            app.struct_roots.push(StructNode::new("第一卷", StructKind::Volume));
+            app.foreshadows.push(Foreshadow::new("玉佩"));
+
+            // The second project has none of the files: it starts empty.
+            app.open_project_now(second.clone());
+            assert!(app.world_objects.is_empty());
+            assert!(app.struct_roots.is_empty());
+            assert!(app.foreshadows.is_empty());
+
+            // Its own first object is all its file holds.
+            app.world_objects.push(WorldObject::new("青城", ObjectKind::Location));
+            app.flush_design_autosave();
+            let (objects, _) = read_world_objects(&second).unwrap();
+            let names: Vec<&str> = objects.iter().map(|o| o.name.as_str()).collect();
+            assert_eq!(names, ["青城"]);
+            assert_eq!(read_world_objects(&first).unwrap().0[0].name, "林远");
+            let _ = std::fs::remove_dir_all(second.join("Design"));
+            let _ = std::fs::remove_file(second.join(PROJECT_DATA_FILE));
+        }
+
+        let _ = std::fs::remove_dir_all(&first);
+        let _ = std::fs::remove_dir_all(&second);
+    }
+
+    #[test]
+    fn test_corrupt_structure_of_other_project_not_overwritten() {
+        let first = temp_project("qingmo_test_corrupt_first");
+        let second = temp_project("qingmo_test_corrupt_second");
+        std::fs::create_dir_all(second.join("Design")).unwrap();
+        let file = second.join("Design").join("章节结构.json");
+        let corrupt = r#"[{"title": "第一卷", "kind": "#;
+        std::fs::write(&file, corrupt).unwrap();
+
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.open_project_now(first.clone());
+        app.struct_roots.push(StructNode::new("别的书", StructKind::Volume));
+
+        // The first project's tree does not come along; the file is reported.
+        app.open_project_now(second.clone());
+        assert!(app.struct_roots.is_empty());
+        assert!(app.status.starts_with("章节结构格式错误"), "{}", app.status);
+
+        // Edits are not saved over the file without asking.
+        app.struct_roots.push(StructNode::new("第一卷", StructKind::Volume));
+        app.flush_design_autosave();
+        app.autosave_struct();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), corrupt);
+        assert!(app.overwrite_confirm.first().is_some_and(|c| c.unloaded));
+
+        // Once repaired and loaded, saving goes ahead again.
+        std::fs::write(&file, "[]").unwrap();
+        app.load_struct_from_json().unwrap();
+        app.struct_roots.push(StructNode::new("第二卷", StructKind::Volume));
+        app.flush_design_autosave();
+        assert_eq!(read_struct_roots(&second).unwrap().0[0].title, "第二卷");
+
+        let _ = std::fs::remove_dir_all(&first);
+        let _ = std::fs::remove_dir_all(&second);
+    }
+
+    #[test]
+    fn test_corrupt_foreshadows_not_overwritten() {
+        let dir = temp_project("qingmo_test_corrupt_foreshadows");
+        std::fs::create_dir_all(dir.join("Design")).unwrap();
+        let file = dir.join("Design").join("伏笔.json");
+        let corrupt = r#"[{"name": "玉佩", "resolved": "#;
+        std::fs::write(&file, corrupt).unwrap();
+        let md = dir.join("Content").join("伏笔.md");
+        std::fs::write(&md, "# 伏笔列表\n\n## 玉佩 ⏳ 未解决\n").unwrap();
+
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.open_project_now(dir.clone());
+        assert!(app.status.starts_with("伏笔格式错误"), "{}", app.status);
+
+        // Neither the JSON nor its Markdown is written over without asking.
+        app.foreshadows.push(Foreshadow::new("断剑"));
+        app.flush_design_autosave();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), corrupt);
+        assert!(std::fs::read_to_string(&md).unwrap().contains("玉佩"));
+        assert!(app.overwrite_confirm.first().is_some_and(|c| c.part == DesignPart::Foreshadows && c.unloaded));
+
+        // Once repaired and loaded, saving goes ahead again.
+        std::fs::write(&file, "[]").unwrap();
+        app.load_design_part(DesignPart::Foreshadows).unwrap();
+        app.foreshadows.push(Foreshadow::new("断剑"));
+        app.flush_design_autosave();
+        assert_eq!(read_foreshadows(&dir).unwrap().0[0].name, "断剑");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,179 @@
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::project_lock::{host_name, lock_status, next_lock, LockStatus, LOCK_FILE, LOCK_HEARTBEAT_SECS};
+
+use super::{format_relative_time, AppError, IoOp, unix_now, DesignLock, DesignPart, ReloadSource, TextToolApp};
+
+// ── 项目锁: one computer writes the design data ───────────────────────────────
+//
+// Opening a project takes `.texttool/lock`, and the heartbeat keeps it while
+// the project stays open.  When another computer holds a fresh lock the
+// panels stay editable, but nothing is written to `Design/` until that lock
+// goes stale or 强制接管 takes it over; a banner says so.  When the lock goes
+// away, the parts left alone here are reloaded with the other computer's
+// changes; for parts edited here meanwhile the user picks whose to keep
+// (see `design_reload.rs`).
+
+/// Every part of the design data, in menu order.
+const DESIGN_PARTS: [DesignPart; 4] =
+    [DesignPart::Objects, DesignPart::Structure, DesignPart::Foreshadows, DesignPart::Milestones];
+
+impl TextToolApp {
+    /// Take the open project's lock, refresh our own, or note who holds it.
+    pub(super) fn acquire_project_lock(&mut self, force: bool) {
+        let Some(root) = self.project_root.clone() else { return };
+        let path = root.join(LOCK_FILE);
+        let host = host_name();
+        let now = unix_now();
+        let status = lock_status(std::fs::read_to_string(&path).ok().as_deref(), &host, now);
+        let held_by = match &status {
+            LockStatus::Held(other) if !force => Some(other.clone()),
+            _ => None,
+        };
+        if let Some(lock) = next_lock(&status, &host, std::process::id(), now, force) {
+            let res = path.parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| text_tool_core::safe_write(&path, lock.to_text()));
+            if let Err(e) = res {
+                self.report(AppError::Io { op: IoOp::Write, path, source: e });
+            }
+        }
+        if let LockStatus::Stale(old) = &status {
+            self.status = format!("已接管 {} 的过期项目锁", old.host);
+        }
+        self.design_lock = Some(DesignLock { root, held_by, checked: Instant::now() });
+    }
+
+    /// Remove the lock file if this computer holds it.
+    pub(super) fn release_project_lock(&mut self) {
+        let Some(lock) = self.design_lock.take() else { return };
+        if lock.held_by.is_some() {
+            return;
+        }
+        let path = lock.root.join(LOCK_FILE);
+        let existing = std::fs::read_to_string(&path).ok();
+        if matches!(lock_status(existing.as_deref(), &host_name(), unix_now()), LockStatus::Ours(_)) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    /// Whether another computer holds the design data.
+    pub(super) fn design_read_only(&self) -> bool {
+        self.design_lock.as_ref().is_some_and(|l| l.held_by.is_some())
+    }
+
+    /// [`Self::design_read_only`] as an error, for explicit writes.
+    pub(super) fn check_design_writable(&self) -> Result<(), AppError> {
+        match self.design_lock.as_ref().and_then(|l| l.held_by.as_ref()) {
+            Some(other) => Err(AppError::DesignLocked { host: other.host.clone() }),
+            None => Ok(()),
+        }
+    }
+
+    /// Heartbeat: refresh our lock, notice a takeover, or resume once the
+    /// other computer's lock is gone or stale.
+    pub(super) fn tick_project_lock(&mut self, ctx: &Context) {
+        let Some(lock) = &self.design_lock else { return };
+        let interval = Duration::from_secs(LOCK_HEARTBEAT_SECS as u64);
+        let elapsed = lock.checked.elapsed();
+        if elapsed < interval {
+            ctx.request_repaint_after(interval - elapsed);
+            return;
+        }
+        let was_held = lock.held_by.clone();
+        self.acquire_project_lock(false);
+        let now_held = self.design_lock.as_ref().and_then(|l| l.held_by.clone());
+        match (was_held, now_held) {
+            (Some(old), None) => self.resume_after_lock(old.host),
+            (None, Some(other)) => {
+                self.status = format!("{} 接管了此项目，设计数据同步已暂停", other.host);
+            }
+            _ => {}
+        }
+    }
+
+    /// The other computer's edits are on disk.  Load them into the parts
+    /// not edited here, and ask about the rest.
+    fn resume_after_lock(&mut self, host: String) {
+        self.status = if self.reload_or_ask(ReloadSource::Computer(host.clone()), DESIGN_PARTS.to_vec()) {
+            format!("{host} 已不再占用项目，请选择保留哪边的修改")
+        } else {
+            format!("{host} 已不再占用项目，设计数据已重新载入，同步已恢复")
+        };
+    }
+
+    /// Banner above the panels while another computer holds the project.
+    pub(super) fn draw_project_lock_banner(&mut self, ctx: &Context) {
+        let Some(other) = self.design_lock.as_ref().and_then(|l| l.held_by.clone()) else { return };
+        let mut take_over = false;
+        egui::TopBottomPanel::top("project_lock_banner").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(RichText::new(format!(
+                    "🔒 此项目正在 {} 上打开（最近心跳: {}）。为避免互相覆盖，世界对象、章节结构等设计数据不会写入 Design/ 文件夹。",
+                    other.host, format_relative_time(other.heartbeat, unix_now()),
+                )).color(Color32::from_rgb(220, 160, 60)));
+                if ui.button("强制接管")
+                    .on_hover_text("改由本机写入设计数据；另一台电脑将在下次心跳时停止写入")
+                    .clicked()
+                {
+                    take_over = true;
+                }
+            });
+        });
+        if take_over {
+            self.acquire_project_lock(true);
+            self.status = format!("已从 {} 接管项目", other.host);
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use text_tool_core::project_lock::ProjectLock;
+
+    use super::*;
+    use super::super::{ObjectKind, StructKind, StructNode, WorldObject};
+    use super::super::sync::{read_struct_roots, read_world_objects};
+
+    #[test]
+    fn test_lock_release_keeps_local_edits() {
+        let dir = std::env::temp_dir().join(format!("qingmo_test_lock_release_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Content")).unwrap();
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.open_project_now(dir.clone());
+
+        // Another computer takes the project; both sides edit.
+        let lock = dir.join(LOCK_FILE);
+        std::fs::write(&lock, ProjectLock::new("别的电脑", 1, unix_now()).to_text()).unwrap();
+        app.acquire_project_lock(false);
+        assert!(app.design_read_only());
+        app.world_objects.push(WorldObject::new("林远", ObjectKind::Character));
+        let theirs = serde_json::to_string(&[StructNode::new("第一卷", StructKind::Volume)]).unwrap();
+        std::fs::write(dir.join("Design").join("章节结构.json"), theirs).unwrap();
+
+        // It lets go: its structure comes in, the objects edited here wait.
+        std::fs::remove_file(&lock).unwrap();
+        let ctx = Context::default();
+        app.design_lock.as_mut().unwrap().checked -= Duration::from_secs(LOCK_HEARTBEAT_SECS as u64);
+        app.tick_project_lock(&ctx);
+        assert!(!app.design_read_only());
+        assert_eq!(app.struct_roots[0].title, "第一卷");
+        assert_eq!(app.design_reload.as_ref().unwrap().parts, [DesignPart::Objects]);
+        assert_eq!(app.world_objects[0].name, "林远");
+
+        // Nothing is written before the answer; 保留本机修改 saves the edits.
+        app.flush_design_autosave();
+        assert!(read_world_objects(&dir).is_err());
+        app.answer_design_reload(false);
+        app.flush_design_autosave();
+        assert_eq!(read_world_objects(&dir).unwrap().0[0].name, "林远");
+        assert_eq!(read_struct_roots(&dir).unwrap().0[0].title, "第一卷");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::outline::{count_nodes, merge_struct_by_title};
+
+use super::{rfd_pick_folder, TextToolApp};
+
+// ── 保存到项目: data entered before a project was opened ──────────────────────
+
+/// What tried to write project files while no project was open.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum SaveTrigger {
+    /// A sync / export the user asked for.
+    Explicit,
+    /// The structure panel's silent autosave.
+    Autosave,
+}
+
+/// Whether a write without an open project should ask for a project folder:
+/// only when objects or structure would otherwise be lost, and for autosaves
+/// only until the user chose 稍后.
+pub(super) fn should_prompt_for_project(
+    has_project: bool,
+    has_data: bool,
+    trigger: SaveTrigger,
+    dismissed: bool,
+) -> bool {
+    !has_project && has_data && (trigger == SaveTrigger::Explicit || !dismissed)
+}
+
+/// Append the `items` whose name is not in `list` yet.  Returns how many
+/// were added.
+pub(super) fn append_new_by_name<T>(list: &mut Vec<T>, items: Vec<T>, name: impl Fn(&T) -> &str) -> usize {
+    let mut added = 0;
+    for item in items {
+        if !list.iter().any(|x| name(x) == name(&item)) {
+            list.push(item);
+            added += 1;
+        }
+    }
+    added
+}
+
+impl TextToolApp {
+    /// World objects, structure, foreshadows or milestones exist in memory.
+    fn has_unsaved_data(&self) -> bool {
+        !self.world_objects.is_empty()
+            || !self.struct_roots.is_empty()
+            || !self.foreshadows.is_empty()
+            || !self.milestones.is_empty()
+    }
+
+    /// A write found no open project: open the 保存到项目 prompt if there
+    /// is data to lose.
+    pub(super) fn request_project_for_save(&mut self, trigger: SaveTrigger) {
+        if should_prompt_for_project(
+            self.project_root.is_some(),
+            self.has_unsaved_data(),
+            trigger,
+            self.project_prompt_dismissed,
+        ) {
+            self.show_project_prompt = true;
+        }
+    }
+
+    /// Open `path` as the project and save the data entered so far into it.
+    /// Data already in the project wins on name clashes; the rest is appended.
+    pub(super) fn adopt_project(&mut self, path: PathBuf) {
+        let objects = std::mem::take(&mut self.world_objects);
+        let roots = std::mem::take(&mut self.struct_roots);
+        let foreshadows = std::mem::take(&mut self.foreshadows);
+        let milestones = std::mem::take(&mut self.milestones);
+
+        self.open_project_now(path.clone());
+        if !self.settings.auto_load {
+            self.load_all_from_files();
+        }
+        let obj_added = append_new_by_name(&mut self.world_objects, objects, |o| o.name.as_str());
+        let nodes_added = merge_struct_by_title(&mut self.struct_roots, roots);
+        append_new_by_name(&mut self.foreshadows, foreshadows, |f| f.name.as_str());
+        append_new_by_name(&mut self.milestones, milestones, |m| m.name.as_str());
+        self.clear_obj_selection();
+        self.selected_node_path.clear();
+
+        let results = [
+            self.sync_world_objects_to_json(),
+            self.sync_struct_to_json(),
+            self.sync_foreshadows(),
+            self.sync_milestones_to_json(),
+        ];
+        self.struct_json_snapshot = serde_json::to_string(&self.struct_roots).ok();
+        self.refresh_tree();
+        match results.into_iter().find_map(Result::err) {
+            Some(e) => self.report(e),
+            None => self.status = format!(
+                "已保存到项目 {}：新增 {obj_added} 个对象、{nodes_added} 个章节节点",
+                path.display(),
+            ),
+        }
+    }
+
+    pub(super) fn draw_project_prompt(&mut self, ctx: &Context) {
+        if !self.show_project_prompt {
+            return;
+        }
+        let mut open = true;
+        let mut pick = false;
+        let mut later = false;
+        egui::Window::new("保存到项目")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("尚未打开项目，已录入的内容只在内存中，退出后会丢失：");
+                ui.label(RichText::new(format!(
+                    "{} 个世界对象 · {} 个章节节点 · {} 条伏笔 · {} 个里程碑",
+                    self.world_objects.len(),
+                    count_nodes(&self.struct_roots),
+                    self.foreshadows.len(),
+                    self.milestones.len(),
+                )).strong());
+                ui.label(RichText::new("选择或新建一个文件夹作为项目：将创建 Content/、Design/、废稿/ 并保存以上内容，已有同名数据保留不变。")
+                    .small().color(Color32::from_gray(140)));
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("📂 选择项目文件夹…").clicked() {
+                        pick = true;
+                    }
+                    if ui.button("稍后").on_hover_text("本次不再自动提示；点击同步按钮时仍会询问").clicked() {
+                        later = true;
+                    }
+                });
+            });
+
+        if pick {
+            if let Some(path) = rfd_pick_folder() {
+                self.show_project_prompt = false;
+                self.adopt_project(path);
+            }
+        } else if !open || later {
+            self.show_project_prompt = false;
+            self.project_prompt_dismissed = true;
+        }
+    }
+}
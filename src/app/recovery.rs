@@ -0,0 +1,235 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::recovery::{
+    flush_buffers, pending_recoveries, relative_key, RecoveryManifest, RECOVERY_DIR,
+};
+
+use super::{format_relative_time, unix_now, OpenFile, Panel, RecoveryCandidate, TextToolApp};
+
+/// Modified buffers are copied at least this often.
+const RECOVERY_INTERVAL: Duration = Duration::from_secs(10);
+/// A buffer that grew or shrank by this many bytes since its last copy is
+/// copied right away.
+const SIGNIFICANT_CHANGE: usize = 500;
+
+fn content_hash(text: &str) -> u64 {
+    let mut h = DefaultHasher::new();
+    text.hash(&mut h);
+    h.finish()
+}
+
+// ── Crash recovery ────────────────────────────────────────────────────────────
+
+impl TextToolApp {
+    /// Copy modified panes to `.texttool/recovery/` when the interval has
+    /// passed or a buffer changed a lot.  Called every frame; cheap when
+    /// nothing is due.
+    pub(super) fn tick_recovery(&mut self, ctx: &Context) {
+        if self.project_root.is_none() {
+            return;
+        }
+        let due = self.recovery_tick.is_none_or(|t| t.elapsed() >= RECOVERY_INTERVAL);
+        let mut to_copy: Vec<(PathBuf, String)> = Vec::new();
+        for f in [&self.left_file, &self.right_file].into_iter().flatten() {
+            if !f.modified {
+                continue;
+            }
+            // Wake up for the next copy even if the user stops typing.
+            ctx.request_repaint_after(RECOVERY_INTERVAL);
+            let last = self.recovery_copies.get(&f.path);
+            let jump = last.is_some_and(|&(_, len)| len.abs_diff(f.content.len()) >= SIGNIFICANT_CHANGE);
+            if (due || jump) && last.is_none_or(|&(hash, _)| hash != content_hash(&f.content)) {
+                to_copy.push((f.path.clone(), f.content.clone()));
+            }
+        }
+        if due {
+            self.recovery_tick = Some(Instant::now());
+        }
+        for (path, content) in to_copy {
+            self.write_recovery_copy(&path, &content);
+        }
+    }
+
+    fn write_recovery_copy(&mut self, path: &Path, content: &str) {
+        let Some(root) = self.project_root.clone() else { return };
+        match flush_buffers(&root, &mut self.recovery_manifest, &[(path, content)], unix_now()) {
+            Ok(_) => {
+                self.recovery_copies.insert(path.to_owned(), (content_hash(content), content.len()));
+            }
+            Err(e) => self.status = format!("写入恢复文件失败: {e}"),
+        }
+    }
+
+    /// Copy every modified pane right away, e.g. after a panic.  Returns the
+    /// number of copies written.
+    pub(super) fn flush_recovery(&mut self) -> std::io::Result<usize> {
+        let Some(root) = self.project_root.clone() else { return Ok(0) };
+        let buffers: Vec<(&Path, &str)> = [&self.left_file, &self.right_file].into_iter()
+            .flatten()
+            .filter(|f| f.modified)
+            .map(|f| (f.path.as_path(), f.content.as_str()))
+            .collect();
+        let written = flush_buffers(&root, &mut self.recovery_manifest, &buffers, unix_now())?;
+        for (path, content) in buffers {
+            self.recovery_copies.insert(path.to_owned(), (content_hash(content), content.len()));
+        }
+        Ok(written)
+    }
+
+    /// Delete the recovery copy of `path`, e.g. after it was saved.
+    pub(super) fn discard_recovery(&mut self, path: &Path) {
+        self.recovery_copies.remove(path);
+        let Some(root) = self.project_root.clone() else { return };
+        let Some(rel) = relative_key(&root, path) else { return };
+        if let Some(entry) = self.recovery_manifest.remove(&rel) {
+            let _ = std::fs::remove_file(root.join(RECOVERY_DIR).join(entry.file));
+            let _ = self.recovery_manifest.save(&root);
+        }
+    }
+
+    /// Load the project's recovery manifest and offer copies newer than
+    /// their files.  Copies identical to the file on disk are dropped.
+    pub(super) fn load_recovery(&mut self) {
+        self.recovery_copies.clear();
+        self.recovery_dialog = None;
+        let Some(root) = self.project_root.clone() else { return };
+        self.recovery_manifest = RecoveryManifest::load(&root);
+
+        let mut candidates = Vec::new();
+        for (path, copy) in pending_recoveries(&root, &self.recovery_manifest) {
+            let Ok(content) = std::fs::read_to_string(&copy) else { continue };
+            if std::fs::read_to_string(&path).is_ok_and(|disk| disk == content) {
+                self.discard_recovery(&path);
+                continue;
+            }
+            let saved_at = relative_key(&root, &path)
+                .and_then(|rel| self.recovery_manifest.get(&rel).map(|e| e.saved_at))
+                .unwrap_or(0);
+            candidates.push(RecoveryCandidate { path, content, saved_at });
+        }
+        if !candidates.is_empty() {
+            self.recovery_dialog = Some(candidates);
+        }
+    }
+
+    /// Put a recovered buffer into its pane, marked as modified.
+    fn restore_recovery(&mut self, c: RecoveryCandidate) {
+        let left = self.opens_in_left(&c.path);
+        // The recovered text replaces the pane's whatever it holds, so
+        // there is nothing to ask about.
+        if c.path.is_file() {
+            if let Err(e) = self.load_file_into_pane(&c.path, left) {
+                self.report(e);
+            }
+        }
+        let pane = if left { &mut self.left_file } else { &mut self.right_file };
+        match pane {
+            Some(f) if f.path == c.path => f.content = c.content,
+            _ => *pane = Some(OpenFile::new(c.path.clone(), c.content)),
+        }
+        if let Some(f) = pane {
+            f.modified = true;
+        }
+        if left {
+            self.active_panel = Panel::Novel;
+        }
+        self.status = format!("已恢复未保存的修改: {}", c.path.display());
+    }
+
+    /// Startup dialog listing recoverable buffers.
+    pub(super) fn draw_recovery_dialog(&mut self, ctx: &Context) {
+        let Some(candidates) = &self.recovery_dialog else { return };
+        let now = unix_now();
+        let root = self.project_root.clone().unwrap_or_default();
+        let mut restore: Option<usize> = None;
+        let mut discard: Option<usize> = None;
+        let mut discard_all = false;
+        let mut open = true;
+
+        egui::Window::new("♻ 恢复未保存的修改")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("上次退出前，以下文件有未保存的修改：");
+                ui.add_space(4.0);
+                egui::Grid::new("recovery_grid").num_columns(3).spacing([12.0, 4.0]).show(ui, |ui| {
+                    for (i, c) in candidates.iter().enumerate() {
+                        let name = relative_key(&root, &c.path)
+                            .unwrap_or_else(|| c.path.display().to_string());
+                        ui.label(name).on_hover_text(c.path.display().to_string());
+                        ui.label(RichText::new(format_relative_time(c.saved_at, now))
+                            .small().color(Color32::from_gray(140)));
+                        ui.horizontal(|ui| {
+                            if ui.button("恢复").on_hover_text("在编辑区打开恢复的内容（未保存）").clicked() {
+                                restore = Some(i);
+                            }
+                            if ui.button("丢弃").clicked() {
+                                discard = Some(i);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(6.0);
+                if ui.button("全部丢弃").clicked() {
+                    discard_all = true;
+                }
+            });
+
+        let Some(mut candidates) = self.recovery_dialog.take() else { return };
+        if discard_all {
+            for c in candidates.drain(..) {
+                self.discard_recovery(&c.path);
+            }
+        } else if let Some(i) = restore {
+            // The copy stays until the restored buffer is saved.
+            let c = candidates.remove(i);
+            self.restore_recovery(c);
+        } else if let Some(i) = discard {
+            let c = candidates.remove(i);
+            self.discard_recovery(&c.path);
+        }
+        // Closing the window keeps the copies for the next start.
+        if open && !candidates.is_empty() {
+            self.recovery_dialog = Some(candidates);
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_over_unsaved_pane() {
+        let dir = std::env::temp_dir().join(format!("qingmo_test_restore_recovery_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (one, two) = (dir.join("一.md"), dir.join("二.md"));
+        std::fs::write(&one, "一").unwrap();
+        std::fs::write(&two, "二").unwrap();
+
+        let mut app = TextToolApp::blank();
+        app.load_file_into_pane(&one, true).unwrap();
+        let f = app.left_file.as_mut().unwrap();
+        f.content.push_str("，改");
+        f.modified = true;
+
+        // Restoring is the user's answer already: no question, no error.
+        app.restore_recovery(RecoveryCandidate { path: two.clone(), content: "二，恢复".into(), saved_at: 0 });
+        assert!(app.unsaved_prompt.is_none());
+        assert!(app.error_toast.is_none());
+        let f = app.left_file.as_ref().unwrap();
+        assert_eq!((f.path.as_path(), f.content.as_str(), f.modified), (two.as_path(), "二，恢复", true));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
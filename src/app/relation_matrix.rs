@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::relation_matrix::{default_matrix_selection, relation_matrix};
+
+use super::{rfd_save_file, AppError, IoOp, ObjectKind, RelationMatrixDialog, TextToolApp};
+
+// ── 导出关系矩阵 ──────────────────────────────────────────────────────────────
+
+/// File stem under `Design/`; the extension follows the format.
+const MATRIX_FILE_STEM: &str = "关系矩阵";
+
+impl RelationMatrixDialog {
+    fn file_name(&self) -> String {
+        format!("{MATRIX_FILE_STEM}.{}", if self.csv { "csv" } else { "md" })
+    }
+}
+
+impl TextToolApp {
+    /// Open the dialog with the linked characters selected.
+    pub(super) fn open_relation_matrix(&mut self) {
+        if self.world_objects.is_empty() {
+            self.status = "还没有世界对象，无可导出的关系".to_owned();
+            return;
+        }
+        self.relation_matrix = Some(RelationMatrixDialog {
+            selected: default_matrix_selection(&self.world_objects).into_iter().collect(),
+            drop_empty: true,
+            csv: false,
+        });
+    }
+
+    /// Object selection, format, a preview and the destinations.
+    pub(super) fn draw_relation_matrix(&mut self, ctx: &Context) {
+        let Some(dlg) = &mut self.relation_matrix else {
+            return;
+        };
+        // Rows in the order of the object list.
+        let names: Vec<String> = self.world_objects.iter()
+            .filter(|o| dlg.selected.contains(&o.name))
+            .map(|o| o.name.clone())
+            .collect();
+        let matrix = relation_matrix(&self.world_objects, &names, dlg.drop_empty);
+        let text = if dlg.csv { matrix.to_csv() } else { matrix.to_markdown() };
+
+        let mut open = true;
+        let mut cancel = false;
+        let mut to_design = false;
+        let mut save_as = false;
+        egui::Window::new("导出关系矩阵")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(520.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("行与列都是所选对象，单元格列出两者之间的关联类型（双向关联合并）。");
+                ui.horizontal(|ui| {
+                    ui.label(format!("已选 {} 个对象", dlg.selected.len()));
+                    if ui.small_button("有关联的人物").clicked() {
+                        dlg.selected = default_matrix_selection(&self.world_objects).into_iter().collect();
+                    }
+                    if ui.small_button("全部人物").clicked() {
+                        dlg.selected = self.world_objects.iter()
+                            .filter(|o| o.kind == ObjectKind::Character)
+                            .map(|o| o.name.clone())
+                            .collect();
+                    }
+                    if ui.small_button("清空").clicked() {
+                        dlg.selected.clear();
+                    }
+                });
+                egui::ScrollArea::vertical().id_salt("relation_matrix_objects").max_height(140.0).show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for obj in &self.world_objects {
+                            let mut on = dlg.selected.contains(&obj.name);
+                            let label = format!("{} {}", self.project_config.kind_icon(&obj.kind), obj.name);
+                            if ui.checkbox(&mut on, label).changed() {
+                                if on {
+                                    dlg.selected.insert(obj.name.clone());
+                                } else {
+                                    dlg.selected.remove(&obj.name);
+                                }
+                            }
+                        }
+                    });
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("格式:");
+                    ui.selectable_value(&mut dlg.csv, false, "Markdown 表格");
+                    ui.selectable_value(&mut dlg.csv, true, "CSV");
+                    ui.separator();
+                    ui.checkbox(&mut dlg.drop_empty, "省略没有关联的行列");
+                });
+                egui::ScrollArea::both().id_salt("relation_matrix_preview").max_height(260.0).show(ui, |ui| {
+                    if matrix.names.is_empty() {
+                        ui.label(RichText::new("所选对象之间没有关联").color(Color32::GRAY));
+                    } else {
+                        ui.label(RichText::new(&text).monospace().small().color(Color32::from_gray(190)));
+                    }
+                });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    let any = !matrix.names.is_empty();
+                    if ui.add_enabled(any, egui::Button::new(format!("写入 Design/{}", dlg.file_name()))).clicked() {
+                        to_design = true;
+                    }
+                    if ui.add_enabled(any, egui::Button::new("另存为…")).clicked() {
+                        save_as = true;
+                    }
+                    if ui.add_enabled(any, egui::Button::new("复制到剪贴板")).clicked() {
+                        ui.ctx().copy_text(text.clone());
+                        self.status = "关系矩阵已复制到剪贴板".to_owned();
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        let file_name = dlg.file_name();
+        if to_design {
+            match self.write_project_file("Design", &file_name, &text) {
+                Ok(_) => {
+                    self.refresh_tree();
+                    self.status = format!("已导出 Design/{file_name}");
+                    self.relation_matrix = None;
+                }
+                Err(e) => self.report(e),
+            }
+        } else if save_as {
+            if let Some(dest) = rfd_save_file(&PathBuf::from(&file_name)) {
+                match text_tool_core::safe_write(&dest, &text) {
+                    Ok(_) => {
+                        self.status = format!("已导出关系矩阵到 {}", dest.display());
+                        self.relation_matrix = None;
+                    }
+                    Err(e) => self.report(AppError::Io { op: IoOp::Write, path: dest, source: e }),
+                }
+            }
+        } else if !open || cancel {
+            self.relation_matrix = None;
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Context, RichText};
+
+use super::{ReminderToast, TextToolApp};
+
+// ── 保存提醒: a gentle nudge when work has stayed unsaved for long ────────────
+//
+// Design data counts as unsaved when it differs from what was last synced
+// or loaded; a pane when it is modified.  Each stretch of unsaved work gets
+// at most one toast, and 本次不再提醒 silences a kind for the session.
+
+/// How often the unsaved state is looked at; hashing the design data is
+/// not free.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long the toast stays up unless answered.
+const TOAST_SECS: u64 = 60;
+
+/// What a reminder is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderKind {
+    /// Objects, structure, foreshadows or milestones not synced.
+    Design,
+    /// An editor pane not saved.
+    Panes,
+}
+
+impl ReminderKind {
+    fn label(self) -> &'static str {
+        match self {
+            ReminderKind::Design => "设计数据未同步",
+            ReminderKind::Panes  => "编辑区文件未保存",
+        }
+    }
+}
+
+/// Reminder bookkeeping of one [`ReminderKind`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReminderState {
+    /// Start of the current unsaved stretch.
+    pub(super) dirty_since: Option<Instant>,
+    /// The toast was shown during this stretch.
+    pub(super) shown: bool,
+    /// 本次不再提醒 was chosen.
+    pub(super) snoozed: bool,
+}
+
+impl ReminderState {
+    /// Follow the unsaved state: a stretch starts when work becomes unsaved
+    /// and ends when it is saved.
+    pub(super) fn observe(&mut self, dirty: bool, now: Instant) {
+        match (dirty, self.dirty_since) {
+            (true, None) => self.dirty_since = Some(now),
+            (false, Some(_)) => {
+                self.dirty_since = None;
+                self.shown = false;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether to show the reminder at `now`: unsaved for at least `after`,
+/// not snoozed, and not shown yet during this stretch.
+pub(super) fn should_remind(state: &ReminderState, now: Instant, after: Duration) -> bool {
+    !state.snoozed && !state.shown && state.dirty_since.is_some_and(|since| now.saturating_duration_since(since) >= after)
+}
+
+impl TextToolApp {
+    /// Update the unsaved stretches and raise the toast when one is due.
+    pub(super) fn tick_save_reminders(&mut self, ctx: &Context) {
+        let opts = self.settings.behavior.save_reminder;
+        if !opts.enabled {
+            return;
+        }
+        let now = Instant::now();
+        if self.reminder_checked.is_some_and(|t| now.duration_since(t) < CHECK_INTERVAL) {
+            return;
+        }
+        self.reminder_checked = Some(now);
+        ctx.request_repaint_after(CHECK_INTERVAL);
+
+        let panes = [&self.left_file, &self.right_file].into_iter().flatten().any(|f| f.modified && !f.scratch);
+        let design = self.project_root.is_some() && {
+            let current = self.design_digest();
+            *self.design_saved.get_or_insert(current) != current
+        };
+        let after = Duration::from_secs(u64::from(opts.minutes) * 60);
+        for (kind, dirty) in [(ReminderKind::Design, design), (ReminderKind::Panes, panes)] {
+            let state = self.reminder_state(kind);
+            state.observe(dirty, now);
+            if should_remind(state, now, after) {
+                state.shown = true;
+                let toast = self.reminder_toast.get_or_insert_with(|| ReminderToast { kinds: Vec::new(), since: now });
+                toast.kinds.push(kind);
+            }
+        }
+    }
+
+    fn reminder_state(&mut self, kind: ReminderKind) -> &mut ReminderState {
+        match kind {
+            ReminderKind::Design => &mut self.design_reminder,
+            ReminderKind::Panes  => &mut self.pane_reminder,
+        }
+    }
+
+    /// 立即保存全部: modified panes and every kind of design data.
+    pub(super) fn save_all(&mut self) {
+        if self.left_file.as_ref().is_some_and(|f| f.modified && !f.scratch) {
+            self.save_left();
+        }
+        if self.right_file.as_ref().is_some_and(|f| f.modified && !f.scratch) {
+            self.save_right();
+        }
+        if self.project_root.is_none() {
+            return;
+        }
+        let results = [
+            self.sync_world_objects_to_json(),
+            self.sync_struct_to_json(),
+            self.sync_foreshadows(),
+            self.sync_milestones_to_json(),
+        ];
+        self.struct_json_snapshot = serde_json::to_string(&self.struct_roots).ok();
+        match results.into_iter().find_map(Result::err) {
+            Some(e) => self.report(e),
+            None => self.status = "已保存全部".to_owned(),
+        }
+    }
+
+    /// Reminder toast, bottom right above the snapshot toast.
+    pub(super) fn draw_reminder_toast(&mut self, ctx: &Context) {
+        let Some(toast) = &self.reminder_toast else { return };
+        let left = Duration::from_secs(TOAST_SECS).saturating_sub(toast.since.elapsed());
+        if left.is_zero() {
+            self.reminder_toast = None;
+            return;
+        }
+        ctx.request_repaint_after(left);
+        let minutes = self.settings.behavior.save_reminder.minutes;
+        let kinds = toast.kinds.clone();
+        let what = kinds.iter().map(|k| k.label()).collect::<Vec<_>>().join("、");
+        let mut save = false;
+        let mut snooze = false;
+        let mut close = false;
+        egui::Area::new(egui::Id::new("reminder_toast"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -156.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("⏰ {what}已超过 {minutes} 分钟 —"));
+                        if ui.button(RichText::new("立即保存全部").color(Color32::from_rgb(120, 190, 120))).clicked() {
+                            save = true;
+                        }
+                        if ui.small_button("本次不再提醒").on_hover_text("本次运行期间不再提醒这一类").clicked() {
+                            snooze = true;
+                        }
+                        if ui.small_button("×").clicked() {
+                            close = true;
+                        }
+                    });
+                });
+            });
+        if save {
+            self.save_all();
+        }
+        if snooze {
+            for kind in kinds {
+                self.reminder_state(kind).snoozed = true;
+            }
+        }
+        if save || snooze || close {
+            self.reminder_toast = None;
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_remind_once_per_stretch() {
+        let after = Duration::from_secs(30 * 60);
+        let t0 = Instant::now();
+        let at = |mins: u64| t0 + Duration::from_secs(mins * 60);
+        let mut state = ReminderState::default();
+
+        // Clean: never.
+        state.observe(false, t0);
+        assert!(!should_remind(&state, at(60), after));
+
+        // Unsaved, but not for long enough yet; later saves do not restart the clock.
+        state.observe(true, t0);
+        state.observe(true, at(10));
+        assert!(!should_remind(&state, at(29), after));
+        assert!(should_remind(&state, at(30), after));
+
+        // Shown: every later frame of the stretch stays quiet.
+        state.shown = true;
+        assert!(!should_remind(&state, at(30), after));
+        assert!(!should_remind(&state, at(90), after));
+
+        // Saving ends the stretch; the next one may remind again.
+        state.observe(false, at(91));
+        assert_eq!(state, ReminderState::default());
+        state.observe(true, at(100));
+        assert!(!should_remind(&state, at(120), after));
+        assert!(should_remind(&state, at(130), after));
+
+        // Snoozed for the session: never again.
+        state.snoozed = true;
+        assert!(!should_remind(&state, at(500), after));
+        state.observe(false, at(501));
+        state.observe(true, at(502));
+        assert!(!should_remind(&state, at(600), after));
+    }
+}
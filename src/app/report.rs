@@ -0,0 +1,79 @@
+use text_tool_core::diagram::{objects_to_mermaid, struct_to_mermaid};
+use text_tool_core::report::build_progress_report;
+
+use super::{count_words, format_day, today_days, MermaidExport, OpenFile, TextToolApp};
+
+// ── Progress report ───────────────────────────────────────────────────────────
+
+impl TextToolApp {
+    /// Write `Content/进度报告.md` from the current structure, foreshadows and
+    /// writing stats, then open it in the left pane.
+    pub(super) fn generate_progress_report(&mut self) {
+        let Some(root) = self.project_root.clone() else {
+            self.status = "请先打开一个项目".to_owned();
+            return;
+        };
+        // Incremental, so cheap when the background index is up to date.
+        self.project_index.rescan(&root);
+        let content_dir = root.join("Content");
+        let word_counts = self.project_index.word_counts_by_stem(&content_dir);
+        let report = build_progress_report(
+            &self.struct_roots,
+            &self.foreshadows,
+            &word_counts,
+            &self.writing_stats,
+            today_days(),
+        );
+
+        let path = content_dir.join("进度报告.md");
+        if let Err(e) = text_tool_core::safe_write(&path, report) {
+            self.status = format!("生成进度报告失败: {e}");
+            return;
+        }
+        self.refresh_tree();
+        let r = self.open_file_in_pane(&path, true);
+        self.notify(r.map(|_| format!("已生成进度报告: {}", path.display())));
+    }
+
+    /// Generate the `kind` Mermaid diagram and copy it to the clipboard or
+    /// write it to `Design/`.
+    pub(super) fn export_mermaid(&mut self, ctx: &egui::Context, kind: MermaidExport, to_clipboard: bool) {
+        let text = match kind {
+            MermaidExport::Relations => objects_to_mermaid(&self.world_objects),
+            MermaidExport::Structure => struct_to_mermaid(&self.struct_roots),
+        };
+        if to_clipboard {
+            ctx.copy_text(text);
+            self.status = "Mermaid 图已复制到剪贴板".to_owned();
+        } else {
+            match self.write_project_file("Design", kind.file_name(), &text) {
+                Ok(_) => {
+                    self.refresh_tree();
+                    self.status = format!("已导出 Design/{}", kind.file_name());
+                }
+                Err(e) => self.report(e),
+            }
+        }
+    }
+
+    /// Words `f` gains (or loses) relative to its on-disk version.  Only
+    /// Markdown files under `Content/` count towards the daily stats.
+    pub(super) fn unsaved_word_delta(&self, f: &OpenFile) -> i64 {
+        let in_content = self.project_root.as_ref()
+            .is_some_and(|root| f.path.starts_with(root.join("Content")));
+        if !in_content || !f.is_markdown() {
+            return 0;
+        }
+        let old = std::fs::read_to_string(&f.path).map(|t| count_words(&t)).unwrap_or(0);
+        count_words(&f.content) as i64 - old as i64
+    }
+
+    /// Add `delta` words to today's entry and persist `Design/写作统计.json`.
+    pub(super) fn record_words_written(&mut self, delta: i64) {
+        if delta == 0 || self.project_root.is_none() {
+            return;
+        }
+        self.writing_stats.record(&format_day(today_days()), delta);
+        self.save_writing_stats();
+    }
+}
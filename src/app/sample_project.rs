@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::sample_project::{write_sample_project, SAMPLE_CHAPTER};
+
+use super::{rfd_pick_folder, AppError, IoOp, Panel, TextToolApp};
+
+// ── 新建示例项目: a first project to explore ──────────────────────────────────
+
+/// What each toolbar button is for, shown by the tour.
+const PANEL_TIPS: &[(Panel, &str)] = &[
+    (Panel::Novel, "编辑正文；左侧文件树打开章节"),
+    (Panel::Objects, "人物、地点等设定及其关联"),
+    (Panel::Structure, "卷、章大纲，摘要与出场人物"),
+    (Panel::Llm, "配置模型后协助续写与整理"),
+];
+
+impl TextToolApp {
+    /// 文件 → 新建示例项目….
+    pub(super) fn create_sample_project(&mut self) {
+        if let Some(root) = rfd_pick_folder() {
+            self.open_sample_project(root);
+        }
+    }
+
+    /// Write the sample into `root` and open it with the sample chapter on
+    /// the left, pointing at the toolbar.
+    pub(super) fn open_sample_project(&mut self, root: PathBuf) {
+        let written = match write_sample_project(&root) {
+            Ok(written) => written,
+            Err(e) => {
+                self.report(AppError::Io { op: IoOp::Create, path: root, source: e });
+                return;
+            }
+        };
+        self.open_project_now(root.clone());
+        if !self.settings.auto_load {
+            self.load_all_from_files();
+        }
+        let r = self.open_file_in_pane(&root.join(SAMPLE_CHAPTER), true);
+        self.notify_open(r);
+        self.active_panel = Panel::Novel;
+        self.sample_tour = true;
+        self.status = if written.is_empty() {
+            format!("示例文件已存在，已打开项目: {}", root.display())
+        } else {
+            format!("已创建示例项目（{} 个文件）: {}", written.len(), root.display())
+        };
+    }
+
+    /// The dismissible tour next to the toolbar.
+    pub(super) fn draw_sample_tour(&mut self, ctx: &Context) {
+        if !self.sample_tour {
+            return;
+        }
+        egui::Area::new(egui::Id::new("sample_tour"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::LEFT_TOP, [60.0, 40.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_max_width(300.0);
+                    ui.label(RichText::new("👈 欢迎使用清墨").strong());
+                    ui.label(RichText::new("左侧工具栏在各面板之间切换：").small().color(Color32::from_gray(160)));
+                    ui.add_space(4.0);
+                    egui::Grid::new("sample_tour_grid").num_columns(2).spacing([8.0, 4.0]).show(ui, |ui| {
+                        for (panel, tip) in PANEL_TIPS {
+                            ui.label(format!("{} {}", panel.icon(), panel.label()));
+                            ui.label(RichText::new(*tip).small());
+                            ui.end_row();
+                        }
+                    });
+                    ui.add_space(4.0);
+                    ui.label(RichText::new("更多说明见 Design/开始写作.md").small().color(Color32::from_gray(160)));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("知道了").clicked() {
+                            self.sample_tour = false;
+                        }
+                    });
+                });
+            });
+    }
+}
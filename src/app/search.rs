@@ -1,12 +1,16 @@
 use std::path::{Path, PathBuf};
 
-use super::{TextToolApp, SearchResult, rfd_save_file, rfd_pick_folder};
+use text_tool_core::export::{merge_chapter_files, objects_to_csv};
+use text_tool_core::ignore::IgnoreRules;
+use text_tool_core::wiki::build_wiki;
+
+use super::{AppError, IoOp, TextToolApp, rfd_save_file, rfd_pick_folder};
 
 // ── Full-text search ──────────────────────────────────────────────────────────
 
 impl TextToolApp {
-    /// Scan all `.md` and `.json` files under the project root for
-    /// `self.search_query` and populate `self.search_results`.
+    /// Search the project index for `self.search_query` (case-insensitive)
+    /// and populate `self.search_results`.
     pub(super) fn run_search(&mut self) {
         self.search_results.clear();
         let query = self.search_query.clone();
@@ -17,7 +21,12 @@ impl TextToolApp {
             self.status = "请先打开一个项目".to_owned();
             return;
         };
-        search_dir(&root, &query, &mut self.search_results);
+        if !self.project_index.is_indexed(&root) {
+            // First search before the background scan finished: build it now.
+            self.project_index.rescan(&root);
+        }
+        self.search_results = self.project_index.search(&query);
+        self.search_generation = Some(self.project_index.generation());
         self.status = format!(
             "搜索「{}」找到 {} 处结果",
             query,
@@ -26,92 +35,53 @@ impl TextToolApp {
     }
 
     // ── Export & Backup ───────────────────────────────────────────────────────
+    //
+    // Each returns the status message, or `None` when the file dialog was
+    // cancelled.
 
     /// Concatenate all `Content/*.md` files in alphabetical order and save to a
     /// user-chosen file via a save-file dialog.
-    pub(super) fn export_chapters_merged(&mut self) {
-        let Some(root) = self.project_root.as_ref() else {
-            self.status = "请先打开一个项目".to_owned();
-            return;
-        };
-        let content_dir = root.join("Content");
-        let mut md_files: Vec<PathBuf> = std::fs::read_dir(&content_dir)
-            .into_iter()
-            .flatten()
-            .flatten()
-            .map(|e| e.path())
-            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("md"))
-            .collect();
-        md_files.sort();
+    pub(super) fn export_chapters_merged(&mut self) -> Result<Option<String>, AppError> {
+        let root = self.project_root.as_ref().ok_or(AppError::NoProject)?;
+        let merged = merge_chapter_files(&root.join("Content"), &IgnoreRules::load(root));
+        let Some(dest) = rfd_save_file(&PathBuf::from("merged.md")) else { return Ok(None) };
+        text_tool_core::safe_write(&dest, &merged).map_err(AppError::io(IoOp::Write, &dest))?;
+        Ok(Some(format!("已导出合集到 {}", dest.display())))
+    }
 
-        let mut merged = String::new();
-        for path in &md_files {
-            if let Ok(text) = std::fs::read_to_string(path) {
-                let name = path.file_name().unwrap_or_default().to_string_lossy();
-                merged.push_str(&format!("# ── {name} ──\n\n"));
-                merged.push_str(&text);
-                merged.push_str("\n\n");
-            }
-        }
+    /// Save all world objects as CSV via a save-file dialog.
+    pub(super) fn export_objects_csv(&mut self) -> Result<Option<String>, AppError> {
+        let csv = objects_to_csv(&self.world_objects);
+        let Some(dest) = rfd_save_file(&PathBuf::from("世界对象.csv")) else { return Ok(None) };
+        text_tool_core::safe_write(&dest, csv).map_err(AppError::io(IoOp::Write, &dest))?;
+        Ok(Some(format!("已导出 {} 个对象到 {}", self.world_objects.len(), dest.display())))
+    }
 
-        let dummy = PathBuf::from("merged.md");
-        if let Some(dest) = rfd_save_file(&dummy) {
-            match std::fs::write(&dest, &merged) {
-                Ok(_) => self.status = format!("已导出合集到 {}", dest.display()),
-                Err(e) => self.status = format!("导出失败: {e}"),
-            }
-        }
+    /// 导出设定 Wiki (HTML): world objects, structure and foreshadows as one
+    /// self-contained, linked HTML file.
+    pub(super) fn export_setting_wiki(&mut self) -> Result<Option<String>, AppError> {
+        let project = self.project_root.as_ref()
+            .and_then(|r| r.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "清墨".to_owned());
+        let html = build_wiki(&format!("{project} 设定"), &self.world_objects, &self.struct_roots, &self.foreshadows);
+        let Some(dest) = rfd_save_file(&PathBuf::from(format!("{project}设定.html"))) else { return Ok(None) };
+        text_tool_core::safe_write(&dest, html).map_err(AppError::io(IoOp::Write, &dest))?;
+        Ok(Some(format!("已导出设定 Wiki 到 {}", dest.display())))
     }
 
     /// Copy the entire project folder to a user-selected destination directory.
-    pub(super) fn backup_project(&mut self) {
-        let Some(root) = self.project_root.clone() else {
-            self.status = "请先打开一个项目".to_owned();
-            return;
-        };
-        let Some(dest_parent) = rfd_pick_folder() else {
-            return;
-        };
-        let folder_name = root.file_name().unwrap_or_default();
-        let dest = dest_parent.join(folder_name);
-        match copy_dir_all(&root, &dest) {
-            Ok(_) => self.status = format!("已备份到 {}", dest.display()),
-            Err(e) => self.status = format!("备份失败: {e}"),
-        }
+    pub(super) fn backup_project(&mut self) -> Result<Option<String>, AppError> {
+        let root = self.project_root.as_ref().ok_or(AppError::NoProject)?;
+        let Some(dest_parent) = rfd_pick_folder() else { return Ok(None) };
+        let dest = dest_parent.join(root.file_name().unwrap_or_default());
+        copy_dir_all(root, &dest).map_err(AppError::io(IoOp::Copy, &dest))?;
+        Ok(Some(format!("已备份到 {}", dest.display())))
     }
 }
 
 // ── File utilities ────────────────────────────────────────────────────────────
 
-/// Recursively scan `dir` for lines in `.md` / `.json` files that contain
-/// `query`.  Results are appended to `results`.
-pub(super) fn search_dir(dir: &Path, query: &str, results: &mut Vec<SearchResult>) {
-    let Ok(entries) = std::fs::read_dir(dir) else {
-        return;
-    };
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.is_dir() {
-            search_dir(&path, query, results);
-        } else {
-            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-            if ext == "md" || ext == "json" {
-                if let Ok(text) = std::fs::read_to_string(&path) {
-                    for (line_no, line) in text.lines().enumerate() {
-                        if line.contains(query) {
-                            results.push(SearchResult {
-                                file_path: path.clone(),
-                                line_no: line_no + 1,
-                                line: line.to_owned(),
-                            });
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
-
 /// Recursively copy directory `src` to `dst`, creating it if necessary.
 pub(super) fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
     std::fs::create_dir_all(dst)?;
@@ -0,0 +1,116 @@
+use std::collections::BTreeSet;
+
+use egui::{Color32, Context, RichText, ViewportCommand};
+use text_tool_core::models::format_day;
+use text_tool_core::report::{goal_streak, SessionSummary};
+
+use super::{today_days, TextToolApp};
+
+// ── 本次写作小结: shown when the main window closes ───────────────────────────
+//
+// The first close request of a session that wrote, finished or resolved
+// something is held back for the summary.  Closing the summary window with
+// × or 退出 lets the app quit; only 继续写作 keeps it open.
+
+/// Track `title` in a session set: added when `on`, removed otherwise.
+pub(super) fn note_session(set: &mut BTreeSet<String>, title: &str, on: bool) {
+    if on {
+        set.insert(title.to_owned());
+    } else {
+        set.remove(title);
+    }
+}
+
+impl TextToolApp {
+    fn session_summary_now(&self) -> SessionSummary {
+        let today = today_days();
+        let goal = self.settings.behavior.daily_goal;
+        SessionSummary {
+            date: format_day(today),
+            words_today: self.writing_stats.words_on(&format_day(today)),
+            daily_goal: goal,
+            streak: goal_streak(&self.writing_stats, today, goal),
+            chapters_done: self.session_done.iter().cloned().collect(),
+            foreshadows_resolved: self.session_resolved.iter().cloned().collect(),
+        }
+    }
+
+    /// Hold back the main window's first close request for the summary
+    /// (main window only, and not while extra windows would take over).
+    pub(super) fn intercept_close_for_summary(&mut self, ctx: &Context) {
+        if self.session_summary_shown
+            || !self.settings.behavior.session_summary
+            || self.project_root.is_none()
+            || !self.project_windows.is_empty()
+            || !ctx.input(|i| i.viewport().close_requested())
+        {
+            return;
+        }
+        let summary = self.session_summary_now();
+        if summary.words_today == 0 && summary.chapters_done.is_empty() && summary.foreshadows_resolved.is_empty() {
+            return;
+        }
+        ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+        self.session_summary_shown = true;
+        self.session_summary = Some(summary);
+    }
+
+    pub(super) fn draw_session_summary(&mut self, ctx: &Context) {
+        let Some(summary) = &self.session_summary else { return };
+        let mut open = true;
+        let mut quit = false;
+        let mut stay = false;
+        egui::Window::new("本次写作小结")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                let reached = summary.words_today >= summary.daily_goal.max(1) as i64;
+                let color = if reached { Color32::from_rgb(120, 190, 120) } else { Color32::from_gray(200) };
+                ui.label(RichText::new(format!("今日字数 {} / {}", summary.words_today, summary.daily_goal))
+                    .size(18.0).color(color));
+                ui.label(if summary.streak > 0 {
+                    format!("🔥 已连续 {} 天达到每日目标", summary.streak)
+                } else {
+                    "今天达到目标即可开始连续记录".to_owned()
+                });
+                ui.add_space(4.0);
+                let list = |ui: &mut egui::Ui, label: &str, items: &[String]| {
+                    if items.is_empty() {
+                        ui.label(RichText::new(format!("{label}：无")).color(Color32::GRAY));
+                    } else {
+                        ui.label(format!("{label}（{}）：{}", items.len(), items.join("、")));
+                    }
+                };
+                list(ui, "本次完成章节", &summary.chapters_done);
+                list(ui, "本次回收伏笔", &summary.foreshadows_resolved);
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("📋 复制为打卡文本").clicked() {
+                        ui.ctx().copy_text(summary.checkin_text());
+                        self.status = "打卡文本已复制到剪贴板".to_owned();
+                    }
+                    if ui.button("退出").clicked() {
+                        quit = true;
+                    }
+                    if ui.button("继续写作").clicked() {
+                        stay = true;
+                    }
+                });
+                ui.label(
+                    RichText::new("每日目标可在 设置 → 行为 中修改")
+                        .small().color(Color32::from_gray(140)),
+                );
+            });
+
+        if stay {
+            // Ask again on the next close.
+            self.session_summary = None;
+            self.session_summary_shown = false;
+        } else if quit || !open {
+            self.session_summary = None;
+            ctx.send_viewport_cmd(ViewportCommand::Close);
+        }
+    }
+}
@@ -0,0 +1,453 @@
+use egui::{Color32, Context, RichText};
+use text_tool_core::undo::{format_bytes, UndoState};
+
+use super::{
+    rfd_pick_file, AppSettings, AppTheme, BehaviorSettings, InstalledFont, LlmConfig, MarkdownSettings,
+    PaneAccept, SettingsTab, StructKind, TextToolApp,
+};
+
+/// Rows of the read-only 快捷键 tab.
+const SHORTCUTS: &[(&str, &str)] = &[
+    ("Ctrl+S", "保存左侧文件"),
+    ("Ctrl+Shift+S", "保存右侧文件"),
+    ("Ctrl+Z", "撤销（最后聚焦的编辑区）"),
+    ("Ctrl+B / Ctrl+I", "加粗 / 斜体"),
+    ("Tab", "插入缩进空格"),
+    ("Ctrl+P", "切换 Markdown 预览"),
+    ("Ctrl+K", "插入章节链接"),
+    ("Ctrl+Shift+F", "全文搜索"),
+    ("Ctrl+Shift+T", "重新打开关闭的文件"),
+    ("Ctrl+= / Ctrl+- / Ctrl+0", "放大 / 缩小 / 重置字号"),
+    ("Ctrl+滚轮", "调整字号"),
+    ("F2", "重命名选中文件"),
+    ("F6 / Ctrl+`", "焦点移到面板的下一区域（加 Shift 反向）"),
+    ("↑ / ↓", "焦点在列表时切换选中项"),
+    ("Esc", "焦点从列表回到上次的编辑区"),
+    ("@", "补全世界对象名称"),
+];
+
+// ── Settings window ───────────────────────────────────────────────────────────
+
+impl TextToolApp {
+    /// Draw the floating settings window.  Every change is applied and
+    /// saved immediately.
+    pub(super) fn draw_settings_window(&mut self, ctx: &Context) {
+        if !self.show_settings_window {
+            return;
+        }
+        let before = self.settings.clone();
+        let llm_before = self.llm_config.clone();
+
+        let mut open = self.show_settings_window;
+        egui::Window::new("⚙ 设置")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .min_width(360.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for tab in SettingsTab::all() {
+                        ui.selectable_value(&mut self.settings_tab, tab, tab.label());
+                    }
+                });
+                ui.separator();
+                ui.add_space(4.0);
+
+                match self.settings_tab {
+                    SettingsTab::Editor    => Self::draw_editor_tab(ui, &mut self.settings, self.installed_font.as_ref()),
+                    SettingsTab::Preview   => Self::draw_preview_tab(ui, &mut self.settings.markdown),
+                    SettingsTab::Behavior  => Self::draw_behavior_tab(
+                        ui, &mut self.settings, [&mut self.left_undo_stack, &mut self.right_undo_stack]),
+                    SettingsTab::Shortcuts => Self::draw_shortcuts_tab(ui),
+                    SettingsTab::Llm       => Self::draw_llm_tab(ui, &mut self.llm_config),
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                ui.add_space(4.0);
+                let resettable = self.settings_tab != SettingsTab::Shortcuts;
+                if ui.add_enabled(resettable, egui::Button::new("重置本页")).clicked() {
+                    self.reset_settings_tab(self.settings_tab);
+                }
+            });
+
+        if self.settings.markdown.hide_json != before.markdown.hide_json
+            || self.settings.markdown.show_ignored != before.markdown.show_ignored
+        {
+            self.file_tree.clear();
+            self.refresh_tree();
+        }
+        if self.settings.cjk_font != before.cjk_font {
+            self.install_fonts(ctx);
+        }
+        let limit = self.settings.behavior.undo_limit;
+        if limit < before.behavior.undo_limit {
+            self.left_undo_stack.truncate(limit);
+            self.right_undo_stack.truncate(limit);
+        }
+        if self.settings != before || self.llm_config != llm_before {
+            self.save_config();
+        }
+        self.show_settings_window = open;
+    }
+
+    /// Restore the defaults of the fields shown on `tab`.
+    fn reset_settings_tab(&mut self, tab: SettingsTab) {
+        let def = AppSettings::default();
+        let md = &mut self.settings.markdown;
+        match tab {
+            SettingsTab::Editor => {
+                md.editor_font_size = def.markdown.editor_font_size;
+                md.show_line_numbers = def.markdown.show_line_numbers;
+                md.wrap_markdown = def.markdown.wrap_markdown;
+                md.wrap_json = def.markdown.wrap_json;
+                md.tab_size = def.markdown.tab_size;
+                md.reading_speed = def.markdown.reading_speed;
+                self.settings.theme = def.theme;
+                self.settings.cjk_font = def.cjk_font;
+            }
+            SettingsTab::Preview => {
+                md.preview_font_size = def.markdown.preview_font_size;
+                md.default_to_preview = def.markdown.default_to_preview;
+                md.design_markdown_preview = def.markdown.design_markdown_preview;
+            }
+            SettingsTab::Behavior => {
+                md.auto_save_interval_secs = def.markdown.auto_save_interval_secs;
+                md.auto_extract_structure = def.markdown.auto_extract_structure;
+                md.hide_json = def.markdown.hide_json;
+                md.show_ignored = def.markdown.show_ignored;
+                md.show_files_tab = def.markdown.show_files_tab;
+                md.deepest_struct_kind = def.markdown.deepest_struct_kind;
+                self.settings.auto_load = def.auto_load;
+                self.settings.behavior = def.behavior;
+            }
+            SettingsTab::Shortcuts => {}
+            SettingsTab::Llm => self.llm_config = LlmConfig::default(),
+        }
+    }
+
+    fn draw_editor_tab(ui: &mut egui::Ui, settings: &mut AppSettings, installed: Option<&InstalledFont>) {
+        let md = &mut settings.markdown;
+        ui.horizontal(|ui| {
+            ui.label("编辑器字体大小:");
+            ui.add(egui::Slider::new(&mut md.editor_font_size, 8.0..=36.0)
+                .step_by(1.0)
+                .suffix(" px"));
+        });
+        ui.checkbox(&mut md.show_line_numbers, "显示行号")
+            .on_hover_text("在编辑区左侧显示行号，自动换行的续行不编号");
+        ui.horizontal(|ui| {
+            ui.label("自动换行:");
+            ui.checkbox(&mut md.wrap_markdown, "Markdown 与文本文件");
+            ui.checkbox(&mut md.wrap_json, "JSON 文件");
+        }).response.on_hover_text("关闭后长行不折行，可横向滚动查看；也可用编辑区标题栏的 ↩ 切换");
+        ui.horizontal(|ui| {
+            ui.label("Tab 缩进空格数:");
+            let mut tab_size = md.tab_size as u32;
+            ui.add(egui::Slider::new(&mut tab_size, 1..=8).step_by(1.0));
+            md.tab_size = tab_size as u8;
+        });
+        ui.horizontal(|ui| {
+            ui.label("阅读速度:");
+            ui.add(egui::Slider::new(&mut md.reading_speed, 100..=1500)
+                .step_by(50.0)
+                .suffix(" 字/分钟"))
+                .on_hover_text("用于估算选中文本的阅读时长");
+        });
+        ui.label(
+            RichText::new("Ctrl+滚轮 / Ctrl+= / Ctrl+- 实时调整字体大小")
+                .small().color(Color32::from_gray(140)),
+        );
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            ui.label("界面主题:");
+            for &t in AppTheme::all() {
+                ui.radio_value(&mut settings.theme, t, t.label());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("中文字体:");
+            let current = settings.cjk_font.as_deref().unwrap_or("默认");
+            let in_use = match installed.map(|f| &f.source) {
+                Some(Some(source)) => format!("当前使用{}", source.label()),
+                Some(None) => "未找到中文字体，中文可能显示为方框".to_owned(),
+                None => String::new(),
+            };
+            ui.label(RichText::new(current).small().color(Color32::from_gray(170)))
+                .on_hover_text(format!("未设置时使用内置字体，没有内置字体时查找系统中文字体\n{in_use}"));
+            if ui.small_button("选择…").clicked() {
+                if let Some(path) = rfd_pick_file("字体", &["ttf", "otf", "ttc"]) {
+                    settings.cjk_font = Some(path.display().to_string());
+                }
+            }
+            if settings.cjk_font.is_some() && ui.small_button("恢复默认").clicked() {
+                settings.cjk_font = None;
+            }
+        });
+    }
+
+    fn draw_preview_tab(ui: &mut egui::Ui, md: &mut MarkdownSettings) {
+        ui.horizontal(|ui| {
+            ui.label("预览字体大小:");
+            ui.add(egui::Slider::new(&mut md.preview_font_size, 8.0..=36.0)
+                .step_by(1.0)
+                .suffix(" px"));
+        });
+        ui.checkbox(&mut md.default_to_preview, "打开 Markdown 文件时默认切换到预览模式");
+        ui.label(RichText::new("Ctrl+P 切换预览").small().color(Color32::from_gray(140)));
+        ui.checkbox(&mut md.design_markdown_preview, "设计面板富文本预览")
+            .on_hover_text("摘要、筹备笔记、描述、背景等字段显示渲染后的 Markdown，点 ✏ 编辑");
+    }
+
+    /// `undo` are the left and right panes' histories, for the 撤销历史 rows.
+    fn draw_behavior_tab(ui: &mut egui::Ui, settings: &mut AppSettings, undo: [&mut UndoState; 2]) {
+        let md = &mut settings.markdown;
+        let behavior: &mut BehaviorSettings = &mut settings.behavior;
+
+        ui.label(RichText::new("编辑").strong());
+        ui.horizontal(|ui| {
+            ui.label("自动保存间隔:");
+            ui.add(egui::Slider::new(&mut md.auto_save_interval_secs, 0..=300)
+                .step_by(10.0)
+                .suffix(" 秒"));
+        });
+        ui.label(
+            RichText::new("0 = 关闭自动保存；状态栏显示上次自动保存时间")
+                .small().color(Color32::from_gray(140)),
+        );
+        ui.horizontal(|ui| {
+            ui.label("撤销步数上限:");
+            ui.add(egui::Slider::new(&mut behavior.undo_limit, 20..=1000).step_by(10.0));
+        });
+        let total: usize = undo.iter().map(|u| u.bytes()).sum();
+        ui.label(RichText::new(format!("撤销历史约占 {}（每步保存整篇文本）", format_bytes(total)))
+            .small().color(Color32::from_gray(140)));
+        for (side, stack) in ["左侧", "右侧"].into_iter().zip(undo) {
+            ui.horizontal(|ui| {
+                ui.label(format!("{side}: {} 步 · {}", stack.len(), format_bytes(stack.bytes())));
+                if ui.add_enabled(!stack.is_empty(), egui::Button::new("清空撤销历史").small()).clicked() {
+                    stack.clear();
+                }
+            });
+        }
+        ui.checkbox(&mut behavior.fullwidth_punctuation, "中文后输入的半角标点自动转为全角（, → ，）");
+        ui.checkbox(&mut md.auto_extract_structure, "Ctrl+S 保存时自动从 Markdown 标题提取章节结构");
+        ui.horizontal_wrapped(|ui| {
+            ui.label("待处理标记:");
+            let mut remove = None;
+            for (i, token) in behavior.stuck_markers.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(token).desired_width(64.0));
+                if ui.small_button("×").on_hover_text("移除此标记").clicked() {
+                    remove = Some(i);
+                }
+            }
+            if let Some(i) = remove {
+                behavior.stuck_markers.remove(i);
+            }
+            if ui.small_button("➕").on_hover_text("添加标记").clicked() {
+                behavior.stuck_markers.push(String::new());
+            }
+        });
+        ui.label(
+            RichText::new("编辑区高亮这些文字，导航「📌 标记」列出全部位置（区分大小写）")
+                .small().color(Color32::from_gray(140)),
+        );
+
+        ui.collapsing("词频分析", |ui| {
+            let freq = &mut behavior.word_frequency;
+            ui.horizontal_wrapped(|ui| {
+                ui.label("监视词:");
+                let mut remove = None;
+                for (i, word) in freq.watch_words.iter_mut().enumerate() {
+                    ui.add(egui::TextEdit::singleline(word).desired_width(64.0));
+                    if ui.small_button("×").on_hover_text("移除此监视词").clicked() {
+                        remove = Some(i);
+                    }
+                }
+                if let Some(i) = remove {
+                    freq.watch_words.remove(i);
+                }
+                if ui.small_button("➕").on_hover_text("添加监视词").clicked() {
+                    freq.watch_words.push(String::new());
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("高频词组:");
+                ui.add(egui::Slider::new(&mut freq.top_n, 5..=200).suffix(" 个"));
+            });
+            ui.label("停用词（空格分隔）:");
+            let mut stopwords = freq.stopwords.join(" ");
+            if ui.add(egui::TextEdit::multiline(&mut stopwords).desired_rows(2).desired_width(f32::INFINITY)).changed() {
+                freq.stopwords = stopwords.split_whitespace().map(str::to_owned).collect();
+            }
+            ui.label(
+                RichText::new("多字停用词处断开统计；单字停用词不出现在词组首尾")
+                    .small().color(Color32::from_gray(140)),
+            );
+        });
+
+        ui.add_space(6.0);
+        ui.label(RichText::new("文件").strong());
+        for (side, accept) in [("左侧编辑区", &mut behavior.left_pane), ("右侧编辑区", &mut behavior.right_pane)] {
+            ui.horizontal(|ui| {
+                ui.label(format!("{side}接收:"));
+                for a in PaneAccept::all() {
+                    ui.radio_value(accept, *a, a.label());
+                }
+            });
+        }
+        ui.label(
+            RichText::new("双击打开文件时进入接收该类型的编辑区；两侧都接收时进入最后聚焦的一侧")
+                .small().color(Color32::from_gray(140)),
+        );
+        ui.checkbox(&mut behavior.auto_update_index, "保存时自动更新目录（Content/目录.md）");
+        ui.checkbox(&mut behavior.design_autosave, "自动保存设计数据")
+            .on_hover_text("世界对象、章节结构、伏笔改动后约 2 秒写入 Design 中的对应文件，关闭窗口或切换项目前也会保存");
+        let switch = &mut behavior.panel_switch_save;
+        ui.checkbox(&mut switch.enabled, "切换面板时自动保存");
+        ui.add_enabled_ui(switch.enabled, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                ui.add_space(18.0);
+                ui.checkbox(&mut switch.panes, "编辑区文件");
+                ui.checkbox(&mut switch.objects, "世界对象");
+                ui.checkbox(&mut switch.structure, "章节结构");
+                ui.checkbox(&mut switch.foreshadows, "伏笔");
+                ui.checkbox(&mut switch.milestones, "里程碑");
+            });
+        });
+        ui.label(
+            RichText::new("离开「小说编辑」时保存已修改的编辑区；离开设计面板时保存在其中改动过的数据")
+                .small().color(Color32::from_gray(140)),
+        );
+        let reminder = &mut behavior.save_reminder;
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut reminder.enabled, "保存提醒：未保存超过");
+            ui.add_enabled(reminder.enabled, egui::DragValue::new(&mut reminder.minutes).range(1..=600).suffix(" 分钟"));
+            ui.label("时提示");
+        });
+        ui.label(
+            RichText::new("设计数据未同步或编辑区文件未保存时，每段未保存时间只提示一次")
+                .small().color(Color32::from_gray(140)),
+        );
+        let auto_done = &mut behavior.auto_done;
+        ui.checkbox(&mut auto_done.enabled, "达到目标字数自动标记完成");
+        ui.add_enabled_ui(auto_done.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.add_space(18.0);
+                ui.checkbox(&mut auto_done.unmark, "删减后低于目标");
+                ui.add_enabled(auto_done.unmark, egui::DragValue::new(&mut auto_done.margin_percent).range(0..=100).suffix("%"));
+                ui.label("以上时取消完成");
+            });
+        });
+        ui.label(
+            RichText::new("针对设了目标字数且有对应章节文件的节点，在字数刷新后判断；本次手动改过进度的节点不受影响")
+                .small().color(Color32::from_gray(140)),
+        );
+        ui.checkbox(&mut behavior.remember_link_usage, "记住常用关联类型与目标")
+            .on_hover_text("添加关联时，常用项排在下拉列表最前；勾选后保存在 Design/项目配置.json，下次打开项目仍然有效");
+        ui.horizontal(|ui| {
+            ui.label("每日目标字数:");
+            ui.add(egui::DragValue::new(&mut behavior.daily_goal).range(0..=100_000).speed(50));
+        });
+        ui.checkbox(&mut behavior.session_summary, "退出时显示本次写作小结")
+            .on_hover_text("今日字数、连续达标天数、本次完成的章节与回收的伏笔，可复制为打卡文本；本次没有写作时不显示");
+        ui.collapsing("项目快照", |ui| {
+            let snap = &mut behavior.snapshot;
+            ui.checkbox(&mut snap.daily, "定期快照：每天首次打开项目时备份到 .texttool/snapshots");
+            ui.add_enabled_ui(snap.daily, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("保留最近:");
+                    ui.add(egui::DragValue::new(&mut snap.keep).range(1..=100).suffix(" 份"));
+                });
+            });
+            ui.label("排除（每行一个模式）:");
+            let mut exclude = snap.exclude.join("\n");
+            if ui.add(egui::TextEdit::multiline(&mut exclude).desired_rows(3).desired_width(f32::INFINITY)).changed() {
+                snap.exclude = exclude.lines().map(str::to_owned).collect();
+            }
+            ui.label(
+                RichText::new("* 不跨目录，** 跨目录；含 / 的模式从项目根目录匹配。.texttool/recovery 始终排除")
+                    .small().color(Color32::from_gray(140)),
+            );
+        });
+        ui.collapsing("历史版本", |ui| {
+            let history = &mut behavior.history;
+            ui.checkbox(&mut history.enabled, "保存历史版本：每次保存 Content 中的 Markdown 文件时存一份压缩副本到 .texttool/history");
+            ui.add_enabled_ui(history.enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("每个文件保留最近:");
+                    ui.add(egui::DragValue::new(&mut history.keep).range(0..=1000).suffix(" 份"));
+                    ui.label("最长保留:");
+                    ui.add(egui::DragValue::new(&mut history.max_age_days).range(0..=3650).suffix(" 天"));
+                });
+            });
+            ui.label(
+                RichText::new("0 表示不限，最新的一份始终保留。在文件树中右键文件 →「历史版本…」查看差异与还原")
+                    .small().color(Color32::from_gray(140)),
+            );
+        });
+        ui.checkbox(&mut md.hide_json, "隐藏 .json 文件（推荐：JSON 为内部数据，无需手动编辑）");
+        ui.checkbox(&mut md.show_ignored, "显示被忽略的文件")
+            .on_hover_text("项目根目录 .gitignore / .texttoolignore 匹配的文件默认不显示；勾选后以灰色显示。\n搜索、索引和导出始终跳过这些文件");
+        ui.checkbox(&mut md.show_files_tab, "在导航中显示「文件」标签页（默认关闭，使用章节树导航）");
+
+        ui.add_space(6.0);
+        ui.label(RichText::new("章节结构与同步").strong());
+        ui.horizontal(|ui| {
+            ui.label("添加子节点时最深到:");
+            for k in &StructKind::all()[1..] {
+                ui.radio_value(
+                    &mut md.deepest_struct_kind,
+                    k.clone(),
+                    format!("{} {}", k.icon(), k.label()),
+                );
+            }
+        });
+        ui.checkbox(&mut settings.auto_load, "打开项目时自动从文件反向同步数据");
+        ui.checkbox(&mut behavior.restore_session, "启动时恢复上次会话")
+            .on_hover_text("重新打开上次的项目、两侧编辑区的文件与所在面板；文件从磁盘重新读取，已不存在的跳过");
+        ui.horizontal(|ui| {
+            ui.label("对象时间线缺席提醒:");
+            ui.add(egui::DragValue::new(&mut behavior.timeline_gap_warning).range(1..=50).suffix(" 章"));
+        });
+    }
+
+    fn draw_shortcuts_tab(ui: &mut egui::Ui) {
+        egui::Grid::new("settings_shortcuts")
+            .num_columns(2)
+            .spacing([16.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for (keys, action) in SHORTCUTS {
+                    ui.label(RichText::new(*keys).monospace());
+                    ui.label(*action);
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn draw_llm_tab(ui: &mut egui::Ui, llm: &mut LlmConfig) {
+        ui.checkbox(&mut llm.use_local, "本地模型 (Ollama)");
+        ui.horizontal(|ui| {
+            ui.label("API 地址:");
+            ui.text_edit_singleline(&mut llm.api_url);
+        });
+        ui.horizontal(|ui| {
+            ui.label("模型:");
+            ui.text_edit_singleline(&mut llm.model_path);
+        });
+        ui.horizontal(|ui| {
+            ui.label("温度:");
+            ui.add(egui::Slider::new(&mut llm.temperature, 0.0..=2.0).step_by(0.05));
+        });
+        ui.horizontal(|ui| {
+            ui.label("最大 Token:");
+            ui.add(egui::Slider::new(&mut llm.max_tokens, 64..=2048).step_by(64.0));
+        });
+        ui.label("系统提示词:");
+        ui.add(egui::TextEdit::multiline(&mut llm.system_prompt)
+            .desired_rows(3)
+            .desired_width(f32::INFINITY));
+    }
+}
@@ -0,0 +1,196 @@
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+use egui::{Context, RichText};
+use text_tool_core::snapshot::{has_snapshot_on, rotate_snapshots, snapshot_name, write_snapshot, SNAPSHOT_DIR};
+use text_tool_core::undo::format_bytes;
+
+use super::{format_day, local_unix_secs, rfd_save_file, today_days, SnapshotJob, TextToolApp};
+
+// ── 项目快照: zip backups of the whole project ────────────────────────────────
+//
+// 创建项目快照 zips the project to a file the user picks; 定期快照 does the
+// same once a day into `.texttool/snapshots`, keeping the newest few.  The
+// archive is written on a background thread to `<name>.part` and renamed
+// when complete, so an interrupted snapshot never looks like a finished one.
+
+/// Seconds the completion toast stays up.
+const TOAST_SECS: u64 = 8;
+
+/// Message from [`spawn_snapshot`].
+#[derive(Debug)]
+pub enum SnapshotEvent {
+    /// Entries written so far, and the total.
+    Progress(usize, usize),
+    /// Files archived and the size of the zip, or the error.
+    Done(Result<(usize, u64), String>),
+}
+
+/// Zip `root` into `dest` on a background thread; with `keep`, rotate the
+/// snapshots in `dest`'s folder afterwards.
+fn spawn_snapshot(root: PathBuf, dest: PathBuf, mut exclude: Vec<String>, keep: Option<usize>) -> Receiver<SnapshotEvent> {
+    let (tx, rx) = channel();
+    std::thread::spawn(move || {
+        let mut part = dest.clone().into_os_string();
+        part.push(".part");
+        let part = PathBuf::from(part);
+        // A destination inside the project must not archive itself.
+        for path in [&dest, &part] {
+            if let Ok(rel) = path.strip_prefix(&root) {
+                exclude.push(format!("/{}", rel.to_string_lossy().replace('\\', "/")));
+            }
+        }
+        let result = (|| -> std::io::Result<(usize, u64)> {
+            let file = BufWriter::new(std::fs::File::create(&part)?);
+            let (file, summary) = write_snapshot(&root, file, &exclude, |done, total| {
+                let _ = tx.send(SnapshotEvent::Progress(done, total));
+            })?;
+            file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+            std::fs::rename(&part, &dest)?;
+            if let (Some(keep), Some(dir)) = (keep, dest.parent()) {
+                rotate_snapshots(dir, keep)?;
+            }
+            Ok((summary.files, std::fs::metadata(&dest)?.len()))
+        })();
+        if result.is_err() {
+            let _ = std::fs::remove_file(&part);
+        }
+        let _ = tx.send(SnapshotEvent::Done(result.map_err(|e| e.to_string())));
+    });
+    rx
+}
+
+impl TextToolApp {
+    /// 文件 → 创建项目快照 (zip)…: pick where, then zip in the background.
+    pub(super) fn create_snapshot(&mut self) {
+        let Some(root) = self.project_root.clone() else {
+            self.status = "请先打开一个项目".to_owned();
+            return;
+        };
+        if self.snapshot_job.as_ref().is_some_and(|j| j.pending.is_some()) {
+            self.status = "项目快照正在创建中".to_owned();
+            return;
+        }
+        let project = root.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let Some(dest) = rfd_save_file(Path::new(&snapshot_name(&project, local_unix_secs()))) else {
+            return;
+        };
+        self.start_snapshot(root, dest, false);
+    }
+
+    fn start_snapshot(&mut self, root: PathBuf, dest: PathBuf, daily: bool) {
+        let snap = &self.settings.behavior.snapshot;
+        let keep = daily.then_some(snap.keep.max(1));
+        self.snapshot_job = Some(SnapshotJob {
+            pending: Some(spawn_snapshot(root, dest.clone(), snap.exclude.clone(), keep)),
+            progress: (0, 0),
+            dest,
+            daily,
+            hidden: daily,
+            done: None,
+        });
+    }
+
+    /// 定期快照: once per day and project, unless today's already exists.
+    pub(super) fn check_daily_snapshot(&mut self) {
+        if !self.settings.behavior.snapshot.daily || self.snapshot_job.as_ref().is_some_and(|j| j.pending.is_some()) {
+            return;
+        }
+        let Some(root) = self.project_root.clone() else { return };
+        let today = today_days();
+        if self.snapshot_checked.as_ref() == Some(&(root.clone(), today)) {
+            return;
+        }
+        self.snapshot_checked = Some((root.clone(), today));
+        let dir = root.join(SNAPSHOT_DIR);
+        if has_snapshot_on(&dir, &format_day(today)) {
+            return;
+        }
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            self.status = format!("定期快照失败: {e}");
+            return;
+        }
+        let project = root.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let dest = dir.join(snapshot_name(&project, local_unix_secs()));
+        self.start_snapshot(root, dest, true);
+    }
+
+    /// Progress of a running snapshot, then its size, bottom right.
+    /// 定期快照 only report in the status bar.
+    pub(super) fn draw_snapshot_toast(&mut self, ctx: &Context) {
+        let Some(job) = &mut self.snapshot_job else { return };
+        if let Some(rx) = &job.pending {
+            while let Ok(event) = rx.try_recv() {
+                match event {
+                    SnapshotEvent::Progress(done, total) => job.progress = (done, total),
+                    SnapshotEvent::Done(result) => {
+                        job.pending = None;
+                        let name = job.dest.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                        match result {
+                            Ok((files, size)) => {
+                                job.done = Some((Instant::now(), size));
+                                self.status = if job.daily {
+                                    format!("定期快照已创建: {name}（{}）", format_bytes(size as usize))
+                                } else {
+                                    format!("项目快照已创建: {}（{files} 个文件，{}）", job.dest.display(), format_bytes(size as usize))
+                                };
+                            }
+                            Err(e) => {
+                                self.snapshot_job = None;
+                                self.show_error_toast(format!("项目快照失败: {e}"));
+                                return;
+                            }
+                        }
+                        break;
+                    }
+                }
+            }
+            if job.pending.is_some() {
+                ctx.request_repaint();
+            }
+        }
+        if job.hidden {
+            if job.done.is_some() {
+                self.snapshot_job = None;
+            }
+            return;
+        }
+        if let Some((done, _)) = job.done {
+            let left = Duration::from_secs(TOAST_SECS).saturating_sub(done.elapsed());
+            if left.is_zero() {
+                self.snapshot_job = None;
+                return;
+            }
+            ctx.request_repaint_after(left);
+        }
+
+        let mut close = false;
+        egui::Area::new(egui::Id::new("snapshot_toast"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -116.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        if let Some((_, size)) = job.done {
+                            ui.label(format!("📦 项目快照已创建（{}）", format_bytes(size as usize)))
+                                .on_hover_text(job.dest.display().to_string());
+                        } else {
+                            let (done, total) = job.progress;
+                            ui.label("📦 正在创建项目快照…");
+                            let fraction = if total == 0 { 0.0 } else { done as f32 / total as f32 };
+                            ui.add(egui::ProgressBar::new(fraction).desired_width(120.0)
+                                .text(RichText::new(format!("{done}/{total}")).small()));
+                        }
+                        if ui.small_button("×").on_hover_text("隐藏（快照继续在后台创建）").clicked() {
+                            close = true;
+                        }
+                    });
+                });
+            });
+        if close {
+            job.hidden = true;
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use egui::{Color32, Context, RichText};
+use text_tool_core::split::{split_by_headings, split_struct_nodes, SplitPart};
+
+use super::{count_words, SplitDialog, TextToolApp};
+
+// ── 按标题拆分文件 ────────────────────────────────────────────────────────────
+
+impl TextToolApp {
+    pub(super) fn open_split_dialog(&mut self) {
+        if self.project_root.is_none() {
+            self.status = "请先打开一个项目".to_owned();
+            return;
+        }
+        if !self.left_file.as_ref().is_some_and(|f| f.is_markdown()) {
+            self.status = "请先在左侧打开一个 Markdown 文件".to_owned();
+            return;
+        }
+        self.split_dialog = Some(SplitDialog {
+            folder_per_top: false,
+            overwrite: false,
+            build_struct: true,
+        });
+    }
+
+    /// List the files the left file would be split into, then write them
+    /// under `Content/`.
+    pub(super) fn draw_split_dialog(&mut self, ctx: &Context) {
+        let (Some(dlg), Some(f), Some(root)) =
+            (&mut self.split_dialog, &self.left_file, &self.project_root)
+        else {
+            return;
+        };
+        let content_dir = root.join("Content");
+        let mut open = true;
+        let mut cancel = false;
+        let mut confirm = false;
+
+        let parts = split_by_headings(&f.content, dlg.folder_per_top);
+        let collisions = parts.iter()
+            .filter(|p| content_dir.join(p.relative_path()).exists())
+            .count();
+
+        egui::Window::new("按标题拆分文件")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(460.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.checkbox(&mut dlg.folder_per_top, "一级标题作为文件夹（二级标题拆为文件）");
+                ui.checkbox(&mut dlg.build_struct, "同时在章节结构中添加对应节点");
+                ui.separator();
+
+                if parts.is_empty() {
+                    ui.label(RichText::new("未找到可拆分的内容").color(Color32::GRAY));
+                } else {
+                    ui.label(format!("将在 Content/ 下生成 {} 个文件:", parts.len()));
+                    egui::ScrollArea::vertical().max_height(280.0).show(ui, |ui| {
+                        for part in &parts {
+                            let exists = content_dir.join(part.relative_path()).exists();
+                            ui.horizontal(|ui| {
+                                let name = RichText::new(part.relative_path());
+                                ui.label(if exists { name.color(Color32::from_rgb(220, 80, 80)) } else { name });
+                                ui.label(RichText::new(format!(
+                                    "第 {}–{} 行 · {} 字",
+                                    part.start_line, part.end_line - 1, count_words(&part.content),
+                                )).small().color(Color32::from_gray(140)));
+                            });
+                        }
+                    });
+                }
+                if collisions > 0 {
+                    ui.label(RichText::new(format!("⚠ {collisions} 个文件已存在（红色标出）"))
+                        .color(Color32::from_rgb(220, 160, 60)));
+                    ui.checkbox(&mut dlg.overwrite, "覆盖已存在的文件（否则跳过）");
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!parts.is_empty(), egui::Button::new("拆分")).clicked() {
+                        confirm = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if confirm {
+            let (overwrite, build_struct) = (dlg.overwrite, dlg.build_struct);
+            self.split_dialog = None;
+            self.write_split_parts(&content_dir, &parts, overwrite, build_struct);
+        } else if !open || cancel {
+            self.split_dialog = None;
+        }
+    }
+
+    pub(super) fn write_split_parts(
+        &mut self,
+        content_dir: &std::path::Path,
+        parts: &[SplitPart],
+        overwrite: bool,
+        build_struct: bool,
+    ) {
+        let mut written: Vec<SplitPart> = Vec::new();
+        let mut skipped = 0;
+        let mut errors = Vec::new();
+        for part in parts {
+            let path = content_dir.join(part.relative_path());
+            if path.exists() && !overwrite {
+                skipped += 1;
+                continue;
+            }
+            let res = path.parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| text_tool_core::safe_write(&path, &part.content));
+            match res {
+                Ok(()) => written.push(part.clone()),
+                Err(e) => errors.push(format!("{}: {e}", part.relative_path())),
+            }
+        }
+
+        self.refresh_tree();
+        if build_struct {
+            self.struct_roots.extend(split_struct_nodes(&written));
+        }
+        self.status = if errors.is_empty() {
+            format!("已拆分为 {} 个文件，跳过 {skipped} 个已存在文件", written.len())
+        } else {
+            format!("拆分时部分文件写入失败: {}", errors.join("; "))
+        };
+    }
+}
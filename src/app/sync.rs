@@ -1,178 +1,400 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use super::{TextToolApp, WorldObject, StructNode, Foreshadow, Milestone, StructKind};
+use serde::de::DeserializeOwned;
+
+use text_tool_core::outline::{
+    build_struct_from_dir, count_nodes, extract_struct_nodes_from_markdown, extract_subtree_node, merge_struct_subtree,
+};
+use text_tool_core::foreshadow_md::{foreshadows_to_markdown, parse_foreshadows_markdown};
+use text_tool_core::ignore::IgnoreRules;
+use text_tool_core::usage::LinkUsage;
+
+use super::{AppError, DesignPart, IoOp, TextToolApp, WorldObject, StructNode, Milestone, Foreshadow, ProjectConfig, ProjectData,
+            WritingStats, SaveTrigger};
 
 // ── Data persistence helpers ──────────────────────────────────────────────────
 
+/// `Design/<filename>` of the project at `root`, parsed, with its path for
+/// display.  An empty file holds the empty collection.
+fn read_design_json<T: DeserializeOwned + Default>(root: &Path, filename: &str, what: &str) -> Result<(T, String), AppError> {
+    let path = root.join("Design").join(filename);
+    let text = std::fs::read_to_string(&path).map_err(AppError::io(IoOp::Read, &path))?;
+    let value = if text.trim().is_empty() {
+        T::default()
+    } else {
+        serde_json::from_str(&text).map_err(AppError::serde(what))?
+    };
+    Ok((value, path.display().to_string()))
+}
+
+/// `Design/世界对象.json` of the project at `root`, parsed.
+pub(super) fn read_world_objects(root: &Path) -> Result<(Vec<WorldObject>, String), AppError> {
+    read_design_json(root, "世界对象.json", "世界对象")
+}
+
+/// `Design/章节结构.json` of the project at `root`, parsed.
+pub(super) fn read_struct_roots(root: &Path) -> Result<(Vec<StructNode>, String), AppError> {
+    read_design_json(root, "章节结构.json", "章节结构")
+}
+
+/// `Design/里程碑.json` of the project at `root`, parsed.
+pub(super) fn read_milestones(root: &Path) -> Result<(Vec<Milestone>, String), AppError> {
+    read_design_json(root, "里程碑.json", "里程碑")
+}
+
+/// `Design/伏笔.json` of the project at `root`, parsed.  A project saved
+/// before the file existed has only `Content/伏笔.md`, which is parsed
+/// instead and becomes the JSON on the next save.
+pub(super) fn read_foreshadows(root: &Path) -> Result<(Vec<Foreshadow>, String), AppError> {
+    match read_design_json(root, "伏笔.json", "伏笔") {
+        Err(e) if e.is_not_found() => {
+            let path = root.join("Content").join("伏笔.md");
+            let text = std::fs::read_to_string(&path).map_err(AppError::io(IoOp::Read, &path))?;
+            Ok((parse_foreshadows_markdown(&text), path.display().to_string()))
+        }
+        read => read,
+    }
+}
+
+/// The four design collections of a project as read from disk, each with
+/// its path for display.  Built off the UI thread when a project opens.
+pub(super) struct LoadedDesign {
+    objects: Result<(Vec<WorldObject>, String), AppError>,
+    roots: Result<(Vec<StructNode>, String), AppError>,
+    milestones: Result<(Vec<Milestone>, String), AppError>,
+    foreshadows: Result<(Vec<Foreshadow>, String), AppError>,
+}
+
+impl LoadedDesign {
+    pub(super) fn read(root: &Path) -> Self {
+        LoadedDesign {
+            objects: read_world_objects(root),
+            roots: read_struct_roots(root),
+            milestones: read_milestones(root),
+            foreshadows: read_foreshadows(root),
+        }
+    }
+}
+
 impl TextToolApp {
-    /// Write `content` to `<project_root>/<subdir>/<filename>`.
-    /// Sets `self.status` on error or when no project is open.
-    /// Returns `true` on success.
-    pub(super) fn write_project_file(&mut self, subdir: &str, filename: &str, content: &str) -> bool {
-        if let Some(root) = self.project_root.as_ref() {
-            let path = root.join(subdir).join(filename);
-            if let Err(e) = std::fs::write(&path, content) {
-                self.status = format!("写入 {} 失败: {e}", path.display());
-                return false;
-            }
-            true
-        } else {
-            self.status = "请先打开一个项目".to_owned();
-            false
+    /// Write `content` to `<project_root>/<subdir>/<filename>` and return its
+    /// path.  Fails when no project is open (and asks for one), or when
+    /// another computer holds the design data (see `project_lock.rs`).
+    pub(super) fn write_project_file(&mut self, subdir: &str, filename: &str, content: &str) -> Result<PathBuf, AppError> {
+        if subdir == "Design" {
+            self.check_design_writable()?;
         }
+        let Some(root) = self.project_root.as_ref() else {
+            self.request_project_for_save(SaveTrigger::Explicit);
+            return Err(AppError::NoProject);
+        };
+        let path = root.join(subdir).join(filename);
+        text_tool_core::safe_write(&path, content).map_err(AppError::io(IoOp::Write, &path))?;
+        Ok(path)
     }
 
-    /// Read `<project_root>/<subdir>/<filename>` as a UTF-8 string.
-    /// Returns `Err` with a Chinese-language message if no project is open or
-    /// the file cannot be read.
-    pub(super) fn read_project_file(&self, subdir: &str, filename: &str) -> Result<(String, String), String> {
-        let root = self.project_root.as_ref()
-            .ok_or_else(|| "请先打开一个项目".to_owned())?;
+    /// Read `<project_root>/<subdir>/<filename>` as a UTF-8 string, together
+    /// with the path for display.
+    pub(super) fn read_project_file(&self, subdir: &str, filename: &str) -> Result<(String, String), AppError> {
+        let root = self.project_root.as_ref().ok_or(AppError::NoProject)?;
         let path = root.join(subdir).join(filename);
-        let display = path.display().to_string();
-        let text = std::fs::read_to_string(&path)
-            .map_err(|e| format!("读取失败: {e}"))?;
-        Ok((text, display))
+        let text = std::fs::read_to_string(&path).map_err(AppError::io(IoOp::Read, &path))?;
+        Ok((text, path.display().to_string()))
     }
 
     // ── Save (app state → file) ───────────────────────────────────────────────
 
-    /// Save world objects to `Design/世界对象.json`.
-    pub(super) fn sync_world_objects_to_json(&mut self) {
-        match serde_json::to_string_pretty(&self.world_objects) {
-            Ok(json) => {
-                if self.write_project_file("Design", "世界对象.json", &json) {
-                    self.status = "世界对象已同步到 Design/世界对象.json".to_owned();
-                }
-            }
-            Err(e) => self.status = format!("序列化失败: {e}"),
-        }
+    /// Save world objects to `Design/世界对象.json`, unless that would
+    /// empty a fuller file (see `sync_guard.rs`).
+    pub(super) fn sync_world_objects_to_json(&mut self) -> Result<String, AppError> {
+        self.guard_design_overwrite(DesignPart::Objects, self.world_objects.len())?;
+        self.write_world_objects_json()
     }
 
-    /// Save chapter structure to `Design/章节结构.json`.
-    pub(super) fn sync_struct_to_json(&mut self) {
-        match serde_json::to_string_pretty(&self.struct_roots) {
-            Ok(json) => {
-                if self.write_project_file("Design", "章节结构.json", &json) {
-                    self.status = "章节结构已同步到 Design/章节结构.json".to_owned();
-                }
-            }
-            Err(e) => self.status = format!("序列化失败: {e}"),
-        }
+    /// Write `Design/世界对象.json` without the overwrite guard.
+    pub(super) fn write_world_objects_json(&mut self) -> Result<String, AppError> {
+        let json = serde_json::to_string_pretty(&self.world_objects).map_err(AppError::serde("世界对象"))?;
+        self.write_project_file("Design", "世界对象.json", &json)?;
+        self.note_design_saved(DesignPart::Objects);
+        Ok("世界对象已同步到 Design/世界对象.json".to_owned())
+    }
+
+    /// Save chapter structure to `Design/章节结构.json`, unless that would
+    /// empty a fuller file (see `sync_guard.rs`).
+    pub(super) fn sync_struct_to_json(&mut self) -> Result<String, AppError> {
+        self.guard_design_overwrite(DesignPart::Structure, self.struct_roots.len())?;
+        self.write_struct_json()
+    }
+
+    /// Write `Design/章节结构.json` without the overwrite guard.
+    pub(super) fn write_struct_json(&mut self) -> Result<String, AppError> {
+        let json = serde_json::to_string_pretty(&self.struct_roots).map_err(AppError::serde("章节结构"))?;
+        self.write_project_file("Design", "章节结构.json", &json)?;
+        self.note_design_saved(DesignPart::Structure);
+        Ok("章节结构已同步到 Design/章节结构.json".to_owned())
     }
 
     /// Save milestones to `Design/里程碑.json`.
-    pub(super) fn sync_milestones_to_json(&mut self) {
-        match serde_json::to_string_pretty(&self.milestones) {
-            Ok(json) => {
-                if self.write_project_file("Design", "里程碑.json", &json) {
-                    self.status = "里程碑已同步到 Design/里程碑.json".to_owned();
-                }
+    pub(super) fn sync_milestones_to_json(&mut self) -> Result<String, AppError> {
+        let json = serde_json::to_string_pretty(&self.milestones).map_err(AppError::serde("里程碑"))?;
+        self.write_project_file("Design", "里程碑.json", &json)?;
+        self.note_design_saved(DesignPart::Milestones);
+        Ok("里程碑已同步到 Design/里程碑.json".to_owned())
+    }
+
+    /// Save foreshadows to `Design/伏笔.json`, and render them to
+    /// `Content/伏笔.md` for reading, unless that would empty a fuller
+    /// file (see `sync_guard.rs`).
+    pub(super) fn sync_foreshadows(&mut self) -> Result<String, AppError> {
+        self.guard_design_overwrite(DesignPart::Foreshadows, self.foreshadows.len())?;
+        self.write_foreshadows()
+    }
+
+    /// Write `Design/伏笔.json` and `Content/伏笔.md` without the overwrite
+    /// guard.
+    pub(super) fn write_foreshadows(&mut self) -> Result<String, AppError> {
+        let json = serde_json::to_string_pretty(&self.foreshadows).map_err(AppError::serde("伏笔"))?;
+        self.write_project_file("Design", "伏笔.json", &json)?;
+        self.note_design_saved(DesignPart::Foreshadows);
+        let md = foreshadows_to_markdown(&self.foreshadows);
+        self.write_project_file("Content", "伏笔.md", &md)?;
+        Ok("伏笔已同步到 Design/伏笔.json 与 Content/伏笔.md".to_owned())
+    }
+
+    // ── Load (file → app state) ───────────────────────────────────────────────
+
+    /// Load world objects from `Design/世界对象.json` into `self.world_objects`.
+    pub(super) fn load_world_objects_from_json(&mut self) -> Result<String, AppError> {
+        let root = self.project_root.as_ref().ok_or(AppError::NoProject)?;
+        let (objects, display) = read_world_objects(root)?;
+        self.set_world_objects(objects);
+        Ok(format!("已从 {display} 加载世界对象"))
+    }
+
+    /// Load chapter structure from `Design/章节结构.json` into `self.struct_roots`.
+    pub(super) fn load_struct_from_json(&mut self) -> Result<String, AppError> {
+        let root = self.project_root.as_ref().ok_or(AppError::NoProject)?;
+        let (roots, display) = read_struct_roots(root)?;
+        self.set_struct_roots(roots);
+        Ok(format!("已从 {display} 加载章节结构"))
+    }
+
+    /// Load milestones from `Design/里程碑.json` into `self.milestones`.
+    pub(super) fn load_milestones_from_json(&mut self) -> Result<String, AppError> {
+        let root = self.project_root.as_ref().ok_or(AppError::NoProject)?;
+        let (milestones, display) = read_milestones(root)?;
+        self.set_milestones(milestones);
+        Ok(format!("已从 {display} 加载里程碑"))
+    }
+
+    /// Load foreshadows from `Design/伏笔.json`, or `Content/伏笔.md` in a
+    /// project without it, into `self.foreshadows`.
+    pub(super) fn load_foreshadows(&mut self) -> Result<String, AppError> {
+        let root = self.project_root.as_ref().ok_or(AppError::NoProject)?;
+        let (foreshadows, display) = read_foreshadows(root)?;
+        self.set_foreshadows(foreshadows);
+        Ok(format!("已从 {display} 加载伏笔"))
+    }
+
+    /// Load `part` from its file, as its 从文件加载 menu entry does.
+    pub(super) fn load_design_part(&mut self, part: DesignPart) -> Result<String, AppError> {
+        match part {
+            DesignPart::Objects => self.load_world_objects_from_json(),
+            DesignPart::Structure => {
+                let loaded = self.load_struct_from_json();
+                self.struct_json_snapshot = serde_json::to_string(&self.struct_roots).ok();
+                loaded
             }
-            Err(e) => self.status = format!("序列化失败: {e}"),
+            DesignPart::Foreshadows => self.load_foreshadows(),
+            DesignPart::Milestones => self.load_milestones_from_json(),
         }
     }
 
-    /// Save foreshadows to `Content/伏笔.md`.
-    pub(super) fn sync_foreshadows_to_md(&mut self) {
-        let mut md = String::from("# 伏笔列表\n\n");
-        for fs in &self.foreshadows {
-            let status = if fs.resolved { "✅ 已解决" } else { "⏳ 未解决" };
-            md.push_str(&format!("## {} {}\n\n", fs.name, status));
-            if !fs.description.is_empty() {
-                md.push_str(&format!("{}\n\n", fs.description));
-            }
-            if !fs.related_chapters.is_empty() {
-                md.push_str(&format!("**关联章节**: {}\n\n", fs.related_chapters.join("、")));
+    /// Put the world objects read when a project opened in place.  A
+    /// project without the file starts with none, so another project's
+    /// objects never stay behind; an unreadable or malformed file keeps the
+    /// list, is reported, and is not written over without asking.
+    pub(super) fn install_world_objects(&mut self, read: Result<(Vec<WorldObject>, String), AppError>) {
+        match read {
+            Ok((objects, _)) => self.set_world_objects(objects),
+            Err(e) if e.is_not_found() => self.set_world_objects(Vec::new()),
+            Err(e) => self.note_design_unloaded(DesignPart::Objects, e),
+        }
+    }
+
+    /// The same for the chapter structure read when a project opened.
+    pub(super) fn install_struct_roots(&mut self, read: Result<(Vec<StructNode>, String), AppError>) {
+        match read {
+            Ok((roots, _)) => self.set_struct_roots(roots),
+            Err(e) if e.is_not_found() => self.set_struct_roots(Vec::new()),
+            Err(e) => {
+                self.selected_node_path.clear();
+                self.note_design_unloaded(DesignPart::Structure, e);
             }
         }
-        if self.write_project_file("Content", "伏笔.md", &md) {
-            self.status = "伏笔已同步到 Content/伏笔.md".to_owned();
+    }
+
+    /// Report the failed read of `part` and hold back writes over its file
+    /// (see `sync_guard.rs`).
+    fn note_design_unloaded(&mut self, part: DesignPart, e: AppError) {
+        if !self.design_unloaded.contains(&part) {
+            self.design_unloaded.push(part);
         }
+        self.report(e);
     }
 
-    // ── Load (file → app state) ───────────────────────────────────────────────
+    /// The same for the foreshadows: a project with neither file starts
+    /// with none.
+    pub(super) fn install_foreshadows(&mut self, read: Result<(Vec<Foreshadow>, String), AppError>) {
+        match read {
+            Ok((foreshadows, _)) => self.set_foreshadows(foreshadows),
+            Err(e) if e.is_not_found() => self.set_foreshadows(Vec::new()),
+            Err(e) => self.note_design_unloaded(DesignPart::Foreshadows, e),
+        }
+    }
 
-    /// Load world objects from `Design/世界对象.json` into `self.world_objects`.
-    pub(super) fn load_world_objects_from_json(&mut self) {
-        match self.read_project_file("Design", "世界对象.json") {
-            Ok((text, display)) => match serde_json::from_str::<Vec<WorldObject>>(&text) {
-                Ok(objs) => {
-                    self.world_objects = objs;
-                    self.selected_obj_idx = None;
-                    self.status = format!("已从 {display} 加载世界对象");
-                }
-                Err(e) => self.status = format!("解析失败: {e}"),
-            },
-            Err(msg) => self.status = msg,
+    /// The same for the milestones, read on opening with 自动加载 only.
+    pub(super) fn install_milestones(&mut self, read: Result<(Vec<Milestone>, String), AppError>) {
+        match read {
+            Ok((milestones, _)) => self.set_milestones(milestones),
+            Err(e) if e.is_not_found() => self.set_milestones(Vec::new()),
+            Err(e) => self.report(e),
         }
     }
 
-    /// Load chapter structure from `Design/章节结构.json` into `self.struct_roots`.
-    pub(super) fn load_struct_from_json(&mut self) {
-        match self.read_project_file("Design", "章节结构.json") {
-            Ok((text, display)) => match serde_json::from_str::<Vec<StructNode>>(&text) {
-                Ok(nodes) => {
-                    self.struct_roots = nodes;
-                    self.selected_node_path.clear();
-                    self.status = format!("已从 {display} 加载章节结构");
-                }
-                Err(e) => self.status = format!("解析失败: {e}"),
-            },
-            Err(msg) => self.status = msg,
+    /// Put `parts` of `data`, read from `project.json`, in place.  The
+    /// parts' own files are still taken to hold what they did, so the
+    /// next save writes whatever differs to them.
+    pub(super) fn install_project_data(&mut self, data: ProjectData, parts: &[DesignPart]) {
+        let saved = self.design_saved;
+        let unloaded = self.design_unloaded.clone();
+        let ProjectData { world_objects, struct_roots, foreshadows } = data;
+        if parts.contains(&DesignPart::Objects) {
+            self.set_world_objects(world_objects);
         }
+        if parts.contains(&DesignPart::Structure) {
+            self.set_struct_roots(struct_roots);
+            self.struct_json_snapshot = serde_json::to_string(&self.struct_roots).ok();
+        }
+        if parts.contains(&DesignPart::Foreshadows) {
+            self.set_foreshadows(foreshadows);
+        }
+        self.design_saved = saved;
+        self.design_unloaded = unloaded;
     }
 
-    /// Load milestones from `Design/里程碑.json` into `self.milestones`.
-    pub(super) fn load_milestones_from_json(&mut self) {
-        match self.read_project_file("Design", "里程碑.json") {
-            Ok((text, display)) => match serde_json::from_str::<Vec<Milestone>>(&text) {
-                Ok(ms) => {
-                    self.milestones = ms;
-                    self.selected_ms_idx = None;
-                    self.status = format!("已从 {display} 加载里程碑");
-                }
-                Err(e) => self.status = format!("解析失败: {e}"),
-            },
-            Err(msg) => self.status = msg,
+    /// Empty every design collection, before another project opens.
+    pub(super) fn clear_design(&mut self) {
+        self.set_world_objects(Vec::new());
+        self.set_struct_roots(Vec::new());
+        self.set_milestones(Vec::new());
+        self.set_foreshadows(Vec::new());
+    }
+
+    fn set_world_objects(&mut self, objects: Vec<WorldObject>) {
+        self.world_objects = objects;
+        self.clear_obj_selection();
+        self.note_design_saved(DesignPart::Objects);
+    }
+
+    fn set_struct_roots(&mut self, roots: Vec<StructNode>) {
+        self.struct_roots = roots;
+        self.selected_node_path.clear();
+        self.note_design_saved(DesignPart::Structure);
+    }
+
+    fn set_milestones(&mut self, milestones: Vec<Milestone>) {
+        self.milestones = milestones;
+        self.selected_ms_idx = None;
+        self.note_design_saved(DesignPart::Milestones);
+    }
+
+    fn set_foreshadows(&mut self, foreshadows: Vec<Foreshadow>) {
+        self.foreshadows = foreshadows;
+        self.selected_fs_idx = None;
+        self.fs_warnings = None;
+        self.note_design_saved(DesignPart::Foreshadows);
+    }
+
+    /// Run all four reverse-sync loads in sequence.  Files the project does
+    /// not have yet are skipped; the first other failure is reported.
+    pub(super) fn load_all_from_files(&mut self) {
+        let Some(root) = &self.project_root else {
+            self.report(AppError::NoProject);
+            return;
+        };
+        let design = LoadedDesign::read(root);
+        self.install_design(design);
+    }
+
+    /// Put the design data read by [`LoadedDesign::read`] in place, skipping
+    /// files the project does not have yet and reporting the first other
+    /// failure.
+    pub(super) fn install_design(&mut self, design: LoadedDesign) {
+        let mut errors = Vec::new();
+        match design.objects {
+            Ok((objects, _)) => self.set_world_objects(objects),
+            Err(e) => errors.push(e),
+        }
+        match design.roots {
+            Ok((roots, _)) => self.set_struct_roots(roots),
+            Err(e) => errors.push(e),
+        }
+        match design.milestones {
+            Ok((milestones, _)) => self.set_milestones(milestones),
+            Err(e) => errors.push(e),
+        }
+        match design.foreshadows {
+            Ok((foreshadows, _)) => self.set_foreshadows(foreshadows),
+            Err(e) => errors.push(e),
+        }
+        match errors.into_iter().find(|e| !e.is_not_found()) {
+            Some(e) => self.report(e),
+            None => self.status = "已从文件加载所有数据".to_owned(),
         }
     }
 
-    /// Parse `Content/伏笔.md` → `self.foreshadows`.
-    ///
-    /// `## name` headings become foreshadow entries; `✅` in the heading marks
-    /// them as resolved.
-    pub(super) fn load_foreshadows_from_md(&mut self) {
-        match self.read_project_file("Content", "伏笔.md") {
-            Ok((text, display)) => {
-                let mut foreshadows = Vec::new();
-                for line in text.lines() {
-                    if let Some(rest) = line.strip_prefix("## ") {
-                        let resolved = rest.contains('✅');
-                        let name = rest.replace("✅", "").replace("已解决", "")
-                            .replace("⏳", "").replace("未解决", "").trim().to_owned();
-                        if !name.is_empty() {
-                            let mut fs = Foreshadow::new(&name);
-                            fs.resolved = resolved;
-                            foreshadows.push(fs);
-                        }
-                    }
-                }
-                self.foreshadows = foreshadows;
-                self.selected_fs_idx = None;
-                self.status = format!("已从 {display} 加载伏笔");
-            }
-            Err(msg) => self.status = msg,
+    // ── Project config ────────────────────────────────────────────────────────
+
+    /// Load `Design/项目配置.json`; a missing or unreadable file yields defaults.
+    pub(super) fn load_project_config(&mut self) {
+        self.project_config = self.read_project_file("Design", "项目配置.json")
+            .ok()
+            .and_then(|(text, _)| serde_json::from_str::<ProjectConfig>(&text).ok())
+            .unwrap_or_default();
+        self.link_usage = if self.settings.behavior.remember_link_usage {
+            self.project_config.link_usage.clone()
+        } else {
+            LinkUsage::default()
+        };
+    }
+
+    /// Save `self.project_config` to `Design/项目配置.json`, reporting failures.
+    pub(super) fn save_project_config(&mut self) {
+        let res = serde_json::to_string_pretty(&self.project_config)
+            .map_err(AppError::serde("项目配置"))
+            .and_then(|json| self.write_project_file("Design", "项目配置.json", &json));
+        if let Err(e) = res {
+            self.report(e);
         }
     }
 
-    /// Run all four reverse-sync loads in sequence.
-    pub(super) fn load_all_from_files(&mut self) {
-        self.load_world_objects_from_json();
-        self.load_struct_from_json();
-        self.load_milestones_from_json();
-        self.load_foreshadows_from_md();
-        self.status = "已从文件加载所有数据".to_owned();
+    /// Load `Design/写作统计.json`; a missing or unreadable file yields empty stats.
+    pub(super) fn load_writing_stats(&mut self) {
+        self.writing_stats = self.read_project_file("Design", "写作统计.json")
+            .ok()
+            .and_then(|(text, _)| serde_json::from_str::<WritingStats>(&text).ok())
+            .unwrap_or_default();
+    }
+
+    /// Save `self.writing_stats` to `Design/写作统计.json`, reporting failures.
+    pub(super) fn save_writing_stats(&mut self) {
+        let res = serde_json::to_string_pretty(&self.writing_stats)
+            .map_err(AppError::serde("写作统计"))
+            .and_then(|json| self.write_project_file("Design", "写作统计.json", &json));
+        if let Err(e) = res {
+            self.report(e);
+        }
     }
 
     // ── Structure extraction ──────────────────────────────────────────────────
@@ -184,7 +406,8 @@ impl TextToolApp {
     ///   `#`  → `StructKind::Outline`
     ///   `##` → `StructKind::Volume`
     ///   `###` → `StructKind::Chapter`
-    ///   `####`+ → `StructKind::Section`
+    ///   `####` → `StructKind::Section`
+    ///   `#####`+ → `StructKind::Scene`
     pub(super) fn extract_structure_from_left(&mut self) {
         let content = if let Some(lf) = &self.left_file {
             if lf.is_markdown() { Some(lf.content.clone()) } else { None }
@@ -203,6 +426,34 @@ impl TextToolApp {
         self.status = format!("已从 Markdown 提取 {count} 个结构节点");
     }
 
+    /// Like [`Self::extract_structure_from_left`], for only the heading
+    /// section around the left editor's caret or selection.  With `merge`
+    /// the section goes into the node of the same title in the existing
+    /// structure instead of replacing it.
+    pub(super) fn extract_structure_from_selection(&mut self, ctx: &egui::Context, merge: bool) {
+        let Some(lf) = self.left_file.as_ref().filter(|f| f.is_markdown()) else {
+            self.status = "请先在左侧打开一个 Markdown 文件".to_owned();
+            return;
+        };
+        let state = egui::text_edit::TextEditState::load(ctx, egui::Id::new("left_editor_main"));
+        let (from, to) = state.and_then(|s| s.cursor.char_range())
+            .map_or((0, 0), |r| (r.primary.index, r.secondary.index));
+        let Some(node) = extract_subtree_node(&lf.content, from, to) else {
+            self.status = "光标不在任何标题之下".to_owned();
+            return;
+        };
+        let title = node.title.clone();
+        if merge {
+            let added = merge_struct_subtree(&mut self.struct_roots, node);
+            self.status = format!("已将「{title}」合并到章节结构，新增 {added} 个节点");
+        } else {
+            let count = 1 + count_nodes(&node.children);
+            self.struct_roots = vec![node];
+            self.selected_node_path.clear();
+            self.status = format!("已从「{title}」提取 {count} 个结构节点");
+        }
+    }
+
     /// Build a chapter structure from the project's `Content/` folder hierarchy.
     ///
     /// Convention (Req 2):
@@ -210,29 +461,25 @@ impl TextToolApp {
     ///   • Subdirectories = higher-level structural nodes (Volume, Outline…)
     ///   • Sub-sections within a `.md` file are below the chapter level and are
     ///     represented by headings inside the file, not by the tree here.
-    pub(super) fn sync_struct_from_folders(&mut self) {
-        let Some(root) = self.project_root.clone() else {
-            self.status = "请先打开一个项目".to_owned();
-            return;
-        };
-        let content_dir = root.join("Content");
-        let nodes = build_struct_from_dir(&content_dir);
+    pub(super) fn sync_struct_from_folders(&mut self) -> Result<String, AppError> {
+        let root = self.project_root.as_ref().ok_or(AppError::NoProject)?;
+        let nodes = build_struct_from_dir(&root.join("Content"), &IgnoreRules::load(root));
         let count = count_nodes(&nodes);
         self.struct_roots = nodes;
         self.selected_node_path.clear();
-        self.status = format!("已从文件夹结构同步 {count} 个章节节点");
+        Ok(format!("已从文件夹结构同步 {count} 个章节节点"))
     }
 
     /// Create a short-novel project template under `self.project_root`:
     /// flat Content/ structure (single layer — only `.md` chapters, no subdirs).
     pub(super) fn apply_template_short(&mut self) {
         let Some(root) = self.project_root.clone() else {
-            self.status = "请先打开一个项目".to_owned();
+            self.report(AppError::NoProject);
             return;
         };
         let content = root.join("Content");
         if let Err(e) = std::fs::create_dir_all(&content) {
-            self.status = format!("创建 Content 目录失败: {e}");
+            self.report(AppError::Io { op: IoOp::Create, path: content, source: e });
             return;
         }
         let chapters = ["序章.md", "第一章.md", "第二章.md", "第三章.md", "尾声.md"];
@@ -243,7 +490,7 @@ impl TextToolApp {
                 let stem = Path::new(name).file_stem()
                     .map(|s| s.to_string_lossy().into_owned())
                     .unwrap_or_default();
-                if let Err(e) = std::fs::write(&path, format!("# {}\n\n", stem)) {
+                if let Err(e) = text_tool_core::safe_write(&path, format!("# {}\n\n", stem)) {
                     errors.push(format!("{name}: {e}"));
                 }
             }
@@ -252,7 +499,10 @@ impl TextToolApp {
             self.status = format!("模板创建部分失败: {}", errors.join("; "));
             return;
         }
-        self.sync_struct_from_folders();
+        if let Err(e) = self.sync_struct_from_folders() {
+            self.report(e);
+            return;
+        }
         self.refresh_tree();
         self.status = "已创建短篇模板（单层章节结构）".to_owned();
     }
@@ -261,12 +511,12 @@ impl TextToolApp {
     /// two-layer Content/ structure (Volume subdirs → Chapter `.md` files).
     pub(super) fn apply_template_long(&mut self) {
         let Some(root) = self.project_root.clone() else {
-            self.status = "请先打开一个项目".to_owned();
+            self.report(AppError::NoProject);
             return;
         };
         let content = root.join("Content");
         if let Err(e) = std::fs::create_dir_all(&content) {
-            self.status = format!("创建 Content 目录失败: {e}");
+            self.report(AppError::Io { op: IoOp::Create, path: content, source: e });
             return;
         }
         let volumes: &[(&str, &[&str])] = &[
@@ -286,7 +536,7 @@ impl TextToolApp {
                     let stem = Path::new(name).file_stem()
                         .map(|s| s.to_string_lossy().into_owned())
                         .unwrap_or_default();
-                    if let Err(e) = std::fs::write(&path, format!("# {}\n\n", stem)) {
+                    if let Err(e) = text_tool_core::safe_write(&path, format!("# {}\n\n", stem)) {
                         errors.push(format!("{vol}/{name}: {e}"));
                     }
                 }
@@ -296,224 +546,11 @@ impl TextToolApp {
             self.status = format!("模板创建部分失败: {}", errors.join("; "));
             return;
         }
-        self.sync_struct_from_folders();
+        if let Err(e) = self.sync_struct_from_folders() {
+            self.report(e);
+            return;
+        }
         self.refresh_tree();
         self.status = "已创建长篇模板（卷→章二层结构）".to_owned();
     }
 }
-
-// ── Free functions: Markdown → StructNode extraction ─────────────────────────
-
-/// Parse ATX headings from Markdown text into a `StructNode` tree.
-///
-/// Level mapping:
-///   `#` → Outline,  `##` → Volume,  `###` → Chapter,  `####`+ → Section
-pub(super) fn extract_struct_nodes_from_markdown(content: &str) -> Vec<StructNode> {
-    let mut flat: Vec<(usize, String)> = Vec::new();
-    for line in content.lines() {
-        // Count leading '#' chars using bytes — '#' is ASCII so this is both
-        // correct and faster than iterating over Unicode code points.
-        let level = line.bytes().take_while(|&b| b == b'#').count();
-        if level == 0 || level > 6 {
-            continue;
-        }
-        let rest = &line[level..]; // safe: '#' is ASCII (1 byte each)
-        // Standard ATX heading: at least one space (or empty body) after '#' run.
-        if !rest.starts_with(' ') && !rest.is_empty() {
-            continue;
-        }
-        let title = rest.trim().to_owned();
-        if !title.is_empty() {
-            flat.push((level, title));
-        }
-    }
-    if flat.is_empty() {
-        return vec![];
-    }
-    nest_struct_nodes(&flat, 0, flat[0].0)
-}
-
-/// Recursively nest the flat (level, title) list into `StructNode`s.
-fn nest_struct_nodes(flat: &[(usize, String)], start: usize, min_level: usize) -> Vec<StructNode> {
-    use StructKind::{Outline, Volume, Chapter, Section};
-    let mut result = Vec::new();
-    let mut i = start;
-    while i < flat.len() {
-        let (lvl, title) = &flat[i];
-        if *lvl < min_level {
-            break;
-        }
-        if *lvl == min_level {
-            let kind = match lvl {
-                1 => Outline,
-                2 => Volume,
-                3 => Chapter,
-                _ => Section,
-            };
-            let mut node = StructNode::new(title, kind);
-            let mut j = i + 1;
-            while j < flat.len() && flat[j].0 > *lvl {
-                j += 1;
-            }
-            node.children = nest_struct_nodes(flat, i + 1, *lvl + 1);
-            result.push(node);
-            i = j;
-        } else {
-            i += 1;
-        }
-    }
-    result
-}
-
-/// Build a `StructNode` tree from a directory:
-/// subdirectories → `Volume`, `.md` files → `Chapter`.
-pub(super) fn build_struct_from_dir(dir: &Path) -> Vec<StructNode> {
-    let Ok(entries) = std::fs::read_dir(dir) else {
-        return vec![];
-    };
-    let mut nodes = Vec::new();
-    let mut sorted: Vec<_> = entries.filter_map(|e| e.ok()).collect();
-    sorted.sort_by_key(|e| e.file_name());
-    for entry in sorted {
-        let path = entry.path();
-        let name = path.file_name()
-            .map(|n| n.to_string_lossy().into_owned())
-            .unwrap_or_default();
-        if path.is_dir() {
-            let mut vol = StructNode::new(&name, StructKind::Volume);
-            vol.children = build_struct_from_dir(&path);
-            nodes.push(vol);
-        } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
-            let stem = path.file_stem()
-                .map(|n| n.to_string_lossy().into_owned())
-                .unwrap_or(name);
-            nodes.push(StructNode::new(&stem, StructKind::Chapter));
-        }
-    }
-    nodes
-}
-
-/// Count the total number of nodes in a tree (depth-first).
-pub(super) fn count_nodes(roots: &[StructNode]) -> usize {
-    roots.iter().map(|n| 1 + count_nodes(&n.children)).sum()
-}
-
-// ── Tests ─────────────────────────────────────────────────────────────────────
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_extract_struct_nodes_h1_h2_h3() {
-        let md = "# 总纲\n## 第一卷\n### 第一章\n### 第二章\n## 第二卷\n";
-        let nodes = extract_struct_nodes_from_markdown(md);
-        assert_eq!(nodes.len(), 1);
-        assert_eq!(nodes[0].title, "总纲");
-        assert_eq!(nodes[0].kind, StructKind::Outline);
-        assert_eq!(nodes[0].children.len(), 2);
-        assert_eq!(nodes[0].children[0].title, "第一卷");
-        assert_eq!(nodes[0].children[0].children.len(), 2);
-        assert_eq!(nodes[0].children[0].children[0].title, "第一章");
-    }
-
-    #[test]
-    fn test_extract_struct_nodes_empty() {
-        let nodes = extract_struct_nodes_from_markdown("no headings here");
-        assert!(nodes.is_empty());
-    }
-
-    #[test]
-    fn test_extract_struct_nodes_flat_chapters() {
-        let md = "### 序章\n### 第一章\n### 第二章\n";
-        let nodes = extract_struct_nodes_from_markdown(md);
-        assert_eq!(nodes.len(), 3);
-        assert!(nodes.iter().all(|n| n.kind == StructKind::Chapter));
-    }
-
-    #[test]
-    fn test_count_nodes_empty() {
-        assert_eq!(count_nodes(&[]), 0);
-    }
-
-    #[test]
-    fn test_count_nodes_nested() {
-        let md = "# 卷一\n## 第一章\n### 第一节\n## 第二章\n";
-        let nodes = extract_struct_nodes_from_markdown(md);
-        // 1 (卷一) + 2 (两章) + 1 (一节) = 4
-        assert_eq!(count_nodes(&nodes), 4);
-    }
-
-    #[test]
-    fn test_build_struct_from_dir() {
-        let dir = std::env::temp_dir().join("qingmo_test_struct_dir");
-        let sub = dir.join("第一卷");
-        std::fs::create_dir_all(&sub).unwrap();
-        std::fs::write(sub.join("第一章.md"), "").unwrap();
-        std::fs::write(dir.join("序章.md"), "").unwrap();
-
-        let nodes = build_struct_from_dir(&dir);
-        // Dir 第一卷 comes after file 序章 (dirs sort first in the tree)
-        assert!(nodes.iter().any(|n| n.title == "第一卷" && n.kind == StructKind::Volume));
-        assert!(nodes.iter().any(|n| n.title == "序章"   && n.kind == StructKind::Chapter));
-
-        let _ = std::fs::remove_dir_all(&dir);
-    }
-
-    // ── Template helpers ──────────────────────────────────────────────────────
-
-    #[test]
-    fn test_short_template_creates_flat_structure() {
-        let dir = std::env::temp_dir().join("qingmo_test_short_tpl");
-        std::fs::create_dir_all(dir.join("Content")).unwrap();
-        std::fs::create_dir_all(dir.join("Design")).unwrap();
-
-        // Simulate apply_template_short logic (no TextToolApp needed)
-        let content = dir.join("Content");
-        let chapters = ["序章.md", "第一章.md", "第二章.md", "第三章.md", "尾声.md"];
-        for name in &chapters {
-            let path = content.join(name);
-            let stem = std::path::Path::new(name).file_stem()
-                .map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
-            std::fs::write(&path, format!("# {}\n\n", stem)).unwrap();
-        }
-
-        // Verify all .md files exist and Content has no subdirs
-        let nodes = build_struct_from_dir(&content);
-        assert_eq!(nodes.len(), chapters.len());
-        assert!(nodes.iter().all(|n| n.kind == StructKind::Chapter));
-        assert!(nodes.iter().all(|n| n.children.is_empty())); // flat, no sub-volumes
-
-        let _ = std::fs::remove_dir_all(&dir);
-    }
-
-    #[test]
-    fn test_long_template_creates_two_layer_structure() {
-        let dir = std::env::temp_dir().join("qingmo_test_long_tpl");
-        std::fs::create_dir_all(dir.join("Content")).unwrap();
-
-        let content = dir.join("Content");
-        let volumes: &[(&str, &[&str])] = &[
-            ("第一卷", &["序章.md", "第一章.md", "第二章.md"]),
-            ("第二卷", &["第一章.md", "第二章.md", "第三章.md"]),
-        ];
-        for (vol, chapters) in volumes {
-            let vol_dir = content.join(vol);
-            std::fs::create_dir_all(&vol_dir).unwrap();
-            for name in *chapters {
-                let stem = std::path::Path::new(name).file_stem()
-                    .map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
-                std::fs::write(vol_dir.join(name), format!("# {}\n\n", stem)).unwrap();
-            }
-        }
-
-        let nodes = build_struct_from_dir(&content);
-        assert_eq!(nodes.len(), 2, "Should have 2 volumes");
-        assert!(nodes.iter().all(|n| n.kind == StructKind::Volume));
-        assert_eq!(nodes[0].children.len(), 3, "第一卷 should have 3 chapters");
-        assert_eq!(nodes[1].children.len(), 3, "第二卷 should have 3 chapters");
-        assert!(nodes[0].children.iter().all(|c| c.kind == StructKind::Chapter));
-
-        let _ = std::fs::remove_dir_all(&dir);
-    }
-}
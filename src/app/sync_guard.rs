@@ -0,0 +1,127 @@
+use egui::{Color32, Context, Key, RichText};
+use text_tool_core::sync_guard::{json_array_len, needs_overwrite_confirm, SHRINK_LIMIT_PERCENT};
+
+use super::{AppError, DesignPart, OverwriteConfirm, TextToolApp};
+
+// ── 覆盖确认: empty panel over a full design file ─────────────────────────────
+//
+// Design data is not loaded when a project opens, so saving the Structure,
+// Objects or foreshadow panel in a fresh session would replace the book's
+// file with `[]`.  Saves whose panel is empty, or holds less than
+// `SHRINK_LIMIT_PERCENT` of the file's entries, stop and ask; the answer is
+// 从 JSON 加载, 仍然覆盖 or 取消.
+// So do saves over a file that could not be read when the project opened:
+// it may be repairable by hand, and the panel does not hold its contents.
+
+/// File under `Design/` and panel name of a guarded part.
+fn guarded_file(part: DesignPart) -> (&'static str, &'static str) {
+    match part {
+        DesignPart::Objects => ("世界对象.json", "世界对象"),
+        DesignPart::Structure => ("章节结构.json", "章节结构"),
+        DesignPart::Foreshadows => ("伏笔.json", "伏笔"),
+        DesignPart::Milestones => ("里程碑.json", "里程碑"),
+    }
+}
+
+impl TextToolApp {
+    /// Fail, and queue the 覆盖确认 dialog, when writing `new_len` entries of
+    /// `part` would shrink its file past the limit, or when the file could
+    /// not be read on opening.  A missing file, or one corrupted later, is
+    /// not guarded.
+    pub(super) fn guard_design_overwrite(&mut self, part: DesignPart, new_len: usize) -> Result<(), AppError> {
+        let (file, what) = guarded_file(part);
+        let unloaded = self.design_unloaded.contains(&part);
+        let existing = self.read_project_file("Design", file)
+            .ok()
+            .and_then(|(text, _)| json_array_len(&text));
+        if !unloaded && !needs_overwrite_confirm(existing, new_len) {
+            return Ok(());
+        }
+        let existing = existing.unwrap_or(0);
+        self.overwrite_confirm.retain(|c| c.part != part);
+        self.overwrite_confirm.push(OverwriteConfirm { part, unloaded, existing, new_len });
+        Err(AppError::Validation(if unloaded {
+            format!("{what}未写入：Design/{file} 打开项目时未能加载，请修复后从 JSON 加载，或确认覆盖")
+        } else {
+            format!("{what}未写入：Design/{file} 中有 {existing} 个条目，面板中只有 {new_len} 个，请确认")
+        }))
+    }
+
+    /// The first queued confirmation.
+    pub(super) fn draw_overwrite_confirm(&mut self, ctx: &Context) {
+        let Some(&OverwriteConfirm { part, unloaded, existing, new_len }) = self.overwrite_confirm.first() else {
+            return;
+        };
+        let (file, what) = guarded_file(part);
+
+        let mut load = false;
+        let mut overwrite = false;
+        let mut cancel = false;
+        egui::Window::new(format!("覆盖 Design/{file}？"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                if unloaded {
+                    ui.label(format!("打开项目时 Design/{file} 未能加载，{what}面板中不是它的内容。"));
+                    ui.label("直接写入会替换这个文件，文件中的内容将无法再手动修复。");
+                    ui.label(
+                        RichText::new("建议先修复文件并「从 JSON 加载」，再继续编辑。")
+                            .small().color(Color32::from_gray(150)),
+                    );
+                } else {
+                    let panel = if new_len == 0 {
+                        format!("{what}面板是空的")
+                    } else {
+                        format!("{what}面板中只有 {new_len} 个（不到 {SHRINK_LIMIT_PERCENT}%）")
+                    };
+                    ui.label(format!("Design/{file} 中有 {existing} 个顶层条目，而{panel}。"));
+                    ui.label("本次会话可能还没有从文件加载数据，直接写入会丢失文件中的内容。");
+                    ui.label(
+                        RichText::new("建议先「从 JSON 加载」，再继续编辑。")
+                            .small().color(Color32::from_gray(150)),
+                    );
+                }
+                ui.add_space(6.0);
+                let confirm = if unloaded {
+                    format!("我确认要用面板数据覆盖 Design/{file}")
+                } else {
+                    format!("我确认要用面板数据覆盖这 {existing} 个条目")
+                };
+                ui.checkbox(&mut self.overwrite_acknowledged, confirm);
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("📂 从 JSON 加载").clicked() { load = true; }
+                    let button = egui::Button::new(RichText::new("仍然覆盖").color(Color32::from_rgb(230, 120, 100)));
+                    if ui.add_enabled(self.overwrite_acknowledged, button).clicked() { overwrite = true; }
+                    if ui.button("取消").clicked() { cancel = true; }
+                });
+                if ctx.input(|i| i.key_pressed(Key::Escape)) { cancel = true; }
+            });
+
+        if !(load || overwrite || cancel) {
+            return;
+        }
+        self.overwrite_confirm.remove(0);
+        self.overwrite_acknowledged = false;
+        if !(load || overwrite) {
+            self.status = format!("已取消写入 Design/{file}");
+            return;
+        }
+        let result = if load {
+            self.load_design_part(part)
+        } else {
+            let written = match part {
+                DesignPart::Objects => self.write_world_objects_json(),
+                DesignPart::Structure => self.write_struct_json(),
+                DesignPart::Foreshadows => self.write_foreshadows(),
+                DesignPart::Milestones => self.sync_milestones_to_json(),
+            };
+            if part == DesignPart::Structure {
+                self.struct_json_snapshot = serde_json::to_string(&self.struct_roots).ok();
+            }
+            written
+        };
+        self.notify(result);
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+use std::sync::mpsc::TryRecvError;
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::synopsis::{build_synopsis, compress_prompt, trim_synopsis, SynopsisOptions};
+
+use super::{count_words, node_at, rfd_save_file, AppError, IoOp, LlmTask, SynopsisDialog, TextToolApp};
+
+// ── 生成梗概: a structure subtree as a synopsis ───────────────────────────────
+//
+// The summaries below a node, joined into prose, in an editable window.  The
+// text can be trimmed to the target length or, as a second step, compressed
+// by the LLM; the window keeps whatever the user edits.
+
+impl TextToolApp {
+    /// Open the window for the node at `path`.
+    pub(super) fn open_synopsis(&mut self, path: &[usize]) {
+        let Some(node) = node_at(&self.struct_roots, path) else { return };
+        let options = SynopsisOptions::default();
+        let text = build_synopsis(node, &options);
+        if text.is_empty() {
+            self.status = format!("「{}」及其下级节点都没有摘要，无法生成梗概", node.title);
+            return;
+        }
+        self.synopsis = Some(SynopsisDialog {
+            title: node.title.clone(),
+            node: node.clone(),
+            options,
+            text,
+            task: None,
+        });
+    }
+
+    pub(super) fn draw_synopsis_dialog(&mut self, ctx: &Context) {
+        let Some(dlg) = &mut self.synopsis else { return };
+
+        // Poll the 压缩 request.
+        let mut failed = None;
+        if let Some(task) = &dlg.task {
+            match task.receiver.try_recv() {
+                Ok(Ok(text)) => {
+                    dlg.text = text.trim().to_owned();
+                    dlg.task = None;
+                    self.status = "梗概已由 LLM 压缩".to_owned();
+                }
+                Ok(Err(e)) => {
+                    dlg.task = None;
+                    failed = Some(e);
+                }
+                Err(TryRecvError::Empty) => ctx.request_repaint(),
+                Err(TryRecvError::Disconnected) => {
+                    dlg.task = None;
+                    self.status = "LLM 后台线程意外断开".to_owned();
+                }
+            }
+        }
+        if let Some(e) = failed {
+            self.report(e);
+        }
+
+        let backend = self.make_llm_backend();
+        let config = self.llm_config.clone();
+        let Some(dlg) = &mut self.synopsis else { return };
+        let mut open = true;
+        let mut copy = false;
+        let mut save_as = false;
+        let mut compress = false;
+        egui::Window::new(format!("生成梗概 — {}", dlg.title))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new("由节点及其下级节点的摘要按顺序拼接，不含正文。可直接编辑。")
+                    .small().color(Color32::from_gray(150)));
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut dlg.options.titles, "包含章节标题").changed() {
+                        dlg.text = build_synopsis(&dlg.node, &dlg.options);
+                    }
+                    ui.separator();
+                    ui.label("目标字数:");
+                    ui.add(egui::DragValue::new(&mut dlg.options.target_words).range(100..=20_000).speed(50));
+                });
+                let words = count_words(&dlg.text);
+                let target = dlg.options.target_words;
+                let color = if words <= target { Color32::from_rgb(120, 190, 120) } else { Color32::from_rgb(230, 160, 60) };
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("当前 {words} 字 / 目标 {target} 字")).color(color));
+                    if words > target && ui.small_button("按目标截断").on_hover_text("从末尾整句删除，直到不超过目标字数").clicked() {
+                        dlg.text = trim_synopsis(&dlg.text, target);
+                    }
+                    if ui.small_button("重新生成").on_hover_text("按当前结构重新拼接摘要，放弃编辑").clicked() {
+                        dlg.text = build_synopsis(&dlg.node, &dlg.options);
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    ui.add_enabled(
+                        dlg.task.is_none(),
+                        egui::TextEdit::multiline(&mut dlg.text).desired_width(f32::INFINITY).desired_rows(14),
+                    );
+                });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("复制").clicked() {
+                        copy = true;
+                    }
+                    if ui.button("保存为文件…").clicked() {
+                        save_as = true;
+                    }
+                    ui.separator();
+                    if dlg.task.is_some() {
+                        ui.add(egui::Spinner::new());
+                        if ui.button("⏹ 取消").clicked() {
+                            dlg.task = None;
+                        }
+                    } else if ui.button(format!("🤖 压缩到约 {target} 字"))
+                        .on_hover_text(format!("用当前 LLM 后端（{}）改写为更短的梗概", backend.name()))
+                        .clicked()
+                    {
+                        compress = true;
+                    }
+                });
+            });
+
+        if compress {
+            let prompt = compress_prompt(&dlg.text, dlg.options.target_words);
+            dlg.task = Some(LlmTask::spawn(backend, config, prompt));
+            self.status = "正在用 LLM 压缩梗概…".to_owned();
+        }
+        if copy {
+            ctx.copy_text(dlg.text.clone());
+            self.status = "梗概已复制到剪贴板".to_owned();
+        }
+        if save_as {
+            if let Some(dest) = rfd_save_file(&PathBuf::from(format!("{}梗概.md", dlg.title))) {
+                let text = format!("{}\n", dlg.text.trim_end());
+                match text_tool_core::safe_write(&dest, text) {
+                    Ok(_) => self.status = format!("已保存梗概到 {}", dest.display()),
+                    Err(e) => self.report(AppError::Io { op: IoOp::Write, path: dest, source: e }),
+                }
+            }
+        }
+        if !open {
+            self.synopsis = None;
+        }
+    }
+}
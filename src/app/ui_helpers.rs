@@ -1,5 +1,6 @@
 use egui::{Context, RichText, Color32, Key};
-use super::{TextToolApp, Panel, rfd_pick_folder, rfd_save_file};
+use text_tool_core::markdown::shift_heading_levels;
+use super::{TextToolApp, Panel, MermaidExport, AppError, IoOp, rfd_pick_folder, rfd_save_file, format_relative_time, unix_now};
 
 /// Minimum Ctrl+scroll delta (in points) required to adjust the font size by one step.
 const CTRL_SCROLL_THRESHOLD: f32 = 1.0;
@@ -17,6 +18,13 @@ impl TextToolApp {
                         }
                         ui.close_menu();
                     }
+                    if ui.button("在新窗口打开项目…")
+                        .on_hover_text("另开一个窗口打开其他项目，两边的文件与设定数据互不影响")
+                        .clicked()
+                    {
+                        self.new_window_request = rfd_pick_folder();
+                        ui.close_menu();
+                    }
                     if ui.button("📋 新建项目（模板）…")
                         .on_hover_text("使用短篇或长篇模板快速创建项目文件结构")
                         .clicked()
@@ -24,6 +32,13 @@ impl TextToolApp {
                         self.show_template_dialog = true;
                         ui.close_menu();
                     }
+                    if ui.button("✨ 新建示例项目…")
+                        .on_hover_text("在所选文件夹中创建带示例章节、人物与大纲的项目，适合初次使用")
+                        .clicked()
+                    {
+                        self.create_sample_project();
+                        ui.close_menu();
+                    }
                     ui.separator();
                     if ui.button("新建文件…").clicked() {
                         if let Some(root) = self.project_root.clone() {
@@ -33,21 +48,55 @@ impl TextToolApp {
                         }
                         ui.close_menu();
                     }
+                    ui.menu_button("最近编辑的文件", |ui| {
+                        self.draw_recent_files_menu(ui);
+                    });
                     ui.separator();
                     if ui.button("保存  Ctrl+S").clicked() {
                         self.save_left();
                         ui.close_menu();
                     }
+                    if ui.add_enabled(self.left_file.is_some(), egui::Button::new("重新载入当前文件"))
+                        .on_hover_text("放弃未保存的修改，从磁盘重新读取")
+                        .clicked()
+                    {
+                        self.request_reload(true);
+                        ui.close_menu();
+                    }
                     ui.separator();
                     if ui.button("导出章节合集…").clicked() {
-                        self.export_chapters_merged();
+                        if let Some(r) = self.export_chapters_merged().transpose() {
+                            self.notify(r);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("导出世界对象 CSV…").clicked() {
+                        if let Some(r) = self.export_objects_csv().transpose() {
+                            self.notify(r);
+                        }
                         ui.close_menu();
                     }
                     if ui.button("备份项目到文件夹…").clicked() {
-                        self.backup_project();
+                        if let Some(r) = self.backup_project().transpose() {
+                            self.notify(r);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("创建项目快照 (zip)…")
+                        .on_hover_text("把整个项目打包为带时间戳的 zip 文件（排除规则见 设置 → 项目快照）")
+                        .clicked()
+                    {
+                        self.create_snapshot();
                         ui.close_menu();
                     }
                     ui.separator();
+                    if ui.button("导入 DOCX…")
+                        .on_hover_text("把 Word 文稿转换为 Markdown 放入 Content，可按一级标题拆分为章节文件")
+                        .clicked()
+                    {
+                        self.import_docx();
+                        ui.close_menu();
+                    }
                     if ui.button("导出当前文件…").clicked() {
                         self.export_left();
                         ui.close_menu();
@@ -63,6 +112,13 @@ impl TextToolApp {
                             ui.close_menu();
                         }
                     }
+                    ui.separator();
+                    if ui.checkbox(&mut self.settings.markdown.design_markdown_preview, "设计面板富文本预览")
+                        .on_hover_text("摘要、筹备笔记、描述、背景等字段显示渲染后的 Markdown，点 ✏ 编辑")
+                        .changed()
+                    {
+                        self.save_config();
+                    }
                 });
 
                 ui.menu_button("工具", |ui| {
@@ -70,42 +126,177 @@ impl TextToolApp {
                         self.extract_structure_from_left();
                         ui.close_menu();
                     }
-                    if ui.button("从文件夹结构生成章节").clicked() {
-                        self.sync_struct_from_folders();
+                    ui.menu_button("同步选中部分大纲", |ui| {
+                        if ui.button("提取为章节结构")
+                            .on_hover_text("只提取左侧光标或选区所在标题及其下级标题，替换现有结构")
+                            .clicked()
+                        {
+                            self.extract_structure_from_selection(ui.ctx(), false);
+                            ui.close_menu();
+                        }
+                        if ui.button("合并到现有结构")
+                            .on_hover_text("按标题找到现有结构中的对应节点，补入新增的下级标题，已有节点的信息保留")
+                            .clicked()
+                        {
+                            self.extract_structure_from_selection(ui.ctx(), true);
+                            ui.close_menu();
+                        }
+                    });
+                    if ui.button("从 Content 目录生成结构…").clicked() {
+                        self.open_content_import();
+                        ui.close_menu();
+                    }
+                    if ui.button("生成目录文件").on_hover_text("写入 Content/目录.md").clicked() {
+                        self.update_content_index();
+                        ui.close_menu();
+                    }
+                    if ui.button("生成进度报告").clicked() {
+                        self.generate_progress_report();
+                        ui.close_menu();
+                    }
+                    if ui.button("词频分析…")
+                        .on_hover_text("统计监视词与高频词组（左侧文件；未打开文件时为整个项目）")
+                        .clicked()
+                    {
+                        self.open_word_frequency();
+                        ui.close_menu();
+                    }
+                    if ui.button("检查文档内链接…")
+                        .on_hover_text("查找 Content/ 中指向不存在的文件或标题的相对链接")
+                        .clicked()
+                    {
+                        self.open_link_check();
+                        ui.close_menu();
+                    }
+                    if ui.button("查找重复段落…")
+                        .on_hover_text("查找 Content/ 中在多处出现的相同段落")
+                        .clicked()
+                    {
+                        self.open_duplicates();
+                        ui.close_menu();
+                    }
+                    if ui.button("对比大纲与结构…")
+                        .on_hover_text("按标题对比左侧 Markdown 大纲与章节结构树，列出缺失与层级不同的条目")
+                        .clicked()
+                    {
+                        self.open_outline_compare();
+                        ui.close_menu();
+                    }
+                    if ui.button("📜 变更记录…")
+                        .on_hover_text("回顾对象、结构、关联与伏笔的改动")
+                        .clicked()
+                    {
+                        self.open_change_log();
+                        ui.close_menu();
+                    }
+                    if ui.button("段落格式整理").clicked() {
+                        self.open_paragraph_format_dialog();
+                        ui.close_menu();
+                    }
+                    if ui.button("提升标题级别  Ctrl+Shift+[").clicked() {
+                        self.shift_headings(ui.ctx(), true);
+                        ui.close_menu();
+                    }
+                    if ui.button("降低标题级别  Ctrl+Shift+]").clicked() {
+                        self.shift_headings(ui.ctx(), false);
+                        ui.close_menu();
+                    }
+                    if ui.button("按标题拆分文件…").clicked() {
+                        self.open_split_dialog();
+                        ui.close_menu();
+                    }
+                    if ui.button("插入章节链接…  Ctrl+K").clicked() {
+                        self.open_link_picker();
+                        ui.close_menu();
+                    }
+                    if ui.button("规范化名称")
+                        .on_hover_text("去除对象、章节、伏笔名称及其引用中多余的空白")
+                        .clicked()
+                    {
+                        self.normalize_names();
                         ui.close_menu();
                     }
                     ui.separator();
+                    if ui.button("导出结构为大纲文档…").clicked() {
+                        self.open_outline_export();
+                        ui.close_menu();
+                    }
+                    if ui.button("导出设定 Wiki (HTML)…")
+                        .on_hover_text("世界对象、章节结构与伏笔导出为单个可互相跳转的网页")
+                        .clicked()
+                    {
+                        if let Some(r) = self.export_setting_wiki().transpose() {
+                            self.notify(r);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("导出关系矩阵…")
+                        .on_hover_text("所选对象两两之间的关联类型，导出为 Markdown 表格或 CSV")
+                        .clicked()
+                    {
+                        self.open_relation_matrix();
+                        ui.close_menu();
+                    }
+                    for kind in [MermaidExport::Relations, MermaidExport::Structure] {
+                        ui.menu_button(kind.label(), |ui| {
+                            if ui.button(format!("写入 Design/{}", kind.file_name())).clicked() {
+                                self.export_mermaid(ctx, kind, false);
+                                ui.close_menu();
+                            }
+                            if ui.button("复制到剪贴板").clicked() {
+                                self.export_mermaid(ctx, kind, true);
+                                ui.close_menu();
+                            }
+                        });
+                    }
+                    ui.separator();
                     if ui.button("保存世界对象到 JSON").clicked() {
-                        self.sync_world_objects_to_json();
+                        let r = self.sync_world_objects_to_json();
+                        self.notify(r);
                         ui.close_menu();
                     }
                     if ui.button("保存章节结构到 JSON").clicked() {
-                        self.sync_struct_to_json();
+                        let r = self.sync_struct_to_json();
+                        self.notify(r);
                         ui.close_menu();
                     }
-                    if ui.button("保存伏笔到 MD").clicked() {
-                        self.sync_foreshadows_to_md();
+                    if ui.button("保存伏笔到 JSON").clicked() {
+                        let r = self.sync_foreshadows();
+                        self.notify(r);
                         ui.close_menu();
                     }
                     if ui.button("保存里程碑到 JSON").clicked() {
-                        self.sync_milestones_to_json();
+                        let r = self.sync_milestones_to_json();
+                        self.notify(r);
                         ui.close_menu();
                     }
                     ui.separator();
                     if ui.button("从 JSON 加载世界对象").clicked() {
-                        self.load_world_objects_from_json();
+                        let r = self.load_world_objects_from_json();
+                        self.notify(r);
                         ui.close_menu();
                     }
                     if ui.button("从 JSON 加载章节结构").clicked() {
-                        self.load_struct_from_json();
+                        let r = self.load_struct_from_json();
+                        self.notify(r);
                         ui.close_menu();
                     }
-                    if ui.button("从 MD 加载伏笔").clicked() {
-                        self.load_foreshadows_from_md();
+                    if ui.button("从 JSON 加载伏笔").clicked() {
+                        let r = self.load_foreshadows();
+                        self.notify(r);
                         ui.close_menu();
                     }
                     if ui.button("从 JSON 加载里程碑").clicked() {
-                        self.load_milestones_from_json();
+                        let r = self.load_milestones_from_json();
+                        self.notify(r);
+                        ui.close_menu();
+                    }
+                    if ui.button("从 project.json 加载")
+                        .on_hover_text("载入自动保存一并写入的世界对象、章节结构与伏笔")
+                        .clicked()
+                    {
+                        let r = self.load_project_data();
+                        self.notify(r);
                         ui.close_menu();
                     }
                 });
@@ -120,6 +311,102 @@ impl TextToolApp {
         });
     }
 
+    /// Quick-access strip under the menu bar listing pinned objects and files.
+    /// Click to jump, drag to reorder, middle-click to unpin.
+    pub(super) fn draw_pin_strip(&mut self, ctx: &Context) {
+        /// Drag payload for reordering pins (objects and files never mix).
+        struct PinDrag { file: bool, idx: usize }
+
+        let pinned_objs: Vec<(usize, String)> = self.world_objects.iter().enumerate()
+            .filter(|(_, o)| o.pinned)
+            .map(|(i, o)| (i, format!("{} {}", self.project_config.kind_icon(&o.kind), o.name)))
+            .collect();
+        let pinned_files = self.project_config.pinned_files.clone();
+        if pinned_objs.is_empty() && pinned_files.is_empty() {
+            return;
+        }
+
+        let mut open_obj: Option<usize> = None;
+        let mut open_file: Option<String> = None;
+        let mut unpin_obj: Option<usize> = None;
+        let mut unpin_file: Option<usize> = None;
+        let mut move_obj: Option<(usize, usize)> = None;
+        let mut move_file: Option<(usize, usize)> = None;
+
+        egui::TopBottomPanel::top("pin_strip").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(RichText::new("⭐").color(Color32::from_rgb(230, 190, 60)));
+                for (i, label) in &pinned_objs {
+                    let ir = ui.dnd_drag_source(egui::Id::new(("pin_obj", *i)), PinDrag { file: false, idx: *i }, |ui| {
+                        ui.selectable_label(false, RichText::new(label).small())
+                    });
+                    if let Some(p) = ir.response.dnd_release_payload::<PinDrag>() {
+                        if !p.file && p.idx != *i { move_obj = Some((p.idx, *i)); }
+                    }
+                    let resp = ir.inner.on_hover_text("单击在世界对象面板中打开  拖动排序  中键取消固定");
+                    if resp.clicked() { open_obj = Some(*i); }
+                    if resp.middle_clicked() { unpin_obj = Some(*i); }
+                }
+                if !pinned_objs.is_empty() && !pinned_files.is_empty() {
+                    ui.separator();
+                }
+                for (k, rel) in pinned_files.iter().enumerate() {
+                    let name = std::path::Path::new(rel).file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| rel.clone());
+                    let ir = ui.dnd_drag_source(egui::Id::new(("pin_file", k)), PinDrag { file: true, idx: k }, |ui| {
+                        ui.selectable_label(false, RichText::new(format!("📄 {name}")).small())
+                    });
+                    if let Some(p) = ir.response.dnd_release_payload::<PinDrag>() {
+                        if p.file && p.idx != k { move_file = Some((p.idx, k)); }
+                    }
+                    let resp = ir.inner.on_hover_text(format!("{rel}\n单击打开  拖动排序  中键取消固定"));
+                    if resp.clicked() { open_file = Some(rel.clone()); }
+                    if resp.middle_clicked() { unpin_file = Some(k); }
+                }
+            });
+        });
+
+        if let Some(i) = open_obj {
+            self.select_obj(i);
+            self.active_panel = Panel::Objects;
+        }
+        if let Some(rel) = open_file {
+            if let Some(root) = self.project_root.clone() {
+                let path = root.join(&rel);
+                let r = self.open_file_in_pane(&path, self.opens_in_left(&path));
+                self.notify_open(r);
+                self.active_panel = Panel::Novel;
+            }
+        }
+        if let Some(i) = unpin_obj {
+            if let Some(obj) = self.world_objects.get_mut(i) { obj.pinned = false; }
+        }
+        if let Some(k) = unpin_file {
+            self.project_config.pinned_files.remove(k);
+            self.save_project_config();
+        }
+        if let Some((from, to)) = move_obj {
+            if from < self.world_objects.len() && to < self.world_objects.len() {
+                let item = self.world_objects.remove(from);
+                self.world_objects.insert(to, item);
+                self.selected_objs = self.selected_objs.iter()
+                    .map(|&s| crate::app::index_after_move(s, from, to))
+                    .collect();
+                self.obj_select_anchor = self.obj_select_anchor
+                    .map(|a| crate::app::index_after_move(a, from, to));
+            }
+        }
+        if let Some((from, to)) = move_file {
+            let files = &mut self.project_config.pinned_files;
+            if from < files.len() && to < files.len() {
+                let item = files.remove(from);
+                files.insert(to, item);
+                self.save_project_config();
+            }
+        }
+    }
+
     pub(super) fn draw_toolbar(&mut self, ctx: &Context) {
         egui::SidePanel::left("toolbar")
             .resizable(false)
@@ -254,6 +541,42 @@ impl TextToolApp {
         }
     }
 
+    pub(super) fn draw_reload_confirm_dialog(&mut self, ctx: &Context) {
+        let Some(left) = self.reload_confirm else { return };
+        let file_name = if left { &self.left_file } else { &self.right_file }.as_ref()
+            .and_then(|f| f.path.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut confirmed = false;
+        let mut cancelled = false;
+
+        egui::Window::new("重新载入文件")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("放弃「{file_name}」未保存的修改，从磁盘重新载入？"));
+                ui.label(
+                    RichText::new("放弃的内容可用 Ctrl+Z 找回。")
+                        .small().color(Color32::from_gray(150)),
+                );
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("重新载入").clicked() { confirmed = true; }
+                    if ui.button("取消").clicked()     { cancelled = true; }
+                });
+                if ctx.input(|i| i.key_pressed(Key::Escape)) { cancelled = true; }
+            });
+
+        if confirmed {
+            self.reload_confirm = None;
+            self.reload_pane(left);
+        } else if cancelled {
+            self.reload_confirm = None;
+        }
+    }
+
     pub(super) fn draw_new_file_dialog(&mut self, ctx: &Context) {
         let mut create_path: Option<std::path::PathBuf> = None;
         let mut close = false;
@@ -289,7 +612,8 @@ impl TextToolApp {
             self.new_file_dialog = None;
         }
         if let Some(p) = create_path {
-            self.create_file(p);
+            let r = self.create_file(p);
+            self.notify(r);
         }
     }
 
@@ -312,11 +636,27 @@ impl TextToolApp {
                 ctrl_scroll,                                        // Ctrl+scroll
                 !ctrl && !shift && i.key_pressed(Key::F2),         // F2 rename
                 ctrl && !shift && i.key_pressed(Key::P),           // Ctrl+P preview toggle
+                ctrl && shift && i.key_pressed(Key::T),            // Ctrl+Shift+T reopen closed file
             )
         });
+        // Ctrl+K: consumed before the editor sees it (TextEdit would delete
+        // to the end of the line).
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, Key::K)) {
+            self.open_link_picker();
+        }
+        // Ctrl+Shift+[ / ]: promote / demote the selected headings.
+        let shift_cmd = egui::Modifiers::COMMAND | egui::Modifiers::SHIFT;
+        if self.last_focused_left {
+            if ctx.input_mut(|i| i.consume_key(shift_cmd, Key::OpenBracket)) {
+                self.shift_headings(ctx, true);
+            }
+            if ctx.input_mut(|i| i.consume_key(shift_cmd, Key::CloseBracket)) {
+                self.shift_headings(ctx, false);
+            }
+        }
         if input.0 {
             self.save_left();
-            if self.md_settings.auto_extract_structure {
+            if self.settings.markdown.auto_extract_structure {
                 self.extract_structure_from_left();
             }
         }
@@ -324,14 +664,14 @@ impl TextToolApp {
         if input.2 {
             // Undo: apply to the last focused pane first
             if self.last_focused_left {
-                if let Some(prev) = self.left_undo_stack.pop_back() {
+                if let Some(prev) = self.left_undo_stack.pop() {
                     if let Some(f) = &mut self.left_file {
                         f.content = prev;
                         f.modified = true;
                         self.status = "撤销 (左侧)".to_owned();
                     }
                 }
-            } else if let Some(prev) = self.right_undo_stack.pop_back() {
+            } else if let Some(prev) = self.right_undo_stack.pop() {
                 if let Some(f) = &mut self.right_file {
                     f.content = prev;
                     f.modified = true;
@@ -347,24 +687,25 @@ impl TextToolApp {
             self.insert_markdown_wrap(ctx, input.4);
         }
         // Tab: insert configurable number of spaces at cursor in left editor
-        if input.6 && self.last_focused_left {
+        // (unless the `@` completion popup claims it)
+        if input.6 && self.last_focused_left && !self.mention_popup_open {
             self.insert_tab_spaces(ctx);
         }
         // Ctrl++ / Ctrl+scroll up: increase font size (editor or preview)
         if input.7 {
             if self.left_preview_mode {
-                self.md_settings.preview_font_size = (self.md_settings.preview_font_size + 1.0).min(36.0);
+                self.settings.markdown.preview_font_size = (self.settings.markdown.preview_font_size + 1.0).min(36.0);
             } else {
-                self.md_settings.editor_font_size = (self.md_settings.editor_font_size + 1.0).min(36.0);
+                self.settings.markdown.editor_font_size = (self.settings.markdown.editor_font_size + 1.0).min(36.0);
             }
             self.save_config();
         }
         // Ctrl+- / Ctrl+scroll down: decrease font size
         if input.8 {
             if self.left_preview_mode {
-                self.md_settings.preview_font_size = (self.md_settings.preview_font_size - 1.0).max(8.0);
+                self.settings.markdown.preview_font_size = (self.settings.markdown.preview_font_size - 1.0).max(8.0);
             } else {
-                self.md_settings.editor_font_size = (self.md_settings.editor_font_size - 1.0).max(8.0);
+                self.settings.markdown.editor_font_size = (self.settings.markdown.editor_font_size - 1.0).max(8.0);
             }
             self.save_config();
         }
@@ -372,9 +713,9 @@ impl TextToolApp {
         if input.9 {
             let def = crate::app::MarkdownSettings::default();
             if self.left_preview_mode {
-                self.md_settings.preview_font_size = def.preview_font_size;
+                self.settings.markdown.preview_font_size = def.preview_font_size;
             } else {
-                self.md_settings.editor_font_size = def.editor_font_size;
+                self.settings.markdown.editor_font_size = def.editor_font_size;
             }
             self.save_config();
         }
@@ -382,10 +723,10 @@ impl TextToolApp {
         if input.10.abs() > CTRL_SCROLL_THRESHOLD {
             let delta = if input.10 > 0.0 { 1.0_f32 } else { -1.0_f32 };
             if self.left_preview_mode {
-                self.md_settings.preview_font_size = (self.md_settings.preview_font_size + delta)
+                self.settings.markdown.preview_font_size = (self.settings.markdown.preview_font_size + delta)
                     .clamp(8.0, 36.0);
             } else {
-                self.md_settings.editor_font_size = (self.md_settings.editor_font_size + delta)
+                self.settings.markdown.editor_font_size = (self.settings.markdown.editor_font_size + delta)
                     .clamp(8.0, 36.0);
             }
             self.save_config();
@@ -409,6 +750,47 @@ impl TextToolApp {
                 self.left_preview_mode = !self.left_preview_mode;
             }
         }
+        if input.13 {
+            self.reopen_closed_file();
+        }
+    }
+
+    /// Entries of 文件 ▸ 最近编辑的文件; files that no longer exist are
+    /// dropped from the list.
+    fn draw_recent_files_menu(&mut self, ui: &mut egui::Ui) {
+        let Some(root) = self.project_root.clone() else {
+            ui.label(RichText::new("未打开项目").color(Color32::GRAY));
+            return;
+        };
+        let before = self.project_config.recent_files.len();
+        self.project_config.recent_files.retain(|r| root.join(&r.path).is_file());
+        if self.project_config.recent_files.len() != before {
+            self.save_project_config();
+        }
+        if self.project_config.recent_files.is_empty() {
+            ui.label(RichText::new("暂无记录").color(Color32::GRAY));
+        }
+        let now = unix_now();
+        let mut open: Option<std::path::PathBuf> = None;
+        for recent in &self.project_config.recent_files {
+            let label = format!("{}  ·  {}", recent.path, format_relative_time(recent.at, now));
+            if ui.button(label).clicked() {
+                open = Some(root.join(&recent.path));
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+        if ui.add_enabled(!self.closed_files.is_empty(), egui::Button::new("重新打开关闭的文件  Ctrl+Shift+T"))
+            .clicked()
+        {
+            self.reopen_closed_file();
+            ui.close_menu();
+        }
+        if let Some(path) = open {
+            let r = self.open_file_in_pane(&path, self.opens_in_left(&path));
+            self.notify_open(r);
+            self.active_panel = Panel::Novel;
+        }
     }
 
     /// Insert `**...**` (bold) or `*...*` (italic) around the current selection
@@ -445,9 +827,31 @@ impl TextToolApp {
         }
     }
 
-    /// Insert spaces (matching `md_settings.tab_size`) at the cursor in the left editor.
+    /// 提升 / 降低标题级别: shift the headings on the selected lines of the
+    /// left editor (or the caret's line) by one level, as one undo step.
+    pub(super) fn shift_headings(&mut self, ctx: &Context, promote: bool) {
+        let te_id = egui::Id::new("left_editor_main");
+        let Some(f) = &mut self.left_file else { return };
+        let mut state = egui::text_edit::TextEditState::load(ctx, te_id).unwrap_or_default();
+        let Some(range) = state.cursor.char_range() else { return };
+        let (from, to) = (range.primary.index, range.secondary.index);
+        let Some((text, a, b)) = shift_heading_levels(&f.content, from.min(to), from.max(to), promote) else {
+            self.status = if promote { "没有可提升的标题" } else { "没有可降低的标题" }.to_owned();
+            return;
+        };
+        self.left_undo_stack.push(std::mem::replace(&mut f.content, text), self.settings.behavior.undo_limit);
+        f.modified = true;
+        // Keep the selection direction.
+        let (primary, secondary) = if range.primary.index >= range.secondary.index { (b, a) } else { (a, b) };
+        state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+            egui::text::CCursor::new(secondary), egui::text::CCursor::new(primary))));
+        egui::text_edit::TextEditState::store(state, ctx, te_id);
+        self.status = if promote { "已提升标题级别" } else { "已降低标题级别" }.to_owned();
+    }
+
+    /// Insert spaces (matching `settings.markdown.tab_size`) at the cursor in the left editor.
     fn insert_tab_spaces(&mut self, ctx: &Context) {
-        let spaces: String = " ".repeat(self.md_settings.tab_size as usize);
+        let spaces: String = " ".repeat(self.settings.markdown.tab_size as usize);
         let te_id = egui::Id::new("left_editor_main");
         if let Some(mut state) = egui::text_edit::TextEditState::load(ctx, te_id) {
             if let Some(range) = state.cursor.char_range() {
@@ -471,187 +875,16 @@ impl TextToolApp {
         }
     }
 
-    pub(super) fn export_left(&self) {
-        if let Some(f) = &self.left_file {
-            if let Some(dest) = rfd_save_file(&f.path) {
-                if let Err(e) = std::fs::write(&dest, &f.content) {
-                    eprintln!("导出失败: {e}");
-                }
-            }
+    pub(super) fn export_left(&mut self) {
+        let Some(f) = &self.left_file else { return };
+        if let Some(dest) = rfd_save_file(&f.path) {
+            let r = text_tool_core::safe_write(&dest, &f.content)
+                .map(|_| format!("已导出到 {}", dest.display()))
+                .map_err(AppError::io(IoOp::Write, &dest));
+            self.notify(r);
         }
     }
 
-    /// Draw the floating editor settings window.
-    pub(super) fn draw_settings_window(&mut self, ctx: &Context) {
-        if !self.show_settings_window {
-            return;
-        }
-
-        let mut open = self.show_settings_window;
-        egui::Window::new("⚙ 编辑器设置")
-            .open(&mut open)
-            .collapsible(false)
-            .resizable(false)
-            .min_width(320.0)
-            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-            .show(ctx, |ui| {
-                ui.add_space(4.0);
-
-                // ── Editor ────────────────────────────────────────────────────
-                ui.heading("编辑器");
-                ui.add_space(2.0);
-                ui.horizontal(|ui| {
-                    ui.label("编辑器字体大小:");
-                    let prev_esz = self.md_settings.editor_font_size;
-                    ui.add(
-                        egui::Slider::new(&mut self.md_settings.editor_font_size, 8.0..=36.0)
-                            .step_by(1.0)
-                            .suffix(" px"),
-                    );
-                    if (self.md_settings.editor_font_size - prev_esz).abs() > f32::EPSILON {
-                        self.save_config();
-                    }
-                });
-                ui.horizontal(|ui| {
-                    ui.label("Tab 缩进空格数:");
-                    let prev_tab = self.md_settings.tab_size;
-                    let mut tab_size = self.md_settings.tab_size as u32;
-                    ui.add(egui::Slider::new(&mut tab_size, 1..=8).step_by(1.0));
-                    self.md_settings.tab_size = tab_size as u8;
-                    if self.md_settings.tab_size != prev_tab { self.save_config(); }
-                });
-                ui.add_space(2.0);
-                let prev_ae = self.md_settings.auto_extract_structure;
-                ui.checkbox(
-                    &mut self.md_settings.auto_extract_structure,
-                    "Ctrl+S 保存时自动从 Markdown 标题提取章节结构",
-                );
-                if self.md_settings.auto_extract_structure != prev_ae { self.save_config(); }
-                ui.label(
-                    RichText::new("Ctrl+滚轮 / Ctrl+= / Ctrl+- 实时调整字体大小  Ctrl+P 切换预览")
-                        .small().color(Color32::from_gray(140)),
-                );
-
-                ui.add_space(6.0);
-                ui.separator();
-
-                // ── Markdown preview ───────────────────────────────────────────
-                ui.heading("Markdown 预览");
-                ui.add_space(2.0);
-                ui.horizontal(|ui| {
-                    ui.label("预览字体大小:");
-                    let prev_size = self.md_settings.preview_font_size;
-                    ui.add(
-                        egui::Slider::new(&mut self.md_settings.preview_font_size, 8.0..=36.0)
-                            .step_by(1.0)
-                            .suffix(" px"),
-                    );
-                    if (self.md_settings.preview_font_size - prev_size).abs() > f32::EPSILON {
-                        self.save_config();
-                    }
-                });
-                ui.add_space(2.0);
-                let prev = self.md_settings.default_to_preview;
-                ui.checkbox(
-                    &mut self.md_settings.default_to_preview,
-                    "打开 Markdown 文件时默认切换到预览模式",
-                );
-                if self.md_settings.default_to_preview != prev { self.save_config(); }
-
-                ui.add_space(6.0);
-                ui.separator();
-
-                // ── 自动保存 ─────────────────────────────────────────────────────
-                ui.heading("自动保存");
-                ui.add_space(2.0);
-                ui.horizontal(|ui| {
-                    ui.label("自动保存间隔:");
-                    let prev_int = self.md_settings.auto_save_interval_secs;
-                    let mut interval = self.md_settings.auto_save_interval_secs as u32;
-                    ui.add(
-                        egui::Slider::new(&mut interval, 0..=300)
-                            .step_by(10.0)
-                            .suffix(" 秒"),
-                    );
-                    self.md_settings.auto_save_interval_secs = interval;
-                    if self.md_settings.auto_save_interval_secs != prev_int {
-                        self.save_config();
-                    }
-                });
-                ui.label(
-                    RichText::new("0 = 关闭自动保存；状态栏显示上次自动保存时间")
-                        .small().color(Color32::from_gray(140)),
-                );
-
-                ui.add_space(6.0);
-                ui.separator();
-
-                // ── 主题 ─────────────────────────────────────────────────────────
-                ui.heading("界面主题");
-                ui.add_space(2.0);
-                ui.horizontal(|ui| {
-                    let prev_theme = self.theme;
-                    for &t in crate::app::AppTheme::all() {
-                        ui.radio_value(&mut self.theme, t, t.label());
-                    }
-                    if self.theme != prev_theme { self.save_config(); }
-                });
-
-                ui.add_space(6.0);
-                ui.separator();
-
-                // ── File tree ──────────────────────────────────────────────────
-                ui.heading("文件树");
-                ui.add_space(2.0);
-                let prev_hide = self.md_settings.hide_json;
-                ui.checkbox(
-                    &mut self.md_settings.hide_json,
-                    "隐藏 .json 文件（推荐：JSON 为内部数据，无需手动编辑）",
-                );
-                if self.md_settings.hide_json != prev_hide {
-                    self.refresh_tree();
-                    self.save_config();
-                }
-                let prev_files_tab = self.md_settings.show_files_tab;
-                ui.checkbox(
-                    &mut self.md_settings.show_files_tab,
-                    "在导航中显示「文件」标签页（默认关闭，使用章节树导航）",
-                );
-                if self.md_settings.show_files_tab != prev_files_tab {
-                    self.save_config();
-                }
-
-                ui.add_space(6.0);
-                ui.separator();
-
-                // ── Data sync ─────────────────────────────────────────────────
-                ui.heading("数据同步");
-                ui.add_space(2.0);
-                let prev_al = self.auto_load_from_files;
-                ui.checkbox(
-                    &mut self.auto_load_from_files,
-                    "打开项目时自动从文件反向同步数据",
-                );
-                if self.auto_load_from_files != prev_al { self.save_config(); }
-
-                ui.add_space(8.0);
-                ui.separator();
-                ui.add_space(4.0);
-
-                if ui.button("重置默认值").clicked() {
-                    self.md_settings = crate::app::MarkdownSettings::default();
-                    self.refresh_tree();
-                    self.save_config();
-                }
-            });
-
-        // Detect window close via X button and save config
-        if !open && self.show_settings_window {
-            self.save_config();
-        }
-        self.show_settings_window = open;
-    }
-
     /// Draw the rename file dialog (triggered by F2 or context menu).
     pub(super) fn draw_rename_dialog(&mut self, ctx: &Context) {
         let mut do_rename: Option<(std::path::PathBuf, String)> = None;
@@ -702,8 +935,10 @@ impl TextToolApp {
         if !self.show_search { return; }
 
         let mut open = self.show_search;
-        let mut run_search = false;
-        let mut open_file: Option<std::path::PathBuf> = None;
+        // Re-run a previous search once the index has moved on.
+        let mut run_search = self.search_generation
+            .is_some_and(|g| g != self.project_index.generation());
+        let mut open_file: Option<(std::path::PathBuf, usize)> = None;
 
         egui::Window::new("🔍 全文搜索")
             .open(&mut open)
@@ -723,6 +958,10 @@ impl TextToolApp {
                     if ui.button("搜索").clicked() {
                         run_search = true;
                     }
+                    if self.project_index.is_scanning() {
+                        ui.spinner();
+                        ui.label(RichText::new("索引中…").small().color(Color32::GRAY));
+                    }
                 });
                 ui.separator();
 
@@ -741,7 +980,7 @@ impl TextToolApp {
                                 RichText::new(&label).monospace().small())
                                 .on_hover_text(result.file_path.display().to_string());
                             if resp.double_clicked() {
-                                open_file = Some(result.file_path.clone());
+                                open_file = Some((result.file_path.clone(), result.line_no));
                             }
                         }
                     });
@@ -750,8 +989,8 @@ impl TextToolApp {
 
         self.show_search = open;
         if run_search { self.run_search(); }
-        if let Some(path) = open_file {
-            self.open_file_in_pane(&path, true);
+        if let Some((path, line_no)) = open_file {
+            self.open_at_line(&path, line_no);
         }
     }
 
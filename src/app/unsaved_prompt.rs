@@ -0,0 +1,321 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, Context, Key, RichText, ViewportCommand};
+
+use super::{AppError, OpenFile, OutlineCompareView, Panel, TextToolApp};
+
+// ── 未保存的修改: ask before edits in a pane are lost ────────────────────────
+//
+// Closing a window, or opening another file into a pane, drops what the
+// panes hold.  When that is unsaved text, the close is cancelled or the open
+// deferred, and a dialog offers 保存 / 不保存 / 取消; the first two carry the
+// action out.  A deferred open takes along what its caller meant to do with
+// the file next (jump to a line, show the 写作上下文, …), which runs once
+// the file is in.  The 本次写作小结 goes first on closing, so the question
+// comes when the app is really about to quit.
+
+/// Whether an action that drops pane contents can go ahead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClosePolicy {
+    Proceed,
+    /// Ask 保存 / 不保存 / 取消 first.
+    Ask,
+}
+
+impl ClosePolicy {
+    /// Closing the window drops both panes.
+    pub fn for_close(left_unsaved: bool, right_unsaved: bool) -> Self {
+        Self::ask_if(left_unsaved || right_unsaved)
+    }
+
+    /// Opening a file into a pane drops that pane only, also when it is
+    /// the same file: the text is read from disk again.
+    pub fn for_open(pane_unsaved: bool) -> Self {
+        Self::ask_if(pane_unsaved)
+    }
+
+    fn ask_if(unsaved: bool) -> Self {
+        if unsaved { ClosePolicy::Ask } else { ClosePolicy::Proceed }
+    }
+}
+
+/// Whether the pane holds edits not on disk; generated buffers have no
+/// file to save to and never count.
+fn unsaved(pane: &Option<OpenFile>) -> bool {
+    pane.as_ref().is_some_and(|f| f.modified && !f.scratch)
+}
+
+fn file_label(path: &Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned())
+}
+
+/// What became of a request to open a file into a pane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaneOpen {
+    /// The file is in the pane; the status message.
+    Opened(String),
+    /// Held back by the 未保存的修改 question; it opens if the user answers
+    /// 保存 or 不保存.
+    Deferred,
+}
+
+/// What to do once a file opened with
+/// [`open_file_then`](TextToolApp::open_file_then) is in its pane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AfterOpen {
+    /// Scroll to and flash this 1-based line.
+    ScrollTo(usize),
+    /// Show the 写作上下文 of the node titled so in the right pane.
+    WritingContext(String),
+    /// Compare the file against the chapter structure.
+    CompareOutline,
+}
+
+/// The action held back by the dialog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnsavedAction {
+    CloseWindow,
+    Open { path: PathBuf, left: bool, then: Option<AfterOpen> },
+}
+
+impl UnsavedAction {
+    /// Panes the action drops (`true` for the left one).
+    fn panes(&self) -> Vec<bool> {
+        match self {
+            UnsavedAction::CloseWindow => vec![true, false],
+            UnsavedAction::Open { left, .. } => vec![*left],
+        }
+    }
+}
+
+impl TextToolApp {
+    fn pane_file(&self, left: bool) -> &Option<OpenFile> {
+        if left { &self.left_file } else { &self.right_file }
+    }
+
+    /// Hold back a close request of this window while a pane has unsaved
+    /// text.  A close confirmed in the dialog passes once.
+    pub(super) fn intercept_close_for_unsaved(&mut self, ctx: &Context) {
+        if !ctx.input(|i| i.viewport().close_requested()) || std::mem::take(&mut self.close_confirmed) {
+            return;
+        }
+        let policy = ClosePolicy::for_close(unsaved(&self.left_file), unsaved(&self.right_file));
+        if policy == ClosePolicy::Ask || self.unsaved_prompt.is_some() {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            self.unsaved_prompt.get_or_insert(UnsavedAction::CloseWindow);
+        }
+    }
+
+    /// Ask first, instead of opening `path`, when the pane has unsaved
+    /// text; the open and `then` wait for the answer.
+    pub(super) fn open_file_with(&mut self, path: &Path, left: bool, then: Option<AfterOpen>) -> Result<PaneOpen, AppError> {
+        let pane = self.pane_file(left);
+        if ClosePolicy::for_open(unsaved(pane)) == ClosePolicy::Ask {
+            let name = pane.as_ref().map(|f| file_label(&f.path)).unwrap_or_default();
+            self.unsaved_prompt = Some(UnsavedAction::Open { path: path.to_owned(), left, then });
+            self.status = format!("「{name}」有未保存的修改，请先选择是否保存");
+            return Ok(PaneOpen::Deferred);
+        }
+        let message = self.load_file_into_pane(path, left)?;
+        Ok(PaneOpen::Opened(self.after_open(path, then).unwrap_or(message)))
+    }
+
+    /// Carry out `then` for `path`, now in its pane; its status message, if
+    /// it has one.
+    fn after_open(&mut self, path: &Path, then: Option<AfterOpen>) -> Option<String> {
+        match then? {
+            AfterOpen::ScrollTo(line_no) => {
+                self.request_scroll_to_line(true, line_no);
+                None
+            }
+            AfterOpen::WritingContext(title) => {
+                self.show_writing_context(&title);
+                self.active_panel = Panel::Novel;
+                Some(format!("开始写作「{title}」"))
+            }
+            AfterOpen::CompareOutline => {
+                self.outline_compare = Some(OutlineCompareView { path: path.to_owned(), ignored: Default::default() });
+                None
+            }
+        }
+    }
+
+    /// [`notify`](Self::notify) for an open; a deferred one leaves the
+    /// status to the question.
+    pub(super) fn notify_open(&mut self, result: Result<PaneOpen, AppError>) {
+        match result {
+            Ok(PaneOpen::Opened(message)) => self.status = message,
+            Ok(PaneOpen::Deferred) => {}
+            Err(e) => self.report(e),
+        }
+    }
+
+    pub(super) fn draw_unsaved_prompt(&mut self, ctx: &Context) {
+        let Some(action) = &self.unsaved_prompt else { return };
+        let names: Vec<String> = action.panes().into_iter()
+            .filter(|&left| unsaved(self.pane_file(left)))
+            .filter_map(|left| self.pane_file(left).as_ref().map(|f| file_label(&f.path)))
+            .collect();
+        let question = match action {
+            UnsavedAction::CloseWindow => "关闭窗口前是否保存？",
+            UnsavedAction::Open { .. } => "打开其他文件前是否保存？",
+        };
+
+        let mut save = false;
+        let mut discard = false;
+        let mut cancel = false;
+        egui::Window::new("未保存的修改")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("「{}」有未保存的修改。", names.join("」「")));
+                ui.label(question);
+                ui.label(
+                    RichText::new("不保存则丢弃这些修改。")
+                        .small().color(Color32::from_gray(150)),
+                );
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("保存").clicked()   { save = true; }
+                    if ui.button("不保存").clicked() { discard = true; }
+                    if ui.button("取消").clicked()   { cancel = true; }
+                });
+                if ctx.input(|i| i.key_pressed(Key::Escape)) { cancel = true; }
+            });
+
+        if save {
+            self.resolve_unsaved_prompt(ctx, true);
+        } else if discard {
+            self.resolve_unsaved_prompt(ctx, false);
+        } else if cancel {
+            self.unsaved_prompt = None;
+        }
+    }
+
+    /// 保存 (`save`) or 不保存, then carry out the held-back action.  A
+    /// failed save keeps the panes and drops the action; the status says why.
+    fn resolve_unsaved_prompt(&mut self, ctx: &Context, save: bool) {
+        let Some(action) = self.unsaved_prompt.take() else { return };
+        for left in action.panes() {
+            if !unsaved(self.pane_file(left)) {
+                continue;
+            }
+            if save {
+                if left { self.save_left() } else { self.save_right() }
+                if unsaved(self.pane_file(left)) {
+                    return;
+                }
+            } else if let Some(path) = self.pane_file(left).as_ref().map(|f| f.path.clone()) {
+                // Not offered again by 恢复未保存的修改 either.
+                self.discard_recovery(&path);
+            }
+        }
+        match action {
+            UnsavedAction::CloseWindow => {
+                self.close_confirmed = true;
+                ctx.send_viewport_cmd(ViewportCommand::Close);
+            }
+            UnsavedAction::Open { path, left, then } => match self.load_file_into_pane(&path, left) {
+                Ok(message) => self.status = self.after_open(&path, then).unwrap_or(message),
+                Err(e) => self.report(e),
+            },
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_policy() {
+        assert_eq!(ClosePolicy::for_close(false, false), ClosePolicy::Proceed);
+        assert_eq!(ClosePolicy::for_close(true, false), ClosePolicy::Ask);
+        assert_eq!(ClosePolicy::for_close(false, true), ClosePolicy::Ask);
+        assert_eq!(ClosePolicy::for_open(false), ClosePolicy::Proceed);
+        assert_eq!(ClosePolicy::for_open(true), ClosePolicy::Ask);
+
+        let mut f = OpenFile::new(PathBuf::from("a.md"), String::new());
+        assert!(!unsaved(&Some(f.clone())) && !unsaved(&None));
+        f.modified = true;
+        assert!(unsaved(&Some(f.clone())));
+        f.scratch = true;
+        assert!(!unsaved(&Some(f)));
+    }
+
+    #[test]
+    fn test_open_into_unsaved_pane_asks() {
+        let dir = std::env::temp_dir().join(format!("qingmo_test_unsaved_prompt_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (one, two) = (dir.join("一.md"), dir.join("二.md"));
+        std::fs::write(&one, "一").unwrap();
+        std::fs::write(&two, "二").unwrap();
+
+        let mut app = TextToolApp::blank();
+        app.open_file_in_pane(&one, true).unwrap();
+        app.open_file_in_pane(&two, false).unwrap();
+        let f = app.left_file.as_mut().unwrap();
+        f.content.push_str("，改");
+        f.modified = true;
+
+        // The other pane is not affected; the modified one waits for an answer.
+        app.open_file_in_pane(&one, false).unwrap();
+        assert!(app.unsaved_prompt.is_none());
+        assert_eq!(app.open_file_in_pane(&two, true).unwrap(), PaneOpen::Deferred);
+        assert_eq!(app.unsaved_prompt, Some(UnsavedAction::Open { path: two.clone(), left: true, then: None }));
+        assert_eq!(app.left_file.as_ref().unwrap().path, one);
+
+        // 保存 writes the edit, then opens the file.
+        let ctx = Context::default();
+        app.resolve_unsaved_prompt(&ctx, true);
+        assert_eq!(std::fs::read_to_string(&one).unwrap(), "一，改");
+        assert_eq!(app.left_file.as_ref().unwrap().path, two);
+
+        // 不保存 drops it.
+        let f = app.left_file.as_mut().unwrap();
+        f.content = "丢弃".into();
+        f.modified = true;
+        assert_eq!(app.open_file_in_pane(&one, true).unwrap(), PaneOpen::Deferred);
+        app.resolve_unsaved_prompt(&ctx, false);
+        assert_eq!(std::fs::read_to_string(&two).unwrap(), "二");
+        assert_eq!(app.left_file.as_ref().unwrap().content, "一，改");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_deferred_open_runs_follow_up() {
+        let dir = std::env::temp_dir().join(format!("qingmo_test_unsaved_follow_up_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (one, two) = (dir.join("一.md"), dir.join("二.md"));
+        std::fs::write(&one, "一").unwrap();
+        std::fs::write(&two, "甲\n乙\n丙\n").unwrap();
+
+        let mut app = TextToolApp::blank();
+        app.open_file_in_pane(&one, true).unwrap();
+        let f = app.left_file.as_mut().unwrap();
+        f.content.push_str("，改");
+        f.modified = true;
+
+        // A jump waits with its line, and is no error.
+        app.open_at_line(&two, 3);
+        assert!(app.error_toast.is_none());
+        assert!(app.pane_scroll_request.is_none());
+        assert!(app.status.contains("未保存"), "{}", app.status);
+
+        // 取消 drops both; 不保存 opens the file, then jumps.
+        app.unsaved_prompt = None;
+        app.open_at_line(&two, 3);
+        app.resolve_unsaved_prompt(&Context::default(), false);
+        assert_eq!(app.left_file.as_ref().unwrap().path, two);
+        assert!(app.pane_scroll_request.as_ref().is_some_and(|r| r.left && r.line_no == 3));
+        assert!(app.unsaved_prompt.is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
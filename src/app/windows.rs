@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Context, ViewportBuilder, ViewportCommand, ViewportId};
+
+use super::{crash, ProjectWindow, TextToolApp};
+
+// ── 在新窗口打开项目: extra native windows ─────────────────────────────────────
+//
+// The main window owns every extra window as an immediate viewport with its
+// own `TextToolApp`: project, open files and design data are separate.
+// Settings are app-level: the windows follow the main window's, and a change
+// made in any window is adopted and saved by the main one.
+
+/// Title of a window showing `project`.
+fn window_title(project: Option<&Path>) -> String {
+    match project.and_then(|p| p.file_name()) {
+        Some(name) => format!("清墨 · {}", name.to_string_lossy()),
+        None => "清墨".to_owned(),
+    }
+}
+
+impl TextToolApp {
+    /// Open `path` in a new window, or bring forward the window showing it.
+    fn open_project_window(&mut self, ctx: &Context, path: PathBuf) {
+        if self.project_root.as_ref() == Some(&path) {
+            self.status = "该项目已在当前窗口中打开".to_owned();
+            return;
+        }
+        if let Some(w) = self.project_windows.iter().find(|w| w.app.project_root.as_ref() == Some(&path)) {
+            ctx.send_viewport_cmd_to(w.id, ViewportCommand::Focus);
+            return;
+        }
+        let mut app = TextToolApp::blank();
+        app.secondary = true;
+        app.settings = self.settings.clone();
+        app.llm_config = self.llm_config.clone();
+        app.open_project(path.clone());
+        self.project_windows.push(ProjectWindow {
+            id: ViewportId::from_hash_of(("project_window", &path)),
+            app: Box::new(app),
+            data: Default::default(),
+        });
+        self.status = format!("已在新窗口打开项目: {}", path.display());
+    }
+
+    /// Draw the extra windows (main window only), then open the windows
+    /// requested this frame and drop the closed ones.
+    pub(super) fn draw_project_windows(&mut self, ctx: &Context) {
+        let closing = ctx.input(|i| i.viewport().close_requested()) && self.unsaved_prompt.is_none();
+        if closing && !self.project_windows.is_empty() {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            self.promote_first_window(ctx);
+        }
+
+        let mut closed = Vec::new();
+        for (i, w) in self.project_windows.iter_mut().enumerate() {
+            w.app.settings.clone_from(&self.settings);
+            w.app.llm_config.clone_from(&self.llm_config);
+            let builder = ViewportBuilder::default()
+                .with_title(window_title(w.app.project_root.as_deref()))
+                .with_inner_size([1200.0, 800.0])
+                .with_min_inner_size([800.0, 600.0]);
+            ctx.show_viewport_immediate(w.id, builder, |ctx, _class| {
+                ctx.data_mut(|d| std::mem::swap(d, &mut w.data));
+                w.app.intercept_close_for_unsaved(ctx);
+                w.app.run_frame(ctx);
+                ctx.data_mut(|d| std::mem::swap(d, &mut w.data));
+                if ctx.input(|i| i.viewport().close_requested()) && w.app.unsaved_prompt.is_none() {
+                    closed.push(i);
+                }
+            });
+        }
+
+        let mut changed = false;
+        for w in &self.project_windows {
+            if w.app.settings != self.settings || w.app.llm_config != self.llm_config {
+                self.settings.clone_from(&w.app.settings);
+                self.llm_config.clone_from(&w.app.llm_config);
+                changed = true;
+            }
+        }
+        if changed {
+            self.save_config();
+        }
+
+        for i in closed.into_iter().rev() {
+            self.project_windows.remove(i);
+        }
+        let requests: Vec<PathBuf> = std::iter::once(&mut self.new_window_request)
+            .chain(self.project_windows.iter_mut().map(|w| &mut w.app.new_window_request))
+            .filter_map(Option::take)
+            .collect();
+        for path in requests {
+            self.open_project_window(ctx, path);
+        }
+    }
+
+    /// Closing the main window while extra windows are open: the first
+    /// extra window's state moves into the main window, which stays open.
+    fn promote_first_window(&mut self, ctx: &Context) {
+        let mut next = self.project_windows.remove(0);
+        let others = std::mem::take(&mut self.project_windows);
+        ctx.data_mut(|d| std::mem::swap(d, &mut next.data));
+        std::mem::swap(self, &mut next.app);
+        self.project_windows = others;
+        self.secondary = false;
+        self.last_project = self.project_root.clone();
+        if let Some(root) = &self.project_root {
+            crash::set_crash_dir(Some(root));
+        }
+        self.save_config();
+        ctx.send_viewport_cmd(ViewportCommand::Title(window_title(self.project_root.as_deref())));
+    }
+}
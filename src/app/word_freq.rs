@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+
+use egui::{Color32, Context, RichText};
+use text_tool_core::content_index::INDEX_FILE;
+use text_tool_core::word_freq::{word_frequency, FreqItem};
+
+use super::{TextToolApp, WordFreqSort, WordFreqView};
+
+// ── 词频分析: watched words and frequent phrases ─────────────────────────────
+
+/// `a` against `b` under the table's sort column, ascending.
+fn cmp_items(a: &FreqItem, b: &FreqItem, sort: WordFreqSort) -> Ordering {
+    match sort {
+        WordFreqSort::Phrase => a.phrase.cmp(&b.phrase),
+        WordFreqSort::Count => a.count.cmp(&b.count),
+        // Watched words first.
+        WordFreqSort::Kind => b.watched.cmp(&a.watched).then(b.count.cmp(&a.count)),
+    }
+}
+
+impl TextToolApp {
+    /// 工具 → 词频分析: analyse the left pane, or the whole project when no
+    /// file is open there.
+    pub(super) fn open_word_frequency(&mut self) {
+        let project = self.left_file.is_none();
+        if project && self.project_root.is_none() {
+            self.status = "请先打开一个文件或项目".to_owned();
+            return;
+        }
+        self.word_freq = Some(WordFreqView {
+            project,
+            files: Vec::new(),
+            report: None,
+            pending: None,
+            sort: WordFreqSort::Kind,
+            descending: false,
+        });
+        self.run_word_frequency();
+    }
+
+    /// (Re)compute the report for the window's scope: the left pane's
+    /// buffer at once, the project's saved files on the background index.
+    fn run_word_frequency(&mut self) {
+        let settings = self.settings.behavior.word_frequency.clone();
+        let Some(view) = &mut self.word_freq else { return };
+        if view.project {
+            let Some(root) = self.project_root.clone() else { return };
+            let content_dir = root.join("Content");
+            let index_file = content_dir.join(INDEX_FILE);
+            view.pending = Some(self.project_index.spawn_word_frequency(
+                root, content_dir, move |path| path != index_file, settings,
+            ));
+        } else if let Some(f) = &self.left_file {
+            view.files = vec![f.path.clone()];
+            view.report = Some(word_frequency(&[f.content.as_str()], &settings));
+            view.pending = None;
+        }
+    }
+
+    pub(super) fn draw_word_frequency(&mut self, ctx: &Context) {
+        let Some(view) = &mut self.word_freq else { return };
+        if let Some(rx) = &view.pending {
+            match rx.try_recv() {
+                Ok((files, report)) => {
+                    view.files = files;
+                    view.report = Some(report);
+                    view.pending = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => ctx.request_repaint(),
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => view.pending = None,
+            }
+        }
+
+        let mut open = true;
+        let mut rerun = false;
+        let mut jump = None;
+        let has_left = self.left_file.is_some();
+        let has_project = self.project_root.is_some();
+        egui::Window::new("词频分析")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([420.0, 480.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("范围:");
+                    rerun |= ui.add_enabled(has_left, egui::SelectableLabel::new(!view.project, "左侧文件"))
+                        .clicked() && view.project;
+                    rerun |= ui.add_enabled(has_project, egui::SelectableLabel::new(view.project, "整个项目"))
+                        .on_hover_text("Content/ 下已保存的 Markdown 文件")
+                        .clicked() && !view.project;
+                    if rerun {
+                        view.project = !view.project;
+                    }
+                    ui.separator();
+                    if ui.add_enabled(view.pending.is_none(), egui::Button::new("🔄 重新分析")).clicked() {
+                        rerun = true;
+                    }
+                });
+                ui.label(RichText::new("监视词、停用词与列表长度在 设置 → 行为 → 词频分析 中修改")
+                    .small().color(Color32::from_gray(140)));
+                ui.separator();
+
+                if view.pending.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("正在分析…");
+                    });
+                    return;
+                }
+                let Some(report) = &mut view.report else { return };
+                let files = if view.project {
+                    format!("{} 个文件 · ", view.files.len())
+                } else {
+                    String::new()
+                };
+                ui.label(RichText::new(format!("{files}共 {} 字", report.chars)).small());
+
+                let (sort, descending) = (view.sort, view.descending);
+                report.items.sort_by(|a, b| {
+                    let ord = cmp_items(a, b, sort);
+                    if descending { ord.reverse() } else { ord }
+                });
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    egui::Grid::new("word_freq_table")
+                        .num_columns(5)
+                        .striped(true)
+                        .spacing([14.0, 4.0])
+                        .show(ui, |ui| {
+                            for (label, column) in [
+                                ("词语", Some(WordFreqSort::Phrase)),
+                                ("次数", Some(WordFreqSort::Count)),
+                                ("每万字", None),
+                                ("类型", Some(WordFreqSort::Kind)),
+                            ] {
+                                let Some(column) = column else {
+                                    ui.label(RichText::new(label).strong());
+                                    continue;
+                                };
+                                let arrow = match (view.sort == column, view.descending) {
+                                    (false, _) => "",
+                                    (true, false) => " ▲",
+                                    (true, true) => " ▼",
+                                };
+                                if ui.button(RichText::new(format!("{label}{arrow}")).strong()).clicked() {
+                                    if view.sort == column {
+                                        view.descending = !view.descending;
+                                    } else {
+                                        view.sort = column;
+                                        view.descending = column == WordFreqSort::Count;
+                                    }
+                                }
+                            }
+                            ui.end_row();
+
+                            for item in &report.items {
+                                ui.label(&item.phrase);
+                                ui.label(item.count.to_string());
+                                let rate = item.count as f32 * 10_000.0 / report.chars.max(1) as f32;
+                                ui.label(format!("{rate:.1}"));
+                                if item.watched {
+                                    ui.label(RichText::new("监视词").color(Color32::from_rgb(230, 180, 80)));
+                                } else {
+                                    ui.label(RichText::new("高频").color(Color32::from_gray(160)));
+                                }
+                                match item.first {
+                                    Some((doc, line_no)) => {
+                                        let file = view.files.get(doc);
+                                        let hover = file.map(|p| format!("{} 第 {line_no} 行", p.display()))
+                                            .unwrap_or_default();
+                                        if ui.small_button("跳转").on_hover_text(hover).clicked() {
+                                            jump = file.map(|p| (p.clone(), line_no));
+                                        }
+                                    }
+                                    None => {
+                                        ui.label("");
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        if !open {
+            self.word_freq = None;
+            return;
+        }
+        if rerun {
+            self.run_word_frequency();
+        }
+        if let Some((path, line_no)) = jump {
+            self.open_at_line(&path, line_no);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Headless command-line mode: sync and export without opening a window.
+//!
+//! ```text
+//! text-tool [--project <dir>] sync-outline <md> <json>
+//! text-tool [--project <dir>] compile --out <book.md>
+//! text-tool [--project <dir>] export-objects-csv [--out <file.csv>]
+//! ```
+//!
+//! File arguments are relative to the current directory; `--project`
+//! defaults to it as well.
+
+use std::path::{Path, PathBuf};
+
+use text_tool_core::export::{merge_chapter_files, objects_to_csv};
+use text_tool_core::ignore::IgnoreRules;
+use text_tool_core::outline::{count_nodes, extract_struct_nodes_from_markdown};
+use text_tool_core::WorldObject;
+
+pub const USAGE: &str = "\
+用法:
+  text-tool [--project <目录>] sync-outline <md文件> <json文件>
+  text-tool [--project <目录>] compile --out <输出.md>
+  text-tool [--project <目录>] export-objects-csv [--out <输出.csv>]
+
+不带参数运行时启动图形界面。";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Extract headings from `md` and write the structure tree to `json`.
+    SyncOutline { md: PathBuf, json: PathBuf },
+    /// Merge `Content/*.md` into `out`.
+    Compile { out: PathBuf },
+    /// Write `Design/世界对象.json` as CSV to `out`, or stdout.
+    ExportObjectsCsv { out: Option<PathBuf> },
+    /// Print [`USAGE`].
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub project: PathBuf,
+    pub command: Command,
+}
+
+/// Parse the process arguments (without the program name).
+/// `Ok(None)` means "no subcommand": start the GUI.
+pub fn parse_args(args: &[String]) -> Result<Option<Invocation>, String> {
+    let mut project: Option<PathBuf> = None;
+    let mut out: Option<PathBuf> = None;
+    let mut positional: Vec<&str> = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--project" | "-p" => {
+                let v = iter.next().ok_or("--project 需要一个目录参数")?;
+                project = Some(PathBuf::from(v));
+            }
+            "--out" | "-o" => {
+                let v = iter.next().ok_or("--out 需要一个文件参数")?;
+                out = Some(PathBuf::from(v));
+            }
+            "--help" | "-h" => {
+                return Ok(Some(Invocation { project: PathBuf::from("."), command: Command::Help }));
+            }
+            a if a.starts_with('-') => return Err(format!("未知选项: {a}")),
+            a => positional.push(a),
+        }
+    }
+
+    let Some((&sub, rest)) = positional.split_first() else {
+        if project.is_some() || out.is_some() {
+            return Err("缺少子命令".to_owned());
+        }
+        return Ok(None);
+    };
+
+    let command = match (sub, rest) {
+        ("sync-outline", [md, json]) => Command::SyncOutline {
+            md: PathBuf::from(md),
+            json: PathBuf::from(json),
+        },
+        ("sync-outline", _) => return Err("sync-outline 需要 <md文件> <json文件> 两个参数".to_owned()),
+        ("compile", []) => Command::Compile {
+            out: out.take().ok_or("compile 需要 --out <输出.md>")?,
+        },
+        ("export-objects-csv", []) => Command::ExportObjectsCsv { out: out.take() },
+        ("compile" | "export-objects-csv", _) => return Err(format!("{sub} 不接受位置参数")),
+        _ => return Err(format!("未知子命令: {sub}")),
+    };
+    if out.is_some() {
+        return Err(format!("{sub} 不支持 --out"));
+    }
+
+    Ok(Some(Invocation {
+        project: project.unwrap_or_else(|| PathBuf::from(".")),
+        command,
+    }))
+}
+
+/// Execute a parsed invocation.  Returns a one-line summary for stdout
+/// (empty when the command already wrote its output there).
+pub fn run(inv: &Invocation) -> Result<String, String> {
+    if inv.command != Command::Help && !inv.project.is_dir() {
+        return Err(format!("项目目录不存在: {}", inv.project.display()));
+    }
+    match &inv.command {
+        Command::Help => Ok(USAGE.to_owned()),
+        Command::SyncOutline { md, json } => sync_outline(md, json),
+        Command::Compile { out } => {
+            let content = inv.project.join("Content");
+            if !content.is_dir() {
+                return Err(format!("找不到 {}", content.display()));
+            }
+            let merged = merge_chapter_files(&content, &IgnoreRules::load(&inv.project));
+            text_tool_core::safe_write(out, merged)
+                .map_err(|e| format!("写入 {} 失败: {e}", out.display()))?;
+            Ok(format!("已导出合集到 {}", out.display()))
+        }
+        Command::ExportObjectsCsv { out } => {
+            let path = inv.project.join("Design").join("世界对象.json");
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| format!("读取 {} 失败: {e}", path.display()))?;
+            let objects: Vec<WorldObject> = serde_json::from_str(&text)
+                .map_err(|e| format!("解析 {} 失败: {e}", path.display()))?;
+            let csv = objects_to_csv(&objects);
+            match out {
+                Some(out) => {
+                    text_tool_core::safe_write(out, csv)
+                        .map_err(|e| format!("写入 {} 失败: {e}", out.display()))?;
+                    Ok(format!("已导出 {} 个对象到 {}", objects.len(), out.display()))
+                }
+                None => {
+                    print!("{csv}");
+                    Ok(String::new())
+                }
+            }
+        }
+    }
+}
+
+fn sync_outline(md: &Path, json: &Path) -> Result<String, String> {
+    let content = std::fs::read_to_string(md)
+        .map_err(|e| format!("读取 {} 失败: {e}", md.display()))?;
+    let nodes = extract_struct_nodes_from_markdown(&content);
+    if nodes.is_empty() {
+        return Err(format!("{} 中未找到 Markdown 标题", md.display()));
+    }
+    let text = serde_json::to_string_pretty(&nodes).map_err(|e| format!("序列化失败: {e}"))?;
+    text_tool_core::safe_write(json, text).map_err(|e| format!("写入 {} 失败: {e}", json.display()))?;
+    Ok(format!("已提取 {} 个结构节点到 {}", count_nodes(&nodes), json.display()))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_no_args_starts_gui() {
+        assert_eq!(parse_args(&[]), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        let inv = parse_args(&args(&["--project", "书", "sync-outline", "大纲.md", "结构.json"]))
+            .unwrap().unwrap();
+        assert_eq!(inv.project, PathBuf::from("书"));
+        assert_eq!(inv.command, Command::SyncOutline {
+            md: PathBuf::from("大纲.md"),
+            json: PathBuf::from("结构.json"),
+        });
+
+        let inv = parse_args(&args(&["compile", "--out", "book.md"])).unwrap().unwrap();
+        assert_eq!(inv.project, PathBuf::from("."));
+        assert_eq!(inv.command, Command::Compile { out: PathBuf::from("book.md") });
+
+        let inv = parse_args(&args(&["export-objects-csv"])).unwrap().unwrap();
+        assert_eq!(inv.command, Command::ExportObjectsCsv { out: None });
+
+        let inv = parse_args(&args(&["compile", "--help"])).unwrap().unwrap();
+        assert_eq!(inv.command, Command::Help);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_args(&args(&["compile"])).is_err());
+        assert!(parse_args(&args(&["sync-outline", "a.md"])).is_err());
+        assert!(parse_args(&args(&["frobnicate"])).is_err());
+        assert!(parse_args(&args(&["--project"])).is_err());
+        assert!(parse_args(&args(&["sync-outline", "a.md", "b.json", "--out", "x"])).is_err());
+        assert!(parse_args(&args(&["--bogus"])).is_err());
+    }
+}
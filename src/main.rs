@@ -1,6 +1,29 @@
 mod app;
+mod cli;
 
 fn main() -> eframe::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::parse_args(&args) {
+        Ok(None) => {}
+        Ok(Some(inv)) => match cli::run(&inv) {
+            Ok(msg) => {
+                if !msg.is_empty() {
+                    println!("{msg}");
+                }
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("错误: {e}");
+                std::process::exit(1);
+            }
+        },
+        Err(e) => {
+            eprintln!("错误: {e}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    app::install_panic_hook();
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_title("清墨")
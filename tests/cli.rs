@@ -0,0 +1,82 @@
+//! Integration tests for the headless command-line mode.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn text_tool(args: &[&str], cwd: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_text_tool"))
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .expect("failed to run text_tool")
+}
+
+fn temp_project(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    for sub in ["Content", "Design", "废稿"] {
+        std::fs::create_dir_all(dir.join(sub)).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn sync_outline_writes_structure_json() {
+    let dir = temp_project("qingmo_cli_sync_outline");
+    std::fs::write(dir.join("Content/大纲.md"), "# 总纲\n## 第一卷\n### 第一章\n### 第二章\n").unwrap();
+
+    let out = text_tool(
+        &["--project", ".", "sync-outline", "Content/大纲.md", "Design/章节结构.json"],
+        &dir,
+    );
+    assert!(out.status.success(), "stderr: {}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("4 个结构节点"));
+
+    let json = std::fs::read_to_string(dir.join("Design/章节结构.json")).unwrap();
+    let roots: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(roots[0]["title"], "总纲");
+    assert_eq!(roots[0]["children"][0]["children"][1]["title"], "第二章");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn sync_outline_missing_file_fails() {
+    let dir = temp_project("qingmo_cli_sync_missing");
+    let out = text_tool(&["sync-outline", "nope.md", "out.json"], &dir);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("nope.md"));
+    assert!(!dir.join("out.json").exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn compile_and_export_csv() {
+    let dir = temp_project("qingmo_cli_compile");
+    std::fs::write(dir.join("Content/01.md"), "甲").unwrap();
+    std::fs::write(dir.join("Content/02.md"), "乙").unwrap();
+    std::fs::write(
+        dir.join("Design/世界对象.json"),
+        r#"[{"name":"林枫","kind":"Character","description":"","background":"","links":[]}]"#,
+    ).unwrap();
+
+    let out = text_tool(&["--project", ".", "compile", "--out", "book.md"], &dir);
+    assert!(out.status.success());
+    let book = std::fs::read_to_string(dir.join("book.md")).unwrap();
+    assert!(book.find("甲").unwrap() < book.find("乙").unwrap());
+
+    let out = text_tool(&["export-objects-csv"], &dir);
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("林枫,人物"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn bad_usage_exits_with_2() {
+    let dir = temp_project("qingmo_cli_usage");
+    let out = text_tool(&["compile"], &dir);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("用法"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,183 @@
+use std::ops::Range;
+
+// ── 注释 (inline reviewer comments) ───────────────────────────────────────────
+//
+// `%% 这里节奏太慢 %%` is a note to the writer, not part of the manuscript.
+// A comment runs from one `%%` to the next and may span lines; an opening
+// `%%` that is never closed is ordinary text.  Exports strip comments, and a
+// line left holding nothing but whitespace or a list / quote marker goes
+// with them, so no empty paragraph or bullet is left behind.
+
+/// Delimiter opening and closing a comment.
+pub const ANNOTATION_DELIM: &str = "%%";
+
+/// One comment in a text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    /// 1-based line of the opening `%%`.
+    pub line_no: usize,
+    /// 1-based column of the opening `%%`, counted in characters.
+    pub column: usize,
+    /// The comment's text, trimmed, without the delimiters.
+    pub text: String,
+    /// Byte range in the text, both delimiters included.
+    pub range: Range<usize>,
+}
+
+/// Byte ranges of the comments in `text`, delimiters included.
+pub fn annotation_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut out = Vec::new();
+    let mut from = 0;
+    while let Some(open) = text[from..].find(ANNOTATION_DELIM).map(|i| from + i) {
+        let body = open + ANNOTATION_DELIM.len();
+        let Some(close) = text[body..].find(ANNOTATION_DELIM).map(|i| body + i) else { break };
+        from = close + ANNOTATION_DELIM.len();
+        out.push(open..from);
+    }
+    out
+}
+
+/// Every comment in `text`, in reading order.
+pub fn find_annotations(text: &str) -> Vec<Annotation> {
+    let delim = ANNOTATION_DELIM.len();
+    annotation_ranges(text).into_iter()
+        .map(|range| {
+            let before = &text[..range.start];
+            let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+            Annotation {
+                line_no: before.matches('\n').count() + 1,
+                column: before[line_start..].chars().count() + 1,
+                text: text[range.start + delim..range.end - delim].trim().to_owned(),
+                range,
+            }
+        })
+        .collect()
+}
+
+/// Whether a line with its comments removed has nothing left worth keeping:
+/// only whitespace, quote markers and at most a bare list marker.
+fn is_left_empty(line: &str) -> bool {
+    let rest = line.trim_start_matches(|c: char| c == '>' || c.is_whitespace()).trim_end();
+    rest.is_empty()
+        || matches!(rest, "-" | "*" | "+")
+        || rest.strip_suffix(['.', ')'])
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// `text` without the byte `ranges` (sorted, non-overlapping comments).
+/// Lines a comment was removed from lose their trailing whitespace, and are
+/// dropped entirely when [`is_left_empty`].  A comment between two spaces
+/// leaves a single space.
+fn remove_ranges(text: &str, ranges: &[Range<usize>]) -> String {
+    if ranges.is_empty() {
+        return text.to_owned();
+    }
+    let mut joined = String::with_capacity(text.len());
+    // Lines of `joined` a comment was removed from.
+    let mut touched = Vec::new();
+    let mut line = 0;
+    let mut at = 0;
+    for range in ranges {
+        let kept = &text[at..range.start];
+        line += kept.matches('\n').count();
+        joined.push_str(kept);
+        if touched.last() != Some(&line) {
+            touched.push(line);
+        }
+        at = range.end;
+        if joined.ends_with(' ') && text[at..].starts_with(' ') {
+            at += 1;
+        }
+    }
+    joined.push_str(&text[at..]);
+
+    let mut out = String::with_capacity(joined.len());
+    let mut touched = touched.into_iter().peekable();
+    for (i, line) in joined.split_inclusive('\n').enumerate() {
+        if touched.next_if_eq(&i).is_none() {
+            out.push_str(line);
+            continue;
+        }
+        let body = line.trim_end_matches(['\n', '\r']);
+        if is_left_empty(body) {
+            continue;
+        }
+        out.push_str(body.trim_end());
+        out.push_str(&line[body.len()..]);
+    }
+    out
+}
+
+/// `text` with every comment removed, as exported.
+pub fn strip_annotations(text: &str) -> String {
+    remove_ranges(text, &annotation_ranges(text))
+}
+
+/// `text` with the single comment at `range` removed (注释 → 解决), tidied
+/// like [`strip_annotations`].
+pub fn remove_annotation(text: &str, range: Range<usize>) -> String {
+    remove_ranges(text, std::slice::from_ref(&range))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_annotations() {
+        let text = "# 第一章\n\n林枫推门而入。%% 这里节奏太慢 %%他停下。\n- 条目 %%列表里的\n  跨行注释%%\n%% 未闭合";
+        let found = find_annotations(text);
+        let at: Vec<(usize, usize, &str)> = found.iter()
+            .map(|a| (a.line_no, a.column, a.text.as_str()))
+            .collect();
+        assert_eq!(at, [(3, 8, "这里节奏太慢"), (4, 6, "列表里的\n  跨行注释")]);
+        assert_eq!(&text[found[0].range.clone()], "%% 这里节奏太慢 %%");
+        assert!(text[found[1].range.clone()].ends_with("跨行注释%%"));
+
+        assert!(find_annotations("百分之百%").is_empty());
+        assert_eq!(annotation_ranges("%%%%").first(), Some(&(0..4)));
+    }
+
+    #[test]
+    fn test_strip_annotations_inline_and_multiline() {
+        // Inline: a comment between spaces leaves one; at the end of a line
+        // it leaves no trailing space.
+        assert_eq!(strip_annotations("他说 %% 改 %% 好。\n走了。 %%删%%\n"), "他说 好。\n走了。\n");
+        assert_eq!(strip_annotations("无注释\n"), "无注释\n");
+
+        // A comment spanning lines joins what is around it.
+        assert_eq!(strip_annotations("开头%%第一行\n第二行%%结尾\n下一行"), "开头结尾\n下一行");
+
+        // The lines of a block comment go; the blank lines around it stay.
+        let text = "第一段\n\n%%\n这里节奏太慢\n考虑删掉\n%%\n\n第二段\n%% 末尾 %%";
+        assert_eq!(strip_annotations(text), "第一段\n\n\n第二段\n");
+
+        // CRLF line endings survive.
+        assert_eq!(strip_annotations("甲 %%注%%\r\n乙\r\n"), "甲\r\n乙\r\n");
+    }
+
+    #[test]
+    fn test_strip_annotations_in_lists() {
+        let text = "\
+- 第一项 %%待定%%
+- %% 整项都是注释 %%
+- 第三项
+  %% 缩进的
+  跨行注释 %%
+1. %%编号项注释%%
+2. 第二步
+> %% 引用里的注释 %%
+> 引文";
+        assert_eq!(strip_annotations(text), "- 第一项\n- 第三项\n2. 第二步\n> 引文");
+    }
+
+    #[test]
+    fn test_remove_annotation() {
+        let text = "甲%%一%%乙\n- %%二%%\n丙";
+        let found = find_annotations(text);
+        assert_eq!(remove_annotation(text, found[1].range.clone()), "甲%%一%%乙\n丙");
+        assert_eq!(remove_annotation(text, found[0].range.clone()), "甲乙\n- %%二%%\n丙");
+    }
+}
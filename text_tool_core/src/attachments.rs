@@ -0,0 +1,178 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::split::sanitize_file_name;
+
+// ── Object attachments: reference art, research PDFs ─────────────────────────
+//
+// Attachments are stored on the object as project-relative `/` paths.  Files
+// picked from outside the project are copied into
+// `Design/attachments/<object>/` first so the project stays self-contained;
+// files already inside it are referenced where they are.  Only the copied
+// files are ever offered for deletion.
+
+/// Project-relative folder holding the copied attachments.
+pub const ATTACHMENTS_DIR: &str = "Design/attachments";
+
+/// Extensions shown as thumbnails (the decoders the app is built with).
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+/// `/`-separated path of `path` relative to `root`, if it lies inside.
+pub fn project_relative(root: &Path, path: &Path) -> Option<String> {
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let rel = canonical(path).strip_prefix(canonical(root)).ok()?.to_path_buf();
+    let parts: Vec<String> = rel.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Absolute path of the project-relative `rel`.
+pub fn resolve_attachment(root: &Path, rel: &str) -> PathBuf {
+    rel.split('/').fold(root.to_path_buf(), |p, part| p.join(part))
+}
+
+/// Whether `rel` is a copy made by [`import_attachment`].
+pub fn is_managed_attachment(rel: &str) -> bool {
+    rel.strip_prefix(ATTACHMENTS_DIR).is_some_and(|rest| rest.starts_with('/'))
+}
+
+pub fn is_image_attachment(rel: &str) -> bool {
+    Path::new(rel).extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.iter().any(|x| x.eq_ignore_ascii_case(e)))
+}
+
+/// File name of `rel` for display.
+pub fn attachment_name(rel: &str) -> &str {
+    rel.rsplit('/').next().unwrap_or(rel)
+}
+
+/// Make `source` an attachment of `object` and return its project-relative
+/// path.  A file inside the project is used in place; any other is copied to
+/// `Design/attachments/<object>/`, as `name (2).ext` … when a different file
+/// already has its name.  An identical file of that name is reused.
+pub fn import_attachment(root: &Path, object: &str, source: &Path) -> io::Result<String> {
+    if let Some(rel) = project_relative(root, source) {
+        return Ok(rel);
+    }
+    let file_name = source.file_name()
+        .map(|n| sanitize_file_name(&n.to_string_lossy()))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "不是文件"))?;
+    let folder = sanitize_file_name(object);
+    let dir = resolve_attachment(root, ATTACHMENTS_DIR).join(&folder);
+    std::fs::create_dir_all(&dir)?;
+
+    let (stem, ext) = match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem.to_owned(), format!(".{ext}")),
+        _ => (file_name.clone(), String::new()),
+    };
+    let data = std::fs::read(source)?;
+    let mut name = file_name.clone();
+    let mut n = 1;
+    loop {
+        let dest = dir.join(&name);
+        match std::fs::read(&dest) {
+            Ok(existing) if existing == data => break,
+            Ok(_) => {
+                n += 1;
+                name = format!("{stem} ({n}){ext}");
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                std::fs::write(&dest, &data)?;
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(format!("{ATTACHMENTS_DIR}/{folder}/{name}"))
+}
+
+/// Delete the copied attachment `rel`, and its object folder once empty.
+/// Files outside [`ATTACHMENTS_DIR`] are refused.
+pub fn delete_attachment_file(root: &Path, rel: &str) -> io::Result<()> {
+    if !is_managed_attachment(rel) || rel.split('/').any(|p| p == "..") {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "只能删除复制到 Design/attachments 的附件"));
+    }
+    let path = resolve_attachment(root, rel);
+    std::fs::remove_file(&path)?;
+    if let Some(dir) = path.parent() {
+        // Fails, harmlessly, while other attachments remain.
+        let _ = std::fs::remove_dir(dir);
+    }
+    Ok(())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Design")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_import_attachment_copies_into_project() {
+        let root = temp_project("qingmo_core_test_attach");
+        let outside = temp_project("qingmo_core_test_attach_src");
+        let art = outside.join("立绘.png");
+        std::fs::write(&art, b"png-1").unwrap();
+
+        let rel = import_attachment(&root, "林远", &art).unwrap();
+        assert_eq!(rel, "Design/attachments/林远/立绘.png");
+        assert_eq!(std::fs::read(resolve_attachment(&root, &rel)).unwrap(), b"png-1");
+        assert!(is_managed_attachment(&rel) && is_image_attachment(&rel));
+        assert_eq!(attachment_name(&rel), "立绘.png");
+
+        // Same content reuses the copy; a different file of that name is numbered.
+        assert_eq!(import_attachment(&root, "林远", &art).unwrap(), rel);
+        std::fs::write(&art, b"png-2").unwrap();
+        assert_eq!(import_attachment(&root, "林远", &art).unwrap(), "Design/attachments/林远/立绘 (2).png");
+
+        // Object names become safe folder names.
+        let notes = outside.join("notes.pdf");
+        std::fs::write(&notes, b"%PDF").unwrap();
+        assert_eq!(import_attachment(&root, "a/b", &notes).unwrap(), "Design/attachments/a_b/notes.pdf");
+        assert!(!is_image_attachment("notes.pdf"));
+
+        // Files already in the project are referenced in place.
+        std::fs::create_dir_all(root.join("Content")).unwrap();
+        std::fs::write(root.join("Content").join("资料.md"), "x").unwrap();
+        assert_eq!(import_attachment(&root, "林远", &root.join("Content/资料.md")).unwrap(), "Content/资料.md");
+        assert!(!is_managed_attachment("Content/资料.md"));
+        assert!(!is_managed_attachment("Design/attachments-old/x.png"));
+
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_dir_all(&outside);
+    }
+
+    #[test]
+    fn test_delete_attachment_file() {
+        let root = temp_project("qingmo_core_test_attach_del");
+        let outside = temp_project("qingmo_core_test_attach_del_src");
+        let src = outside.join("a.pdf");
+        std::fs::write(&src, b"a").unwrap();
+        let a = import_attachment(&root, "苏晚", &src).unwrap();
+        let src = outside.join("b.pdf");
+        std::fs::write(&src, b"b").unwrap();
+        let b = import_attachment(&root, "苏晚", &src).unwrap();
+        let folder = root.join("Design/attachments/苏晚");
+
+        delete_attachment_file(&root, &a).unwrap();
+        assert!(!resolve_attachment(&root, &a).exists() && folder.exists());
+        delete_attachment_file(&root, &b).unwrap();
+        assert!(!folder.exists());
+
+        // Only copies under Design/attachments can be deleted.
+        std::fs::write(root.join("Design").join("世界对象.json"), "[]").unwrap();
+        assert!(delete_attachment_file(&root, "Design/世界对象.json").is_err());
+        assert!(delete_attachment_file(&root, "Design/attachments/../世界对象.json").is_err());
+        assert!(root.join("Design").join("世界对象.json").exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_dir_all(&outside);
+    }
+}
@@ -0,0 +1,233 @@
+use std::io::Write as _;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::format_day;
+
+// ── 变更记录: a journal of structural edits ────────────────────────────────────
+//
+// Adding, removing or renaming objects, moving structure nodes, linking and
+// resolving foreshadows each leave one line in `changelog.jsonl`, so a long
+// planning session can be reviewed afterwards.  The journal is append-only;
+// a line that does not parse is skipped on reading.
+
+/// Journal file, relative to the project root.
+pub const CHANGELOG_PATH: &str = "Design/.texttool/changelog.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChangeCategory {
+    Object,
+    Structure,
+    Link,
+    Foreshadow,
+}
+
+impl ChangeCategory {
+    pub fn all() -> &'static [ChangeCategory] {
+        &[ChangeCategory::Object, ChangeCategory::Structure, ChangeCategory::Link, ChangeCategory::Foreshadow]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeCategory::Object     => "对象",
+            ChangeCategory::Structure  => "结构",
+            ChangeCategory::Link       => "关联",
+            ChangeCategory::Foreshadow => "伏笔",
+        }
+    }
+}
+
+/// One line of the journal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeEntry {
+    /// Unix seconds (UTC).
+    pub at: i64,
+    pub category: ChangeCategory,
+    pub description: String,
+}
+
+impl ChangeEntry {
+    /// Local day number of the entry, `offset_secs` east of UTC.
+    pub fn day(&self, offset_secs: i64) -> i64 {
+        (self.at + offset_secs).div_euclid(86_400)
+    }
+}
+
+/// A structural edit, described for the journal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<'a> {
+    ObjectAdded { name: &'a str, kind: &'a str },
+    ObjectRemoved { name: &'a str },
+    ObjectRenamed { from: &'a str, to: &'a str },
+    /// `parent` is `None` for a top-level node; `index` counts from 0.
+    NodeMoved { title: &'a str, parent: Option<&'a str>, index: usize },
+    LinkAdded { from: &'a str, relation: &'a str, to: &'a str },
+    ForeshadowResolved { name: &'a str, resolved: bool },
+}
+
+impl Change<'_> {
+    pub fn category(&self) -> ChangeCategory {
+        match self {
+            Change::ObjectAdded { .. } | Change::ObjectRemoved { .. } | Change::ObjectRenamed { .. } => {
+                ChangeCategory::Object
+            }
+            Change::NodeMoved { .. } => ChangeCategory::Structure,
+            Change::LinkAdded { .. } => ChangeCategory::Link,
+            Change::ForeshadowResolved { .. } => ChangeCategory::Foreshadow,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Change::ObjectAdded { name, kind } => format!("新增{kind}「{name}」"),
+            Change::ObjectRemoved { name } => format!("删除对象「{name}」"),
+            Change::ObjectRenamed { from, to } => format!("对象「{from}」改名为「{to}」"),
+            Change::NodeMoved { title, parent: Some(parent), index } => {
+                format!("节点「{title}」移到「{parent}」下第 {} 位", index + 1)
+            }
+            Change::NodeMoved { title, parent: None, index } => {
+                format!("节点「{title}」移到顶层第 {} 位", index + 1)
+            }
+            Change::LinkAdded { from, relation, to } => format!("「{from}」→「{to}」（{relation}）"),
+            Change::ForeshadowResolved { name, resolved: true } => format!("伏笔「{name}」已回收"),
+            Change::ForeshadowResolved { name, resolved: false } => format!("伏笔「{name}」重新标为未回收"),
+        }
+    }
+
+    pub fn entry(&self, at: i64) -> ChangeEntry {
+        ChangeEntry { at, category: self.category(), description: self.describe() }
+    }
+}
+
+/// Entries of a journal's text, one JSON object per line.
+pub fn parse_entries(text: &str) -> Vec<ChangeEntry> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect()
+}
+
+/// Append `entries` to the journal at `path`, creating it and its folder.
+pub fn append_entries(path: &Path, entries: &[ChangeEntry]) -> std::io::Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut text = String::new();
+    for entry in entries {
+        text.push_str(&serde_json::to_string(entry).map_err(std::io::Error::other)?);
+        text.push('\n');
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(text.as_bytes())
+}
+
+/// The journal at `path`, oldest first; empty if it does not exist yet.
+pub fn read_entries(path: &Path) -> std::io::Result<Vec<ChangeEntry>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(parse_entries(&text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// `entries` as Markdown: a `## YYYY-MM-DD` heading per local day, then
+/// `- HH:MM [类别] 描述` lines in journal order.
+pub fn entries_to_markdown(entries: &[ChangeEntry], offset_secs: i64) -> String {
+    let mut out = String::from("# 变更记录\n");
+    let mut current_day = None;
+    for entry in entries {
+        let day = entry.day(offset_secs);
+        if current_day != Some(day) {
+            out.push_str(&format!("\n## {}\n\n", format_day(day)));
+            current_day = Some(day);
+        }
+        out.push_str(&format!(
+            "- {} [{}] {}\n",
+            format_clock(entry.at + offset_secs),
+            entry.category.label(),
+            entry.description,
+        ));
+    }
+    out
+}
+
+/// `HH:MM` of local Unix seconds.
+pub fn format_clock(local_secs: i64) -> String {
+    let secs = local_secs.rem_euclid(86_400);
+    format!("{:02}:{:02}", secs / 3_600, secs % 3_600 / 60)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_descriptions() {
+        let cases = [
+            (Change::ObjectAdded { name: "林远", kind: "人物" }, ChangeCategory::Object, "新增人物「林远」"),
+            (Change::ObjectRemoved { name: "玉佩" }, ChangeCategory::Object, "删除对象「玉佩」"),
+            (Change::ObjectRenamed { from: "林远", to: "林逸" }, ChangeCategory::Object, "对象「林远」改名为「林逸」"),
+            (
+                Change::NodeMoved { title: "第三章", parent: Some("第一卷"), index: 0 },
+                ChangeCategory::Structure,
+                "节点「第三章」移到「第一卷」下第 1 位",
+            ),
+            (Change::NodeMoved { title: "第二卷", parent: None, index: 2 }, ChangeCategory::Structure, "节点「第二卷」移到顶层第 3 位"),
+            (Change::LinkAdded { from: "林远", relation: "敌对", to: "赵七" }, ChangeCategory::Link, "「林远」→「赵七」（敌对）"),
+            (Change::ForeshadowResolved { name: "玉佩来历", resolved: true }, ChangeCategory::Foreshadow, "伏笔「玉佩来历」已回收"),
+            (
+                Change::ForeshadowResolved { name: "玉佩来历", resolved: false },
+                ChangeCategory::Foreshadow,
+                "伏笔「玉佩来历」重新标为未回收",
+            ),
+        ];
+        for (change, category, text) in cases {
+            let entry = change.entry(42);
+            assert_eq!(entry, ChangeEntry { at: 42, category, description: text.to_owned() });
+        }
+    }
+
+    #[test]
+    fn test_journal_round_trip() {
+        let dir = std::env::temp_dir().join(format!("tt_changelog_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join(CHANGELOG_PATH);
+        assert_eq!(read_entries(&path).unwrap(), Vec::new());
+
+        let first = Change::ObjectAdded { name: "林远", kind: "人物" }.entry(100);
+        let second = Change::ForeshadowResolved { name: "玉佩", resolved: true }.entry(200);
+        append_entries(&path, std::slice::from_ref(&first)).unwrap();
+        append_entries(&path, &[]).unwrap();
+        append_entries(&path, std::slice::from_ref(&second)).unwrap();
+        assert_eq!(read_entries(&path).unwrap(), vec![first.clone(), second.clone()]);
+
+        // A damaged line is skipped, the rest still reads.
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, format!("{{\"at\":\n{text}")).unwrap();
+        assert_eq!(read_entries(&path).unwrap(), vec![first, second]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_entries_to_markdown() {
+        // 2024-03-01 23:30 and 2024-03-02 08:05 at UTC+8.
+        let offset = 8 * 3_600;
+        let day = crate::models::days_from_civil(2024, 3, 1) * 86_400 - offset;
+        let entries = vec![
+            Change::ObjectAdded { name: "林远", kind: "人物" }.entry(day + 23 * 3_600 + 30 * 60),
+            Change::NodeMoved { title: "第二章", parent: None, index: 0 }.entry(day + 32 * 3_600 + 5 * 60),
+        ];
+        assert_eq!(entries[0].day(offset) + 1, entries[1].day(offset));
+        assert_eq!(
+            entries_to_markdown(&entries, offset),
+            "# 变更记录\n\n## 2024-03-01\n\n- 23:30 [对象] 新增人物「林远」\n\n## 2024-03-02\n\n- 08:05 [结构] 节点「第二章」移到顶层第 1 位\n",
+        );
+        assert_eq!(format_clock(-60), "23:59");
+    }
+}
@@ -0,0 +1,140 @@
+// ── @-mention completion ──────────────────────────────────────────────────────
+
+/// Longest query (in characters) still treated as an `@` mention.
+pub const MAX_MENTION_LEN: usize = 16;
+
+/// An `@token` immediately before the caret.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MentionToken {
+    /// Char index of the `@`.
+    pub start: usize,
+    /// Text typed after the `@` (may be empty).
+    pub query: String,
+}
+
+/// Find the `@token` ending at `caret` (a char index into `text`).
+///
+/// The token runs back from the caret over non-whitespace, non-punctuation
+/// characters (CJK included) to an `@`.  An `@` glued to ASCII letters or
+/// digits (as in an e-mail address) does not count.
+pub fn mention_before_caret(text: &str, caret: usize) -> Option<MentionToken> {
+    let chars: Vec<char> = text.chars().collect();
+    let caret = caret.min(chars.len());
+    let mut i = caret;
+    while i > 0 {
+        let c = chars[i - 1];
+        if c == '@' {
+            let start = i - 1;
+            if start > 0 && chars[start - 1].is_ascii_alphanumeric() {
+                return None;
+            }
+            return Some(MentionToken {
+                start,
+                query: chars[i..caret].iter().collect(),
+            });
+        }
+        if c.is_whitespace() || is_punctuation(c) || caret - i >= MAX_MENTION_LEN {
+            return None;
+        }
+        i -= 1;
+    }
+    None
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || "，。！？；：、“”‘’（）《》〈〉【】「」『』…—·".contains(c)
+}
+
+/// Names matching `query` (case-insensitive): prefix matches first, then
+/// other substring matches, each group in input order; at most `limit`.
+pub fn filter_mentions<'a>(names: &'a [String], query: &str, limit: usize) -> Vec<&'a str> {
+    let q = query.to_lowercase();
+    let mut prefix = Vec::new();
+    let mut contains = Vec::new();
+    for name in names {
+        let lower = name.to_lowercase();
+        if prefix.contains(&name.as_str()) || contains.contains(&name.as_str()) {
+            continue;
+        }
+        if lower.starts_with(&q) {
+            prefix.push(name.as_str());
+        } else if lower.contains(&q) {
+            contains.push(name.as_str());
+        }
+    }
+    prefix.extend(contains);
+    prefix.truncate(limit);
+    prefix
+}
+
+/// Replace the `@token` (from its `@` up to `caret`) with `name`.
+/// Returns the new text and the caret position just after the name.
+pub fn apply_mention(text: &str, token: &MentionToken, caret: usize, name: &str) -> (String, usize) {
+    let chars: Vec<char> = text.chars().collect();
+    let caret = caret.min(chars.len()).max(token.start);
+    let mut out: String = chars[..token.start].iter().collect();
+    out.push_str(name);
+    out.extend(&chars[caret..]);
+    (out, token.start + name.chars().count())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mention_in_cjk_text() {
+        let text = "他看见@林";
+        let tok = mention_before_caret(text, text.chars().count()).unwrap();
+        assert_eq!(tok, MentionToken { start: 3, query: "林".to_owned() });
+
+        // Bare `@` right after typing it.
+        let tok = mention_before_caret("他说@", 3).unwrap();
+        assert_eq!(tok.query, "");
+    }
+
+    #[test]
+    fn test_mention_caret_mid_text() {
+        // Caret after "@林" with more text following.
+        let tok = mention_before_caret("@林枫走了", 2).unwrap();
+        assert_eq!(tok, MentionToken { start: 0, query: "林".to_owned() });
+    }
+
+    #[test]
+    fn test_mention_stops_at_space_and_punctuation() {
+        assert!(mention_before_caret("@林 枫", 4).is_none());
+        assert!(mention_before_caret("@林，枫", 4).is_none());
+        assert!(mention_before_caret("没有提及", 4).is_none());
+    }
+
+    #[test]
+    fn test_mention_ignores_email() {
+        assert!(mention_before_caret("mail me@host", 12).is_none());
+        assert!(mention_before_caret("邮件@host", 7).is_some());
+    }
+
+    #[test]
+    fn test_mention_too_long() {
+        let text = format!("@{}", "字".repeat(MAX_MENTION_LEN + 1));
+        assert!(mention_before_caret(&text, text.chars().count()).is_none());
+    }
+
+    #[test]
+    fn test_filter_mentions_prefix_first() {
+        let names: Vec<String> = ["老林", "林枫", "Lina", "苏晴"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(filter_mentions(&names, "林", 8), vec!["林枫", "老林"]);
+        assert_eq!(filter_mentions(&names, "li", 8), vec!["Lina"]);
+        assert_eq!(filter_mentions(&names, "", 2), vec!["老林", "林枫"]);
+    }
+
+    #[test]
+    fn test_apply_mention_replaces_token() {
+        let text = "他看见@林走来";
+        let tok = mention_before_caret(text, 5).unwrap();
+        let (out, caret) = apply_mention(text, &tok, 5, "林枫");
+        assert_eq!(out, "他看见林枫走来");
+        assert_eq!(caret, 5);
+    }
+}
@@ -0,0 +1,163 @@
+use std::cmp::Ordering;
+use std::fmt::Write as _;
+
+use crate::links::{encode_link_path, markdown_link};
+use crate::outline::{natural_cmp, ContentFile};
+
+// ── 目录.md: generated index of Content/ ──────────────────────────────────────
+//
+// Every Markdown file of `Content/` as a link with its first heading and word
+// count, grouped by folder.  Only the part below `INDEX_MARKER` is
+// generated, so a hand-written preamble survives regeneration.
+
+/// File name of the index, directly under `Content/`.
+pub const INDEX_FILE: &str = "目录.md";
+/// Everything from this line on is regenerated; the text above it is kept.
+pub const INDEX_MARKER: &str = "<!-- texttool:index -->";
+/// Preamble of a newly created index.
+const DEFAULT_PREAMBLE: &str = "# 目录\n\n";
+
+/// Split a `Content/`-relative path into its folder (`""` at the top) and
+/// file name.
+fn split_rel(rel: &str) -> (&str, &str) {
+    rel.rsplit_once('/').unwrap_or(("", rel))
+}
+
+/// Folders component by component in natural order (a folder's own files
+/// before its subfolders), then file names in natural order.
+fn cmp_files(a: &ContentFile, b: &ContentFile) -> Ordering {
+    let ((dir_a, name_a), (dir_b, name_b)) = (split_rel(&a.rel), split_rel(&b.rel));
+    let mut x = dir_a.split('/').filter(|s| !s.is_empty());
+    let mut y = dir_b.split('/').filter(|s| !s.is_empty());
+    loop {
+        match (x.next(), y.next()) {
+            (Some(p), Some(q)) => {
+                let ord = natural_cmp(p, q);
+                if ord.is_ne() {
+                    return ord;
+                }
+            }
+            (None, None) => return natural_cmp(name_a, name_b),
+            (p, q) => return p.is_some().cmp(&q.is_some()),
+        }
+    }
+}
+
+/// The generated part of the index, starting with [`INDEX_MARKER`]: one
+/// `##` section per folder (files at the top of `Content/` first, without
+/// a heading).  The index file itself is left out.
+pub fn build_index_body(files: &[ContentFile]) -> String {
+    let mut sorted: Vec<&ContentFile> = files.iter().filter(|f| f.rel != INDEX_FILE).collect();
+    sorted.sort_by(|a, b| cmp_files(a, b));
+
+    let mut out = format!("{INDEX_MARKER}\n\n");
+    if sorted.is_empty() {
+        out.push_str("（Content/ 下没有 Markdown 文件）\n");
+        return out;
+    }
+    let mut folder = None;
+    for f in sorted {
+        let (dir, name) = split_rel(&f.rel);
+        if folder != Some(dir) {
+            if folder.is_some() {
+                out.push('\n');
+            }
+            if !dir.is_empty() {
+                let _ = writeln!(out, "## {}\n", dir.replace('/', " / "));
+            }
+            folder = Some(dir);
+        }
+        let stem = name.strip_suffix(".md").unwrap_or(name);
+        let title = f.heading.as_deref().unwrap_or(stem);
+        let link = markdown_link(title, &encode_link_path(&f.rel), None);
+        let _ = writeln!(out, "- {link} · {} 字", f.words);
+    }
+    out
+}
+
+/// The index document: the preamble of `existing` (everything above
+/// [`INDEX_MARKER`]; all of it when the marker is missing, so a hand-made
+/// index is never lost) followed by a fresh [`build_index_body`].
+pub fn update_index_document(existing: Option<&str>, files: &[ContentFile]) -> String {
+    let preamble = match existing {
+        Some(text) if !text.trim().is_empty() => match text.find(INDEX_MARKER) {
+            Some(at) => text[..at].to_owned(),
+            None => format!("{}\n\n", text.trim_end()),
+        },
+        _ => DEFAULT_PREAMBLE.to_owned(),
+    };
+    let mut out = preamble;
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&build_index_body(files));
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(rel: &str, heading: Option<&str>, words: usize) -> ContentFile {
+        ContentFile { rel: rel.to_owned(), heading: heading.map(str::to_owned), words }
+    }
+
+    fn snapshot() -> Vec<ContentFile> {
+        vec![
+            file("第一卷/第10章.md", Some("第十章 决战"), 4100),
+            file("第一卷/第2章.md", None, 2800),
+            file("第一卷/番外/外传 (上).md", Some("外传"), 900),
+            file("目录.md", Some("目录"), 50),
+            file("序章.md", Some("序章 雨夜"), 1200),
+            file("第二卷/第11章.md", Some("第十一章"), 0),
+        ]
+    }
+
+    #[test]
+    fn test_index_body_groups_in_natural_order() {
+        assert_eq!(build_index_body(&snapshot()), "\
+<!-- texttool:index -->
+
+- [序章 雨夜](序章.md) · 1200 字
+
+## 第一卷
+
+- [第2章](第一卷/第2章.md) · 2800 字
+- [第十章 决战](第一卷/第10章.md) · 4100 字
+
+## 第一卷 / 番外
+
+- [外传](第一卷/番外/外传%20%28上%29.md) · 900 字
+
+## 第二卷
+
+- [第十一章](第二卷/第11章.md) · 0 字
+");
+        assert_eq!(build_index_body(&[]), "<!-- texttool:index -->\n\n（Content/ 下没有 Markdown 文件）\n");
+    }
+
+    #[test]
+    fn test_update_index_keeps_preamble() {
+        let files = vec![file("第1章.md", Some("第一章"), 10)];
+        // New index: default preamble.
+        let fresh = update_index_document(None, &files);
+        assert_eq!(fresh, "# 目录\n\n<!-- texttool:index -->\n\n- [第一章](第1章.md) · 10 字\n");
+        assert_eq!(update_index_document(Some("  \n"), &files), fresh);
+
+        // Hand-written text above the marker survives; the stale list goes.
+        let existing = "# 我的小说\n\n写作顺序见下。\n<!-- texttool:index -->\n\n- [旧章](旧章.md) · 1 字\n";
+        let updated = update_index_document(Some(existing), &files);
+        assert_eq!(updated, "# 我的小说\n\n写作顺序见下。\n<!-- texttool:index -->\n\n- [第一章](第1章.md) · 10 字\n");
+        // Regenerating is stable.
+        assert_eq!(update_index_document(Some(&updated), &files), updated);
+
+        // A hand-made index without the marker is kept whole as the preamble.
+        let manual = "# 目录\n\n1. 第一章";
+        assert_eq!(
+            update_index_document(Some(manual), &files),
+            "# 目录\n\n1. 第一章\n\n<!-- texttool:index -->\n\n- [第一章](第1章.md) · 10 字\n",
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use std::any::Any;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+// ── Crash log ─────────────────────────────────────────────────────────────────
+//
+// Panics are appended to `texttool-crash.log` in the project (or the temp
+// directory).  A marker file remembers the log until the next start, when
+// the app tells the user the previous session ended abnormally.
+
+/// Crash log file name.
+pub const CRASH_LOG: &str = "texttool-crash.log";
+
+/// Text of a panic payload: the `&str` or `String` passed to `panic!`.
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload.downcast_ref::<&str>().copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("（无法显示的 panic 信息）")
+}
+
+/// One log entry.  `at` is a Unix time in seconds.
+pub fn format_crash_report(message: &str, location: Option<&str>, backtrace: &str, at: i64) -> String {
+    let mut out = format!("=== panic @ {at} ===\n{message}\n");
+    if let Some(loc) = location {
+        out.push_str(&format!("位置: {loc}\n"));
+    }
+    let backtrace = backtrace.trim_end();
+    if !backtrace.is_empty() {
+        out.push_str(backtrace);
+        out.push('\n');
+    }
+    out.push('\n');
+    out
+}
+
+/// Append `report` to the crash log in `dir` and return the log's path.
+pub fn append_crash_log(dir: &Path, report: &str) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(CRASH_LOG);
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    file.write_all(report.as_bytes())?;
+    file.flush()?;
+    Ok(path)
+}
+
+/// Remember `log` at `marker` so the next start can point to it.
+pub fn set_crash_marker(marker: &Path, log: &Path) -> std::io::Result<()> {
+    if let Some(parent) = marker.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(marker, log.to_string_lossy().as_bytes())
+}
+
+/// The log remembered at `marker`, removing the marker.
+pub fn take_crash_marker(marker: &Path) -> Option<PathBuf> {
+    let text = std::fs::read_to_string(marker).ok()?;
+    let _ = std::fs::remove_file(marker);
+    let log = text.trim();
+    (!log.is_empty()).then(|| PathBuf::from(log))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_message() {
+        let payload = std::panic::catch_unwind(|| panic!("索引越界")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "索引越界");
+        let n = 3;
+        let payload = std::panic::catch_unwind(|| panic!("第 {n} 项")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "第 3 项");
+        let payload = std::panic::catch_unwind(|| std::panic::panic_any(7)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "（无法显示的 panic 信息）");
+    }
+
+    #[test]
+    fn test_crash_log_and_marker() {
+        let dir = std::env::temp_dir().join("qingmo_core_test_crash");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let first = format_crash_report("boom", Some("src/a.rs:1:2"), "  0: main\n", 100);
+        assert_eq!(first, "=== panic @ 100 ===\nboom\n位置: src/a.rs:1:2\n  0: main\n\n");
+        let log = append_crash_log(&dir, &first).unwrap();
+        assert_eq!(log, dir.join(CRASH_LOG));
+        append_crash_log(&dir, &format_crash_report("again", None, "", 200)).unwrap();
+        let text = std::fs::read_to_string(&log).unwrap();
+        assert!(text.starts_with(&first));
+        assert!(text.ends_with("=== panic @ 200 ===\nagain\n\n"));
+
+        let marker = dir.join("state").join("crash-pending");
+        assert_eq!(take_crash_marker(&marker), None);
+        set_crash_marker(&marker, &log).unwrap();
+        assert_eq!(take_crash_marker(&marker), Some(log));
+        // Taken once only.
+        assert_eq!(take_crash_marker(&marker), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
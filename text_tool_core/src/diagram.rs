@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::models::{node_path_titled_near, LinkTarget, RelationKind, StructNode, WorldObject};
+
+// ── Mermaid diagrams ──────────────────────────────────────────────────────────
+//
+// Node identifiers are generated ASCII (`o3`, `n0_2`), so names never have to
+// be valid Mermaid identifiers; names only appear in quoted labels.
+
+/// Escape `text` for a double-quoted Mermaid label.  Characters that end or
+/// confuse the label are written as Mermaid entity codes; line breaks become
+/// spaces.
+pub fn mermaid_label(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"'  => out.push_str("#quot;"),
+            '#'  => out.push_str("#35;"),
+            '<'  => out.push_str("#lt;"),
+            '>'  => out.push_str("#gt;"),
+            '`'  => out.push_str("#96;"),
+            '\r' => {}
+            '\n' => out.push(' '),
+            c    => out.push(c),
+        }
+    }
+    out
+}
+
+/// `graph TD` of object ↔ object links.  Nodes are labelled `icon name`,
+/// edges with the relation kind; links to objects that do not exist are
+/// left out.
+pub fn objects_to_mermaid(objects: &[WorldObject]) -> String {
+    let ids: HashMap<&str, usize> = objects.iter().enumerate()
+        .map(|(i, o)| (o.name.as_str(), i))
+        .collect();
+    let mut out = String::from("graph TD\n");
+    for (i, obj) in objects.iter().enumerate() {
+        let _ = writeln!(out, "    o{i}[\"{}\"]", mermaid_label(&format!("{} {}", obj.icon(), obj.name)));
+    }
+    for (i, obj) in objects.iter().enumerate() {
+        for link in &obj.links {
+            let LinkTarget::Object(name) = &link.target else { continue };
+            if let Some(j) = ids.get(name.as_str()) {
+                let _ = writeln!(out, "    o{i} -->|\"{}\"| o{j}", mermaid_label(link.kind.label()));
+            }
+        }
+    }
+    out
+}
+
+/// `graph TD` of the structure tree: parent → child edges, plus 铺垫 / 回收
+/// cross-links as dashed edges.  Cross-links resolve to the node with the
+/// target title nearest their source (see [`node_path_titled_near`]) and
+/// are left out when none matches.
+pub fn struct_to_mermaid(roots: &[StructNode]) -> String {
+    fn node_id(path: &[usize]) -> String {
+        let parts: Vec<String> = path.iter().map(usize::to_string).collect();
+        format!("n{}", parts.join("_"))
+    }
+    fn walk(nodes: &[StructNode], path: &mut Vec<usize>, flat: &mut Vec<(String, Vec<usize>)>) {
+        for (i, n) in nodes.iter().enumerate() {
+            path.push(i);
+            flat.push((node_id(path), path.clone()));
+            walk(&n.children, path, flat);
+            path.pop();
+        }
+    }
+    let mut flat = Vec::new();
+    walk(roots, &mut Vec::new(), &mut flat);
+    let node = |path: &[usize]| crate::models::node_at(roots, path).expect("path from walk");
+
+    let mut out = String::from("graph TD\n");
+    for (id, path) in &flat {
+        let n = node(path);
+        let _ = writeln!(out, "    {id}[\"{}\"]", mermaid_label(&format!("{} {}", n.kind.icon(), n.title)));
+    }
+    for (id, path) in &flat {
+        if path.len() > 1 {
+            let _ = writeln!(out, "    {} --> {id}", node_id(&path[..path.len() - 1]));
+        }
+    }
+    for (id, path) in &flat {
+        for link in &node(path).node_links {
+            if !matches!(link.kind, RelationKind::Foreshadows | RelationKind::Resolves) {
+                continue;
+            }
+            if let Some(target) = node_path_titled_near(roots, &link.target_title, path) {
+                let _ = writeln!(out, "    {id} -.->|\"{}\"| {}", mermaid_label(link.kind.label()), node_id(&target));
+            }
+        }
+    }
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{NodeLink, ObjectKind, ObjectLink, StructKind};
+
+    #[test]
+    fn test_mermaid_label_escapes() {
+        assert_eq!(mermaid_label("林枫"), "林枫");
+        assert_eq!(mermaid_label("说\"不\" #1 <剑>\n`x`"), "说#quot;不#quot; #35;1 #lt;剑#gt; #96;x#96;");
+    }
+
+    #[test]
+    fn test_objects_to_mermaid_snapshot() {
+        let mut a = WorldObject::new("林枫", ObjectKind::Character);
+        a.links.push(ObjectLink {
+            target: LinkTarget::Object("青云\"宗\"".into()),
+            kind: RelationKind::BelongsTo,
+            note: String::new(),
+        });
+        a.links.push(ObjectLink {
+            target: LinkTarget::Object("不存在".into()),
+            kind: RelationKind::Friend,
+            note: String::new(),
+        });
+        a.links.push(ObjectLink {
+            target: LinkTarget::Node("第一章".into()),
+            kind: RelationKind::AppearsIn,
+            note: String::new(),
+        });
+        let b = WorldObject::new("青云\"宗\"", ObjectKind::Faction);
+
+        assert_eq!(objects_to_mermaid(&[a, b]), "\
+graph TD
+    o0[\"👤 林枫\"]
+    o1[\"🏰 青云#quot;宗#quot;\"]
+    o0 -->|\"所属\"| o1
+");
+    }
+
+    #[test]
+    fn test_struct_to_mermaid_snapshot() {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        let mut c1 = StructNode::new("第一章 <雨夜>", StructKind::Chapter);
+        c1.node_links.push(NodeLink {
+            target_title: "第二章".into(),
+            kind: RelationKind::Foreshadows,
+            note: String::new(),
+        });
+        c1.node_links.push(NodeLink {
+            target_title: "第二章".into(),
+            kind: RelationKind::Parallels,
+            note: String::new(),
+        });
+        let mut c2 = StructNode::new("第二章", StructKind::Chapter);
+        c2.node_links.push(NodeLink {
+            target_title: " 第一章 <雨夜> ".into(),
+            kind: RelationKind::Resolves,
+            note: String::new(),
+        });
+        vol.children = vec![c1, c2];
+
+        assert_eq!(struct_to_mermaid(&[vol]), "\
+graph TD
+    n0[\"📚 第一卷\"]
+    n0_0[\"📖 第一章 #lt;雨夜#gt;\"]
+    n0_1[\"📖 第二章\"]
+    n0 --> n0_0
+    n0 --> n0_1
+    n0_0 -.->|\"铺垫\"| n0_1
+    n0_1 -.->|\"回收\"| n0_0
+");
+    }
+}
@@ -0,0 +1,259 @@
+use std::iter::repeat_n;
+use std::ops::Range;
+
+// ── 对比左右: line and character diff ─────────────────────────────────────────
+//
+// A longest-common-subsequence diff run after trimming the common prefix and
+// suffix.  Two drafts of a chapter usually differ in a few places, so the
+// middle left for the LCS table stays small; when it does not
+// (`MAX_LCS_CELLS`), the middle is reported as one replaced block.
+
+/// Largest LCS table built (cells of `u32`, so about 16 MB).
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Edit script turning `a` into `b`, one op per element.
+fn diff_ops<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let mid_a = &a[prefix..a.len() - suffix];
+    let mid_b = &b[prefix..b.len() - suffix];
+
+    let mut ops = vec![Op::Equal; prefix];
+    if mid_a.len().saturating_mul(mid_b.len()) > MAX_LCS_CELLS {
+        ops.extend(repeat_n(Op::Delete, mid_a.len()));
+        ops.extend(repeat_n(Op::Insert, mid_b.len()));
+    } else {
+        ops.extend(lcs_ops(mid_a, mid_b));
+    }
+    ops.extend(repeat_n(Op::Equal, suffix));
+    ops
+}
+
+/// Edit script from a full LCS table.  Deletions come before insertions
+/// within a changed block.
+fn lcs_ops<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Op> {
+    let (n, m) = (a.len(), b.len());
+    let w = m + 1;
+    // lens[i * w + j]: LCS length of a[i..] and b[j..].
+    let mut lens = vec![0u32; (n + 1) * w];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lens[i * w + j] = if a[i] == b[j] {
+                lens[(i + 1) * w + j + 1] + 1
+            } else {
+                lens[(i + 1) * w + j].max(lens[i * w + j + 1])
+            };
+        }
+    }
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            ops.push(Op::Equal);
+            i += 1;
+            j += 1;
+        } else if lens[(i + 1) * w + j] >= lens[i * w + j + 1] {
+            ops.push(Op::Delete);
+            i += 1;
+        } else {
+            ops.push(Op::Insert);
+            j += 1;
+        }
+    }
+    ops.extend(repeat_n(Op::Delete, n - i));
+    ops.extend(repeat_n(Op::Insert, m - j));
+    ops
+}
+
+/// How a row of the side-by-side view differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowKind {
+    Same,
+    /// A left line replaced by a right line.
+    Changed,
+    /// Only on the left.
+    Removed,
+    /// Only on the right.
+    Added,
+}
+
+/// One row of the side-by-side view, with 0-based line indices per side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffRow {
+    pub kind: RowKind,
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+}
+
+/// Line diff of two texts, aligned for side-by-side display.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextDiff {
+    pub rows: Vec<DiffRow>,
+    /// Row ranges of consecutive rows that are not [`RowKind::Same`].
+    pub hunks: Vec<Range<usize>>,
+    /// Lines only in the right text (changed lines count on both sides).
+    pub added: usize,
+    /// Lines only in the left text.
+    pub removed: usize,
+}
+
+impl TextDiff {
+    pub fn is_identical(&self) -> bool {
+        self.hunks.is_empty()
+    }
+}
+
+/// Diff `left` against `right` line by line.  Within a changed block the
+/// n-th removed line is paired with the n-th added line as a
+/// [`RowKind::Changed`] row; the surplus of either side stands alone.
+pub fn diff_lines(left: &str, right: &str) -> TextDiff {
+    let a: Vec<&str> = left.lines().collect();
+    let b: Vec<&str> = right.lines().collect();
+    let ops = diff_ops(&a, &b);
+
+    let mut diff = TextDiff::default();
+    let (mut i, mut j) = (0, 0);
+    let mut k = 0;
+    while k < ops.len() {
+        if ops[k] == Op::Equal {
+            diff.rows.push(DiffRow { kind: RowKind::Same, left: Some(i), right: Some(j) });
+            i += 1;
+            j += 1;
+            k += 1;
+            continue;
+        }
+        let end = ops[k..].iter().position(|op| *op == Op::Equal).map_or(ops.len(), |p| k + p);
+        let dels = ops[k..end].iter().filter(|op| **op == Op::Delete).count();
+        let ins = end - k - dels;
+        let start = diff.rows.len();
+        for n in 0..dels.max(ins) {
+            let (l, r) = (n < dels, n < ins);
+            let kind = match (l, r) {
+                (true, true) => RowKind::Changed,
+                (true, false) => RowKind::Removed,
+                _ => RowKind::Added,
+            };
+            diff.rows.push(DiffRow {
+                kind,
+                left: l.then_some(i + n),
+                right: r.then_some(j + n),
+            });
+        }
+        diff.hunks.push(start..diff.rows.len());
+        diff.removed += dels;
+        diff.added += ins;
+        i += dels;
+        j += ins;
+        k = end;
+    }
+    diff
+}
+
+/// Byte ranges of the characters that differ between two versions of a
+/// line: `(in left, in right)`.  Adjacent characters merge into one range.
+pub fn char_changes(left: &str, right: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let a: Vec<(usize, char)> = left.char_indices().collect();
+    let b: Vec<(usize, char)> = right.char_indices().collect();
+    let chars_a: Vec<char> = a.iter().map(|&(_, c)| c).collect();
+    let chars_b: Vec<char> = b.iter().map(|&(_, c)| c).collect();
+
+    fn mark(ranges: &mut Vec<Range<usize>>, (at, c): (usize, char)) {
+        match ranges.last_mut() {
+            Some(r) if r.end == at => r.end = at + c.len_utf8(),
+            _ => ranges.push(at..at + c.len_utf8()),
+        }
+    }
+    let (mut out_a, mut out_b) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    for op in diff_ops(&chars_a, &chars_b) {
+        match op {
+            Op::Equal => {
+                i += 1;
+                j += 1;
+            }
+            Op::Delete => {
+                mark(&mut out_a, a[i]);
+                i += 1;
+            }
+            Op::Insert => {
+                mark(&mut out_b, b[j]);
+                j += 1;
+            }
+        }
+    }
+    (out_a, out_b)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(diff: &TextDiff) -> Vec<RowKind> {
+        diff.rows.iter().map(|r| r.kind).collect()
+    }
+
+    #[test]
+    fn test_diff_lines_identical_and_empty() {
+        let diff = diff_lines("第一段\n第二段\n", "第一段\n第二段");
+        assert!(diff.is_identical());
+        assert_eq!(diff.rows.len(), 2);
+        assert_eq!((diff.added, diff.removed), (0, 0));
+
+        let diff = diff_lines("", "新写的一段");
+        assert_eq!(kinds(&diff), [RowKind::Added]);
+        assert_eq!(diff.hunks, vec![Range { start: 0, end: 1 }]);
+        assert_eq!((diff.added, diff.removed), (1, 0));
+    }
+
+    #[test]
+    fn test_diff_lines_aligns_changes() {
+        let left = "雨夜。\n林枫推门而入。\n苏婉抬头。\n旧段落\n结尾。";
+        let right = "雨夜。\n林枫缓缓推门而入。\n苏婉抬头。\n结尾。\n尾声。";
+        let diff = diff_lines(left, right);
+        use RowKind::*;
+        assert_eq!(kinds(&diff), [Same, Changed, Same, Removed, Same, Added]);
+        assert_eq!(diff.hunks, [1..2, 3..4, 5..6]);
+        assert_eq!((diff.added, diff.removed), (2, 2));
+        assert_eq!(diff.rows[1], DiffRow { kind: Changed, left: Some(1), right: Some(1) });
+        assert_eq!(diff.rows[3], DiffRow { kind: Removed, left: Some(3), right: None });
+        assert_eq!(diff.rows[5], DiffRow { kind: Added, left: None, right: Some(4) });
+    }
+
+    #[test]
+    fn test_diff_lines_uneven_block() {
+        // Two lines rewritten as three: two changed rows plus one added.
+        let diff = diff_lines("甲\n乙\n丙\n丁", "甲\n乙一\n丙一\n丙二\n丁");
+        use RowKind::*;
+        assert_eq!(kinds(&diff), [Same, Changed, Changed, Added, Same]);
+        assert_eq!(diff.hunks, vec![Range { start: 1, end: 4 }]);
+        assert_eq!(diff.rows[3].right, Some(3));
+        assert_eq!(diff.rows[4], DiffRow { kind: Same, left: Some(3), right: Some(4) });
+        assert_eq!((diff.added, diff.removed), (3, 2));
+    }
+
+    #[test]
+    fn test_char_changes() {
+        let (a, b) = char_changes("林枫推门而入。", "林枫缓缓推门而入！");
+        assert_eq!(a, vec![Range { start: 18, end: 21 }]);
+        assert_eq!(b, [6..12, 24..27]);
+        assert_eq!(&"林枫缓缓推门而入！"[6..12], "缓缓");
+
+        let (a, b) = char_changes("same", "same");
+        assert!(a.is_empty() && b.is_empty());
+        let (a, b) = char_changes("", "新");
+        assert!(a.is_empty());
+        assert_eq!(b, vec![Range { start: 0, end: 3 }]);
+    }
+}
@@ -0,0 +1,600 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+use flate2::read::DeflateDecoder;
+
+// ── 导入 DOCX: Word manuscripts to Markdown ───────────────────────────────────
+//
+// A .docx is a zip archive; the text is in `word/document.xml` and the
+// heading styles in `word/styles.xml`.  Paragraphs become Markdown
+// paragraphs, heading styles `#` headings, list paragraphs `- ` items and
+// bold / italic runs `**` / `*`.  Images, tables and text boxes are left out
+// and listed as warnings.  The XML reader below handles exactly what Word
+// writes: elements, attributes, text, entities, comments and declarations.
+
+/// Result of [`read_docx`] / [`document_to_markdown`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DocxImport {
+    pub markdown: String,
+    /// What was left out, one line each (`第 3 段：图片已跳过`).
+    pub warnings: Vec<String>,
+}
+
+/// Convert the bytes of a .docx file.
+pub fn read_docx(bytes: &[u8]) -> Result<DocxImport, String> {
+    let document = zip_member(bytes, "word/document.xml")?
+        .ok_or_else(|| "不是有效的 DOCX 文件：缺少 word/document.xml".to_owned())?;
+    let document = String::from_utf8(document).map_err(|_| "word/document.xml 不是 UTF-8 文本".to_owned())?;
+    let styles = zip_member(bytes, "word/styles.xml")?.and_then(|s| String::from_utf8(s).ok());
+    Ok(document_to_markdown(&document, styles.as_deref()))
+}
+
+// ── Zip reading ───────────────────────────────────────────────────────────────
+
+fn bad_zip() -> String {
+    "不是有效的 DOCX 文件（zip 结构损坏）".to_owned()
+}
+
+fn u16_at(b: &[u8], i: usize) -> Result<usize, String> {
+    let s = b.get(i..i + 2).ok_or_else(bad_zip)?;
+    Ok(u16::from_le_bytes([s[0], s[1]]) as usize)
+}
+
+fn u32_at(b: &[u8], i: usize) -> Result<usize, String> {
+    let s = b.get(i..i + 4).ok_or_else(bad_zip)?;
+    Ok(u32::from_le_bytes([s[0], s[1], s[2], s[3]]) as usize)
+}
+
+/// Contents of the member `name` of the zip archive `bytes`, by way of the
+/// central directory; `None` when there is no such member.
+fn zip_member(bytes: &[u8], name: &str) -> Result<Option<Vec<u8>>, String> {
+    // End of central directory: 22 bytes plus a comment of up to 64 KiB.
+    let lowest = bytes.len().saturating_sub(22 + 0xFFFF);
+    let end = (lowest..=bytes.len().saturating_sub(22)).rev()
+        .find(|&i| bytes[i..].starts_with(&[0x50, 0x4b, 0x05, 0x06]))
+        .ok_or_else(bad_zip)?;
+    let count = u16_at(bytes, end + 10)?;
+    let mut at = u32_at(bytes, end + 16)?;
+    for _ in 0..count {
+        if u32_at(bytes, at)? != 0x0201_4b50 {
+            return Err(bad_zip());
+        }
+        let method = u16_at(bytes, at + 10)?;
+        let compressed = u32_at(bytes, at + 20)?;
+        let name_len = u16_at(bytes, at + 28)?;
+        let extra_len = u16_at(bytes, at + 30)?;
+        let comment_len = u16_at(bytes, at + 32)?;
+        let offset = u32_at(bytes, at + 42)?;
+        let entry_name = bytes.get(at + 46..at + 46 + name_len).ok_or_else(bad_zip)?;
+        at += 46 + name_len + extra_len + comment_len;
+        if entry_name != name.as_bytes() {
+            continue;
+        }
+        let data = offset + 30 + u16_at(bytes, offset + 26)? + u16_at(bytes, offset + 28)?;
+        let raw = bytes.get(data..data + compressed).ok_or_else(bad_zip)?;
+        return match method {
+            0 => Ok(Some(raw.to_vec())),
+            8 => {
+                let mut out = Vec::new();
+                DeflateDecoder::new(raw).read_to_end(&mut out).map_err(|_| bad_zip())?;
+                Ok(Some(out))
+            }
+            _ => Err(format!("{name} 使用了不支持的压缩方式")),
+        };
+    }
+    Ok(None)
+}
+
+// ── XML reading ───────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum XmlEvent<'a> {
+    /// Start tag; `empty` for `<x/>`, which has no end event.
+    Start { name: &'a str, attrs: Vec<(&'a str, String)>, empty: bool },
+    End(&'a str),
+    Text(String),
+}
+
+/// `&lt;`, `&amp;`, `&#x4E00;` … decoded; unknown entities stay as written.
+fn unescape(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_owned();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let Some(end) = rest.find(';') else { break };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X"))
+                .map_or_else(|| entity.strip_prefix('#').and_then(|n| n.parse().ok()), |h| u32::from_str_radix(h, 16).ok())
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Local part of a prefixed name: `w:p` → `p`.
+fn local(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Pull-reader over an XML document.  Malformed input ends the stream.
+struct XmlReader<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> XmlReader<'a> {
+    fn new(src: &'a str) -> Self {
+        XmlReader { src: src.trim_start_matches('\u{feff}'), pos: 0 }
+    }
+
+    fn parse_tag(tag: &'a str) -> XmlEvent<'a> {
+        if let Some(name) = tag.strip_prefix('/') {
+            return XmlEvent::End(name.trim());
+        }
+        let empty = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let mut attrs = Vec::new();
+        let mut rest = &tag[name_end..];
+        while let Some(eq) = rest.find('=') {
+            let key = rest[..eq].trim();
+            let after = rest[eq + 1..].trim_start();
+            let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else { break };
+            let Some(close) = after[1..].find(quote) else { break };
+            attrs.push((key, unescape(&after[1..1 + close])));
+            rest = &after[close + 2..];
+        }
+        XmlEvent::Start { name: &tag[..name_end], attrs, empty }
+    }
+}
+
+impl<'a> Iterator for XmlReader<'a> {
+    type Item = XmlEvent<'a>;
+
+    fn next(&mut self) -> Option<XmlEvent<'a>> {
+        loop {
+            let rest = &self.src[self.pos..];
+            if rest.is_empty() {
+                return None;
+            }
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.pos += end;
+                return Some(XmlEvent::Text(unescape(&rest[..end])));
+            }
+            if let Some(body) = rest.strip_prefix("<![CDATA[") {
+                let end = body.find("]]>")?;
+                self.pos += 9 + end + 3;
+                return Some(XmlEvent::Text(body[..end].to_owned()));
+            }
+            // Comments, declarations and processing instructions.
+            let skip = [("<!--", "-->"), ("<?", "?>"), ("<!", ">")].into_iter().find(|(open, _)| rest.starts_with(open));
+            if let Some((_, close)) = skip {
+                self.pos += rest.find(close)? + close.len();
+                continue;
+            }
+            let end = rest.find('>')?;
+            self.pos += end + 1;
+            return Some(Self::parse_tag(&rest[1..end]));
+        }
+    }
+}
+
+fn attr<'a>(attrs: &'a [(&str, String)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|(k, _)| local(k) == name).map(|(_, v)| v.as_str())
+}
+
+/// `<w:b/>` and `<w:b w:val="true"/>` switch a property on, `w:val="0"`
+/// off.
+fn toggle_on(attrs: &[(&str, String)]) -> bool {
+    !matches!(attr(attrs, "val"), Some("0" | "false" | "off" | "none"))
+}
+
+// ── Heading styles ────────────────────────────────────────────────────────────
+
+/// Heading level (1–6) of a style by its display name: `heading 1`,
+/// `标题 2`, `Title`.
+fn level_from_style_name(name: &str) -> Option<usize> {
+    let lower = name.trim().to_lowercase();
+    if lower == "title" || lower == "标题" {
+        return Some(1);
+    }
+    let digits = lower.strip_prefix("heading").or_else(|| lower.strip_prefix("标题"))?.trim();
+    digits.parse::<usize>().ok().filter(|n| (1..=9).contains(n)).map(|n| n.min(6))
+}
+
+/// Heading level of each paragraph style id in `styles.xml`, from the
+/// style's name or its outline level.
+fn heading_styles(styles: &str) -> HashMap<String, usize> {
+    let mut out = HashMap::new();
+    let mut current: Option<String> = None;
+    for event in XmlReader::new(styles) {
+        match event {
+            XmlEvent::Start { name, attrs, .. } => match local(name) {
+                "style" => current = attr(&attrs, "styleId").map(str::to_owned),
+                "name" | "outlineLvl" => {
+                    let level = match (local(name), attr(&attrs, "val")) {
+                        ("name", Some(v)) => level_from_style_name(v),
+                        (_, Some(v)) => v.parse::<usize>().ok().filter(|&n| n < 9).map(|n| (n + 1).min(6)),
+                        _ => None,
+                    };
+                    if let (Some(id), Some(level)) = (&current, level) {
+                        out.entry(id.clone()).or_insert(level);
+                    }
+                }
+                _ => {}
+            },
+            XmlEvent::End(name) if local(name) == "style" => current = None,
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Heading level of a style id without `styles.xml`: `Heading1`, `Title`.
+fn level_from_style_id(id: &str) -> Option<usize> {
+    level_from_style_name(id).or_else(|| {
+        let lower = id.to_lowercase();
+        lower.strip_prefix("heading")?.parse::<usize>().ok().filter(|n| (1..=9).contains(n)).map(|n| n.min(6))
+    })
+}
+
+// ── document.xml → Markdown ───────────────────────────────────────────────────
+
+/// Escape what would otherwise read as Markdown emphasis or code.
+fn escape_inline(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('*', "\\*").replace('`', "\\`")
+}
+
+/// One run of text with the same formatting.
+#[derive(Debug, Default)]
+struct Span {
+    text: String,
+    bold: bool,
+    italic: bool,
+}
+
+/// `spans` as Markdown; emphasis markers hug the text (whitespace stays
+/// outside) and do not cross line breaks.  `plain` drops emphasis, for
+/// headings.
+fn render_spans(spans: &[Span], plain: bool) -> String {
+    let mut out = String::new();
+    for span in spans {
+        let marker = match (span.bold && !plain, span.italic && !plain) {
+            (true, true) => "***",
+            (true, false) => "**",
+            (false, true) => "*",
+            (false, false) => "",
+        };
+        for (i, line) in span.text.split('\n').enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            let core = line.trim();
+            if marker.is_empty() || core.is_empty() {
+                out.push_str(&escape_inline(line));
+                continue;
+            }
+            let lead = &line[..line.len() - line.trim_start().len()];
+            let trail = &line[line.trim_end().len()..];
+            out.push_str(lead);
+            out.push_str(marker);
+            out.push_str(&escape_inline(core));
+            out.push_str(marker);
+            out.push_str(trail);
+        }
+    }
+    out
+}
+
+/// State of the paragraph being read.
+#[derive(Debug, Default)]
+struct Paragraph {
+    style: Option<String>,
+    outline_level: Option<usize>,
+    list: bool,
+    spans: Vec<Span>,
+}
+
+impl Paragraph {
+    fn push_text(&mut self, text: &str, bold: bool, italic: bool) {
+        match self.spans.last_mut() {
+            Some(last) if last.bold == bold && last.italic == italic => last.text.push_str(text),
+            _ => self.spans.push(Span { text: text.to_owned(), bold, italic }),
+        }
+    }
+}
+
+/// Convert the XML of `word/document.xml`; `styles` is `word/styles.xml`,
+/// which tells heading styles apart when their ids are not `HeadingN`.
+pub fn document_to_markdown(document: &str, styles: Option<&str>) -> DocxImport {
+    let headings = styles.map(heading_styles).unwrap_or_default();
+    let mut blocks: Vec<String> = Vec::new();
+    let mut warnings = Vec::new();
+
+    let mut depth = 0usize;
+    // Depth at which a skipped element (image, table, text box) started.
+    let mut skip_from: Option<usize> = None;
+    let mut table_rows = 0;
+    let mut para: Option<Paragraph> = None;
+    let mut in_ppr = false;
+    let mut in_rpr = false;
+    let mut in_text = false;
+    let (mut bold, mut italic) = (false, false);
+
+    for event in XmlReader::new(document) {
+        let (name, attrs, empty) = match event {
+            XmlEvent::Text(text) => {
+                if skip_from.is_none() && in_text {
+                    if let Some(p) = &mut para {
+                        p.push_text(&text, bold, italic);
+                    }
+                }
+                continue;
+            }
+            XmlEvent::End(name) => {
+                depth = depth.saturating_sub(1);
+                if let Some(from) = skip_from {
+                    if local(name) == "tr" {
+                        table_rows += 1;
+                    }
+                    if depth == from {
+                        skip_from = None;
+                        if local(name) == "tbl" {
+                            warnings.push(format!("第 {} 段后：表格已跳过（{table_rows} 行）", blocks.len()));
+                        }
+                    }
+                    continue;
+                }
+                match local(name) {
+                    "pPr" => in_ppr = false,
+                    "rPr" => in_rpr = false,
+                    "t" => in_text = false,
+                    "p" => {
+                        if let Some(p) = para.take() {
+                            if let Some(block) = paragraph_block(&p, &headings) {
+                                blocks.push(block);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            XmlEvent::Start { name, attrs, empty } => (name, attrs, empty),
+        };
+        if skip_from.is_some() {
+            if !empty {
+                depth += 1;
+            }
+            continue;
+        }
+        let tag = local(name);
+        match tag {
+            "tbl" | "drawing" | "pict" | "object" | "txbxContent" if !empty => {
+                if tag == "tbl" {
+                    table_rows = 0;
+                } else {
+                    warnings.push(format!("第 {} 段：图片或文本框已跳过", blocks.len() + 1));
+                }
+                skip_from = Some(depth);
+            }
+            "p" => {
+                para = Some(Paragraph::default());
+                in_ppr = false;
+            }
+            "pPr" => in_ppr = true,
+            "pStyle" if in_ppr => {
+                if let Some(p) = &mut para {
+                    p.style = attr(&attrs, "val").map(str::to_owned);
+                }
+            }
+            "outlineLvl" if in_ppr => {
+                if let Some(p) = &mut para {
+                    p.outline_level = attr(&attrs, "val").and_then(|v| v.parse::<usize>().ok()).filter(|&n| n < 9);
+                }
+            }
+            "numPr" if in_ppr => {
+                if let Some(p) = &mut para {
+                    p.list = true;
+                }
+            }
+            "r" => (bold, italic) = (false, false),
+            "rPr" if !in_ppr => in_rpr = true,
+            "b" if in_rpr => bold = toggle_on(&attrs),
+            "i" if in_rpr => italic = toggle_on(&attrs),
+            "t" => in_text = !empty,
+            "tab" if !in_ppr => {
+                if let Some(p) = &mut para {
+                    p.push_text(" ", bold, italic);
+                }
+            }
+            "br" | "cr" if attr(&attrs, "type").is_none_or(|t| t == "textWrapping") => {
+                if let Some(p) = &mut para {
+                    p.push_text("\n", bold, italic);
+                }
+            }
+            _ => {}
+        }
+        if !empty {
+            depth += 1;
+        }
+        // `<w:p/>`: an empty paragraph, nothing to emit.
+        if empty && tag == "p" {
+            para = None;
+        }
+    }
+
+    let mut markdown = blocks.join("\n\n");
+    if !markdown.is_empty() {
+        markdown.push('\n');
+    }
+    DocxImport { markdown, warnings }
+}
+
+/// Markdown of one paragraph; `None` when it holds no text.
+fn paragraph_block(p: &Paragraph, headings: &HashMap<String, usize>) -> Option<String> {
+    let level = p.outline_level.map(|n| (n + 1).min(6)).or_else(|| {
+        let style = p.style.as_deref()?;
+        headings.get(style).copied().or_else(|| level_from_style_id(style))
+    });
+    let text = render_spans(&p.spans, level.is_some());
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(match level {
+        // A heading is one line.
+        Some(level) => format!("{} {}", "#".repeat(level), text.split_whitespace().collect::<Vec<_>>().join(" ")),
+        None if p.list => format!("- {text}"),
+        None if text.starts_with(['#', '>']) => format!("\\{text}"),
+        None => text.to_owned(),
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::ZipWriter;
+
+    /// A `document.xml` with `body` inside `<w:body>`.
+    fn doc(body: &str) -> String {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">\
+             <w:body>{body}<w:sectPr/></w:body></w:document>"
+        )
+    }
+
+    fn para(style: Option<&str>, runs: &str) -> String {
+        let ppr = style.map_or(String::new(), |s| format!("<w:pPr><w:pStyle w:val=\"{s}\"/></w:pPr>"));
+        format!("<w:p>{ppr}{runs}</w:p>")
+    }
+
+    fn run(text: &str, props: &str) -> String {
+        let rpr = if props.is_empty() { String::new() } else { format!("<w:rPr>{props}</w:rPr>") };
+        format!("<w:r>{rpr}<w:t xml:space=\"preserve\">{text}</w:t></w:r>")
+    }
+
+    #[test]
+    fn test_xml_reader() {
+        let xml = "<?xml version=\"1.0\"?><!-- 注释 --><a x=\"1 &amp; 2\" y='&#x4E00;'><b/>甲&lt;乙<![CDATA[<丙>]]></a>";
+        let events: Vec<XmlEvent> = XmlReader::new(xml).collect();
+        assert_eq!(events, [
+            XmlEvent::Start { name: "a", attrs: vec![("x", "1 & 2".to_owned()), ("y", "一".to_owned())], empty: false },
+            XmlEvent::Start { name: "b", attrs: vec![], empty: true },
+            XmlEvent::Text("甲<乙".to_owned()),
+            XmlEvent::Text("<丙>".to_owned()),
+            XmlEvent::End("a"),
+        ]);
+        assert_eq!(unescape("a &unknown; &#20013; &"), "a &unknown; 中 &");
+    }
+
+    #[test]
+    fn test_headings_and_emphasis() {
+        let body = [
+            para(Some("Heading1"), &run("第一卷", "")),
+            para(Some("Heading2"), &(run("第一章 ", "<w:b/>") + &run("雨夜", ""))),
+            para(None, &(run("他", "") + &run("推门", "<w:b/>") + &run("而入，", "") + &run(" 雨很大 ", "<w:i/>"))),
+            para(None, &(run("两者", "<w:b/><w:i/>") + &run("不加粗", "<w:b w:val=\"0\"/>"))),
+            para(None, ""),
+            para(Some("ListParagraph"), &run("列表项", "")).replace("<w:pPr>", "<w:pPr><w:numPr><w:ilvl w:val=\"0\"/></w:numPr>"),
+            para(None, &run("# 不是标题 *星号*", "")),
+            para(None, &(run("第一行", "") + "<w:r><w:br/></w:r>" + &run("第二行", ""))),
+        ].concat();
+        let out = document_to_markdown(&doc(&body), None);
+        assert_eq!(out.markdown, "\
+# 第一卷
+
+## 第一章 雨夜
+
+他**推门**而入， *雨很大*
+
+***两者***不加粗
+
+- 列表项
+
+\\# 不是标题 \\*星号\\*
+
+第一行
+第二行
+");
+        assert!(out.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_styles_xml_and_outline_level() {
+        // Chinese Word names heading styles 标题 N with numeric ids.
+        let styles = "<w:styles>\
+            <w:style w:type=\"paragraph\" w:styleId=\"1\"><w:name w:val=\"heading 1\"/></w:style>\
+            <w:style w:type=\"paragraph\" w:styleId=\"a3\"><w:name w:val=\"标题 2\"/></w:style>\
+            <w:style w:type=\"paragraph\" w:styleId=\"custom\"><w:name w:val=\"章节\"/>\
+              <w:pPr><w:outlineLvl w:val=\"2\"/></w:pPr></w:style>\
+            <w:style w:type=\"paragraph\" w:styleId=\"a4\"><w:name w:val=\"Normal\"/></w:style>\
+            </w:styles>";
+        let direct = "<w:p><w:pPr><w:outlineLvl w:val=\"1\"/></w:pPr><w:r><w:t>直接大纲级别</w:t></w:r></w:p>";
+        let body = [
+            para(Some("1"), &run("卷", "")),
+            para(Some("a3"), &run("章", "")),
+            para(Some("custom"), &run("节", "")),
+            para(Some("a4"), &run("正文", "")),
+            direct.to_owned(),
+        ].concat();
+        let out = document_to_markdown(&doc(&body), Some(styles));
+        assert_eq!(out.markdown, "# 卷\n\n## 章\n\n### 节\n\n正文\n\n## 直接大纲级别\n");
+        // Without styles.xml the numeric id is not a heading.
+        assert!(document_to_markdown(&doc(&body), None).markdown.starts_with("卷\n"));
+    }
+
+    #[test]
+    fn test_tables_and_images_skipped() {
+        let table = "<w:tbl><w:tr><w:tc><w:p><w:r><w:t>格</w:t></w:r></w:p></w:tc></w:tr>\
+                     <w:tr><w:tc><w:p><w:r><w:t>格</w:t></w:r></w:p></w:tc></w:tr></w:tbl>";
+        let image = "<w:p><w:r><w:t>图前</w:t></w:r><w:r><w:drawing><wp:inline><a:graphic>\
+                     <w:txbxContent><w:p><w:r><w:t>框内</w:t></w:r></w:p></w:txbxContent>\
+                     </a:graphic></wp:inline></w:drawing></w:r><w:r><w:t>图后</w:t></w:r></w:p>";
+        let body = [para(None, &run("开头", "")), table.to_owned(), image.to_owned()].concat();
+        let out = document_to_markdown(&doc(&body), None);
+        assert_eq!(out.markdown, "开头\n\n图前图后\n");
+        assert_eq!(out.warnings, ["第 1 段后：表格已跳过（2 行）", "第 2 段：图片或文本框已跳过"]);
+    }
+
+    #[test]
+    fn test_read_docx() {
+        let body = para(Some("Heading1"), &run("第一章", "")) + &para(None, &run("正文 &amp; 引号", ""));
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("[Content_Types].xml", "<Types/>".as_bytes(), None).unwrap();
+        zip.add_file("word/document.xml", doc(&body).as_bytes(), None).unwrap();
+        let bytes = zip.finish().unwrap();
+        let out = read_docx(&bytes).unwrap();
+        assert_eq!(out.markdown, "# 第一章\n\n正文 & 引号\n");
+
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("readme.txt", "x".as_bytes(), None).unwrap();
+        assert!(read_docx(&zip.finish().unwrap()).unwrap_err().contains("word/document.xml"));
+        assert!(read_docx(b"not a zip").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::models::count_words;
+
+// ── 查找重复段落: the same paragraph pasted into several places ────────────────
+//
+// A paragraph is a run of non-blank lines; headings end it and are not
+// compared.  Whitespace is normalized first, so re-indented or re-wrapped
+// copies still match.
+
+/// Shortest paragraph reported, in 字 (non-whitespace characters).
+pub const DEFAULT_MIN_CHARS: usize = 50;
+
+/// Where a duplicated paragraph starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateLocation {
+    pub file: PathBuf,
+    /// 1-based line of the paragraph's first line.
+    pub line_no: usize,
+}
+
+/// One paragraph found at two or more places.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    /// The paragraph, whitespace normalized.
+    pub text: String,
+    /// Non-whitespace characters of `text`.
+    pub chars: usize,
+    /// In document order, then line order.
+    pub locations: Vec<DuplicateLocation>,
+}
+
+/// Paragraphs of `text` as `(first line, normalized text)`.
+pub fn split_paragraphs(text: &str) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut current: Option<(usize, Vec<&str>)> = None;
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            out.extend(current.take());
+            continue;
+        }
+        current.get_or_insert_with(|| (i + 1, Vec::new())).1.push(trimmed);
+    }
+    out.extend(current);
+    out.into_iter()
+        .map(|(line_no, lines)| (line_no, normalize_whitespace(&lines.join(" "))))
+        .collect()
+}
+
+/// `text` trimmed, with each whitespace run between two ASCII characters
+/// replaced by one space and every other run (the 首行缩进, a line break
+/// inside Chinese text) dropped.
+pub fn normalize_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for word in text.split_whitespace() {
+        let joins_ascii = out.chars().next_back().is_some_and(|c| c.is_ascii())
+            && word.chars().next().is_some_and(|c| c.is_ascii());
+        if joins_ascii {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    out
+}
+
+/// Paragraphs of `docs` (`(path, text)`, in order) with at least
+/// `min_chars` 字 that occur more than once, in one file or across files.
+/// Groups are ordered by their first occurrence.  `progress` is called with
+/// the number of documents done and the total after each one.
+pub fn find_duplicate_paragraphs(
+    docs: &[(&Path, &str)],
+    min_chars: usize,
+    mut progress: impl FnMut(usize, usize),
+) -> Vec<DuplicateGroup> {
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    // Paragraph hash → indices into `groups` (more than one on a collision).
+    let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (done, (file, text)) in docs.iter().enumerate() {
+        for (line_no, para) in split_paragraphs(text) {
+            let chars = count_words(&para);
+            if chars < min_chars {
+                continue;
+            }
+            let mut hasher = DefaultHasher::new();
+            para.hash(&mut hasher);
+            let location = DuplicateLocation { file: file.to_path_buf(), line_no };
+            let slots = by_hash.entry(hasher.finish()).or_default();
+            match slots.iter().find(|&&g| groups[g].text == para) {
+                Some(&g) => groups[g].locations.push(location),
+                None => {
+                    slots.push(groups.len());
+                    groups.push(DuplicateGroup { text: para, chars, locations: vec![location] });
+                }
+            }
+        }
+        progress(done + 1, docs.len());
+    }
+    groups.retain(|g| g.locations.len() > 1);
+    groups
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_paragraphs() {
+        let text = "# 第一章\n\u{3000}\u{3000}雨下了一夜，\n  街上没有人。\n\n\n第二段\t 在这里\n## 小节\n第三段\n";
+        assert_eq!(split_paragraphs(text), vec![
+            (2, "雨下了一夜，街上没有人。".to_owned()),
+            (6, "第二段在这里".to_owned()),
+            (8, "第三段".to_owned()),
+        ]);
+        assert_eq!(normalize_whitespace("  the  quick\n fox 跑了 \u{3000}起来 "), "the quick fox跑了起来");
+    }
+
+    #[test]
+    fn test_find_duplicate_paragraphs() {
+        let scene = "林远推开门，雨夜里只有一盏灯还亮着。";
+        let ch1 = Path::new("/p/Content/第1章.md");
+        let ch2 = Path::new("/p/Content/第2章.md");
+        let ch3 = Path::new("/p/Content/第3章.md");
+        let text1 = format!("# 第1章\n\n{scene}\n\n短句。\n\n另一段不同的文字，长度也足够长了。\n");
+        // Re-indented and re-wrapped: still the same paragraph.
+        let text2 = "# 第2章\n\n\u{3000}\u{3000}林远推开门，\n雨夜里只有一盏灯还亮着。\n\n短句。\n".to_owned();
+        // Twice in one file.
+        let text3 = "另一段不同的文字，长度也足够长了。\n\n中间\n\n另一段不同的文字，长度也足够长了。\n".to_owned();
+        let docs = [(ch1, text1.as_str()), (ch2, text2.as_str()), (ch3, text3.as_str())];
+
+        let mut calls = Vec::new();
+        let groups = find_duplicate_paragraphs(&docs, 10, |done, total| calls.push((done, total)));
+        assert_eq!(calls, vec![(1, 3), (2, 3), (3, 3)]);
+        let found: Vec<(usize, Vec<(&Path, usize)>)> = groups.iter()
+            .map(|g| (g.chars, g.locations.iter().map(|l| (l.file.as_path(), l.line_no)).collect()))
+            .collect();
+        assert_eq!(found, vec![
+            (count_words(scene), vec![(ch1, 3), (ch2, 3)]),
+            (17, vec![(ch1, 7), (ch3, 1), (ch3, 5)]),
+        ]);
+        assert_eq!(groups[0].text, scene);
+
+        // "短句。" repeats too, but is under the threshold.
+        assert!(find_duplicate_paragraphs(&docs, 100, |_, _| {}).is_empty());
+        assert_eq!(find_duplicate_paragraphs(&docs, 1, |_, _| {}).len(), 3);
+    }
+}
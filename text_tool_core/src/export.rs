@@ -0,0 +1,167 @@
+use std::path::{Path, PathBuf};
+
+use crate::annotations::strip_annotations;
+use crate::ignore::IgnoreRules;
+use crate::markdown::strip_heading;
+use crate::models::WorldObject;
+
+// ── Export ────────────────────────────────────────────────────────────────────
+
+/// Concatenate the `.md` files directly inside `content_dir` in alphabetical
+/// order, each preceded by a `# ── name ──` banner and with its 注释
+/// stripped.  Unreadable files and those `ignore` matches are skipped.
+pub fn merge_chapter_files(content_dir: &Path, ignore: &IgnoreRules) -> String {
+    let mut md_files: Vec<PathBuf> = std::fs::read_dir(content_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("md"))
+        .filter(|p| !ignore.is_ignored_path(p, false))
+        .collect();
+    md_files.sort();
+
+    let mut merged = String::new();
+    for path in &md_files {
+        if let Ok(text) = std::fs::read_to_string(path) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            merged.push_str(&format!("# ── {name} ──\n\n"));
+            merged.push_str(&strip_annotations(&text));
+            merged.push_str("\n\n");
+        }
+    }
+    merged
+}
+
+/// Concatenate `(title, text)` documents, each under its own `# title`
+/// heading.  The documents' own headings are demoted one level (see
+/// [`demote_headings`]) so the per-document heading stays on top; a leading
+/// `# title` that just repeats the title is dropped.
+pub fn merge_documents(docs: &[(String, String)]) -> String {
+    let mut merged = String::new();
+    for (title, text) in docs {
+        let mut lines = text.lines().skip_while(|l| l.trim().is_empty()).peekable();
+        if lines.peek().and_then(|l| strip_heading(l, 1)) == Some(title.trim()) {
+            lines.next();
+        }
+        let body: Vec<&str> = lines.collect();
+        let body = demote_headings(&body.join("\n"));
+        if !merged.is_empty() {
+            merged.push('\n');
+        }
+        merged.push_str(&format!("# {title}\n\n"));
+        let body = body.trim_matches('\n');
+        if !body.is_empty() {
+            merged.push_str(body);
+            merged.push('\n');
+        }
+    }
+    merged
+}
+
+/// Add one `#` to every ATX heading outside fenced code blocks.  Level-6
+/// headings stay at level 6, the deepest Markdown has.
+pub fn demote_headings(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 16);
+    let mut in_code = false;
+    for (i, line) in text.lines().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        } else if !in_code && (1..=5).any(|n| strip_heading(line, n).is_some()) {
+            out.push('#');
+        }
+        out.push_str(line);
+    }
+    if text.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// Render world objects as CSV (`名称,类型,描述,背景,标签`), one row per
+/// object.  Tags are joined with `、`; fields are quoted per RFC 4180 when
+/// needed.
+pub fn objects_to_csv(objects: &[WorldObject]) -> String {
+    let mut csv = String::from("名称,类型,描述,背景,标签\n");
+    for obj in objects {
+        let fields = [
+            obj.name.as_str(),
+            obj.kind.label(),
+            obj.description.as_str(),
+            obj.background.as_str(),
+            &obj.tags.join("、"),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// `field` quoted per RFC 4180 when it holds a comma, quote or line break.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demote_headings_skips_code() {
+        let text = "# 一\n正文 # 不是\n###### 六\n```\n# 代码\n```\n##无空格\n";
+        assert_eq!(demote_headings(text), "## 一\n正文 # 不是\n###### 六\n```\n# 代码\n```\n##无空格\n");
+    }
+
+    #[test]
+    fn test_merge_documents_normalizes_levels() {
+        let docs = vec![
+            ("第一章".to_owned(), "# 第一章\n\n正文一\n## 雨夜\n".to_owned()),
+            ("第二章".to_owned(), "开头\n# 小标题\n".to_owned()),
+            ("空章".to_owned(), "\n".to_owned()),
+        ];
+        assert_eq!(
+            merge_documents(&docs),
+            "# 第一章\n\n正文一\n### 雨夜\n\n# 第二章\n\n开头\n## 小标题\n\n# 空章\n\n",
+        );
+    }
+
+    #[test]
+    fn test_merge_chapter_files_sorted() {
+        let dir = std::env::temp_dir().join("qingmo_core_test_merge");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("02.md"), "乙%% 待改 %%").unwrap();
+        std::fs::write(dir.join("01.md"), "甲").unwrap();
+        std::fs::write(dir.join("notes.json"), "{}").unwrap();
+
+        let merged = merge_chapter_files(&dir, &IgnoreRules::default());
+        assert_eq!(merged, "# ── 01.md ──\n\n甲\n\n# ── 02.md ──\n\n乙\n\n");
+
+        let ignore = IgnoreRules::parse(&dir, "02.md\n");
+        assert_eq!(merge_chapter_files(&dir, &ignore), "# ── 01.md ──\n\n甲\n\n");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_objects_to_csv_quotes_fields() {
+        use crate::models::ObjectKind;
+        let mut obj = WorldObject::new("林枫", ObjectKind::Character);
+        obj.description = "少年,剑客".to_owned();
+        obj.background = "说\"不\"".to_owned();
+        obj.tags = vec!["主角团".to_owned(), "天才".to_owned()];
+
+        let csv = objects_to_csv(&[obj]);
+        assert_eq!(csv, "名称,类型,描述,背景,标签\n林枫,人物,\"少年,剑客\",\"说\"\"不\"\"\",主角团、天才\n");
+    }
+}
@@ -0,0 +1,554 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::front_matter::{parse_front_matter, FrontMatter};
+use crate::ignore::IgnoreRules;
+
+// ── File tree node ────────────────────────────────────────────────────────────
+
+/// What the file tree leaves out.
+#[derive(Debug, Clone, Default)]
+pub struct TreeFilter {
+    /// Hide `.json` files.
+    pub hide_json: bool,
+    /// The project's `.gitignore` / `.texttoolignore` rules.
+    pub ignore: IgnoreRules,
+    /// List ignored entries, marked [`FileNode::ignored`], instead of
+    /// leaving them out.
+    pub show_ignored: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileNode {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// Matched by the project's ignore rules; only listed when
+    /// [`TreeFilter::show_ignored`] is set.
+    pub ignored: bool,
+    pub expanded: bool,
+    /// Directory entries have been read into `children`.  Directories below
+    /// the one a tree is built from start unloaded; see [`FileNode::load_children`].
+    pub loaded: bool,
+    /// Modification time of the directory when it was listed, used by
+    /// [`FileNode::refresh`] to skip unchanged directories.
+    pub listed_mtime: Option<SystemTime>,
+    pub children: Vec<FileNode>,
+}
+
+impl FileNode {
+    /// Build a file tree node, leaving out what `filter` hides.  A directory
+    /// lists its own entries only; subdirectories stay unloaded (and
+    /// collapsed) until [`FileNode::load_children`] is called on them.
+    pub fn from_path_filtered(path: &Path, filter: &TreeFilter) -> Option<Self> {
+        let mut node = Self::entry(path, filter)?;
+        if node.is_dir {
+            node.load_children(filter).ok()?;
+            node.expanded = true;
+        }
+        Some(node)
+    }
+
+    /// A node for `path` without reading anything below it.
+    fn entry(path: &Path, filter: &TreeFilter) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().into_owned();
+        let is_dir = path.is_dir();
+        // When hide_json is set, exclude .json files from the visible tree.
+        if !is_dir && filter.hide_json && path.extension().and_then(|e| e.to_str()) == Some("json") {
+            return None;
+        }
+        let ignored = filter.ignore.is_ignored_path(path, is_dir);
+        if ignored && !filter.show_ignored {
+            return None;
+        }
+        Some(FileNode {
+            name,
+            path: path.to_owned(),
+            is_dir,
+            ignored,
+            expanded: false,
+            loaded: !is_dir,
+            listed_mtime: None,
+            children: vec![],
+        })
+    }
+
+    /// The entries of `dir`, folders first and then by name.  Subdirectories
+    /// are returned unloaded.
+    pub fn list_dir(dir: &Path, filter: &TreeFilter) -> std::io::Result<Vec<FileNode>> {
+        let mut children: Vec<FileNode> = std::fs::read_dir(dir)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| FileNode::entry(&e.path(), filter))
+            .collect();
+        children.sort_by(|a, b| {
+            b.is_dir.cmp(&a.is_dir).then(a.name.cmp(&b.name))
+        });
+        Ok(children)
+    }
+
+    /// Read the entries of this directory into `children`.
+    pub fn load_children(&mut self, filter: &TreeFilter) -> std::io::Result<()> {
+        let mtime = dir_mtime(&self.path);
+        self.set_children(Self::list_dir(&self.path, filter)?, mtime);
+        Ok(())
+    }
+
+    /// Install a listing made elsewhere (e.g. on a background thread),
+    /// keeping the state of entries that were already there.
+    pub fn set_children(&mut self, children: Vec<FileNode>, mtime: Option<SystemTime>) {
+        let mut old = std::mem::take(&mut self.children);
+        self.children = children.into_iter()
+            .map(|new| match old.iter().position(|o| o.path == new.path && o.is_dir == new.is_dir) {
+                Some(i) => FileNode { ignored: new.ignored, ..old.swap_remove(i) },
+                None => new,
+            })
+            .collect();
+        self.loaded = true;
+        self.listed_mtime = mtime;
+    }
+
+    /// Load this directory and every directory below it, expanded.
+    pub fn load_all(&mut self, filter: &TreeFilter) {
+        if !self.is_dir {
+            return;
+        }
+        if !self.loaded && self.load_children(filter).is_err() {
+            return;
+        }
+        self.expanded = true;
+        for child in &mut self.children {
+            child.load_all(filter);
+        }
+    }
+
+    /// Re-list loaded directories whose modification time changed since they
+    /// were listed; unchanged directories and unloaded ones are not read.
+    pub fn refresh(&mut self, filter: &TreeFilter) {
+        if !self.is_dir || !self.loaded {
+            return;
+        }
+        let mtime = dir_mtime(&self.path);
+        if mtime.is_none() || mtime != self.listed_mtime {
+            let _ = self.load_children(filter);
+        }
+        for child in &mut self.children {
+            child.refresh(filter);
+        }
+    }
+}
+
+/// The node for `path` in `nodes` or below.
+pub fn find_node_mut<'a>(nodes: &'a mut [FileNode], path: &Path) -> Option<&'a mut FileNode> {
+    for node in nodes {
+        if node.path == path {
+            return Some(node);
+        }
+        if node.is_dir && path.starts_with(&node.path) {
+            return find_node_mut(&mut node.children, path);
+        }
+    }
+    None
+}
+
+fn dir_mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// ── Open file ─────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct OpenFile {
+    pub path: PathBuf,
+    pub content: String,
+    pub modified: bool,
+    /// A generated, read-only buffer with no file behind it (`path` only
+    /// names it); see [`OpenFile::scratch`].
+    pub scratch: bool,
+}
+
+impl OpenFile {
+    pub fn new(path: PathBuf, content: String) -> Self {
+        OpenFile { path, content, modified: false, scratch: false }
+    }
+
+    /// A read-only buffer named `name` that is never written to disk.
+    pub fn scratch(name: &str, content: String) -> Self {
+        OpenFile { path: PathBuf::from(name), content, modified: false, scratch: true }
+    }
+
+    pub fn save(&mut self) -> std::io::Result<()> {
+        if self.scratch {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("「{}」是临时文档，不会保存", self.path.display()),
+            ));
+        }
+        safe_write(&self.path, &self.content)?;
+        self.modified = false;
+        Ok(())
+    }
+
+    /// Replace the buffer with the file on disk and return the discarded
+    /// text.  On error (e.g. the file was deleted) the buffer is untouched.
+    pub fn reload(&mut self) -> std::io::Result<String> {
+        let content = std::fs::read_to_string(&self.path)?;
+        self.modified = false;
+        Ok(std::mem::replace(&mut self.content, content))
+    }
+
+    pub fn title(&self) -> String {
+        let name = self.path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "untitled".to_owned());
+        if self.modified {
+            format!("● {name}")
+        } else {
+            name
+        }
+    }
+
+    /// Fields of the leading front-matter block, if the buffer has one.
+    pub fn front_matter(&self) -> Option<FrontMatter> {
+        parse_front_matter(&self.content)
+    }
+
+    pub fn is_markdown(&self) -> bool {
+        matches!(
+            self.path.extension().and_then(|e| e.to_str()),
+            Some("md") | Some("markdown")
+        )
+    }
+}
+
+// ── Atomic writes ─────────────────────────────────────────────────────────────
+
+/// Replace `path` with `contents` without ever leaving it half-written.
+///
+/// The data goes to a temporary sibling file, is flushed to disk and then
+/// renamed over the target, so a crash or full disk leaves either the old or
+/// the new file.  A read-only target is refused with `PermissionDenied`, as
+/// a plain `std::fs::write` would; the target's permissions are kept.
+pub fn safe_write(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let existing = std::fs::metadata(path).ok();
+    if existing.as_ref().is_some_and(|m| m.permissions().readonly()) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("{} 为只读文件", path.display()),
+        ));
+    }
+    let tmp = temp_sibling(path);
+    let result = (|| {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        drop(file);
+        if let Some(meta) = &existing {
+            std::fs::set_permissions(&tmp, meta.permissions())?;
+        }
+        rename_over(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// `.<name>.tmp-<pid>` next to `path`.
+fn temp_sibling(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{name}.tmp-{}", std::process::id()))
+}
+
+#[cfg(not(windows))]
+fn rename_over(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::rename(from, to)
+}
+
+/// On Windows `rename` replaces the target, but fails with
+/// `PermissionDenied` while another process (virus scanner, indexer, sync
+/// client) briefly holds it open, so retry a few times before giving up.
+#[cfg(windows)]
+fn rename_over(from: &Path, to: &Path) -> std::io::Result<()> {
+    let mut attempt = 0;
+    loop {
+        match std::fs::rename(from, to) {
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied && attempt < 5 => {
+                attempt += 1;
+                std::thread::sleep(std::time::Duration::from_millis(20 * attempt));
+            }
+            other => return other,
+        }
+    }
+}
+
+// ── Breadcrumbs ───────────────────────────────────────────────────────────────
+
+/// One segment of a pane-header breadcrumb.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crumb {
+    pub label: String,
+    /// Folder (or, for the last crumb, file) this segment stands for.
+    pub path: PathBuf,
+}
+
+/// Split `path` into project-relative breadcrumb segments, e.g.
+/// `Content ▸ 第二卷 ▸ 第3章.md`.  Files outside `root` (or with no project
+/// open) yield a single crumb holding the absolute path.
+pub fn breadcrumb(root: Option<&Path>, path: &Path) -> Vec<Crumb> {
+    let rel = root.and_then(|r| path.strip_prefix(r).ok().map(|rel| (r, rel)));
+    match rel {
+        Some((root, rel)) if rel.components().next().is_some() => {
+            let mut acc = root.to_owned();
+            rel.components()
+                .map(|c| {
+                    acc.push(c);
+                    Crumb {
+                        label: c.as_os_str().to_string_lossy().into_owned(),
+                        path: acc.clone(),
+                    }
+                })
+                .collect()
+        }
+        _ => vec![Crumb {
+            label: path.display().to_string(),
+            path: path.to_owned(),
+        }],
+    }
+}
+
+/// Pick the crumbs to show within roughly `max_chars` characters (a
+/// separator counts as 3).  The first and last crumbs are always kept;
+/// `None` marks an elided run in the middle, drawn as `…`.
+pub fn elide_crumbs(crumbs: &[Crumb], max_chars: usize) -> Vec<Option<usize>> {
+    let width = |i: usize| crumbs[i].label.chars().count() + 3;
+    let total: usize = (0..crumbs.len()).map(width).sum();
+    if crumbs.len() <= 2 || total <= max_chars + 3 {
+        return (0..crumbs.len()).map(Some).collect();
+    }
+    // First + "…" + last, then add crumbs back from the end while they fit.
+    let last = crumbs.len() - 1;
+    let mut used = width(0) + 4 + width(last);
+    let mut tail_start = last;
+    while tail_start > 1 && used + width(tail_start - 1) <= max_chars + 3 {
+        tail_start -= 1;
+        used += width(tail_start);
+    }
+    let mut out = vec![Some(0), None];
+    out.extend((tail_start..=last).map(Some));
+    out
+}
+
+/// Shorten `text` to at most `max_chars` characters by replacing its middle
+/// with `…`.
+pub fn middle_truncate(text: &str, max_chars: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= max_chars || max_chars == 0 {
+        return text.to_owned();
+    }
+    let keep = max_chars - 1;
+    let head = keep / 2;
+    let tail = keep - head;
+    let mut out: String = chars[..head].iter().collect();
+    out.push('…');
+    out.extend(&chars[chars.len() - tail..]);
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_node_loads_lazily() {
+        let root = temp_dir("qingmo_core_test_lazy_tree");
+        std::fs::create_dir_all(root.join("a").join("b").join("c")).unwrap();
+        std::fs::write(root.join("a").join("b").join("c").join("深.md"), "").unwrap();
+        std::fs::write(root.join("a").join("浅.md"), "").unwrap();
+        std::fs::write(root.join("根.md"), "").unwrap();
+
+        let filter = TreeFilter::default();
+        let mut tree = FileNode::from_path_filtered(&root, &filter).unwrap();
+        assert!(tree.loaded && tree.expanded);
+        let names: Vec<&str> = tree.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "根.md"]);
+        // The child folder is known but its entries were never read.
+        let a = &tree.children[0];
+        assert!(a.is_dir && !a.loaded && !a.expanded && a.children.is_empty());
+
+        let a = find_node_mut(std::slice::from_mut(&mut tree), &root.join("a")).unwrap();
+        a.load_children(&filter).unwrap();
+        let names: Vec<&str> = a.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "浅.md"]);
+        assert!(!a.children[0].loaded);
+        a.expanded = true;
+
+        // Refresh re-lists only the changed folder and keeps the state of the rest.
+        std::fs::write(root.join("新.md"), "").unwrap();
+        tree.refresh(&filter);
+        let names: Vec<&str> = tree.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "新.md", "根.md"]);
+        assert!(tree.children[0].loaded && tree.children[0].expanded);
+        assert!(!tree.children[0].children[0].loaded);
+
+        tree.load_all(&filter);
+        let c = &tree.children[0].children[0].children[0];
+        assert!(c.loaded && c.expanded);
+        assert_eq!(c.children[0].name, "深.md");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_file_node_ignore_rules() {
+        let root = temp_dir("qingmo_core_test_tree_ignore");
+        std::fs::create_dir_all(root.join("Content").join("node_modules")).unwrap();
+        std::fs::write(root.join("Content").join("node_modules").join("x.md"), "").unwrap();
+        std::fs::write(root.join("Content").join("第一章.md"), "").unwrap();
+        std::fs::write(root.join("Content").join("index.html"), "").unwrap();
+        let mut filter = TreeFilter {
+            ignore: IgnoreRules::parse(&root, "node_modules/\n*.html\n"),
+            ..Default::default()
+        };
+
+        let tree = FileNode::from_path_filtered(&root.join("Content"), &filter).unwrap();
+        let names: Vec<&str> = tree.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["第一章.md"]);
+
+        // Shown, but marked, with the toggle on.
+        filter.show_ignored = true;
+        let mut tree = FileNode::from_path_filtered(&root.join("Content"), &filter).unwrap();
+        tree.load_all(&filter);
+        let flags: Vec<(&str, bool)> = tree.children.iter().map(|n| (n.name.as_str(), n.ignored)).collect();
+        assert_eq!(flags, vec![("node_modules", true), ("index.html", true), ("第一章.md", false)]);
+        assert!(tree.children[0].children[0].ignored);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_safe_write_replaces_content() {
+        let dir = temp_dir("qingmo_core_test_safe_write");
+        let path = dir.join("第一章.md");
+        safe_write(&path, "旧").unwrap();
+        safe_write(&path, "新内容").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "新内容");
+        // No temp file left behind.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_safe_write_failure_keeps_original() {
+        let dir = temp_dir("qingmo_core_test_safe_write_fail");
+        let path = dir.join("世界对象.json");
+        std::fs::write(&path, "[1]").unwrap();
+        // Block the temp file so the write fails half-way.
+        std::fs::create_dir(temp_sibling(&path)).unwrap();
+        assert!(safe_write(&path, "[").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[1]");
+
+        // Missing parent directory: error, nothing created.
+        assert!(safe_write(&dir.join("缺失").join("a.md"), "x").is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_safe_write_refuses_read_only_target() {
+        let dir = temp_dir("qingmo_core_test_safe_write_ro");
+        let path = dir.join("只读.md");
+        std::fs::write(&path, "原文").unwrap();
+        let mut perms = std::fs::metadata(&path).unwrap().permissions();
+        perms.set_readonly(true);
+        std::fs::set_permissions(&path, perms.clone()).unwrap();
+
+        let err = safe_write(&path, "改写").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "原文");
+
+        #[allow(clippy::permissions_set_readonly_false)]
+        perms.set_readonly(false);
+        std::fs::set_permissions(&path, perms).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_scratch_buffer_is_never_saved() {
+        let mut f = OpenFile::scratch("写作上下文.md", "# 上下文".into());
+        f.modified = true;
+        let err = f.save().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        assert!(!f.path.exists());
+        assert!(f.modified);
+    }
+
+    fn labels(crumbs: &[Crumb]) -> Vec<&str> {
+        crumbs.iter().map(|c| c.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_breadcrumb_inside_project() {
+        let root = Path::new("/书/项目");
+        let path = root.join("Content").join("第二卷").join("第3章.md");
+        let crumbs = breadcrumb(Some(root), &path);
+        assert_eq!(labels(&crumbs), vec!["Content", "第二卷", "第3章.md"]);
+        assert_eq!(crumbs[1].path, root.join("Content").join("第二卷"));
+        assert_eq!(crumbs[2].path, path);
+    }
+
+    #[test]
+    fn test_breadcrumb_outside_project_falls_back_to_absolute() {
+        let path = Path::new("/tmp/导出/合集.md");
+        let crumbs = breadcrumb(Some(Path::new("/书/项目")), path);
+        assert_eq!(crumbs, vec![Crumb { label: path.display().to_string(), path: path.to_owned() }]);
+
+        let crumbs = breadcrumb(None, path);
+        assert_eq!(labels(&crumbs), vec![path.display().to_string().as_str()]);
+    }
+
+    #[test]
+    fn test_elide_crumbs_keeps_ends() {
+        let root = Path::new("/p");
+        let path = root.join("Content").join("第一部很长的名字").join("第二卷").join("第3章.md");
+        let crumbs = breadcrumb(Some(root), &path);
+        assert_eq!(elide_crumbs(&crumbs, 100), vec![Some(0), Some(1), Some(2), Some(3)]);
+        assert_eq!(elide_crumbs(&crumbs, 26), vec![Some(0), None, Some(2), Some(3)]);
+        assert_eq!(elide_crumbs(&crumbs, 5), vec![Some(0), None, Some(3)]);
+    }
+
+    #[test]
+    fn test_middle_truncate() {
+        assert_eq!(middle_truncate("短路径", 10), "短路径");
+        assert_eq!(middle_truncate("/home/user/novel/第3章.md", 9), "/hom…章.md");
+    }
+
+    #[test]
+    fn test_open_file_reload() {
+        let dir = temp_dir("qingmo_core_test_reload");
+        let path = dir.join("第一章.md");
+        std::fs::write(&path, "磁盘上的版本").unwrap();
+        let mut f = OpenFile::new(path.clone(), "改坏的版本".into());
+        f.modified = true;
+        assert_eq!(f.reload().unwrap(), "改坏的版本");
+        assert_eq!(f.content, "磁盘上的版本");
+        assert!(!f.modified);
+
+        // A deleted file leaves the buffer as it was.
+        std::fs::remove_file(&path).unwrap();
+        f.content = "未保存".into();
+        f.modified = true;
+        let err = f.reload().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(f.content, "未保存");
+        assert!(f.modified);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use crate::models::{all_node_titles, normalize_title, Foreshadow, StructNode};
+
+// ── Foreshadow continuity: planted before resolved ───────────────────────────
+//
+// Reading order is the depth-first order of the structure tree.  A
+// foreshadow is planted in its `related_chapters` and paid off in its
+// `resolve_chapters`; paying it off before it was first planted, or
+// pointing at chapters that are gone, is reported.
+
+/// What is wrong with a foreshadow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForeshadowIssue {
+    /// The earliest resolving chapter comes before the earliest planting one.
+    ResolvedBeforePlanted { planted: String, resolved: String },
+    /// A planting chapter names no node of the structure.
+    MissingPlant(String),
+    /// A resolving chapter names no node of the structure.
+    MissingResolve(String),
+}
+
+impl ForeshadowIssue {
+    pub fn message(&self) -> String {
+        match self {
+            ForeshadowIssue::ResolvedBeforePlanted { planted, resolved } => {
+                format!("在「{resolved}」回收，早于首次埋设的「{planted}」")
+            }
+            ForeshadowIssue::MissingPlant(title) => format!("埋设章节「{title}」已不存在"),
+            ForeshadowIssue::MissingResolve(title) => format!("回收章节「{title}」已不存在"),
+        }
+    }
+}
+
+/// One finding of [`check_foreshadows`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeshadowWarning {
+    /// Index into the foreshadow list.
+    pub index: usize,
+    pub issue: ForeshadowIssue,
+}
+
+/// Reading position of every node title (depth-first, a parent before its
+/// children), keyed by [`normalize_title`]; a repeated title keeps its
+/// first position.
+pub fn reading_order(roots: &[StructNode]) -> HashMap<String, usize> {
+    let mut order = HashMap::new();
+    for (pos, title) in all_node_titles(roots).iter().enumerate() {
+        order.entry(normalize_title(title)).or_insert(pos);
+    }
+    order
+}
+
+/// Earliest of `chapters` in `order` with its title; the titles not in
+/// `order` are pushed to `missing`.
+fn earliest<'a>(
+    chapters: &'a [String],
+    order: &HashMap<String, usize>,
+    missing: &mut Vec<&'a str>,
+) -> Option<(usize, &'a str)> {
+    let mut first: Option<(usize, &str)> = None;
+    for title in chapters.iter().filter(|t| !t.trim().is_empty()) {
+        match order.get(&normalize_title(title)) {
+            Some(&pos) if first.is_none_or(|(p, _)| pos < p) => first = Some((pos, title)),
+            Some(_) => {}
+            None => missing.push(title),
+        }
+    }
+    first
+}
+
+/// Continuity problems of `foreshadows` against the structure `roots`, in
+/// list order: missing planting chapters, missing resolving chapters, then
+/// a resolve before the first plant.  Foreshadows without any resolving
+/// chapter are only checked for missing plants.
+pub fn check_foreshadows(roots: &[StructNode], foreshadows: &[Foreshadow]) -> Vec<ForeshadowWarning> {
+    let order = reading_order(roots);
+    let mut out = Vec::new();
+    for (index, fs) in foreshadows.iter().enumerate() {
+        let mut missing_plants = Vec::new();
+        let mut missing_resolves = Vec::new();
+        let plant = earliest(&fs.related_chapters, &order, &mut missing_plants);
+        let resolve = earliest(&fs.resolve_chapters, &order, &mut missing_resolves);
+        let issues = missing_plants.into_iter()
+            .map(|t| ForeshadowIssue::MissingPlant(t.to_owned()))
+            .chain(missing_resolves.into_iter().map(|t| ForeshadowIssue::MissingResolve(t.to_owned())));
+        out.extend(issues.map(|issue| ForeshadowWarning { index, issue }));
+        if let (Some((p, planted)), Some((r, resolved))) = (plant, resolve) {
+            if r < p {
+                out.push(ForeshadowWarning {
+                    index,
+                    issue: ForeshadowIssue::ResolvedBeforePlanted {
+                        planted: planted.to_owned(),
+                        resolved: resolved.to_owned(),
+                    },
+                });
+            }
+        }
+    }
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StructKind;
+
+    fn node(title: &str, children: Vec<StructNode>) -> StructNode {
+        let mut n = StructNode::new(title, StructKind::Chapter);
+        n.children = children;
+        n
+    }
+
+    /// 第一卷 [第一章, 第二章], 第二卷 [第三章, 第四章]
+    fn roots() -> Vec<StructNode> {
+        vec![
+            node("第一卷", vec![node("第一章", vec![]), node("第二章", vec![])]),
+            node("第二卷", vec![node("第三章", vec![]), node("第四章", vec![])]),
+        ]
+    }
+
+    fn foreshadow(name: &str, plants: &[&str], resolves: &[&str]) -> Foreshadow {
+        let mut fs = Foreshadow::new(name);
+        fs.related_chapters = plants.iter().map(|s| s.to_string()).collect();
+        fs.resolve_chapters = resolves.iter().map(|s| s.to_string()).collect();
+        fs
+    }
+
+    #[test]
+    fn test_reading_order_is_depth_first() {
+        let order = reading_order(&roots());
+        let mut titles: Vec<(&str, usize)> = order.iter().map(|(t, &p)| (t.as_str(), p)).collect();
+        titles.sort_by_key(|&(_, p)| p);
+        assert_eq!(titles, vec![
+            ("第一卷", 0), ("第一章", 1), ("第二章", 2), ("第二卷", 3), ("第三章", 4), ("第四章", 5),
+        ]);
+        // A repeated title keeps its first position.
+        let order = reading_order(&[node("序章", vec![]), node("序章", vec![])]);
+        assert_eq!(order.get("序章"), Some(&0));
+    }
+
+    #[test]
+    fn test_check_foreshadows() {
+        let list = vec![
+            // Fine: planted in 第一章 (and 第三章), resolved in 第四章.
+            foreshadow("玉佩", &["第三章", "第一章"], &["第四章"]),
+            // Earliest resolve (第二章) precedes earliest plant (第三章),
+            // even though 第四章 also resolves it after.
+            foreshadow("密信", &["第四章", "第三章"], &["第四章", "第二章"]),
+            // Same chapter: not before.
+            foreshadow("旧债", &["第二章"], &[" 第二章 "]),
+            // Gone chapters; the order check uses those that remain.
+            foreshadow("剑", &["已删除", "第二章"], &["第一章", "也删了"]),
+            // Not resolved anywhere yet.
+            foreshadow("未解", &["第四章", "不存在"], &[]),
+        ];
+        let found: Vec<(usize, String)> = check_foreshadows(&roots(), &list).into_iter()
+            .map(|w| (w.index, w.issue.message()))
+            .collect();
+        assert_eq!(found, vec![
+            (1, "在「第二章」回收，早于首次埋设的「第三章」".to_owned()),
+            (3, "埋设章节「已删除」已不存在".to_owned()),
+            (3, "回收章节「也删了」已不存在".to_owned()),
+            (3, "在「第一章」回收，早于首次埋设的「第二章」".to_owned()),
+            (4, "埋设章节「不存在」已不存在".to_owned()),
+        ]);
+        assert!(check_foreshadows(&[], &[]).is_empty());
+    }
+}
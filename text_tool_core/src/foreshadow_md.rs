@@ -0,0 +1,119 @@
+use crate::models::Foreshadow;
+
+// ── 伏笔.md format ────────────────────────────────────────────────────────────
+
+/// Render foreshadows in the `Content/伏笔.md` format: one `##` heading per
+/// entry carrying its status, an HTML comment with its timestamps, then the
+/// description and related chapters.
+pub fn foreshadows_to_markdown(foreshadows: &[Foreshadow]) -> String {
+    let mut md = String::from("# 伏笔列表\n\n");
+    for fs in foreshadows {
+        let status = if fs.resolved { "✅ 已解决" } else { "⏳ 未解决" };
+        md.push_str(&format!("## {} {}\n\n", fs.name, status));
+        if fs.created_at > 0 || fs.updated_at > 0 {
+            md.push_str(&format!("<!-- created: {} updated: {} -->\n\n", fs.created_at, fs.updated_at));
+        }
+        if !fs.description.is_empty() {
+            md.push_str(&format!("{}\n\n", fs.description));
+        }
+        if !fs.related_chapters.is_empty() {
+            md.push_str(&format!("**关联章节**: {}\n\n", fs.related_chapters.join("、")));
+        }
+        if !fs.resolve_chapters.is_empty() {
+            md.push_str(&format!("**回收章节**: {}\n\n", fs.resolve_chapters.join("、")));
+        }
+    }
+    md
+}
+
+/// Read foreshadows back from `伏笔.md` text, for projects saved before
+/// `Design/伏笔.json`: names and resolved flags from the `##` headings,
+/// timestamps from the comment, chapters from the `**关联章节**` and
+/// `**回收章节**` lines and the other paragraphs as the description.
+/// Entries without a timestamp comment get `0` (unknown).
+pub fn parse_foreshadows_markdown(text: &str) -> Vec<Foreshadow> {
+    fn chapters(list: &str) -> Vec<String> {
+        list.split('、').map(str::trim).filter(|c| !c.is_empty()).map(str::to_owned).collect()
+    }
+
+    let mut foreshadows: Vec<Foreshadow> = Vec::new();
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("## ") {
+            let resolved = rest.contains('✅');
+            let name = rest.replace("✅", "").replace("已解决", "")
+                .replace("⏳", "").replace("未解决", "").trim().to_owned();
+            if !name.is_empty() {
+                let mut fs = Foreshadow::new(&name);
+                fs.resolved = resolved;
+                fs.created_at = 0;
+                fs.updated_at = 0;
+                foreshadows.push(fs);
+            }
+            continue;
+        }
+        // Lines before the first entry belong to none.
+        let Some(fs) = foreshadows.last_mut() else { continue };
+        let trimmed = line.trim();
+        if let Some(stamp) = trimmed.strip_prefix("<!-- created:").and_then(|r| r.strip_suffix("-->")) {
+            if let Some((created, updated)) = stamp.split_once("updated:") {
+                fs.created_at = created.trim().parse().unwrap_or(0);
+                fs.updated_at = updated.trim().parse().unwrap_or(0);
+            }
+        } else if let Some(list) = trimmed.strip_prefix("**关联章节**:") {
+            fs.related_chapters = chapters(list);
+        } else if let Some(list) = trimmed.strip_prefix("**回收章节**:") {
+            fs.resolve_chapters = chapters(list);
+        } else if !fs.description.is_empty() || !trimmed.is_empty() {
+            fs.description.push_str(line);
+            fs.description.push('\n');
+        }
+    }
+    for fs in &mut foreshadows {
+        fs.description.truncate(fs.description.trim_end().len());
+    }
+    foreshadows
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_foreshadow_markdown_roundtrip() {
+        let mut a = Foreshadow::new("神秘玉佩");
+        a.description = "主角幼时所得".to_owned();
+        a.related_chapters = vec!["第一章".to_owned(), "第九章".to_owned()];
+        a.resolve_chapters = vec!["第二十章".to_owned()];
+        a.created_at = 1_700_000_000;
+        a.updated_at = 1_700_086_400;
+        let mut b = Foreshadow::new("师父的信");
+        b.resolved = true;
+        b.created_at = 0;
+        b.updated_at = 0;
+
+        let md = foreshadows_to_markdown(&[a, b]);
+        assert!(md.starts_with("# 伏笔列表"));
+        assert!(md.contains("**关联章节**: 第一章、第九章\n\n**回收章节**: 第二十章"));
+
+        let parsed = parse_foreshadows_markdown(&md);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].name, "神秘玉佩");
+        assert!(!parsed[0].resolved);
+        assert_eq!(parsed[1].name, "师父的信");
+        assert!(parsed[1].resolved);
+        assert_eq!((parsed[0].created_at, parsed[0].updated_at), (1_700_000_000, 1_700_086_400));
+        assert_eq!(parsed[1].updated_at, 0);
+        assert_eq!(parsed[0].description, "主角幼时所得");
+        assert_eq!(parsed[0].related_chapters, ["第一章", "第九章"]);
+        assert_eq!(parsed[0].resolve_chapters, ["第二十章"]);
+        assert!(parsed[1].description.is_empty() && parsed[1].related_chapters.is_empty());
+
+        // A description of several paragraphs comes back whole.
+        let mut c = Foreshadow::new("断剑");
+        c.description = "剑断于雪夜。\n\n断口齐整，非人力所为。".to_owned();
+        let parsed = parse_foreshadows_markdown(&foreshadows_to_markdown(std::slice::from_ref(&c)));
+        assert_eq!(parsed[0].description, c.description);
+    }
+}
@@ -0,0 +1,149 @@
+// ── Front matter: leading `---` … `---` metadata block ───────────────────────
+//
+// A chapter may start with a YAML-style block of `key: value` lines (title,
+// POV character, date …).  Only that flat subset is understood: list items
+// under a key are joined into its value, comments and blank lines are
+// ignored.  A block containing anything else is not front matter, so a
+// document that merely opens with a horizontal rule is left alone.
+
+/// The parsed front matter of a document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrontMatter {
+    /// `key: value` pairs in document order; values are unquoted and
+    /// list items joined with `", "`.
+    pub fields: Vec<(String, String)>,
+    /// Lines taken by the block, both delimiters included.
+    pub lines: usize,
+}
+
+impl FrontMatter {
+    /// Value of `key`, compared ASCII case-insensitively.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Drop one pair of matching surrounding quotes.
+fn unquote(value: &str) -> &str {
+    ['"', '\''].iter()
+        .find_map(|&q| value.strip_prefix(q)?.strip_suffix(q))
+        .unwrap_or(value)
+}
+
+/// A scalar value, or an inline `[a, b]` list joined like block lists.
+fn parse_value(raw: &str) -> String {
+    let raw = raw.trim();
+    match raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+        Some(items) => items.split(',')
+            .map(|i| unquote(i.trim()))
+            .filter(|i| !i.is_empty())
+            .collect::<Vec<_>>()
+            .join(", "),
+        None => unquote(raw).to_owned(),
+    }
+}
+
+/// Parse the front matter at the very start of `content` (a UTF-8 BOM is
+/// skipped).  `None` when there is no opening `---`, the block is never
+/// closed (by `---` or `...`), it has no fields, or a line in it is not
+/// a `key: value`, list item, comment or blank line.
+pub fn parse_front_matter(content: &str) -> Option<FrontMatter> {
+    let mut lines = content.strip_prefix('\u{feff}').unwrap_or(content).lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let trimmed = line.trim();
+        if matches!(line.trim_end(), "---" | "...") {
+            return (!fields.is_empty()).then_some(FrontMatter { fields, lines: i + 2 });
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix("- ").or((trimmed == "-").then_some("")) {
+            let (_, value) = fields.last_mut()?;
+            let item = unquote(item.trim());
+            if !item.is_empty() {
+                if !value.is_empty() {
+                    value.push_str(", ");
+                }
+                value.push_str(item);
+            }
+            continue;
+        }
+        // YAML wants a space after the colon, which keeps URLs and times out.
+        let (key, value) = trimmed.strip_suffix(':').map(|k| (k, ""))
+            .or_else(|| trimmed.split_once(": "))?;
+        let key = unquote(key.trim());
+        if key.is_empty() {
+            return None;
+        }
+        fields.push((key.to_owned(), parse_value(value)));
+    }
+    None
+}
+
+/// Split `content` into its front matter and the rest of the document.
+/// Without front matter the whole text is the body.
+pub fn split_front_matter(content: &str) -> (Option<FrontMatter>, &str) {
+    let Some(fm) = parse_front_matter(content) else {
+        return (None, content);
+    };
+    let start: usize = content.split_inclusive('\n').take(fm.lines).map(str::len).sum();
+    let body = &content[start..];
+    (Some(fm), body)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(fm: &FrontMatter) -> Vec<(&str, &str)> {
+        fm.fields.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect()
+    }
+
+    #[test]
+    fn test_parse_front_matter() {
+        let text = "---\ntitle: \"第一章 雨夜\"\nPOV: 林远\n# 注释\n\ndate: 2024-03-01\ntags:\n  - 开篇\n  - '伏笔'\ncast: [林远, 苏晴]\n---\n# 第一章\n正文";
+        let fm = parse_front_matter(text).unwrap();
+        assert_eq!(pairs(&fm), vec![
+            ("title", "第一章 雨夜"),
+            ("POV", "林远"),
+            ("date", "2024-03-01"),
+            ("tags", "开篇, 伏笔"),
+            ("cast", "林远, 苏晴"),
+        ]);
+        assert_eq!(fm.lines, 11);
+        assert_eq!(fm.get("pov"), Some("林远"));
+        assert_eq!(fm.get("missing"), None);
+        assert_eq!(split_front_matter(text).1, "# 第一章\n正文");
+
+        // BOM, `...` terminator, CRLF, and a block that ends the file.
+        let fm = parse_front_matter("\u{feff}---\r\nPOV: 苏晴\r\n...\r\n").unwrap();
+        assert_eq!(pairs(&fm), vec![("POV", "苏晴")]);
+        assert_eq!(split_front_matter("---\nPOV: 苏晴\n---"), (Some(fm.clone()), ""));
+    }
+
+    #[test]
+    fn test_front_matter_rejected() {
+        // Missing, not at the start, unterminated, empty.
+        assert_eq!(parse_front_matter("# 第一章\n---\na: b\n---"), None);
+        assert_eq!(parse_front_matter("\n---\na: b\n---"), None);
+        assert_eq!(parse_front_matter("---\ntitle: 第一章\n正文"), None);
+        assert_eq!(parse_front_matter("---\n---\n正文"), None);
+        assert_eq!(parse_front_matter(""), None);
+        // A rule followed by prose and another rule is not metadata.
+        assert_eq!(parse_front_matter("---\n那一夜下着雨。\n---\n"), None);
+        assert_eq!(parse_front_matter("---\n见 https://example.com\n---\n"), None);
+        // A list item before any key.
+        assert_eq!(parse_front_matter("---\n- 开篇\n---\n"), None);
+
+        let text = "---\n那一夜。\n---";
+        assert_eq!(split_front_matter(text), (None, text));
+    }
+}
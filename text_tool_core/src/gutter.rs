@@ -0,0 +1,57 @@
+// ── Editor line-number gutter ─────────────────────────────────────────────────
+//
+// The editor soft-wraps long lines, so its laid-out rows are not lines of
+// the file.  The gutter numbers hard lines only: the first row of each line
+// gets the number and its wrapped continuations stay blank.
+
+/// Line number beside each laid-out row, given whether each row ends with a
+/// newline (as the editor's layout reports it): `Some(n)` (1-based) on the
+/// first row of hard line `n`, `None` on its wrapped continuations.
+pub fn row_line_numbers(ends_with_newline: impl IntoIterator<Item = bool>) -> Vec<Option<usize>> {
+    let mut line = 1;
+    let mut starts_line = true;
+    ends_with_newline.into_iter()
+        .map(|newline| {
+            let number = starts_line.then_some(line);
+            if newline {
+                line += 1;
+            }
+            starts_line = newline;
+            number
+        })
+        .collect()
+}
+
+/// Digits the gutter reserves for a text of `lines` lines; at least two so
+/// the width does not jump while a short file grows.
+pub fn gutter_digits(lines: usize) -> usize {
+    lines.max(1).to_string().len().max(2)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_line_numbers_wrapping() {
+        // Line 1 wraps onto two extra rows, line 2 fits, line 3 is empty.
+        let rows = [false, false, true, true, false];
+        assert_eq!(row_line_numbers(rows), [Some(1), None, None, Some(2), Some(3)]);
+        // An empty text still has one row; a trailing newline opens a last line.
+        assert_eq!(row_line_numbers([false]), [Some(1)]);
+        assert_eq!(row_line_numbers([true, false]), [Some(1), Some(2)]);
+        // Wrapped last line without a trailing newline.
+        assert_eq!(row_line_numbers([true, false, false]), [Some(1), Some(2), None]);
+        assert!(row_line_numbers([]).is_empty());
+    }
+
+    #[test]
+    fn test_gutter_digits() {
+        assert_eq!(gutter_digits(0), 2);
+        assert_eq!(gutter_digits(9), 2);
+        assert_eq!(gutter_digits(100), 3);
+        assert_eq!(gutter_digits(12_345), 5);
+    }
+}
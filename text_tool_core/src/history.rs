@@ -0,0 +1,207 @@
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::file_manager::safe_write;
+use crate::models::HistorySettings;
+
+// ── 历史版本: compressed revisions of each manuscript file ────────────────────
+//
+// Independent of any version control, a save of a Content Markdown file can
+// leave a gzip copy in `.texttool/history/<relpath>/<millis>-<chars>.gz`.
+// The name carries the save time and the text's length in characters, so
+// listing the revisions never decompresses them.
+
+/// Folder of the revisions, relative to the project root.
+pub const HISTORY_DIR: &str = ".texttool/history";
+
+/// One stored revision of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub path: PathBuf,
+    /// Unix milliseconds of the save.
+    pub millis: i64,
+    /// Length of the text in characters.
+    pub chars: usize,
+}
+
+fn revision_name(millis: i64, chars: usize) -> String {
+    format!("{millis}-{chars}.gz")
+}
+
+/// `(millis, chars)` of a [`revision_name`].
+fn parse_revision_name(name: &str) -> Option<(i64, usize)> {
+    let (millis, chars) = name.strip_suffix(".gz")?.split_once('-')?;
+    Some((millis.parse().ok()?, chars.parse().ok()?))
+}
+
+/// Folder of the revisions of `file`: under [`HISTORY_DIR`], one folder per
+/// component of its path relative to `root`, the file's own name included
+/// (`Content/卷一/第一章.md/`).  `None` for a file outside `root`.
+pub fn revision_dir(root: &Path, file: &Path) -> Option<PathBuf> {
+    let rel = file.strip_prefix(root).ok()?;
+    let mut dir = root.join(HISTORY_DIR);
+    let mut parts = 0;
+    for component in rel.components() {
+        match component {
+            Component::Normal(part) => dir.push(part),
+            _ => return None,
+        }
+        parts += 1;
+    }
+    (parts > 0).then_some(dir)
+}
+
+/// The revisions in `dir`, newest first.  Other entries (the folders of
+/// files in a folder of the same name) are skipped.
+pub fn list_revisions(dir: &Path) -> Vec<Revision> {
+    let mut revisions: Vec<Revision> = std::fs::read_dir(dir).into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|e| {
+            let (millis, chars) = parse_revision_name(&e.file_name().to_string_lossy())?;
+            Some(Revision { path: e.path(), millis, chars })
+        })
+        .collect();
+    revisions.sort_by_key(|r| std::cmp::Reverse(r.millis));
+    revisions
+}
+
+/// The text of `revision`.
+pub fn read_revision(revision: &Revision) -> io::Result<String> {
+    let mut text = String::new();
+    GzDecoder::new(std::fs::File::open(&revision.path)?).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Store `content` as a revision of `file` saved at `millis`, then prune per
+/// `settings`.  Nothing is stored when the newest revision has the same
+/// text.  Returns the new revision.
+pub fn save_revision(
+    root: &Path,
+    file: &Path,
+    content: &str,
+    millis: i64,
+    settings: &HistorySettings,
+) -> io::Result<Option<Revision>> {
+    let dir = revision_dir(root, file)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "文件不在项目中"))?;
+    let chars = content.chars().count();
+    let latest = list_revisions(&dir).into_iter().next();
+    if let Some(latest) = &latest {
+        if latest.chars == chars && read_revision(latest).is_ok_and(|text| text == content) {
+            return Ok(None);
+        }
+    }
+    // Names stay in save order even when two saves share a millisecond.
+    let millis = latest.map_or(millis, |l| millis.max(l.millis + 1));
+    let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+    gz.write_all(content.as_bytes())?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(revision_name(millis, chars));
+    safe_write(&path, gz.finish()?)?;
+    prune_revisions(&dir, settings, millis)?;
+    Ok(Some(Revision { path, millis, chars }))
+}
+
+/// Delete the revisions in `dir` beyond the newest `settings.keep` or older
+/// than `settings.max_age_days` at `now_millis`; `0` lifts either limit.
+/// The newest revision always stays.  Returns how many were deleted.
+pub fn prune_revisions(dir: &Path, settings: &HistorySettings, now_millis: i64) -> io::Result<usize> {
+    let max_age = i64::from(settings.max_age_days) * 86_400_000;
+    let mut deleted = 0;
+    for (i, revision) in list_revisions(dir).iter().enumerate().skip(1) {
+        let too_many = settings.keep > 0 && i >= settings.keep;
+        let too_old = max_age > 0 && now_millis - revision.millis > max_age;
+        if too_many || too_old {
+            std::fs::remove_file(&revision.path)?;
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400_000;
+
+    fn temp_project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Content")).unwrap();
+        dir
+    }
+
+    fn settings(keep: usize, max_age_days: u32) -> HistorySettings {
+        HistorySettings { enabled: true, keep, max_age_days }
+    }
+
+    #[test]
+    fn test_revision_dir() {
+        let root = Path::new("/p/书");
+        assert_eq!(
+            revision_dir(root, &root.join("Content").join("卷一").join("第一章.md")),
+            Some(root.join(".texttool").join("history").join("Content").join("卷一").join("第一章.md")),
+        );
+        assert_eq!(revision_dir(root, Path::new("/p/别的/a.md")), None);
+        assert_eq!(revision_dir(root, &root.join("Content").join("..").join("..").join("a.md")), None);
+        assert_eq!(revision_dir(root, root), None);
+        assert_eq!(parse_revision_name(&revision_name(1_700_000_000_123, 42)), Some((1_700_000_000_123, 42)));
+        assert_eq!(parse_revision_name("notes.txt"), None);
+    }
+
+    #[test]
+    fn test_save_and_list_revisions() {
+        let root = temp_project("qingmo_test_history_save");
+        let file = root.join("Content").join("第一章.md");
+        let keep_all = settings(0, 0);
+
+        let first = save_revision(&root, &file, "雨夜。", 1_000, &keep_all).unwrap().unwrap();
+        assert_eq!(first.chars, 3);
+        // Unchanged text stores nothing; a save in the same millisecond still sorts after.
+        assert_eq!(save_revision(&root, &file, "雨夜。", 2_000, &keep_all).unwrap(), None);
+        let second = save_revision(&root, &file, "雨夜，灯灭。", 1_000, &keep_all).unwrap().unwrap();
+        assert_eq!(second.millis, 1_001);
+
+        let dir = revision_dir(&root, &file).unwrap();
+        // A folder named like a revision does not count.
+        std::fs::create_dir_all(dir.join("5-5.gz")).unwrap();
+        let listed = list_revisions(&dir);
+        assert_eq!(listed, vec![second.clone(), first.clone()]);
+        assert_eq!(read_revision(&listed[0]).unwrap(), "雨夜，灯灭。");
+        assert_eq!(read_revision(&listed[1]).unwrap(), "雨夜。");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_prune_revisions() {
+        let root = temp_project("qingmo_test_history_prune");
+        let file = root.join("Content").join("a.md");
+        let dir = revision_dir(&root, &file).unwrap();
+        for (i, day) in [0, 10, 20, 30].into_iter().enumerate() {
+            save_revision(&root, &file, &"字".repeat(i + 1), day * DAY, &settings(0, 0)).unwrap();
+        }
+
+        // By count: the newest two stay.
+        assert_eq!(prune_revisions(&dir, &settings(2, 0), 30 * DAY).unwrap(), 2);
+        let chars: Vec<usize> = list_revisions(&dir).iter().map(|r| r.chars).collect();
+        assert_eq!(chars, [4, 3]);
+
+        // By age, with saving: the newest stays however old it is.
+        save_revision(&root, &file, "新", 100 * DAY, &settings(0, 15)).unwrap();
+        let chars: Vec<usize> = list_revisions(&dir).iter().map(|r| r.chars).collect();
+        assert_eq!(chars, [1]);
+        assert_eq!(prune_revisions(&dir, &settings(1, 1), 500 * DAY).unwrap(), 0);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,321 @@
+use std::path::{Path, PathBuf};
+
+// ── 忽略规则: .gitignore and .texttoolignore ─────────────────────────────────
+//
+// Projects kept in git often carry build output or a companion site's
+// `node_modules` next to the manuscript.  The rules in the project root's
+// `.gitignore`, then `.texttoolignore`, keep those out of the file tree, the
+// project index and the chapter scanners.  Only the root files are read;
+// `.gitignore` files in subfolders are not.
+
+/// Ignore files read from the project root, in the order their rules apply.
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".texttoolignore"];
+
+/// Glob match: `*` and `?` stay within one path component, `**` spans any
+/// number of them (`**/` also matches none).  `[abc]`, `[a-z]` and `[!a]`
+/// match one character; `\` makes the next character literal.
+pub(crate) fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            let after_slash = rest.strip_prefix(&['/']);
+            after_slash.is_some_and(|r| glob_match(r, text))
+                || (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+        }
+        ['*', rest @ ..] => {
+            let run = text.iter().position(|&c| c == '/').unwrap_or(text.len());
+            (0..=run).any(|i| glob_match(rest, &text[i..]))
+        }
+        ['?', rest @ ..] => text.first().is_some_and(|&c| c != '/') && glob_match(rest, &text[1..]),
+        ['[', class @ ..] if class.contains(&']') => {
+            let Some((&c, text_rest)) = text.split_first() else { return false };
+            match match_class(class, c) {
+                Some((hit, rest)) => hit && c != '/' && glob_match(rest, text_rest),
+                None => false,
+            }
+        }
+        ['\\', c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+/// Whether `c` is in the bracket expression starting just after `[`, and the
+/// pattern after its `]`.  `None` when the class is not closed.
+fn match_class(class: &[char], c: char) -> Option<(bool, &[char])> {
+    let (negated, mut rest) = match class {
+        ['!' | '^', rest @ ..] => (true, rest),
+        _ => (false, class),
+    };
+    let mut hit = false;
+    let mut first = true;
+    loop {
+        match rest {
+            [']', after @ ..] if !first => return Some((hit != negated, after)),
+            [lo, '-', hi, after @ ..] if *hi != ']' => {
+                hit |= (*lo..=*hi).contains(&c);
+                rest = after;
+            }
+            [x, after @ ..] => {
+                hit |= *x == c;
+                rest = after;
+            }
+            [] => return None,
+        }
+        first = false;
+    }
+}
+
+/// One line of an ignore file.
+#[derive(Debug, Clone, PartialEq)]
+struct IgnoreRule {
+    pattern: Vec<char>,
+    /// `!pattern`: un-ignore what an earlier rule ignored.
+    negated: bool,
+    /// `pattern/`: matches folders only.
+    dir_only: bool,
+    /// Contains a `/` before its end: matched against the whole path from
+    /// the project root rather than against the last component.
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    /// The rule on `line`, or `None` for blank lines and `#` comments.
+    fn parse(line: &str) -> Option<Self> {
+        let mut line = line.trim_end_matches(['\r', '\n']);
+        // Trailing spaces are dropped unless escaped with `\`.
+        while line.ends_with(' ') && !line.ends_with("\\ ") {
+            line = &line[..line.len() - 1];
+        }
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let line = line.strip_prefix('\\').filter(|l| l.starts_with(['#', '!'])).unwrap_or(line);
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        if line.is_empty() {
+            return None;
+        }
+        let anchored = line.contains('/');
+        let pattern = line.strip_prefix('/').unwrap_or(line).chars().collect();
+        Some(IgnoreRule { pattern, negated, dir_only, anchored })
+    }
+
+    fn hits(&self, rel: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let text = if self.anchored { rel } else { rel.rsplit('/').next().unwrap_or(rel) };
+        glob_match(&self.pattern, &text.chars().collect::<Vec<_>>())
+    }
+}
+
+/// The ignore rules of one project.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Read the [`IGNORE_FILES`] of the project at `root`; missing files add
+    /// no rules.
+    pub fn load(root: &Path) -> Self {
+        let text: Vec<String> = IGNORE_FILES.iter()
+            .filter_map(|name| std::fs::read_to_string(root.join(name)).ok())
+            .collect();
+        Self::parse(root, &text.join("\n"))
+    }
+
+    /// Rules from the text of an ignore file, for the project at `root`.
+    pub fn parse(root: &Path, text: &str) -> Self {
+        IgnoreRules { root: root.to_owned(), rules: text.lines().filter_map(IgnoreRule::parse).collect() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether the rules ignore `rel` itself (a `/`-separated path relative
+    /// to the project root), not looking at its folders: the last rule that
+    /// matches decides.
+    pub fn matches(&self, rel: &str, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.negated == ignored && rule.hits(rel, is_dir) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+
+    /// Whether `rel` is ignored, itself or through a folder above it.  As in
+    /// git, a file inside an ignored folder cannot be un-ignored.
+    pub fn is_ignored(&self, rel: &str, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let folders = rel.match_indices('/').map(|(i, _)| &rel[..i]);
+        folders.into_iter().any(|dir| self.matches(dir, true)) || self.matches(rel, is_dir)
+    }
+
+    /// [`IgnoreRules::is_ignored`] for a path on disk.  Paths outside the
+    /// project are never ignored.
+    pub fn is_ignored_path(&self, path: &Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let Ok(rel) = path.strip_prefix(&self.root) else { return false };
+        let rel: Vec<_> = rel.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+        !rel.is_empty() && self.is_ignored(&rel.join("/"), is_dir)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(text: &str) -> IgnoreRules {
+        IgnoreRules::parse(Path::new("/proj"), text)
+    }
+
+    fn glob(pattern: &str, text: &str) -> bool {
+        glob_match(&pattern.chars().collect::<Vec<_>>(), &text.chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob("*.md", "第一章.md"));
+        assert!(!glob("*.md", "Content/第一章.md"));
+        assert!(glob("**/*.md", "Content/第一卷/第一章.md"));
+        assert!(glob("**/*.md", "第一章.md"));
+        assert!(glob("a/**/b", "a/b"));
+        assert!(glob("a/**/b", "a/x/y/b"));
+        assert!(!glob("a/**", "a"));
+        assert!(glob("第?章", "第一章"));
+        assert!(!glob("?", "/"));
+        assert!(glob("*.py[cod]", "x.pyc"));
+        assert!(!glob("*.py[cod]", "x.pyx"));
+        assert!(glob("[a-c]x", "bx"));
+        assert!(!glob("[!a-c]x", "bx"));
+        assert!(glob("[!a-c]x", "dx"));
+        assert!(glob("[]]", "]"));
+        assert!(!glob("[a", "a"));
+        assert!(glob("[a", "[a"));
+        assert!(glob("\\*", "*"));
+        assert!(!glob("\\*", "x"));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        assert!(rules("").is_empty());
+        assert!(rules("# comment\n\n   \n").is_empty());
+        assert!(rules("!").is_empty());
+        assert!(rules("/").is_empty());
+        // Escaped `#` and `!` are literal.
+        assert!(rules("\\#notes").matches("#notes", false));
+        assert!(rules("\\!important").matches("!important", false));
+        assert!(!rules("\\!important").matches("important", false));
+        // Trailing spaces are trimmed unless escaped; CRLF line ends.
+        assert!(rules("build   ").matches("build", true));
+        assert!(rules("a\\ ").matches("a ", false));
+        assert!(rules("dist\r\nout\r\n").matches("out", true));
+    }
+
+    #[test]
+    fn test_unanchored_patterns_match_any_level() {
+        let r = rules("*.log\nnode_modules\n");
+        assert!(r.is_ignored("debug.log", false));
+        assert!(r.is_ignored("site/logs/debug.log", false));
+        assert!(r.is_ignored("node_modules", true));
+        assert!(r.is_ignored("site/node_modules", true));
+        assert!(r.is_ignored("site/node_modules/left-pad/index.js", false));
+        assert!(!r.is_ignored("Content/第一章.md", false));
+        assert!(!r.is_ignored("node_modules_notes.md", false));
+    }
+
+    #[test]
+    fn test_anchored_patterns() {
+        let r = rules("/build\nsite/dist\ndocs/**/*.pdf\n**/cache\n");
+        assert!(r.is_ignored("build", true));
+        assert!(r.is_ignored("build/out.html", false));
+        assert!(!r.is_ignored("site/build", true));
+        assert!(r.is_ignored("site/dist/index.html", false));
+        assert!(!r.is_ignored("other/site/dist", true));
+        assert!(r.is_ignored("docs/a.pdf", false));
+        assert!(r.is_ignored("docs/x/y/a.pdf", false));
+        assert!(!r.is_ignored("a.pdf", false));
+        assert!(r.is_ignored("cache", true));
+        assert!(r.is_ignored("a/b/cache", true));
+    }
+
+    #[test]
+    fn test_dir_only_patterns() {
+        let r = rules("out/\n");
+        assert!(r.is_ignored("out", true));
+        assert!(r.is_ignored("Content/out", true));
+        assert!(r.is_ignored("out/a.md", false));
+        // A file named like the folder pattern stays.
+        assert!(!r.is_ignored("out", false));
+        assert!(!r.is_ignored("Content/out", false));
+    }
+
+    #[test]
+    fn test_negation() {
+        let r = rules("*.json\n!Design/*.json\n");
+        assert!(r.is_ignored("data.json", false));
+        assert!(!r.is_ignored("Design/世界对象.json", false));
+        assert!(r.is_ignored("Design/备份/世界对象.json", false));
+
+        // The last matching rule wins.
+        let r = rules("!keep.md\n*.md\n");
+        assert!(r.is_ignored("keep.md", false));
+        let r = rules("*.md\n!keep.md\n*.md\n");
+        assert!(r.is_ignored("keep.md", false));
+
+        // Nothing inside an ignored folder can be un-ignored.
+        let r = rules("build/\n!build/keep.md\n");
+        assert!(r.is_ignored("build/keep.md", false));
+        // Un-ignoring the folder itself works.
+        let r = rules("build/\n!/build/\n");
+        assert!(!r.is_ignored("build/keep.md", false));
+        // The usual `dir/*` + `!dir/file` idiom.
+        let r = rules("/site/*\n!/site/README.md\n");
+        assert!(r.is_ignored("site/index.html", false));
+        assert!(!r.is_ignored("site/README.md", false));
+    }
+
+    #[test]
+    fn test_is_ignored_path() {
+        let r = rules("node_modules/\n");
+        assert!(r.is_ignored_path(Path::new("/proj/site/node_modules"), true));
+        assert!(r.is_ignored_path(Path::new("/proj/site/node_modules/x/y.md"), false));
+        assert!(!r.is_ignored_path(Path::new("/proj/Content/a.md"), false));
+        assert!(!r.is_ignored_path(Path::new("/proj"), true));
+        assert!(!r.is_ignored_path(Path::new("/elsewhere/node_modules"), true));
+    }
+
+    #[test]
+    fn test_load_reads_both_files_in_order() {
+        let dir = std::env::temp_dir().join("tt_ignore_load");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(IgnoreRules::load(&dir).is_empty());
+
+        std::fs::write(dir.join(".gitignore"), "*.html\nnode_modules/\n").unwrap();
+        std::fs::write(dir.join(".texttoolignore"), "!导出.html\n草稿/\n").unwrap();
+        let r = IgnoreRules::load(&dir);
+        assert!(r.is_ignored_path(&dir.join("site/index.html"), false));
+        assert!(!r.is_ignored_path(&dir.join("导出.html"), false));
+        assert!(r.is_ignored_path(&dir.join("Content/草稿"), true));
+        assert!(r.is_ignored_path(&dir.join("node_modules"), true));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,55 @@
+//! Non-UI core of 清墨: data models, Markdown parsing, outline extraction
+//! and export helpers.  Usable from scripts without pulling in egui; enable
+//! the `egui` feature for colour helpers such as `ChapterTag::color`.
+
+pub mod annotations;
+pub mod attachments;
+pub mod changelog;
+pub mod completion;
+pub mod content_index;
+pub mod crash;
+pub mod diagram;
+pub mod diff;
+pub mod docx;
+pub mod duplicates;
+pub mod export;
+pub mod file_manager;
+pub mod foreshadow_check;
+pub mod foreshadow_md;
+pub mod front_matter;
+pub mod gutter;
+pub mod history;
+pub mod ignore;
+pub mod links;
+pub mod markdown;
+pub mod markers;
+pub mod models;
+pub mod node_ops;
+pub mod node_rename;
+pub mod outline;
+pub mod outline_compare;
+pub mod outline_doc;
+pub mod paragraphs;
+pub mod profile;
+pub mod project_lock;
+pub mod reading;
+pub mod recovery;
+pub mod relation_matrix;
+pub mod report;
+pub mod sample_project;
+pub mod snapshot;
+pub mod split;
+pub mod stats;
+pub mod sync_guard;
+pub mod synopsis;
+pub mod timeline;
+pub mod undo;
+pub mod usage;
+pub mod wiki;
+pub mod word_freq;
+pub mod writing_context;
+
+pub use file_manager::{safe_write, FileNode, OpenFile, TreeFilter};
+pub use foreshadow_md::{foreshadows_to_markdown, parse_foreshadows_markdown};
+pub use models::*;
+pub use outline::{build_struct_from_dir, count_nodes, extract_struct_nodes_from_markdown, parse_outline_entries};
@@ -0,0 +1,441 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+use crate::models::OutlineEntry;
+use crate::outline::parse_outline_entries;
+
+// ── Links between project files ──────────────────────────────────────────────
+
+/// Anchor name of a heading, GitHub style: lower-cased, spaces become `-`,
+/// punctuation (ASCII and full-width) is dropped, and letters of any script
+/// — including CJK — pass through unchanged.
+///
+/// The preview and any HTML export must name heading anchors with this
+/// function so that links inserted in the editor keep resolving.
+pub fn heading_slug(title: &str) -> String {
+    title.trim()
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter_map(|c| match c {
+            ' ' | '-' => Some('-'),
+            '_' => Some('_'),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Anchors of all headings of one file, in order.  Repeated slugs get
+/// `-1`, `-2`, … suffixes, as GitHub does.
+pub fn heading_anchors(entries: &[OutlineEntry]) -> Vec<String> {
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    entries.iter()
+        .map(|e| {
+            let slug = heading_slug(&e.title);
+            let n = seen.entry(slug.clone()).or_insert(0);
+            let anchor = if *n == 0 { slug } else { format!("{slug}-{n}") };
+            *n += 1;
+            anchor
+        })
+        .collect()
+}
+
+/// Path of `target` relative to the directory `from_dir`, `/`-separated and
+/// ready for a Markdown link destination.  Both paths must share a prefix
+/// (the project root); CJK characters are kept as is.
+pub fn relative_link(from_dir: &Path, target: &Path) -> String {
+    let from: Vec<Component> = from_dir.components().collect();
+    let to: Vec<Component> = target.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_owned(); from.len() - common];
+    parts.extend(to[common..].iter().map(|c| c.as_os_str().to_string_lossy().into_owned()));
+    encode_link_path(&parts.join("/"))
+}
+
+/// Percent-encode the characters that would end or break a Markdown link
+/// destination (space, parentheses, `<`, `>`, `#`, `?`, `%`).
+pub fn encode_link_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            ' ' | '(' | ')' | '<' | '>' | '#' | '?' | '%' => out.push_str(&format!("%{:02X}", c as u32)),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `[text](path#anchor)`, with brackets in `text` escaped.
+pub fn markdown_link(text: &str, path: &str, anchor: Option<&str>) -> String {
+    let text = text.replace('[', "\\[").replace(']', "\\]");
+    match anchor {
+        Some(a) if !a.is_empty() => format!("[{text}]({path}#{a})"),
+        _ => format!("[{text}]({path})"),
+    }
+}
+
+/// Undo [`encode_link_path`]-style percent-encoding.  Invalid escapes are
+/// kept as they are; the bytes must form UTF-8 (else replaced, lossily).
+pub fn decode_link_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// ── Link checking ─────────────────────────────────────────────────────────────
+//
+// Inline links `[text](target)` between manuscript files break when a file
+// is renamed or a heading reworded.  Only local targets are checked: a
+// relative path (resolved against the linking file's folder), optionally
+// with a `#anchor` that must name a heading of the target (see
+// [`heading_anchors`]); `#anchor` alone points into the same file.
+
+/// One inline link in a Markdown text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MdLink {
+    /// 1-based line number.
+    pub line_no: usize,
+    /// 1-based column of the `[`, counted in characters.
+    pub column: usize,
+    pub text: String,
+    /// The destination as written (still percent-encoded).
+    pub target: String,
+    /// Byte range of `target` in the text.
+    pub range: Range<usize>,
+}
+
+/// End (exclusive) of the link text starting after the `[` at `open`:
+/// the matching unescaped `]`, nested brackets allowed.
+fn closing_bracket(line: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, c) in line[open + 1..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => depth += 1,
+            ']' if depth == 0 => return Some(open + 1 + i),
+            ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Destination of a link whose `(` is at `paren`: the byte range of the
+/// target (without `<>`) and the index after the closing `)`.
+fn link_destination(line: &str, paren: usize) -> Option<(Range<usize>, usize)> {
+    let start = paren + 1 + (line[paren + 1..].len() - line[paren + 1..].trim_start().len());
+    let rest = &line[start..];
+    let (range, after) = if let Some(inner) = rest.strip_prefix('<') {
+        let end = inner.find('>')?;
+        (start + 1..start + 1 + end, start + 2 + end)
+    } else {
+        let mut depth = 0;
+        let end = rest.char_indices()
+            .find(|&(_, c)| match c {
+                '(' => { depth += 1; false }
+                ')' if depth == 0 => true,
+                ')' => { depth -= 1; false }
+                c => c.is_whitespace(),
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        (start..start + end, start + end)
+    };
+    // An optional title, then the closing parenthesis.
+    let tail = &line[after..];
+    let close = match tail.trim_start().chars().next()? {
+        ')' => tail.find(')')?,
+        '"' | '\'' => {
+            let q = tail.trim_start().chars().next()?;
+            let title_start = tail.find(q)? + 1;
+            let title_end = title_start + tail[title_start..].find(q)?;
+            title_end + 1 + tail[title_end + 1..].find(')')?
+        }
+        _ => return None,
+    };
+    Some((range, after + close + 1))
+}
+
+/// Every inline link `[text](target)` in `text`, outside fenced code
+/// blocks and inline code.  Images (`![…](…)`) are not links.
+pub fn find_links(text: &str) -> Vec<MdLink> {
+    let mut out = Vec::new();
+    let mut offset = 0;
+    let mut in_code = false;
+    for (n, raw) in text.split_inclusive('\n').enumerate() {
+        let line = raw.trim_end_matches(['\n', '\r']);
+        let line_offset = offset;
+        offset += raw.len();
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let mut i = 0;
+        while let Some(open) = line[i..].find('[').map(|p| i + p) {
+            i = open + 1;
+            let before = &line[..open];
+            if before.ends_with('\\') || before.ends_with('!') || before.matches('`').count() % 2 == 1 {
+                continue;
+            }
+            let Some(close) = closing_bracket(line, open) else { continue };
+            if !line[close + 1..].starts_with('(') {
+                continue;
+            }
+            let Some((range, end)) = link_destination(line, close + 1) else { continue };
+            out.push(MdLink {
+                line_no: n + 1,
+                column: before.chars().count() + 1,
+                text: line[open + 1..close].to_owned(),
+                target: line[range.clone()].to_owned(),
+                range: line_offset + range.start..line_offset + range.end,
+            });
+            i = end;
+        }
+    }
+    out
+}
+
+/// Whether `target` points into the project rather than elsewhere (a URL,
+/// an e-mail address, an absolute path).
+pub fn is_local_target(target: &str) -> bool {
+    !target.is_empty()
+        && !target.contains("://")
+        && !target.starts_with('/')
+        && !target.starts_with('\\')
+        && !["mailto:", "tel:", "data:"].iter().any(|p| target.starts_with(p))
+}
+
+/// File and decoded anchor a local link `target` in `from_file` points to;
+/// `..` and `.` are resolved without touching the disk.  `None` for
+/// targets that are not [`is_local_target`].
+pub fn resolve_link(from_file: &Path, target: &str) -> Option<(PathBuf, Option<String>)> {
+    if !is_local_target(target) {
+        return None;
+    }
+    let (path, anchor) = match target.split_once('#') {
+        Some((p, a)) => (p, Some(decode_link_path(a)).filter(|a| !a.is_empty())),
+        None => (target, None),
+    };
+    let path = path.split_once('?').map_or(path, |(p, _)| p);
+    if path.is_empty() {
+        return Some((from_file.to_path_buf(), anchor));
+    }
+    let mut resolved = from_file.parent().unwrap_or(Path::new("")).to_path_buf();
+    for part in decode_link_path(path).split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                resolved.pop();
+            }
+            part => resolved.push(part),
+        }
+    }
+    Some((resolved, anchor))
+}
+
+/// Why a link is broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkProblem {
+    /// The target file does not exist.
+    MissingFile,
+    /// The file exists but has no heading with the anchor.
+    MissingAnchor,
+}
+
+/// A broken link found by [`check_links`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    /// The file containing the link.
+    pub file: PathBuf,
+    pub link: MdLink,
+    /// Where the link resolves to.
+    pub target: PathBuf,
+    pub anchor: Option<String>,
+    pub problem: LinkProblem,
+}
+
+impl BrokenLink {
+    pub fn message(&self) -> String {
+        let name = self.target.file_name().map_or_else(
+            || self.target.display().to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+        match self.problem {
+            LinkProblem::MissingFile => format!("找不到文件「{name}」"),
+            LinkProblem::MissingAnchor => {
+                format!("「{name}」中没有标题 #{}", self.anchor.as_deref().unwrap_or_default())
+            }
+        }
+    }
+}
+
+/// Check the local links of `docs` (`(path, text)`, in order).  Targets
+/// among `docs` are checked against the given text, other files on disk;
+/// anchors are only checked in Markdown files.  `progress` is called with
+/// the number of documents done and the total after each one.
+pub fn check_links(docs: &[(&Path, &str)], mut progress: impl FnMut(usize, usize)) -> Vec<BrokenLink> {
+    let texts: HashMap<&Path, &str> = docs.iter().copied().collect();
+    let mut anchors: HashMap<PathBuf, Option<Vec<String>>> = HashMap::new();
+    let mut out = Vec::new();
+    for (done, (file, text)) in docs.iter().enumerate() {
+        for link in find_links(text) {
+            let Some((target, anchor)) = resolve_link(file, &link.target) else { continue };
+            let is_md = target.extension().is_some_and(|e| e == "md" || e == "markdown");
+            let known = anchors.entry(target.clone()).or_insert_with(|| {
+                match texts.get(target.as_path()) {
+                    Some(text) => Some(heading_anchors(&parse_outline_entries(text))),
+                    None if is_md => std::fs::read_to_string(&target).ok()
+                        .map(|text| heading_anchors(&parse_outline_entries(&text))),
+                    // Anchors are not checked outside Markdown.
+                    None => target.exists().then(Vec::new),
+                }
+            });
+            let problem = match (known, &anchor) {
+                (None, _) => LinkProblem::MissingFile,
+                (Some(list), Some(a)) if is_md && !list.contains(&a.to_lowercase()) => LinkProblem::MissingAnchor,
+                _ => continue,
+            };
+            out.push(BrokenLink { file: file.to_path_buf(), link, target, anchor, problem });
+        }
+        progress(done + 1, docs.len());
+    }
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outline::parse_outline_entries;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_heading_slug() {
+        assert_eq!(heading_slug("第一章 雨夜"), "第一章-雨夜");
+        assert_eq!(heading_slug("第1章：归来！"), "第1章归来");
+        assert_eq!(heading_slug("  Chapter One: The End? "), "chapter-one-the-end");
+        assert_eq!(heading_slug("《剑》与「琴」"), "剑与琴");
+        assert_eq!(heading_slug("snake_case - 混排"), "snake_case---混排");
+    }
+
+    #[test]
+    fn test_heading_anchors_deduplicate() {
+        let entries = parse_outline_entries("# 序\n## 序\n## 尾声\n## 序\n");
+        assert_eq!(heading_anchors(&entries), vec!["序", "序-1", "尾声", "序-2"]);
+    }
+
+    #[test]
+    fn test_relative_link() {
+        let root = PathBuf::from("/书/Content");
+        assert_eq!(
+            relative_link(&root.join("第二卷"), &root.join("第一卷").join("第3章 重逢.md")),
+            "../第一卷/第3章%20重逢.md",
+        );
+        assert_eq!(relative_link(&root, &root.join("序章.md")), "序章.md");
+        assert_eq!(relative_link(&root.join("a").join("b"), &root.join("c (1).md")), "../../c%20%281%29.md");
+    }
+
+    #[test]
+    fn test_find_links() {
+        let text = "\
+# 第一章
+见[雨夜](ch1.md#雨夜)与[外传 [上]](<番外/外传 (上).md> \"题注\")。
+![插图](img.png) \\[不是](x.md) `[代码](y.md)` [网址](https://example.com)
+```
+[围栏](z.md)
+```
+[空]() [未闭合](a.md";
+        let links = find_links(text);
+        let at: Vec<(usize, usize, &str, &str)> = links.iter()
+            .map(|l| (l.line_no, l.column, l.text.as_str(), l.target.as_str()))
+            .collect();
+        assert_eq!(at, vec![
+            (2, 2, "雨夜", "ch1.md#雨夜"),
+            (2, 18, "外传 [上]", "番外/外传 (上).md"),
+            (3, 41, "网址", "https://example.com"),
+            (7, 1, "空", ""),
+        ]);
+        for l in &links {
+            assert_eq!(text[l.range.clone()], l.target);
+        }
+    }
+
+    #[test]
+    fn test_resolve_link() {
+        let from = Path::new("/书/Content/第二卷/第5章.md");
+        assert_eq!(
+            resolve_link(from, "../第一卷/第3章%20重逢.md#重逢"),
+            Some((PathBuf::from("/书/Content/第一卷/第3章 重逢.md"), Some("重逢".to_owned()))),
+        );
+        assert_eq!(resolve_link(from, "./附录.md"), Some((PathBuf::from("/书/Content/第二卷/附录.md"), None)));
+        assert_eq!(resolve_link(from, "#本章"), Some((from.to_path_buf(), Some("本章".to_owned()))));
+        assert_eq!(resolve_link(from, "https://example.com/a.md"), None);
+        assert_eq!(resolve_link(from, "mailto:a@b.c"), None);
+        assert_eq!(resolve_link(from, ""), None);
+        assert_eq!(decode_link_path("c%20%281%29%zz%"), "c (1)%zz%");
+    }
+
+    #[test]
+    fn test_check_links_temp_tree() {
+        let dir = std::env::temp_dir().join("qingmo_core_test_check_links");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("第一卷")).unwrap();
+        // On disk only, not among the documents.
+        std::fs::write(dir.join("附录.md"), "# 人物表\n").unwrap();
+        std::fs::write(dir.join("图.png"), "").unwrap();
+
+        let ch1 = dir.join("第一卷").join("第1章.md");
+        let ch2 = dir.join("第一卷").join("第2章 雨夜.md");
+        let text1 = "# 第1章\n[下一章](第2章%20雨夜.md#雨夜)\n[锚点错](第2章%20雨夜.md#晴天)\n\
+            [附录](../附录.md#人物表) [附录错](../附录.md#地图) [图](../图.png#x)\n[改名了](第3章.md)\n[本章](#第1章) [本章错](#第9章)\n";
+        let text2 = "# 雨夜\n[回](第1章.md) [网](https://example.com/none.md)\n";
+        let docs = [(ch1.as_path(), text1), (ch2.as_path(), text2)];
+
+        let mut calls = Vec::new();
+        let broken = check_links(&docs, |done, total| calls.push((done, total)));
+        assert_eq!(calls, vec![(1, 2), (2, 2)]);
+        let found: Vec<(usize, &str, String)> = broken.iter()
+            .map(|b| (b.link.line_no, b.link.text.as_str(), b.message()))
+            .collect();
+        assert_eq!(found, vec![
+            (3, "锚点错", "「第2章 雨夜.md」中没有标题 #晴天".to_owned()),
+            (4, "附录错", "「附录.md」中没有标题 #地图".to_owned()),
+            (5, "改名了", "找不到文件「第3章.md」".to_owned()),
+            (6, "本章错", "「第1章.md」中没有标题 #第9章".to_owned()),
+        ]);
+        assert_eq!(broken[2].target, dir.join("第一卷").join("第3章.md"));
+        assert!(broken.iter().all(|b| b.file == ch1));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_markdown_link() {
+        assert_eq!(markdown_link("雨夜", "ch1.md", Some("雨夜")), "[雨夜](ch1.md#雨夜)");
+        assert_eq!(markdown_link("[注]", "a.md", None), r"[\[注\]](a.md)");
+        assert_eq!(markdown_link("x", "a.md", Some("")), "[x](a.md)");
+    }
+}
@@ -0,0 +1,512 @@
+use std::fmt::Write as _;
+
+use crate::annotations::strip_annotations;
+use crate::front_matter::{parse_front_matter, FrontMatter};
+use crate::profile::html_escape;
+
+// ── Markdown block parser ─────────────────────────────────────────────────────
+
+/// One line-level Markdown block, as understood by the preview renderer.
+///
+/// Supports:
+/// - A leading front-matter block (`---` … `---`, see [`parse_front_matter`])
+/// - ATX headings (`#` … `######`)
+/// - Fenced code blocks (``` ``` ```)
+/// - Blockquotes (`> …`)
+/// - Unordered lists (`-`, `*`, `+`)
+/// - Ordered lists (`1. …`)
+/// - Horizontal rules (`---`, `***`, `___`)
+/// - Plain paragraphs, with blank lines kept for spacing
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block<'a> {
+    /// Only ever the first block.
+    FrontMatter(FrontMatter),
+    Heading { level: usize, text: &'a str },
+    /// A closed fenced code block; an unclosed fence at the end of the
+    /// document produces nothing.
+    Code(String),
+    Blank,
+    Rule,
+    Quote(&'a str),
+    Bullet(&'a str),
+    Ordered { num: &'a str, text: &'a str },
+    Paragraph(&'a str),
+}
+
+/// Split Markdown `content` into [`Block`]s, one per line (code blocks
+/// aside).  Inline markup is left in the text; see [`parse_inline`].
+pub fn parse_blocks(content: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut in_code_block = false;
+    let mut code_lines: Vec<&str> = Vec::new();
+
+    let front_matter = parse_front_matter(content);
+    let skip = front_matter.as_ref().map_or(0, |fm| fm.lines);
+    blocks.extend(front_matter.map(Block::FrontMatter));
+    for line in content.lines().skip(skip) {
+        if line.trim_start().starts_with("```") {
+            if in_code_block {
+                blocks.push(Block::Code(code_lines.join("\n")));
+                code_lines.clear();
+            }
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            code_lines.push(line);
+            continue;
+        }
+
+        let block = if line.trim().is_empty() {
+            Block::Blank
+        } else if let Some((level, text)) = (1..=6).find_map(|n| strip_heading(line, n).map(|t| (n, t))) {
+            Block::Heading { level, text }
+        } else if is_horizontal_rule(line) {
+            Block::Rule
+        } else if let Some(rest) = line.strip_prefix("> ").or_else(|| line.strip_prefix(">")) {
+            Block::Quote(rest)
+        } else if let Some(rest) = line.strip_prefix("- ")
+            .or_else(|| line.strip_prefix("* "))
+            .or_else(|| line.strip_prefix("+ "))
+        {
+            Block::Bullet(rest)
+        } else if let Some((num, text)) = parse_ordered_item(line) {
+            Block::Ordered { num, text }
+        } else {
+            Block::Paragraph(line)
+        };
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// Strip `n` leading `#` characters followed by a space (or end of line).
+pub fn strip_heading(line: &str, n: usize) -> Option<&str> {
+    let prefix: String = "#".repeat(n);
+    if line.starts_with(prefix.as_str()) {
+        let after = &line[n..];
+        if let Some(stripped) = after.strip_prefix(' ') {
+            Some(stripped.trim_end())
+        } else if after.is_empty() {
+            Some("")
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+/// Return `true` if the line is a Markdown thematic break (`---`, `***`, `___`).
+pub fn is_horizontal_rule(line: &str) -> bool {
+    let trimmed = line.trim();
+    if trimmed.len() < 3 {
+        return false;
+    }
+    let first = trimmed.chars().next().unwrap();
+    if !matches!(first, '-' | '*' | '_') {
+        return false;
+    }
+    trimmed.chars().all(|c| c == first || c == ' ')
+        && trimmed.chars().filter(|&c| c == first).count() >= 3
+}
+
+/// If `line` is an ordered-list item (`1. text`), return `(number_str, rest_text)`.
+pub fn parse_ordered_item(line: &str) -> Option<(&str, &str)> {
+    let dot = line.find(". ")?;
+    let num = &line[..dot];
+    if num.chars().all(|c| c.is_ascii_digit()) && !num.is_empty() {
+        Some((num, &line[dot + 2..]))
+    } else {
+        None
+    }
+}
+
+// ── Inline parser ─────────────────────────────────────────────────────────────
+
+/// An inline span of a single line of Markdown text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Span<'a> {
+    Plain(&'a str),
+    Bold(&'a str),
+    Italic(&'a str),
+    Code(&'a str),
+}
+
+/// `true` if `text` may contain inline markup worth parsing.
+pub fn has_inline_markup(text: &str) -> bool {
+    text.contains('*') || text.contains('`')
+}
+
+/// Parse inline Markdown spans.
+///
+/// Recognised spans (processed left-to-right, longest match first):
+/// - `**text**` → [`Span::Bold`]; an unclosed `**` stays literal
+/// - `*text*`   → [`Span::Italic`]; unclosed runs to end of line
+/// - `` `code` `` → [`Span::Code`]; unclosed runs to end of line
+///
+/// Empty spans are dropped.
+pub fn parse_inline(text: &str) -> Vec<Span<'_>> {
+    let mut spans = Vec::new();
+    let bytes = text.as_bytes();
+    let len = bytes.len();
+    let mut i = 0;
+    let mut plain_start = 0;
+
+    macro_rules! flush_plain {
+        () => {
+            if plain_start < i {
+                spans.push(Span::Plain(&text[plain_start..i]));
+            }
+        };
+    }
+
+    while i < len {
+        // Bold: **...**  (check before single *)
+        if i + 1 < len && bytes[i] == b'*' && bytes[i + 1] == b'*' {
+            let open = i;
+            flush_plain!();
+            i += 2;
+            let start = i;
+            // find closing **
+            let mut found_close = false;
+            while i + 1 < len {
+                if bytes[i] == b'*' && bytes[i + 1] == b'*' {
+                    found_close = true;
+                    break;
+                }
+                i += 1;
+            }
+            if found_close {
+                let bold_text = &text[start..i];
+                i += 2; // skip closing **
+                if !bold_text.is_empty() {
+                    spans.push(Span::Bold(bold_text));
+                }
+            } else {
+                // No closing ** found – treat opening ** as literal text
+                i = open + 2;
+                spans.push(Span::Plain("**"));
+            }
+            plain_start = i;
+        }
+        // Inline code: `...`
+        else if bytes[i] == b'`' {
+            flush_plain!();
+            i += 1;
+            let start = i;
+            while i < len && bytes[i] != b'`' {
+                i += 1;
+            }
+            let code_text = &text[start..i];
+            if i < len { i += 1; } // skip closing `
+            if !code_text.is_empty() {
+                spans.push(Span::Code(code_text));
+            }
+            plain_start = i;
+        }
+        // Italic: *...*  (single asterisk)
+        else if bytes[i] == b'*' {
+            flush_plain!();
+            i += 1;
+            let start = i;
+            while i < len && bytes[i] != b'*' {
+                i += 1;
+            }
+            let italic_text = &text[start..i];
+            if i < len { i += 1; } // skip closing *
+            if !italic_text.is_empty() {
+                spans.push(Span::Italic(italic_text));
+            }
+            plain_start = i;
+        }
+        else {
+            i += 1;
+        }
+    }
+
+    // Flush remaining plain text
+    if plain_start < text.len() {
+        spans.push(Span::Plain(&text[plain_start..]));
+    }
+
+    spans
+}
+
+// ── HTML rendering ────────────────────────────────────────────────────────────
+
+/// Render Markdown as an HTML fragment, covering the same subset as the
+/// preview ([`Block`] and [`Span`]).  Consecutive list items share one
+/// list; all text is escaped and 注释 are left out.
+pub fn markdown_to_html(content: &str) -> String {
+    let content = strip_annotations(content);
+    let mut out = String::new();
+    let mut list: Option<&str> = None;
+    for block in parse_blocks(&content) {
+        let wanted = match block {
+            Block::Bullet(_) => Some("ul"),
+            Block::Ordered { .. } => Some("ol"),
+            _ => None,
+        };
+        if list != wanted {
+            if let Some(tag) = list {
+                let _ = writeln!(out, "</{tag}>");
+            }
+            if let Some(tag) = wanted {
+                let _ = writeln!(out, "<{tag}>");
+            }
+            list = wanted;
+        }
+        let _ = match block {
+            Block::FrontMatter(fm) => {
+                let _ = writeln!(out, "<table class=\"front-matter\">");
+                for (key, value) in &fm.fields {
+                    let _ = writeln!(out, "<tr><th>{}</th><td>{}</td></tr>", html_escape(key), html_escape(value));
+                }
+                writeln!(out, "</table>")
+            }
+            Block::Heading { level, text } => writeln!(out, "<h{level}>{}</h{level}>", inline_html(text)),
+            Block::Code(code) => writeln!(out, "<pre><code>{}</code></pre>", html_escape(&code)),
+            Block::Blank => Ok(()),
+            Block::Rule => writeln!(out, "<hr>"),
+            Block::Quote(text) => writeln!(out, "<blockquote>{}</blockquote>", inline_html(text)),
+            Block::Bullet(text) | Block::Ordered { text, .. } => writeln!(out, "<li>{}</li>", inline_html(text)),
+            Block::Paragraph(text) => writeln!(out, "<p>{}</p>", inline_html(text)),
+        };
+    }
+    if let Some(tag) = list {
+        let _ = writeln!(out, "</{tag}>");
+    }
+    out
+}
+
+/// One line of inline Markdown as escaped HTML.
+fn inline_html(text: &str) -> String {
+    parse_inline(text).into_iter()
+        .map(|span| match span {
+            Span::Plain(t) => html_escape(t),
+            Span::Bold(t) => format!("<strong>{}</strong>", html_escape(t)),
+            Span::Italic(t) => format!("<em>{}</em>", html_escape(t)),
+            Span::Code(t) => format!("<code>{}</code>", html_escape(t)),
+        })
+        .collect()
+}
+
+// ── Heading level shift ───────────────────────────────────────────────────────
+
+/// ATX heading level of `line` (1–6), if it is a heading.
+fn heading_level(line: &str) -> Option<usize> {
+    (1..=6).find(|&n| strip_heading(line, n).is_some())
+}
+
+/// Move the headings on the lines touched by the character range
+/// `from..to` one level up (`promote`, `###` → `##`) or down, clamped to
+/// levels 1 and 6.  A range ending at the start of a line does not touch
+/// that line.  Headings inside fenced code blocks are left alone.
+///
+/// Returns the new text and the range mapped into it, or `None` when no
+/// heading changed.
+pub fn shift_heading_levels(text: &str, from: usize, to: usize, promote: bool) -> Option<(String, usize, usize)> {
+    let (from, to) = (from.min(to), from.max(to));
+    let mut out = String::with_capacity(text.len() + 8);
+    // Character offset of each line start in `text`, and the change there.
+    let mut edits: Vec<(usize, isize)> = Vec::new();
+    let mut line_start = 0;
+    let mut in_code = false;
+    for line in text.split_inclusive('\n') {
+        let len = line.chars().count();
+        let content = line.trim_end_matches(['\n', '\r']);
+        let touched = if from == to {
+            (line_start..=line_start + content.chars().count()).contains(&from)
+        } else {
+            line_start < to && line_start + len > from
+        };
+        if content.trim_start().starts_with("```") {
+            in_code = !in_code;
+        } else if touched && !in_code {
+            match heading_level(content) {
+                Some(n) if promote && n > 1 => {
+                    out.push_str(&line[1..]);
+                    edits.push((line_start, -1));
+                    line_start += len;
+                    continue;
+                }
+                Some(n) if !promote && n < 6 => {
+                    out.push('#');
+                    edits.push((line_start, 1));
+                }
+                _ => {}
+            }
+        }
+        out.push_str(line);
+        line_start += len;
+    }
+    if edits.is_empty() {
+        return None;
+    }
+    // Positions after an edited line start move with the inserted or
+    // removed `#`; one at the line start stays there.
+    let map = |pos: usize| -> usize {
+        let delta: isize = edits.iter().filter(|(start, _)| *start < pos).map(|(_, d)| d).sum();
+        pos.saturating_add_signed(delta)
+    };
+    Some((out, map(from), map(to)))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_heading() {
+        assert_eq!(strip_heading("# Hello", 1), Some("Hello"));
+        assert_eq!(strip_heading("## World", 2), Some("World"));
+        assert_eq!(strip_heading("### Test", 3), Some("Test"));
+        assert_eq!(strip_heading("#NoSpace", 1), None);
+        assert_eq!(strip_heading("# ", 1), Some(""));
+    }
+
+    #[test]
+    fn test_is_horizontal_rule() {
+        assert!(is_horizontal_rule("---"));
+        assert!(is_horizontal_rule("***"));
+        assert!(is_horizontal_rule("___"));
+        assert!(is_horizontal_rule("----"));
+        assert!(is_horizontal_rule("- - -"));
+        assert!(!is_horizontal_rule("--"));
+        assert!(!is_horizontal_rule("abc"));
+    }
+
+    #[test]
+    fn test_parse_ordered_item() {
+        let (num, rest) = parse_ordered_item("1. First item").unwrap();
+        assert_eq!(num, "1");
+        assert_eq!(rest, "First item");
+
+        let (num2, rest2) = parse_ordered_item("10. Tenth item").unwrap();
+        assert_eq!(num2, "10");
+        assert_eq!(rest2, "Tenth item");
+
+        assert!(parse_ordered_item("Not a list").is_none());
+        assert!(parse_ordered_item("a. Not ordered").is_none());
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let md = "# 标题\n\n> 引用\n- 列表\n2. 第二\n---\n```\nlet x;\n```\n正文";
+        let blocks = parse_blocks(md);
+        assert_eq!(blocks, vec![
+            Block::Heading { level: 1, text: "标题" },
+            Block::Blank,
+            Block::Quote("引用"),
+            Block::Bullet("列表"),
+            Block::Ordered { num: "2", text: "第二" },
+            Block::Rule,
+            Block::Code("let x;".to_owned()),
+            Block::Paragraph("正文"),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_blocks_front_matter() {
+        let blocks = parse_blocks("---\nPOV: 林远\n---\n# 第一章");
+        assert_eq!(blocks, vec![
+            Block::FrontMatter(FrontMatter { fields: vec![("POV".into(), "林远".into())], lines: 3 }),
+            Block::Heading { level: 1, text: "第一章" },
+        ]);
+        // Unterminated: an ordinary rule and text.
+        assert_eq!(parse_blocks("---\nPOV: 林远"), vec![Block::Rule, Block::Paragraph("POV: 林远")]);
+        assert_eq!(
+            markdown_to_html("---\nPOV: <林远>\n---\n正文"),
+            "<table class=\"front-matter\">\n<tr><th>POV</th><td>&lt;林远&gt;</td></tr>\n</table>\n<p>正文</p>\n",
+        );
+    }
+
+    #[test]
+    fn test_parse_blocks_unclosed_fence_is_dropped() {
+        let blocks = parse_blocks("前文\n```\n# 不是标题");
+        assert_eq!(blocks, vec![Block::Paragraph("前文")]);
+    }
+
+    #[test]
+    fn test_parse_inline_mixed() {
+        assert_eq!(parse_inline("Hello **world** and *there* `x`"), vec![
+            Span::Plain("Hello "),
+            Span::Bold("world"),
+            Span::Plain(" and "),
+            Span::Italic("there"),
+            Span::Plain(" "),
+            Span::Code("x"),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_inline_unclosed_bold() {
+        assert_eq!(parse_inline("**unclosed"), vec![Span::Plain("**"), Span::Plain("unclosed")]);
+    }
+
+    #[test]
+    fn test_shift_heading_levels_selection() {
+        let text = "# 卷\n## 第一章\n正文\n```\n## 代码\n```\n###### 深\n";
+        // Demote everything: the fenced heading stays, level 6 is clamped.
+        let all = text.chars().count();
+        let (out, a, b) = shift_heading_levels(text, 0, all, false).unwrap();
+        assert_eq!(out, "## 卷\n### 第一章\n正文\n```\n## 代码\n```\n###### 深\n");
+        assert_eq!((a, b), (0, all + 2));
+
+        // Promote: level 1 is clamped.
+        let (out, _, _) = shift_heading_levels(text, 0, all, true).unwrap();
+        assert_eq!(out, "# 卷\n# 第一章\n正文\n```\n## 代码\n```\n##### 深\n");
+
+        // Nothing to change.
+        assert_eq!(shift_heading_levels("# 卷\n正文", 0, 4, true), None);
+        assert_eq!(shift_heading_levels("###### 深", 0, 0, false), None);
+    }
+
+    #[test]
+    fn test_markdown_to_html() {
+        let md = "# 林枫 <主角>\n\n**沉默**寡言，擅用*剑*。\n- 青云宗\n- `内门`\n1. 入门\n> 引文\n---\n```\na < b\n```";
+        assert_eq!(markdown_to_html(md), "\
+<h1>林枫 &lt;主角&gt;</h1>
+<p><strong>沉默</strong>寡言，擅用<em>剑</em>。</p>
+<ul>
+<li>青云宗</li>
+<li><code>内门</code></li>
+</ul>
+<ol>
+<li>入门</li>
+</ol>
+<blockquote>引文</blockquote>
+<hr>
+<pre><code>a &lt; b</code></pre>
+");
+        assert_eq!(markdown_to_html(""), "");
+        // 注释 are left out, including list items holding nothing else.
+        assert_eq!(
+            markdown_to_html("- 甲 %% 待改 %%\n- %%删\n掉%%\n正文%%注%%"),
+            "<ul>\n<li>甲</li>\n</ul>\n<p>正文</p>\n",
+        );
+    }
+
+    #[test]
+    fn test_shift_heading_levels_touched_lines() {
+        let text = "## 甲\n## 乙\n## 丙";
+        // Caret inside the second line: only that line.
+        let (out, a, b) = shift_heading_levels(text, 7, 7, true).unwrap();
+        assert_eq!(out, "## 甲\n# 乙\n## 丙");
+        assert_eq!((a, b), (6, 6));
+        // Caret at the very end of the text: the last line.
+        let (out, _, _) = shift_heading_levels(text, 14, 14, false).unwrap();
+        assert_eq!(out, "## 甲\n## 乙\n### 丙");
+        // Selection ending at the start of line 3 leaves it alone; the
+        // start stays put at the start of line 1.
+        let (out, a, b) = shift_heading_levels(text, 0, 10, false).unwrap();
+        assert_eq!(out, "### 甲\n### 乙\n## 丙");
+        assert_eq!((a, b), (0, 12));
+        // CRLF line endings are kept.
+        let (out, _, _) = shift_heading_levels("## 甲\r\n正文\r\n", 0, 0, true).unwrap();
+        assert_eq!(out, "# 甲\r\n正文\r\n");
+    }
+}
@@ -0,0 +1,98 @@
+use std::ops::Range;
+
+// ── 待处理标记 (stuck markers) ────────────────────────────────────────────────
+//
+// Tokens such as `TODO` or `【卡】` the writer leaves where a draft is stuck.
+// Matching is literal and case-sensitive; at any position the longest token
+// wins, and matches never overlap.
+
+/// One marker occurrence in a text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    /// 1-based line number.
+    pub line_no: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    pub token: String,
+    /// The whole line, trimmed, for display.
+    pub line: String,
+}
+
+/// Byte ranges of the `tokens` occurring in `text`.  Blank tokens are ignored.
+pub fn marker_ranges(text: &str, tokens: &[String]) -> Vec<Range<usize>> {
+    let tokens: Vec<&str> = tokens.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).collect();
+    let mut out = Vec::new();
+    if tokens.is_empty() {
+        return out;
+    }
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let longest = tokens.iter()
+            .filter(|t| rest.starts_with(**t))
+            .map(|t| t.len())
+            .max();
+        match longest {
+            Some(len) => {
+                out.push(i..i + len);
+                i += len;
+            }
+            None => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    out
+}
+
+/// Every marker in `text`, in reading order, with its line and column.
+pub fn find_markers(text: &str, tokens: &[String]) -> Vec<Marker> {
+    let mut out = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        for range in marker_ranges(line, tokens) {
+            out.push(Marker {
+                line_no: n + 1,
+                column: line[..range.start].chars().count() + 1,
+                token: line[range].to_owned(),
+                line: line.trim().to_owned(),
+            });
+        }
+    }
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(list: &[&str]) -> Vec<String> {
+        list.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_find_markers_positions() {
+        let text = "# 第一章\n\n林枫推门而入。【卡】这里要写对话\n\nTODO 补一段打斗 TODO\n";
+        let found = find_markers(text, &tokens(&["TODO", "【卡】"]));
+        let at: Vec<(usize, usize, &str)> = found.iter()
+            .map(|m| (m.line_no, m.column, m.token.as_str()))
+            .collect();
+        assert_eq!(at, [(3, 8, "【卡】"), (5, 1, "TODO"), (5, 12, "TODO")]);
+        assert_eq!(found[0].line, "林枫推门而入。【卡】这里要写对话");
+    }
+
+    #[test]
+    fn test_marker_ranges_longest_and_case() {
+        let text = "TODO: 见 TODOS，todo 不算";
+        let ranges = marker_ranges(text, &tokens(&["TODO", "TODOS"]));
+        let hits: Vec<&str> = ranges.into_iter().map(|r| &text[r]).collect();
+        assert_eq!(hits, ["TODO", "TODOS"]);
+    }
+
+    #[test]
+    fn test_marker_blank_tokens_ignored() {
+        assert!(marker_ranges("随便写点", &tokens(&["", "  "])).is_empty());
+        assert!(find_markers("TODO", &[]).is_empty());
+        // Tokens are trimmed before matching.
+        assert_eq!(find_markers("x【卡】", &tokens(&[" 【卡】 "]))[0].column, 2);
+    }
+}
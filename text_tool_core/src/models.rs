@@ -0,0 +1,2240 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[cfg(feature = "egui")]
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+use crate::usage::LinkUsage;
+
+// ── ObjectKind ────────────────────────────────────────────────────────────────
+
+/// The category of a world object (content element).  Serialised as its
+/// variant name (`"Character"`), or as the bare name for a custom kind; any
+/// other string reads back as [`ObjectKind::Custom`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectKind {
+    Character,  // 人物
+    Scene,      // 场景
+    Location,   // 地点
+    Item,       // 道具
+    Faction,    // 势力
+    Other,      // 其他
+    /// A kind the project defines (功法, 种族, …), by name; its icon and
+    /// color come from [`ProjectConfig::object_kinds`].
+    Custom(String),
+}
+
+/// Icon of a custom kind the project has no [`CustomKind`] entry for.
+pub const CUSTOM_KIND_ICON: &str = "🏷";
+
+impl ObjectKind {
+    pub fn label(&self) -> &str {
+        match self {
+            ObjectKind::Character => "人物",
+            ObjectKind::Scene     => "场景",
+            ObjectKind::Location  => "地点",
+            ObjectKind::Item      => "道具",
+            ObjectKind::Faction   => "势力",
+            ObjectKind::Other     => "其他",
+            ObjectKind::Custom(name) => name,
+        }
+    }
+    /// Icon of a built-in kind; custom kinds get [`CUSTOM_KIND_ICON`] here
+    /// and their own icon from [`ProjectConfig::kind_icon`].
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ObjectKind::Character => "👤",
+            ObjectKind::Scene     => "🎭",
+            ObjectKind::Location  => "📍",
+            ObjectKind::Item      => "🗡",
+            ObjectKind::Faction   => "🏰",
+            ObjectKind::Other     => "⬡",
+            ObjectKind::Custom(_) => CUSTOM_KIND_ICON,
+        }
+    }
+    /// The built-in kinds.
+    pub fn all() -> &'static [ObjectKind] {
+        &[
+            ObjectKind::Character,
+            ObjectKind::Scene,
+            ObjectKind::Location,
+            ObjectKind::Item,
+            ObjectKind::Faction,
+            ObjectKind::Other,
+        ]
+    }
+
+    /// The name written to JSON.
+    fn key(&self) -> &str {
+        match self {
+            ObjectKind::Character => "Character",
+            ObjectKind::Scene     => "Scene",
+            ObjectKind::Location  => "Location",
+            ObjectKind::Item      => "Item",
+            ObjectKind::Faction   => "Faction",
+            ObjectKind::Other     => "Other",
+            ObjectKind::Custom(name) => name,
+        }
+    }
+
+    fn from_key(key: &str) -> ObjectKind {
+        ObjectKind::all().iter()
+            .find(|k| k.key() == key)
+            .cloned()
+            .unwrap_or_else(|| ObjectKind::Custom(key.to_owned()))
+    }
+
+    /// Whether `name` is taken by a built-in kind, as its label or its JSON
+    /// name, and so cannot name a custom kind.
+    pub fn is_builtin_name(name: &str) -> bool {
+        ObjectKind::all().iter().any(|k| k.label() == name || k.key() == name)
+    }
+}
+
+impl Serialize for ObjectKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.key())
+    }
+}
+
+impl<'de> Deserialize<'de> for ObjectKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        Ok(ObjectKind::from_key(&key))
+    }
+}
+
+/// The kinds to offer for `objects`: the built-in ones, those of
+/// `registry`, then custom kinds that objects use without a registry entry.
+pub fn object_kinds(registry: &[CustomKind], objects: &[WorldObject]) -> Vec<ObjectKind> {
+    let mut kinds: Vec<ObjectKind> = ObjectKind::all().to_vec();
+    let used = objects.iter().map(|o| &o.kind).filter(|k| matches!(k, ObjectKind::Custom(_)));
+    for kind in registry.iter().map(|c| ObjectKind::Custom(c.name.clone())).chain(used.cloned()) {
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    kinds
+}
+
+/// A user-defined object kind of the project: an [`ObjectKind::Custom`] name
+/// with how it is drawn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomKind {
+    pub name: String,
+    pub icon: String,
+    /// sRGB.
+    pub color: [u8; 3],
+}
+
+impl CustomKind {
+    #[cfg(feature = "egui")]
+    pub fn color32(&self) -> Color32 {
+        let [r, g, b] = self.color;
+        Color32::from_rgb(r, g, b)
+    }
+}
+
+// ── RelationKind ──────────────────────────────────────────────────────────────
+
+/// The semantic type of a link between two elements.
+/// Works for Object↔Object, Object↔StructNode, and StructNode↔StructNode links.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum RelationKind {
+    // Object ↔ Object
+    Friend,     // 友好
+    Enemy,      // 敌对
+    Family,     // 亲属
+    Owns,       // 持有 (持有某道具)
+    LocatedAt,  // 所在 (人物所在地点)
+    BelongsTo,  // 所属 (人物所属势力)
+    // Object ↔ StructNode
+    AppearsIn,  // 出场 (对象在某章节出现)
+    MentionedIn,// 提及 (对象在某章节被提及)
+    // StructNode ↔ StructNode (non-parent cross links)
+    Foreshadows,// 铺垫 (一节为另一节铺垫)
+    Resolves,   // 回收 (一节回收另一节的伏笔)
+    Parallels,  // 并行 (两节并行叙述)
+    // Fallback
+    Other,      // 其他
+}
+
+impl RelationKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RelationKind::Friend      => "友好",
+            RelationKind::Enemy       => "敌对",
+            RelationKind::Family      => "亲属",
+            RelationKind::Owns        => "持有",
+            RelationKind::LocatedAt   => "所在",
+            RelationKind::BelongsTo   => "所属",
+            RelationKind::AppearsIn   => "出场",
+            RelationKind::MentionedIn => "提及",
+            RelationKind::Foreshadows => "铺垫",
+            RelationKind::Resolves    => "回收",
+            RelationKind::Parallels   => "并行",
+            RelationKind::Other       => "其他",
+        }
+    }
+    pub fn all() -> &'static [RelationKind] {
+        &[
+            RelationKind::Friend,
+            RelationKind::Enemy,
+            RelationKind::Family,
+            RelationKind::Owns,
+            RelationKind::LocatedAt,
+            RelationKind::BelongsTo,
+            RelationKind::AppearsIn,
+            RelationKind::MentionedIn,
+            RelationKind::Foreshadows,
+            RelationKind::Resolves,
+            RelationKind::Parallels,
+            RelationKind::Other,
+        ]
+    }
+}
+
+// ── LinkTarget ────────────────────────────────────────────────────────────────
+
+/// What a link points to — another world object (by name) or a structure node
+/// (by title).  Using names rather than integer IDs keeps the data human-readable
+/// and consistent with the rest of the app, which uses names throughout.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum LinkTarget {
+    /// Name of another `WorldObject`.
+    Object(String),
+    /// Title path of a `StructNode` (e.g. "第一卷/第一章").
+    Node(String),
+}
+
+impl LinkTarget {
+    pub fn display_name(&self) -> &str {
+        match self {
+            LinkTarget::Object(n) | LinkTarget::Node(n) => n,
+        }
+    }
+    pub fn type_label(&self) -> &'static str {
+        match self {
+            LinkTarget::Object(_) => "对象",
+            LinkTarget::Node(_)   => "章节",
+        }
+    }
+}
+
+// ── ObjectLink ────────────────────────────────────────────────────────────────
+
+/// A directed association from a `WorldObject` to another element.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectLink {
+    pub target: LinkTarget,
+    pub kind: RelationKind,
+    pub note: String,
+}
+
+// ── WorldObject ───────────────────────────────────────────────────────────────
+
+/// A unified "content element": character, scene, location, item, faction, …
+/// Replaces the old `Character` struct and extends it with a `kind` discriminant
+/// and a generalised `links` list that can point to other objects *or* to
+/// structure nodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldObject {
+    pub name: String,
+    pub kind: ObjectKind,
+    /// Core traits / description (what was `traits` in the old Character).
+    pub description: String,
+    pub background: String,
+    pub links: Vec<ObjectLink>,
+    /// Free-form labels (e.g. "主角团", "已退场") used for grouping.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Shown in the quick-access strip under the menu bar.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Reference files (art, research notes) as project-relative paths;
+    /// see [`crate::attachments`].
+    #[serde(default)]
+    pub attachments: Vec<String>,
+    /// Unix seconds; `0` for objects saved before timestamps existed.
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+impl WorldObject {
+    pub fn new(name: &str, kind: ObjectKind) -> Self {
+        let now = unix_now();
+        WorldObject {
+            name: name.to_owned(),
+            kind,
+            description: String::new(),
+            background: String::new(),
+            links: vec![],
+            tags: vec![],
+            pinned: false,
+            attachments: vec![],
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Mark as modified now.
+    pub fn touch(&mut self) {
+        self.updated_at = unix_now();
+    }
+    pub fn icon(&self) -> &'static str { self.kind.icon() }
+
+    /// Render this object as a Markdown section (used for exports).
+    pub fn to_markdown(&self) -> String {
+        crate::profile::ProfileSheet::new(self).to_markdown()
+    }
+}
+
+// ── Name comparison ───────────────────────────────────────────────────────────
+
+/// Canonical form of an object name or node title for duplicate checks:
+/// full-width ASCII and the ideographic space fold to half-width, all
+/// whitespace is dropped and letters are lower-cased.
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            c => c,
+        })
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Stored form of a title or name as entered: trimmed, with every run of
+/// whitespace (the ideographic space included) collapsed into one
+/// half-width space.  Unlike [`normalize_name`] the result is still the
+/// name to display.
+pub fn normalize_title(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `a` and `b` name the same thing once both are [`normalize_title`]d.
+/// Used wherever names and titles link data together.
+pub fn same_title(a: &str, b: &str) -> bool {
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
+/// One name rewritten by [`normalize_all_names`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameChange {
+    /// Where the name is stored: 对象, 章节, 伏笔, 关联对象, …
+    pub place: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+/// [`normalize_title`] every object name, node title, foreshadow name and
+/// every reference to them, in place.  Returns what changed, in data order.
+pub fn normalize_all_names(
+    objects: &mut [WorldObject],
+    roots: &mut [StructNode],
+    foreshadows: &mut [Foreshadow],
+) -> Vec<NameChange> {
+    fn fix(name: &mut String, place: &'static str, changes: &mut Vec<NameChange>) {
+        let clean = normalize_title(name);
+        if clean != *name {
+            let before = std::mem::replace(name, clean);
+            changes.push(NameChange { place, before, after: name.clone() });
+        }
+    }
+    fn walk(nodes: &mut [StructNode], changes: &mut Vec<NameChange>) {
+        for node in nodes {
+            fix(&mut node.title, "章节", changes);
+            for name in &mut node.linked_objects {
+                fix(name, "关联对象", changes);
+            }
+            for link in &mut node.node_links {
+                fix(&mut link.target_title, "跨节点关联", changes);
+            }
+            walk(&mut node.children, changes);
+        }
+    }
+
+    let mut changes = Vec::new();
+    for obj in objects {
+        fix(&mut obj.name, "对象", &mut changes);
+        for link in &mut obj.links {
+            let (LinkTarget::Object(name) | LinkTarget::Node(name)) = &mut link.target;
+            fix(name, "对象关系", &mut changes);
+        }
+    }
+    walk(roots, &mut changes);
+    for fs in foreshadows {
+        fix(&mut fs.name, "伏笔", &mut changes);
+        for chapter in &mut fs.related_chapters {
+            fix(chapter, "伏笔关联章节", &mut changes);
+        }
+        for chapter in &mut fs.resolve_chapters {
+            fix(chapter, "伏笔回收章节", &mut changes);
+        }
+    }
+    changes
+}
+
+/// The first of `existing` equal to `name` under [`normalize_name`].  Blank
+/// names never count as duplicates.
+pub fn find_duplicate_name<'a>(name: &str, existing: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let key = normalize_name(name);
+    if key.is_empty() {
+        return None;
+    }
+    existing.into_iter().find(|e| normalize_name(e) == key)
+}
+
+/// `base`, or `base 2`, `base 3`, … — the first not already in `existing`.
+pub fn unique_name<'a>(base: &str, existing: impl IntoIterator<Item = &'a str> + Clone) -> String {
+    let mut name = base.to_owned();
+    let mut n = 1;
+    while find_duplicate_name(&name, existing.clone()).is_some() {
+        n += 1;
+        name = format!("{base} {n}");
+    }
+    name
+}
+
+/// Append each of `names`, [`normalize_title`]d, to `list` unless it is
+/// blank or already there under [`normalize_name`] (including earlier names
+/// of the same call).  Returns how many were added.
+pub fn push_unique_names(list: &mut Vec<String>, names: impl IntoIterator<Item = String>) -> usize {
+    let mut added = 0;
+    for name in names {
+        let name = normalize_title(&name);
+        if name.is_empty() || find_duplicate_name(&name, list.iter().map(String::as_str)).is_some() {
+            continue;
+        }
+        list.push(name);
+        added += 1;
+    }
+    added
+}
+
+// ── Index-selection helpers ───────────────────────────────────────────────────
+
+/// Where index `idx` ends up after the list item at `from` is moved to `to`.
+pub fn index_after_move(idx: usize, from: usize, to: usize) -> usize {
+    if idx == from {
+        to
+    } else if from < to && idx > from && idx <= to {
+        idx - 1
+    } else if from > to && idx >= to && idx < from {
+        idx + 1
+    } else {
+        idx
+    }
+}
+
+/// Swap the item at `idx` with its neighbour — the previous one if `up`,
+/// else the next.  Returns the item's new index, or `None` if it is already
+/// at that end of the list (or out of range).
+pub fn move_item<T>(items: &mut [T], idx: usize, up: bool) -> Option<usize> {
+    let to = if up { idx.checked_sub(1)? } else { idx + 1 };
+    if to >= items.len() || idx >= items.len() {
+        return None;
+    }
+    items.swap(idx, to);
+    Some(to)
+}
+
+/// Re-index `selection` after the items in `removed` were deleted from the list.
+/// Selected items that were removed are dropped; the rest shift down.
+pub fn selection_after_removal(
+    selection: &std::collections::BTreeSet<usize>,
+    removed: &std::collections::BTreeSet<usize>,
+) -> std::collections::BTreeSet<usize> {
+    selection.iter()
+        .filter(|i| !removed.contains(i))
+        .map(|&i| i - removed.range(..i).count())
+        .collect()
+}
+
+// ── ChapterTag ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ChapterTag {
+    Normal,     // 普通
+    Climax,     // 高潮
+    Foreshadow, // 伏笔
+    Transition, // 过渡
+}
+
+impl ChapterTag {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChapterTag::Normal     => "普通",
+            ChapterTag::Climax     => "高潮",
+            ChapterTag::Foreshadow => "伏笔",
+            ChapterTag::Transition => "过渡",
+        }
+    }
+    pub fn all() -> &'static [ChapterTag] {
+        &[ChapterTag::Normal, ChapterTag::Climax, ChapterTag::Foreshadow, ChapterTag::Transition]
+    }
+    #[cfg(feature = "egui")]
+    pub fn color(&self) -> Color32 {
+        match self {
+            ChapterTag::Normal     => Color32::from_gray(160),
+            ChapterTag::Climax     => Color32::from_rgb(220, 80, 80),
+            ChapterTag::Foreshadow => Color32::from_rgb(80, 160, 220),
+            ChapterTag::Transition => Color32::from_rgb(120, 190, 120),
+        }
+    }
+}
+
+// ── NodeStage ─────────────────────────────────────────────────────────────────
+
+/// Writing progress of a structure node; the columns of the 看板 view.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NodeStage {
+    NotStarted, // 未开始
+    Drafting,   // 草稿中
+    Done,       // 已完成
+}
+
+impl NodeStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            NodeStage::NotStarted => "未开始",
+            NodeStage::Drafting   => "草稿中",
+            NodeStage::Done       => "已完成",
+        }
+    }
+    pub fn all() -> [NodeStage; 3] {
+        [NodeStage::NotStarted, NodeStage::Drafting, NodeStage::Done]
+    }
+}
+
+// ── StructKind ────────────────────────────────────────────────────────────────
+
+/// The hierarchical level of a structure node.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum StructKind {
+    Outline,  // 总纲
+    Volume,   // 卷
+    Chapter,  // 章
+    Section,  // 节
+    Scene,    // 场
+}
+
+impl StructKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            StructKind::Outline => "总纲",
+            StructKind::Volume  => "卷",
+            StructKind::Chapter => "章",
+            StructKind::Section => "节",
+            StructKind::Scene   => "场",
+        }
+    }
+    pub fn icon(&self) -> &'static str {
+        match self {
+            StructKind::Outline => "📋",
+            StructKind::Volume  => "📚",
+            StructKind::Chapter => "📖",
+            StructKind::Section => "📑",
+            StructKind::Scene   => "🎬",
+        }
+    }
+    /// Numbered name of the `n`-th node of this kind, e.g. `第7章`.
+    /// 总纲 is not numbered.
+    pub fn ordinal(&self, n: usize) -> String {
+        match self {
+            StructKind::Outline => self.label().to_owned(),
+            _ => format!("第{n}{}", self.label()),
+        }
+    }
+    pub fn all() -> &'static [StructKind] {
+        &[
+            StructKind::Outline,
+            StructKind::Volume,
+            StructKind::Chapter,
+            StructKind::Section,
+            StructKind::Scene,
+        ]
+    }
+    /// Position of this level in the hierarchy (总纲 = 0 … 场 = 4).
+    /// Used to detect out-of-order nesting.
+    pub fn rank(&self) -> usize {
+        match self {
+            StructKind::Outline => 0,
+            StructKind::Volume  => 1,
+            StructKind::Chapter => 2,
+            StructKind::Section => 3,
+            StructKind::Scene   => 4,
+        }
+    }
+    /// The natural child kind when adding a child to this level.
+    pub fn default_child_kind(&self) -> StructKind {
+        match self {
+            StructKind::Outline => StructKind::Volume,
+            StructKind::Volume  => StructKind::Chapter,
+            StructKind::Chapter => StructKind::Section,
+            StructKind::Section => StructKind::Scene,
+            StructKind::Scene   => StructKind::Scene,
+        }
+    }
+    /// Like `default_child_kind`, but never deeper than `deepest`.
+    /// Lets projects that stop at 节 keep adding 节 instead of 场.
+    pub fn child_kind_within(&self, deepest: &StructKind) -> StructKind {
+        let child = self.default_child_kind();
+        if child.rank() > deepest.rank() { deepest.clone() } else { child }
+    }
+}
+
+// ── Hierarchy lint ────────────────────────────────────────────────────────────
+
+/// A structure node whose kind does not fit under its parent's kind.
+/// Such nesting is allowed (the tree is never rejected) but reported in the
+/// structure panel so the author can decide whether to fix it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchyIssue {
+    /// Index path of the offending child node.
+    pub path: Vec<usize>,
+    pub parent_title: String,
+    pub parent_kind: StructKind,
+    pub child_title: String,
+    pub child_kind: StructKind,
+}
+
+impl HierarchyIssue {
+    pub fn describe(&self) -> String {
+        let problem = if self.child_kind.rank() > self.parent_kind.rank() {
+            "跳级"
+        } else {
+            "层级倒置"
+        };
+        format!(
+            "{}「{}」位于{}「{}」之下（{problem}）",
+            self.child_kind.label(), self.child_title,
+            self.parent_kind.label(), self.parent_title,
+        )
+    }
+}
+
+/// Walk the tree and list every parent → child pair whose kinds are out of
+/// order: a child at the same or a higher level than its parent (except 场
+/// inside 场), or a child that skips a level (e.g. 章 directly under 总纲).
+pub fn hierarchy_issues(roots: &[StructNode]) -> Vec<HierarchyIssue> {
+    fn walk(parent: &StructNode, path: &mut Vec<usize>, out: &mut Vec<HierarchyIssue>) {
+        for (i, child) in parent.children.iter().enumerate() {
+            path.push(i);
+            let (p, c) = (parent.kind.rank(), child.kind.rank());
+            let same_leaf = p == c && child.kind == StructKind::Scene;
+            if !same_leaf && (c <= p || c > p + 1) {
+                out.push(HierarchyIssue {
+                    path: path.clone(),
+                    parent_title: parent.title.clone(),
+                    parent_kind: parent.kind.clone(),
+                    child_title: child.title.clone(),
+                    child_kind: child.kind.clone(),
+                });
+            }
+            walk(child, path, out);
+            path.pop();
+        }
+    }
+    let mut out = Vec::new();
+    for (i, root) in roots.iter().enumerate() {
+        let mut path = vec![i];
+        walk(root, &mut path, &mut out);
+    }
+    out
+}
+
+// ── Duplicate titles ──────────────────────────────────────────────────────────
+
+/// A title carried by more than one structure node.  Node links, chapter
+/// file matching and foreshadow chapter lists refer to nodes by title, so
+/// such a title is ambiguous.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateTitle {
+    /// The title, [`normalize_title`]d.
+    pub title: String,
+    /// Paths of the nodes carrying it, depth-first.
+    pub paths: Vec<Vec<usize>>,
+}
+
+/// Every title shared by several nodes ([`same_title`]), in the order of
+/// their first node.
+pub fn duplicate_titles(roots: &[StructNode]) -> Vec<DuplicateTitle> {
+    let mut groups: Vec<DuplicateTitle> = Vec::new();
+    let mut index: BTreeMap<String, usize> = BTreeMap::new();
+    for path in node_paths(roots) {
+        let Some(node) = node_at(roots, &path) else { continue };
+        let title = normalize_title(&node.title);
+        if title.is_empty() {
+            continue;
+        }
+        match index.get(&title) {
+            Some(&i) => groups[i].paths.push(path),
+            None => {
+                index.insert(title.clone(), groups.len());
+                groups.push(DuplicateTitle { title, paths: vec![path] });
+            }
+        }
+    }
+    groups.retain(|g| g.paths.len() > 1);
+    groups
+}
+
+/// Path of the node titled `title` ([`same_title`]) that a reference made
+/// from the node at `near` most likely means: among several, the one
+/// sharing the longest leading path with `near` (the same parent subtree
+/// first), then the first depth-first.
+pub fn node_path_titled_near(roots: &[StructNode], title: &str, near: &[usize]) -> Option<Vec<usize>> {
+    node_paths(roots).into_iter()
+        .filter(|p| node_at(roots, p).is_some_and(|n| same_title(&n.title, title)))
+        .min_by_key(|p| std::cmp::Reverse(p.iter().zip(near).take_while(|(a, b)| a == b).count()))
+}
+
+/// [`node_titled`] preferring the match nearest to `near`, see
+/// [`node_path_titled_near`].
+pub fn node_titled_near<'a>(roots: &'a [StructNode], title: &str, near: &[usize]) -> Option<&'a StructNode> {
+    node_at(roots, &node_path_titled_near(roots, title, near)?)
+}
+
+/// A title for the node at `path` that no other node has: its title with
+/// the parent's appended, 「第一章（第一卷）」, numbered on when even that is
+/// taken.  `None` for a root node.
+pub fn unique_title_for(roots: &[StructNode], path: &[usize]) -> Option<String> {
+    let (_, parent_path) = path.split_last()?;
+    let parent = node_at(roots, parent_path).filter(|_| !parent_path.is_empty())?;
+    let node = node_at(roots, path)?;
+    let base = format!("{}（{}）", normalize_title(&node.title), normalize_title(&parent.title));
+    let titles = all_node_titles(roots);
+    (1..)
+        .map(|n| if n == 1 { base.clone() } else { format!("{base} {n}") })
+        .find(|t| !titles.iter().any(|other| same_title(other, t)))
+}
+
+// ── NodeLink ──────────────────────────────────────────────────────────────────
+
+/// A non-parent cross-link between two structure nodes (e.g. a chapter that
+/// foreshadows another chapter many levels away).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeLink {
+    /// Title of the target node.
+    pub target_title: String,
+    pub kind: RelationKind,
+    pub note: String,
+}
+
+// ── StructNode ────────────────────────────────────────────────────────────────
+
+/// A hierarchical structure element (总纲 / 卷 / 章 / 节 / 场).
+/// Replaces the old flat `Chapter` and adds nesting, a `kind` discriminant,
+/// a list of linked world-objects, and cross-node links.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructNode {
+    pub title: String,
+    pub kind: StructKind,
+    pub tag: ChapterTag,
+    pub summary: String,
+    /// Longer planning notes in Markdown (beats, research links) kept out
+    /// of the manuscript.
+    #[serde(default)]
+    pub notes: String,
+    pub done: bool,
+    /// Explicit writing stage; `None` for nodes saved before stages existed.
+    /// See [`StructNode::stage`].
+    #[serde(default)]
+    pub stage: Option<NodeStage>,
+    /// Nested children (e.g. a Volume contains Chapters).
+    pub children: Vec<StructNode>,
+    /// Names of `WorldObject`s associated with this node.
+    pub linked_objects: Vec<String>,
+    /// Non-parent cross-links to other structure nodes.
+    pub node_links: Vec<NodeLink>,
+    /// Chapter file relative to `Content/` with `/` separators, set when
+    /// the node was imported from the folder layout.  `None` means the file
+    /// is found by title.
+    #[serde(default)]
+    pub file: Option<String>,
+    /// 目标字数: words this node should reach.
+    #[serde(default)]
+    pub target_words: Option<usize>,
+    /// 截止日期, `YYYY-MM-DD` like the keys of [`WritingStats::daily_words`].
+    #[serde(default)]
+    pub due_date: Option<String>,
+    /// Unix seconds; `0` for nodes saved before timestamps existed.
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+impl StructNode {
+    pub fn new(title: &str, kind: StructKind) -> Self {
+        let now = unix_now();
+        StructNode {
+            title: title.to_owned(),
+            kind,
+            tag: ChapterTag::Normal,
+            summary: String::new(),
+            notes: String::new(),
+            done: false,
+            stage: None,
+            children: vec![],
+            linked_objects: vec![],
+            node_links: vec![],
+            file: None,
+            target_words: None,
+            due_date: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Mark as modified now.
+    pub fn touch(&mut self) {
+        self.updated_at = unix_now();
+    }
+
+    /// Lowercase file stem this node's chapter file is looked up by: the
+    /// stem of [`file`](Self::file) if set, else the title.
+    pub fn file_key(&self) -> String {
+        let name = self.file.as_deref()
+            .map(|f| {
+                let base = f.rsplit('/').next().unwrap_or(f);
+                base.strip_suffix(".md").unwrap_or(base)
+            })
+            .unwrap_or(&self.title);
+        name.trim().to_lowercase()
+    }
+
+    /// Writing stage: `done` always means 已完成; otherwise the explicit
+    /// stage, where a stale 已完成 (the node was un-done) counts as 草稿中.
+    pub fn stage(&self) -> NodeStage {
+        if self.done {
+            return NodeStage::Done;
+        }
+        match self.stage {
+            None | Some(NodeStage::NotStarted) => NodeStage::NotStarted,
+            Some(NodeStage::Drafting) | Some(NodeStage::Done) => NodeStage::Drafting,
+        }
+    }
+
+    /// Move the node to `stage`, keeping `done` in step.
+    pub fn set_stage(&mut self, stage: NodeStage) {
+        self.done = stage == NodeStage::Done;
+        self.stage = Some(stage);
+        self.touch();
+    }
+
+    /// Total number of leaf nodes (nodes without children).
+    pub fn leaf_count(&self) -> usize {
+        if self.children.is_empty() {
+            1
+        } else {
+            self.children.iter().map(|c| c.leaf_count()).sum()
+        }
+    }
+
+    /// The leaf nodes below this one in tree order; the node itself if it
+    /// has no children.
+    pub fn leaves(&self) -> Vec<&StructNode> {
+        if self.children.is_empty() {
+            vec![self]
+        } else {
+            self.children.iter().flat_map(|c| c.leaves()).collect()
+        }
+    }
+
+    /// Number of done leaf nodes.
+    pub fn done_count(&self) -> usize {
+        if self.children.is_empty() {
+            if self.done { 1 } else { 0 }
+        } else {
+            self.children.iter().map(|c| c.done_count()).sum()
+        }
+    }
+}
+
+// ── Tree helpers ──────────────────────────────────────────────────────────────
+
+/// Navigate immutably into a tree of `StructNode`s by index path.
+#[allow(dead_code)]
+pub fn node_at<'a>(roots: &'a [StructNode], path: &[usize]) -> Option<&'a StructNode> {
+    if path.is_empty() { return None; }
+    let node = roots.get(path[0])?;
+    if path.len() == 1 { Some(node) } else { node_at(&node.children, &path[1..]) }
+}
+
+/// Navigate mutably into a tree of `StructNode`s by index path.
+pub fn node_at_mut<'a>(roots: &'a mut [StructNode], path: &[usize]) -> Option<&'a mut StructNode> {
+    if path.is_empty() { return None; }
+    if path.len() == 1 {
+        return roots.get_mut(path[0]);
+    }
+    let node = roots.get_mut(path[0])?;
+    node_at_mut(&mut node.children, &path[1..])
+}
+
+/// Kind and 1-based number of every node along `path`, root first: each
+/// node is counted among its siblings of the same kind, so a 节 placed
+/// between chapters does not shift their numbers.  `None` when `path` is
+/// empty or leads nowhere.
+pub fn node_ordinals(roots: &[StructNode], path: &[usize]) -> Option<Vec<(StructKind, usize)>> {
+    if path.is_empty() {
+        return None;
+    }
+    let mut siblings = roots;
+    let mut out = Vec::with_capacity(path.len());
+    for &i in path {
+        let node = siblings.get(i)?;
+        let n = siblings[..i].iter().filter(|s| s.kind == node.kind).count() + 1;
+        out.push((node.kind.clone(), n));
+        siblings = &node.children;
+    }
+    Some(out)
+}
+
+/// Position of the node at `path` for display or generated headings,
+/// e.g. `第2卷 · 第7章 · 第3节` (see [`node_ordinals`]).
+pub fn node_position(roots: &[StructNode], path: &[usize]) -> Option<String> {
+    let parts: Vec<String> = node_ordinals(roots, path)?.iter()
+        .map(|(kind, n)| kind.ordinal(*n))
+        .collect();
+    Some(parts.join(" · "))
+}
+
+/// Index path of the leaf whose chapter file is `rel` (relative to
+/// `Content/`, `/`-separated): the leaf linked to it by
+/// [`StructNode::file`], else an unlinked leaf whose
+/// [`file_key`](StructNode::file_key) is the file's stem.
+pub fn leaf_for_file(roots: &[StructNode], rel: &str) -> Option<Vec<usize>> {
+    fn find(nodes: &[StructNode], path: &mut Vec<usize>, hit: &dyn Fn(&StructNode) -> bool) -> bool {
+        for (i, n) in nodes.iter().enumerate() {
+            path.push(i);
+            let found = if n.children.is_empty() { hit(n) } else { find(&n.children, path, hit) };
+            if found {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+    let base = rel.rsplit('/').next().unwrap_or(rel);
+    let stem = base.strip_suffix(".md").unwrap_or(base).trim().to_lowercase();
+    let mut path = Vec::new();
+    if find(roots, &mut path, &|n| n.file.as_deref() == Some(rel))
+        || find(roots, &mut path, &|n| n.file.is_none() && n.file_key() == stem)
+    {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// `rel` with `/` separators and without empty, `.` or resolved `..`
+/// components: `./第一卷\\01.md` → `第一卷/01.md`.
+pub fn normalize_rel_path(rel: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rel.split(['/', '\\']) {
+        match part.trim() {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Chapter file → leaf node lookup for the whole structure, with the
+/// matching rules of [`leaf_for_file`]; build it once per structure change
+/// rather than searching the tree for every file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileNodeMap {
+    /// Normalized [`StructNode::file`] → path of the first leaf linked to it.
+    by_file: std::collections::HashMap<String, Vec<usize>>,
+    /// [`StructNode::file_key`] → path of the first unlinked leaf.
+    by_stem: std::collections::HashMap<String, Vec<usize>>,
+}
+
+impl FileNodeMap {
+    pub fn build(roots: &[StructNode]) -> Self {
+        fn walk(nodes: &[StructNode], path: &mut Vec<usize>, map: &mut FileNodeMap) {
+            for (i, n) in nodes.iter().enumerate() {
+                path.push(i);
+                if !n.children.is_empty() {
+                    walk(&n.children, path, map);
+                } else if let Some(file) = &n.file {
+                    map.by_file.entry(normalize_rel_path(file)).or_insert_with(|| path.clone());
+                } else {
+                    map.by_stem.entry(n.file_key()).or_insert_with(|| path.clone());
+                }
+                path.pop();
+            }
+        }
+        let mut map = FileNodeMap::default();
+        walk(roots, &mut Vec::new(), &mut map);
+        map
+    }
+
+    /// Index path of the leaf for the chapter file `rel` (relative to
+    /// `Content/`, any separators).
+    pub fn lookup(&self, rel: &str) -> Option<&[usize]> {
+        let rel = normalize_rel_path(rel);
+        if let Some(path) = self.by_file.get(&rel) {
+            return Some(path);
+        }
+        let base = rel.rsplit('/').next().unwrap_or(&rel);
+        let stem = base.strip_suffix(".md").unwrap_or(base).trim().to_lowercase();
+        self.by_stem.get(&stem).map(Vec::as_slice)
+    }
+}
+
+/// Index paths of the leaf nodes in each 看板 column, in
+/// [`NodeStage::all`] order and depth-first within a column.
+pub fn kanban_columns(roots: &[StructNode]) -> [Vec<Vec<usize>>; 3] {
+    fn walk(nodes: &[StructNode], path: &mut Vec<usize>, out: &mut [Vec<Vec<usize>>; 3]) {
+        for (i, n) in nodes.iter().enumerate() {
+            path.push(i);
+            if n.children.is_empty() {
+                out[n.stage() as usize].push(path.clone());
+            } else {
+                walk(&n.children, path, out);
+            }
+            path.pop();
+        }
+    }
+    let mut out: [Vec<Vec<usize>>; 3] = Default::default();
+    walk(roots, &mut Vec::new(), &mut out);
+    out
+}
+
+/// Path of every node in the tree, depth-first (the order the tree view
+/// lists them in).
+pub fn node_paths(roots: &[StructNode]) -> Vec<Vec<usize>> {
+    fn walk(nodes: &[StructNode], path: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+        for (i, n) in nodes.iter().enumerate() {
+            path.push(i);
+            out.push(path.clone());
+            walk(&n.children, path, out);
+            path.pop();
+        }
+    }
+    let mut out = Vec::new();
+    walk(roots, &mut Vec::new(), &mut out);
+    out
+}
+
+/// Collect the flat title of every node in the tree (depth-first).
+pub fn all_node_titles(roots: &[StructNode]) -> Vec<String> {
+    let mut out = Vec::new();
+    fn walk(nodes: &[StructNode], out: &mut Vec<String>) {
+        for n in nodes {
+            out.push(n.title.clone());
+            walk(&n.children, out);
+        }
+    }
+    walk(roots, &mut out);
+    out
+}
+
+/// First node (depth-first) whose title is [`same_title`] as `title`.
+pub fn node_titled<'a>(roots: &'a [StructNode], title: &str) -> Option<&'a StructNode> {
+    roots.iter().find_map(|n| {
+        if same_title(&n.title, title) { Some(n) } else { node_titled(&n.children, title) }
+    })
+}
+
+/// Mutable [`node_titled`].
+pub fn node_titled_mut<'a>(roots: &'a mut [StructNode], title: &str) -> Option<&'a mut StructNode> {
+    roots.iter_mut().find_map(|n| {
+        if same_title(&n.title, title) { Some(n) } else { node_titled_mut(&mut n.children, title) }
+    })
+}
+
+// ── Milestone ─────────────────────────────────────────────────────────────────
+
+/// A project milestone – a named, describable, completable target for the novel.
+/// Examples: "完成第一章草稿", "10万字初稿", "第一阶段验收".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Milestone {
+    pub name: String,
+    pub description: String,
+    pub completed: bool,
+}
+
+impl Milestone {
+    pub fn new(name: &str) -> Self {
+        Milestone {
+            name: name.to_owned(),
+            description: String::new(),
+            completed: false,
+        }
+    }
+}
+
+// ── Foreshadow ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Foreshadow {
+    pub name: String,
+    pub description: String,
+    /// Chapters where the foreshadow is planted (埋设) or mentioned.
+    pub related_chapters: Vec<String>,
+    /// Chapters that pay it off (回收); see [`crate::foreshadow_check`].
+    #[serde(default)]
+    pub resolve_chapters: Vec<String>,
+    pub resolved: bool,
+    /// Unix seconds; `0` when unknown.
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+impl Foreshadow {
+    pub fn new(name: &str) -> Self {
+        let now = unix_now();
+        Foreshadow {
+            name: name.to_owned(),
+            description: String::new(),
+            related_chapters: vec![],
+            resolve_chapters: vec![],
+            resolved: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Mark as modified now.
+    pub fn touch(&mut self) {
+        self.updated_at = unix_now();
+    }
+
+    /// 已回收 once resolved, otherwise 待回收 once planted in a chapter.
+    pub fn stage(&self) -> ForeshadowStage {
+        if self.resolved {
+            ForeshadowStage::Resolved
+        } else if self.related_chapters.is_empty() {
+            ForeshadowStage::Unplanted
+        } else {
+            ForeshadowStage::Pending
+        }
+    }
+
+    /// Move to `stage` on the board by setting `resolved`.  Only 待回收 ⇄
+    /// 已回收: 未植入 follows from the chapters, not the flag, so a
+    /// foreshadow without chapters cannot go back to 待回收.  Returns whether
+    /// it moved.
+    pub fn set_stage(&mut self, stage: ForeshadowStage) -> bool {
+        match (self.stage(), stage) {
+            (ForeshadowStage::Pending, ForeshadowStage::Resolved) => self.resolved = true,
+            (ForeshadowStage::Resolved, ForeshadowStage::Pending) if !self.related_chapters.is_empty() => {
+                self.resolved = false;
+            }
+            _ => return false,
+        }
+        self.touch();
+        true
+    }
+}
+
+/// Column of a foreshadow on the 伏笔看板.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeshadowStage {
+    Unplanted, // 未植入
+    Pending,   // 待回收
+    Resolved,  // 已回收
+}
+
+impl ForeshadowStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ForeshadowStage::Unplanted => "未植入",
+            ForeshadowStage::Pending   => "待回收",
+            ForeshadowStage::Resolved  => "已回收",
+        }
+    }
+    pub fn all() -> [ForeshadowStage; 3] {
+        [ForeshadowStage::Unplanted, ForeshadowStage::Pending, ForeshadowStage::Resolved]
+    }
+}
+
+/// Indices of the foreshadows in each board column, in
+/// [`ForeshadowStage::all`] order and list order within a column.
+pub fn foreshadow_columns(foreshadows: &[Foreshadow]) -> [Vec<usize>; 3] {
+    let mut out: [Vec<usize>; 3] = Default::default();
+    for (i, fs) in foreshadows.iter().enumerate() {
+        out[fs.stage() as usize].push(i);
+    }
+    out
+}
+
+// ── Foreshadow density index ──────────────────────────────────────────────────
+
+/// Reverse index from struct-node title to the foreshadows whose
+/// `related_chapters` mention it, used for the badges on the struct tree.
+///
+/// Keyed by [`normalize_title`], so nodes sharing a title share one entry and
+/// all show the same badge.  Titles mentioned by no node are not indexed.
+#[derive(Debug, Clone, Default)]
+pub struct ForeshadowIndex {
+    by_title: BTreeMap<String, Vec<usize>>,
+    fingerprint: u64,
+}
+
+impl ForeshadowIndex {
+    pub fn build(roots: &[StructNode], foreshadows: &[Foreshadow]) -> Self {
+        let mut by_title: BTreeMap<String, Vec<usize>> = all_node_titles(roots)
+            .into_iter()
+            .map(|t| (normalize_title(&t), Vec::new()))
+            .collect();
+        for (i, fs) in foreshadows.iter().enumerate() {
+            for chapter in &fs.related_chapters {
+                if let Some(list) = by_title.get_mut(&normalize_title(chapter)) {
+                    // A chapter listed twice by one foreshadow counts once.
+                    if list.last() != Some(&i) {
+                        list.push(i);
+                    }
+                }
+            }
+        }
+        by_title.retain(|_, list| !list.is_empty());
+        ForeshadowIndex { by_title, fingerprint: Self::fingerprint(roots, foreshadows) }
+    }
+
+    /// Rebuild if node titles or foreshadow links changed since the last build.
+    pub fn refresh(&mut self, roots: &[StructNode], foreshadows: &[Foreshadow]) {
+        if self.fingerprint != Self::fingerprint(roots, foreshadows) {
+            *self = Self::build(roots, foreshadows);
+        }
+    }
+
+    /// Indices into the foreshadow list related to `title` (ascending).
+    pub fn get(&self, title: &str) -> &[usize] {
+        self.by_title.get(&normalize_title(title)).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn fingerprint(roots: &[StructNode], foreshadows: &[Foreshadow]) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut h = std::collections::hash_map::DefaultHasher::new();
+        all_node_titles(roots).hash(&mut h);
+        for fs in foreshadows {
+            fs.related_chapters.hash(&mut h);
+        }
+        foreshadows.len().hash(&mut h);
+        h.finish()
+    }
+}
+
+// ── LLM config ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmConfig {
+    pub model_path: String,
+    pub api_url: String,
+    pub temperature: f32,
+    pub max_tokens: u32,
+    pub use_local: bool,
+    /// Optional system prompt sent before the user message (OpenAI / llama.cpp).
+    pub system_prompt: String,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        LlmConfig {
+            model_path: String::new(),
+            api_url: "http://localhost:11434/api/generate".to_owned(),
+            temperature: 0.7,
+            max_tokens: 512,
+            use_local: true,
+            system_prompt: String::new(),
+        }
+    }
+}
+
+// ── App theme ─────────────────────────────────────────────────────────────────
+
+/// UI colour theme preference.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum AppTheme {
+    /// Follow the operating-system dark/light preference (egui default).
+    #[default]
+    Dark,
+    Light,
+}
+
+impl AppTheme {
+    pub fn label(self) -> &'static str {
+        match self {
+            AppTheme::Dark  => "暗色",
+            AppTheme::Light => "亮色",
+        }
+    }
+    pub fn all() -> &'static [AppTheme] {
+        &[AppTheme::Dark, AppTheme::Light]
+    }
+}
+
+// ── Editor pane roles ─────────────────────────────────────────────────────────
+
+/// Which files an editor pane takes when a file is opened without naming a
+/// pane (double-click, Enter, quick-access strip, new files).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum PaneAccept {
+    #[default]
+    Any,
+    /// `.md` / `.markdown` files.
+    Markdown,
+    /// `.json` files.
+    Json,
+}
+
+impl PaneAccept {
+    pub fn label(self) -> &'static str {
+        match self {
+            PaneAccept::Any      => "任意",
+            PaneAccept::Markdown => "Markdown",
+            PaneAccept::Json     => "JSON",
+        }
+    }
+    pub fn all() -> &'static [PaneAccept] {
+        &[PaneAccept::Any, PaneAccept::Markdown, PaneAccept::Json]
+    }
+    pub fn accepts(self, path: &Path) -> bool {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        match self {
+            PaneAccept::Any      => true,
+            PaneAccept::Markdown => matches!(ext, "md" | "markdown"),
+            PaneAccept::Json     => ext == "json",
+        }
+    }
+}
+
+// ── Save on panel switch ──────────────────────────────────────────────────────
+
+/// 切换面板时自动保存: what is written when the user leaves a panel.
+/// Editor panes are saved when leaving 小说编辑, design data when leaving
+/// a design panel, each only if its category is ticked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelSwitchSave {
+    pub enabled: bool,
+    /// Modified editor panes.
+    pub panes: bool,
+    /// `Design/世界对象.json`.
+    pub objects: bool,
+    /// `Design/章节结构.json`.
+    pub structure: bool,
+    /// `Design/伏笔.json` (and `Content/伏笔.md`).
+    pub foreshadows: bool,
+    /// `Design/里程碑.json`.
+    pub milestones: bool,
+}
+
+impl Default for PanelSwitchSave {
+    fn default() -> Self {
+        PanelSwitchSave {
+            enabled: false,
+            panes: true,
+            objects: true,
+            structure: true,
+            foreshadows: true,
+            milestones: true,
+        }
+    }
+}
+
+// ── Save reminders ────────────────────────────────────────────────────────────
+
+/// 保存提醒: a toast once design data or an editor pane has stayed unsaved
+/// for `minutes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveReminder {
+    pub enabled: bool,
+    pub minutes: u32,
+}
+
+impl Default for SaveReminder {
+    fn default() -> Self {
+        SaveReminder { enabled: true, minutes: 30 }
+    }
+}
+
+// ── Automatic 完成 ────────────────────────────────────────────────────────────
+
+/// 达到目标字数自动标记完成: nodes with a 目标字数 and a chapter file are
+/// marked done once the file reaches the target.  With `unmark`, a done
+/// node is un-marked again when cuts take it more than `margin_percent`
+/// below the target, so a count hovering at the target does not flip back
+/// and forth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoDone {
+    pub enabled: bool,
+    pub unmark: bool,
+    pub margin_percent: u32,
+}
+
+impl Default for AutoDone {
+    fn default() -> Self {
+        AutoDone { enabled: false, unmark: false, margin_percent: 10 }
+    }
+}
+
+/// 词频分析: watched crutch words and the n-gram report's filters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WordFrequencySettings {
+    /// Words and phrases always counted (`突然`, `不由得`).
+    pub watch_words: Vec<String>,
+    /// Kept out of the frequent-phrase list: longer stopwords split the text
+    /// like punctuation, single characters may not start or end a phrase.
+    pub stopwords: Vec<String>,
+    /// Length of the frequent-phrase list.
+    pub top_n: usize,
+}
+
+impl Default for WordFrequencySettings {
+    fn default() -> Self {
+        WordFrequencySettings {
+            watch_words: ["突然", "不由得", "忽然", "缓缓", "微微"].map(str::to_owned).to_vec(),
+            stopwords: [
+                "的", "了", "着", "过", "吗", "呢", "吧", "啊", "是", "在", "和", "与", "也", "就", "都",
+                "我们", "你们", "他们", "她们", "自己", "什么", "这个", "那个", "一个", "没有", "不是",
+                "这样", "那样", "怎么", "已经", "因为", "所以", "但是", "如果", "时候", "知道", "可以",
+            ].map(str::to_owned).to_vec(),
+            top_n: 30,
+        }
+    }
+}
+
+/// 项目快照: what a zip snapshot leaves out and the 定期快照.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotSettings {
+    /// Exclusion patterns, one per line (`*.tmp`, `/导出`); see
+    /// `snapshot::pattern_matches`.  `.texttool/recovery` is always left out.
+    pub exclude: Vec<String>,
+    /// Once a day, snapshot the open project into `.texttool/snapshots`.
+    pub daily: bool,
+    /// 定期快照 kept; older ones are deleted.
+    pub keep: usize,
+}
+
+impl Default for SnapshotSettings {
+    fn default() -> Self {
+        SnapshotSettings {
+            exclude: [".git", "target", "*.tmp", "*~"].map(str::to_owned).to_vec(),
+            daily: false,
+            keep: 7,
+        }
+    }
+}
+
+/// 历史版本: compressed copies of Content Markdown files kept on save; see
+/// `history`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    pub enabled: bool,
+    /// Revisions kept per file; `0` keeps any number.
+    pub keep: usize,
+    /// Revisions older than this many days are deleted; `0` keeps any age.
+    pub max_age_days: u32,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        HistorySettings { enabled: false, keep: 50, max_age_days: 90 }
+    }
+}
+
+// ── Markdown rendering settings ───────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarkdownSettings {
+    /// Base font size used when rendering the preview.
+    pub preview_font_size: f32,
+    /// When a Markdown file is opened, default to preview mode.
+    pub default_to_preview: bool,
+    /// Hide `.json` files from the project file tree by default.
+    /// JSON files are internal data files; users primarily write Markdown.
+    #[serde(default = "default_true")]
+    pub hide_json: bool,
+    /// List files matched by `.gitignore` / `.texttoolignore` in the file
+    /// tree, dimmed, instead of hiding them.
+    #[serde(default)]
+    pub show_ignored: bool,
+    /// Number of spaces inserted when Tab is pressed in the Markdown editor.
+    #[serde(default = "default_tab_size")]
+    pub tab_size: u8,
+    /// Automatically extract Markdown headings into the Structure panel when
+    /// a file is saved (Ctrl+S).
+    #[serde(default)]
+    pub auto_extract_structure: bool,
+    /// Font size for the plain-text Markdown editor (independent of preview).
+    #[serde(default = "default_editor_font_size")]
+    pub editor_font_size: f32,
+    /// Line-number gutter beside the editors (costs a layout pass per frame).
+    #[serde(default)]
+    pub show_line_numbers: bool,
+    /// Soft-wrap long lines of Markdown and other text files; off, they run
+    /// on and the pane scrolls horizontally.  Applies in either pane.
+    #[serde(default = "default_true")]
+    pub wrap_markdown: bool,
+    /// The same for JSON files, whose long lines are data rather than prose.
+    #[serde(default)]
+    pub wrap_json: bool,
+    /// Auto-save interval in seconds. 0 = disabled.
+    #[serde(default = "default_auto_save_interval")]
+    pub auto_save_interval_secs: u32,
+    /// Show the "Files" tab in the navigation sidebar.
+    /// Off by default — users primarily navigate via the Chapter tree.
+    /// Can be enabled in Settings.
+    #[serde(default)]
+    pub show_files_tab: bool,
+    /// Deepest structure level offered when adding child nodes.
+    /// Projects that do not outline down to scenes can stop at 节.
+    #[serde(default = "default_deepest_struct_kind")]
+    pub deepest_struct_kind: StructKind,
+    /// Reading speed (字/分钟) for the selection reading-time estimate.
+    #[serde(default = "default_reading_speed")]
+    pub reading_speed: u32,
+    /// Show the multiline note fields of the design panels (摘要, 筹备笔记,
+    /// 描述, 背景) rendered, with a ✏ button to edit.
+    #[serde(default)]
+    pub design_markdown_preview: bool,
+}
+
+fn default_true() -> bool { true }
+fn default_tab_size() -> u8 { 2 }
+fn default_editor_font_size() -> f32 { 13.0 }
+fn default_auto_save_interval() -> u32 { 60 }
+fn default_deepest_struct_kind() -> StructKind { StructKind::Scene }
+fn default_reading_speed() -> u32 { 400 }
+
+impl Default for MarkdownSettings {
+    fn default() -> Self {
+        MarkdownSettings {
+            preview_font_size: 14.0,
+            default_to_preview: false,
+            hide_json: true,
+            show_ignored: false,
+            tab_size: 2,
+            auto_extract_structure: false,
+            editor_font_size: 13.0,
+            show_line_numbers: false,
+            wrap_markdown: true,
+            wrap_json: false,
+            auto_save_interval_secs: 60,
+            show_files_tab: false,
+            deepest_struct_kind: StructKind::Scene,
+            reading_speed: 400,
+            design_markdown_preview: false,
+        }
+    }
+}
+
+impl MarkdownSettings {
+    /// Whether the editor wraps the file at `path`, by its file type.
+    pub fn wrap_for(&self, path: &Path) -> bool {
+        if is_json_path(path) { self.wrap_json } else { self.wrap_markdown }
+    }
+
+    /// The wrap setting of `path`'s file type, for the pane's ↩ toggle.
+    pub fn wrap_for_mut(&mut self, path: &Path) -> &mut bool {
+        if is_json_path(path) { &mut self.wrap_json } else { &mut self.wrap_markdown }
+    }
+}
+
+fn is_json_path(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("json"))
+}
+
+// ── App configuration (persisted to disk) ─────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppConfig {
+    pub llm_config: LlmConfig,
+    /// Flattened so the keys stay where older config files had them.
+    #[serde(flatten)]
+    pub settings: AppSettings,
+    pub last_project: Option<String>,
+    #[serde(default)]
+    pub last_session: LastSession,
+}
+
+/// What the main window showed when the config was last saved, restored on
+/// the next start with 启动时恢复上次会话.  Paths only: unsaved edits are
+/// not kept.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LastSession {
+    pub left_file: Option<String>,
+    pub right_file: Option<String>,
+    /// Key of the active panel: `novel`, `objects`, `structure` or `llm`.
+    pub panel: String,
+    pub left_preview: bool,
+}
+
+// ── App settings (the settings window) ───────────────────────────────────────
+
+/// Everything edited in the settings window, one field per tab group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// Editor and preview options (编辑器 / 预览 tabs).
+    #[serde(rename = "md_settings", default)]
+    pub markdown: MarkdownSettings,
+    /// UI colour theme.
+    #[serde(default)]
+    pub theme: AppTheme,
+    /// CJK font file loaded at startup instead of the built-in one.
+    #[serde(default)]
+    pub cjk_font: Option<String>,
+    /// Whether to automatically load JSON/MD data files when opening a project.
+    #[serde(default)]
+    pub auto_load: bool,
+    /// 行为 tab.
+    #[serde(default)]
+    pub behavior: BehaviorSettings,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            markdown: MarkdownSettings::default(),
+            theme: AppTheme::Dark,
+            cjk_font: None,
+            auto_load: false,
+            behavior: BehaviorSettings::default(),
+        }
+    }
+}
+
+/// Editing behaviour options.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BehaviorSettings {
+    /// Snapshots kept per editor pane for Ctrl+Z.
+    #[serde(default = "default_undo_limit")]
+    pub undo_limit: usize,
+    /// Files the left (main) editor pane takes by default.
+    #[serde(default = "default_left_pane")]
+    pub left_pane: PaneAccept,
+    /// Files the right editor pane takes by default.
+    #[serde(default = "default_right_pane")]
+    pub right_pane: PaneAccept,
+    /// Turn ASCII punctuation typed right after a CJK character into its
+    /// full-width form (`,` → `，`).
+    #[serde(default)]
+    pub fullwidth_punctuation: bool,
+    /// Object timelines warn when an object is absent for more than this
+    /// many consecutive chapters.
+    #[serde(default = "default_timeline_gap_warning")]
+    pub timeline_gap_warning: usize,
+    /// Tokens marking spots where the draft is stuck (`TODO`, `【卡】`);
+    /// highlighted in the editor and listed under 待处理标记.
+    #[serde(default = "default_stuck_markers")]
+    pub stuck_markers: Vec<String>,
+    /// Regenerate `Content/目录.md` after each save of a Content file.
+    #[serde(default)]
+    pub auto_update_index: bool,
+    #[serde(default)]
+    pub panel_switch_save: PanelSwitchSave,
+    /// Save world objects, structure and foreshadows to their Design files
+    /// shortly after each change.
+    #[serde(default = "default_true")]
+    pub design_autosave: bool,
+    /// Reopen the last project, its files and panel on startup.
+    #[serde(default = "default_true")]
+    pub restore_session: bool,
+    #[serde(default)]
+    pub save_reminder: SaveReminder,
+    #[serde(default)]
+    pub auto_done: AutoDone,
+    /// Keep the link forms' 常用项 ranking in the project config across
+    /// sessions, not just for this run.
+    #[serde(default)]
+    pub remember_link_usage: bool,
+    #[serde(default)]
+    pub word_frequency: WordFrequencySettings,
+    #[serde(default)]
+    pub snapshot: SnapshotSettings,
+    #[serde(default)]
+    pub history: HistorySettings,
+    /// 每日目标字数; the 连续达标 streak counts the days reaching it.
+    #[serde(default = "default_daily_goal")]
+    pub daily_goal: usize,
+    /// Show 本次写作小结 when the main window closes.
+    #[serde(default = "default_true")]
+    pub session_summary: bool,
+}
+
+fn default_undo_limit() -> usize { 200 }
+fn default_left_pane() -> PaneAccept { PaneAccept::Markdown }
+fn default_right_pane() -> PaneAccept { PaneAccept::Json }
+fn default_timeline_gap_warning() -> usize { 5 }
+fn default_stuck_markers() -> Vec<String> { vec!["TODO".to_owned(), "【卡】".to_owned()] }
+fn default_daily_goal() -> usize { 1000 }
+
+impl Default for BehaviorSettings {
+    fn default() -> Self {
+        BehaviorSettings {
+            undo_limit: 200,
+            left_pane: default_left_pane(),
+            right_pane: default_right_pane(),
+            fullwidth_punctuation: false,
+            timeline_gap_warning: 5,
+            stuck_markers: default_stuck_markers(),
+            auto_update_index: false,
+            panel_switch_save: PanelSwitchSave::default(),
+            design_autosave: true,
+            restore_session: true,
+            save_reminder: SaveReminder::default(),
+            auto_done: AutoDone::default(),
+            remember_link_usage: false,
+            word_frequency: WordFrequencySettings::default(),
+            snapshot: SnapshotSettings::default(),
+            history: HistorySettings::default(),
+            daily_goal: default_daily_goal(),
+            session_summary: true,
+        }
+    }
+}
+
+// ── Project configuration (persisted per project) ───────────────────────────
+
+/// Per-project settings stored in `Design/项目配置.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    /// Project-relative paths of files pinned to the quick-access strip,
+    /// in display order.
+    #[serde(default)]
+    pub pinned_files: Vec<String>,
+    /// Files most recently opened or saved, newest first.
+    #[serde(default)]
+    pub recent_files: Vec<RecentFile>,
+    /// Where reading stopped in each volume, keyed by volume title.
+    #[serde(default)]
+    pub reading_positions: BTreeMap<String, ReadingPosition>,
+    /// Caret and scroll position of files edited in a pane, most recently
+    /// used first.
+    #[serde(default)]
+    pub editor_positions: Vec<EditorPosition>,
+    /// User-defined object kinds, in the order they are offered.
+    #[serde(default)]
+    pub object_kinds: Vec<CustomKind>,
+    /// 常用关联类型与目标, kept when [`BehaviorSettings::remember_link_usage`]
+    /// is on.
+    #[serde(default, skip_serializing_if = "LinkUsage::is_empty")]
+    pub link_usage: LinkUsage,
+}
+
+/// Length of [`ProjectConfig::recent_files`].
+pub const MAX_RECENT_FILES: usize = 15;
+
+/// One entry of the 最近编辑的文件 list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentFile {
+    /// Project-relative path with `/` separators.
+    pub path: String,
+    /// Unix seconds of the last open or save.
+    pub at: i64,
+}
+
+/// Length of [`ProjectConfig::editor_positions`].
+pub const MAX_EDITOR_POSITIONS: usize = 100;
+
+/// Where the caret and the view of an editor pane were in one file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditorPosition {
+    /// Project-relative path with `/` separators.
+    pub path: String,
+    /// Caret as a char index into the file.
+    pub caret: usize,
+    /// Vertical scroll offset in points.
+    pub scroll: f32,
+}
+
+/// Position of the 阅读 view in one volume.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadingPosition {
+    /// Chapter index; [`title`](Self::title) wins if the chapters moved.
+    pub chapter: usize,
+    pub title: String,
+    /// Scroll fraction within the chapter, `0.0..=1.0`.
+    pub scroll: f32,
+}
+
+impl ProjectConfig {
+    /// Move `rel` to the front of the recent list (adding it if new), keeping
+    /// at most [`MAX_RECENT_FILES`] entries.
+    pub fn note_recent_file(&mut self, rel: &str, now: i64) {
+        self.recent_files.retain(|r| r.path != rel);
+        self.recent_files.insert(0, RecentFile { path: rel.to_owned(), at: now });
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Remember where the caret and view of `rel` are, evicting the least
+    /// recently used entries beyond [`MAX_EDITOR_POSITIONS`].
+    pub fn note_editor_position(&mut self, rel: &str, caret: usize, scroll: f32) {
+        self.editor_positions.retain(|p| p.path != rel);
+        self.editor_positions.insert(0, EditorPosition { path: rel.to_owned(), caret, scroll });
+        self.editor_positions.truncate(MAX_EDITOR_POSITIONS);
+    }
+
+    /// The remembered position of `rel`, now `char_len` chars long.  An entry
+    /// whose caret lies past the end was made for a different version of the
+    /// file (changed outside the app) and is dropped.
+    pub fn editor_position(&mut self, rel: &str, char_len: usize) -> Option<EditorPosition> {
+        let i = self.editor_positions.iter().position(|p| p.path == rel)?;
+        if self.editor_positions[i].caret > char_len {
+            self.editor_positions.remove(i);
+            return None;
+        }
+        Some(self.editor_positions[i].clone())
+    }
+
+    /// The registry entry of `kind`, if it is a registered custom kind.
+    pub fn custom_kind(&self, kind: &ObjectKind) -> Option<&CustomKind> {
+        match kind {
+            ObjectKind::Custom(name) => self.object_kinds.iter().find(|c| &c.name == name),
+            _ => None,
+        }
+    }
+
+    /// Icon of `kind`, from the registry for a custom kind.
+    pub fn kind_icon<'a>(&'a self, kind: &ObjectKind) -> &'a str {
+        self.custom_kind(kind).map_or(kind.icon(), |c| c.icon.as_str())
+    }
+
+    /// Register a custom kind named `name` (trimmed).  Blank names, names of
+    /// built-in kinds and names already registered are refused with a
+    /// message for the user.
+    pub fn add_object_kind(&mut self, name: &str, icon: &str, color: [u8; 3]) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("类型名称不能为空".to_owned());
+        }
+        if ObjectKind::is_builtin_name(name) {
+            return Err(format!("「{name}」是内置类型"));
+        }
+        if self.object_kinds.iter().any(|c| c.name == name) {
+            return Err(format!("已存在类型「{name}」"));
+        }
+        let icon = icon.trim();
+        let icon = if icon.is_empty() { CUSTOM_KIND_ICON } else { icon };
+        self.object_kinds.push(CustomKind { name: name.to_owned(), icon: icon.to_owned(), color });
+        Ok(())
+    }
+}
+
+// ── Project data (persisted per project) ─────────────────────────────────────
+
+/// Name of the file at the project root holding [`ProjectData`].
+pub const PROJECT_DATA_FILE: &str = "project.json";
+
+/// The design data saved automatically, kept together in
+/// [`PROJECT_DATA_FILE`] besides each part's own file under `Design/`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectData {
+    #[serde(default)]
+    pub world_objects: Vec<WorldObject>,
+    #[serde(default)]
+    pub struct_roots: Vec<StructNode>,
+    #[serde(default)]
+    pub foreshadows: Vec<Foreshadow>,
+}
+
+// ── Writing statistics ────────────────────────────────────────────────────────
+
+/// Daily word counts stored in `Design/写作统计.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WritingStats {
+    /// Net words written per day, keyed by `YYYY-MM-DD`.
+    #[serde(default)]
+    pub daily_words: BTreeMap<String, i64>,
+}
+
+impl WritingStats {
+    /// Add `delta` (may be negative) to the count for `date`.
+    pub fn record(&mut self, date: &str, delta: i64) {
+        if delta != 0 {
+            *self.daily_words.entry(date.to_owned()).or_insert(0) += delta;
+        }
+    }
+
+    pub fn words_on(&self, date: &str) -> i64 {
+        self.daily_words.get(date).copied().unwrap_or(0)
+    }
+}
+
+/// Format a day number (days since 1970-01-01) as `YYYY-MM-DD`.
+pub fn format_day(days: i64) -> String {
+    let (y, m, d) = civil_from_days(days);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// Day number of a `YYYY-MM-DD` date, the inverse of [`format_day`].
+/// `None` unless the date exists.
+pub fn parse_day(s: &str) -> Option<i64> {
+    let mut parts = s.trim().splitn(3, '-');
+    let mut next = || parts.next()?.parse::<i64>().ok();
+    let (y, m, d) = (next()?, next()?, next()?);
+    let days = days_from_civil(y, m, d);
+    (civil_from_days(days) == (y, m, d)).then_some(days)
+}
+
+/// Day number (days since 1970-01-01) of a proleptic Gregorian date; the
+/// inverse of [`civil_from_days`].
+pub fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Year, month and day of a day number (days since 1970-01-01), in the
+/// proleptic Gregorian calendar.
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+/// Current time as Unix seconds (UTC).
+pub fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Describe `then` relative to `now` (both Unix seconds): 刚刚, N分钟前,
+/// N小时前, N天前, and the date from 30 days on.  `0` means unknown.
+pub fn format_relative_time(then: i64, now: i64) -> String {
+    if then <= 0 {
+        return "未知".to_owned();
+    }
+    let secs = (now - then).max(0);
+    match secs {
+        0..=59 => "刚刚".to_owned(),
+        60..=3_599 => format!("{}分钟前", secs / 60),
+        3_600..=86_399 => format!("{}小时前", secs / 3_600),
+        86_400..=2_591_999 => format!("{}天前", secs / 86_400),
+        _ => format_day(then.div_euclid(86_400)),
+    }
+}
+
+/// Non-whitespace character count — the word count used throughout the app.
+pub fn count_words(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+/// Length figures for a passage (e.g. the editor selection).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextStats {
+    /// Non-whitespace characters, as [`count_words`].
+    pub chars: usize,
+    /// CJK-aware words: each CJK character counts as one word, each run of
+    /// other letters/digits as one; punctuation is not counted.
+    pub words: usize,
+}
+
+impl TextStats {
+    pub fn of(text: &str) -> Self {
+        let mut words = 0;
+        let mut in_word = false;
+        for c in text.chars() {
+            if is_cjk(c) {
+                words += 1;
+                in_word = false;
+            } else if c.is_alphanumeric() {
+                if !in_word {
+                    words += 1;
+                }
+                in_word = true;
+            } else {
+                in_word = false;
+            }
+        }
+        TextStats { chars: count_words(text), words }
+    }
+
+    /// Estimated reading time in minutes at `words_per_minute`.
+    pub fn reading_minutes(&self, words_per_minute: u32) -> f32 {
+        self.words as f32 / words_per_minute.max(1) as f32
+    }
+}
+
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'     // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}'   // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK Unified Ideographs
+        | '\u{AC00}'..='\u{D7AF}'   // Hangul syllables
+        | '\u{F900}'..='\u{FAFF}'   // CJK Compatibility Ideographs
+        | '\u{20000}'..='\u{2FA1F}' // Extensions B+ and supplement
+    )
+}
+
+/// Human-readable reading time, e.g. `不到 1 分钟`, `约 3 分钟`, `约 1 小时 5 分钟`.
+pub fn format_reading_time(minutes: f32) -> String {
+    if minutes < 1.0 {
+        return "不到 1 分钟".to_owned();
+    }
+    let total = minutes.round() as u64;
+    if total < 60 {
+        format!("约 {total} 分钟")
+    } else {
+        format!("约 {} 小时 {} 分钟", total / 60, total % 60)
+    }
+}
+
+// ── Full-text search result ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub file_path: std::path::PathBuf,
+    pub line_no: usize,
+    pub line: String,
+}
+
+// ── Heading outline ───────────────────────────────────────────────────────────
+
+/// One Markdown heading of a file, as listed in the 大纲 navigation view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineEntry {
+    /// Heading level (1 = `#`, 2 = `##`, …).
+    pub level: usize,
+    pub title: String,
+    /// 1-based line number of the heading in the file.
+    pub line_no: usize,
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_titles() {
+        let mut vol1 = StructNode::new("第一卷", StructKind::Volume);
+        vol1.children = vec![StructNode::new("第一章", StructKind::Chapter), StructNode::new("第二章", StructKind::Chapter)];
+        let mut vol2 = StructNode::new("第二卷", StructKind::Volume);
+        vol2.children = vec![StructNode::new(" 第一章 ", StructKind::Chapter), StructNode::new("第三章", StructKind::Chapter)];
+        let mut roots = vec![vol1, vol2];
+
+        let dups = duplicate_titles(&roots);
+        assert_eq!(dups, vec![DuplicateTitle { title: "第一章".into(), paths: vec![vec![0, 0], vec![1, 0]] }]);
+
+        // Ambiguous links prefer the same parent subtree, else the first.
+        assert_eq!(node_path_titled_near(&roots, "第一章", &[1, 1]), Some(vec![1, 0]));
+        assert_eq!(node_path_titled_near(&roots, "第一章", &[0, 1]), Some(vec![0, 0]));
+        assert_eq!(node_path_titled_near(&roots, "第一章", &[]), Some(vec![0, 0]));
+        assert_eq!(node_titled_near(&roots, "第三章", &[0]).map(|n| n.title.as_str()), Some("第三章"));
+        assert_eq!(node_path_titled_near(&roots, "第九章", &[0]), None);
+
+        assert_eq!(unique_title_for(&roots, &[1, 0]).as_deref(), Some("第一章（第二卷）"));
+        assert_eq!(unique_title_for(&roots, &[1]), None);
+        roots[0].children.push(StructNode::new("第一章（第二卷）", StructKind::Chapter));
+        assert_eq!(unique_title_for(&roots, &[1, 0]).as_deref(), Some("第一章（第二卷） 2"));
+    }
+
+    #[test]
+    fn test_normalize_title() {
+        assert_eq!(normalize_title("第一章 "), "第一章");
+        assert_eq!(normalize_title("林　 枫"), "林 枫");
+        assert_eq!(normalize_title("\t第一章  雨夜\n"), "第一章 雨夜");
+        assert_eq!(normalize_title("　 "), "");
+        assert!(same_title("第一章 ", "第一章"));
+        assert!(same_title("林　枫", " 林 枫"));
+        assert!(!same_title("林枫", "林 枫"));
+        assert!(!same_title("第一章", "第二章"));
+
+        // Old data with stray spaces still resolves its foreshadows.
+        let roots = vec![StructNode::new("第一章 ", StructKind::Chapter)];
+        let mut letter = Foreshadow::new("神秘信件");
+        letter.related_chapters = vec!["第一章　".into()];
+        assert_eq!(ForeshadowIndex::build(&roots, &[letter]).get("第一章"), &[0]);
+    }
+
+    #[test]
+    fn test_normalize_all_names() {
+        let mut lin = WorldObject::new(" 林　枫", ObjectKind::Character);
+        lin.links.push(ObjectLink {
+            target: LinkTarget::Node("第一章 ".into()),
+            kind: RelationKind::AppearsIn,
+            note: String::new(),
+        });
+        let mut objects = vec![lin, WorldObject::new("苏婉", ObjectKind::Character)];
+        let mut ch1 = StructNode::new("第一章 ", StructKind::Chapter);
+        ch1.linked_objects = vec!["林 枫 ".into(), "苏婉".into()];
+        let mut ch2 = StructNode::new("第二章", StructKind::Chapter);
+        ch2.node_links.push(NodeLink {
+            target_title: "第一章  ".into(),
+            kind: RelationKind::Foreshadows,
+            note: String::new(),
+        });
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        vol.children = vec![ch1, ch2];
+        let mut roots = vec![vol];
+        let mut letter = Foreshadow::new("神秘信件");
+        letter.related_chapters = vec!["第一章　".into(), "第二章".into()];
+        let mut foreshadows = vec![letter];
+
+        let changes = normalize_all_names(&mut objects, &mut roots, &mut foreshadows);
+        let summary: Vec<_> = changes.iter()
+            .map(|c| (c.place, c.before.as_str(), c.after.as_str()))
+            .collect();
+        assert_eq!(summary, [
+            ("对象", " 林　枫", "林 枫"),
+            ("对象关系", "第一章 ", "第一章"),
+            ("章节", "第一章 ", "第一章"),
+            ("关联对象", "林 枫 ", "林 枫"),
+            ("跨节点关联", "第一章  ", "第一章"),
+            ("伏笔关联章节", "第一章　", "第一章"),
+        ]);
+        assert_eq!(objects[0].name, "林 枫");
+        assert_eq!(objects[0].links[0].target, LinkTarget::Node("第一章".into()));
+        let chapters = &roots[0].children;
+        assert_eq!(chapters[0].title, "第一章");
+        assert_eq!(chapters[0].linked_objects, ["林 枫", "苏婉"]);
+        assert_eq!(chapters[1].node_links[0].target_title, "第一章");
+        assert_eq!(foreshadows[0].related_chapters, ["第一章", "第二章"]);
+
+        // A second run finds nothing left to do.
+        assert!(normalize_all_names(&mut objects, &mut roots, &mut foreshadows).is_empty());
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        let now = 1_700_000_000;
+        assert_eq!(format_relative_time(0, now), "未知");
+        assert_eq!(format_relative_time(now - 30, now), "刚刚");
+        assert_eq!(format_relative_time(now + 5, now), "刚刚");
+        assert_eq!(format_relative_time(now - 5 * 60, now), "5分钟前");
+        assert_eq!(format_relative_time(now - 3 * 3600, now), "3小时前");
+        assert_eq!(format_relative_time(now - 3 * 86_400, now), "3天前");
+        // 30+ days: the calendar date (2023-10-15 is 30 days before 2023-11-14).
+        assert_eq!(format_relative_time(now - 30 * 86_400, now), "2023-10-15");
+    }
+
+    #[test]
+    fn test_node_position() {
+        let mut vol2 = StructNode::new("第二卷", StructKind::Volume);
+        let mut ch = StructNode::new("重逢", StructKind::Chapter);
+        ch.children = vec![
+            StructNode::new("一", StructKind::Section),
+            StructNode::new("插曲", StructKind::Scene),
+            StructNode::new("二", StructKind::Section),
+        ];
+        // A section between chapters is not counted as a chapter.
+        vol2.children = vec![
+            StructNode::new("第一章", StructKind::Chapter),
+            StructNode::new("卷首语", StructKind::Section),
+            ch,
+        ];
+        let roots = vec![
+            StructNode::new("总纲", StructKind::Outline),
+            StructNode::new("第一卷", StructKind::Volume),
+            vol2,
+        ];
+        assert_eq!(node_position(&roots, &[2, 2, 2]).as_deref(), Some("第2卷 · 第2章 · 第2节"));
+        assert_eq!(node_position(&roots, &[2, 2, 1]).as_deref(), Some("第2卷 · 第2章 · 第1场"));
+        assert_eq!(node_position(&roots, &[2, 1]).as_deref(), Some("第2卷 · 第1节"));
+        assert_eq!(node_position(&roots, &[0]).as_deref(), Some("总纲"));
+        assert_eq!(
+            node_ordinals(&roots, &[1]),
+            Some(vec![(StructKind::Volume, 1)]),
+        );
+        assert_eq!(node_position(&roots, &[]), None);
+        assert_eq!(node_position(&roots, &[2, 9]), None);
+    }
+
+    #[test]
+    fn test_kanban_columns() {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        let mut c1 = StructNode::new("第一章", StructKind::Chapter);
+        c1.set_stage(NodeStage::Done);
+        let mut c2 = StructNode::new("第二章", StructKind::Chapter);
+        c2.set_stage(NodeStage::Drafting);
+        vol.children = vec![c1, c2, StructNode::new("第三章", StructKind::Chapter)];
+        let mut roots = vec![vol, StructNode::new("第四章", StructKind::Chapter)];
+
+        let [todo, drafting, done] = kanban_columns(&roots);
+        assert_eq!(todo, vec![vec![0, 2], vec![1]]);
+        assert_eq!(drafting, vec![vec![0, 1]]);
+        assert_eq!(done, vec![vec![0, 0]]);
+
+        // Dropping 第四章 on 已完成 moves it there.
+        node_at_mut(&mut roots, &[1]).unwrap().set_stage(NodeStage::Done);
+        let [todo, _, done] = kanban_columns(&roots);
+        assert_eq!(todo, vec![vec![0, 2]]);
+        assert_eq!(done, vec![vec![0, 0], vec![1]]);
+        assert!(roots[1].done);
+    }
+
+    #[test]
+    fn test_hierarchy_issues() {
+        let mut outline = StructNode::new("总纲", StructKind::Outline);
+        outline.children.push(StructNode::new("第一章", StructKind::Chapter));
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        vol.children.push(StructNode::new("楔子", StructKind::Outline));
+        let mut sec = StructNode::new("第一节", StructKind::Section);
+        let mut scene = StructNode::new("雨夜", StructKind::Scene);
+        scene.children.push(StructNode::new("回忆", StructKind::Scene));
+        sec.children.push(scene);
+        let roots = vec![outline, vol, sec];
+
+        let issues = hierarchy_issues(&roots);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].path, vec![0, 0]);
+        assert!(issues[0].describe().contains("跳级"));
+        assert_eq!(issues[1].path, vec![1, 0]);
+        assert!(issues[1].describe().contains("层级倒置"));
+    }
+
+    #[test]
+    fn test_move_item() {
+        let mut v = vec!["a", "b", "c"];
+        assert_eq!(move_item(&mut v, 2, true), Some(1));
+        assert_eq!(v, ["a", "c", "b"]);
+        assert_eq!(move_item(&mut v, 0, false), Some(1));
+        assert_eq!(v, ["c", "a", "b"]);
+        // Already at the ends, or out of range: unchanged.
+        assert_eq!(move_item(&mut v, 0, true), None);
+        assert_eq!(move_item(&mut v, 2, false), None);
+        assert_eq!(move_item(&mut v, 5, true), None);
+        assert_eq!(v, ["c", "a", "b"]);
+
+        // The new order survives a save/load round trip.
+        let mut obj = WorldObject::new("林枫", ObjectKind::Character);
+        for name in ["甲", "乙"] {
+            obj.links.push(ObjectLink {
+                target: LinkTarget::Object(name.into()),
+                kind: RelationKind::Friend,
+                note: String::new(),
+            });
+        }
+        move_item(&mut obj.links, 1, true);
+        let back: WorldObject = serde_json::from_str(&serde_json::to_string(&obj).unwrap()).unwrap();
+        let names: Vec<&str> = back.links.iter().map(|l| l.target.display_name()).collect();
+        assert_eq!(names, ["乙", "甲"]);
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeSet;
+
+use crate::models::{node_at_mut, same_title, NodeStage, StructNode};
+
+// ── 拆分节点 / 与下一个兄弟合并 ───────────────────────────────────────────────
+//
+// Both work on a node and its following sibling and leave every other node
+// where it was, so paths outside that parent stay valid.  The chapter file
+// of a split node is cut by `split::split_at_line`.
+
+/// The list holding the node at `path`: its parent's children, or `roots`.
+fn siblings_mut<'a>(roots: &'a mut Vec<StructNode>, path: &[usize]) -> Option<&'a mut Vec<StructNode>> {
+    match path.split_last()? {
+        (_, []) => Some(roots),
+        (_, parent) => node_at_mut(roots, parent).map(|p| &mut p.children),
+    }
+}
+
+/// Insert a sibling titled `title` right after the node at `path`, of the
+/// same kind, and move the children at the indices in `moved` into it (in
+/// their order).  Returns the new node's path.
+pub fn split_node(roots: &mut Vec<StructNode>, path: &[usize], title: &str, moved: &BTreeSet<usize>) -> Option<Vec<usize>> {
+    let (&idx, _) = path.split_last()?;
+    let siblings = siblings_mut(roots, path)?;
+    let node = siblings.get_mut(idx)?;
+    let mut second = StructNode::new(title, node.kind.clone());
+    let children = std::mem::take(&mut node.children);
+    for (i, child) in children.into_iter().enumerate() {
+        if moved.contains(&i) {
+            second.children.push(child);
+        } else {
+            node.children.push(child);
+        }
+    }
+    node.touch();
+    siblings.insert(idx + 1, second);
+    let mut new_path = path.to_vec();
+    *new_path.last_mut()? = idx + 1;
+    Some(new_path)
+}
+
+/// Title of two merged nodes.
+pub fn merged_title(first: &str, second: &str, keep_first: bool) -> String {
+    if keep_first {
+        first.to_owned()
+    } else {
+        format!("{first} + {second}")
+    }
+}
+
+/// Merge the node after `path` into the node at `path` and remove it:
+/// children follow the first node's, linked objects and cross-links are
+/// added unless already there, summaries and notes are appended.  The
+/// merged node is done only if both were, and keeps the earlier stage;
+/// target words add up.  `false` when there is no next sibling.
+pub fn merge_with_next(roots: &mut Vec<StructNode>, path: &[usize], keep_first: bool) -> bool {
+    let Some((&idx, _)) = path.split_last() else { return false };
+    let Some(siblings) = siblings_mut(roots, path) else { return false };
+    if idx + 1 >= siblings.len() {
+        return false;
+    }
+    let second = siblings.remove(idx + 1);
+    let first = &mut siblings[idx];
+    let (first_title, second_title) = (first.title.clone(), second.title.clone());
+    let second_stage = second.stage();
+
+    first.title = merged_title(&first_title, &second_title, keep_first);
+    first.children.extend(second.children);
+    for name in second.linked_objects {
+        if !first.linked_objects.iter().any(|n| same_title(n, &name)) {
+            first.linked_objects.push(name);
+        }
+    }
+    for link in second.node_links {
+        let duplicate = first.node_links.iter()
+            .any(|l| same_title(&l.target_title, &link.target_title) && l.kind == link.kind);
+        if !duplicate {
+            first.node_links.push(link);
+        }
+    }
+    // Links between the two would now point at the node itself.
+    first.node_links.retain(|l| !same_title(&l.target_title, &first_title) && !same_title(&l.target_title, &second_title));
+    for (text, more) in [(&mut first.summary, second.summary), (&mut first.notes, second.notes)] {
+        let more = more.trim();
+        if !more.is_empty() {
+            if !text.trim().is_empty() {
+                text.push_str("\n\n");
+            }
+            text.push_str(more);
+        }
+    }
+    let rank = |s: NodeStage| NodeStage::all().iter().position(|&x| x == s);
+    let stage = [first.stage(), second_stage].into_iter().min_by_key(|&s| rank(s)).unwrap_or(NodeStage::NotStarted);
+    first.done = stage == NodeStage::Done;
+    first.stage = Some(stage);
+    first.target_words = match (first.target_words, second.target_words) {
+        (None, None) => None,
+        (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+    };
+    first.due_date = first.due_date.take().or(second.due_date);
+    first.touch();
+    true
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{NodeLink, RelationKind, StructKind};
+
+    fn chapter(title: &str, scenes: &[&str]) -> StructNode {
+        let mut node = StructNode::new(title, StructKind::Chapter);
+        node.children = scenes.iter().map(|s| StructNode::new(s, StructKind::Scene)).collect();
+        node
+    }
+
+    fn titles(nodes: &[StructNode]) -> Vec<&str> {
+        nodes.iter().map(|n| n.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_split_node_moves_chosen_children() {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        vol.children = vec![chapter("第一章", &["甲", "乙", "丙", "丁"]), chapter("第二章", &[])];
+        let mut roots = vec![vol];
+
+        let new_path = split_node(&mut roots, &[0, 0], "第一章（下）", &BTreeSet::from([1, 3])).unwrap();
+        assert_eq!(new_path, [0, 1]);
+        let chapters = &roots[0].children;
+        assert_eq!(titles(chapters), ["第一章", "第一章（下）", "第二章"]);
+        assert_eq!(titles(&chapters[0].children), ["甲", "丙"]);
+        assert_eq!(titles(&chapters[1].children), ["乙", "丁"]);
+        assert_eq!(chapters[1].kind, StructKind::Chapter);
+
+        // Roots split too; a bad path changes nothing.
+        assert_eq!(split_node(&mut roots, &[0], "第二卷", &BTreeSet::new()), Some(vec![1]));
+        assert_eq!(titles(&roots), ["第一卷", "第二卷"]);
+        assert_eq!(split_node(&mut roots, &[5], "x", &BTreeSet::new()), None);
+        assert_eq!(split_node(&mut roots, &[], "x", &BTreeSet::new()), None);
+    }
+
+    #[test]
+    fn test_merge_with_next() {
+        let mut a = chapter("第三章", &["甲"]);
+        a.summary = "林远下山。".to_owned();
+        a.linked_objects = vec!["林远".to_owned()];
+        a.node_links = vec![NodeLink { target_title: "第四章".to_owned(), kind: RelationKind::Other, note: String::new() }];
+        a.set_stage(NodeStage::Done);
+        a.target_words = Some(3000);
+        let mut b = chapter("第四章", &["乙"]);
+        b.summary = "遇见苏晚。".to_owned();
+        b.linked_objects = vec![" 林远 ".to_owned(), "苏晚".to_owned()];
+        b.node_links = vec![NodeLink { target_title: "第一章".to_owned(), kind: RelationKind::Friend, note: String::new() }];
+        b.set_stage(NodeStage::Drafting);
+        let mut roots = vec![a, b, chapter("第五章", &[])];
+
+        assert!(merge_with_next(&mut roots, &[0], false));
+        assert_eq!(titles(&roots), ["第三章 + 第四章", "第五章"]);
+        let merged = &roots[0];
+        assert_eq!(titles(&merged.children), ["甲", "乙"]);
+        assert_eq!(merged.linked_objects, ["林远", "苏晚"]);
+        assert_eq!(merged.node_links.len(), 1);
+        assert_eq!(merged.node_links[0].target_title, "第一章");
+        assert_eq!(merged.summary, "林远下山。\n\n遇见苏晚。");
+        assert_eq!(merged.stage(), NodeStage::Drafting);
+        assert!(!merged.done);
+        assert_eq!(merged.target_words, Some(3000));
+
+        assert!(merge_with_next(&mut roots, &[0], true));
+        assert_eq!(titles(&roots), ["第三章 + 第四章"]);
+        // Nothing after the last node.
+        assert!(!merge_with_next(&mut roots, &[0], true));
+        assert!(!merge_with_next(&mut roots, &[], true));
+    }
+}
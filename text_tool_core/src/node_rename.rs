@@ -0,0 +1,254 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::links::{encode_link_path, find_links, resolve_link};
+use crate::models::{same_title, Foreshadow};
+use crate::split::sanitize_file_name;
+
+// ── 节点改名: carry a new node title over to its file and references ─────────
+//
+// Renaming a node whose chapter file is named after its title leaves the
+// file, the Markdown links to it and the foreshadows' chapter lists behind.
+// [`plan_node_rename`] lists what would follow the title; the app shows the
+// plan as a checklist and applies the chosen items.
+
+/// Rename of the node's chapter file; paths relative to `Content/`, `/`-separated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRename {
+    pub from: String,
+    pub to: String,
+    /// Another file already has the new name; the file cannot follow.
+    pub taken: bool,
+}
+
+/// A Markdown link to the chapter file, pointed at the renamed file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkRewrite {
+    /// The file containing the link.
+    pub file: PathBuf,
+    /// 1-based line number.
+    pub line_no: usize,
+    /// Byte range of the link target in the file's text.
+    pub range: Range<usize>,
+    pub old_target: String,
+    pub new_target: String,
+}
+
+/// A foreshadow listing the node under its old title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChapterRef {
+    /// Index into the foreshadow list.
+    pub index: usize,
+    pub name: String,
+}
+
+/// Everything that follows a node from `old_title` to `new_title`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRenamePlan {
+    pub old_title: String,
+    pub new_title: String,
+    pub file: Option<FileRename>,
+    pub links: Vec<LinkRewrite>,
+    pub foreshadows: Vec<ChapterRef>,
+}
+
+impl NodeRenamePlan {
+    pub fn is_empty(&self) -> bool {
+        self.file.is_none() && self.links.is_empty() && self.foreshadows.is_empty()
+    }
+}
+
+/// Whether the file `rel` is named after `title`: its stem is the title
+/// made file-safe (see [`sanitize_file_name`]), ignoring case.
+pub fn file_named_after(rel: &str, title: &str) -> bool {
+    let base = rel.rsplit('/').next().unwrap_or(rel);
+    let stem = base.strip_suffix(".md").unwrap_or(base);
+    stem.trim().to_lowercase() == sanitize_file_name(title).to_lowercase()
+}
+
+/// `target` with the file name of its path replaced by `file_name`,
+/// keeping the folders and any `#anchor` or `?query` as written.
+fn retarget(target: &str, file_name: &str) -> String {
+    let split = target.find(['#', '?']).unwrap_or(target.len());
+    let (path, suffix) = target.split_at(split);
+    let dir = path.rfind('/').map_or("", |i| &path[..=i]);
+    format!("{dir}{}{suffix}", encode_link_path(file_name))
+}
+
+/// What renaming a node from `old_title` to `new_title` touches.
+///
+/// `chapter_file` is the node's chapter file relative to `content_dir`; it
+/// follows only when named after the old title but not the new one, and
+/// not onto a name `taken` reports as existing.  The local links in `docs`
+/// (`(path, text)`) that resolve to the file are then repointed.
+/// Foreshadows listing the old title among their chapters are always
+/// included.
+pub fn plan_node_rename(
+    old_title: &str,
+    new_title: &str,
+    chapter_file: Option<&str>,
+    content_dir: &Path,
+    taken: impl Fn(&str) -> bool,
+    docs: &[(&Path, &str)],
+    foreshadows: &[Foreshadow],
+) -> NodeRenamePlan {
+    let file = chapter_file
+        .filter(|rel| file_named_after(rel, old_title) && !file_named_after(rel, new_title))
+        .map(|from| {
+            let name = format!("{}.md", sanitize_file_name(new_title));
+            let to = match from.rsplit_once('/') {
+                Some((dir, _)) => format!("{dir}/{name}"),
+                None => name,
+            };
+            FileRename { from: from.to_owned(), taken: taken(&to), to }
+        });
+
+    let mut links = Vec::new();
+    if let Some(rename) = file.as_ref().filter(|f| !f.taken) {
+        let old_path = rename.from.split('/').fold(content_dir.to_path_buf(), |p, part| p.join(part));
+        let new_name = rename.to.rsplit('/').next().unwrap_or(&rename.to);
+        for (path, text) in docs {
+            for link in find_links(text) {
+                let Some((target, _)) = resolve_link(path, &link.target) else { continue };
+                if target != old_path || link.target.starts_with(['#', '?']) {
+                    continue;
+                }
+                links.push(LinkRewrite {
+                    file: path.to_path_buf(),
+                    line_no: link.line_no,
+                    new_target: retarget(&link.target, new_name),
+                    old_target: link.target,
+                    range: link.range,
+                });
+            }
+        }
+    }
+
+    let foreshadows = foreshadows.iter().enumerate()
+        .filter(|(_, fs)| fs.related_chapters.iter().chain(&fs.resolve_chapters).any(|c| same_title(c, old_title)))
+        .map(|(index, fs)| ChapterRef { index, name: fs.name.clone() })
+        .collect();
+
+    NodeRenamePlan {
+        old_title: old_title.to_owned(),
+        new_title: new_title.to_owned(),
+        file,
+        links,
+        foreshadows,
+    }
+}
+
+/// `text` with the `rewrites` (all of one file) applied, or `None` if the
+/// text no longer has the old targets at their ranges.
+pub fn apply_link_rewrites(text: &str, rewrites: &[&LinkRewrite]) -> Option<String> {
+    let mut sorted: Vec<&&LinkRewrite> = rewrites.iter().collect();
+    sorted.sort_by_key(|r| std::cmp::Reverse(r.range.start));
+    let mut out = text.to_owned();
+    for r in sorted {
+        if out.get(r.range.clone()) != Some(r.old_target.as_str()) {
+            return None;
+        }
+        out.replace_range(r.range.clone(), &r.new_target);
+    }
+    Some(out)
+}
+
+/// Replace `old_title` by `new_title` in the foreshadow's chapter lists;
+/// returns how many entries changed.
+pub fn retitle_chapter_refs(foreshadow: &mut Foreshadow, old_title: &str, new_title: &str) -> usize {
+    let mut n = 0;
+    for chapter in foreshadow.related_chapters.iter_mut().chain(&mut foreshadow.resolve_chapters) {
+        if same_title(chapter, old_title) {
+            *chapter = new_title.to_owned();
+            n += 1;
+        }
+    }
+    n
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn foreshadow(name: &str, related: &[&str], resolve: &[&str]) -> Foreshadow {
+        let mut fs = Foreshadow::new(name);
+        fs.related_chapters = related.iter().map(|c| c.to_string()).collect();
+        fs.resolve_chapters = resolve.iter().map(|c| c.to_string()).collect();
+        fs
+    }
+
+    #[test]
+    fn test_plan_node_rename() {
+        let content = Path::new("/p/Content");
+        let other = content.join("第一卷/第二章.md");
+        let notes = content.join("笔记.md");
+        let docs = [
+            (other.as_path(), "见[上一章](第一章%20雨夜.md#结尾)，[本章](#开头)\n"),
+            (notes.as_path(), "[a](第一卷/第一章%20雨夜.md) [b](第一卷/别的.md) [c](https://x.com/第一章%20雨夜.md)\n"),
+        ];
+        let foreshadows = [
+            foreshadow("玉佩", &["第一章 雨夜"], &["第九章"]),
+            foreshadow("旧信", &["第三章"], &[" 第一章  雨夜 "]),
+            foreshadow("断剑", &["第二章"], &[]),
+        ];
+        let plan = plan_node_rename(
+            "第一章 雨夜", "第一章 夜雨", Some("第一卷/第一章 雨夜.md"), content, |_| false, &docs, &foreshadows,
+        );
+        assert_eq!(plan.file, Some(FileRename {
+            from: "第一卷/第一章 雨夜.md".into(),
+            to: "第一卷/第一章 夜雨.md".into(),
+            taken: false,
+        }));
+        let targets: Vec<(&str, &str)> = plan.links.iter()
+            .map(|l| (l.old_target.as_str(), l.new_target.as_str()))
+            .collect();
+        assert_eq!(targets, [
+            ("第一章%20雨夜.md#结尾", "第一章%20夜雨.md#结尾"),
+            ("第一卷/第一章%20雨夜.md", "第一卷/第一章%20夜雨.md"),
+        ]);
+        assert_eq!(plan.links[1].file, notes);
+        let names: Vec<&str> = plan.foreshadows.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["玉佩", "旧信"]);
+
+        // The new name is taken: the file and its links stay, foreshadows still follow.
+        let taken = plan_node_rename(
+            "第一章 雨夜", "第一章 夜雨", Some("第一卷/第一章 雨夜.md"), content,
+            |rel| rel == "第一卷/第一章 夜雨.md", &docs, &foreshadows,
+        );
+        assert!(taken.file.as_ref().unwrap().taken);
+        assert!(taken.links.is_empty());
+        assert_eq!(taken.foreshadows.len(), 2);
+
+        // A file not named after the title stays, and so does one whose
+        // name already fits the new title (a change of case).
+        let unrelated = plan_node_rename("第三章", "第三章 终局", Some("003.md"), content, |_| true, &[], &[]);
+        assert!(unrelated.is_empty());
+        let case = plan_node_rename("Prologue", "PROLOGUE", Some("prologue.md"), content, |_| true, &[], &[]);
+        assert!(case.is_empty());
+        let case = plan_node_rename("Prologue", "Prologue 2", Some("Prologue.md"), content, |_| false, &[], &[]);
+        assert_eq!(case.file.unwrap().to, "Prologue 2.md");
+    }
+
+    #[test]
+    fn test_apply_rename_edits() {
+        let content = Path::new("/p/Content");
+        let file = content.join("目录.md");
+        let text = "- [雨夜](雨夜.md)\n- [雨夜结尾](雨夜.md#结尾)\n";
+        let docs = [(file.as_path(), text)];
+        let plan = plan_node_rename("雨夜", "夜雨", Some("雨夜.md"), content, |_| false, &docs, &[]);
+        let rewrites: Vec<&LinkRewrite> = plan.links.iter().collect();
+        assert_eq!(
+            apply_link_rewrites(text, &rewrites).as_deref(),
+            Some("- [雨夜](夜雨.md)\n- [雨夜结尾](夜雨.md#结尾)\n"),
+        );
+        // Edited since the plan was made.
+        assert_eq!(apply_link_rewrites("- [雨夜](其他.md)\n", &rewrites), None);
+
+        let mut fs = foreshadow("玉佩", &["雨夜", "第二章"], &["雨夜"]);
+        assert_eq!(retitle_chapter_refs(&mut fs, "雨夜", "夜雨"), 2);
+        assert_eq!(fs.related_chapters, ["夜雨", "第二章"]);
+        assert_eq!(fs.resolve_chapters, ["夜雨"]);
+    }
+}
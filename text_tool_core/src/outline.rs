@@ -0,0 +1,605 @@
+use std::path::Path;
+
+use crate::front_matter::parse_front_matter;
+use crate::ignore::IgnoreRules;
+use crate::models::{OutlineEntry, StructKind, StructNode};
+
+// ── Free functions: Markdown → StructNode extraction ─────────────────────────
+
+/// Parse ATX headings from Markdown text into a `StructNode` tree.
+///
+/// Level mapping:
+///   `#` → Outline,  `##` → Volume,  `###` → Chapter,  `####` → Section,
+///   `#####`+ → Scene
+///
+/// A leading front-matter block is skipped (its `#` comments are not headings).
+pub fn extract_struct_nodes_from_markdown(content: &str) -> Vec<StructNode> {
+    let mut flat: Vec<(usize, String)> = Vec::new();
+    for line in content.lines().skip(front_matter_lines(content)) {
+        // Count leading '#' chars using bytes — '#' is ASCII so this is both
+        // correct and faster than iterating over Unicode code points.
+        let level = line.bytes().take_while(|&b| b == b'#').count();
+        if level == 0 || level > 6 {
+            continue;
+        }
+        let rest = &line[level..]; // safe: '#' is ASCII (1 byte each)
+        // Standard ATX heading: at least one space (or empty body) after '#' run.
+        if !rest.starts_with(' ') && !rest.is_empty() {
+            continue;
+        }
+        let title = rest.trim().to_owned();
+        if !title.is_empty() {
+            flat.push((level, title));
+        }
+    }
+    if flat.is_empty() {
+        return vec![];
+    }
+    nest_struct_nodes(&flat, 0, flat[0].0)
+}
+
+/// List the ATX headings of `content` with their 1-based line numbers.
+/// Headings inside fenced code blocks and a leading front-matter block are
+/// skipped.
+pub fn parse_outline_entries(content: &str) -> Vec<OutlineEntry> {
+    let mut out = Vec::new();
+    let mut in_code_block = false;
+    for (i, line) in content.lines().enumerate().skip(front_matter_lines(content)) {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        let level = line.bytes().take_while(|&b| b == b'#').count();
+        if level == 0 || level > 6 {
+            continue;
+        }
+        let rest = &line[level..];
+        if !rest.starts_with(' ') {
+            continue;
+        }
+        let title = rest.trim();
+        if !title.is_empty() {
+            out.push(OutlineEntry { level, title: title.to_owned(), line_no: i + 1 });
+        }
+    }
+    out
+}
+
+/// The heading section around a selection of character offsets
+/// `from..to`: the 1-based lines `start..end` from the deepest heading whose
+/// section (up to the next heading of the same or a higher level) holds the
+/// whole selection.  A selection ending at the start of a line does not
+/// reach into that line.  `None` before the first heading.
+pub fn heading_subtree(content: &str, from: usize, to: usize) -> Option<(usize, usize)> {
+    let (from, to) = (from.min(to), from.max(to));
+    let line_of = |offset: usize| content.chars().take(offset).filter(|&c| c == '\n').count() + 1;
+    let first = line_of(from);
+    let last = if to > from && content.chars().nth(to - 1) == Some('\n') { line_of(to - 1) } else { line_of(to) };
+    let end_of_doc = content.lines().count() + 1;
+    let entries = parse_outline_entries(content);
+    entries.iter().enumerate()
+        .filter(|(_, e)| e.line_no <= first)
+        .map(|(i, e)| {
+            let end = entries[i + 1..].iter().find(|n| n.level <= e.level).map_or(end_of_doc, |n| n.line_no);
+            (e.line_no, end)
+        })
+        .rfind(|&(_, end)| end > last)
+}
+
+/// The structure of the heading section around `from..to` (see
+/// [`heading_subtree`]): its heading as the root node, with the headings
+/// below it as children.
+pub fn extract_subtree_node(content: &str, from: usize, to: usize) -> Option<StructNode> {
+    let (start, end) = heading_subtree(content, from, to)?;
+    let section: Vec<&str> = content.lines().skip(start - 1).take(end - start).collect();
+    extract_struct_nodes_from_markdown(&section.join("\n")).into_iter().next()
+}
+
+/// Lines taken by the front matter of `content`, 0 without one.
+fn front_matter_lines(content: &str) -> usize {
+    parse_front_matter(content).map_or(0, |fm| fm.lines)
+}
+
+/// Recursively nest the flat (level, title) list into `StructNode`s.
+fn nest_struct_nodes(flat: &[(usize, String)], start: usize, min_level: usize) -> Vec<StructNode> {
+    use StructKind::{Outline, Volume, Chapter, Section, Scene};
+    let mut result = Vec::new();
+    let mut i = start;
+    while i < flat.len() {
+        let (lvl, title) = &flat[i];
+        if *lvl < min_level {
+            break;
+        }
+        if *lvl == min_level {
+            let kind = match lvl {
+                1 => Outline,
+                2 => Volume,
+                3 => Chapter,
+                4 => Section,
+                _ => Scene,
+            };
+            let mut node = StructNode::new(title, kind);
+            let mut j = i + 1;
+            while j < flat.len() && flat[j].0 > *lvl {
+                j += 1;
+            }
+            node.children = nest_struct_nodes(flat, i + 1, *lvl + 1);
+            result.push(node);
+            i = j;
+        } else {
+            i += 1;
+        }
+    }
+    result
+}
+
+/// Build a `StructNode` tree from a directory:
+/// subdirectories → `Volume`, `.md` files → `Chapter`.  Entries `ignore`
+/// matches are left out.
+pub fn build_struct_from_dir(dir: &Path, ignore: &IgnoreRules) -> Vec<StructNode> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut nodes = Vec::new();
+    let mut sorted: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+    sorted.sort_by_key(|e| e.file_name());
+    for entry in sorted {
+        let path = entry.path();
+        let name = path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let is_dir = path.is_dir();
+        if ignore.is_ignored_path(&path, is_dir) {
+            continue;
+        }
+        if is_dir {
+            let mut vol = StructNode::new(&name, StructKind::Volume);
+            vol.children = build_struct_from_dir(&path, ignore);
+            nodes.push(vol);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
+            let stem = path.file_stem()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or(name);
+            nodes.push(StructNode::new(&stem, StructKind::Chapter));
+        }
+    }
+    nodes
+}
+
+// ── Content/ layout → StructNode import ──────────────────────────────────────
+
+/// One Markdown file of a `Content/` listing snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentFile {
+    /// Path relative to `Content/` with `/` separators.
+    pub rel: String,
+    /// Text of the file's first `# ` heading, if any.
+    pub heading: Option<String>,
+    pub words: usize,
+}
+
+/// Compare names so that digit runs order by value: `第2章` < `第10章`.
+pub fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return std::cmp::Ordering::Equal,
+            (None, Some(_)) => return std::cmp::Ordering::Less,
+            (Some(_), None) => return std::cmp::Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let take = |it: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = it.next_if(char::is_ascii_digit) {
+                        digits.push(c);
+                    }
+                    digits.trim_start_matches('0').to_owned()
+                };
+                let (da, db) = (take(&mut a), take(&mut b));
+                let ord = da.len().cmp(&db.len()).then_with(|| da.cmp(&db));
+                if ord.is_ne() {
+                    return ord;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+/// Snapshot of every `.md` file under `content_dir`.  Hidden folders and
+/// entries `ignore` matches are skipped.
+pub fn list_content_files(content_dir: &Path, ignore: &IgnoreRules) -> Vec<ContentFile> {
+    fn walk(dir: &Path, prefix: &str, ignore: &IgnoreRules, out: &mut Vec<ContentFile>) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let rel = if prefix.is_empty() { name.clone() } else { format!("{prefix}/{name}") };
+            let is_dir = path.is_dir();
+            if ignore.is_ignored_path(&path, is_dir) {
+                continue;
+            }
+            if is_dir {
+                if !name.starts_with('.') {
+                    walk(&path, &rel, ignore, out);
+                }
+            } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
+                let text = std::fs::read_to_string(&path).unwrap_or_default();
+                let heading = text.lines()
+                    .find_map(|l| l.strip_prefix("# "))
+                    .map(|h| h.trim().to_owned())
+                    .filter(|h| !h.is_empty());
+                out.push(ContentFile { rel, heading, words: crate::models::count_words(&text) });
+            }
+        }
+    }
+    let mut out = Vec::new();
+    walk(content_dir, "", ignore, &mut out);
+    out
+}
+
+/// Build a tree from a [`list_content_files`] snapshot: first-level folders
+/// become volumes, files become chapters titled by their first heading (or
+/// file name) with [`StructNode::file`] set.  Files in deeper folders belong
+/// to their first-level folder.  Everything is in natural order.
+pub fn struct_from_content(files: &[ContentFile]) -> Vec<StructNode> {
+    let mut sorted: Vec<&ContentFile> = files.iter().collect();
+    sorted.sort_by(|a, b| {
+        let (mut x, mut y) = (a.rel.split('/'), b.rel.split('/'));
+        loop {
+            match (x.next(), y.next()) {
+                (Some(p), Some(q)) => {
+                    let ord = natural_cmp(p, q);
+                    if ord.is_ne() {
+                        return ord;
+                    }
+                }
+                (p, q) => return p.is_some().cmp(&q.is_some()),
+            }
+        }
+    });
+
+    let mut roots: Vec<StructNode> = Vec::new();
+    for f in sorted {
+        let base = f.rel.rsplit('/').next().unwrap_or(&f.rel);
+        let stem = base.strip_suffix(".md").unwrap_or(base);
+        let mut chapter = StructNode::new(f.heading.as_deref().unwrap_or(stem), StructKind::Chapter);
+        chapter.file = Some(f.rel.clone());
+        match f.rel.split_once('/') {
+            Some((folder, _)) => {
+                let vol = match roots.last_mut() {
+                    Some(v) if v.kind == StructKind::Volume && v.title == folder => v,
+                    _ => {
+                        roots.push(StructNode::new(folder, StructKind::Volume));
+                        roots.last_mut().expect("just pushed")
+                    }
+                };
+                vol.children.push(chapter);
+            }
+            None => roots.push(chapter),
+        }
+    }
+    roots
+}
+
+/// Merge `imported` into `existing`, matching siblings by title (see
+/// [`find_duplicate_name`](crate::models::find_duplicate_name)).  Matched
+/// nodes keep their data and take the imported file link; unmatched ones
+/// are appended.  Returns the number of nodes added.
+pub fn merge_struct_by_title(existing: &mut Vec<StructNode>, imported: Vec<StructNode>) -> usize {
+    let mut added = 0;
+    for node in imported {
+        let pos = existing.iter().position(|e| {
+            crate::models::find_duplicate_name(&node.title, [e.title.as_str()]).is_some()
+        });
+        match pos {
+            Some(i) => {
+                let target = &mut existing[i];
+                if node.file.is_some() && target.file != node.file {
+                    target.file = node.file;
+                    target.touch();
+                }
+                added += merge_struct_by_title(&mut target.children, node.children);
+            }
+            None => {
+                added += 1 + count_nodes(&node.children);
+                existing.push(node);
+            }
+        }
+    }
+    added
+}
+
+/// Merge the section `subtree` into the node of the same title anywhere in
+/// `roots` (see [`merge_struct_by_title`]), or append it when there is
+/// none.  Returns the number of nodes added.
+pub fn merge_struct_subtree(roots: &mut Vec<StructNode>, subtree: StructNode) -> usize {
+    match crate::models::node_titled_mut(roots, &subtree.title) {
+        Some(target) => merge_struct_by_title(&mut target.children, subtree.children),
+        None => merge_struct_by_title(roots, vec![subtree]),
+    }
+}
+
+/// Count the total number of nodes in a tree (depth-first).
+pub fn count_nodes(roots: &[StructNode]) -> usize {
+    roots.iter().map(|n| 1 + count_nodes(&n.children)).sum()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_struct_nodes_h1_h2_h3() {
+        let md = "# 总纲\n## 第一卷\n### 第一章\n### 第二章\n## 第二卷\n";
+        let nodes = extract_struct_nodes_from_markdown(md);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].title, "总纲");
+        assert_eq!(nodes[0].kind, StructKind::Outline);
+        assert_eq!(nodes[0].children.len(), 2);
+        assert_eq!(nodes[0].children[0].title, "第一卷");
+        assert_eq!(nodes[0].children[0].children.len(), 2);
+        assert_eq!(nodes[0].children[0].children[0].title, "第一章");
+    }
+
+    #[test]
+    fn test_heading_subtree() {
+        let md = "前言\n# 总纲\n## 第一卷\n### 第一章\n正文\n### 第二章\n## 第二卷\n### 第三章\n";
+        let offset = |line: usize| md.lines().take(line - 1).map(|l| l.chars().count() + 1).sum::<usize>();
+        // Caret in 第一章's body: its section ends at the next level-3 heading.
+        assert_eq!(heading_subtree(md, offset(5), offset(5)), Some((4, 6)));
+        // Caret on 第一卷's heading line: the whole volume.
+        assert_eq!(heading_subtree(md, offset(3) + 2, offset(3) + 2), Some((3, 7)));
+        // A selection across two chapters widens to the volume; one ending
+        // at the start of 第二章's line stays inside 第一章.
+        assert_eq!(heading_subtree(md, offset(4), offset(6) + 1), Some((3, 7)));
+        assert_eq!(heading_subtree(md, offset(4), offset(6)), Some((4, 6)));
+        // The last section runs to the end of the document.
+        assert_eq!(heading_subtree(md, offset(8), offset(8)), Some((8, 9)));
+        assert_eq!(heading_subtree(md, 0, 1), None);
+
+        let node = extract_subtree_node(md, offset(3), offset(3)).unwrap();
+        assert_eq!(node.title, "第一卷");
+        assert_eq!(node.kind, StructKind::Volume);
+        let chapters: Vec<&str> = node.children.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(chapters, ["第一章", "第二章"]);
+    }
+
+    #[test]
+    fn test_merge_struct_subtree() {
+        let mut roots = extract_struct_nodes_from_markdown("# 总纲\n## 第一卷\n### 第一章\n## 第二卷\n");
+        roots[0].children[0].children[0].summary = "保留".to_owned();
+        let subtree = extract_struct_nodes_from_markdown("## 第一卷\n### 第一章\n### 第二章\n").remove(0);
+        assert_eq!(merge_struct_subtree(&mut roots, subtree), 1);
+        let volume = &roots[0].children[0];
+        assert_eq!(volume.children.len(), 2);
+        assert_eq!(volume.children[0].summary, "保留");
+        // No matching title: appended at the top level.
+        let other = extract_struct_nodes_from_markdown("## 外传\n").remove(0);
+        assert_eq!(merge_struct_subtree(&mut roots, other), 1);
+        assert_eq!(roots[1].title, "外传");
+    }
+
+    #[test]
+    fn test_extract_struct_nodes_empty() {
+        let nodes = extract_struct_nodes_from_markdown("no headings here");
+        assert!(nodes.is_empty());
+    }
+
+    #[test]
+    fn test_extract_struct_nodes_flat_chapters() {
+        let md = "### 序章\n### 第一章\n### 第二章\n";
+        let nodes = extract_struct_nodes_from_markdown(md);
+        assert_eq!(nodes.len(), 3);
+        assert!(nodes.iter().all(|n| n.kind == StructKind::Chapter));
+    }
+
+    #[test]
+    fn test_extract_struct_nodes_scene_level() {
+        let md = "### 第一章\n#### 第一节\n##### 雨夜\n##### 追兵\n";
+        let nodes = extract_struct_nodes_from_markdown(md);
+        let section = &nodes[0].children[0];
+        assert_eq!(section.kind, StructKind::Section);
+        assert_eq!(section.children.len(), 2);
+        assert!(section.children.iter().all(|n| n.kind == StructKind::Scene));
+    }
+
+    #[test]
+    fn test_parse_outline_entries_line_numbers() {
+        let md = "# 第一章\n\n正文\n## 雨夜\n```\n# 不是标题\n```\n##没有空格\n### 追兵\n";
+        let entries = parse_outline_entries(md);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0], OutlineEntry { level: 1, title: "第一章".to_owned(), line_no: 1 });
+        assert_eq!(entries[1].line_no, 4);
+        assert_eq!(entries[2].title, "追兵");
+        assert_eq!(entries[2].line_no, 9);
+    }
+
+    #[test]
+    fn test_outline_skips_front_matter() {
+        let md = "---\ntitle: 第一章\n# 草稿备注\n---\n# 第一章\n## 雨夜\n";
+        let entries = parse_outline_entries(md);
+        assert_eq!(entries.iter().map(|e| (e.title.as_str(), e.line_no)).collect::<Vec<_>>(),
+            vec![("第一章", 5), ("雨夜", 6)]);
+        let nodes = extract_struct_nodes_from_markdown(md);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].title, "第一章");
+        assert_eq!(nodes[0].children[0].title, "雨夜");
+        // An unterminated block is ordinary text.
+        assert_eq!(parse_outline_entries("---\n# 标题\n").len(), 1);
+    }
+
+    #[test]
+    fn test_count_nodes_empty() {
+        assert_eq!(count_nodes(&[]), 0);
+    }
+
+    #[test]
+    fn test_count_nodes_nested() {
+        let md = "# 卷一\n## 第一章\n### 第一节\n## 第二章\n";
+        let nodes = extract_struct_nodes_from_markdown(md);
+        // 1 (卷一) + 2 (两章) + 1 (一节) = 4
+        assert_eq!(count_nodes(&nodes), 4);
+    }
+
+    #[test]
+    fn test_build_struct_from_dir() {
+        let dir = std::env::temp_dir().join("qingmo_test_struct_dir");
+        let sub = dir.join("第一卷");
+        std::fs::create_dir_all(&sub).unwrap();
+        std::fs::write(sub.join("第一章.md"), "").unwrap();
+        std::fs::write(dir.join("序章.md"), "").unwrap();
+
+        let nodes = build_struct_from_dir(&dir, &IgnoreRules::default());
+        // Dir 第一卷 comes after file 序章 (dirs sort first in the tree)
+        assert!(nodes.iter().any(|n| n.title == "第一卷" && n.kind == StructKind::Volume));
+        assert!(nodes.iter().any(|n| n.title == "序章"   && n.kind == StructKind::Chapter));
+
+        let ignore = IgnoreRules::parse(&dir, "第一卷/\n");
+        let nodes = build_struct_from_dir(&dir, &ignore);
+        assert_eq!(nodes.iter().map(|n| n.title.as_str()).collect::<Vec<_>>(), ["序章"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        use std::cmp::Ordering::*;
+        assert_eq!(natural_cmp("第2章", "第10章"), Less);
+        assert_eq!(natural_cmp("第10章", "第9章"), Greater);
+        assert_eq!(natural_cmp("ch007", "ch7"), Equal);
+        assert_eq!(natural_cmp("序章", "序章二"), Less);
+        assert_eq!(natural_cmp("a10b2", "a10b10"), Less);
+    }
+
+    #[test]
+    fn test_struct_from_content_layout() {
+        let dir = std::env::temp_dir().join("qingmo_test_content_import");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("第10卷")).unwrap();
+        std::fs::create_dir_all(dir.join("第2卷").join("番外")).unwrap();
+        std::fs::create_dir_all(dir.join(".texttool")).unwrap();
+        std::fs::write(dir.join("序章.md"), "正文").unwrap();
+        std::fs::write(dir.join("第2卷").join("第10章.md"), "# 第十章 决战\n\n剑光").unwrap();
+        std::fs::write(dir.join("第2卷").join("第9章.md"), "## 小节\n").unwrap();
+        std::fs::write(dir.join("第2卷").join("番外").join("外传.md"), "").unwrap();
+        std::fs::write(dir.join("第10卷").join("第1章.md"), "").unwrap();
+        std::fs::write(dir.join("第10卷").join("笔记.txt"), "").unwrap();
+        std::fs::write(dir.join(".texttool").join("x.md"), "").unwrap();
+
+        let files = list_content_files(&dir, &IgnoreRules::default());
+        assert_eq!(files.len(), 5);
+        let ch10 = files.iter().find(|f| f.rel == "第2卷/第10章.md").unwrap();
+        assert_eq!(ch10.heading.as_deref(), Some("第十章 决战"));
+        assert_eq!(ch10.words, 8);
+
+        let roots = struct_from_content(&files);
+        let titles: Vec<&str> = roots.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(titles, ["序章", "第2卷", "第10卷"]);
+        assert_eq!(roots[0].kind, StructKind::Chapter);
+        assert_eq!(roots[0].file.as_deref(), Some("序章.md"));
+        assert_eq!(roots[1].kind, StructKind::Volume);
+        let vol: Vec<(&str, Option<&str>)> = roots[1].children.iter()
+            .map(|c| (c.title.as_str(), c.file.as_deref()))
+            .collect();
+        assert_eq!(vol, [
+            ("外传", Some("第2卷/番外/外传.md")),
+            ("第9章", Some("第2卷/第9章.md")),
+            ("第十章 决战", Some("第2卷/第10章.md")),
+        ]);
+        assert_eq!(roots[1].children[2].file_key(), "第10章");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_merge_struct_by_title() {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        let mut c1 = StructNode::new("第一章", StructKind::Chapter);
+        c1.summary = "保留".into();
+        vol.children.push(c1);
+        let mut existing = vec![vol];
+
+        let mut imp_vol = StructNode::new("第一卷 ", StructKind::Volume);
+        let mut imp_c1 = StructNode::new("第一章", StructKind::Chapter);
+        imp_c1.file = Some("第一卷/01.md".into());
+        imp_vol.children = vec![imp_c1, StructNode::new("第二章", StructKind::Chapter)];
+        let imported = vec![imp_vol, StructNode::new("尾声", StructKind::Chapter)];
+
+        assert_eq!(merge_struct_by_title(&mut existing, imported), 2);
+        assert_eq!(existing.len(), 2);
+        assert_eq!(existing[0].title, "第一卷");
+        let c1 = &existing[0].children[0];
+        assert_eq!(c1.summary, "保留");
+        assert_eq!(c1.file.as_deref(), Some("第一卷/01.md"));
+        assert_eq!(existing[0].children[1].title, "第二章");
+        assert_eq!(existing[1].title, "尾声");
+    }
+
+    // ── Template helpers ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_short_template_creates_flat_structure() {
+        let dir = std::env::temp_dir().join("qingmo_test_short_tpl");
+        std::fs::create_dir_all(dir.join("Content")).unwrap();
+        std::fs::create_dir_all(dir.join("Design")).unwrap();
+
+        // Simulate apply_template_short logic (no TextToolApp needed)
+        let content = dir.join("Content");
+        let chapters = ["序章.md", "第一章.md", "第二章.md", "第三章.md", "尾声.md"];
+        for name in &chapters {
+            let path = content.join(name);
+            let stem = std::path::Path::new(name).file_stem()
+                .map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            std::fs::write(&path, format!("# {}\n\n", stem)).unwrap();
+        }
+
+        // Verify all .md files exist and Content has no subdirs
+        let nodes = build_struct_from_dir(&content, &IgnoreRules::default());
+        assert_eq!(nodes.len(), chapters.len());
+        assert!(nodes.iter().all(|n| n.kind == StructKind::Chapter));
+        assert!(nodes.iter().all(|n| n.children.is_empty())); // flat, no sub-volumes
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_long_template_creates_two_layer_structure() {
+        let dir = std::env::temp_dir().join("qingmo_test_long_tpl");
+        std::fs::create_dir_all(dir.join("Content")).unwrap();
+
+        let content = dir.join("Content");
+        let volumes: &[(&str, &[&str])] = &[
+            ("第一卷", &["序章.md", "第一章.md", "第二章.md"]),
+            ("第二卷", &["第一章.md", "第二章.md", "第三章.md"]),
+        ];
+        for (vol, chapters) in volumes {
+            let vol_dir = content.join(vol);
+            std::fs::create_dir_all(&vol_dir).unwrap();
+            for name in *chapters {
+                let stem = std::path::Path::new(name).file_stem()
+                    .map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+                std::fs::write(vol_dir.join(name), format!("# {}\n\n", stem)).unwrap();
+            }
+        }
+
+        let nodes = build_struct_from_dir(&content, &IgnoreRules::default());
+        assert_eq!(nodes.len(), 2, "Should have 2 volumes");
+        assert!(nodes.iter().all(|n| n.kind == StructKind::Volume));
+        assert_eq!(nodes[0].children.len(), 3, "第一卷 should have 3 chapters");
+        assert_eq!(nodes[1].children.len(), 3, "第二卷 should have 3 chapters");
+        assert!(nodes[0].children.iter().all(|c| c.kind == StructKind::Chapter));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::models::{normalize_title, OutlineEntry, StructNode};
+
+// ── 对比大纲与结构: planning headings vs the structure tree ─────────────────────
+//
+// Headings and nodes are aligned by title in three passes: same title path
+// (so reordered siblings still match), then same title at the same depth
+// (so children of a renamed parent still match), then same title anywhere,
+// which is reported as a level mismatch.  What is left over exists on one
+// side only.  A single top heading that no root node carries is taken as
+// the document's title and left out.
+
+/// Outcome of [`align_outline`].  Entry indices point into the headings,
+/// node paths into the tree.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutlineAlignment {
+    /// The document-title heading left out of the comparison, if any.
+    pub title_entry: Option<usize>,
+    /// Heading ↔ node at the same level, in document order.
+    pub matched: Vec<(usize, Vec<usize>)>,
+    /// 仅在 Markdown 中, in document order.
+    pub only_markdown: Vec<usize>,
+    /// 仅在结构树中, depth first.
+    pub only_tree: Vec<Vec<usize>>,
+    /// 标题相同但层级不同, in document order.
+    pub level_mismatch: Vec<LevelMismatch>,
+}
+
+/// A heading and a node of the same title at different depths.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelMismatch {
+    pub entry: usize,
+    pub node: Vec<usize>,
+    /// 1 for a top-level heading (below the document title).
+    pub markdown_depth: usize,
+    /// 1 for a root node.
+    pub tree_depth: usize,
+}
+
+/// A heading with its place in the heading tree.
+struct Heading {
+    index: usize,
+    /// Normalized titles from the top heading down to this one.
+    path: Vec<String>,
+    /// Index of the enclosing heading.
+    parent: Option<usize>,
+}
+
+/// The headings of `entries` nested by level; a skipped level (`#` then
+/// `###`) still counts as one step down.
+fn heading_tree(entries: &[OutlineEntry], skip: Option<usize>) -> Vec<Heading> {
+    let mut stack: Vec<(usize, usize, Vec<String>)> = Vec::new(); // (level, index, path)
+    let mut out = Vec::new();
+    for (i, e) in entries.iter().enumerate() {
+        if Some(i) == skip {
+            continue;
+        }
+        while stack.last().is_some_and(|&(level, _, _)| level >= e.level) {
+            stack.pop();
+        }
+        let (parent, mut path) = stack.last().map_or((None, Vec::new()), |(_, p, path)| (Some(*p), path.clone()));
+        path.push(normalize_title(&e.title));
+        stack.push((e.level, i, path.clone()));
+        out.push(Heading { index: i, path, parent });
+    }
+    out
+}
+
+/// Every node of `roots`, depth first, as `(index path, title path)`.
+fn flatten_tree(roots: &[StructNode]) -> Vec<(Vec<usize>, Vec<String>)> {
+    fn walk(nodes: &[StructNode], index: &mut Vec<usize>, titles: &mut Vec<String>, out: &mut Vec<(Vec<usize>, Vec<String>)>) {
+        for (i, node) in nodes.iter().enumerate() {
+            index.push(i);
+            titles.push(normalize_title(&node.title));
+            out.push((index.clone(), titles.clone()));
+            walk(&node.children, index, titles, out);
+            index.pop();
+            titles.pop();
+        }
+    }
+    let mut out = Vec::new();
+    walk(roots, &mut Vec::new(), &mut Vec::new(), &mut out);
+    out
+}
+
+/// The document-title heading of `entries`: the only top-level heading,
+/// coming first, with no root node of its title.
+fn document_title(entries: &[OutlineEntry], roots: &[StructNode]) -> Option<usize> {
+    let top = entries.iter().map(|e| e.level).min()?;
+    let mut tops = entries.iter().enumerate().filter(|(_, e)| e.level == top);
+    let (first, entry) = tops.next()?;
+    let key = normalize_title(&entry.title);
+    (first == 0 && tops.next().is_none() && entries.len() > 1 && !roots.iter().any(|r| normalize_title(&r.title) == key))
+        .then_some(first)
+}
+
+/// Align the headings `entries` of a planning document with `roots`.
+pub fn align_outline(entries: &[OutlineEntry], roots: &[StructNode]) -> OutlineAlignment {
+    let title_entry = document_title(entries, roots);
+    let headings = heading_tree(entries, title_entry);
+    let nodes = flatten_tree(roots);
+    let mut node_taken = vec![false; nodes.len()];
+    let mut pair: Vec<Option<usize>> = vec![None; headings.len()];
+
+    // Pass 1: the whole title path; pass 2: title and depth; pass 3: title.
+    type Key = Box<dyn Fn(&[String]) -> (String, usize)>;
+    let keys: [Key; 3] = [
+        Box::new(|p: &[String]| (p.join("\u{1f}"), 0)),
+        Box::new(|p: &[String]| (p.last().cloned().unwrap_or_default(), p.len())),
+        Box::new(|p: &[String]| (p.last().cloned().unwrap_or_default(), 0)),
+    ];
+    for key in &keys {
+        let mut free: HashMap<(String, usize), VecDeque<usize>> = HashMap::new();
+        for (j, (_, titles)) in nodes.iter().enumerate().filter(|&(j, _)| !node_taken[j]) {
+            free.entry(key(titles)).or_default().push_back(j);
+        }
+        for (heading, paired) in headings.iter().zip(pair.iter_mut()).filter(|(_, p)| p.is_none()) {
+            if let Some(j) = free.get_mut(&key(&heading.path)).and_then(VecDeque::pop_front) {
+                node_taken[j] = true;
+                *paired = Some(j);
+            }
+        }
+    }
+
+    let mut out = OutlineAlignment { title_entry, ..OutlineAlignment::default() };
+    for (heading, j) in headings.iter().zip(&pair) {
+        match j {
+            Some(j) if nodes[*j].0.len() == heading.path.len() => out.matched.push((heading.index, nodes[*j].0.clone())),
+            Some(j) => out.level_mismatch.push(LevelMismatch {
+                entry: heading.index,
+                node: nodes[*j].0.clone(),
+                markdown_depth: heading.path.len(),
+                tree_depth: nodes[*j].0.len(),
+            }),
+            None => out.only_markdown.push(heading.index),
+        }
+    }
+    out.only_tree = nodes.iter().zip(&node_taken).filter(|(_, &t)| !t).map(|((p, _), _)| p.clone()).collect();
+    out
+}
+
+impl OutlineAlignment {
+    /// Node aligned with heading `entry`, at any level.
+    pub fn node_of(&self, entry: usize) -> Option<&[usize]> {
+        self.matched.iter().find(|(e, _)| *e == entry).map(|(_, p)| p.as_slice())
+            .or_else(|| self.level_mismatch.iter().find(|m| m.entry == entry).map(|m| m.node.as_slice()))
+    }
+
+    /// Heading aligned with the node at `path`, at any level.
+    pub fn entry_of(&self, path: &[usize]) -> Option<usize> {
+        self.matched.iter().find(|(_, p)| p == path).map(|(e, _)| *e)
+            .or_else(|| self.level_mismatch.iter().find(|m| m.node == path).map(|m| m.entry))
+    }
+}
+
+/// Where a node for the unaligned heading `entry` goes: the parent's index
+/// path (empty for a root) and the position among its children, before the
+/// first later sibling heading that has a node there.  `None` while the
+/// enclosing heading has no node itself.
+pub fn node_insertion(
+    entries: &[OutlineEntry],
+    roots: &[StructNode],
+    alignment: &OutlineAlignment,
+    entry: usize,
+) -> Option<(Vec<usize>, usize)> {
+    let headings = heading_tree(entries, alignment.title_entry);
+    let heading = headings.iter().find(|h| h.index == entry)?;
+    let parent = match heading.parent {
+        Some(p) => alignment.node_of(p)?.to_vec(),
+        None => Vec::new(),
+    };
+    let siblings = if parent.is_empty() {
+        roots
+    } else {
+        crate::models::node_at(roots, &parent).map(|n| n.children.as_slice())?
+    };
+    let before = headings.iter()
+        .filter(|h| h.index > entry && h.parent == heading.parent)
+        .filter_map(|h| alignment.node_of(h.index))
+        .find(|p| p.len() == parent.len() + 1 && p.starts_with(&parent))
+        .map(|p| p[parent.len()]);
+    Some((parent, before.unwrap_or(siblings.len())))
+}
+
+/// Where a heading for the unaligned node at `path` goes: the 1-based line
+/// to insert it before (one past the last line appends it) and its level.
+/// Placed before the first later sibling that has a heading, else at the
+/// end of the parent's section.  `None` while the parent node has no heading.
+pub fn heading_insertion(
+    entries: &[OutlineEntry],
+    line_count: usize,
+    alignment: &OutlineAlignment,
+    path: &[usize],
+    sibling_count: usize,
+) -> Option<(usize, usize)> {
+    let (&index, parent) = path.split_last()?;
+    let parent_entry = match parent {
+        [] => alignment.title_entry,
+        parent => Some(alignment.entry_of(parent)?),
+    };
+    let level = match parent_entry {
+        Some(p) => entries[p].level + 1,
+        None => entries.iter().map(|e| e.level).min().unwrap_or(1),
+    };
+    let later_sibling = (index + 1..sibling_count)
+        .map(|i| [parent, &[i]].concat())
+        .find_map(|p| alignment.entry_of(&p));
+    let line = match (later_sibling, parent_entry) {
+        (Some(e), _) => entries[e].line_no,
+        (None, Some(p)) => entries[p + 1..].iter()
+            .find(|e| e.level <= entries[p].level)
+            .map_or(line_count + 1, |e| e.line_no),
+        (None, None) => line_count + 1,
+    };
+    Some((line, level))
+}
+
+/// `text` with a `level` heading `title` and a blank line inserted before
+/// 1-based line `line_no`, or appended after a blank line when `line_no`
+/// is past the end.
+pub fn insert_heading(text: &str, line_no: usize, level: usize, title: &str) -> String {
+    let heading = format!("{} {title}\n", "#".repeat(level));
+    let offset = text.split_inclusive('\n').take(line_no.saturating_sub(1)).map(str::len).sum::<usize>();
+    if line_no > 0 && line_no <= text.lines().count() {
+        return format!("{}{heading}\n{}", &text[..offset], &text[offset..]);
+    }
+    let mut out = text.to_owned();
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+    out.push_str(&heading);
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StructKind;
+    use crate::outline::parse_outline_entries;
+
+    fn node(title: &str, children: Vec<StructNode>) -> StructNode {
+        let mut n = StructNode::new(title, StructKind::Chapter);
+        n.children = children;
+        n
+    }
+
+    fn titles(entries: &[OutlineEntry], idx: &[usize]) -> Vec<String> {
+        idx.iter().map(|&i| entries[i].title.clone()).collect()
+    }
+
+    #[test]
+    fn test_align_reordered_and_renamed() {
+        let tree = vec![
+            node("第一卷", vec![node("第一章", vec![]), node("第二章", vec![]), node("第三章", vec![])]),
+            node("第二卷", vec![node("第一章", vec![]), node("旧名", vec![node("雨夜", vec![])])]),
+        ];
+        // 第二章 and 第三章 swapped, 旧名 renamed (its child still aligns)
+        // and one new chapter.
+        let md = "# 书名\n## 第一卷\n### 第三章\n### 第一章\n### 第二章\n## 第二卷\n### 第一章\n### 新名\n#### 雨夜\n### 第四章\n";
+        let entries = parse_outline_entries(md);
+        let a = align_outline(&entries, &tree);
+
+        assert_eq!(a.title_entry, Some(0));
+        assert_eq!(titles(&entries, &a.only_markdown), ["新名", "第四章"]);
+        assert_eq!(a.only_tree, vec![vec![1, 1]]);
+        assert!(a.level_mismatch.is_empty());
+        assert_eq!(a.node_of(2), Some(&[0, 2][..]));
+        assert_eq!(a.node_of(6), Some(&[1, 0][..]));
+        assert_eq!(a.node_of(8), Some(&[1, 1, 0][..]));
+    }
+
+    #[test]
+    fn test_align_level_mismatch() {
+        let tree = vec![node("第一卷", vec![node("第一章", vec![]), node("楔子", vec![])])];
+        // 楔子 moved up to the top level.
+        let md = "# 楔子\n# 第一卷\n## 第一章\n";
+        let entries = parse_outline_entries(md);
+        let a = align_outline(&entries, &tree);
+        assert_eq!(a.title_entry, None);
+        assert_eq!(a.level_mismatch, vec![LevelMismatch { entry: 0, node: vec![0, 1], markdown_depth: 1, tree_depth: 2 }]);
+        assert!(a.only_markdown.is_empty() && a.only_tree.is_empty());
+        assert_eq!(a.matched.len(), 2);
+
+        // Duplicate titles pair up in order.
+        let tree = vec![node("第一章", vec![]), node("第一章", vec![])];
+        let entries = parse_outline_entries("# 第一章\n# 第一章\n# 第一章\n");
+        let a = align_outline(&entries, &tree);
+        assert_eq!(a.matched, vec![(0, vec![0]), (1, vec![1])]);
+        assert_eq!(a.only_markdown, vec![2]);
+    }
+
+    #[test]
+    fn test_insertion_points() {
+        let tree = vec![
+            node("第一卷", vec![node("第一章", vec![]), node("第二章", vec![]), node("第三章", vec![])]),
+            node("第二卷", vec![]),
+        ];
+        let md = "# 书名\n\n## 第一卷\n\n### 第一章\n正文\n### 第三章\n\n## 番外\n### 花絮\n";
+        let entries = parse_outline_entries(md);
+        let a = align_outline(&entries, &tree);
+        assert_eq!(a.only_tree, vec![vec![0, 1], vec![1]]);
+        assert_eq!(titles(&entries, &a.only_markdown), ["番外", "花絮"]);
+
+        // 第二章 goes before 第三章; 第二卷 at the end, under the title.
+        let lines = md.lines().count();
+        assert_eq!(heading_insertion(&entries, lines, &a, &[0, 1], 3), Some((7, 3)));
+        assert_eq!(heading_insertion(&entries, lines, &a, &[1], 2), Some((11, 2)));
+        assert_eq!(
+            insert_heading(md, 7, 3, "第二章"),
+            md.replace("### 第三章", "### 第二章\n\n### 第三章"),
+        );
+        assert_eq!(insert_heading("# 书名", 9, 2, "第二卷"), "# 书名\n\n## 第二卷\n");
+
+        // 番外 becomes the last root; 花絮 waits for its parent.
+        assert_eq!(node_insertion(&entries, &tree, &a, 4), Some((vec![], 2)));
+        assert_eq!(node_insertion(&entries, &tree, &a, 5), None);
+        let mut tree = tree;
+        tree.push(node("番外", vec![]));
+        let a = align_outline(&entries, &tree);
+        assert_eq!(node_insertion(&entries, &tree, &a, 5), Some((vec![2], 0)));
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::models::{ChapterTag, StructKind, StructNode};
+
+// ── 大纲文档 export ───────────────────────────────────────────────────────────
+//
+// The structure tree as a Markdown document for readers who do not want
+// JSON: 总纲 and 卷 nodes become headings, everything below them nested
+// bullet lists.
+
+/// What [`build_outline_document`] writes next to each title.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutlineDocOptions {
+    /// The chapter tag (普通 is left out).
+    pub tags: bool,
+    /// The writing stage of a leaf, `完成 m/n` of a branch.
+    pub status: bool,
+    /// Words written, summed over the leaves of a branch.
+    pub word_counts: bool,
+    pub summaries: bool,
+}
+
+impl Default for OutlineDocOptions {
+    fn default() -> Self {
+        OutlineDocOptions { tags: true, status: true, word_counts: true, summaries: true }
+    }
+}
+
+/// Render `roots` as an outline document.
+///
+/// `word_counts` maps lowercase chapter file stems to word counts, as for
+/// [`build_progress_report`](crate::report::build_progress_report).
+/// Top-level nodes that are not 总纲 / 卷 are gathered under 未分卷.
+pub fn build_outline_document(
+    roots: &[StructNode],
+    word_counts: &BTreeMap<String, usize>,
+    opts: &OutlineDocOptions,
+) -> String {
+    let mut out = String::from("# 章节大纲\n\n");
+    if roots.is_empty() {
+        out.push_str("（章节结构为空）\n");
+        return out;
+    }
+    let doc = OutlineDoc { word_counts, opts };
+    let loose: Vec<&StructNode> = roots.iter().filter(|n| !is_heading(n)).collect();
+    for root in roots.iter().filter(|n| is_heading(n)) {
+        doc.write_node(&mut out, root, 2, 0);
+    }
+    if !loose.is_empty() {
+        end_list(&mut out);
+        out.push_str("## 未分卷\n\n");
+        for node in loose {
+            doc.write_item(&mut out, node, 0);
+        }
+    }
+    end_list(&mut out);
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+/// 总纲 and 卷 nodes are written as headings.
+fn is_heading(node: &StructNode) -> bool {
+    matches!(node.kind, StructKind::Outline | StructKind::Volume)
+}
+
+/// Close a bullet list with a blank line before the next heading.
+fn end_list(out: &mut String) {
+    if !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+struct OutlineDoc<'a> {
+    word_counts: &'a BTreeMap<String, usize>,
+    opts: &'a OutlineDocOptions,
+}
+
+impl OutlineDoc<'_> {
+    /// Known words of `node`'s leaves; `None` when none has a count.
+    fn words(&self, node: &StructNode) -> Option<usize> {
+        node.leaves().into_iter()
+            .filter_map(|leaf| self.word_counts.get(&leaf.file_key()).copied())
+            .reduce(|a, b| a + b)
+    }
+
+    /// Tag, status and word count of `node`, joined with ` · `.
+    fn meta(&self, node: &StructNode) -> String {
+        let mut parts = Vec::new();
+        if self.opts.tags && node.tag != ChapterTag::Normal {
+            parts.push(node.tag.label().to_owned());
+        }
+        if self.opts.status {
+            parts.push(if node.children.is_empty() {
+                node.stage().label().to_owned()
+            } else {
+                format!("完成 {}/{}", node.done_count(), node.leaf_count())
+            });
+        }
+        if self.opts.word_counts {
+            if let Some(words) = self.words(node) {
+                parts.push(format!("{words} 字"));
+            }
+        }
+        parts.join(" · ")
+    }
+
+    /// A heading node at `level` (capped at 6); nested headings go one
+    /// level deeper, other children become bullets.
+    fn write_node(&self, out: &mut String, node: &StructNode, level: usize, indent: usize) {
+        if !is_heading(node) {
+            self.write_item(out, node, indent);
+            return;
+        }
+        end_list(out);
+        let _ = writeln!(out, "{} {}\n", "#".repeat(level.min(6)), node.title);
+        let meta = self.meta(node);
+        if !meta.is_empty() {
+            let _ = writeln!(out, "*{meta}*\n");
+        }
+        if self.opts.summaries && !node.summary.trim().is_empty() {
+            for line in node.summary.trim().lines() {
+                let _ = writeln!(out, "> {}", line.trim_end());
+            }
+            out.push('\n');
+        }
+        for child in &node.children {
+            self.write_node(out, child, level + 1, 0);
+        }
+    }
+
+    /// `node` as a bullet `indent` spaces deep, with its summary as
+    /// continuation lines and its children as a nested list.
+    fn write_item(&self, out: &mut String, node: &StructNode, indent: usize) {
+        let pad = " ".repeat(indent);
+        let meta = self.meta(node);
+        if meta.is_empty() {
+            let _ = writeln!(out, "{pad}- {}", node.title);
+        } else {
+            let _ = writeln!(out, "{pad}- {}（{meta}）", node.title);
+        }
+        if self.opts.summaries {
+            for line in node.summary.trim().lines().filter(|l| !l.trim().is_empty()) {
+                let _ = writeln!(out, "{pad}  {}", line.trim());
+            }
+        }
+        for child in &node.children {
+            self.write_item(out, child, indent + 2);
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NodeStage;
+
+    fn tree() -> (Vec<StructNode>, BTreeMap<String, usize>) {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        vol.summary = "少年离乡".into();
+        let mut c1 = StructNode::new("第一章", StructKind::Chapter);
+        c1.tag = ChapterTag::Foreshadow;
+        c1.summary = "雨夜相遇\n埋下玉佩".into();
+        c1.set_stage(NodeStage::Done);
+        let mut c2 = StructNode::new("第二章", StructKind::Chapter);
+        let mut s1 = StructNode::new("出城", StructKind::Section);
+        s1.set_stage(NodeStage::Drafting);
+        c2.children.push(s1);
+        c2.children.push(StructNode::new("追兵", StructKind::Section));
+        vol.children.push(c1);
+        vol.children.push(c2);
+        let mut extra = StructNode::new("番外", StructKind::Chapter);
+        extra.tag = ChapterTag::Climax;
+        let counts = BTreeMap::from([("第一章".to_owned(), 3200), ("出城".to_owned(), 800)]);
+        (vec![vol, extra], counts)
+    }
+
+    #[test]
+    fn test_outline_document_all_options() {
+        let (roots, counts) = tree();
+        let doc = build_outline_document(&roots, &counts, &OutlineDocOptions::default());
+        assert_eq!(doc, "\
+# 章节大纲
+
+## 第一卷
+
+*完成 1/3 · 4000 字*
+
+> 少年离乡
+
+- 第一章（伏笔 · 已完成 · 3200 字）
+  雨夜相遇
+  埋下玉佩
+- 第二章（完成 0/2 · 800 字）
+  - 出城（草稿中 · 800 字）
+  - 追兵（未开始）
+
+## 未分卷
+
+- 番外（高潮 · 未开始）
+");
+    }
+
+    #[test]
+    fn test_outline_document_titles_only() {
+        let (roots, counts) = tree();
+        let none = OutlineDocOptions { tags: false, status: false, word_counts: false, summaries: false };
+        assert_eq!(build_outline_document(&roots, &counts, &none), "\
+# 章节大纲
+
+## 第一卷
+
+- 第一章
+- 第二章
+  - 出城
+  - 追兵
+
+## 未分卷
+
+- 番外
+");
+    }
+
+    #[test]
+    fn test_outline_document_option_permutations() {
+        let (roots, counts) = tree();
+        for bits in 0..16u8 {
+            let opts = OutlineDocOptions {
+                tags: bits & 1 != 0,
+                status: bits & 2 != 0,
+                word_counts: bits & 4 != 0,
+                summaries: bits & 8 != 0,
+            };
+            let doc = build_outline_document(&roots, &counts, &opts);
+            assert_eq!(doc.contains("伏笔"), opts.tags, "{opts:?}");
+            assert_eq!(doc.contains("已完成"), opts.status, "{opts:?}");
+            assert_eq!(doc.contains("3200 字"), opts.word_counts, "{opts:?}");
+            assert_eq!(doc.contains("埋下玉佩"), opts.summaries, "{opts:?}");
+            assert_eq!(doc.contains("> 少年离乡"), opts.summaries, "{opts:?}");
+            // The skeleton never changes.
+            for title in ["## 第一卷", "- 第一章", "  - 出城", "## 未分卷", "- 番外"] {
+                assert!(doc.contains(title), "{opts:?} lost {title}");
+            }
+            assert!(doc.ends_with('\n') && !doc.ends_with("\n\n"));
+        }
+    }
+
+    #[test]
+    fn test_outline_document_nested_headings_and_empty() {
+        let mut outline = StructNode::new("总纲", StructKind::Outline);
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        vol.children.push(StructNode::new("第一章", StructKind::Chapter));
+        outline.children.push(vol);
+        let none = OutlineDocOptions { tags: false, status: false, word_counts: false, summaries: false };
+        let doc = build_outline_document(&[outline], &BTreeMap::new(), &none);
+        assert_eq!(doc, "# 章节大纲\n\n## 总纲\n\n### 第一卷\n\n- 第一章\n");
+
+        let empty = build_outline_document(&[], &BTreeMap::new(), &OutlineDocOptions::default());
+        assert_eq!(empty, "# 章节大纲\n\n（章节结构为空）\n");
+    }
+}
@@ -0,0 +1,258 @@
+use crate::markdown::{is_horizontal_rule, parse_ordered_item, strip_heading};
+use crate::models::is_cjk;
+
+// ── Paragraph formatting ──────────────────────────────────────────────────────
+
+/// Full-width indentation used by the 首行缩进 style.
+pub const INDENT: &str = "\u{3000}\u{3000}";
+
+/// How paragraphs should be separated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParagraphStyle {
+    /// 空行分段: a blank line between paragraphs, no indentation.
+    BlankLine,
+    /// 首行缩进两格: two full-width spaces before each paragraph.
+    Indent,
+    /// 两者: blank line and indentation.
+    Both,
+}
+
+impl ParagraphStyle {
+    pub fn label(self) -> &'static str {
+        match self {
+            ParagraphStyle::BlankLine => "空行分段",
+            ParagraphStyle::Indent => "首行缩进两格",
+            ParagraphStyle::Both => "两者",
+        }
+    }
+
+    fn blank_lines(self) -> bool {
+        matches!(self, ParagraphStyle::BlankLine | ParagraphStyle::Both)
+    }
+
+    fn indent(self) -> bool {
+        matches!(self, ParagraphStyle::Indent | ParagraphStyle::Both)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+    pub style: ParagraphStyle,
+    /// Collapse runs of 3+ blank lines into one.
+    pub collapse_blank_lines: bool,
+    pub trim_trailing_whitespace: bool,
+}
+
+/// One paragraph that [`format_paragraphs`] changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParagraphChange {
+    /// 1-based line number in the original text.
+    pub line_no: usize,
+    pub before: String,
+    pub after: String,
+    /// A blank line was inserted before the paragraph.
+    pub blank_inserted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatResult {
+    pub text: String,
+    pub changes: Vec<ParagraphChange>,
+}
+
+/// Normalise paragraph separation in prose.
+///
+/// Every non-blank prose line counts as one paragraph.  Headings, lists,
+/// quotes, tables, horizontal rules and fenced code blocks are left alone
+/// (code blocks are not even trimmed); blank-line options still apply
+/// around them.
+pub fn format_paragraphs(text: &str, opts: &FormatOptions) -> FormatResult {
+    let mut out: Vec<String> = Vec::new();
+    let mut changes = Vec::new();
+    let mut in_code = false;
+    let mut blanks: Vec<&str> = Vec::new();
+    // The last non-blank line emitted was a prose paragraph.
+    let mut prev_prose = false;
+
+    let flush_blanks = |blanks: &mut Vec<&str>, out: &mut Vec<String>| {
+        let keep = if opts.collapse_blank_lines && blanks.len() >= 3 { 1 } else { blanks.len() };
+        for b in &blanks[..keep] {
+            out.push(if opts.trim_trailing_whitespace { String::new() } else { (*b).to_owned() });
+        }
+        blanks.clear();
+    };
+
+    for (i, line) in text.lines().enumerate() {
+        let fence = line.trim_start().starts_with("```");
+        if in_code || fence {
+            flush_blanks(&mut blanks, &mut out);
+            out.push(line.to_owned());
+            if fence {
+                in_code = !in_code;
+            }
+            prev_prose = false;
+            continue;
+        }
+        if line.trim().is_empty() {
+            blanks.push(line);
+            continue;
+        }
+
+        let prose = is_prose(line);
+        let blank_inserted = prose && prev_prose && blanks.is_empty() && opts.style.blank_lines();
+        flush_blanks(&mut blanks, &mut out);
+        if blank_inserted {
+            out.push(String::new());
+        }
+
+        let new_line = if prose {
+            let body = line.trim_start();
+            let body = if opts.trim_trailing_whitespace { body.trim_end() } else { body };
+            let new_line = if opts.style.indent() { format!("{INDENT}{body}") } else { body.to_owned() };
+            if new_line != line || blank_inserted {
+                changes.push(ParagraphChange {
+                    line_no: i + 1,
+                    before: line.to_owned(),
+                    after: new_line.clone(),
+                    blank_inserted,
+                });
+            }
+            new_line
+        } else if opts.trim_trailing_whitespace {
+            line.trim_end().to_owned()
+        } else {
+            line.to_owned()
+        };
+        out.push(new_line);
+        prev_prose = prose;
+    }
+    flush_blanks(&mut blanks, &mut out);
+
+    let mut text_out = out.join("\n");
+    if text.ends_with('\n') {
+        text_out.push('\n');
+    }
+    FormatResult { text: text_out, changes }
+}
+
+/// `true` for a line of running text (not Markdown structure).
+fn is_prose(line: &str) -> bool {
+    // `trim_start` also strips full-width spaces (U+3000).
+    let t = line.trim_start();
+    let heading = (1..=6).any(|n| strip_heading(t, n).is_some());
+    !(heading
+        || is_horizontal_rule(t)
+        || t.starts_with('>')
+        || t.starts_with('|')
+        || t.starts_with("- ")
+        || t.starts_with("* ")
+        || t.starts_with("+ ")
+        || parse_ordered_item(t).is_some())
+}
+
+// ── Full-width punctuation while typing ──────────────────────────────────────
+
+/// Full-width form of an ASCII punctuation mark used in Chinese prose.
+pub fn fullwidth_punct(c: char) -> Option<char> {
+    Some(match c {
+        ',' => '，',
+        '.' => '。',
+        '?' => '？',
+        '!' => '！',
+        ':' => '：',
+        ';' => '；',
+        '(' => '（',
+        ')' => '）',
+        _ => return None,
+    })
+}
+
+/// If the character just before `caret` (a char index) is ASCII punctuation
+/// typed right after a CJK character, return `text` with it replaced by its
+/// full-width form.  The caret position stays valid.
+pub fn convert_punct_before_caret(text: &str, caret: usize) -> Option<String> {
+    let typed_at = caret.checked_sub(1)?;
+    let mut chars = text.chars().skip(typed_at.checked_sub(1)?);
+    let (before, typed) = (chars.next()?, chars.next()?);
+    let full = fullwidth_punct(typed).filter(|_| is_cjk(before))?;
+    Some(text.chars()
+        .enumerate()
+        .map(|(i, c)| if i == typed_at { full } else { c })
+        .collect())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(style: ParagraphStyle) -> FormatOptions {
+        FormatOptions { style, collapse_blank_lines: true, trim_trailing_whitespace: true }
+    }
+
+    #[test]
+    fn test_blank_line_style_separates_and_unindents() {
+        let text = "# 第一章\n\u{3000}\u{3000}第一段。\n第二段。  \n";
+        let res = format_paragraphs(text, &opts(ParagraphStyle::BlankLine));
+        assert_eq!(res.text, "# 第一章\n第一段。\n\n第二段。\n");
+        assert_eq!(res.changes.len(), 2);
+        assert!(!res.changes[0].blank_inserted);
+        assert!(res.changes[1].blank_inserted);
+        assert_eq!(res.changes[1].line_no, 3);
+    }
+
+    #[test]
+    fn test_indent_style_keeps_blank_lines() {
+        let text = "第一段。\n\n  第二段。";
+        let res = format_paragraphs(text, &opts(ParagraphStyle::Indent));
+        assert_eq!(res.text, "\u{3000}\u{3000}第一段。\n\n\u{3000}\u{3000}第二段。");
+    }
+
+    #[test]
+    fn test_both_style() {
+        let res = format_paragraphs("甲。\n乙。", &opts(ParagraphStyle::Both));
+        assert_eq!(res.text, "\u{3000}\u{3000}甲。\n\n\u{3000}\u{3000}乙。");
+    }
+
+    #[test]
+    fn test_skips_code_fences_and_headings() {
+        let text = "## 小节\n```\nlet a = 1;   \n\n\n\nlet b = 2;\n```\n- 列表项\n> 引用\n正文";
+        let res = format_paragraphs(text, &opts(ParagraphStyle::Both));
+        assert_eq!(
+            res.text,
+            "## 小节\n```\nlet a = 1;   \n\n\n\nlet b = 2;\n```\n- 列表项\n> 引用\n\u{3000}\u{3000}正文"
+        );
+        assert_eq!(res.changes.len(), 1);
+    }
+
+    #[test]
+    fn test_collapse_blank_lines() {
+        let text = "甲。\n\n\n\n乙。\n\n丙。";
+        let res = format_paragraphs(text, &opts(ParagraphStyle::BlankLine));
+        assert_eq!(res.text, "甲。\n\n乙。\n\n丙。");
+
+        let keep = FormatOptions { collapse_blank_lines: false, ..opts(ParagraphStyle::BlankLine) };
+        assert_eq!(format_paragraphs(text, &keep).text, text);
+    }
+
+    #[test]
+    fn test_already_formatted_has_no_changes() {
+        let text = "\u{3000}\u{3000}甲。\n\n\u{3000}\u{3000}乙。\n";
+        let res = format_paragraphs(text, &opts(ParagraphStyle::Both));
+        assert_eq!(res.text, text);
+        assert!(res.changes.is_empty());
+    }
+
+    #[test]
+    fn test_convert_punct_before_caret() {
+        assert_eq!(convert_punct_before_caret("你好,", 3).as_deref(), Some("你好，"));
+        assert_eq!(convert_punct_before_caret("走了.再见", 3).as_deref(), Some("走了。再见"));
+        // Only right after a CJK character, and only the char before the caret.
+        assert_eq!(convert_punct_before_caret("ok,", 3), None);
+        assert_eq!(convert_punct_before_caret("你,好,", 2).as_deref(), Some("你，好,"));
+        assert_eq!(convert_punct_before_caret("你好a", 3), None);
+        assert_eq!(convert_punct_before_caret(",", 1), None);
+        assert_eq!(convert_punct_before_caret("", 0), None);
+    }
+}
@@ -0,0 +1,235 @@
+use std::fmt::Write as _;
+
+use crate::models::{StructNode, WorldObject};
+use crate::timeline::object_timeline;
+
+// ── Object profile sheet ──────────────────────────────────────────────────────
+//
+// One layout for every rendering of an object's profile: the Markdown export,
+// the print view and the HTML page all walk the same `ProfileSheet`.
+
+/// Links of one relation kind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelationGroup {
+    pub kind: &'static str,
+    pub items: Vec<RelationItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelationItem {
+    pub target: String,
+    /// `对象` or `章节`.
+    pub target_type: &'static str,
+    pub note: String,
+}
+
+/// The printable content of a [`WorldObject`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileSheet {
+    pub icon: String,
+    pub name: String,
+    pub kind: String,
+    pub tags: Vec<String>,
+    pub description: String,
+    pub background: String,
+    /// Links grouped by kind, groups in order of their first link.
+    pub relations: Vec<RelationGroup>,
+    /// Titles of the chapters the object appears in; `None` when built
+    /// without the structure.
+    pub appearances: Option<Vec<String>>,
+}
+
+impl ProfileSheet {
+    pub fn new(obj: &WorldObject) -> Self {
+        let mut relations: Vec<RelationGroup> = Vec::new();
+        for l in &obj.links {
+            let item = RelationItem {
+                target: l.target.display_name().to_owned(),
+                target_type: l.target.type_label(),
+                note: l.note.clone(),
+            };
+            match relations.iter_mut().find(|g| g.kind == l.kind.label()) {
+                Some(g) => g.items.push(item),
+                None => relations.push(RelationGroup { kind: l.kind.label(), items: vec![item] }),
+            }
+        }
+        ProfileSheet {
+            icon: obj.icon().to_owned(),
+            name: obj.name.clone(),
+            kind: obj.kind.label().to_owned(),
+            tags: obj.tags.clone(),
+            description: obj.description.clone(),
+            background: obj.background.clone(),
+            relations,
+            appearances: None,
+        }
+    }
+
+    /// Add the appearance timeline of `obj` in `roots`.
+    pub fn with_timeline(mut self, obj: &WorldObject, roots: &[StructNode]) -> Self {
+        self.appearances = Some(object_timeline(roots, obj).into_iter()
+            .filter(|e| e.present)
+            .map(|e| e.title)
+            .collect());
+        self
+    }
+
+    /// Markdown section, as used by 导出所选为 Markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("## {} {} ({})\n\n", self.icon, self.name, self.kind);
+        if !self.tags.is_empty() {
+            let _ = write!(md, "**标签**: {}\n\n", self.tags.join("、"));
+        }
+        if !self.description.is_empty() {
+            let _ = write!(md, "**描述**: {}\n\n", self.description);
+        }
+        if !self.background.is_empty() {
+            let _ = write!(md, "**背景**: {}\n\n", self.background);
+        }
+        if !self.relations.is_empty() {
+            md.push_str("**关联**:\n\n");
+            for g in &self.relations {
+                for r in &g.items {
+                    let _ = write!(md, "- {} → {} ({})", g.kind, r.target, r.target_type);
+                    if !r.note.is_empty() {
+                        let _ = write!(md, " — {}", r.note);
+                    }
+                    md.push('\n');
+                }
+            }
+            md.push('\n');
+        }
+        if let Some(apps) = self.appearances.as_ref().filter(|a| !a.is_empty()) {
+            let _ = write!(md, "**出场**: {}\n\n", apps.join("、"));
+        }
+        md
+    }
+
+    /// Standalone HTML page sized for A4 printing.
+    pub fn to_html(&self) -> String {
+        let e = html_escape;
+        let mut h = String::new();
+        let _ = writeln!(h, "<!DOCTYPE html>\n<html lang=\"zh\">\n<head>\n<meta charset=\"utf-8\">");
+        let _ = writeln!(h, "<title>{}</title>\n<style>{PRINT_CSS}</style>\n</head>\n<body>", e(&self.name));
+        let _ = writeln!(h, "<h1>{} {}</h1>\n<p class=\"kind\">{}</p>", self.icon, e(&self.name), self.kind);
+        if !self.tags.is_empty() {
+            let tags: Vec<String> = self.tags.iter().map(|t| format!("<span class=\"tag\">{}</span>", e(t))).collect();
+            let _ = writeln!(h, "<p>{}</p>", tags.join(" "));
+        }
+        for (title, text) in [("描述", &self.description), ("背景", &self.background)] {
+            if !text.is_empty() {
+                let _ = writeln!(h, "<h2>{title}</h2>\n<p>{}</p>", e(text).replace('\n', "<br>"));
+            }
+        }
+        if !self.relations.is_empty() {
+            let _ = writeln!(h, "<h2>关联</h2>");
+            for g in &self.relations {
+                let _ = writeln!(h, "<h3>{}</h3>\n<ul>", g.kind);
+                for r in &g.items {
+                    let note = if r.note.is_empty() { String::new() } else { format!(" — {}", e(&r.note)) };
+                    let _ = writeln!(h, "<li>{} <small>({})</small>{note}</li>", e(&r.target), r.target_type);
+                }
+                let _ = writeln!(h, "</ul>");
+            }
+        }
+        if let Some(apps) = &self.appearances {
+            let _ = writeln!(h, "<h2>出场</h2>");
+            if apps.is_empty() {
+                let _ = writeln!(h, "<p class=\"muted\">（暂无出场章节）</p>");
+            } else {
+                let items: Vec<String> = apps.iter().map(|a| format!("<li>{}</li>", e(a))).collect();
+                let _ = writeln!(h, "<ol>{}</ol>", items.concat());
+            }
+        }
+        let _ = writeln!(h, "</body>\n</html>");
+        h
+    }
+}
+
+const PRINT_CSS: &str = "\
+@page { size: A4; margin: 18mm; }
+body { font-family: serif; max-width: 174mm; margin: 0 auto; line-height: 1.6; color: #222; }
+h1 { margin-bottom: 0; }
+h2 { border-bottom: 1px solid #ccc; font-size: 1.1em; margin-top: 1.4em; }
+h3 { font-size: 1em; margin: 0.8em 0 0.2em; }
+.kind, .muted, small { color: #777; }
+.tag { border: 1px solid #aaa; border-radius: 3px; padding: 0 4px; font-size: 0.9em; }";
+
+/// Escape `&`, `<`, `>` and `"` for HTML text and attribute values.
+pub fn html_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c   => out.push(c),
+        }
+    }
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{LinkTarget, ObjectKind, ObjectLink, RelationKind, StructKind};
+
+    fn sample() -> WorldObject {
+        let mut obj = WorldObject::new("林枫", ObjectKind::Character);
+        obj.tags = vec!["主角团".into()];
+        obj.description = "沉默<寡言>".into();
+        for (target, kind, note) in [
+            (LinkTarget::Object("苏婉".into()), RelationKind::Friend, "同门"),
+            (LinkTarget::Object("青云宗".into()), RelationKind::BelongsTo, ""),
+            (LinkTarget::Object("墨白".into()), RelationKind::Friend, ""),
+        ] {
+            obj.links.push(ObjectLink { target, kind, note: note.into() });
+        }
+        obj
+    }
+
+    #[test]
+    fn test_profile_sheet_groups_relations() {
+        let sheet = ProfileSheet::new(&sample());
+        let groups: Vec<(&str, usize)> = sheet.relations.iter().map(|g| (g.kind, g.items.len())).collect();
+        assert_eq!(groups, [("友好", 2), ("所属", 1)]);
+        assert_eq!(sheet.appearances, None);
+
+        assert_eq!(sample().to_markdown(), "\
+## 👤 林枫 (人物)
+
+**标签**: 主角团
+
+**描述**: 沉默<寡言>
+
+**关联**:
+
+- 友好 → 苏婉 (对象) — 同门
+- 友好 → 墨白 (对象)
+- 所属 → 青云宗 (对象)
+
+");
+    }
+
+    #[test]
+    fn test_profile_sheet_timeline_and_html() {
+        let obj = sample();
+        let mut c1 = StructNode::new("第一章", StructKind::Chapter);
+        c1.linked_objects.push("林枫".into());
+        let c2 = StructNode::new("第二章", StructKind::Chapter);
+        let sheet = ProfileSheet::new(&obj).with_timeline(&obj, &[c1, c2]);
+        assert_eq!(sheet.appearances.as_deref(), Some(&["第一章".to_owned()][..]));
+        assert!(sheet.to_markdown().ends_with("**出场**: 第一章\n\n"));
+
+        let html = sheet.to_html();
+        assert!(html.contains("<title>林枫</title>"));
+        assert!(html.contains("<p>沉默&lt;寡言&gt;</p>"));
+        assert!(html.contains("<h3>友好</h3>"));
+        assert!(html.contains("<ol><li>第一章</li></ol>"));
+        assert!(html.contains("size: A4"));
+        assert_eq!(html_escape("a&\"b\""), "a&amp;&quot;b&quot;");
+    }
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+
+// ── Project lock: one computer writes the design data at a time ───────────────
+//
+// A project on a synced drive may be open on two computers.  The first one
+// to open it writes `.texttool/lock` and refreshes its heartbeat while the
+// project stays open; a second computer that finds a fresh lock keeps its
+// hands off the `Design/` JSON files.  A lock whose heartbeat stopped for
+// [`LOCK_STALE_SECS`] belonged to a computer that crashed or lost the sync
+// and is taken over.
+
+/// Lock file, relative to the project root.
+pub const LOCK_FILE: &str = ".texttool/lock";
+
+/// Seconds between heartbeats of the computer holding the lock.
+pub const LOCK_HEARTBEAT_SECS: i64 = 60;
+
+/// A lock without a heartbeat for this long is stale.  Several heartbeats,
+/// so a slow sync does not hand the project to a second computer.
+pub const LOCK_STALE_SECS: i64 = 5 * 60;
+
+/// Contents of `.texttool/lock`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectLock {
+    pub host: String,
+    pub pid: u32,
+    /// Unix seconds the lock was taken.
+    pub since: i64,
+    /// Unix seconds of the last heartbeat.
+    pub heartbeat: i64,
+}
+
+impl ProjectLock {
+    pub fn new(host: &str, pid: u32, now: i64) -> Self {
+        ProjectLock { host: host.to_owned(), pid, since: now, heartbeat: now }
+    }
+
+    /// Parse a lock file; `None` for anything unreadable.
+    pub fn parse(text: &str) -> Option<Self> {
+        serde_json::from_str(text).ok()
+    }
+
+    pub fn to_text(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Whether the heartbeat is older than [`LOCK_STALE_SECS`] at `now`.
+    pub fn is_stale(&self, now: i64) -> bool {
+        now - self.heartbeat > LOCK_STALE_SECS
+    }
+
+    /// The same lock with its heartbeat at `now`.
+    pub fn beat(&self, now: i64) -> Self {
+        ProjectLock { heartbeat: now, ..self.clone() }
+    }
+}
+
+/// What an existing lock file means for the computer `host`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockStatus {
+    /// No lock, or one that cannot be read: take it.
+    Free,
+    /// This computer's lock: keep it alive.
+    Ours(ProjectLock),
+    /// Another computer's lock without a recent heartbeat: take it over.
+    Stale(ProjectLock),
+    /// Another computer's fresh lock: leave the design data alone.
+    Held(ProjectLock),
+}
+
+/// Classify the lock file `existing` (its text, if there is one) for `host`
+/// at `now`.  Locks are per computer: a second instance on the same computer
+/// shares the claim.
+pub fn lock_status(existing: Option<&str>, host: &str, now: i64) -> LockStatus {
+    match existing.and_then(ProjectLock::parse) {
+        None => LockStatus::Free,
+        Some(lock) if lock.host == host => LockStatus::Ours(lock),
+        Some(lock) if lock.is_stale(now) => LockStatus::Stale(lock),
+        Some(lock) => LockStatus::Held(lock),
+    }
+}
+
+/// The lock file this computer writes after reading `status`: a fresh lock,
+/// or its own with a new heartbeat.  `None` while another computer holds
+/// the project, unless `force` takes it over.
+pub fn next_lock(status: &LockStatus, host: &str, pid: u32, now: i64, force: bool) -> Option<ProjectLock> {
+    match status {
+        LockStatus::Ours(lock) => Some(ProjectLock { pid, ..lock.beat(now) }),
+        LockStatus::Held(_) if !force => None,
+        _ => Some(ProjectLock::new(host, pid, now)),
+    }
+}
+
+/// Name of this computer, for telling lock holders apart.
+pub fn host_name() -> String {
+    ["COMPUTERNAME", "HOSTNAME"].iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .chain(std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_owned())
+        .find(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_owned())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_status() {
+        let theirs = ProjectLock::new("laptop", 42, 1_000).to_text();
+        assert_eq!(lock_status(None, "desk", 1_000), LockStatus::Free);
+        assert_eq!(lock_status(Some("garbage"), "desk", 1_000), LockStatus::Free);
+        assert!(matches!(lock_status(Some(&theirs), "desk", 1_000 + LOCK_STALE_SECS), LockStatus::Held(l) if l.pid == 42));
+        assert!(matches!(lock_status(Some(&theirs), "desk", 1_001 + LOCK_STALE_SECS), LockStatus::Stale(_)));
+        // The same computer's lock is ours whatever its age or process.
+        assert!(matches!(lock_status(Some(&theirs), "laptop", 9_999), LockStatus::Ours(_)));
+    }
+
+    #[test]
+    fn test_next_lock() {
+        let held = lock_status(Some(&ProjectLock::new("laptop", 42, 1_000).to_text()), "desk", 1_030);
+        assert_eq!(next_lock(&held, "desk", 7, 1_030, false), None);
+        assert_eq!(next_lock(&held, "desk", 7, 1_030, true), Some(ProjectLock::new("desk", 7, 1_030)));
+        assert_eq!(next_lock(&LockStatus::Free, "desk", 7, 1_030, false), Some(ProjectLock::new("desk", 7, 1_030)));
+
+        // A heartbeat keeps the time the lock was taken.
+        let ours = lock_status(Some(&ProjectLock::new("desk", 7, 1_000).to_text()), "desk", 1_060);
+        let beat = next_lock(&ours, "desk", 7, 1_060, false).unwrap();
+        assert_eq!((beat.since, beat.heartbeat), (1_000, 1_060));
+        assert_eq!(ProjectLock::parse(&beat.to_text()), Some(beat));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::annotations::strip_annotations;
+use crate::export::merge_documents;
+use crate::models::ReadingPosition;
+
+// ── Reading mode ──────────────────────────────────────────────────────────────
+//
+// A volume compiled for the 阅读 view: one page per chapter, each compiled
+// like the 合并 export.  Progress runs over the whole volume so one slider
+// covers every chapter.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadingPage {
+    pub title: String,
+    /// The chapter under its own `# title` heading.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReadingBook {
+    pub pages: Vec<ReadingPage>,
+}
+
+impl ReadingBook {
+    /// One page per `(title, text)` chapter, compiled with [`merge_documents`]
+    /// after stripping its 注释.
+    pub fn compile(docs: &[(String, String)]) -> Self {
+        let pages = docs.iter()
+            .map(|(title, text)| ReadingPage {
+                title: title.clone(),
+                text: merge_documents(&[(title.clone(), strip_annotations(text))]),
+            })
+            .collect();
+        ReadingBook { pages }
+    }
+
+    /// Page of a saved position: the chapter with the saved title, else the
+    /// saved index clamped to the book.
+    pub fn resolve(&self, pos: &ReadingPosition) -> usize {
+        self.pages.iter()
+            .position(|p| p.title == pos.title)
+            .unwrap_or_else(|| pos.chapter.min(self.pages.len().saturating_sub(1)))
+    }
+
+    /// Saved form of `scroll` (`0.0..=1.0`) into page `page`.
+    pub fn position(&self, page: usize, scroll: f32) -> ReadingPosition {
+        ReadingPosition {
+            chapter: page,
+            title: self.pages.get(page).map(|p| p.title.clone()).unwrap_or_default(),
+            scroll: scroll.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Progress through the whole book, `0.0..=1.0`, at `scroll` into `page`.
+    pub fn progress(&self, page: usize, scroll: f32) -> f32 {
+        let n = self.pages.len();
+        if n == 0 {
+            return 0.0;
+        }
+        (page.min(n - 1) as f32 + scroll.clamp(0.0, 1.0)) / n as f32
+    }
+
+    /// Page and scroll fraction at `progress`; the inverse of [`progress`](Self::progress).
+    pub fn locate(&self, progress: f32) -> (usize, f32) {
+        let n = self.pages.len();
+        if n == 0 {
+            return (0, 0.0);
+        }
+        let x = progress.clamp(0.0, 1.0) * n as f32;
+        let page = (x.floor() as usize).min(n - 1);
+        (page, (x - page as f32).clamp(0.0, 1.0))
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn docs() -> Vec<(String, String)> {
+        vec![
+            ("第一章".to_owned(), "# 第一章\n\n雨夜。\n\n## 相遇\n".to_owned()),
+            ("第二章".to_owned(), "出城。\n".to_owned()),
+            ("第三章".to_owned(), String::new()),
+        ]
+    }
+
+    #[test]
+    fn test_compile_pages() {
+        let docs = docs();
+        let book = ReadingBook::compile(&docs);
+        let titles: Vec<&str> = book.pages.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["第一章", "第二章", "第三章"]);
+        assert_eq!(book.pages[0].text, "# 第一章\n\n雨夜。\n\n### 相遇\n");
+        assert_eq!(book.pages[2].text, "# 第三章\n\n");
+        // The pages together are the compiled volume.
+        let joined: Vec<&str> = book.pages.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(joined.join("\n"), merge_documents(&docs));
+    }
+
+    #[test]
+    fn test_progress_and_locate() {
+        let book = ReadingBook::compile(&docs());
+        assert_eq!(book.progress(0, 0.0), 0.0);
+        assert_eq!(book.progress(1, 0.5), 0.5);
+        assert_eq!(book.progress(2, 1.0), 1.0);
+        // Out-of-range input is clamped.
+        assert_eq!(book.progress(9, 2.0), 1.0);
+
+        assert_eq!(book.locate(0.5), (1, 0.5));
+        assert_eq!(book.locate(1.0), (2, 1.0));
+        assert_eq!(book.locate(-1.0), (0, 0.0));
+        let (page, scroll) = book.locate(book.progress(2, 0.25));
+        assert_eq!(page, 2);
+        assert!((scroll - 0.25).abs() < 1e-5);
+
+        let empty = ReadingBook::default();
+        assert_eq!(empty.progress(0, 0.5), 0.0);
+        assert_eq!(empty.locate(0.5), (0, 0.0));
+    }
+
+    #[test]
+    fn test_position_round_trip() {
+        let book = ReadingBook::compile(&docs());
+        let pos = book.position(1, 0.4);
+        assert_eq!(pos, ReadingPosition { chapter: 1, title: "第二章".into(), scroll: 0.4 });
+        assert_eq!(book.resolve(&pos), 1);
+
+        // The title wins after the chapters moved…
+        let mut moved = docs();
+        moved.swap(0, 1);
+        assert_eq!(ReadingBook::compile(&moved).resolve(&pos), 0);
+        // …and the index is clamped when the chapter is gone.
+        let fewer = ReadingBook::compile(&docs()[..1]);
+        assert_eq!(fewer.resolve(&pos), 0);
+        let gone = ReadingPosition { chapter: 5, title: "番外".into(), scroll: 0.0 };
+        assert_eq!(book.resolve(&gone), 2);
+    }
+}
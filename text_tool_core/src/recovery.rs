@@ -0,0 +1,234 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+// ── Crash recovery of unsaved buffers ─────────────────────────────────────────
+//
+// Modified editor buffers are copied to `<project>/.texttool/recovery/` every
+// so often.  The manifest maps each copy back to the file it belongs to;
+// saving the real file removes the copy again.
+
+/// Recovery directory, relative to the project root.
+pub const RECOVERY_DIR: &str = ".texttool/recovery";
+/// Manifest file inside [`RECOVERY_DIR`].
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// One recovered buffer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecoveryEntry {
+    /// The edited file, relative to the project root with `/` separators.
+    pub path: String,
+    /// Name of the copy inside [`RECOVERY_DIR`].
+    pub file: String,
+    /// Unix seconds of the last copy.
+    pub saved_at: i64,
+}
+
+/// `manifest.json` of [`RECOVERY_DIR`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RecoveryManifest {
+    #[serde(default)]
+    pub entries: Vec<RecoveryEntry>,
+}
+
+impl RecoveryManifest {
+    pub fn get(&self, rel: &str) -> Option<&RecoveryEntry> {
+        self.entries.iter().find(|e| e.path == rel)
+    }
+
+    /// Note a fresh copy of `rel` and return the file name to write it to.
+    pub fn record(&mut self, rel: &str, now: i64) -> String {
+        if let Some(e) = self.entries.iter_mut().find(|e| e.path == rel) {
+            e.saved_at = now;
+            return e.file.clone();
+        }
+        let file = recovery_file_name(rel);
+        self.entries.push(RecoveryEntry { path: rel.to_owned(), file: file.clone(), saved_at: now });
+        file
+    }
+
+    /// Forget the copy of `rel`, returning its entry.
+    pub fn remove(&mut self, rel: &str) -> Option<RecoveryEntry> {
+        let idx = self.entries.iter().position(|e| e.path == rel)?;
+        Some(self.entries.remove(idx))
+    }
+
+    /// Read the manifest of the project at `root`; empty if missing or
+    /// unreadable.
+    pub fn load(root: &Path) -> Self {
+        std::fs::read_to_string(root.join(RECOVERY_DIR).join(MANIFEST_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, root: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        crate::safe_write(&root.join(RECOVERY_DIR).join(MANIFEST_FILE), json)
+    }
+}
+
+/// Write recovery copies of `buffers` (`(file, content)` pairs) under `root`
+/// and save the manifest.  Files outside the project are skipped.  Returns
+/// the number of copies written.
+pub fn flush_buffers(
+    root: &Path,
+    manifest: &mut RecoveryManifest,
+    buffers: &[(&Path, &str)],
+    now: i64,
+) -> std::io::Result<usize> {
+    let dir = root.join(RECOVERY_DIR);
+    std::fs::create_dir_all(&dir)?;
+    let mut written = 0;
+    for (path, content) in buffers {
+        let Some(rel) = relative_key(root, path) else { continue };
+        let file = manifest.record(&rel, now);
+        crate::safe_write(&dir.join(file), content)?;
+        written += 1;
+    }
+    manifest.save(root)?;
+    Ok(written)
+}
+
+/// Stable file name for the copy of `rel`: a 64-bit FNV-1a hash of the
+/// path, so the same file always maps to the same copy.
+pub fn recovery_file_name(rel: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in rel.as_bytes() {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}.md")
+}
+
+/// `path` relative to `root` with `/` separators, or `None` outside it.
+pub fn relative_key(root: &Path, path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(root).ok()?;
+    let parts: Vec<String> = rel.components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Whether a copy written at `copy` should be offered for `real`, the
+/// modification time of the file itself (`None` if it no longer exists).
+pub fn copy_is_newer(copy: SystemTime, real: Option<SystemTime>) -> bool {
+    real.is_none_or(|real| copy > real)
+}
+
+/// Copies in `manifest` worth offering on startup: the copy exists and is
+/// newer than the real file.  Returns `(real path, copy path)` pairs.
+pub fn pending_recoveries(root: &Path, manifest: &RecoveryManifest) -> Vec<(PathBuf, PathBuf)> {
+    let dir = root.join(RECOVERY_DIR);
+    let mtime = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    manifest.entries.iter()
+        .filter_map(|e| {
+            let copy = dir.join(&e.file);
+            let real = root.join(&e.path);
+            let copy_time = mtime(&copy)?;
+            copy_is_newer(copy_time, mtime(&real)).then_some((real, copy))
+        })
+        .collect()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_recovery_manifest_record_remove() {
+        let mut m = RecoveryManifest::default();
+        let a = m.record("Content/第一章.md", 10);
+        assert_eq!(a, recovery_file_name("Content/第一章.md"));
+        assert_ne!(a, recovery_file_name("Content/第二章.md"));
+        m.record("Content/第二章.md", 11);
+        // Re-recording keeps the file name and bumps the time.
+        assert_eq!(m.record("Content/第一章.md", 20), a);
+        assert_eq!(m.entries.len(), 2);
+        assert_eq!(m.get("Content/第一章.md").unwrap().saved_at, 20);
+
+        assert_eq!(m.remove("Content/第一章.md").unwrap().file, a);
+        assert!(m.remove("Content/第一章.md").is_none());
+        assert_eq!(m.entries.len(), 1);
+
+        let root = Path::new("/proj");
+        assert_eq!(relative_key(root, &root.join("Content").join("卷一").join("a.md")).as_deref(),
+            Some("Content/卷一/a.md"));
+        assert_eq!(relative_key(root, Path::new("/other/a.md")), None);
+    }
+
+    #[test]
+    fn test_flush_buffers_writes_copies_and_manifest() {
+        let root = std::env::temp_dir().join("qingmo_core_test_recovery_flush");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let a = root.join("Content").join("a.md");
+        let outside = Path::new("/elsewhere/b.md");
+
+        let mut m = RecoveryManifest::default();
+        let n = flush_buffers(&root, &mut m, &[(&a, "未保存"), (outside, "外部")], 42).unwrap();
+        assert_eq!(n, 1);
+        let entry = m.get("Content/a.md").unwrap().clone();
+        assert_eq!(entry.saved_at, 42);
+        let copy = root.join(RECOVERY_DIR).join(&entry.file);
+        assert_eq!(std::fs::read_to_string(&copy).unwrap(), "未保存");
+        // The manifest on disk matches, so the copy is offered next start.
+        assert_eq!(RecoveryManifest::load(&root), m);
+        assert_eq!(pending_recoveries(&root, &m), vec![(a.clone(), copy)]);
+
+        // Flushing again overwrites the same copy.
+        flush_buffers(&root, &mut m, &[(&a, "更新")], 50).unwrap();
+        assert_eq!(m.entries.len(), 1);
+        assert_eq!(std::fs::read_to_string(root.join(RECOVERY_DIR).join(&entry.file)).unwrap(), "更新");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_pending_recoveries_newer_than() {
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        assert!(copy_is_newer(t0, None));
+        assert!(copy_is_newer(t0 + Duration::from_secs(1), Some(t0)));
+        assert!(!copy_is_newer(t0, Some(t0)));
+
+        let root = std::env::temp_dir().join("qingmo_core_test_recovery");
+        let _ = std::fs::remove_dir_all(&root);
+        let dir = root.join(RECOVERY_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::create_dir_all(root.join("Content")).unwrap();
+
+        let set_mtime = |p: &Path, t: SystemTime| {
+            std::fs::File::options().write(true).open(p).unwrap().set_modified(t).unwrap();
+        };
+        let mut m = RecoveryManifest::default();
+        // Copy newer than the file: offered.
+        let newer = m.record("Content/a.md", 0);
+        std::fs::write(root.join("Content/a.md"), "旧").unwrap();
+        std::fs::write(dir.join(&newer), "新").unwrap();
+        set_mtime(&root.join("Content/a.md"), t0);
+        set_mtime(&dir.join(&newer), t0 + Duration::from_secs(5));
+        // File saved after the copy: not offered.
+        let older = m.record("Content/b.md", 0);
+        std::fs::write(root.join("Content/b.md"), "已保存").unwrap();
+        std::fs::write(dir.join(&older), "旧稿").unwrap();
+        set_mtime(&dir.join(&older), t0);
+        set_mtime(&root.join("Content/b.md"), t0 + Duration::from_secs(5));
+        // File deleted: offered.
+        let gone = m.record("Content/c.md", 0);
+        std::fs::write(dir.join(&gone), "孤本").unwrap();
+        // Copy missing: skipped.
+        m.record("Content/d.md", 0);
+
+        let pending = pending_recoveries(&root, &m);
+        assert_eq!(pending, vec![
+            (root.join("Content/a.md"), dir.join(&newer)),
+            (root.join("Content/c.md"), dir.join(&gone)),
+        ]);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
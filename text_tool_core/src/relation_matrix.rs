@@ -0,0 +1,183 @@
+use std::fmt::Write;
+
+use crate::export::csv_field;
+use crate::models::{same_title, LinkTarget, ObjectKind, RelationKind, WorldObject};
+
+// ── 关系矩阵: who relates to whom, as a table ─────────────────────────────────
+//
+// Rows and columns are the chosen objects in the same order.  The grid is
+// symmetric: a link A → B and its reciprocal B → A fill both cells, and a
+// kind found in both directions is listed once.
+
+/// The relation grid over `names`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelationMatrix {
+    pub names: Vec<String>,
+    /// `cells[i][j]`: kinds linking `names[i]` and `names[j]`, in
+    /// [`RelationKind::all`] order; the diagonal stays empty.
+    pub cells: Vec<Vec<Vec<RelationKind>>>,
+}
+
+/// Objects preselected for the matrix: the characters with at least one
+/// link to or from another object, in list order.
+pub fn default_matrix_selection(objects: &[WorldObject]) -> Vec<String> {
+    let links_object = |o: &WorldObject| o.links.iter().any(|l| matches!(l.target, LinkTarget::Object(_)));
+    let linked_to = |o: &WorldObject| objects.iter().any(|other| {
+        other.links.iter().any(|l| matches!(&l.target, LinkTarget::Object(n) if same_title(n, &o.name)))
+    });
+    objects.iter()
+        .filter(|o| o.kind == ObjectKind::Character && (links_object(o) || linked_to(o)))
+        .map(|o| o.name.clone())
+        .collect()
+}
+
+/// The matrix of `names` from the object → object links of `objects`.
+/// Names are matched with [`same_title`]; links to objects outside `names`
+/// and links of an object to itself are left out.  With `drop_empty`,
+/// names without any relation in the grid are removed.
+pub fn relation_matrix(objects: &[WorldObject], names: &[String], drop_empty: bool) -> RelationMatrix {
+    let index = |name: &str| names.iter().position(|n| same_title(n, name));
+    let n = names.len();
+    let mut cells = vec![vec![Vec::new(); n]; n];
+    for obj in objects {
+        let Some(i) = index(&obj.name) else { continue };
+        for link in &obj.links {
+            let LinkTarget::Object(target) = &link.target else { continue };
+            let Some(j) = index(target).filter(|&j| j != i) else { continue };
+            for (a, b) in [(i, j), (j, i)] {
+                let cell: &mut Vec<RelationKind> = &mut cells[a][b];
+                if !cell.contains(&link.kind) {
+                    cell.push(link.kind.clone());
+                }
+            }
+        }
+    }
+    let rank = |k: &RelationKind| RelationKind::all().iter().position(|r| r == k);
+    for cell in cells.iter_mut().flatten() {
+        cell.sort_by_key(rank);
+    }
+
+    let keep: Vec<usize> = (0..n)
+        .filter(|&i| !drop_empty || cells[i].iter().any(|c| !c.is_empty()))
+        .collect();
+    RelationMatrix {
+        names: keep.iter().map(|&i| names[i].clone()).collect(),
+        cells: keep.iter().map(|&i| keep.iter().map(|&j| std::mem::take(&mut cells[i][j])).collect()).collect(),
+    }
+}
+
+impl RelationMatrix {
+    /// Text of cell `(i, j)`: the kinds separated by `, `, `—` on the diagonal.
+    fn cell_text(&self, i: usize, j: usize) -> String {
+        if i == j {
+            return "—".to_owned();
+        }
+        self.cells[i][j].iter().map(RelationKind::label).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Markdown pipe table with an empty top-left header cell.
+    pub fn to_markdown(&self) -> String {
+        let esc = |s: &str| s.replace('|', "\\|");
+        let mut out = String::from("|  |");
+        for name in &self.names {
+            let _ = write!(out, " {} |", esc(name));
+        }
+        out.push_str("\n| --- |");
+        out.push_str(&" --- |".repeat(self.names.len()));
+        out.push('\n');
+        for (i, name) in self.names.iter().enumerate() {
+            let _ = write!(out, "| **{}** |", esc(name));
+            for j in 0..self.names.len() {
+                let _ = write!(out, " {} |", esc(&self.cell_text(i, j)));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// CSV with the names as the header row and first column.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        let header: Vec<String> = std::iter::once(String::new())
+            .chain(self.names.iter().map(|n| csv_field(n)))
+            .collect();
+        out.push_str(&header.join(","));
+        out.push('\n');
+        for (i, name) in self.names.iter().enumerate() {
+            let row: Vec<String> = std::iter::once(csv_field(name))
+                .chain((0..self.names.len()).map(|j| csv_field(&self.cell_text(i, j))))
+                .collect();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ObjectLink;
+
+    fn link(obj: &mut WorldObject, target: &str, kind: RelationKind) {
+        obj.links.push(ObjectLink { target: LinkTarget::Object(target.to_owned()), kind, note: String::new() });
+    }
+
+    fn sample() -> Vec<WorldObject> {
+        let mut lin = WorldObject::new("林远", ObjectKind::Character);
+        let mut su = WorldObject::new("苏晚", ObjectKind::Character);
+        let mut zhao = WorldObject::new("赵三", ObjectKind::Character);
+        let mut sect = WorldObject::new("青云宗", ObjectKind::Faction);
+        let loner = WorldObject::new("路人", ObjectKind::Character);
+        // Reciprocal 友好 is listed once; 亲属 only one way still fills both cells.
+        link(&mut lin, "苏晚", RelationKind::Friend);
+        link(&mut su, "林远", RelationKind::Friend);
+        link(&mut su, " 林远 ", RelationKind::Family);
+        // Reverse order of RelationKind::all() on purpose.
+        link(&mut zhao, "林远", RelationKind::Enemy);
+        link(&mut lin, "赵三", RelationKind::Friend);
+        link(&mut zhao, "赵三", RelationKind::Other);
+        link(&mut sect, "林远", RelationKind::BelongsTo);
+        link(&mut lin, "不存在", RelationKind::Friend);
+        vec![lin, su, zhao, sect, loner]
+    }
+
+    #[test]
+    fn test_relation_matrix_merges_reciprocal_links() {
+        let objects = sample();
+        assert_eq!(default_matrix_selection(&objects), ["林远", "苏晚", "赵三"]);
+
+        let names: Vec<String> = ["林远", "苏晚", "赵三", "路人"].map(str::to_owned).to_vec();
+        let m = relation_matrix(&objects, &names, false);
+        assert_eq!(m.names, names);
+        assert_eq!(m.cells[0][1], [RelationKind::Friend, RelationKind::Family]);
+        assert_eq!(m.cells[1][0], m.cells[0][1]);
+        assert_eq!(m.cells[0][2], [RelationKind::Friend, RelationKind::Enemy]);
+        assert_eq!(m.cells[2][0], m.cells[0][2]);
+        // Self-links and objects outside the selection are left out.
+        assert!(m.cells[2][2].is_empty());
+        assert!(m.cells[1][2].is_empty());
+        assert!(m.cells[3].iter().all(Vec::is_empty));
+
+        let dropped = relation_matrix(&objects, &names, true);
+        assert_eq!(dropped.names, ["林远", "苏晚", "赵三"]);
+        assert_eq!(dropped.cells[0][1], m.cells[0][1]);
+    }
+
+    #[test]
+    fn test_relation_matrix_output() {
+        let objects = sample();
+        let names: Vec<String> = ["林远", "苏晚"].map(str::to_owned).to_vec();
+        let m = relation_matrix(&objects, &names, false);
+        assert_eq!(m.to_markdown(), "\
+|  | 林远 | 苏晚 |
+| --- | --- | --- |
+| **林远** | — | 友好, 亲属 |
+| **苏晚** | 友好, 亲属 | — |
+");
+        assert_eq!(m.to_csv(), ",林远,苏晚\n林远,—,\"友好, 亲属\"\n苏晚,\"友好, 亲属\",—\n");
+        assert_eq!(relation_matrix(&objects, &[], false).to_csv(), "\n");
+    }
+}
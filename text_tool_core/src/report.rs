@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::models::{format_day, Foreshadow, StructNode, WritingStats};
+
+// ── Progress report ───────────────────────────────────────────────────────────
+
+/// Render the progress report as Markdown.
+///
+/// `word_counts` maps lowercase chapter file stems to word counts (see
+/// the app's `ProjectIndex::word_counts_by_stem`); `today` is a day number as used by
+/// [`format_day`].
+pub fn build_progress_report(
+    roots: &[StructNode],
+    foreshadows: &[Foreshadow],
+    word_counts: &BTreeMap<String, usize>,
+    stats: &WritingStats,
+    today: i64,
+) -> String {
+    let words_of = |node: &StructNode| word_counts.get(&node.file_key()).copied();
+
+    let mut leaves = Vec::new();
+    collect_leaves(roots, &mut leaves);
+    let total: usize = roots.iter().map(|n| n.leaf_count()).sum();
+    let done: usize = roots.iter().map(|n| n.done_count()).sum();
+    let total_words: usize = leaves.iter().filter_map(|n| words_of(n)).sum();
+    let pct = (done * 100).checked_div(total).unwrap_or(0);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# 进度报告\n");
+    let _ = writeln!(out, "生成日期：{}\n", format_day(today));
+
+    let _ = writeln!(out, "## 总体进度\n");
+    let _ = writeln!(out, "- 完成章节：{done} / {total}（{pct}%）");
+    let _ = writeln!(out, "- 总字数：{total_words}\n");
+
+    let _ = writeln!(out, "## 分卷详情\n");
+    if roots.is_empty() {
+        let _ = writeln!(out, "（章节结构为空）\n");
+    }
+    let write_table = |out: &mut String, nodes: &[&StructNode]| {
+        let _ = writeln!(out, "| 章节 | 标签 | 完成 | 字数 |");
+        let _ = writeln!(out, "|---|---|---|---|");
+        for node in nodes {
+            let words = words_of(node).map_or("—".to_owned(), |w| w.to_string());
+            let _ = writeln!(out, "| {} | {} | {} | {} |",
+                node.title.replace('|', "\\|"),
+                node.tag.label(),
+                if node.done { "✅" } else { "⬜" },
+                words);
+        }
+        let _ = writeln!(out);
+    };
+    let mut loose = Vec::new();
+    for root in roots {
+        if root.children.is_empty() {
+            loose.push(root);
+            continue;
+        }
+        let _ = writeln!(out, "### {} {}（{}/{}）\n",
+            root.kind.icon(), root.title, root.done_count(), root.leaf_count());
+        let mut chapters = Vec::new();
+        collect_leaves(&root.children, &mut chapters);
+        write_table(&mut out, &chapters);
+    }
+    if !loose.is_empty() {
+        let _ = writeln!(out, "### 未分卷\n");
+        write_table(&mut out, &loose);
+    }
+
+    let open: Vec<&Foreshadow> = foreshadows.iter().filter(|f| !f.resolved).collect();
+    let _ = writeln!(out, "## 未回收伏笔（{}）\n", open.len());
+    if open.is_empty() {
+        let _ = writeln!(out, "（无）");
+    }
+    for f in &open {
+        let _ = writeln!(out, "- {}", f.name);
+    }
+    let _ = writeln!(out);
+
+    let _ = writeln!(out, "## 近 7 日字数\n");
+    let _ = writeln!(out, "| 日期 | 字数 |");
+    let _ = writeln!(out, "|---|---|");
+    let mut week_total = 0;
+    for day in today - 6..=today {
+        let date = format_day(day);
+        let words = stats.words_on(&date);
+        week_total += words;
+        let _ = writeln!(out, "| {date} | {words} |");
+    }
+    let _ = writeln!(out, "| 合计 | {week_total} |");
+    out
+}
+
+/// Depth-first list of the leaf nodes under `nodes`.
+fn collect_leaves<'a>(nodes: &'a [StructNode], out: &mut Vec<&'a StructNode>) {
+    for node in nodes {
+        if node.children.is_empty() {
+            out.push(node);
+        } else {
+            collect_leaves(&node.children, out);
+        }
+    }
+}
+
+// ── Session summary (打卡) ────────────────────────────────────────────────────
+
+/// Consecutive days up to `today` on which `stats` records at least `goal`
+/// words (a goal of 0 counts any day with words).  A today still short of
+/// the goal does not break the streak yet: it then ends yesterday.
+pub fn goal_streak(stats: &WritingStats, today: i64, goal: usize) -> usize {
+    let goal = goal.max(1) as i64;
+    let met = |day: i64| stats.words_on(&format_day(day)) >= goal;
+    let last = if met(today) { today } else { today - 1 };
+    (0..).take_while(|&back| met(last - back)).count()
+}
+
+/// What the 本次写作小结 dialog shows when the app closes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    pub words_today: i64,
+    pub daily_goal: usize,
+    /// See [`goal_streak`].
+    pub streak: usize,
+    /// Titles of the nodes marked done this session.
+    pub chapters_done: Vec<String>,
+    /// Names of the foreshadows resolved this session.
+    pub foreshadows_resolved: Vec<String>,
+}
+
+impl SessionSummary {
+    /// Short Markdown snippet for 复制为打卡文本; empty lists are left out.
+    pub fn checkin_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "**写作打卡 · {}**\n", self.date);
+        let reached = if self.words_today >= self.daily_goal.max(1) as i64 { " ✅" } else { "" };
+        let _ = writeln!(out, "- 今日字数：{} / {}{reached}", self.words_today, self.daily_goal);
+        let _ = writeln!(out, "- 连续达标：{} 天", self.streak);
+        if !self.chapters_done.is_empty() {
+            let _ = writeln!(out, "- 完成章节：{}", self.chapters_done.join("、"));
+        }
+        if !self.foreshadows_resolved.is_empty() {
+            let _ = writeln!(out, "- 回收伏笔：{}", self.foreshadows_resolved.join("、"));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChapterTag, StructKind};
+
+    #[test]
+    fn test_format_day() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(19723), "2024-01-01");
+        assert_eq!(format_day(19782), "2024-02-29");
+        assert_eq!(format_day(-1), "1969-12-31");
+    }
+
+    #[test]
+    fn test_progress_report_snapshot() {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        let mut ch1 = StructNode::new("第一章", StructKind::Chapter);
+        ch1.done = true;
+        let mut ch2 = StructNode::new("第二章", StructKind::Chapter);
+        ch2.tag = ChapterTag::Climax;
+        vol.children = vec![ch1, ch2];
+        let loose = StructNode::new("番外", StructKind::Chapter);
+
+        let mut fs_open = Foreshadow::new("神秘玉佩");
+        fs_open.resolved = false;
+        let mut fs_done = Foreshadow::new("师父的信");
+        fs_done.resolved = true;
+
+        let word_counts: BTreeMap<String, usize> =
+            [("第一章".to_owned(), 3000), ("番外".to_owned(), 500)].into_iter().collect();
+        let mut stats = WritingStats::default();
+        stats.record("2024-01-01", 1200);
+        stats.record("2023-12-30", 800);
+        stats.record("2023-12-20", 999); // outside the 7-day window
+
+        let report = build_progress_report(
+            &[vol, loose], &[fs_open, fs_done], &word_counts, &stats, 19723);
+
+        let expected = "\
+# 进度报告
+
+生成日期：2024-01-01
+
+## 总体进度
+
+- 完成章节：1 / 3（33%）
+- 总字数：3500
+
+## 分卷详情
+
+### 📚 第一卷（1/2）
+
+| 章节 | 标签 | 完成 | 字数 |
+|---|---|---|---|
+| 第一章 | 普通 | ✅ | 3000 |
+| 第二章 | 高潮 | ⬜ | — |
+
+### 未分卷
+
+| 章节 | 标签 | 完成 | 字数 |
+|---|---|---|---|
+| 番外 | 普通 | ⬜ | 500 |
+
+## 未回收伏笔（1）
+
+- 神秘玉佩
+
+## 近 7 日字数
+
+| 日期 | 字数 |
+|---|---|
+| 2023-12-26 | 0 |
+| 2023-12-27 | 0 |
+| 2023-12-28 | 0 |
+| 2023-12-29 | 0 |
+| 2023-12-30 | 800 |
+| 2023-12-31 | 0 |
+| 2024-01-01 | 1200 |
+| 合计 | 2000 |
+";
+        assert_eq!(report, expected);
+    }
+
+    #[test]
+    fn test_writing_stats_record() {
+        let mut stats = WritingStats::default();
+        stats.record("2024-01-01", 100);
+        stats.record("2024-01-01", -30);
+        stats.record("2024-01-02", 0);
+        assert_eq!(stats.words_on("2024-01-01"), 70);
+        assert!(!stats.daily_words.contains_key("2024-01-02"));
+    }
+
+    #[test]
+    fn test_goal_streak() {
+        let day = |s: &str| crate::models::parse_day(s).unwrap();
+        let mut stats = WritingStats::default();
+        stats.record("2024-02-27", 1500);
+        // 02-28 falls short of the goal, so the streak starts on 02-29.
+        stats.record("2024-02-28", 300);
+        stats.record("2024-02-29", 1000);
+        stats.record("2024-03-01", 2400);
+        stats.record("2024-03-02", 1200);
+        assert_eq!(goal_streak(&stats, day("2024-03-02"), 1000), 3);
+        // Today not reached yet: the streak up to yesterday still counts.
+        stats.record("2024-03-03", 200);
+        assert_eq!(goal_streak(&stats, day("2024-03-03"), 1000), 3);
+        // A full day missed breaks it.
+        assert_eq!(goal_streak(&stats, day("2024-03-04"), 1000), 0);
+        // Goal 0: any day with words counts.
+        assert_eq!(goal_streak(&stats, day("2024-03-03"), 0), 6);
+        assert_eq!(goal_streak(&WritingStats::default(), day("2024-03-03"), 1000), 0);
+    }
+
+    #[test]
+    fn test_session_checkin_text() {
+        let mut summary = SessionSummary {
+            date: "2024-03-02".to_owned(),
+            words_today: 1200,
+            daily_goal: 1000,
+            streak: 3,
+            chapters_done: vec!["第一章".to_owned(), "第二章".to_owned()],
+            foreshadows_resolved: vec!["神秘玉佩".to_owned()],
+        };
+        assert_eq!(summary.checkin_text(), "\
+**写作打卡 · 2024-03-02**
+
+- 今日字数：1200 / 1000 ✅
+- 连续达标：3 天
+- 完成章节：第一章、第二章
+- 回收伏笔：神秘玉佩
+");
+        summary.words_today = 400;
+        summary.chapters_done.clear();
+        summary.foreshadows_resolved.clear();
+        assert_eq!(summary.checkin_text(), "**写作打卡 · 2024-03-02**\n\n- 今日字数：400 / 1000\n- 连续达标：3 天\n");
+    }
+}
@@ -0,0 +1,143 @@
+use std::path::{Path, PathBuf};
+
+use crate::models::{LinkTarget, ObjectKind, ObjectLink, RelationKind, StructKind, StructNode, WorldObject};
+
+// ── 示例项目: a small project for first-time users ─────────────────────────────
+//
+// The Markdown comes from the templates under `templates/sample/`; the
+// Design JSON is built from the model types and serialized like the panels
+// save it, so the sample keeps loading as the schema grows.
+
+/// Folders every project has.
+pub const PROJECT_DIRS: &[&str] = &["Content", "Design", "废稿"];
+
+/// The chapter opened once the sample project is created, relative to the root.
+pub const SAMPLE_CHAPTER: &str = "Content/第一章 雨夜.md";
+
+const CHAPTER_TEMPLATE: &str = include_str!("../templates/sample/第一章 雨夜.md");
+const GUIDE_TEMPLATE: &str = include_str!("../templates/sample/开始写作.md");
+
+/// One file of the sample project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleFile {
+    /// Relative to the project root, `/`-separated.
+    pub path: &'static str,
+    pub content: String,
+}
+
+/// The two linked characters of the sample.
+pub fn sample_objects() -> Vec<WorldObject> {
+    let link = |target: LinkTarget, kind: RelationKind, note: &str| ObjectLink { target, kind, note: note.to_owned() };
+    let mut lin = WorldObject::new("林远", ObjectKind::Character);
+    lin.description = "沉默寡言的年轻人，总在雨夜出门。".to_owned();
+    lin.background = "父亲留下一块来历不明的玉佩。".to_owned();
+    lin.tags = vec!["主角".to_owned()];
+    lin.links = vec![
+        link(LinkTarget::Object("苏晚".to_owned()), RelationKind::Friend, "自幼相识"),
+        link(LinkTarget::Node("第一卷/第一章 雨夜".to_owned()), RelationKind::AppearsIn, ""),
+    ];
+    let mut su = WorldObject::new("苏晚", ObjectKind::Character);
+    su.description = "城南旧书铺的主人，消息灵通。".to_owned();
+    su.links = vec![
+        link(LinkTarget::Object("林远".to_owned()), RelationKind::Friend, "自幼相识"),
+        link(LinkTarget::Node("第一卷/第一章 雨夜".to_owned()), RelationKind::AppearsIn, ""),
+    ];
+    vec![lin, su]
+}
+
+/// One volume with the sample chapter and an empty one to plan.
+pub fn sample_structure() -> Vec<StructNode> {
+    let mut first = StructNode::new("第一章 雨夜", StructKind::Chapter);
+    first.summary = "林远雨夜造访苏晚的书铺，带来一块玉佩。".to_owned();
+    first.file = Some("第一章 雨夜.md".to_owned());
+    first.linked_objects = vec!["林远".to_owned(), "苏晚".to_owned()];
+    first.target_words = Some(3000);
+    let mut second = StructNode::new("第二章", StructKind::Chapter);
+    second.notes = "- 玉佩的来历\n- 苏晚打听到的消息\n".to_owned();
+    let mut volume = StructNode::new("第一卷", StructKind::Volume);
+    volume.children = vec![first, second];
+    vec![volume]
+}
+
+/// Every file of the sample project.
+pub fn sample_project() -> serde_json::Result<Vec<SampleFile>> {
+    Ok(vec![
+        SampleFile { path: SAMPLE_CHAPTER, content: CHAPTER_TEMPLATE.to_owned() },
+        SampleFile { path: "Design/世界对象.json", content: serde_json::to_string_pretty(&sample_objects())? },
+        SampleFile { path: "Design/章节结构.json", content: serde_json::to_string_pretty(&sample_structure())? },
+        SampleFile { path: "Design/开始写作.md", content: GUIDE_TEMPLATE.to_owned() },
+    ])
+}
+
+/// Create the sample project under `root`.  Files that already exist are
+/// left alone; returns the paths written.
+pub fn write_sample_project(root: &Path) -> std::io::Result<Vec<PathBuf>> {
+    for dir in PROJECT_DIRS {
+        std::fs::create_dir_all(root.join(dir))?;
+    }
+    let mut written = Vec::new();
+    for file in sample_project().map_err(std::io::Error::other)? {
+        let path = root.join(file.path);
+        if !path.exists() {
+            crate::safe_write(&path, &file.content)?;
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outline::parse_outline_entries;
+
+    #[test]
+    fn test_sample_project_loads() {
+        let files = sample_project().unwrap();
+        let text = |path: &str| files.iter().find(|f| f.path == path).unwrap().content.as_str();
+
+        // The Design files read back the way the panels load them.
+        let objects: Vec<WorldObject> = serde_json::from_str(text("Design/世界对象.json")).unwrap();
+        let roots: Vec<StructNode> = serde_json::from_str(text("Design/章节结构.json")).unwrap();
+        assert_eq!(objects.len(), 2);
+        assert!(objects.iter().all(|o| o.kind == ObjectKind::Character));
+
+        // Every link resolves within the sample.
+        let chapter = &roots[0].children[0];
+        for link in objects.iter().flat_map(|o| &o.links) {
+            match &link.target {
+                LinkTarget::Object(name) => assert!(objects.iter().any(|o| &o.name == name), "{name}"),
+                LinkTarget::Node(path) => assert_eq!(path, &format!("{}/{}", roots[0].title, chapter.title)),
+            }
+        }
+        for name in &chapter.linked_objects {
+            assert!(objects.iter().any(|o| &o.name == name), "{name}");
+        }
+        let file = format!("Content/{}", chapter.file.as_deref().unwrap());
+        assert_eq!(file, SAMPLE_CHAPTER);
+
+        let headings: Vec<String> = parse_outline_entries(text(SAMPLE_CHAPTER)).into_iter().map(|e| e.title).collect();
+        assert_eq!(headings, ["第一章 雨夜", "旧巷", "来客"]);
+        assert!(text("Design/开始写作.md").starts_with("# 开始写作"));
+    }
+
+    #[test]
+    fn test_write_sample_project_keeps_existing_files() {
+        let dir = std::env::temp_dir().join(format!("tt_sample_project_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Design")).unwrap();
+        std::fs::write(dir.join("Design/世界对象.json"), "[]").unwrap();
+
+        let written = write_sample_project(&dir).unwrap();
+        assert_eq!(written.len(), 3);
+        assert!(!written.contains(&dir.join("Design/世界对象.json")));
+        assert_eq!(std::fs::read_to_string(dir.join("Design/世界对象.json")).unwrap(), "[]");
+        assert_eq!(std::fs::read_to_string(dir.join(SAMPLE_CHAPTER)).unwrap(), CHAPTER_TEMPLATE);
+        assert!(PROJECT_DIRS.iter().all(|d| dir.join(d).is_dir()));
+
+        assert!(write_sample_project(&dir).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,518 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+
+use crate::ignore::glob_match;
+use crate::models::civil_from_days;
+
+// ── 项目快照: the whole project as one zip archive ────────────────────────────
+//
+// The archive is written as a stream: every file is read and deflated in
+// chunks, with its CRC and sizes in a trailing data descriptor, so memory
+// use does not grow with the project.  Names are flagged UTF-8 so Chinese
+// file names survive.  There is no Zip64: a member or archive over 4 GiB is
+// refused.
+
+/// Folder of the 定期快照, relative to the project root.
+pub const SNAPSHOT_DIR: &str = ".texttool/snapshots";
+
+/// Always left out: recovery copies and the snapshots themselves.
+pub const BUILTIN_EXCLUDES: &[&str] = &[".texttool/recovery", SNAPSHOT_DIR];
+
+// ── Exclusion patterns ────────────────────────────────────────────────────────
+
+/// Whether one exclusion `pattern` matches the `/`-separated relative path
+/// `rel`.  A pattern without `/` (`*.tmp`, `.git`) matches any component of
+/// the path; one with `/` is anchored at the project root and matches the
+/// path or any folder above it.  A trailing `/` is ignored; blank lines and
+/// `#` comments match nothing.
+pub fn pattern_matches(pattern: &str, rel: &str) -> bool {
+    let pattern = pattern.trim();
+    if pattern.is_empty() || pattern.starts_with('#') {
+        return false;
+    }
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.contains('/');
+    let pattern: Vec<char> = pattern.trim_start_matches('/').chars().collect();
+    if anchored {
+        let text: Vec<char> = rel.chars().collect();
+        let ends = text.iter().enumerate()
+            .filter(|&(_, &c)| c == '/')
+            .map(|(i, _)| i)
+            .chain([text.len()]);
+        ends.into_iter().any(|end| glob_match(&pattern, &text[..end]))
+    } else {
+        rel.split('/').any(|part| glob_match(&pattern, &part.chars().collect::<Vec<_>>()))
+    }
+}
+
+/// Whether `rel` is left out of a snapshot by [`BUILTIN_EXCLUDES`] or by
+/// one of `patterns` (see [`pattern_matches`]).
+pub fn is_excluded(rel: &str, patterns: &[String]) -> bool {
+    BUILTIN_EXCLUDES.iter().copied()
+        .chain(patterns.iter().map(String::as_str))
+        .any(|p| pattern_matches(p, rel))
+}
+
+// ── Zip writing ───────────────────────────────────────────────────────────────
+
+/// Writer that counts the bytes passed through, for header offsets.
+struct Counter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for Counter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// One member, as listed again in the central directory.
+struct Entry {
+    name: String,
+    flags: u16,
+    method: u16,
+    time: u16,
+    date: u16,
+    crc: u32,
+    compressed: u32,
+    size: u32,
+    offset: u32,
+    directory: bool,
+}
+
+const FLAG_DESCRIPTOR: u16 = 1 << 3;
+const FLAG_UTF8: u16 = 1 << 11;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+const VERSION: u16 = 20;
+/// Copy buffer for streaming a file into the archive.
+const CHUNK: usize = 64 * 1024;
+
+fn too_large() -> io::Error {
+    io::Error::other("快照超过 4 GB 或文件过多，zip 格式无法写入")
+}
+
+fn fit_u32(n: u64) -> io::Result<u32> {
+    u32::try_from(n).map_err(|_| too_large())
+}
+
+/// MS-DOS time and date of `t` (UTC); before 1980, 1980-01-01.
+fn dos_datetime(t: Option<SystemTime>) -> (u16, u16) {
+    let secs = t.and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map_or(0, |d| d.as_secs() as i64);
+    let (y, m, d) = civil_from_days(secs.div_euclid(86_400));
+    if y < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let s = secs.rem_euclid(86_400);
+    let time = ((s / 3600) << 11) | ((s % 3600 / 60) << 5) | (s % 60 / 2);
+    let date = ((y.min(2107) - 1980) << 9) | (m << 5) | d;
+    (time as u16, date as u16)
+}
+
+/// Streaming zip archive writer (deflate, UTF-8 names, data descriptors).
+pub struct ZipWriter<W: Write> {
+    out: Counter<W>,
+    entries: Vec<Entry>,
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(out: W) -> Self {
+        ZipWriter { out: Counter { inner: out, count: 0 }, entries: Vec::new() }
+    }
+
+    fn local_header(&mut self, entry: &Entry) -> io::Result<()> {
+        let mut h = Vec::with_capacity(30 + entry.name.len());
+        h.extend(0x0403_4b50u32.to_le_bytes());
+        for v in [VERSION, entry.flags, entry.method, entry.time, entry.date] {
+            h.extend(v.to_le_bytes());
+        }
+        // CRC and sizes: zero here, in the data descriptor instead.
+        h.extend([0; 12]);
+        h.extend((entry.name.len() as u16).to_le_bytes());
+        h.extend(0u16.to_le_bytes());
+        h.extend(entry.name.as_bytes());
+        self.out.write_all(&h)
+    }
+
+    /// Deflate everything `reader` yields into a member `name` (a
+    /// `/`-separated path).  Returns the uncompressed size.
+    pub fn add_file(&mut self, name: &str, mut reader: impl Read, modified: Option<SystemTime>) -> io::Result<u64> {
+        let (time, date) = dos_datetime(modified);
+        let mut entry = Entry {
+            name: name.to_owned(),
+            flags: FLAG_DESCRIPTOR | FLAG_UTF8,
+            method: METHOD_DEFLATE,
+            time,
+            date,
+            crc: 0,
+            compressed: 0,
+            size: 0,
+            offset: fit_u32(self.out.count)?,
+            directory: false,
+        };
+        self.local_header(&entry)?;
+
+        let start = self.out.count;
+        let mut crc = Crc::new();
+        let mut size = 0u64;
+        let mut encoder = DeflateEncoder::new(&mut self.out, Compression::default());
+        let mut buf = vec![0; CHUNK];
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            crc.update(&buf[..n]);
+            size += n as u64;
+            encoder.write_all(&buf[..n])?;
+        }
+        encoder.finish()?;
+
+        entry.crc = crc.sum();
+        entry.compressed = fit_u32(self.out.count - start)?;
+        entry.size = fit_u32(size)?;
+        let mut d = Vec::with_capacity(16);
+        for v in [0x0807_4b50, entry.crc, entry.compressed, entry.size] {
+            d.extend(v.to_le_bytes());
+        }
+        self.out.write_all(&d)?;
+        self.entries.push(entry);
+        Ok(size)
+    }
+
+    /// An empty folder member; `name` gets a trailing `/` if missing.
+    pub fn add_directory(&mut self, name: &str, modified: Option<SystemTime>) -> io::Result<()> {
+        let (time, date) = dos_datetime(modified);
+        let name = if name.ends_with('/') { name.to_owned() } else { format!("{name}/") };
+        let entry = Entry {
+            name,
+            flags: FLAG_UTF8,
+            method: METHOD_STORED,
+            time,
+            date,
+            crc: 0,
+            compressed: 0,
+            size: 0,
+            offset: fit_u32(self.out.count)?,
+            directory: true,
+        };
+        self.local_header(&entry)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Write the central directory and return the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let start = fit_u32(self.out.count)?;
+        let count = u16::try_from(self.entries.len()).map_err(|_| too_large())?;
+        for e in std::mem::take(&mut self.entries) {
+            let mut h = Vec::with_capacity(46 + e.name.len());
+            h.extend(0x0201_4b50u32.to_le_bytes());
+            for v in [VERSION, VERSION, e.flags, e.method, e.time, e.date] {
+                h.extend(v.to_le_bytes());
+            }
+            for v in [e.crc, e.compressed, e.size] {
+                h.extend(v.to_le_bytes());
+            }
+            // Name length, extra, comment, disk number, internal attributes.
+            for v in [e.name.len() as u16, 0, 0, 0, 0] {
+                h.extend(v.to_le_bytes());
+            }
+            let external: u32 = if e.directory { 0x10 } else { 0 };
+            h.extend(external.to_le_bytes());
+            h.extend(e.offset.to_le_bytes());
+            h.extend(e.name.as_bytes());
+            self.out.write_all(&h)?;
+        }
+        let size = fit_u32(self.out.count - start as u64)?;
+        let mut end = Vec::with_capacity(22);
+        end.extend(0x0605_4b50u32.to_le_bytes());
+        for v in [0, 0, count, count] {
+            end.extend(v.to_le_bytes());
+        }
+        end.extend(size.to_le_bytes());
+        end.extend(start.to_le_bytes());
+        end.extend(0u16.to_le_bytes());
+        self.out.write_all(&end)?;
+        self.out.flush()?;
+        Ok(self.out.inner)
+    }
+}
+
+// ── Project snapshots ─────────────────────────────────────────────────────────
+
+/// Result of [`write_snapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotSummary {
+    pub files: usize,
+    /// Total size of the files before compression.
+    pub bytes: u64,
+}
+
+/// Files and folders of `root` to archive, as `(path, relative name)`;
+/// folders end in `/`.  Excluded folders are not descended into, symbolic
+/// links are skipped, and the order is sorted per folder.
+pub fn snapshot_entries(root: &Path, patterns: &[String]) -> io::Result<Vec<(PathBuf, String)>> {
+    fn walk(dir: &Path, prefix: &str, patterns: &[String], out: &mut Vec<(PathBuf, String)>) -> io::Result<()> {
+        let mut entries: Vec<_> = std::fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let rel = format!("{prefix}{}", entry.file_name().to_string_lossy());
+            let ty = entry.file_type()?;
+            if ty.is_symlink() || is_excluded(&rel, patterns) {
+                continue;
+            }
+            if ty.is_dir() {
+                out.push((entry.path(), format!("{rel}/")));
+                walk(&entry.path(), &format!("{rel}/"), patterns, out)?;
+            } else {
+                out.push((entry.path(), rel));
+            }
+        }
+        Ok(())
+    }
+    let mut out = Vec::new();
+    walk(root, "", patterns, &mut out)?;
+    Ok(out)
+}
+
+/// Zip the project at `root` into `out`, leaving out what
+/// [`is_excluded`].  Member names start with the project folder's name.
+/// `progress` is called with the entries done and the total after each.
+pub fn write_snapshot<W: Write>(
+    root: &Path,
+    out: W,
+    patterns: &[String],
+    mut progress: impl FnMut(usize, usize),
+) -> io::Result<(W, SnapshotSummary)> {
+    let entries = snapshot_entries(root, patterns)?;
+    let top = root.file_name().map(|n| format!("{}/", n.to_string_lossy())).unwrap_or_default();
+    let mut zip = ZipWriter::new(out);
+    let mut summary = SnapshotSummary { files: 0, bytes: 0 };
+    for (i, (path, rel)) in entries.iter().enumerate() {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if rel.ends_with('/') {
+            zip.add_directory(&format!("{top}{rel}"), modified)?;
+        } else {
+            summary.bytes += zip.add_file(&format!("{top}{rel}"), File::open(path)?, modified)?;
+            summary.files += 1;
+        }
+        progress(i + 1, entries.len());
+    }
+    Ok((zip.finish()?, summary))
+}
+
+/// File name of a snapshot of `project` taken at `local_secs` (Unix
+/// seconds shifted to local time): `书名_2024-03-01_213005.zip`.
+pub fn snapshot_name(project: &str, local_secs: i64) -> String {
+    let (y, m, d) = civil_from_days(local_secs.div_euclid(86_400));
+    let s = local_secs.rem_euclid(86_400);
+    format!("{project}_{y:04}-{m:02}-{d:02}_{:02}{:02}{:02}.zip", s / 3600, s % 3600 / 60, s % 60)
+}
+
+/// `.zip` files directly in `dir`, oldest name first.  Snapshot names of
+/// one project sort by time.
+fn snapshots_in(dir: &Path) -> Vec<PathBuf> {
+    let mut zips: Vec<PathBuf> = std::fs::read_dir(dir).into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "zip"))
+        .collect();
+    zips.sort();
+    zips
+}
+
+/// Whether `dir` holds a snapshot taken on `date` (`YYYY-MM-DD`).
+pub fn has_snapshot_on(dir: &Path, date: &str) -> bool {
+    let tag = format!("_{date}_");
+    snapshots_in(dir).iter()
+        .any(|p| p.file_name().is_some_and(|n| n.to_string_lossy().contains(&tag)))
+}
+
+/// Delete the oldest snapshots in `dir` beyond the newest `keep`.  Returns
+/// how many were deleted.
+pub fn rotate_snapshots(dir: &Path, keep: usize) -> io::Result<usize> {
+    let zips = snapshots_in(dir);
+    let excess = zips.len().saturating_sub(keep);
+    for path in &zips[..excess] {
+        std::fs::remove_file(path)?;
+    }
+    Ok(excess)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::DeflateDecoder;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn u16_at(b: &[u8], i: usize) -> u16 {
+        u16::from_le_bytes([b[i], b[i + 1]])
+    }
+
+    fn u32_at(b: &[u8], i: usize) -> u32 {
+        u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]])
+    }
+
+    /// Members of a zip archive by way of its central directory, each
+    /// inflated and checked against its CRC.
+    fn read_zip(b: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let end = b.len() - 22;
+        assert_eq!(u32_at(b, end), 0x0605_4b50);
+        let count = u16_at(b, end + 10) as usize;
+        let mut at = u32_at(b, end + 16) as usize;
+        let mut out = Vec::new();
+        for _ in 0..count {
+            assert_eq!(u32_at(b, at), 0x0201_4b50);
+            assert_ne!(u16_at(b, at + 8) & FLAG_UTF8, 0);
+            let method = u16_at(b, at + 10);
+            let crc = u32_at(b, at + 16);
+            let compressed = u32_at(b, at + 20) as usize;
+            let size = u32_at(b, at + 24) as usize;
+            let name_len = u16_at(b, at + 28) as usize;
+            let offset = u32_at(b, at + 42) as usize;
+            let name = String::from_utf8(b[at + 46..at + 46 + name_len].to_vec()).unwrap();
+            at += 46 + name_len;
+
+            assert_eq!(u32_at(b, offset), 0x0403_4b50);
+            let data = offset + 30 + u16_at(b, offset + 26) as usize + u16_at(b, offset + 28) as usize;
+            let raw = &b[data..data + compressed];
+            let mut content = Vec::new();
+            if method == METHOD_DEFLATE {
+                DeflateDecoder::new(raw).read_to_end(&mut content).unwrap();
+                // The data descriptor repeats CRC and sizes.
+                assert_eq!(u32_at(b, data + compressed), 0x0807_4b50);
+                assert_eq!(u32_at(b, data + compressed + 4), crc);
+            } else {
+                content.extend(raw);
+            }
+            assert_eq!(content.len(), size);
+            let mut check = Crc::new();
+            check.update(&content);
+            assert_eq!(check.sum(), crc);
+            out.push((name, content));
+        }
+        out
+    }
+
+    #[test]
+    fn test_pattern_matches() {
+        // Unanchored: any component.
+        assert!(pattern_matches("*.tmp", "Content/第一章.md.tmp"));
+        assert!(pattern_matches(".git", ".git/objects/ab"));
+        assert!(pattern_matches("草稿?", "废稿/草稿1/a.md"));
+        assert!(!pattern_matches("*.tmp", "Content/tmp/第一章.md"));
+        assert!(!pattern_matches("草稿?", "草稿12/a.md"));
+        // Anchored at the root, matching the path or a folder above it.
+        assert!(pattern_matches("/废稿", "废稿/旧/a.md"));
+        assert!(pattern_matches("Design/*.json", "Design/世界对象.json"));
+        assert!(!pattern_matches("Design/*.json", "Design/备份/世界对象.json"));
+        assert!(pattern_matches("Design/**/*.json", "Design/备份/世界对象.json"));
+        assert!(pattern_matches("Design/**/*.json", "Design/世界对象.json"));
+        assert!(pattern_matches("Content/第一卷/", "Content/第一卷"));
+        assert!(!pattern_matches("Content/第一", "Content/第一卷/a.md"));
+        // Blank lines and comments match nothing.
+        assert!(!pattern_matches("", "a.md"));
+        assert!(!pattern_matches("# *.md", "a.md"));
+
+        assert!(is_excluded(".texttool/recovery/manifest.json", &[]));
+        assert!(is_excluded(".texttool/snapshots", &[]));
+        assert!(!is_excluded(".texttool/state.json", &[]));
+        assert!(is_excluded("a.bak", &words(&["*.bak"])));
+    }
+
+    #[test]
+    fn test_zip_writer_roundtrip() {
+        let big: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let mut zip = ZipWriter::new(Vec::new());
+        assert_eq!(zip.add_file("书/第一章.md", "林远推门而入。".as_bytes(), None).unwrap(), 21);
+        zip.add_directory("书/空卷", None).unwrap();
+        assert_eq!(zip.add_file("书/big.bin", big.as_slice(), Some(SystemTime::now())).unwrap(), 200_000);
+        zip.add_file("书/empty.md", io::empty(), None).unwrap();
+        let bytes = zip.finish().unwrap();
+
+        let members = read_zip(&bytes);
+        let names: Vec<&str> = members.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["书/第一章.md", "书/空卷/", "书/big.bin", "书/empty.md"]);
+        assert_eq!(members[0].1, "林远推门而入。".as_bytes());
+        assert_eq!(members[2].1, big);
+        assert!(members[3].1.is_empty());
+        // Deflated, not stored.
+        assert!(bytes.len() < big.len());
+    }
+
+    #[test]
+    fn test_dos_datetime() {
+        // 2024-03-01 21:30:05 UTC.
+        let t = UNIX_EPOCH + std::time::Duration::from_secs(1_709_328_605);
+        let (time, date) = dos_datetime(Some(t));
+        assert_eq!((date >> 9, (date >> 5) & 0xf, date & 0x1f), (44, 3, 1));
+        assert_eq!((time >> 11, (time >> 5) & 0x3f, (time & 0x1f) * 2), (21, 30, 4));
+        assert_eq!(dos_datetime(None), (0, 33));
+    }
+
+    #[test]
+    fn test_write_snapshot_and_rotate() {
+        let base = std::env::temp_dir().join("qingmo_core_test_snapshot");
+        let _ = std::fs::remove_dir_all(&base);
+        let root = base.join("青云志");
+        for dir in ["Content/第一卷", "Content/空卷", ".texttool/recovery", ".texttool/snapshots", "cache"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("Content/第一卷/第一章.md"), "# 第一章\n").unwrap();
+        std::fs::write(root.join("Content/第一卷/第一章.md~"), "旧").unwrap();
+        std::fs::write(root.join(".texttool/recovery/x.md"), "恢复").unwrap();
+        std::fs::write(root.join(".texttool/snapshots/old.zip"), "").unwrap();
+        std::fs::write(root.join("cache/a"), "").unwrap();
+
+        let mut calls = Vec::new();
+        let (bytes, summary) = write_snapshot(&root, Vec::new(), &words(&["*~", "/cache"]), |d, t| calls.push((d, t)))
+            .unwrap();
+        let names: Vec<String> = read_zip(&bytes).into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, [
+            "青云志/.texttool/",
+            "青云志/Content/",
+            "青云志/Content/空卷/",
+            "青云志/Content/第一卷/",
+            "青云志/Content/第一卷/第一章.md",
+        ]);
+        assert_eq!(summary, SnapshotSummary { files: 1, bytes: "# 第一章\n".len() as u64 });
+        assert_eq!(calls.last(), Some(&(5, 5)));
+
+        // Names, the daily check and rotation.
+        assert_eq!(snapshot_name("青云志", 1_709_328_605), "青云志_2024-03-01_213005.zip");
+        let dir = root.join(SNAPSHOT_DIR);
+        for secs in [1_709_328_605, 1_709_415_005, 1_709_501_405] {
+            std::fs::write(dir.join(snapshot_name("青云志", secs)), "").unwrap();
+        }
+        assert!(has_snapshot_on(&dir, "2024-03-02"));
+        assert!(!has_snapshot_on(&dir, "2024-03-04"));
+        assert_eq!(rotate_snapshots(&dir, 2).unwrap(), 2);
+        assert_eq!(snapshots_in(&dir), [
+            dir.join("青云志_2024-03-02_213005.zip"),
+            dir.join("青云志_2024-03-03_213005.zip"),
+        ]);
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
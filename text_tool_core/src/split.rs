@@ -0,0 +1,236 @@
+use std::collections::BTreeSet;
+
+use crate::models::{StructKind, StructNode};
+use crate::outline::parse_outline_entries;
+
+// ── Split one Markdown file into per-heading files ───────────────────────────
+
+/// Title used for text before the first heading.
+pub const PREAMBLE_TITLE: &str = "前言";
+
+/// One file produced by [`split_by_headings`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitPart {
+    pub title: String,
+    /// Sanitised folder name (a level-1 heading) when splitting into folders.
+    pub folder: Option<String>,
+    /// Sanitised, de-duplicated file name including `.md`.
+    pub file_name: String,
+    /// 1-based line range `[start, end)` of the source covered by this part.
+    pub start_line: usize,
+    pub end_line: usize,
+    pub content: String,
+}
+
+impl SplitPart {
+    /// Path relative to the destination directory, `/`-separated.
+    pub fn relative_path(&self) -> String {
+        match &self.folder {
+            Some(dir) => format!("{dir}/{}", self.file_name),
+            None => self.file_name.clone(),
+        }
+    }
+}
+
+/// Cut `content` at its headings.
+///
+/// Without `folder_per_top`, every level-1 heading starts a new file.  With
+/// it, level-1 headings become folders and every level-2 heading starts a
+/// file inside the current folder; a level-1 section without level-2
+/// headings becomes a single file of the same name in its folder.
+///
+/// Heading lines stay in the files.  Blank-only stretches (such as the gap
+/// between a volume heading and its first chapter) produce no file.
+/// Headings inside fenced code blocks are ignored.
+pub fn split_by_headings(content: &str, folder_per_top: bool) -> Vec<SplitPart> {
+    let lines: Vec<&str> = content.lines().collect();
+    let total = lines.len() + 1;
+    let entries = parse_outline_entries(content);
+
+    // (title, folder, start_line) for every cut point.
+    let mut cuts: Vec<(String, Option<String>, usize)> = Vec::new();
+    let mut folder: Option<String> = None;
+    for e in &entries {
+        match (e.level, folder_per_top) {
+            (1, false) => cuts.push((e.title.clone(), None, e.line_no)),
+            (1, true) => {
+                let dir = sanitize_file_name(&e.title);
+                folder = Some(dir.clone());
+                cuts.push((e.title.clone(), Some(dir), e.line_no));
+            }
+            (2, true) => cuts.push((e.title.clone(), folder.clone(), e.line_no)),
+            _ => {}
+        }
+    }
+    let first = cuts.first().map(|c| c.2).unwrap_or(total);
+    if first > 1 {
+        cuts.insert(0, (PREAMBLE_TITLE.to_owned(), None, 1));
+    }
+
+    let mut used: BTreeSet<(Option<String>, String)> = BTreeSet::new();
+    let mut parts = Vec::new();
+    for (i, (title, folder, start)) in cuts.iter().enumerate() {
+        let end = cuts.get(i + 1).map(|c| c.2).unwrap_or(total);
+        let body = &lines[start - 1..end - 1];
+        // With folders, a level-1 heading line alone is not worth a file.
+        let only_heading = folder_per_top
+            && entries.iter().any(|e| e.level == 1 && e.line_no == *start)
+            && body.iter().skip(1).all(|l| l.trim().is_empty());
+        if body.iter().all(|l| l.trim().is_empty()) || only_heading {
+            continue;
+        }
+        let stem = sanitize_file_name(title);
+        let mut file_name = format!("{stem}.md");
+        let mut n = 2;
+        while !used.insert((folder.clone(), file_name.to_lowercase())) {
+            file_name = format!("{stem} ({n}).md");
+            n += 1;
+        }
+        let mut text = body.join("\n");
+        text.push('\n');
+        parts.push(SplitPart {
+            title: title.clone(),
+            folder: folder.clone(),
+            file_name,
+            start_line: *start,
+            end_line: end,
+            content: text,
+        });
+    }
+    parts
+}
+
+/// Turn a heading into a file name that is legal on Windows, macOS and
+/// Linux: illegal characters become `_`, trailing dots/spaces are dropped,
+/// reserved device names get a `_` suffix and the result is at most 80
+/// characters.  An empty result becomes `未命名`.
+pub fn sanitize_file_name(title: &str) -> String {
+    let cleaned: String = title.chars()
+        .map(|c| if c.is_control() || r#"/\:*?"<>|"#.contains(c) { '_' } else { c })
+        .take(80)
+        .collect();
+    let trimmed = cleaned.trim().trim_end_matches(['.', ' ']).trim_start();
+    if trimmed.is_empty() {
+        return "未命名".to_owned();
+    }
+    const RESERVED: &[&str] = &[
+        "CON", "PRN", "AUX", "NUL",
+        "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+        "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+    if RESERVED.iter().any(|r| r.eq_ignore_ascii_case(trimmed)) {
+        format!("{trimmed}_")
+    } else {
+        trimmed.to_owned()
+    }
+}
+
+/// Cut `content` in two before its 1-based line `line` (the heading where
+/// 拆分节点 starts the second file).  Each part ends with a newline unless
+/// it is empty; trailing blank lines of the first part are dropped.
+pub fn split_at_line(content: &str, line: usize) -> (String, String) {
+    let lines: Vec<&str> = content.lines().collect();
+    let at = line.saturating_sub(1).min(lines.len());
+    let join = |part: &[&str]| {
+        let text = part.join("\n");
+        let text = text.trim_end();
+        if text.is_empty() { String::new() } else { format!("{text}\n") }
+    };
+    (join(&lines[..at]), join(&lines[at..]))
+}
+
+/// Structure nodes matching the split files: folders become volumes,
+/// files chapters (titled by file stem so the chapter tree finds them).
+pub fn split_struct_nodes(parts: &[SplitPart]) -> Vec<StructNode> {
+    let mut roots: Vec<StructNode> = Vec::new();
+    let mut current_folder: Option<&str> = None;
+    for part in parts {
+        let stem = part.file_name.trim_end_matches(".md");
+        let chapter = StructNode::new(stem, StructKind::Chapter);
+        match part.folder.as_deref() {
+            Some(dir) => {
+                if current_folder != Some(dir) {
+                    roots.push(StructNode::new(dir, StructKind::Volume));
+                    current_folder = Some(dir);
+                }
+                if let Some(vol) = roots.last_mut() {
+                    vol.children.push(chapter);
+                }
+            }
+            None => {
+                current_folder = None;
+                roots.push(chapter);
+            }
+        }
+    }
+    roots
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_flat_with_preamble_and_duplicates() {
+        let md = "简介\n\n# 第一章\n正文一\n## 小节\n# 第一章\n正文二\n```\n# 代码\n```\n";
+        let parts = split_by_headings(md, false);
+        let names: Vec<&str> = parts.iter().map(|p| p.file_name.as_str()).collect();
+        assert_eq!(names, vec!["前言.md", "第一章.md", "第一章 (2).md"]);
+        assert_eq!((parts[0].start_line, parts[0].end_line), (1, 3));
+        assert_eq!(parts[0].content, "简介\n\n");
+        assert_eq!((parts[1].start_line, parts[1].end_line), (3, 6));
+        assert_eq!(parts[1].content, "# 第一章\n正文一\n## 小节\n");
+        // The heading inside the code fence does not cut.
+        assert_eq!(parts[2].content, "# 第一章\n正文二\n```\n# 代码\n```\n");
+    }
+
+    #[test]
+    fn test_split_into_folders() {
+        let md = "# 第一卷\n\n## 第一章\n甲\n## 第二章\n乙\n# 第二卷\n丙\n";
+        let parts = split_by_headings(md, true);
+        let paths: Vec<String> = parts.iter().map(|p| p.relative_path()).collect();
+        assert_eq!(paths, vec!["第一卷/第一章.md", "第一卷/第二章.md", "第二卷/第二卷.md"]);
+
+        let nodes = split_struct_nodes(&parts);
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].kind, StructKind::Volume);
+        assert_eq!(nodes[0].children.len(), 2);
+        assert_eq!(nodes[0].children[1].title, "第二章");
+        assert_eq!(nodes[1].children[0].title, "第二卷");
+    }
+
+    #[test]
+    fn test_split_duplicates_are_per_folder() {
+        let md = "# 甲卷\n## 序\nx\n# 乙卷\n## 序\ny\n## 序\nz\n";
+        let paths: Vec<String> = split_by_headings(md, true).iter().map(|p| p.relative_path()).collect();
+        assert_eq!(paths, vec!["甲卷/序.md", "乙卷/序.md", "乙卷/序 (2).md"]);
+    }
+
+    #[test]
+    fn test_split_without_headings() {
+        assert_eq!(split_by_headings("只有正文", false)[0].title, PREAMBLE_TITLE);
+        assert!(split_by_headings("\n\n", false).is_empty());
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("第1章：雨/夜?"), "第1章：雨_夜_");
+        assert_eq!(sanitize_file_name("  尾声... "), "尾声");
+        assert_eq!(sanitize_file_name("a<b>c|d\"e*f"), "a_b_c_d_e_f");
+        assert_eq!(sanitize_file_name("con"), "con_");
+        assert_eq!(sanitize_file_name("..."), "未命名");
+        assert_eq!(sanitize_file_name(&"长".repeat(100)).chars().count(), 80);
+    }
+
+    #[test]
+    fn test_split_at_line() {
+        let text = "# 第一章\n\n上半。\n\n## 转折\n\n下半。\n";
+        let (first, second) = split_at_line(text, 5);
+        assert_eq!(first, "# 第一章\n\n上半。\n");
+        assert_eq!(second, "## 转折\n\n下半。\n");
+        assert_eq!(split_at_line(text, 1), (String::new(), text.to_owned()));
+        assert_eq!(split_at_line(text, 99), (text.to_owned(), String::new()));
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::models::{parse_day, AutoDone, ChapterTag, NodeStage, StructKind, StructNode};
+
+// ── Structure statistics ──────────────────────────────────────────────────────
+//
+// Tree-wide aggregations behind the structure panel's 统计 window.  Each is a
+// pure function over the roots; `StructStats::collect` runs them all.
+
+fn walk<'a>(nodes: &'a [StructNode], f: &mut impl FnMut(&'a StructNode)) {
+    for n in nodes {
+        f(n);
+        walk(&n.children, f);
+    }
+}
+
+fn leaves(roots: &[StructNode]) -> Vec<&StructNode> {
+    roots.iter().flat_map(StructNode::leaves).collect()
+}
+
+/// Number of nodes of each kind, in [`StructKind::all`] order.
+pub fn kind_counts(roots: &[StructNode]) -> Vec<(StructKind, usize)> {
+    let mut counts = vec![0; StructKind::all().len()];
+    walk(roots, &mut |n| counts[n.kind.rank()] += 1);
+    StructKind::all().iter().cloned().zip(counts).collect()
+}
+
+/// Done / total leaves of one volume.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeProgress {
+    pub title: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+impl VolumeProgress {
+    /// Done fraction in `0.0..=1.0`; `0.0` for an empty volume.
+    pub fn ratio(&self) -> f32 {
+        if self.total == 0 { 0.0 } else { self.done as f32 / self.total as f32 }
+    }
+}
+
+/// Progress of every 卷 node, in tree order.
+pub fn volume_progress(roots: &[StructNode]) -> Vec<VolumeProgress> {
+    let mut out = Vec::new();
+    walk(roots, &mut |n| {
+        if n.kind == StructKind::Volume {
+            out.push(VolumeProgress { title: n.title.clone(), done: n.done_count(), total: n.leaf_count() });
+        }
+    });
+    out
+}
+
+/// Number of leaves with each tag, in [`ChapterTag::all`] order.  Only leaves
+/// count, so volumes do not inflate 普通.
+pub fn tag_counts(roots: &[StructNode]) -> Vec<(ChapterTag, usize)> {
+    let leaves = leaves(roots);
+    ChapterTag::all().iter()
+        .map(|tag| (tag.clone(), leaves.iter().filter(|n| n.tag == *tag).count()))
+        .collect()
+}
+
+/// Mean and median summary length in characters, over nodes that have a
+/// summary.  `None` when no node has one.
+pub fn summary_lengths(roots: &[StructNode]) -> Option<(f32, f32)> {
+    let mut lens = Vec::new();
+    walk(roots, &mut |n| {
+        let len = n.summary.trim().chars().count();
+        if len > 0 {
+            lens.push(len);
+        }
+    });
+    if lens.is_empty() {
+        return None;
+    }
+    lens.sort_unstable();
+    let mean = lens.iter().sum::<usize>() as f32 / lens.len() as f32;
+    let mid = lens.len() / 2;
+    let median = if lens.len() % 2 == 0 {
+        (lens[mid - 1] + lens[mid]) as f32 / 2.0
+    } else {
+        lens[mid] as f32
+    };
+    Some((mean, median))
+}
+
+/// Titles of leaves without any linked object.
+pub fn unlinked_nodes(roots: &[StructNode]) -> Vec<String> {
+    leaves(roots).into_iter()
+        .filter(|n| n.linked_objects.is_empty())
+        .map(|n| n.title.clone())
+        .collect()
+}
+
+/// Total words of the leaves that have a chapter file.  `word_counts` maps
+/// lowercase file stems to word counts, as for
+/// [`build_progress_report`](crate::report::build_progress_report).
+pub fn written_words(roots: &[StructNode], word_counts: &BTreeMap<String, usize>) -> usize {
+    leaves(roots).into_iter()
+        .filter_map(|n| word_counts.get(&n.file_key()))
+        .sum()
+}
+
+/// All statistics of the 统计 window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructStats {
+    pub kinds: Vec<(StructKind, usize)>,
+    pub volumes: Vec<VolumeProgress>,
+    pub tags: Vec<(ChapterTag, usize)>,
+    /// Mean and median summary length, see [`summary_lengths`].
+    pub summary: Option<(f32, f32)>,
+    pub unlinked: Vec<String>,
+    pub words: usize,
+}
+
+impl StructStats {
+    pub fn collect(roots: &[StructNode], word_counts: &BTreeMap<String, usize>) -> Self {
+        StructStats {
+            kinds: kind_counts(roots),
+            volumes: volume_progress(roots),
+            tags: tag_counts(roots),
+            summary: summary_lengths(roots),
+            unlinked: unlinked_nodes(roots),
+            words: written_words(roots, word_counts),
+        }
+    }
+
+    /// Markdown rendering for the clipboard.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# 章节结构统计\n\n");
+        let _ = writeln!(out, "## 节点数量\n");
+        for (kind, n) in &self.kinds {
+            let _ = writeln!(out, "- {} {}：{n}", kind.icon(), kind.label());
+        }
+        let _ = writeln!(out, "\n## 分卷进度\n");
+        if self.volumes.is_empty() {
+            let _ = writeln!(out, "（无分卷）");
+        }
+        for v in &self.volumes {
+            let _ = writeln!(out, "- {}：{} / {}（{:.0}%）", v.title, v.done, v.total, v.ratio() * 100.0);
+        }
+        let _ = writeln!(out, "\n## 标签分布\n");
+        for (tag, n) in &self.tags {
+            let _ = writeln!(out, "- {}：{n}", tag.label());
+        }
+        let _ = writeln!(out, "\n## 概要长度\n");
+        match self.summary {
+            Some((mean, median)) => {
+                let _ = writeln!(out, "- 平均：{mean:.1} 字\n- 中位数：{median:.1} 字");
+            }
+            None => { let _ = writeln!(out, "（暂无概要）"); }
+        }
+        let _ = writeln!(out, "\n## 未关联对象的章节（{}）\n", self.unlinked.len());
+        for title in &self.unlinked {
+            let _ = writeln!(out, "- {title}");
+        }
+        let _ = writeln!(out, "\n## 字数\n");
+        let _ = writeln!(out, "- 已写：{}", self.words);
+        out
+    }
+}
+
+// ── 计划 vs 实际 ──────────────────────────────────────────────────────────────
+//
+// The burndown of the 统计 window: words planned by each node's 截止日期
+// against words actually written per day (`Design/写作统计.json`).
+
+/// Longest span [`burndown`] covers, so a mistyped year cannot produce
+/// millions of points.
+pub const BURNDOWN_MAX_DAYS: i64 = 3660;
+
+/// Words due on each day (day numbers): the 目标字数 of every node with
+/// both a target and a valid 截止日期.  Below a node that counts, its
+/// descendants are not counted again — a volume's own goal covers its
+/// chapters.
+pub fn planned_by_day(roots: &[StructNode]) -> BTreeMap<i64, usize> {
+    fn go(nodes: &[StructNode], out: &mut BTreeMap<i64, usize>) {
+        for n in nodes {
+            match (n.target_words, n.due_date.as_deref().and_then(parse_day)) {
+                (Some(target), Some(day)) => *out.entry(day).or_insert(0) += target,
+                _ => go(&n.children, out),
+            }
+        }
+    }
+    let mut out = BTreeMap::new();
+    go(roots, &mut out);
+    out
+}
+
+/// One day of the 计划 vs 实际 chart, both values cumulative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BurndownPoint {
+    pub day: i64,
+    pub planned: usize,
+    /// `None` after `today`.
+    pub written: Option<i64>,
+}
+
+/// Day-by-day cumulative plan and words written, from the first day with
+/// either up to the last due date or `today`, whichever is later (at most
+/// [`BURNDOWN_MAX_DAYS`]).  `daily` holds net words per `YYYY-MM-DD`, as
+/// [`WritingStats::daily_words`](crate::models::WritingStats::daily_words).
+/// Empty while nothing has a due date.
+pub fn burndown(roots: &[StructNode], daily: &BTreeMap<String, i64>, today: i64) -> Vec<BurndownPoint> {
+    let planned = planned_by_day(roots);
+    let (Some(&first_due), Some(&last_due)) = (planned.keys().next(), planned.keys().next_back()) else {
+        return Vec::new();
+    };
+    let written: BTreeMap<i64, i64> = daily.iter()
+        .filter_map(|(date, &n)| Some((parse_day(date)?, n)))
+        .collect();
+    let start = written.keys().next().map_or(first_due, |&d| d.min(first_due)).min(today);
+    let end = last_due.max(today).min(start + BURNDOWN_MAX_DAYS);
+
+    let mut plan_sum = 0;
+    let mut written_sum = 0;
+    (start..=end)
+        .map(|day| {
+            plan_sum += planned.get(&day).copied().unwrap_or(0);
+            written_sum += written.get(&day).copied().unwrap_or(0);
+            BurndownPoint { day, planned: plan_sum, written: (day <= today).then_some(written_sum) }
+        })
+        .collect()
+}
+
+/// Day the plan's total is reached at the pace of the last `window` days
+/// up to `today`; `None` without progress in that window.  Today itself
+/// when the total is already reached.
+pub fn projected_finish(points: &[BurndownPoint], today: i64, window: i64) -> Option<i64> {
+    let total = points.last()?.planned as i64;
+    let at = |day: i64| points.iter().rev().find(|p| p.day <= day).and_then(|p| p.written).unwrap_or(0);
+    let now = at(today);
+    if now >= total {
+        return Some(today);
+    }
+    let gained = now - at(today - window);
+    if gained <= 0 {
+        return None;
+    }
+    // Ceiling division: the day the remaining words are in.
+    Some(today + ((total - now) * window + gained - 1) / gained)
+}
+
+// ── 达到目标字数自动标记完成 ──────────────────────────────────────────────────
+
+/// Words below which [`auto_done_update`] un-marks a done node with
+/// `target`: `margin_percent` under it, rounded down.
+pub fn unmark_below(target: usize, margin_percent: u32) -> usize {
+    target - target * margin_percent.min(100) as usize / 100
+}
+
+/// The `done` state `rule` gives `node`, when it differs from the current
+/// one.  Only nodes with a non-zero 目标字数 whose chapter file is in
+/// `counts` (keyed like [`StructNode::file_key`]) take part.  A node is
+/// marked at the target and, with [`AutoDone::unmark`], un-marked only
+/// below [`unmark_below`], so small edits around the target change nothing.
+pub fn auto_done_update(node: &StructNode, counts: &BTreeMap<String, usize>, rule: &AutoDone) -> Option<bool> {
+    if !rule.enabled {
+        return None;
+    }
+    let target = node.target_words.filter(|&t| t > 0)?;
+    let words = *counts.get(&node.file_key())?;
+    if !node.done && words >= target {
+        Some(true)
+    } else if node.done && rule.unmark && words < unmark_below(target, rule.margin_percent) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Apply [`auto_done_update`] to every node under `roots` except those
+/// `manual` reports as set by hand.  Returns the titles changed with their
+/// new state, in tree order.
+pub fn apply_auto_done(
+    roots: &mut [StructNode],
+    counts: &BTreeMap<String, usize>,
+    rule: &AutoDone,
+    manual: &impl Fn(&StructNode) -> bool,
+) -> Vec<(String, bool)> {
+    let mut changed = Vec::new();
+    for node in roots {
+        if !manual(node) {
+            if let Some(done) = auto_done_update(node, counts, rule) {
+                node.set_stage(if done { NodeStage::Done } else { NodeStage::Drafting });
+                changed.push((node.title.clone(), done));
+            }
+        }
+        changed.extend(apply_auto_done(&mut node.children, counts, rule, manual));
+    }
+    changed
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<StructNode> {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        let mut c1 = StructNode::new("第一章", StructKind::Chapter);
+        c1.done = true;
+        c1.tag = ChapterTag::Climax;
+        c1.summary = "雨夜相遇".into();
+        c1.linked_objects.push("林枫".into());
+        let mut c2 = StructNode::new("第二章", StructKind::Chapter);
+        c2.tag = ChapterTag::Transition;
+        c2.summary = "  出城  ".into();
+        let mut c3 = StructNode::new("第三章", StructKind::Chapter);
+        c3.summary = "山门拜师，初见掌门".into();
+        vol.children = vec![c1, c2, c3];
+        let empty = StructNode::new("第二卷", StructKind::Volume);
+        vec![vol, empty]
+    }
+
+    #[test]
+    fn test_kind_and_tag_counts() {
+        let roots = sample();
+        let kinds = kind_counts(&roots);
+        assert_eq!(kinds.len(), StructKind::all().len());
+        assert!(kinds.contains(&(StructKind::Volume, 2)));
+        assert!(kinds.contains(&(StructKind::Chapter, 3)));
+        assert!(kinds.contains(&(StructKind::Scene, 0)));
+
+        // The empty volume is a leaf tagged 普通.
+        let tags = tag_counts(&roots);
+        assert_eq!(tags, vec![
+            (ChapterTag::Normal, 2),
+            (ChapterTag::Climax, 1),
+            (ChapterTag::Foreshadow, 0),
+            (ChapterTag::Transition, 1),
+        ]);
+    }
+
+    #[test]
+    fn test_volume_progress() {
+        let vols = volume_progress(&sample());
+        assert_eq!(vols[0], VolumeProgress { title: "第一卷".into(), done: 1, total: 3 });
+        assert!((vols[0].ratio() - 1.0 / 3.0).abs() < 1e-6);
+        // An empty volume is its own (undone) leaf.
+        assert_eq!(vols[1].total, 1);
+        assert_eq!(vols[1].ratio(), 0.0);
+        assert_eq!(VolumeProgress { title: String::new(), done: 0, total: 0 }.ratio(), 0.0);
+    }
+
+    #[test]
+    fn test_summary_lengths() {
+        // Lengths 4, 2, 9: mean 5, median 4.
+        let (mean, median) = summary_lengths(&sample()).unwrap();
+        assert!((mean - 5.0).abs() < 1e-6);
+        assert_eq!(median, 4.0);
+
+        let mut roots = sample();
+        roots[1].summary = "一二三四五六".into();
+        // Lengths 2, 4, 6, 9: median between 4 and 6.
+        assert_eq!(summary_lengths(&roots).unwrap().1, 5.0);
+
+        assert_eq!(summary_lengths(&[StructNode::new("空", StructKind::Chapter)]), None);
+    }
+
+    #[test]
+    fn test_unlinked_and_words() {
+        let roots = sample();
+        assert_eq!(unlinked_nodes(&roots), vec!["第二章", "第三章", "第二卷"]);
+
+        let counts = BTreeMap::from([("第一章".to_owned(), 3000), ("第三章".to_owned(), 1200)]);
+        assert_eq!(written_words(&roots, &counts), 4200);
+
+        let stats = StructStats::collect(&roots, &counts);
+        let md = stats.to_markdown();
+        assert!(md.contains("- 第一卷：1 / 3（33%）"));
+        assert!(md.contains("- 高潮：1"));
+        assert!(md.contains("- 中位数：4.0 字"));
+        assert!(md.contains("## 未关联对象的章节（3）"));
+        assert!(md.contains("- 已写：4200"));
+    }
+
+    #[test]
+    fn test_parse_day() {
+        use crate::models::format_day;
+        for day in [-1, 0, 19723, 19782, 20_000] {
+            assert_eq!(parse_day(&format_day(day)), Some(day));
+        }
+        assert_eq!(parse_day(" 2024-3-1 "), parse_day("2024-03-01"));
+        assert_eq!(parse_day("2023-02-29"), None);
+        assert_eq!(parse_day("2024-13-01"), None);
+        assert_eq!(parse_day("明天"), None);
+    }
+
+    #[test]
+    fn test_burndown() {
+        // 2024-03-01 is day 19783.
+        let day = |s: &str| parse_day(s).unwrap();
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        let mut c1 = StructNode::new("第一章", StructKind::Chapter);
+        c1.target_words = Some(3000);
+        c1.due_date = Some("2024-03-02".into());
+        let mut c2 = StructNode::new("第二章", StructKind::Chapter);
+        c2.target_words = Some(2000);
+        c2.due_date = Some("2024-03-04".into());
+        // No due date, or an invalid one: not planned.
+        let mut c3 = StructNode::new("第三章", StructKind::Chapter);
+        c3.target_words = Some(9999);
+        let mut c4 = StructNode::new("第四章", StructKind::Chapter);
+        c4.target_words = Some(9999);
+        c4.due_date = Some("2024-02-30".into());
+        vol.children = vec![c1, c2, c3, c4];
+        // A volume goal covers its chapters.
+        let mut vol2 = StructNode::new("第二卷", StructKind::Volume);
+        vol2.target_words = Some(10_000);
+        vol2.due_date = Some("2024-03-04".into());
+        let mut c5 = StructNode::new("第五章", StructKind::Chapter);
+        c5.target_words = Some(500);
+        c5.due_date = Some("2024-03-03".into());
+        vol2.children.push(c5);
+        let roots = vec![vol, vol2];
+
+        assert_eq!(planned_by_day(&roots), BTreeMap::from([(day("2024-03-02"), 3000), (day("2024-03-04"), 12_000)]));
+
+        let daily = BTreeMap::from([
+            ("2024-02-29".to_owned(), 1000),
+            ("2024-03-02".to_owned(), 2500),
+            ("2024-03-03".to_owned(), -200),
+            ("坏日期".to_owned(), 50),
+        ]);
+        let points = burndown(&roots, &daily, day("2024-03-03"));
+        let rows: Vec<(usize, Option<i64>)> = points.iter().map(|p| (p.planned, p.written)).collect();
+        assert_eq!(points[0].day, day("2024-02-29"));
+        assert_eq!(rows, [
+            (0, Some(1000)),
+            (0, Some(1000)),
+            (3000, Some(3500)),
+            (3000, Some(3300)),
+            (15_000, None),
+        ]);
+
+        // 2300 words over the last 2 days; 11 700 to go → 11 more days.
+        assert_eq!(projected_finish(&points, day("2024-03-03"), 2), Some(day("2024-03-14")));
+        assert_eq!(projected_finish(&points, day("2024-03-03"), 1), None);
+
+        assert!(burndown(&sample(), &daily, 0).is_empty());
+    }
+
+    #[test]
+    fn test_auto_done_hysteresis() {
+        let rule = AutoDone { enabled: true, unmark: true, margin_percent: 10 };
+        let mut node = StructNode::new("第一章", StructKind::Chapter);
+        node.target_words = Some(1000);
+        let counts = |words: usize| BTreeMap::from([("第一章".to_owned(), words)]);
+        assert_eq!(unmark_below(1000, 10), 900);
+
+        // Marked at the target, not before.
+        assert_eq!(auto_done_update(&node, &counts(999), &rule), None);
+        assert_eq!(auto_done_update(&node, &counts(1000), &rule), Some(true));
+
+        // Once done, small cuts keep it done; cuts past the margin un-mark it.
+        node.done = true;
+        assert_eq!(auto_done_update(&node, &counts(950), &rule), None);
+        assert_eq!(auto_done_update(&node, &counts(900), &rule), None);
+        assert_eq!(auto_done_update(&node, &counts(899), &rule), Some(false));
+        let keep = AutoDone { unmark: false, ..rule };
+        assert_eq!(auto_done_update(&node, &counts(10), &keep), None);
+
+        // Off, without a target or without a file: nothing.
+        node.done = false;
+        assert_eq!(auto_done_update(&node, &counts(5000), &AutoDone { enabled: false, ..rule }), None);
+        assert_eq!(auto_done_update(&node, &BTreeMap::new(), &rule), None);
+        node.target_words = Some(0);
+        assert_eq!(auto_done_update(&node, &counts(5000), &rule), None);
+    }
+
+    #[test]
+    fn test_apply_auto_done_skips_manual() {
+        let rule = AutoDone { enabled: true, ..AutoDone::default() };
+        let mut roots = sample();
+        for c in &mut roots[0].children {
+            c.target_words = Some(100);
+        }
+        let counts = BTreeMap::from([
+            ("第一章".to_owned(), 10),
+            ("第二章".to_owned(), 150),
+            ("第三章".to_owned(), 200),
+        ]);
+        let changed = apply_auto_done(&mut roots, &counts, &rule, &|n: &StructNode| n.title == "第三章");
+        assert_eq!(changed, vec![("第二章".to_owned(), true)]);
+        let chapters = &roots[0].children;
+        // 第一章 stays done: un-marking is off by default.
+        assert!(chapters[0].done && chapters[1].done && !chapters[2].done);
+        assert_eq!(chapters[1].stage(), NodeStage::Done);
+    }
+}
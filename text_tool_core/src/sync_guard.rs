@@ -0,0 +1,105 @@
+// ── Overwrite guard for the design JSON files ────────────────────────────────
+//
+// Writing the Objects or Structure panel over `Design/*.json` replaces the
+// whole file.  When the panel holds nothing, or far fewer entries than the
+// file, that is far more likely a session that never loaded the file than a
+// deliberate deletion, so the app asks first.  Counting only needs the
+// top-level array entries, so the file is scanned rather than deserialized.
+
+/// Below this share of the file's entries (in percent) a write needs
+/// confirmation.
+pub const SHRINK_LIMIT_PERCENT: usize = 20;
+
+/// Number of entries of the top-level JSON array in `text`, without
+/// parsing them.  `None` when `text` is not a complete array (empty file,
+/// an object, cut off mid-way, unbalanced brackets).
+pub fn json_array_len(text: &str) -> Option<usize> {
+    let body = text.trim_start_matches('\u{feff}').trim();
+    let inner = body.strip_prefix('[')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut commas = 0usize;
+    let mut any = false;
+    for (i, c) in inner.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '[' | '{' => depth += 1,
+            ']' | '}' if depth > 0 => depth -= 1,
+            ']' => {
+                // The closing bracket of the top-level array must end the text.
+                return inner[i + 1..].trim().is_empty().then_some(if any { commas + 1 } else { 0 });
+            }
+            '}' => return None,
+            ',' if depth == 0 => commas += 1,
+            _ => {}
+        }
+        if !(c.is_whitespace() || (c == ',' && depth == 0)) {
+            any = true;
+        }
+    }
+    None
+}
+
+/// Whether writing `new_len` entries over a file holding `existing` ones
+/// (`None`: missing or unreadable) should be confirmed first: the panel is
+/// empty while the file is not, or holds less than [`SHRINK_LIMIT_PERCENT`]
+/// of the file's entries.
+pub fn needs_overwrite_confirm(existing: Option<usize>, new_len: usize) -> bool {
+    match existing {
+        Some(existing) if existing > 0 => new_len == 0 || new_len * 100 < existing * SHRINK_LIMIT_PERCENT,
+        _ => false,
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_array_len() {
+        assert_eq!(json_array_len("[]"), Some(0));
+        assert_eq!(json_array_len("  [ \n ]\n"), Some(0));
+        assert_eq!(json_array_len("\u{feff}[1, 2, 3]"), Some(3));
+        // Commas and brackets inside entries and strings do not count.
+        let text = r#"[
+  {"title": "第一章, [上]", "children": [{"title": "a"}, {"title": "b"}]},
+  {"title": "引号 \" 与 \\", "tags": ["x", "y"]}
+]"#;
+        assert_eq!(json_array_len(text), Some(2));
+        assert_eq!(json_array_len(&serde_json::to_string_pretty(&vec![vec![1, 2]; 7]).unwrap()), Some(7));
+
+        // Corrupt or not an array.
+        assert_eq!(json_array_len(""), None);
+        assert_eq!(json_array_len("{\"a\": 1}"), None);
+        assert_eq!(json_array_len("[{\"a\": 1}, {\"b\":"), None);
+        assert_eq!(json_array_len("[1, 2]]"), None);
+        assert_eq!(json_array_len("[1, 2} "), None);
+        assert_eq!(json_array_len("[\"unterminated]"), None);
+    }
+
+    #[test]
+    fn test_needs_overwrite_confirm() {
+        // Empty panel over a non-empty file.
+        assert!(needs_overwrite_confirm(Some(12), 0));
+        // Much smaller: 1 of 10 is below 20 %, 2 of 10 is not.
+        assert!(needs_overwrite_confirm(Some(10), 1));
+        assert!(!needs_overwrite_confirm(Some(10), 2));
+        assert!(!needs_overwrite_confirm(Some(3), 5));
+        // Empty, missing or corrupt file: nothing to protect that can be counted.
+        assert!(!needs_overwrite_confirm(Some(0), 0));
+        assert!(!needs_overwrite_confirm(None, 0));
+        assert!(!needs_overwrite_confirm(json_array_len("[{\"title\":"), 0));
+    }
+}
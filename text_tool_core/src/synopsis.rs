@@ -0,0 +1,194 @@
+use crate::models::{count_words, StructNode};
+
+// ── 梗概: a subtree's summaries as prose ─────────────────────────────────────
+//
+// Agents and editors ask for a synopsis of a page or two.  It is built from
+// the summaries of a structure node and its descendants (never from the
+// manuscript), in tree order: the node's own summary opens, then each child
+// subtree becomes one paragraph.
+
+/// Characters that end a sentence.
+const SENTENCE_END: [char; 6] = ['。', '！', '？', '!', '?', '…'];
+
+/// Closing marks that stay with the sentence before them.
+const CLOSERS: [char; 6] = ['”', '」', '』', '）', ')', '…'];
+
+/// Punctuation after which lines of a summary join without a comma.
+const JOINING: [char; 4] = ['，', '；', '、', '：'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SynopsisOptions {
+    /// Lead each summary below the node with its title: `第一章：…`.
+    pub titles: bool,
+    /// Length aimed at, in 字 ([`count_words`]).
+    pub target_words: usize,
+}
+
+impl Default for SynopsisOptions {
+    fn default() -> Self {
+        SynopsisOptions { titles: false, target_words: 1500 }
+    }
+}
+
+/// The summaries of `node` and its descendants as paragraphs separated by
+/// blank lines.  Nodes without a summary are skipped.
+pub fn build_synopsis(node: &StructNode, opts: &SynopsisOptions) -> String {
+    let mut paragraphs = Vec::new();
+    let own = summary_sentences(&node.summary);
+    if !own.is_empty() {
+        paragraphs.push(own);
+    }
+    for child in &node.children {
+        let mut para = String::new();
+        collect(child, opts, &mut para);
+        if !para.is_empty() {
+            paragraphs.push(para);
+        }
+    }
+    paragraphs.join("\n\n")
+}
+
+fn collect(node: &StructNode, opts: &SynopsisOptions, out: &mut String) {
+    let text = summary_sentences(&node.summary);
+    if !text.is_empty() {
+        if opts.titles {
+            out.push_str(node.title.trim());
+            out.push('：');
+        }
+        out.push_str(&text);
+    }
+    for child in &node.children {
+        collect(child, opts, out);
+    }
+}
+
+/// One summary as running text: its lines joined with `，` (or directly
+/// after punctuation), closed with `。` unless it already ends a sentence.
+fn summary_sentences(summary: &str) -> String {
+    let mut out = String::new();
+    for line in summary.lines().map(|l| l.trim().trim_start_matches(['-', '*', '•']).trim()) {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(last) = out.chars().last() {
+            if !ends_sentence(last) && !JOINING.contains(&last) {
+                out.push('，');
+            }
+        }
+        out.push_str(line);
+    }
+    if out.chars().last().is_some_and(|c| !ends_sentence(c)) {
+        out.push('。');
+    }
+    out
+}
+
+fn ends_sentence(c: char) -> bool {
+    SENTENCE_END.contains(&c) || CLOSERS.contains(&c)
+}
+
+/// `text` cut to at most `max_words` 字, dropping whole sentences from the
+/// end.  A first sentence longer than that is cut mid-way and ends in `…`.
+pub fn trim_synopsis(text: &str, max_words: usize) -> String {
+    if count_words(text) <= max_words {
+        return text.to_owned();
+    }
+    let finish = |cut: Option<usize>, at: usize| match cut {
+        Some(end) => text[..end].trim_end().to_owned(),
+        None => format!("{}…", text[..at].trim_end()),
+    };
+    let mut words = 0;
+    let mut cut = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !c.is_whitespace() {
+            words += 1;
+            if words > max_words {
+                return finish(cut, i);
+            }
+        }
+        if SENTENCE_END.contains(&c) {
+            // Closing quotes and a doubled `……` belong to the sentence.
+            let mut end = i + c.len_utf8();
+            while let Some((j, next)) = chars.next_if(|(_, n)| CLOSERS.contains(n)) {
+                words += 1;
+                if words > max_words {
+                    return finish(cut, j);
+                }
+                end = j + next.len_utf8();
+            }
+            cut = Some(end);
+        }
+    }
+    text.to_owned()
+}
+
+/// Prompt for the optional LLM step: compress `synopsis` to about
+/// `target_words` 字.
+pub fn compress_prompt(synopsis: &str, target_words: usize) -> String {
+    format!(
+        "请将下面的故事梗概压缩到约 {target_words} 字。保留主线情节、主要人物及其动机和结局，\
+         用连贯的段落叙述，不要添加梗概中没有的情节，只输出压缩后的梗概：\n\n{synopsis}"
+    )
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StructKind;
+
+    fn node(title: &str, kind: StructKind, summary: &str) -> StructNode {
+        let mut n = StructNode::new(title, kind);
+        n.summary = summary.into();
+        n
+    }
+
+    fn volume() -> StructNode {
+        let mut vol = node("第一卷", StructKind::Volume, "少年离乡，踏上寻父之路");
+        let mut c1 = node("第一章", StructKind::Chapter, "雨夜相遇\n- 埋下玉佩");
+        c1.children.push(node("追兵", StructKind::Section, "二人被追至江边！"));
+        vol.children.push(c1);
+        vol.children.push(node("第二章", StructKind::Chapter, ""));
+        vol.children.push(node("第三章", StructKind::Chapter, "“你究竟是谁？”"));
+        vol
+    }
+
+    #[test]
+    fn test_build_synopsis() {
+        let vol = volume();
+        let plain = build_synopsis(&vol, &SynopsisOptions::default());
+        assert_eq!(plain, "少年离乡，踏上寻父之路。\n\n雨夜相遇，埋下玉佩。二人被追至江边！\n\n“你究竟是谁？”");
+
+        let titled = build_synopsis(&vol, &SynopsisOptions { titles: true, ..Default::default() });
+        assert_eq!(
+            titled,
+            "少年离乡，踏上寻父之路。\n\n第一章：雨夜相遇，埋下玉佩。追兵：二人被追至江边！\n\n第三章：“你究竟是谁？”",
+        );
+
+        assert_eq!(build_synopsis(&StructNode::new("空", StructKind::Chapter), &SynopsisOptions::default()), "");
+        assert_eq!(summary_sentences("出城，\n遇伏"), "出城，遇伏。");
+    }
+
+    #[test]
+    fn test_trim_synopsis() {
+        let text = "少年离乡。他遇到了“她。”\n\n二人逃亡……最终分别！";
+        assert_eq!(trim_synopsis(text, 100), text);
+        // Whole sentences only; closing quotes stay with theirs.
+        assert_eq!(trim_synopsis(text, 13), "少年离乡。他遇到了“她。”");
+        assert_eq!(trim_synopsis(text, 12), "少年离乡。");
+        assert_eq!(trim_synopsis(text, 19), "少年离乡。他遇到了“她。”\n\n二人逃亡……");
+        assert_eq!(trim_synopsis(text, 20), trim_synopsis(text, 19));
+        // No sentence fits: cut mid-sentence.
+        assert_eq!(trim_synopsis(text, 3), "少年离…");
+        assert!(count_words(&trim_synopsis(text, 18)) <= 18);
+    }
+
+    #[test]
+    fn test_compress_prompt() {
+        let prompt = compress_prompt("少年离乡。", 300);
+        assert!(prompt.contains("约 300 字"));
+        assert!(prompt.ends_with("\n\n少年离乡。"));
+    }
+}
@@ -0,0 +1,262 @@
+use std::ops::Range;
+
+use crate::models::{find_duplicate_name, same_title, ChapterTag, LinkTarget, RelationKind, StructNode, WorldObject};
+
+// ── Per-object appearance timeline ───────────────────────────────────────────
+
+/// One leaf of the structure, as seen from a single world object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEntry {
+    /// Index path of the node in the structure tree.
+    pub path: Vec<usize>,
+    pub title: String,
+    pub tag: ChapterTag,
+    /// The object appears in (or is linked to) this node.
+    pub present: bool,
+    /// Note of the link that made the node match, if any.
+    pub note: Option<String>,
+}
+
+/// A run of consecutive leaves without the object, after its first appearance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimelineGap {
+    /// Index into the timeline of the first missing leaf.
+    pub start: usize,
+    pub len: usize,
+    /// The object never comes back after this gap.
+    pub trailing: bool,
+}
+
+/// Leaf nodes of `roots` in depth-first order, marking those where `obj`
+/// appears: the node lists it in `linked_objects`, the object has a
+/// [`LinkTarget::Node`] link to the node (by title, or by a `卷/章` title
+/// path ending in it), or one of the node's cross-links mentions the
+/// object's name in its note.
+pub fn object_timeline(roots: &[StructNode], obj: &WorldObject) -> Vec<TimelineEntry> {
+    fn walk(nodes: &[StructNode], obj: &WorldObject, path: &mut Vec<usize>, out: &mut Vec<TimelineEntry>) {
+        for (i, node) in nodes.iter().enumerate() {
+            path.push(i);
+            if node.children.is_empty() {
+                out.push(entry_for(node, obj, path));
+            } else {
+                walk(&node.children, obj, path, out);
+            }
+            path.pop();
+        }
+    }
+    let mut out = Vec::new();
+    walk(roots, obj, &mut Vec::new(), &mut out);
+    out
+}
+
+fn entry_for(node: &StructNode, obj: &WorldObject, path: &[usize]) -> TimelineEntry {
+    let object_link = obj.links.iter().find(|l| match &l.target {
+        LinkTarget::Node(t) => t.rsplit('/').next().is_some_and(|t| same_title(t, &node.title)),
+        LinkTarget::Object(_) => false,
+    });
+    let node_link = node.node_links.iter()
+        .find(|l| !obj.name.is_empty() && l.note.contains(&obj.name));
+    let listed = node.linked_objects.iter().any(|n| same_title(n, &obj.name));
+    let note = object_link.map(|l| &l.note)
+        .or(node_link.map(|l| &l.note))
+        .filter(|n| !n.is_empty())
+        .cloned();
+    TimelineEntry {
+        path: path.to_vec(),
+        title: node.title.clone(),
+        tag: node.tag.clone(),
+        present: listed || object_link.is_some() || node_link.is_some(),
+        note,
+    }
+}
+
+/// Whether `obj` belongs to the cast of `node`: the node lists it in
+/// `linked_objects`, or the object has an 出场 / 提及 link to the node (by
+/// title, or by a `卷/章` title path ending in it).
+pub fn in_node_cast(node: &StructNode, obj: &WorldObject) -> bool {
+    node.linked_objects.iter().any(|n| same_title(n, &obj.name))
+        || obj.links.iter().any(|l| {
+            matches!(l.kind, RelationKind::AppearsIn | RelationKind::MentionedIn)
+                && matches!(&l.target, LinkTarget::Node(t) if t.rsplit('/').next().is_some_and(|t| same_title(t, &node.title)))
+        })
+}
+
+/// Gaps of more than `max_missing` consecutive absent leaves once the object
+/// has appeared, including a trailing gap up to the last leaf.
+pub fn timeline_gaps(entries: &[TimelineEntry], max_missing: usize) -> Vec<TimelineGap> {
+    let Some(first) = entries.iter().position(|e| e.present) else {
+        return Vec::new();
+    };
+    let mut gaps = Vec::new();
+    let mut run_start: Option<usize> = None;
+    for (i, e) in entries.iter().enumerate().skip(first) {
+        match (e.present, run_start) {
+            (false, None) => run_start = Some(i),
+            (true, Some(start)) => {
+                if i - start > max_missing {
+                    gaps.push(TimelineGap { start, len: i - start, trailing: false });
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = run_start {
+        if entries.len() - start > max_missing {
+            gaps.push(TimelineGap { start, len: entries.len() - start, trailing: true });
+        }
+    }
+    gaps
+}
+
+// ── Object mentions in chapter text ──────────────────────────────────────────
+
+/// The `names` that occur in `text` outside fenced code blocks, in order of
+/// first occurrence.  Where names overlap the longest wins, so 北城门 does
+/// not also count as 北城.
+pub fn mentioned_names<'a>(text: &str, names: &[&'a str]) -> Vec<&'a str> {
+    let mut by_len: Vec<&str> = names.iter().copied().filter(|n| !n.trim().is_empty()).collect();
+    by_len.sort_by_key(|n| std::cmp::Reverse(n.len()));
+    let mut first: Vec<(usize, &str)> = Vec::new();
+    let mut offset = 0;
+    let mut in_code = false;
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        } else if !in_code {
+            let mut taken: Vec<Range<usize>> = Vec::new();
+            for name in &by_len {
+                for (i, _) in line.match_indices(name) {
+                    let r = i..i + name.len();
+                    if taken.iter().any(|t| t.start < r.end && r.start < t.end) {
+                        continue;
+                    }
+                    taken.push(r);
+                    if !first.iter().any(|(_, n)| n == name) {
+                        first.push((offset + i, name));
+                    }
+                }
+            }
+        }
+        offset += line.len();
+    }
+    first.sort_by_key(|&(at, _)| at);
+    first.into_iter().map(|(_, n)| n).collect()
+}
+
+/// Object names mentioned in a chapter's `text` that its node does not link
+/// yet — the suggestions offered after saving the chapter.
+pub fn suggest_links<'a>(text: &str, linked: &[String], names: &[&'a str]) -> Vec<&'a str> {
+    mentioned_names(text, names).into_iter()
+        .filter(|n| find_duplicate_name(n, linked.iter().map(String::as_str)).is_none())
+        .collect()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{NodeLink, ObjectKind, ObjectLink, StructKind};
+
+    fn chapter(title: &str) -> StructNode {
+        StructNode::new(title, StructKind::Chapter)
+    }
+
+    fn present(entries: &[TimelineEntry]) -> String {
+        entries.iter().map(|e| if e.present { 'x' } else { '.' }).collect()
+    }
+
+    #[test]
+    fn test_object_timeline_matches() {
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        let mut c1 = chapter("第一章");
+        c1.linked_objects.push("林枫".into());
+        let mut c3 = chapter("第三章");
+        c3.node_links.push(NodeLink {
+            target_title: "第一章".into(),
+            kind: RelationKind::Foreshadows,
+            note: "林枫的玉佩".into(),
+        });
+        vol.children = vec![c1, chapter("第二章"), c3];
+        let roots = vec![vol, chapter("第四章"), chapter("第五章")];
+
+        let mut obj = WorldObject::new("林枫", ObjectKind::Character);
+        obj.links.push(ObjectLink {
+            target: LinkTarget::Node("第一卷/第四章".into()),
+            kind: RelationKind::AppearsIn,
+            note: "受伤".into(),
+        });
+
+        let tl = object_timeline(&roots, &obj);
+        let titles: Vec<&str> = tl.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["第一章", "第二章", "第三章", "第四章", "第五章"]);
+        assert_eq!(present(&tl), "x.xx.");
+        assert_eq!(tl[0].path, vec![0, 0]);
+        assert_eq!(tl[0].note, None);
+        assert_eq!(tl[2].note.as_deref(), Some("林枫的玉佩"));
+        assert_eq!(tl[3].note.as_deref(), Some("受伤"));
+        assert_eq!(tl[3].path, vec![1]);
+    }
+
+    #[test]
+    fn test_in_node_cast() {
+        let mut node = chapter("第二章");
+        node.linked_objects.push("苏晚".into());
+        let link = |target: &str, kind| ObjectLink { target: LinkTarget::Node(target.into()), kind, note: String::new() };
+        let listed = WorldObject::new(" 苏晚 ", ObjectKind::Character);
+        let mut appears = WorldObject::new("林枫", ObjectKind::Character);
+        appears.links.push(link("第一卷/第二章", RelationKind::AppearsIn));
+        let mut mentioned = WorldObject::new("古剑", ObjectKind::Item);
+        mentioned.links.push(link("第二章", RelationKind::MentionedIn));
+        // Other relations to the node, or 出场 elsewhere, do not count.
+        let mut other = WorldObject::new("青云宗", ObjectKind::Faction);
+        other.links.push(link("第二章", RelationKind::Other));
+        other.links.push(link("第三章", RelationKind::AppearsIn));
+
+        assert!(in_node_cast(&node, &listed));
+        assert!(in_node_cast(&node, &appears));
+        assert!(in_node_cast(&node, &mentioned));
+        assert!(!in_node_cast(&node, &other));
+    }
+
+    #[test]
+    fn test_timeline_gaps() {
+        let entries: Vec<TimelineEntry> = "..x...x.x....".chars().enumerate()
+            .map(|(i, c)| TimelineEntry {
+                path: vec![i],
+                title: i.to_string(),
+                tag: ChapterTag::Normal,
+                present: c == 'x',
+                note: None,
+            })
+            .collect();
+        // Leading absences do not count; the trailing run does.
+        assert_eq!(timeline_gaps(&entries, 2), vec![
+            TimelineGap { start: 3, len: 3, trailing: false },
+            TimelineGap { start: 9, len: 4, trailing: true },
+        ]);
+        assert_eq!(timeline_gaps(&entries, 3), vec![TimelineGap { start: 9, len: 4, trailing: true }]);
+        assert!(timeline_gaps(&entries[..2], 0).is_empty());
+    }
+
+    #[test]
+    fn test_mentioned_names() {
+        let names = ["北城", "北城门", "古剑", "张三", "", "李四"];
+        let text = "张三出了北城门。\n```\n李四\n```\n他拔出古剑，回望北城。\n";
+        assert_eq!(mentioned_names(text, &names), ["张三", "北城门", "古剑", "北城"]);
+        // 北城门 alone does not mention 北城.
+        assert_eq!(mentioned_names("北城门外", &names), ["北城门"]);
+        assert!(mentioned_names("", &names).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_links() {
+        let names = ["张三", "古剑", "北城", "林枫"];
+        let text = "北城的张三拿着古剑。";
+        let linked = vec!["张三".to_owned()];
+        assert_eq!(suggest_links(text, &linked, &names), ["北城", "古剑"]);
+        let all: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        assert!(suggest_links(text, &all, &names).is_empty());
+    }
+}
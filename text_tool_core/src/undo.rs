@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+
+// ── Editor undo history ───────────────────────────────────────────────────────
+//
+// Each pane keeps whole-buffer snapshots, so the history of a long chapter
+// can get large.  `UndoState` enforces the step limit and keeps a running
+// byte count for the settings window.
+
+/// Undo snapshots of one editor pane, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct UndoState {
+    snapshots: VecDeque<String>,
+    bytes: usize,
+}
+
+impl UndoState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `snapshot` (the text before an edit), then drop the oldest
+    /// snapshots beyond `limit`.
+    pub fn push(&mut self, snapshot: String, limit: usize) {
+        self.bytes += snapshot.len();
+        self.snapshots.push_back(snapshot);
+        self.truncate(limit);
+    }
+
+    /// The most recent snapshot, removed.
+    pub fn pop(&mut self) -> Option<String> {
+        let snapshot = self.snapshots.pop_back()?;
+        self.bytes -= snapshot.len();
+        Some(snapshot)
+    }
+
+    /// Drop the oldest snapshots until at most `limit` remain.
+    pub fn truncate(&mut self, limit: usize) {
+        while self.snapshots.len() > limit {
+            if let Some(old) = self.snapshots.pop_front() {
+                self.bytes -= old.len();
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.bytes = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Approximate memory held: the summed length of the snapshots.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+/// `bytes` for display: `512 B`, `3.4 KB`, `12.0 MB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    let b = bytes as f64;
+    if b < KB {
+        format!("{bytes} B")
+    } else if b < KB * KB {
+        format!("{:.1} KB", b / KB)
+    } else {
+        format!("{:.1} MB", b / (KB * KB))
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_evicts_oldest_first() {
+        let mut undo = UndoState::new();
+        for s in ["a", "bb", "ccc", "dddd"] {
+            undo.push(s.to_owned(), 3);
+        }
+        // "a" went first; the newest comes back first.
+        assert_eq!(undo.len(), 3);
+        assert_eq!(undo.bytes(), 2 + 3 + 4);
+        assert_eq!(undo.pop().as_deref(), Some("dddd"));
+        assert_eq!(undo.bytes(), 2 + 3);
+
+        // Lowering the limit drops from the old end too.
+        undo.push("ee".to_owned(), 3);
+        undo.truncate(1);
+        assert_eq!(undo.bytes(), 2);
+        assert_eq!(undo.pop().as_deref(), Some("ee"));
+        assert_eq!(undo.pop(), None);
+        assert_eq!(undo.bytes(), 0);
+        assert!(undo.is_empty());
+    }
+
+    #[test]
+    fn test_undo_clear_and_zero_limit() {
+        let mut undo = UndoState::new();
+        undo.push("林枫".to_owned(), 10);
+        assert_eq!(undo.bytes(), "林枫".len());
+        undo.clear();
+        assert_eq!((undo.len(), undo.bytes()), (0, 0));
+        undo.push("x".to_owned(), 0);
+        assert!(undo.is_empty());
+        assert_eq!(undo.bytes(), 0);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MB");
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::normalize_title;
+
+// ── 常用项: what the link forms offer first ───────────────────────────────────
+//
+// Every use adds 1 to an entry's score after all scores have decayed by
+// [`USAGE_DECAY`], so an entry used often ranks high but one used just now
+// soon catches up.  Scores that decay below [`MIN_SCORE`] are forgotten,
+// which keeps the map small without a separate size limit.
+
+/// Share of its score an entry keeps each time something is used.
+pub const USAGE_DECAY: f32 = 0.8;
+/// Scores below this are dropped.
+const MIN_SCORE: f32 = 0.02;
+/// Entries the pickers list above the separator.
+pub const USAGE_TOP: usize = 5;
+
+/// Decayed use counts of picker entries, keyed by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UsageRank {
+    scores: BTreeMap<String, f32>,
+}
+
+impl UsageRank {
+    /// Note a use of `key` (whitespace normalized).
+    pub fn record(&mut self, key: &str) {
+        let key = normalize_title(key);
+        if key.is_empty() {
+            return;
+        }
+        for score in self.scores.values_mut() {
+            *score *= USAGE_DECAY;
+        }
+        self.scores.retain(|_, s| *s >= MIN_SCORE);
+        *self.scores.entry(key).or_insert(0.0) += 1.0;
+    }
+
+    pub fn score(&self, key: &str) -> f32 {
+        self.scores.get(&normalize_title(key)).copied().unwrap_or(0.0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Up to `n` of `items` that were used, highest score first (ties in
+    /// `items` order).  Entries no longer among `items` are skipped.
+    pub fn top<'a, T>(&self, items: &'a [T], key: impl Fn(&T) -> &str, n: usize) -> Vec<&'a T> {
+        let mut used: Vec<(f32, &T)> = items.iter()
+            .map(|item| (self.score(key(item)), item))
+            .filter(|(score, _)| *score > 0.0)
+            .collect();
+        used.sort_by(|a, b| b.0.total_cmp(&a.0));
+        used.into_iter().take(n).map(|(_, item)| item).collect()
+    }
+}
+
+/// The rankings behind the Objects and Structure link forms.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkUsage {
+    /// Relation kinds of object links, by label.
+    pub object_relations: UsageRank,
+    /// Relation kinds of 跨节点关联.
+    pub node_relations: UsageRank,
+    /// Objects linked to, by name.
+    pub object_targets: UsageRank,
+    /// Structure nodes linked to, by title.
+    pub node_targets: UsageRank,
+}
+
+impl LinkUsage {
+    pub fn is_empty(&self) -> bool {
+        self.object_relations.is_empty()
+            && self.node_relations.is_empty()
+            && self.object_targets.is_empty()
+            && self.node_targets.is_empty()
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_rank_frequency_and_recency() {
+        let items = ["友好", "敌对", "亲属", "所属"];
+        let top = |rank: &UsageRank| rank.top(&items, |s| s, USAGE_TOP).into_iter().copied().collect::<Vec<_>>();
+        let mut rank = UsageRank::default();
+        assert!(top(&rank).is_empty());
+
+        // Frequent beats once-but-recent …
+        for _ in 0..3 {
+            rank.record("友好");
+        }
+        rank.record("敌对");
+        assert_eq!(top(&rank), ["友好", "敌对"]);
+        assert!((rank.score("友好") - (1.0 + 0.8 + 0.64) * 0.8).abs() < 1e-5);
+
+        // … until the recent one has been used enough lately.
+        rank.record("敌对");
+        rank.record("敌对");
+        assert_eq!(top(&rank), ["敌对", "友好"]);
+
+        // Keys are compared normalized; unknown keys do not show up.
+        rank.record(" 亲属 ");
+        rank.record("不在列表");
+        assert_eq!(top(&rank), ["敌对", "亲属", "友好"]);
+        assert_eq!(rank.top(&items, |s| s, 1), [&"敌对"]);
+    }
+
+    #[test]
+    fn test_usage_rank_forgets_old_entries() {
+        let mut rank = UsageRank::default();
+        rank.record("旧");
+        for _ in 0..17 {
+            rank.record("新");
+        }
+        // 0.8^17 ≈ 0.0225 is still kept, one more use drops it.
+        assert!(rank.score("旧") > 0.0);
+        rank.record("新");
+        assert_eq!(rank.score("旧"), 0.0);
+
+        let json = serde_json::to_string(&LinkUsage { node_targets: rank.clone(), ..LinkUsage::default() }).unwrap();
+        let back: LinkUsage = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.node_targets, rank);
+        assert!(back.object_targets.is_empty() && !back.is_empty());
+    }
+}
@@ -0,0 +1,548 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+use crate::links::heading_slug;
+use crate::markdown::markdown_to_html;
+use crate::models::{normalize_title, object_kinds, same_title, Foreshadow, StructNode, WorldObject};
+use crate::profile::{html_escape, ProfileSheet};
+
+// ── 设定 Wiki: one self-contained HTML file of the setting ────────────────────
+//
+// A sidebar of world objects (by kind), the structure tree and foreshadows,
+// and one page per entry; a few lines of inline JavaScript show the page
+// named by the URL fragment.  Object pages are laid out from the same
+// `ProfileSheet` as the Markdown export, with names turned into links.
+
+/// Id of the overview page shown when the fragment names no page.
+const HOME_ID: &str = "home";
+
+/// Page ids by kind of page, unique across the whole document.
+#[derive(Default)]
+struct WikiIds {
+    used: HashSet<String>,
+    objects: Vec<String>,
+    /// In depth-first order of the structure tree.
+    nodes: Vec<String>,
+    foreshadows: Vec<String>,
+    /// First page of each normalized name, per kind.
+    object_by_name: HashMap<String, usize>,
+    node_by_title: HashMap<String, usize>,
+    foreshadow_by_name: HashMap<String, usize>,
+}
+
+impl WikiIds {
+    fn new(objects: &[WorldObject], nodes: &[&StructNode], foreshadows: &[Foreshadow]) -> Self {
+        let mut ids = WikiIds::default();
+        ids.used.insert(HOME_ID.to_owned());
+        for (i, o) in objects.iter().enumerate() {
+            let id = ids.alloc("obj", &o.name);
+            ids.objects.push(id);
+            ids.object_by_name.entry(normalize_title(&o.name)).or_insert(i);
+        }
+        for (i, n) in nodes.iter().enumerate() {
+            let id = ids.alloc("node", &n.title);
+            ids.nodes.push(id);
+            ids.node_by_title.entry(normalize_title(&n.title)).or_insert(i);
+        }
+        for (i, f) in foreshadows.iter().enumerate() {
+            let id = ids.alloc("fs", &f.name);
+            ids.foreshadows.push(id);
+            ids.foreshadow_by_name.entry(normalize_title(&f.name)).or_insert(i);
+        }
+        ids
+    }
+
+    /// `prefix-slug`, with `-1`, `-2`, … appended while taken: duplicate
+    /// names and names that slug alike (`A B` / `a-b`) get distinct ids.
+    fn alloc(&mut self, prefix: &str, name: &str) -> String {
+        let slug = heading_slug(name);
+        let base = if slug.is_empty() { prefix.to_owned() } else { format!("{prefix}-{slug}") };
+        let mut id = base.clone();
+        let mut n = 1;
+        while !self.used.insert(id.clone()) {
+            id = format!("{base}-{n}");
+            n += 1;
+        }
+        id
+    }
+
+    fn object(&self, name: &str) -> Option<&str> {
+        self.object_by_name.get(&normalize_title(name)).map(|&i| self.objects[i].as_str())
+    }
+
+    fn node(&self, title: &str) -> Option<&str> {
+        self.node_by_title.get(&normalize_title(title)).map(|&i| self.nodes[i].as_str())
+    }
+
+    fn foreshadow(&self, name: &str) -> Option<&str> {
+        self.foreshadow_by_name.get(&normalize_title(name)).map(|&i| self.foreshadows[i].as_str())
+    }
+}
+
+/// `<a>` to the page `id`, or a greyed name when nothing has that name.
+fn link(id: Option<&str>, text: &str) -> String {
+    match id {
+        Some(id) => format!("<a href=\"#{}\">{}</a>", html_escape(id), html_escape(text)),
+        None => format!("<span class=\"missing\" title=\"未找到\">{}</span>", html_escape(text)),
+    }
+}
+
+/// Every node of the tree, depth-first.
+fn flatten(roots: &[StructNode]) -> Vec<&StructNode> {
+    fn walk<'a>(nodes: &'a [StructNode], out: &mut Vec<&'a StructNode>) {
+        for n in nodes {
+            out.push(n);
+            walk(&n.children, out);
+        }
+    }
+    let mut out = Vec::new();
+    walk(roots, &mut out);
+    out
+}
+
+/// The wiki document titled `title`.
+pub fn build_wiki(title: &str, objects: &[WorldObject], roots: &[StructNode], foreshadows: &[Foreshadow]) -> String {
+    let nodes = flatten(roots);
+    let ids = WikiIds::new(objects, &nodes, foreshadows);
+    let e = html_escape;
+
+    let mut h = String::new();
+    let _ = writeln!(h, "<!DOCTYPE html>\n<html lang=\"zh\">\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(h, "<title>{}</title>\n<style>{WIKI_CSS}</style>\n</head>\n<body>", e(title));
+
+    // ── Sidebar ──
+    let _ = writeln!(h, "<nav>\n<h1><a href=\"#{HOME_ID}\">{}</a></h1>", e(title));
+    let _ = writeln!(h, "<input id=\"filter\" type=\"search\" placeholder=\"筛选…\">");
+    let _ = writeln!(h, "<h2>世界对象</h2>");
+    for kind in &object_kinds(&[], objects) {
+        let members: Vec<usize> = (0..objects.len()).filter(|&i| &objects[i].kind == kind).collect();
+        if members.is_empty() {
+            continue;
+        }
+        let _ = writeln!(h, "<h3>{} {}</h3>\n<ul>", kind.icon(), kind.label());
+        for i in members {
+            let _ = writeln!(h, "<li>{}</li>", link(Some(&ids.objects[i]), &objects[i].name));
+        }
+        let _ = writeln!(h, "</ul>");
+    }
+    let _ = writeln!(h, "<h2>章节结构</h2>");
+    sidebar_tree(&mut h, roots, &ids, &mut 0);
+    if !foreshadows.is_empty() {
+        let _ = writeln!(h, "<h2>伏笔</h2>\n<ul>");
+        for (i, f) in foreshadows.iter().enumerate() {
+            let _ = writeln!(h, "<li>{}</li>", link(Some(&ids.foreshadows[i]), &f.name));
+        }
+        let _ = writeln!(h, "</ul>");
+    }
+    let _ = writeln!(h, "</nav>\n<main>");
+
+    // ── Pages ──
+    let _ = writeln!(h, "<section class=\"page\" id=\"{HOME_ID}\">\n<h1>{}</h1>", e(title));
+    let _ = writeln!(
+        h,
+        "<p>{} 个世界对象 · {} 个章节节点 · {} 条伏笔</p>\n</section>",
+        objects.len(), nodes.len(), foreshadows.len(),
+    );
+    for (i, obj) in objects.iter().enumerate() {
+        object_page(&mut h, &ids.objects[i], obj, roots, &ids);
+    }
+    for (i, node) in nodes.iter().enumerate() {
+        node_page(&mut h, &ids.nodes[i], node, roots, foreshadows, &ids);
+    }
+    for (i, f) in foreshadows.iter().enumerate() {
+        foreshadow_page(&mut h, &ids.foreshadows[i], f, &ids);
+    }
+    let _ = writeln!(h, "</main>\n<script>{WIKI_JS}</script>\n</body>\n</html>");
+    h
+}
+
+/// The structure tree as nested lists; `next` counts nodes depth-first to
+/// pick their ids.
+fn sidebar_tree(h: &mut String, nodes: &[StructNode], ids: &WikiIds, next: &mut usize) {
+    if nodes.is_empty() {
+        return;
+    }
+    let _ = writeln!(h, "<ul>");
+    for n in nodes {
+        let id = &ids.nodes[*next];
+        *next += 1;
+        let _ = write!(h, "<li>{}", link(Some(id), &format!("{} {}", n.kind.icon(), n.title)));
+        if !n.children.is_empty() {
+            h.push('\n');
+            sidebar_tree(h, &n.children, ids, next);
+        }
+        let _ = writeln!(h, "</li>");
+    }
+    let _ = writeln!(h, "</ul>");
+}
+
+fn object_page(h: &mut String, id: &str, obj: &WorldObject, roots: &[StructNode], ids: &WikiIds) {
+    let e = html_escape;
+    let sheet = ProfileSheet::new(obj).with_timeline(obj, roots);
+    let _ = writeln!(h, "<section class=\"page\" id=\"{}\">", e(id));
+    let _ = writeln!(h, "<h1>{} {}</h1>\n<p class=\"kind\">{}</p>", sheet.icon, e(&sheet.name), sheet.kind);
+    if !sheet.tags.is_empty() {
+        let tags: Vec<String> = sheet.tags.iter().map(|t| format!("<span class=\"tag\">{}</span>", e(t))).collect();
+        let _ = writeln!(h, "<p>{}</p>", tags.join(" "));
+    }
+    for (title, text) in [("描述", &sheet.description), ("背景", &sheet.background)] {
+        if !text.is_empty() {
+            let _ = writeln!(h, "<h2>{title}</h2>\n<p>{}</p>", e(text).replace('\n', "<br>"));
+        }
+    }
+    if !sheet.relations.is_empty() {
+        let _ = writeln!(h, "<h2>关联</h2>");
+        for g in &sheet.relations {
+            let _ = writeln!(h, "<h3>{}</h3>\n<ul>", g.kind);
+            for r in &g.items {
+                let target = if r.target_type == "章节" { ids.node(&r.target) } else { ids.object(&r.target) };
+                let note = if r.note.is_empty() { String::new() } else { format!(" — {}", e(&r.note)) };
+                let _ = writeln!(h, "<li>{} <small>({})</small>{note}</li>", link(target, &r.target), r.target_type);
+            }
+            let _ = writeln!(h, "</ul>");
+        }
+    }
+    if let Some(apps) = sheet.appearances.as_ref().filter(|a| !a.is_empty()) {
+        let items: Vec<String> = apps.iter().map(|a| format!("<li>{}</li>", link(ids.node(a), a))).collect();
+        let _ = writeln!(h, "<h2>出场</h2>\n<ol>{}</ol>", items.concat());
+    }
+    let _ = writeln!(h, "</section>");
+}
+
+fn node_page(
+    h: &mut String,
+    id: &str,
+    node: &StructNode,
+    roots: &[StructNode],
+    foreshadows: &[Foreshadow],
+    ids: &WikiIds,
+) {
+    let e = html_escape;
+    let _ = writeln!(h, "<section class=\"page\" id=\"{}\">", e(id));
+    let _ = writeln!(h, "<h1>{} {}</h1>", node.kind.icon(), e(&node.title));
+    let done = if node.done { "已完成" } else { "未完成" };
+    let _ = writeln!(h, "<p class=\"kind\">{} · {} · {done}</p>", node.kind.label(), node.tag.label());
+    if let Some(parents) = parent_titles(roots, node).filter(|p| !p.is_empty()) {
+        let crumbs: Vec<String> = parents.iter().map(|t| link(ids.node(t), t)).collect();
+        let _ = writeln!(h, "<p class=\"crumbs\">{}</p>", crumbs.join(" › "));
+    }
+    if !node.summary.trim().is_empty() {
+        let _ = writeln!(h, "<h2>摘要</h2>\n<p>{}</p>", e(&node.summary).replace('\n', "<br>"));
+    }
+    if !node.notes.trim().is_empty() {
+        let _ = write!(h, "<h2>备注</h2>\n<div class=\"notes\">\n{}</div>\n", markdown_to_html(&node.notes));
+    }
+    let sections: [(&str, Vec<String>); 4] = [
+        ("下级", node.children.iter()
+            .map(|c| link(ids.node(&c.title), &format!("{} {}", c.kind.icon(), c.title)))
+            .collect()),
+        ("关联对象", node.linked_objects.iter().map(|o| link(ids.object(o), o)).collect()),
+        ("跨节点关联", node.node_links.iter()
+            .map(|l| {
+                let note = if l.note.is_empty() { String::new() } else { format!(" — {}", e(&l.note)) };
+                format!("{} <small>({})</small>{note}", link(ids.node(&l.target_title), &l.target_title), l.kind.label())
+            })
+            .collect()),
+        ("伏笔", foreshadows.iter()
+            .filter(|f| f.related_chapters.iter().any(|c| same_title(c, &node.title)))
+            .map(|f| format!("{} <small>({})</small>", link(ids.foreshadow(&f.name), &f.name), status(f)))
+            .collect()),
+    ];
+    for (title, items) in sections {
+        if !items.is_empty() {
+            let items: Vec<String> = items.into_iter().map(|i| format!("<li>{i}</li>")).collect();
+            let _ = writeln!(h, "<h2>{title}</h2>\n<ul>{}</ul>", items.concat());
+        }
+    }
+    let _ = writeln!(h, "</section>");
+}
+
+fn foreshadow_page(h: &mut String, id: &str, f: &Foreshadow, ids: &WikiIds) {
+    let e = html_escape;
+    let _ = writeln!(h, "<section class=\"page\" id=\"{}\">", e(id));
+    let _ = writeln!(h, "<h1>{}</h1>\n<p class=\"kind\">伏笔 · {}</p>", e(&f.name), status(f));
+    if !f.description.trim().is_empty() {
+        let _ = writeln!(h, "<p>{}</p>", e(&f.description).replace('\n', "<br>"));
+    }
+    if !f.related_chapters.is_empty() {
+        let items: Vec<String> = f.related_chapters.iter().map(|c| format!("<li>{}</li>", link(ids.node(c), c))).collect();
+        let _ = writeln!(h, "<h2>相关章节</h2>\n<ul>{}</ul>", items.concat());
+    }
+    let _ = writeln!(h, "</section>");
+}
+
+fn status(f: &Foreshadow) -> &'static str {
+    if f.resolved { "已解决" } else { "未解决" }
+}
+
+/// Titles of the ancestors of `target` (found by identity), outermost
+/// first; `None` when it is not in the tree.
+fn parent_titles<'a>(roots: &'a [StructNode], target: &StructNode) -> Option<Vec<&'a str>> {
+    for n in roots {
+        if std::ptr::eq(n, target) {
+            return Some(Vec::new());
+        }
+        if let Some(mut path) = parent_titles(&n.children, target) {
+            path.insert(0, &n.title);
+            return Some(path);
+        }
+    }
+    None
+}
+
+const WIKI_CSS: &str = "
+body { margin: 0; display: flex; font-family: sans-serif; line-height: 1.6; color: #222; }
+nav { width: 16em; flex: none; height: 100vh; overflow-y: auto; position: sticky; top: 0;
+      padding: 0.8em; box-sizing: border-box; background: #f4f4f2; border-right: 1px solid #ddd; font-size: 0.92em; }
+nav h1 { font-size: 1.2em; margin: 0 0 0.5em; }
+nav h2 { font-size: 1em; margin: 1em 0 0.2em; color: #555; }
+nav h3 { font-size: 0.92em; margin: 0.6em 0 0.1em; color: #777; }
+nav ul { list-style: none; margin: 0; padding-left: 0.9em; }
+nav > ul { padding-left: 0; }
+nav a { color: #246; text-decoration: none; }
+nav a.current { font-weight: bold; color: #c60; }
+#filter { width: 100%; box-sizing: border-box; }
+main { flex: 1; padding: 1em 2.5em; max-width: 52em; }
+h2 { border-bottom: 1px solid #ccc; font-size: 1.1em; margin-top: 1.4em; }
+h3 { font-size: 1em; margin: 0.8em 0 0.2em; }
+.kind, .crumbs, small { color: #777; }
+.tag { border: 1px solid #aaa; border-radius: 3px; padding: 0 4px; font-size: 0.9em; }
+.missing { color: #999; border-bottom: 1px dotted #999; }";
+
+const WIKI_JS: &str = "
+function show() {
+  var id = decodeURIComponent(location.hash.slice(1)) || 'home';
+  var page = document.getElementById(id);
+  if (!page || !page.classList.contains('page')) page = document.getElementById('home');
+  document.querySelectorAll('.page').forEach(function (p) { p.hidden = p !== page; });
+  document.querySelectorAll('nav a').forEach(function (a) {
+    a.classList.toggle('current', decodeURIComponent(a.getAttribute('href')) === '#' + page.id);
+  });
+  window.scrollTo(0, 0);
+}
+window.addEventListener('hashchange', show);
+document.getElementById('filter').addEventListener('input', function () {
+  var q = this.value.toLowerCase();
+  document.querySelectorAll('nav li').forEach(function (li) {
+    li.style.display = !q || li.textContent.toLowerCase().indexOf(q) >= 0 ? '' : 'none';
+  });
+});
+show();";
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{LinkTarget, NodeLink, ObjectKind, ObjectLink, RelationKind, StructKind};
+
+    fn project() -> (Vec<WorldObject>, Vec<StructNode>, Vec<Foreshadow>) {
+        let mut lin = WorldObject::new("林枫", ObjectKind::Character);
+        lin.description = "沉默<寡言>".into();
+        lin.links.push(ObjectLink { target: LinkTarget::Object("青云宗".into()), kind: RelationKind::BelongsTo, note: String::new() });
+        lin.links.push(ObjectLink { target: LinkTarget::Node("第一章".into()), kind: RelationKind::AppearsIn, note: "初登场".into() });
+        lin.links.push(ObjectLink { target: LinkTarget::Object("无名氏".into()), kind: RelationKind::Friend, note: String::new() });
+        // Same name twice, and a name whose slug equals another's.
+        let sect = WorldObject::new("青云宗", ObjectKind::Faction);
+        let peak = WorldObject::new("青云宗", ObjectKind::Location);
+        let a = WorldObject::new("A B", ObjectKind::Item);
+        let b = WorldObject::new("a-b", ObjectKind::Item);
+
+        let mut ch1 = StructNode::new("第一章", StructKind::Chapter);
+        ch1.linked_objects.push("林枫".into());
+        ch1.notes = "**雨夜**登场".into();
+        ch1.node_links.push(NodeLink { target_title: "第二章".into(), kind: RelationKind::Other, note: String::new() });
+        let ch2 = StructNode::new("第二章", StructKind::Chapter);
+        let mut vol = StructNode::new("第一卷", StructKind::Volume);
+        vol.children = vec![ch1, ch2];
+
+        let mut letter = Foreshadow::new("神秘信件");
+        letter.related_chapters.push("第一章".into());
+        (vec![lin, sect, peak, a, b], vec![vol], vec![letter])
+    }
+
+    /// Tags and text of the document without the inline CSS and JS.
+    fn skeleton(html: &str) -> String {
+        let cut = |s: &str, open: &str, close: &str| -> String {
+            let start = s.find(open).unwrap() + open.len();
+            let end = s.find(close).unwrap();
+            format!("{}{}", &s[..start], &s[end..])
+        };
+        cut(&cut(html, "<style>", "</style>"), "<script>", "</script>")
+    }
+
+    #[test]
+    fn test_wiki_ids_unique() {
+        let (objects, roots, foreshadows) = project();
+        let nodes = flatten(&roots);
+        let ids = WikiIds::new(&objects, &nodes, &foreshadows);
+        assert_eq!(ids.objects, ["obj-林枫", "obj-青云宗", "obj-青云宗-1", "obj-a-b", "obj-a-b-1"]);
+        assert_eq!(ids.nodes, ["node-第一卷", "node-第一章", "node-第二章"]);
+        assert_eq!(ids.foreshadows, ["fs-神秘信件"]);
+        // Names link to their first page, whitespace-insensitively.
+        assert_eq!(ids.object("青云宗 "), Some("obj-青云宗"));
+        assert_eq!(ids.node("第二章"), Some("node-第二章"));
+        assert_eq!(ids.object("无名氏"), None);
+
+        let mut ids = WikiIds::default();
+        ids.used.insert(HOME_ID.to_owned());
+        assert_eq!(ids.alloc("obj", "!!"), "obj");
+        assert_eq!(ids.alloc("obj", "？"), "obj-1");
+    }
+
+    #[test]
+    fn test_build_wiki_structure() {
+        let (objects, roots, foreshadows) = project();
+        let html = build_wiki("青云志 设定", &objects, &roots, &foreshadows);
+        assert!(html.contains("<title>青云志 设定</title>"));
+        assert!(html.contains("function show()"));
+
+        // Every internal link lands on a page.
+        let ids: HashSet<&str> = html.match_indices("id=\"").map(|(at, _)| {
+            let rest = &html[at + 4..];
+            &rest[..rest.find('"').unwrap()]
+        }).collect();
+        for (at, _) in html.match_indices("href=\"#") {
+            let rest = &html[at + 7..];
+            let target = &rest[..rest.find('"').unwrap()];
+            assert!(ids.contains(target), "dangling link #{target}");
+        }
+
+        assert_eq!(skeleton(&html), "\
+<!DOCTYPE html>
+<html lang=\"zh\">
+<head>
+<meta charset=\"utf-8\">
+<title>青云志 设定</title>
+<style></style>
+</head>
+<body>
+<nav>
+<h1><a href=\"#home\">青云志 设定</a></h1>
+<input id=\"filter\" type=\"search\" placeholder=\"筛选…\">
+<h2>世界对象</h2>
+<h3>👤 人物</h3>
+<ul>
+<li><a href=\"#obj-林枫\">林枫</a></li>
+</ul>
+<h3>📍 地点</h3>
+<ul>
+<li><a href=\"#obj-青云宗-1\">青云宗</a></li>
+</ul>
+<h3>🗡 道具</h3>
+<ul>
+<li><a href=\"#obj-a-b\">A B</a></li>
+<li><a href=\"#obj-a-b-1\">a-b</a></li>
+</ul>
+<h3>🏰 势力</h3>
+<ul>
+<li><a href=\"#obj-青云宗\">青云宗</a></li>
+</ul>
+<h2>章节结构</h2>
+<ul>
+<li><a href=\"#node-第一卷\">📚 第一卷</a>
+<ul>
+<li><a href=\"#node-第一章\">📖 第一章</a></li>
+<li><a href=\"#node-第二章\">📖 第二章</a></li>
+</ul>
+</li>
+</ul>
+<h2>伏笔</h2>
+<ul>
+<li><a href=\"#fs-神秘信件\">神秘信件</a></li>
+</ul>
+</nav>
+<main>
+<section class=\"page\" id=\"home\">
+<h1>青云志 设定</h1>
+<p>5 个世界对象 · 3 个章节节点 · 1 条伏笔</p>
+</section>
+<section class=\"page\" id=\"obj-林枫\">
+<h1>👤 林枫</h1>
+<p class=\"kind\">人物</p>
+<h2>描述</h2>
+<p>沉默&lt;寡言&gt;</p>
+<h2>关联</h2>
+<h3>所属</h3>
+<ul>
+<li><a href=\"#obj-青云宗\">青云宗</a> <small>(对象)</small></li>
+</ul>
+<h3>出场</h3>
+<ul>
+<li><a href=\"#node-第一章\">第一章</a> <small>(章节)</small> — 初登场</li>
+</ul>
+<h3>友好</h3>
+<ul>
+<li><span class=\"missing\" title=\"未找到\">无名氏</span> <small>(对象)</small></li>
+</ul>
+<h2>出场</h2>
+<ol><li><a href=\"#node-第一章\">第一章</a></li></ol>
+</section>
+<section class=\"page\" id=\"obj-青云宗\">
+<h1>🏰 青云宗</h1>
+<p class=\"kind\">势力</p>
+</section>
+<section class=\"page\" id=\"obj-青云宗-1\">
+<h1>📍 青云宗</h1>
+<p class=\"kind\">地点</p>
+</section>
+<section class=\"page\" id=\"obj-a-b\">
+<h1>🗡 A B</h1>
+<p class=\"kind\">道具</p>
+</section>
+<section class=\"page\" id=\"obj-a-b-1\">
+<h1>🗡 a-b</h1>
+<p class=\"kind\">道具</p>
+</section>
+<section class=\"page\" id=\"node-第一卷\">
+<h1>📚 第一卷</h1>
+<p class=\"kind\">卷 · 普通 · 未完成</p>
+<h2>下级</h2>
+<ul><li><a href=\"#node-第一章\">📖 第一章</a></li><li><a href=\"#node-第二章\">📖 第二章</a></li></ul>
+</section>
+<section class=\"page\" id=\"node-第一章\">
+<h1>📖 第一章</h1>
+<p class=\"kind\">章 · 普通 · 未完成</p>
+<p class=\"crumbs\"><a href=\"#node-第一卷\">第一卷</a></p>
+<h2>备注</h2>
+<div class=\"notes\">
+<p><strong>雨夜</strong>登场</p>
+</div>
+<h2>关联对象</h2>
+<ul><li><a href=\"#obj-林枫\">林枫</a></li></ul>
+<h2>跨节点关联</h2>
+<ul><li><a href=\"#node-第二章\">第二章</a> <small>(其他)</small></li></ul>
+<h2>伏笔</h2>
+<ul><li><a href=\"#fs-神秘信件\">神秘信件</a> <small>(未解决)</small></li></ul>
+</section>
+<section class=\"page\" id=\"node-第二章\">
+<h1>📖 第二章</h1>
+<p class=\"kind\">章 · 普通 · 未完成</p>
+<p class=\"crumbs\"><a href=\"#node-第一卷\">第一卷</a></p>
+</section>
+<section class=\"page\" id=\"fs-神秘信件\">
+<h1>神秘信件</h1>
+<p class=\"kind\">伏笔 · 未解决</p>
+<h2>相关章节</h2>
+<ul><li><a href=\"#node-第一章\">第一章</a></li></ul>
+</section>
+</main>
+<script></script>
+</body>
+</html>
+");
+    }
+
+    #[test]
+    fn test_build_wiki_lists_custom_kinds() {
+        let objects = vec![
+            WorldObject::new("九阳真经", ObjectKind::Custom("功法".into())),
+            WorldObject::new("林枫", ObjectKind::Character),
+        ];
+        let html = build_wiki("设定", &objects, &[], &[]);
+        let people = html.find("<h3>👤 人物</h3>").unwrap();
+        let custom = html.find("<h3>🏷 功法</h3>").unwrap();
+        assert!(people < custom);
+        assert!(html[custom..].contains("九阳真经"));
+    }
+}
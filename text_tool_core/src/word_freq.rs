@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use crate::models::{count_words, is_cjk, WordFrequencySettings};
+
+// ── 词频分析: watched words and frequent n-grams ──────────────────────────────
+//
+// Chinese has no spaces to split words on, so frequent phrases are found as
+// character n-grams: every window of 2–4 characters inside a run of CJK
+// characters.  Punctuation, whitespace and Latin text end a run, so no
+// n-gram spans a sentence break.
+
+/// Shortest and longest n-gram counted.
+pub const MIN_GRAM: usize = 2;
+pub const MAX_GRAM: usize = 4;
+
+/// One row of the report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FreqItem {
+    pub phrase: String,
+    pub count: usize,
+    /// From the watch list rather than the n-gram statistics.
+    pub watched: bool,
+    /// First occurrence: index into the documents and 1-based line.
+    pub first: Option<(usize, usize)>,
+}
+
+/// Result of [`word_frequency`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FreqReport {
+    /// Watched words in list order (also when absent), then the most
+    /// frequent n-grams.
+    pub items: Vec<FreqItem>,
+    /// Non-whitespace characters of all documents, as [`count_words`].
+    pub chars: usize,
+}
+
+/// The pieces of `text` n-grams are taken from: maximal runs of CJK
+/// characters, further split wherever a stopword of two or more characters
+/// occurs.
+fn segments<'a>(text: &'a str, stopwords: &[String]) -> Vec<&'a str> {
+    let mut parts: Vec<&str> = text.split(|c: char| !is_cjk(c)).filter(|p| !p.is_empty()).collect();
+    for stop in stopwords.iter().filter(|s| s.chars().nth(1).is_some()) {
+        parts = parts.into_iter()
+            .flat_map(|p| p.split(stop.as_str()))
+            .filter(|p| !p.is_empty())
+            .collect();
+    }
+    parts
+}
+
+/// Occurrences of every [`MIN_GRAM`]–[`MAX_GRAM`]-character window within
+/// the [`segments`] of `text`, added to `counts`.
+pub fn count_ngrams(text: &str, stopwords: &[String], counts: &mut HashMap<String, usize>) {
+    for segment in segments(text, stopwords) {
+        let chars: Vec<char> = segment.chars().collect();
+        for n in MIN_GRAM..=MAX_GRAM.min(chars.len()) {
+            for window in chars.windows(n) {
+                *counts.entry(window.iter().collect()).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// Whether `gram` starts or ends with a one-character stopword.
+fn is_stopped(gram: &str, stopwords: &[String]) -> bool {
+    stopwords.iter()
+        .filter(|s| s.chars().count() == 1)
+        .any(|s| gram.starts_with(s.as_str()) || gram.ends_with(s.as_str()))
+}
+
+/// The `top_n` most frequent n-grams of `counts` (seen at least twice), most
+/// frequent first, longer before shorter on ties.  [`is_stopped`] n-grams
+/// and those in `exclude` are left out, and so is an n-gram occurring
+/// exactly as often as a listed n-gram one character longer that contains
+/// it: it is always part of that one (`不由` of `不由得`).
+pub fn top_ngrams(
+    counts: &HashMap<String, usize>,
+    stopwords: &[String],
+    exclude: &[String],
+    top_n: usize,
+) -> Vec<(String, usize)> {
+    let mut subsumed: HashMap<&str, ()> = HashMap::new();
+    for (gram, &count) in counts {
+        let chars: Vec<(usize, char)> = gram.char_indices().collect();
+        if chars.len() <= MIN_GRAM || is_stopped(gram, stopwords) {
+            continue;
+        }
+        let prefix = &gram[..chars[chars.len() - 1].0];
+        let suffix = &gram[chars[1].0..];
+        for part in [prefix, suffix] {
+            if counts.get(part) == Some(&count) {
+                subsumed.insert(part, ());
+            }
+        }
+    }
+    let mut out: Vec<(String, usize)> = counts.iter()
+        .filter(|&(gram, &count)| count >= 2 && !subsumed.contains_key(gram.as_str()))
+        .filter(|(gram, _)| !is_stopped(gram, stopwords) && !exclude.iter().any(|e| e == *gram))
+        .map(|(gram, &count)| (gram.clone(), count))
+        .collect();
+    out.sort_by(|(a, x), (b, y)| {
+        y.cmp(x).then(b.chars().count().cmp(&a.chars().count())).then(a.cmp(b))
+    });
+    out.truncate(top_n);
+    out
+}
+
+/// Document index and 1-based line of the first line containing `phrase`.
+fn first_hit(docs: &[&str], phrase: &str) -> Option<(usize, usize)> {
+    docs.iter().enumerate().find_map(|(d, text)| {
+        text.lines().position(|line| line.contains(phrase)).map(|i| (d, i + 1))
+    })
+}
+
+/// 词频分析 over `docs`: the watched words (non-overlapping occurrences),
+/// then the frequent n-grams of [`top_ngrams`] other than watched words.
+pub fn word_frequency(docs: &[&str], settings: &WordFrequencySettings) -> FreqReport {
+    let watch: Vec<String> = settings.watch_words.iter()
+        .map(|w| w.trim().to_owned())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let mut items: Vec<FreqItem> = watch.iter()
+        .map(|w| FreqItem {
+            phrase: w.clone(),
+            count: docs.iter().map(|d| d.matches(w.as_str()).count()).sum(),
+            watched: true,
+            first: first_hit(docs, w),
+        })
+        .collect();
+
+    let mut counts = HashMap::new();
+    for doc in docs {
+        count_ngrams(doc, &settings.stopwords, &mut counts);
+    }
+    items.extend(top_ngrams(&counts, &settings.stopwords, &watch, settings.top_n).into_iter()
+        .map(|(phrase, count)| FreqItem {
+            first: first_hit(docs, &phrase),
+            phrase,
+            count,
+            watched: false,
+        }));
+
+    FreqReport { items, chars: docs.iter().map(|d| count_words(d)).sum() }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts_of(text: &str, stopwords: &[String]) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        count_ngrams(text, stopwords, &mut counts);
+        counts
+    }
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_count_ngrams_stops_at_punctuation() {
+        let counts = counts_of("他笑了，笑了 OK笑", &[]);
+        // Runs: 他笑了 / 笑了 / 笑 — nothing spans the comma, space or Latin.
+        let mut all: Vec<(&str, usize)> = counts.iter().map(|(g, &c)| (g.as_str(), c)).collect();
+        all.sort();
+        assert_eq!(all, vec![("他笑", 1), ("他笑了", 1), ("笑了", 2)]);
+
+        let counts = counts_of("一二三四五", &[]);
+        assert_eq!(counts.len(), 4 + 3 + 2);
+        assert_eq!(counts.get("一二三四"), Some(&1));
+        assert!(!counts.contains_key("一二三四五"));
+        assert!(counts_of("abc, 1234。！", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_top_ngrams_drops_fragments_and_stopwords() {
+        let text = "他不由得一愣。她不由得后退。风不由得停了。雨突然下了，雷突然响了。";
+        let counts = counts_of(text, &[]);
+        let top = top_ngrams(&counts, &words(&["了"]), &[], 10);
+        // 不由 / 由得 only ever occur inside 不由得; 然下了 ends with a stopword.
+        assert_eq!(top, vec![("不由得".to_owned(), 3), ("突然".to_owned(), 2)]);
+
+        // Excluded phrases and the length limit.
+        let top = top_ngrams(&counts, &[], &words(&["不由得"]), 1);
+        assert_eq!(top, vec![("突然".to_owned(), 2)]);
+
+        // Multi-character stopwords split the text like punctuation.
+        let stop = words(&["我们", "他们"]);
+        let counts = counts_of("我们回家。他们回家了。", &stop);
+        assert_eq!(counts.get("们回"), None);
+        assert_eq!(top_ngrams(&counts, &stop, &[], 10), vec![("回家".to_owned(), 2)]);
+
+        // A stopped n-gram does not hide its parts: 笑了 goes, 笑笑 stays.
+        let counts = counts_of("笑笑了。笑笑了。", &[]);
+        assert_eq!(top_ngrams(&counts, &words(&["了"]), &[], 10), vec![("笑笑".to_owned(), 2)]);
+    }
+
+    #[test]
+    fn test_word_frequency_report() {
+        let docs = ["# 第一章\n\n他突然回头。", "突然，门开了。\n她缓缓坐下，缓缓开口。\n缓缓"];
+        let settings = WordFrequencySettings {
+            watch_words: words(&["突然", " 缓缓 ", "", "不由得"]),
+            stopwords: vec![],
+            top_n: 5,
+        };
+        let report = word_frequency(&docs, &settings);
+        let item = |phrase: &str, count, first| FreqItem { phrase: phrase.to_owned(), count, watched: true, first };
+        assert_eq!(report.items, vec![
+            item("突然", 2, Some((0, 3))),
+            item("缓缓", 3, Some((1, 2))),
+            item("不由得", 0, None),
+        ]);
+        assert_eq!(report.chars, docs.iter().map(|d| count_words(d)).sum::<usize>());
+    }
+}
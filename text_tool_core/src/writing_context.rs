@@ -0,0 +1,143 @@
+use std::fmt::Write as _;
+
+use crate::models::{same_title, Foreshadow, StructNode, WorldObject};
+
+// ── 写作上下文: design notes gathered for the chapter being written ────────────
+//
+// Opened next to a chapter by 开始写作 as a read-only scratch buffer, so the
+// summary, planning notes, linked objects and foreshadows of the node are
+// at hand while writing.  Built from the current design data every time.
+
+/// Name of the scratch buffer shown in the right pane.
+pub const WRITING_CONTEXT_NAME: &str = "写作上下文.md";
+
+/// Placeholder of a section with nothing in it.
+const NONE: &str = "（无）";
+
+/// The 写作上下文 document of `node`: its summary and notes, the
+/// description of each linked object (in the node's order, missing ones
+/// marked) and the foreshadows whose related chapters name the node.
+pub fn writing_context(node: &StructNode, objects: &[WorldObject], foreshadows: &[Foreshadow]) -> String {
+    let mut out = format!("# 写作上下文：{}\n\n", node.title);
+
+    let _ = writeln!(out, "## 摘要\n");
+    let summary = node.summary.trim();
+    let _ = writeln!(out, "{}\n", if summary.is_empty() { NONE } else { summary });
+
+    let notes = node.notes.trim();
+    if !notes.is_empty() {
+        let _ = writeln!(out, "## 筹备笔记\n\n{notes}\n");
+    }
+
+    let _ = writeln!(out, "## 关联对象\n");
+    if node.linked_objects.is_empty() {
+        let _ = writeln!(out, "{NONE}\n");
+    }
+    for name in &node.linked_objects {
+        match objects.iter().find(|o| same_title(&o.name, name)) {
+            Some(obj) => {
+                let _ = writeln!(out, "### {} {}（{}）\n", obj.kind.icon(), obj.name, obj.kind.label());
+                let description = obj.description.trim();
+                let _ = writeln!(out, "{}\n", if description.is_empty() { "（暂无描述）" } else { description });
+            }
+            None => {
+                let _ = writeln!(out, "### {name}\n\n（未找到该对象）\n");
+            }
+        }
+    }
+
+    let _ = writeln!(out, "## 相关伏笔\n");
+    let related: Vec<&Foreshadow> = foreshadows.iter()
+        .filter(|f| f.related_chapters.iter().any(|c| same_title(c, &node.title)))
+        .collect();
+    if related.is_empty() {
+        let _ = writeln!(out, "{NONE}\n");
+    }
+    for fs in related {
+        let state = if fs.resolved { "✅ 已回收" } else { "⏳ 未回收" };
+        let _ = writeln!(out, "### {}（{state}）\n", fs.name);
+        let description = fs.description.trim();
+        if !description.is_empty() {
+            let _ = writeln!(out, "{description}\n");
+        }
+    }
+
+    let len = out.trim_end().len();
+    out.truncate(len);
+    out.push('\n');
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ObjectKind, StructKind};
+
+    #[test]
+    fn test_writing_context_document() {
+        let mut node = StructNode::new("第三章 雨夜", StructKind::Chapter);
+        node.summary = "林远在雨夜发现玉佩。".into();
+        node.notes = "- 节拍一：追兵\n- 节拍二：藏身".into();
+        node.linked_objects = vec!["林远".into(), "失踪的人".into(), "旧城".into()];
+
+        let mut hero = WorldObject::new("林远", ObjectKind::Character);
+        hero.description = "沉默寡言的刀客".into();
+        let city = WorldObject::new("旧城", ObjectKind::Location);
+        let other = WorldObject::new("苏晴", ObjectKind::Character);
+
+        let mut jade = Foreshadow::new("玉佩之谜");
+        jade.description = "玉佩背面的刻字".into();
+        jade.related_chapters = vec!["第一章".into(), " 第三章 雨夜 ".into()];
+        let mut debt = Foreshadow::new("旧债");
+        debt.related_chapters = vec!["第三章 雨夜".into()];
+        debt.resolved = true;
+        let unrelated = Foreshadow::new("无关");
+
+        let doc = writing_context(&node, &[other, hero, city], &[jade, unrelated, debt]);
+        assert_eq!(doc, "\
+# 写作上下文：第三章 雨夜
+
+## 摘要
+
+林远在雨夜发现玉佩。
+
+## 筹备笔记
+
+- 节拍一：追兵
+- 节拍二：藏身
+
+## 关联对象
+
+### 👤 林远（人物）
+
+沉默寡言的刀客
+
+### 失踪的人
+
+（未找到该对象）
+
+### 📍 旧城（地点）
+
+（暂无描述）
+
+## 相关伏笔
+
+### 玉佩之谜（⏳ 未回收）
+
+玉佩背面的刻字
+
+### 旧债（✅ 已回收）
+");
+    }
+
+    #[test]
+    fn test_writing_context_empty_node() {
+        let node = StructNode::new("序章", StructKind::Chapter);
+        assert_eq!(
+            writing_context(&node, &[], &[]),
+            "# 写作上下文：序章\n\n## 摘要\n\n（无）\n\n## 关联对象\n\n（无）\n\n## 相关伏笔\n\n（无）\n",
+        );
+    }
+}